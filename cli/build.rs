@@ -0,0 +1,44 @@
+//! Build metadata for `sigil version`: the git commit the binary was built
+//! from and when.  Both fall back gracefully outside a git checkout (a
+//! crates.io install), and `SOURCE_DATE_EPOCH` pins the date for
+//! reproducible builds.
+
+use std::process::Command;
+
+fn git(args: &[&str]) -> Option<String> {
+    let out = Command::new("git").args(args).output().ok()?;
+    if !out.status.success() {
+        return None;
+    }
+    let text = String::from_utf8(out.stdout).ok()?.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+fn main() {
+    let commit = git(&["rev-parse", "--short=12", "HEAD"]).unwrap_or_else(|| "unknown".into());
+    println!("cargo:rustc-env=SIGIL_GIT_COMMIT={}", commit);
+
+    let date = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|secs| secs.parse::<i64>().ok())
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .unwrap_or_else(chrono::Utc::now);
+    println!(
+        "cargo:rustc-env=SIGIL_BUILD_DATE={}",
+        date.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    );
+
+    // Rebuild the metadata when the checked-out commit moves.
+    if let Some(git_dir) = git(&["rev-parse", "--absolute-git-dir"]) {
+        println!("cargo:rerun-if-changed={}/HEAD", git_dir);
+        // A packed ref has no file of its own; watching a missing path
+        // would rerun this script on every build.
+        let branch = git(&["symbolic-ref", "-q", "HEAD"])
+            .map(|head_ref| format!("{}/{}", git_dir, head_ref))
+            .filter(|path| std::path::Path::new(path).exists());
+        if let Some(path) = branch {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
@@ -0,0 +1,288 @@
+//! `sigil aggregate`: one roll-up of many stored scan results, such as the
+//! JSON a CI job keeps for each package it scans.
+//!
+//! Nothing is scanned.  Each file is read as a serialized `ScanResult` (a
+//! baseline, `diff --baseline` input) or as `sigil scan -f json` output, and
+//! the results are summed into a verdict distribution, the worst packages,
+//! and the rules that fire most across them.  A file that is neither is
+//! reported and left out, so one stale or truncated artifact does not sink
+//! the report.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::post_scan::verdict_level;
+use crate::scanner::{scoring, Finding, ScanResult, Verdict};
+
+/// One stored result and the file it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageRow {
+    pub source: String,
+    pub verdict: Verdict,
+    pub score: u32,
+    pub findings: usize,
+}
+
+/// How many packages got one verdict.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VerdictCount {
+    pub verdict: Verdict,
+    pub packages: usize,
+}
+
+/// How often one rule fired across the packages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RuleCount {
+    pub rule: String,
+    /// Packages it fired in at least once.
+    pub packages: usize,
+    pub findings: usize,
+}
+
+/// The combined report.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrgSummary {
+    pub packages: usize,
+    /// Files that could not be read as a scan result.
+    pub skipped: usize,
+    pub total_findings: usize,
+    /// Every verdict, worst first, including those no package got.
+    pub verdicts: Vec<VerdictCount>,
+    /// The highest-risk packages: worst verdict, then highest score.
+    pub worst: Vec<PackageRow>,
+    /// The rules that fired in the most packages.
+    pub top_rules: Vec<RuleCount>,
+}
+
+/// The files to read for `inputs`: each file as given, and the `.json`
+/// files directly inside each directory, in name order.
+pub fn input_files(inputs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for input in inputs {
+        if !input.is_dir() {
+            files.push(input.clone());
+            continue;
+        }
+        let mut found: Vec<PathBuf> = std::fs::read_dir(input)
+            .map(|entries| entries.filter_map(|e| e.ok().map(|e| e.path())).collect())
+            .unwrap_or_default();
+        found.retain(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "json"));
+        found.sort();
+        files.extend(found);
+    }
+    files
+}
+
+/// Read one stored result: a serialized `ScanResult`, or saved
+/// `sigil scan -f json` output, which is rescored from its findings.
+pub fn load_result(path: &Path) -> Result<ScanResult, String> {
+    let data = std::fs::read_to_string(path).map_err(|e| format!("cannot read: {}", e))?;
+    let stored = match serde_json::from_str::<ScanResult>(&data) {
+        Ok(result) => return Ok(result),
+        Err(e) => e,
+    };
+
+    let (summary, findings) = crate::output::parse_scan_report(&data).map_err(|_| {
+        // One JSON document that is neither: an older or foreign schema.
+        if serde_json::from_str::<serde_json::Value>(&data).is_ok() {
+            format!("not a scan result this version can read: {}", stored)
+        } else {
+            "neither a scan result nor `sigil scan -f json` output".to_string()
+        }
+    })?;
+    let findings = serde_json::from_value::<Vec<Finding>>(serde_json::Value::Array(findings))
+        .map_err(|e| format!("findings do not match this version's schema: {}", e))?;
+    let files_scanned = summary
+        .get("files_scanned")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_default() as usize;
+    let mut result = ScanResult {
+        findings,
+        files_scanned,
+        ..Default::default()
+    };
+    scoring::rescore(&mut result);
+    Ok(result)
+}
+
+/// Sum `results` (by source) into one report, listing at most `top`
+/// packages and rules.
+pub fn summarize(results: &[(String, ScanResult)], skipped: usize, top: usize) -> OrgSummary {
+    let verdicts = [
+        Verdict::CriticalRisk,
+        Verdict::HighRisk,
+        Verdict::MediumRisk,
+        Verdict::LowRisk,
+    ]
+    .into_iter()
+    .map(|verdict| VerdictCount {
+        verdict,
+        packages: results.iter().filter(|(_, r)| r.verdict == verdict).count(),
+    })
+    .collect();
+
+    let mut worst: Vec<PackageRow> = results
+        .iter()
+        .map(|(source, r)| PackageRow {
+            source: source.clone(),
+            verdict: r.verdict,
+            score: r.score,
+            findings: r.findings.len(),
+        })
+        .collect();
+    worst.sort_by(|a, b| {
+        verdict_level(&b.verdict)
+            .cmp(&verdict_level(&a.verdict))
+            .then(b.score.cmp(&a.score))
+            .then(a.source.cmp(&b.source))
+    });
+    worst.truncate(top);
+
+    let mut rules: HashMap<&str, RuleCount> = HashMap::new();
+    for (_, result) in results {
+        let mut seen: Vec<&str> = Vec::new();
+        for finding in &result.findings {
+            let entry = rules.entry(&finding.rule).or_insert_with(|| RuleCount {
+                rule: finding.rule.clone(),
+                packages: 0,
+                findings: 0,
+            });
+            entry.findings += 1;
+            if !seen.contains(&finding.rule.as_str()) {
+                seen.push(&finding.rule);
+                entry.packages += 1;
+            }
+        }
+    }
+    let mut top_rules: Vec<RuleCount> = rules.into_values().collect();
+    top_rules.sort_by(|a, b| {
+        b.packages
+            .cmp(&a.packages)
+            .then(b.findings.cmp(&a.findings))
+            .then(a.rule.cmp(&b.rule))
+    });
+    top_rules.truncate(top);
+
+    OrgSummary {
+        packages: results.len(),
+        skipped,
+        total_findings: results.iter().map(|(_, r)| r.findings.len()).sum(),
+        verdicts,
+        worst,
+        top_rules,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Phase, Severity};
+    use std::fs;
+
+    fn finding(rule: &str, severity: Severity) -> Finding {
+        Finding {
+            phase: Phase::CodePatterns,
+            rule: rule.to_string(),
+            severity,
+            file: "index.js".to_string(),
+            line: Some(1),
+            snippet: "eval(x)".to_string(),
+            weight: 5,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        }
+    }
+
+    fn result(findings: Vec<Finding>) -> ScanResult {
+        let mut result = ScanResult {
+            findings,
+            files_scanned: 3,
+            duration_ms: 5,
+            ..Default::default()
+        };
+        scoring::rescore(&mut result);
+        result
+    }
+
+    #[test]
+    fn stored_results_and_scan_output_both_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let stored = result(vec![finding("CODE-001", Severity::High)]);
+        let baseline = dir.path().join("a.json");
+        fs::write(&baseline, serde_json::to_string(&stored).unwrap()).unwrap();
+        assert_eq!(load_result(&baseline).unwrap().findings.len(), 1);
+
+        // `scan -f json`: one report object.
+        let report = dir.path().join("r.json");
+        fs::write(
+            &report,
+            crate::output::scan_report_json(&stored, 5).to_string(),
+        )
+        .unwrap();
+        let loaded = load_result(&report).unwrap();
+        assert_eq!((loaded.score, loaded.files_scanned), (stored.score, 3));
+        fs::remove_file(&report).unwrap();
+
+        // Older `scan -f json`: summary, findings, and trailing documents.
+        let printed = format!(
+            "{}\n{}\n{{\"verdict\":\"HIGH RISK\"}}\n",
+            crate::output::scan_summary_json(&stored),
+            serde_json::to_string_pretty(&stored.findings).unwrap()
+        );
+        let output = dir.path().join("b.json");
+        fs::write(&output, printed).unwrap();
+        let loaded = load_result(&output).unwrap();
+        assert_eq!((loaded.score, loaded.files_scanned), (stored.score, 3));
+        assert_eq!(loaded.verdict, stored.verdict);
+
+        let broken = dir.path().join("c.json");
+        fs::write(&broken, "{\"findings\": 3}").unwrap();
+        assert!(load_result(&broken).is_err());
+        fs::write(dir.path().join("notes.txt"), "x").unwrap();
+        assert_eq!(
+            input_files(&[dir.path().to_path_buf()]),
+            vec![baseline, output, broken]
+        );
+    }
+
+    #[test]
+    fn summary_ranks_packages_and_rules() {
+        let results = vec![
+            ("left-pad.json".to_string(), result(vec![])),
+            (
+                "evil.json".to_string(),
+                result(vec![
+                    finding("INSTALL-001", Severity::Critical),
+                    finding("CODE-001", Severity::High),
+                ]),
+            ),
+            (
+                "util.json".to_string(),
+                result(vec![
+                    finding("CODE-001", Severity::High),
+                    finding("CODE-001", Severity::High),
+                ]),
+            ),
+        ];
+        let summary = summarize(&results, 1, 2);
+        assert_eq!((summary.packages, summary.skipped), (3, 1));
+        assert_eq!(summary.total_findings, 4);
+        let counts: Vec<usize> = summary.verdicts.iter().map(|v| v.packages).collect();
+        assert_eq!(counts, vec![1, 1, 0, 1]);
+        let worst: Vec<&str> = summary.worst.iter().map(|p| p.source.as_str()).collect();
+        assert_eq!(worst, vec!["evil.json", "util.json"]);
+        assert_eq!(
+            summary.top_rules[0],
+            RuleCount {
+                rule: "CODE-001".to_string(),
+                packages: 2,
+                findings: 3,
+            }
+        );
+        assert_eq!(summary.top_rules.len(), 2);
+    }
+}
@@ -0,0 +1,153 @@
+//! `~/.sigil/allowlist.json`: file contents a reviewer has already cleared.
+//!
+//! Entries are keyed by the SHA-256 of a file's bytes, so a vendored file is
+//! skipped by the content phases wherever it turns up and only for as long as
+//! it is byte-for-byte the reviewed copy; any edit brings it back into the
+//! scan.  Provenance checks still see the file.  `sigil allowlist add`
+//! records entries; `scan --allowlist FILE` reads another list instead.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// One cleared file content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllowEntry {
+    /// Why the content was approved.
+    pub note: String,
+    /// Path the file was added from, for reference only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// RFC 3339 time the entry was added.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_at: Option<String>,
+}
+
+/// Approved file contents by lower-case hex SHA-256.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Allowlist {
+    pub entries: BTreeMap<String, AllowEntry>,
+}
+
+impl Allowlist {
+    /// The approval note for content hashing to `sha256`, if any.
+    pub fn note(&self, sha256: &str) -> Option<&str> {
+        self.entries.get(sha256).map(|entry| entry.note.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// `~/.sigil/allowlist.json`.
+pub fn default_path() -> PathBuf {
+    crate::paths::in_home("allowlist.json")
+}
+
+/// Read the allowlist at `path`.  A missing file is an empty list; a
+/// malformed one is an error rather than silently allowing nothing.
+pub fn load(path: &Path) -> Result<Allowlist, String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| format!("{}: invalid allowlist: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Allowlist::default()),
+        Err(e) => Err(format!("failed to read {}: {}", path.display(), e)),
+    }
+}
+
+fn save(path: &Path, list: &Allowlist) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(list).map_err(|e| e.to_string())?;
+    std::fs::write(path, json + "\n")
+        .map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+/// Hex SHA-256 of a file's bytes, as the scanner computes it.  The file is
+/// hashed as it is read, so its size does not matter.
+pub fn hash_file(file: &Path) -> Result<String, String> {
+    let mut hasher = Sha256::new();
+    std::fs::File::open(file)
+        .and_then(|f| std::io::copy(&mut std::io::BufReader::new(f), &mut hasher))
+        .map_err(|e| format!("failed to read {}: {}", file.display(), e))?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Add every file in `targets` (directories are walked) to the allowlist at
+/// `list_path` with `note`.  Returns the `(path, sha256)` pairs that were
+/// new; contents already listed keep their original note.
+pub fn add(
+    list_path: &Path,
+    targets: &[PathBuf],
+    note: &str,
+) -> Result<Vec<(String, String)>, String> {
+    let mut list = load(list_path)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut added = Vec::new();
+    for target in targets {
+        let files = if target.is_dir() {
+            crate::scanner::collect_files(target)
+        } else {
+            vec![target.clone()]
+        };
+        for file in files {
+            let sha256 = hash_file(&file)?;
+            if list.entries.contains_key(&sha256) {
+                continue;
+            }
+            let shown = file.display().to_string();
+            list.entries.insert(
+                sha256.clone(),
+                AllowEntry {
+                    note: note.to_string(),
+                    path: Some(shown.clone()),
+                    added_at: Some(now.clone()),
+                },
+            );
+            added.push((shown, sha256));
+        }
+    }
+    if !added.is_empty() {
+        save(list_path, &list)?;
+    }
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_records_each_content_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let vendor = dir.path().join("vendor");
+        std::fs::create_dir_all(&vendor).unwrap();
+        std::fs::write(vendor.join("a.js"), "eval(a)\n").unwrap();
+        std::fs::write(vendor.join("b.js"), "eval(b)\n").unwrap();
+        let list_path = dir.path().join("allowlist.json");
+
+        let added = add(&list_path, std::slice::from_ref(&vendor), "reviewed v1.2").unwrap();
+        assert_eq!(added.len(), 2);
+        assert!(add(&list_path, &[vendor.join("a.js")], "again")
+            .unwrap()
+            .is_empty());
+
+        let list = load(&list_path).unwrap();
+        let sha = hash_file(&vendor.join("a.js")).unwrap();
+        assert_eq!(list.note(&sha), Some("reviewed v1.2"));
+    }
+
+    #[test]
+    fn missing_list_is_empty_and_malformed_list_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load(&dir.path().join("none.json")).unwrap().is_empty());
+        let bad = dir.path().join("bad.json");
+        std::fs::write(&bad, "[").unwrap();
+        assert!(load(&bad).is_err());
+    }
+}
@@ -1,17 +1,42 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
-
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use crate::error::SigilError;
+use crate::logging::log_warn;
 use crate::scanner::cloud_sigs::{self, SignatureResponse as CloudSigResponse};
-use crate::scanner::ScanResult;
+use crate::scanner::{Finding, ScanResult};
+
+pub(crate) const DEFAULT_ENDPOINT: &str = "https://api.sigilsec.ai";
 
-const DEFAULT_ENDPOINT: &str = "https://api.sigilsec.ai";
+/// How many times a rate-limited threat lookup is retried.
+const LOOKUP_RETRIES: u32 = 4;
+/// Ceiling on a single rate-limit wait, whatever `Retry-After` says.
+const MAX_BACKOFF_SECS: u64 = 30;
+
+/// Config key: seconds allowed to establish a connection to the API.
+pub const CONNECT_TIMEOUT_KEY: &str = "api.connect_timeout";
+/// Config key: seconds allowed for a whole API request.
+pub const REQUEST_TIMEOUT_KEY: &str = "api.timeout";
+/// Config key: seconds allowed for a signature download, which can be large.
+pub const SIGNATURES_TIMEOUT_KEY: &str = "api.signatures_timeout";
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_SIGNATURES_TIMEOUT_SECS: u64 = 300;
 
 /// API client for the Sigil cloud service.
+#[derive(Clone)]
 pub struct SigilClient {
     endpoint: String,
     client: reqwest::Client,
     token: Option<String>,
+    /// A token is stored but past its expiry, so calls go out unauthenticated.
+    session_expired: bool,
+    timeouts: Timeouts,
 }
 
 /// Response from a scan submission.
@@ -23,7 +48,7 @@ pub struct ScanResponse {
 }
 
 /// Response from a threat lookup.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct ThreatInfo {
     pub hash: String,
@@ -59,6 +84,24 @@ struct AuthResponse {
     pub expires_at: Option<String>,
 }
 
+/// Account details reported by GET /v1/auth/verify.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AccountInfo {
+    pub email: Option<String>,
+    pub expires_at: Option<String>,
+}
+
+impl AccountInfo {
+    /// The email may sit at the top level or under `user`.
+    fn from_verify(body: &serde_json::Value) -> Self {
+        let text = |v: Option<&serde_json::Value>| v.and_then(|v| v.as_str()).map(str::to_string);
+        AccountInfo {
+            email: text(body.get("email")).or_else(|| text(body.pointer("/user/email"))),
+            expires_at: text(body.get("expires_at")),
+        }
+    }
+}
+
 /// Response from POST /v1/auth/device/code.
 #[derive(Debug, Deserialize)]
 struct DeviceCodeResponse {
@@ -79,36 +122,116 @@ fn default_interval() -> u64 {
 #[derive(Debug, Deserialize)]
 struct DeviceTokenResponse {
     access_token: String,
+    /// Lifetime of the access token in seconds.
+    #[serde(default)]
+    expires_in: Option<i64>,
 }
 
 // ---------------------------------------------------------------------------
 // Token storage
 // ---------------------------------------------------------------------------
 
-/// Path to the stored API token: ~/.sigil/token
-fn token_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".sigil")
-        .join("token")
+/// Session expired hint shown when an authenticated call finds only a stale token.
+pub(crate) const SESSION_EXPIRED_HINT: &str =
+    "session expired, please log in again (run: sigil login)";
+
+/// A stored API token and, when the server reported one, its expiry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct StoredToken {
+    pub token: String,
+    /// RFC 3339 timestamp after which the token is no longer accepted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    /// API endpoint the token was issued by.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+}
+
+impl StoredToken {
+    /// A token whose expiry cannot be parsed is treated as still valid; the
+    /// server remains the authority and will reject it if it is not.
+    pub fn is_expired(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.expires_at
+            .as_deref()
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+            .is_some_and(|at| at <= now)
+    }
 }
 
-/// Load a stored API token from disk.
-pub(crate) fn load_token() -> Option<String> {
-    fs::read_to_string(token_path())
-        .ok()
-        .map(|t| t.trim().to_string())
-        .filter(|t| !t.is_empty())
+/// What the token store holds for the current user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TokenState {
+    Valid(String),
+    Expired,
+    Missing,
 }
 
-/// Save an API token to disk.
-fn save_token(token: &str) -> Result<(), String> {
-    let path = token_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("failed to create config directory: {}", e))?;
+/// Directory holding the token store: the Sigil home (~/.sigil)
+fn token_dir() -> PathBuf {
+    crate::paths::sigil_home()
+}
+
+/// Read `token.json` from `dir`, migrating a plaintext `token` file written by
+/// older releases on first sight.
+fn read_stored_token(dir: &Path) -> Option<StoredToken> {
+    if let Ok(raw) = fs::read_to_string(dir.join("token.json")) {
+        return serde_json::from_str::<StoredToken>(&raw)
+            .ok()
+            .filter(|t| !t.token.is_empty());
     }
-    fs::write(&path, token).map_err(|e| format!("failed to save token: {}", e))?;
+
+    let legacy = dir.join("token");
+    let token = fs::read_to_string(&legacy).ok()?.trim().to_string();
+    if token.is_empty() {
+        return None;
+    }
+    let stored = StoredToken {
+        token,
+        expires_at: None,
+        endpoint: None,
+    };
+    if write_stored_token(dir, &stored).is_ok() {
+        let _ = fs::remove_file(&legacy);
+    }
+    Some(stored)
+}
+
+fn token_state_in(dir: &Path, now: chrono::DateTime<chrono::Utc>) -> TokenState {
+    match read_stored_token(dir) {
+        Some(stored) if stored.is_expired(now) => TokenState::Expired,
+        Some(stored) => TokenState::Valid(stored.token),
+        None => TokenState::Missing,
+    }
+}
+
+/// The stored token record, expired or not. A `SIGIL_TOKEN` override is
+/// reported as a token with no recorded expiry or endpoint.
+pub(crate) fn stored_token() -> Option<StoredToken> {
+    if let Some(token) = crate::config::env_token() {
+        return Some(StoredToken {
+            token,
+            expires_at: None,
+            endpoint: None,
+        });
+    }
+    read_stored_token(&token_dir())
+}
+
+/// Current state of the stored API token. `SIGIL_TOKEN` wins over the stored login, so CI can authenticate without
+/// writing a token to disk.
+pub(crate) fn token_state() -> TokenState {
+    if let Some(token) = crate::config::env_token() {
+        return TokenState::Valid(token);
+    }
+    token_state_in(&token_dir(), chrono::Utc::now())
+}
+
+fn write_stored_token(dir: &Path, stored: &StoredToken) -> Result<(), SigilError> {
+    fs::create_dir_all(dir)
+        .map_err(|e| SigilError::Io(format!("failed to create config directory: {}", e)))?;
+    let path = dir.join("token.json");
+    let json = serde_json::to_string_pretty(stored)?;
+    fs::write(&path, json).map_err(|e| SigilError::Io(format!("failed to save token: {}", e)))?;
 
     // Restrict permissions on Unix
     #[cfg(unix)]
@@ -120,37 +243,245 @@ fn save_token(token: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Save an API token, its expiry, and the issuing endpoint to disk.
+fn save_token(stored: &StoredToken) -> Result<(), SigilError> {
+    let dir = token_dir();
+    write_stored_token(&dir, stored)?;
+    // A plaintext token left over from an older release must not shadow this one.
+    let _ = fs::remove_file(dir.join("token"));
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// HTTP transport configuration
+// ---------------------------------------------------------------------------
+
+/// Set by the global `--insecure` flag: skip TLS certificate verification.
+static INSECURE_TLS: AtomicBool = AtomicBool::new(false);
+
+/// Disable TLS certificate verification for API clients created afterwards.
+/// Only meant for self-signed development endpoints.
+pub fn set_insecure(insecure: bool) {
+    INSECURE_TLS.store(insecure, Ordering::Relaxed);
+}
+
+/// Time limits for API calls. The connect limit is set on the client; the
+/// total limits are set per request, so a signature download can outlast an
+/// auth check.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Timeouts {
+    connect: Duration,
+    request: Duration,
+    signatures: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts {
+            connect: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS),
+            request: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+            signatures: Duration::from_secs(DEFAULT_SIGNATURES_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl Timeouts {
+    /// Read the `api.*timeout` keys from `config` (file plus `SIGIL_API_*`
+    /// overrides), keeping the default for any key unset or unusable.
+    fn from_config(config: &serde_json::Map<String, serde_json::Value>) -> Self {
+        let secs = |key: &str, default: Duration| match config.get(key) {
+            None => default,
+            Some(raw) => match raw
+                .as_u64()
+                .or_else(|| raw.as_str().and_then(|s| s.trim().parse().ok()))
+                .filter(|secs| *secs > 0)
+            {
+                Some(secs) => Duration::from_secs(secs),
+                None => {
+                    log_warn!(
+                        "ignoring {} = {} (expected a whole number of seconds)",
+                        key,
+                        raw
+                    );
+                    default
+                }
+            },
+        };
+        let default = Timeouts::default();
+        Timeouts {
+            connect: secs(CONNECT_TIMEOUT_KEY, default.connect),
+            request: secs(REQUEST_TIMEOUT_KEY, default.request),
+            signatures: secs(SIGNATURES_TIMEOUT_KEY, default.signatures),
+        }
+    }
+}
+
+/// Proxy and trust settings for the API client, normally read from the
+/// environment: `HTTPS_PROXY`/`HTTP_PROXY` (lowercase variants accepted,
+/// `NO_PROXY` honoured) and `SIGIL_CA_BUNDLE`, a PEM file of extra root
+/// certificates for TLS-intercepting corporate proxies.
+#[derive(Debug, Clone)]
+struct HttpConfig {
+    https_proxy: Option<String>,
+    http_proxy: Option<String>,
+    ca_bundle: Option<PathBuf>,
+    insecure: bool,
+    connect_timeout: Duration,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            https_proxy: None,
+            http_proxy: None,
+            ca_bundle: None,
+            insecure: false,
+            connect_timeout: Timeouts::default().connect,
+        }
+    }
+}
+
+impl HttpConfig {
+    fn from_env(timeouts: &Timeouts) -> Self {
+        let var = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|n| std::env::var(n).ok())
+                .filter(|v| !v.trim().is_empty())
+        };
+        HttpConfig {
+            https_proxy: var(&["HTTPS_PROXY", "https_proxy"]),
+            http_proxy: var(&["HTTP_PROXY", "http_proxy"]),
+            ca_bundle: var(&["SIGIL_CA_BUNDLE"]).map(PathBuf::from),
+            insecure: INSECURE_TLS.load(Ordering::Relaxed),
+            connect_timeout: timeouts.connect,
+        }
+    }
+}
+
+/// Build the API `reqwest::Client` for `config`. Fails when a proxy URL is
+/// malformed or the CA bundle cannot be read or parsed.  Only the connect
+/// timeout is set here; each request carries its own total timeout.
+fn build_http_client(config: &HttpConfig) -> Result<reqwest::Client, SigilError> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(config.connect_timeout)
+        .user_agent(format!("sigil-cli/{}", env!("CARGO_PKG_VERSION")));
+
+    if let Some(url) = &config.https_proxy {
+        let proxy = reqwest::Proxy::https(url)
+            .map_err(|e| SigilError::Config(format!("invalid HTTPS_PROXY '{}': {}", url, e)))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+    if let Some(url) = &config.http_proxy {
+        let proxy = reqwest::Proxy::http(url)
+            .map_err(|e| SigilError::Config(format!("invalid HTTP_PROXY '{}': {}", url, e)))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(path) = &config.ca_bundle {
+        let pem = fs::read(path).map_err(|e| {
+            SigilError::Config(format!(
+                "failed to read SIGIL_CA_BUNDLE '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+            SigilError::Config(format!(
+                "failed to parse SIGIL_CA_BUNDLE '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        if certs.is_empty() {
+            return Err(SigilError::Config(format!(
+                "SIGIL_CA_BUNDLE '{}' contains no PEM certificates",
+                path.display()
+            )));
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    if config.insecure {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    builder
+        .build()
+        .map_err(|e| SigilError::Config(format!("http client: {}", e)))
+}
+
 // ---------------------------------------------------------------------------
 // Client implementation
 // ---------------------------------------------------------------------------
 
+/// Body of `POST /v1/report`.  `findings` is only sent when non-empty.
+pub(crate) fn report_body(
+    hash: &str,
+    threat_type: &str,
+    description: &str,
+    findings: &[Finding],
+) -> serde_json::Value {
+    let mut body = serde_json::json!({
+        "hash": hash,
+        "threat_type": threat_type,
+        "description": description,
+    });
+    if !findings.is_empty() {
+        body["findings"] = serde_json::json!(findings);
+    }
+    body
+}
+
 impl SigilClient {
-    /// Create a new API client. If no endpoint is provided, uses the default.
-    /// Automatically loads a stored token if one exists.
-    pub fn new(endpoint: Option<String>) -> Self {
-        let endpoint = endpoint.unwrap_or_else(|| DEFAULT_ENDPOINT.to_string());
-        let token = load_token();
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .user_agent(format!("sigil-cli/{}", env!("CARGO_PKG_VERSION")))
-            .build()
-            .unwrap_or_default();
-
-        SigilClient {
+    /// Create a new API client. The endpoint is resolved through
+    /// [`crate::config::resolve`]: `SIGIL_ENDPOINT`, then `endpoint`, then
+    /// the config file, then the default.
+    /// Automatically loads a stored token if one exists.  Fails with
+    /// [`SigilError::Config`] when the proxy or CA bundle settings are
+    /// unusable, rather than connecting without them.
+    pub fn new(endpoint: Option<String>) -> Result<Self, SigilError> {
+        let endpoint = crate::config::resolve().endpoint(endpoint.as_deref());
+        let (token, session_expired) = match token_state() {
+            TokenState::Valid(token) => (Some(token), false),
+            TokenState::Expired => (None, true),
+            TokenState::Missing => (None, false),
+        };
+        let timeouts = Timeouts::from_config(&crate::config::resolve().merged());
+        let client = build_http_client(&HttpConfig::from_env(&timeouts))?;
+
+        Ok(SigilClient {
             endpoint,
             client,
             token,
-        }
+            session_expired,
+            timeouts,
+        })
+    }
+
+    /// A GET bounded by the request timeout; override it with `.timeout()`
+    /// for calls expected to take longer.
+    fn get(&self, url: impl reqwest::IntoUrl) -> reqwest::RequestBuilder {
+        self.client.get(url).timeout(self.timeouts.request)
+    }
+
+    /// A POST bounded by the request timeout.
+    fn post(&self, url: impl reqwest::IntoUrl) -> reqwest::RequestBuilder {
+        self.client.post(url).timeout(self.timeouts.request)
     }
 
     /// Submit a scan result to the Sigil cloud.
     ///
     /// POST /v1/scan
-    pub async fn submit_scan(&self, result: &ScanResult) -> Result<ScanResponse, String> {
+    pub async fn submit_scan(&self, result: &ScanResult) -> Result<ScanResponse, SigilError> {
         let url = format!("{}/v1/scan", self.endpoint);
 
-        let mut request = self.client.post(&url).json(result);
-        if let Some(ref token) = self.token {
+        let mut request = self.post(&url).json(result);
+        if let Some(token) = self.bearer() {
             request = request.bearer_auth(token);
         }
 
@@ -160,28 +491,31 @@ impl SigilClient {
             .map_err(|e| offline_fallback_message(&e))?;
 
         if !response.status().is_success() {
-            return Err(format!(
-                "API error: {} {}",
-                response.status(),
-                response.text().await.unwrap_or_default()
+            let status = response.status();
+            return Err(SigilError::from_status(
+                status,
+                format!(
+                    "API error: {} {}",
+                    status,
+                    response.text().await.unwrap_or_default()
+                ),
             ));
         }
 
         response
             .json::<ScanResponse>()
             .await
-            .map_err(|e| format!("failed to parse response: {}", e))
+            .map_err(|e| SigilError::Parse(format!("failed to parse response: {}", e)))
     }
 
     /// Look up a file hash in the threat intelligence database.
     ///
     /// GET /v1/threat/{hash}
-    #[allow(dead_code)]
-    pub async fn lookup_threat(&self, hash: &str) -> Result<ThreatInfo, String> {
+    pub async fn lookup_threat(&self, hash: &str) -> Result<ThreatInfo, SigilError> {
         let url = format!("{}/v1/threat/{}", self.endpoint, hash);
 
-        let mut request = self.client.get(&url);
-        if let Some(ref token) = self.token {
+        let mut request = self.get(&url);
+        if let Some(token) = self.bearer() {
             request = request.bearer_auth(token);
         }
 
@@ -201,14 +535,81 @@ impl SigilClient {
             });
         }
 
+        if response.status().as_u16() == 429 {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse().ok());
+            return Err(SigilError::RateLimited {
+                message: "threat lookup rate limited (429)".to_string(),
+                retry_after,
+            });
+        }
+
         if !response.status().is_success() {
-            return Err(format!("API error: {}", response.status()));
+            return Err(SigilError::from_status(
+                response.status(),
+                format!("API error: {}", response.status()),
+            ));
         }
 
         response
             .json::<ThreatInfo>()
             .await
-            .map_err(|e| format!("failed to parse response: {}", e))
+            .map_err(|e| SigilError::Parse(format!("failed to parse response: {}", e)))
+    }
+
+    /// Look up many hashes with at most `max_in_flight` requests outstanding.
+    /// A 429 is retried after the server's `Retry-After` (or an exponential
+    /// backoff) up to `LOOKUP_RETRIES` times. Results come back in input order.
+    pub async fn lookup_threats(
+        &self,
+        hashes: &[String],
+        max_in_flight: usize,
+    ) -> Vec<(String, Result<ThreatInfo, SigilError>)> {
+        let semaphore = Arc::new(Semaphore::new(max_in_flight.max(1)));
+        let mut tasks = tokio::task::JoinSet::new();
+
+        for (index, hash) in hashes.iter().enumerate() {
+            let client = self.clone();
+            let semaphore = Arc::clone(&semaphore);
+            let hash = hash.clone();
+            tasks.spawn(async move {
+                let mut backoff = 1u64;
+                let mut attempt = 0;
+                let result = loop {
+                    let outcome = {
+                        let _permit = semaphore.acquire().await;
+                        client.lookup_threat(&hash).await
+                    };
+                    match outcome {
+                        Err(SigilError::RateLimited { retry_after, .. })
+                            if attempt < LOOKUP_RETRIES =>
+                        {
+                            attempt += 1;
+                            let wait = retry_after.unwrap_or(backoff).min(MAX_BACKOFF_SECS);
+                            tokio::time::sleep(std::time::Duration::from_secs(wait)).await;
+                            backoff = (backoff * 2).min(MAX_BACKOFF_SECS);
+                        }
+                        other => break other,
+                    }
+                };
+                (index, hash, result)
+            });
+        }
+
+        let mut results = Vec::with_capacity(hashes.len());
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok(entry) = joined {
+                results.push(entry);
+            }
+        }
+        results.sort_by_key(|(index, _, _)| *index);
+        results
+            .into_iter()
+            .map(|(_, hash, result)| (hash, result))
+            .collect()
     }
 
     /// Fetch the latest threat detection signatures.
@@ -220,7 +621,11 @@ impl SigilClient {
     /// and merged with the local set.
     ///
     /// Returns the total number of local signatures after the update.
-    pub async fn get_signatures(&self, force: bool) -> Result<usize, String> {
+    pub async fn get_signatures(
+        &self,
+        force: bool,
+        dry_run: bool,
+    ) -> Result<cloud_sigs::SignatureDelta, SigilError> {
         let mut url = format!("{}/v1/signatures", self.endpoint);
 
         // Delta sync: append ?since= if we have a previous sync timestamp
@@ -230,8 +635,9 @@ impl SigilClient {
             }
         }
 
-        let mut request = self.client.get(&url);
-        if let Some(ref token) = self.token {
+        // The signature set can be large: allow it longer than other calls.
+        let mut request = self.get(&url).timeout(self.timeouts.signatures);
+        if let Some(token) = self.bearer() {
             request = request.bearer_auth(token);
         }
 
@@ -241,41 +647,50 @@ impl SigilClient {
             .map_err(|e| offline_fallback_message(&e))?;
 
         if !response.status().is_success() {
-            return Err(format!("API error: {}", response.status()));
+            return Err(SigilError::from_status(
+                response.status(),
+                format!("API error: {}", response.status()),
+            ));
         }
 
         let body = response
             .text()
             .await
-            .map_err(|e| format!("failed to read response: {}", e))?;
+            .map_err(|e| SigilError::Network(format!("failed to read response: {}", e)))?;
+
+        // Refuse a bundle that is not signed by the pinned key before it can
+        // touch the local set.
+        if cloud_sigs::verification_enabled() {
+            cloud_sigs::verify_bundle(&body, &cloud_sigs::SIGNATURES_PUBLIC_KEY).map_err(|e| {
+                SigilError::Integrity(format!(
+                    "signature bundle failed verification, local signatures left unchanged: {}",
+                    e
+                ))
+            })?;
+        }
 
         // Parse the wrapped response format: {signatures: [...], total, last_updated}
-        let sig_response: CloudSigResponse = serde_json::from_str(&body)
-            .map_err(|e| format!("failed to parse signatures response: {}", e))?;
+        let sig_response: CloudSigResponse = serde_json::from_str(&body).map_err(|e| {
+            SigilError::Parse(format!("failed to parse signatures response: {}", e))
+        })?;
 
         let fetched = sig_response.signatures;
         let last_updated = sig_response.last_updated.unwrap_or_default();
 
-        // Merge with existing local signatures (for delta sync)
-        let mut all_sigs = if force {
-            vec![]
+        // Merge with existing local signatures (for delta sync). The bundled
+        // set is merged at scan time, so it is never written to disk. A
+        // forced fetch replaces the set, but the delta is still measured
+        // against what is on disk.
+        let existing = cloud_sigs::load_fetched_signatures();
+        let (merged, mut delta) = cloud_sigs::apply_fetched(existing, &fetched);
+        let all_sigs = if force {
+            delta.total = fetched.len();
+            fetched
         } else {
-            cloud_sigs::load_cloud_signatures()
+            merged
         };
-
-        // Upsert fetched signatures by ID
-        for new_sig in &fetched {
-            if let Some(pos) = all_sigs.iter().position(|s| s.id == new_sig.id) {
-                all_sigs[pos] = new_sig.clone();
-            } else {
-                all_sigs.push(new_sig.clone());
-            }
-        }
-
-        // Write merged set to disk
-        let sigs_path = cloud_sigs::signatures_path();
-        if let Some(parent) = sigs_path.parent() {
-            let _ = fs::create_dir_all(parent);
+        if dry_run {
+            return Ok(delta);
         }
 
         // Store in the wrapped format so load_cloud_signatures can read it back
@@ -285,18 +700,20 @@ impl SigilClient {
             "last_updated": &last_updated,
         });
         let json = serde_json::to_string_pretty(&wrapped)
-            .map_err(|e| format!("failed to serialize signatures: {}", e))?;
-        fs::write(&sigs_path, json).map_err(|e| format!("failed to write signatures: {}", e))?;
+            .map_err(|e| SigilError::Parse(format!("failed to serialize signatures: {}", e)))?;
+        cloud_sigs::save_fetched_signatures(&json)
+            .map_err(|e| SigilError::Io(format!("failed to write signatures: {}", e)))?;
 
         // Save sync metadata for next delta sync
         if !last_updated.is_empty() {
             cloud_sigs::save_sync_meta(&last_updated);
         }
 
-        Ok(all_sigs.len())
+        Ok(delta)
     }
 
-    /// Report a new threat to the Sigil cloud.
+    /// Report a new threat to the Sigil cloud, with the findings of a local
+    /// scan of the file when there are any.
     ///
     /// POST /v1/report
     pub async fn report_threat(
@@ -304,17 +721,14 @@ impl SigilClient {
         hash: &str,
         threat_type: &str,
         description: &str,
-    ) -> Result<ReportResponse, String> {
+        findings: &[Finding],
+    ) -> Result<ReportResponse, SigilError> {
         let url = format!("{}/v1/report", self.endpoint);
 
-        let body = serde_json::json!({
-            "hash": hash,
-            "threat_type": threat_type,
-            "description": description,
-        });
+        let body = report_body(hash, threat_type, description, findings);
 
-        let mut request = self.client.post(&url).json(&body);
-        if let Some(ref token) = self.token {
+        let mut request = self.post(&url).json(&body);
+        if let Some(token) = self.bearer() {
             request = request.bearer_auth(token);
         }
 
@@ -324,23 +738,25 @@ impl SigilClient {
             .map_err(|e| offline_fallback_message(&e))?;
 
         if !response.status().is_success() {
-            return Err(format!("API error: {}", response.status()));
+            return Err(SigilError::from_status(
+                response.status(),
+                format!("API error: {}", response.status()),
+            ));
         }
 
         response
             .json::<ReportResponse>()
             .await
-            .map_err(|e| format!("failed to parse response: {}", e))
+            .map_err(|e| SigilError::Parse(format!("failed to parse response: {}", e)))
     }
 
     /// Authenticate with a pre-existing API token.
     /// Validates the token against the server, then stores it locally.
-    pub async fn login_with_token(&self, token: &str) -> Result<(), String> {
+    pub async fn login_with_token(&self, token: &str) -> Result<(), SigilError> {
         // Validate token by calling a simple authenticated endpoint
         let url = format!("{}/v1/auth/verify", self.endpoint);
 
         let response = self
-            .client
             .get(&url)
             .bearer_auth(token)
             .send()
@@ -348,21 +764,86 @@ impl SigilClient {
             .map_err(|e| offline_fallback_message(&e))?;
 
         if !response.status().is_success() {
-            return Err(format!(
+            return Err(SigilError::Auth(format!(
                 "invalid token (server returned {})",
                 response.status()
-            ));
+            )));
         }
 
-        save_token(token)?;
+        // The verify endpoint may report when the token lapses.
+        let expires_at = response
+            .json::<serde_json::Value>()
+            .await
+            .ok()
+            .and_then(|v| v.get("expires_at")?.as_str().map(str::to_string));
+        save_token(&self.stored(token, expires_at))?;
         Ok(())
     }
 
-    /// Check whether the client has a stored authentication token.
+    /// Check whether the client has a stored, unexpired authentication token.
     pub fn is_authenticated(&self) -> bool {
         self.token.is_some()
     }
 
+    /// Whether a stored token was discarded because it has expired.
+    pub fn session_expired(&self) -> bool {
+        self.session_expired
+    }
+
+    /// The token to send with a request. When the stored one has expired the
+    /// user is told once per process, since the call will go out anonymously.
+    fn bearer(&self) -> Option<&str> {
+        if self.token.is_none() && self.session_expired {
+            static HINT_SHOWN: AtomicBool = AtomicBool::new(false);
+            if !HINT_SHOWN.swap(true, Ordering::Relaxed) {
+                log_warn!("{}", SESSION_EXPIRED_HINT);
+            }
+        }
+        self.token.as_deref()
+    }
+
+    fn stored(&self, token: &str, expires_at: Option<String>) -> StoredToken {
+        StoredToken {
+            token: token.to_string(),
+            expires_at,
+            endpoint: Some(self.endpoint.clone()),
+        }
+    }
+
+    /// Ask the server who the stored token belongs to.
+    ///
+    /// GET /v1/auth/verify
+    pub async fn whoami(&self) -> Result<AccountInfo, SigilError> {
+        let url = format!("{}/v1/auth/verify", self.endpoint);
+        let Some(token) = self.bearer() else {
+            return Err(SigilError::Auth(if self.session_expired {
+                SESSION_EXPIRED_HINT.to_string()
+            } else {
+                "not logged in (run: sigil login)".to_string()
+            }));
+        };
+
+        let response = self
+            .get(&url)
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(|e| offline_fallback_message(&e))?;
+
+        if !response.status().is_success() {
+            return Err(SigilError::from_status(
+                response.status(),
+                format!("token rejected (server returned {})", response.status()),
+            ));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| SigilError::Parse(format!("failed to parse response: {}", e)))?;
+        Ok(AccountInfo::from_verify(&body))
+    }
+
     /// Submit an enhanced scan with LLM analysis (Pro feature).
     ///
     /// POST /v1/scan-enhanced
@@ -370,15 +851,16 @@ impl SigilClient {
         &self,
         result: &ScanResult,
         file_contents: std::collections::HashMap<String, String>,
-    ) -> Result<ScanResponse, String> {
+    ) -> Result<ScanResponse, SigilError> {
         let url = format!("{}/v1/scan-enhanced", self.endpoint);
 
         // Build enhanced request with file contents for LLM analysis
         let mut metadata = serde_json::Map::new();
         metadata.insert(
             "file_contents".to_string(),
-            serde_json::to_value(&file_contents)
-                .map_err(|e| format!("failed to serialize file contents: {}", e))?,
+            serde_json::to_value(&file_contents).map_err(|e| {
+                SigilError::Parse(format!("failed to serialize file contents: {}", e))
+            })?,
         );
 
         let request_body = serde_json::json!({
@@ -389,13 +871,15 @@ impl SigilClient {
             "metadata": metadata,
         });
 
-        let mut request = self.client.post(&url).json(&request_body);
-        if let Some(ref token) = self.token {
+        let mut request = self.post(&url).json(&request_body);
+        if let Some(token) = self.bearer() {
             request = request.bearer_auth(token);
         } else {
-            return Err(
-                "Authentication required for enhanced scanning. Run: sigil login".to_string(),
-            );
+            return Err(SigilError::Auth(if self.session_expired {
+                SESSION_EXPIRED_HINT.to_string()
+            } else {
+                "Authentication required for enhanced scanning. Run: sigil login".to_string()
+            }));
         }
 
         let response = request
@@ -405,24 +889,27 @@ impl SigilClient {
 
         let status = response.status();
         if status.as_u16() == 402 {
-            return Err(
+            return Err(SigilError::Auth(
                 "Pro subscription required for LLM analysis. Upgrade at https://app.sigilsec.ai/upgrade"
                     .to_string(),
-            );
+            ));
         }
 
         if !status.is_success() {
-            return Err(format!(
-                "API error: {} {}",
+            return Err(SigilError::from_status(
                 status,
-                response.text().await.unwrap_or_default()
+                format!(
+                    "API error: {} {}",
+                    status,
+                    response.text().await.unwrap_or_default()
+                ),
             ));
         }
 
         response
             .json::<ScanResponse>()
             .await
-            .map_err(|e| format!("failed to parse response: {}", e))
+            .map_err(|e| SigilError::Parse(format!("failed to parse response: {}", e)))
     }
 
     /// Authenticate via the OAuth 2.0 device authorization flow.
@@ -430,12 +917,11 @@ impl SigilClient {
     /// Requests a device code, shows the user the verification URL + code,
     /// then polls until they complete sign-in in the browser. Saves the
     /// resulting access token. This replaces the removed password login.
-    pub async fn login_device_flow(&self) -> Result<(), String> {
+    pub async fn login_device_flow(&self) -> Result<(), SigilError> {
         use colored::Colorize;
 
         // 1. Request a device code.
         let code: DeviceCodeResponse = self
-            .client
             .post(format!("{}/v1/auth/device/code", self.endpoint))
             .json(&serde_json::json!({}))
             .send()
@@ -443,16 +929,21 @@ impl SigilClient {
             .map_err(|e| offline_fallback_message(&e))?
             .error_for_status()
             .map_err(|e| match e.status() {
-                Some(s) if s.as_u16() == 503 => {
+                Some(s) if s.as_u16() == 503 => SigilError::Network(
                     "device flow unavailable (server returned 503 — Auth0 not configured)"
-                        .to_string()
-                }
-                Some(s) => format!("could not start device flow (server returned {})", s),
-                None => format!("could not start device flow: {}", e),
+                        .to_string(),
+                ),
+                Some(s) => SigilError::from_status(
+                    s,
+                    format!("could not start device flow (server returned {})", s),
+                ),
+                None => SigilError::Network(format!("could not start device flow: {}", e)),
             })?
             .json()
             .await
-            .map_err(|e| format!("failed to parse device code response: {}", e))?;
+            .map_err(|e| {
+                SigilError::Parse(format!("failed to parse device code response: {}", e))
+            })?;
 
         // 2. Prompt the user.
         let url = if code.verification_uri_complete.is_empty() {
@@ -481,7 +972,6 @@ impl SigilClient {
             tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
 
             let resp = self
-                .client
                 .post(&token_url)
                 .send()
                 .await
@@ -489,11 +979,13 @@ impl SigilClient {
 
             let status = resp.status();
             if status.is_success() {
-                let body: DeviceTokenResponse = resp
-                    .json()
-                    .await
-                    .map_err(|e| format!("failed to parse token response: {}", e))?;
-                save_token(&body.access_token)?;
+                let body: DeviceTokenResponse = resp.json().await.map_err(|e| {
+                    SigilError::Parse(format!("failed to parse token response: {}", e))
+                })?;
+                let expires_at = body.expires_in.map(|secs| {
+                    (chrono::Utc::now() + chrono::Duration::seconds(secs)).to_rfc3339()
+                });
+                save_token(&self.stored(&body.access_token, expires_at))?;
                 return Ok(());
             }
 
@@ -511,23 +1003,24 @@ impl SigilClient {
                     continue;
                 }
                 "expired_token" => {
-                    return Err("the sign-in code expired — run `sigil login` again".to_string())
+                    return Err(SigilError::Auth(
+                        "the sign-in code expired — run `sigil login` again".to_string(),
+                    ))
                 }
-                "access_denied" => return Err("sign-in was denied".to_string()),
-                other => return Err(format!("device flow failed: {}", other)),
+                "access_denied" => return Err(SigilError::Auth("sign-in was denied".to_string())),
+                other => return Err(SigilError::Auth(format!("device flow failed: {}", other))),
             }
         }
     }
 
     /// Register a new account and receive a token.
     #[allow(dead_code)]
-    pub async fn register(&self, email: &str) -> Result<String, String> {
+    pub async fn register(&self, email: &str) -> Result<String, SigilError> {
         let url = format!("{}/v1/auth/register", self.endpoint);
 
         let body = serde_json::json!({ "email": email });
 
         let response = self
-            .client
             .post(&url)
             .json(&body)
             .send()
@@ -535,15 +1028,18 @@ impl SigilClient {
             .map_err(|e| offline_fallback_message(&e))?;
 
         if !response.status().is_success() {
-            return Err(format!("registration failed: {}", response.status()));
+            return Err(SigilError::from_status(
+                response.status(),
+                format!("registration failed: {}", response.status()),
+            ));
         }
 
         let auth: AuthResponse = response
             .json()
             .await
-            .map_err(|e| format!("failed to parse auth response: {}", e))?;
+            .map_err(|e| SigilError::Parse(format!("failed to parse auth response: {}", e)))?;
 
-        save_token(&auth.token)?;
+        save_token(&self.stored(&auth.token, auth.expires_at.clone()))?;
         Ok(auth.token)
     }
 }
@@ -552,14 +1048,262 @@ impl SigilClient {
 // Helpers
 // ---------------------------------------------------------------------------
 
-/// Produce a user-friendly error message when the API is unreachable.
-fn offline_fallback_message(err: &reqwest::Error) -> String {
+/// Produce a user-friendly error when the API is unreachable.
+fn offline_fallback_message(err: &reqwest::Error) -> SigilError {
     if err.is_connect() || err.is_timeout() {
-        "Sigil cloud is unreachable (running in offline mode). \
-         Local scanning will continue to work, but threat intelligence \
-         and signature updates are unavailable."
-            .to_string()
+        SigilError::Network(
+            "Sigil cloud is unreachable (running in offline mode). \
+             Local scanning will continue to work, but threat intelligence \
+             and signature updates are unavailable."
+                .to_string(),
+        )
     } else {
-        format!("network error: {}", err)
+        SigilError::Network(format!("network error: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_builds() {
+        assert!(build_http_client(&HttpConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn proxy_and_insecure_are_accepted() {
+        let config = HttpConfig {
+            https_proxy: Some("http://proxy.corp.example:3128".into()),
+            http_proxy: Some("http://proxy.corp.example:3128".into()),
+            insecure: true,
+            ..HttpConfig::default()
+        };
+        assert!(build_http_client(&config).is_ok());
+    }
+
+    #[test]
+    fn unreadable_ca_bundle_is_an_error() {
+        let config = HttpConfig {
+            ca_bundle: Some(PathBuf::from("/nonexistent/sigil-ca.pem")),
+            ..HttpConfig::default()
+        };
+        let err = build_http_client(&config).unwrap_err();
+        assert!(matches!(err, SigilError::Config(_)));
+        assert!(err.to_string().contains("SIGIL_CA_BUNDLE"), "{err}");
+    }
+
+    #[test]
+    fn ca_bundle_without_certificates_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ca.pem");
+        fs::write(&path, "not a certificate\n").unwrap();
+        let config = HttpConfig {
+            ca_bundle: Some(path),
+            ..HttpConfig::default()
+        };
+        assert!(build_http_client(&config).is_err());
+    }
+
+    /// Serve `responses` in order on a loopback port, one per connection.
+    async fn serve_responses(responses: Vec<String>) -> String {
+        serve_responses_after(Duration::ZERO, responses).await
+    }
+
+    /// Like [`serve_responses`], but wait `delay` before each response.
+    async fn serve_responses_after(delay: Duration, responses: Vec<String>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                tokio::time::sleep(delay).await;
+                // The client may have given up already.
+                let _ = socket.write_all(response.as_bytes()).await;
+                socket.shutdown().await.ok();
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn timeouts_are_read_from_config() {
+        let config: serde_json::Map<String, serde_json::Value> = [
+            (REQUEST_TIMEOUT_KEY, serde_json::json!(5)),
+            (SIGNATURES_TIMEOUT_KEY, serde_json::json!("600")),
+            (CONNECT_TIMEOUT_KEY, serde_json::json!(0)),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        let timeouts = Timeouts::from_config(&config);
+        assert_eq!(timeouts.request, Duration::from_secs(5));
+        assert_eq!(timeouts.signatures, Duration::from_secs(600));
+        // Zero is unusable, so the default stands.
+        assert_eq!(timeouts.connect, Timeouts::default().connect);
+        assert_eq!(
+            Timeouts::from_config(&serde_json::Map::new()),
+            Timeouts::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_endpoint_times_out_auth_but_not_signature_fetch() {
+        let body = r#"{"signatures":[],"total":0,"last_updated":null}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let endpoint =
+            serve_responses_after(Duration::from_millis(500), vec![response.clone(), response])
+                .await;
+        let client = SigilClient {
+            endpoint,
+            client: reqwest::Client::new(),
+            token: Some("token".to_string()),
+            session_expired: false,
+            timeouts: Timeouts {
+                request: Duration::from_millis(100),
+                signatures: Duration::from_secs(10),
+                ..Timeouts::default()
+            },
+        };
+
+        let err = client.whoami().await.unwrap_err();
+        assert!(matches!(err, SigilError::Network(_)), "{err}");
+        // The download gets its own, longer budget, so it is answered; what
+        // the answer is (an unsigned bundle) does not matter here.
+        let fetched = client.get_signatures(true, true).await;
+        assert!(
+            !matches!(fetched, Err(SigilError::Network(_))),
+            "{:?}",
+            fetched.err()
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limited_lookup_is_retried() {
+        let body = r#"{"hash":"abc","known_malicious":true,"threat_type":null,"description":null,"first_seen":null,"references":[]}"#;
+        let endpoint = serve_responses(vec![
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            ),
+        ])
+        .await;
+        let client = SigilClient {
+            endpoint,
+            client: reqwest::Client::new(),
+            token: None,
+            session_expired: false,
+            timeouts: Timeouts::default(),
+        };
+
+        let results = client.lookup_threats(&["abc".to_string()], 8).await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "abc");
+        assert!(results[0].1.as_ref().unwrap().known_malicious);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_reports_retry_after() {
+        let endpoint = serve_responses(vec![
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 7\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+        ])
+        .await;
+        let client = SigilClient {
+            endpoint,
+            client: reqwest::Client::new(),
+            token: None,
+            session_expired: false,
+            timeouts: Timeouts::default(),
+        };
+        let err = client.lookup_threat("abc").await.unwrap_err();
+        assert!(matches!(
+            err,
+            SigilError::RateLimited {
+                retry_after: Some(7),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn plaintext_token_is_migrated() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("token"), "legacy-token\n").unwrap();
+
+        let now = chrono::Utc::now();
+        assert_eq!(
+            token_state_in(dir.path(), now),
+            TokenState::Valid("legacy-token".into())
+        );
+        assert!(!dir.path().join("token").exists());
+        let stored = read_stored_token(dir.path()).unwrap();
+        assert_eq!(stored.token, "legacy-token");
+        assert_eq!(stored.expires_at, None);
+    }
+
+    #[test]
+    fn expired_token_is_treated_as_absent() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = chrono::Utc::now();
+        let stored = StoredToken {
+            token: "t".into(),
+            expires_at: Some((now - chrono::Duration::hours(1)).to_rfc3339()),
+            endpoint: None,
+        };
+        write_stored_token(dir.path(), &stored).unwrap();
+        assert_eq!(token_state_in(dir.path(), now), TokenState::Expired);
+
+        let fresh = StoredToken {
+            expires_at: Some((now + chrono::Duration::hours(1)).to_rfc3339()),
+            ..stored
+        };
+        write_stored_token(dir.path(), &fresh).unwrap();
+        assert_eq!(
+            token_state_in(dir.path(), now),
+            TokenState::Valid("t".into())
+        );
+    }
+
+    #[test]
+    fn missing_or_unparseable_expiry_is_not_expired() {
+        let now = chrono::Utc::now();
+        let stored = StoredToken {
+            token: "t".into(),
+            expires_at: Some("next tuesday".into()),
+            endpoint: None,
+        };
+        assert!(!stored.is_expired(now));
+        assert!(!StoredToken {
+            expires_at: None,
+            ..stored
+        }
+        .is_expired(now));
+        assert_eq!(
+            token_state_in(tempfile::tempdir().unwrap().path(), now),
+            TokenState::Missing
+        );
+    }
+
+    #[test]
+    fn account_email_read_from_either_shape() {
+        let flat =
+            serde_json::json!({"email": "a@example.com", "expires_at": "2030-01-01T00:00:00Z"});
+        let info = AccountInfo::from_verify(&flat);
+        assert_eq!(info.email.as_deref(), Some("a@example.com"));
+        assert_eq!(info.expires_at.as_deref(), Some("2030-01-01T00:00:00Z"));
+        let nested = serde_json::json!({"user": {"email": "b@example.com"}});
+        assert_eq!(
+            AccountInfo::from_verify(&nested).email.as_deref(),
+            Some("b@example.com")
+        );
     }
 }
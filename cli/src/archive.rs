@@ -0,0 +1,539 @@
+//! Archive extraction for quarantined packages.
+//!
+//! Wheels, zips and gzipped tarballs are unpacked in place so the scanner sees
+//! the source files inside them. Extraction is recursive (a `.tar.gz` holding a
+//! `.whl` is fully opened) up to a depth limit, and every byte written counts
+//! against a shared expansion budget so a zip bomb cannot exhaust the disk.
+//! Archive entry paths are never trusted: anything that would resolve outside
+//! the extraction directory (zip-slip / tar-slip) is skipped and reported as a
+//! Critical finding — a legitimate package never ships one.
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::corpus::registry;
+use crate::scanner::{relative_path, Finding, Phase, Severity};
+
+/// Default nesting depth: top-level archives are depth 1.
+pub const DEFAULT_MAX_DEPTH: usize = 3;
+
+/// Default cap on total bytes written across all archives (500 MB).
+pub const DEFAULT_MAX_TOTAL_BYTES: u64 = 500 * 1024 * 1024;
+
+/// Limits for a single `extract_archives` call.
+#[derive(Debug, Clone)]
+pub struct ExtractOptions {
+    /// How many archive layers to open. Archives nested deeper are left as-is.
+    pub max_depth: usize,
+    /// Total decompressed bytes allowed before extraction is aborted.
+    pub max_total_bytes: u64,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        ExtractOptions {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_total_bytes: DEFAULT_MAX_TOTAL_BYTES,
+        }
+    }
+}
+
+/// What extraction did beyond unpacking files: findings the caller must merge
+/// into the scan result, plus counters for verbose output.
+#[derive(Debug, Default)]
+pub struct ExtractReport {
+    pub findings: Vec<Finding>,
+    pub archives_extracted: usize,
+    pub bytes_written: u64,
+    /// Entries dropped because their path (or link target) escaped the
+    /// extraction directory. Each one also produced a PROV-008 finding.
+    pub skipped_entries: usize,
+    /// Why extraction stopped early, if it did. Everything above still
+    /// describes the archives handled before the failure.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    TarGz,
+}
+
+fn archive_kind(name: &str) -> Option<ArchiveKind> {
+    let lower = name.to_lowercase();
+    if lower.ends_with(".whl") || lower.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else {
+        None
+    }
+}
+
+/// Directory an archive unpacks into: its name minus the archive suffix.
+fn extract_dir_for(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let lower = name.to_lowercase();
+    let stem_len = [".tar.gz", ".tgz", ".whl", ".zip"]
+        .iter()
+        .find(|s| lower.ends_with(*s))
+        .map(|s| name.len() - s.len())
+        .unwrap_or(name.len());
+    path.with_file_name(&name[..stem_len])
+}
+
+/// Resolve an archive entry name under `root`, or `None` when it is absolute
+/// or climbs out via `..`. Purely lexical — nothing on disk is consulted, so a
+/// pre-planted symlink cannot redirect the check.
+pub fn safe_join(root: &Path, entry_name: &Path) -> Option<PathBuf> {
+    let mut out = root.to_path_buf();
+    let mut depth = 0usize;
+    for component in entry_name.components() {
+        match component {
+            Component::Normal(part) => {
+                out.push(part);
+                depth += 1;
+            }
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if depth == 0 {
+                    return None;
+                }
+                out.pop();
+                depth -= 1;
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (depth > 0).then_some(out)
+}
+
+/// Shared state threaded through one recursive extraction.
+struct Extraction<'a> {
+    root: &'a Path,
+    options: &'a ExtractOptions,
+    report: ExtractReport,
+    limit_hit: bool,
+}
+
+impl Extraction<'_> {
+    fn rel(&self, path: &Path) -> String {
+        relative_path(path, self.root)
+    }
+
+    fn remaining(&self) -> u64 {
+        self.options
+            .max_total_bytes
+            .saturating_sub(self.report.bytes_written)
+    }
+
+    /// Copy one entry body to `dest`, charging it against the budget. Returns
+    /// `false` (and records the finding) when the budget runs out.
+    fn write_entry(
+        &mut self,
+        reader: &mut dyn Read,
+        dest: &Path,
+        archive: &Path,
+    ) -> io::Result<bool> {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let budget = self.remaining();
+        let mut out = fs::File::create(dest)?;
+        // Read one byte past the budget so "exactly at the cap" still passes.
+        let written = io::copy(&mut reader.take(budget + 1), &mut out)?;
+        if written > budget {
+            drop(out);
+            let _ = fs::remove_file(dest);
+            self.report.bytes_written = self.options.max_total_bytes;
+            self.expansion_limit_exceeded(archive);
+            return Ok(false);
+        }
+        self.report.bytes_written += written;
+        Ok(true)
+    }
+
+    fn expansion_limit_exceeded(&mut self, archive: &Path) {
+        self.limit_hit = true;
+        self.report.findings.push(Finding {
+            phase: Phase::Provenance,
+            rule: "PROV-007".to_string(),
+            severity: Severity::High,
+            file: self.rel(archive),
+            line: None,
+            snippet: format!(
+                "Archive expansion limit exceeded ({} bytes) — possible decompression bomb; extraction aborted",
+                self.options.max_total_bytes
+            ),
+            weight: 3,
+            confidence: registry::confidence("PROV-007"),
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        });
+    }
+
+    /// Record an entry that tried to resolve outside its extraction directory.
+    fn path_escape(&mut self, archive: &Path, entry: &Path, how: &str) {
+        self.report.skipped_entries += 1;
+        self.report.findings.push(Finding {
+            phase: Phase::Provenance,
+            rule: "PROV-008".to_string(),
+            severity: Severity::Critical,
+            file: self.rel(archive),
+            line: None,
+            snippet: format!(
+                "Archive entry escapes the extraction directory ({}): {} — path traversal (zip-slip); entry skipped",
+                how,
+                entry.display()
+            ),
+            weight: 3,
+            confidence: registry::confidence("PROV-008"),
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        });
+    }
+
+    fn extract_zip(
+        &mut self,
+        archive_path: &Path,
+        dest: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let file = fs::File::open(archive_path)?;
+        let mut archive = zip::ZipArchive::new(file)?;
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)?;
+            let name = PathBuf::from(entry.name());
+            let target = match safe_join(dest, &name) {
+                Some(t) => t,
+                None => {
+                    self.path_escape(archive_path, &name, "entry path");
+                    continue;
+                }
+            };
+            if entry.is_dir() {
+                fs::create_dir_all(&target)?;
+                continue;
+            }
+            if !self.write_entry(&mut entry, &target, archive_path)? {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    fn extract_tar_gz(
+        &mut self,
+        archive_path: &Path,
+        dest: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let file = fs::File::open(archive_path)?;
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.into_owned();
+            let target = match safe_join(dest, &name) {
+                Some(t) => t,
+                None => {
+                    self.path_escape(archive_path, &name, "entry path");
+                    continue;
+                }
+            };
+            let kind = entry.header().entry_type();
+            if let Some(link) = entry.link_name()? {
+                // Symlink targets resolve from the entry's directory; hardlink
+                // targets from the archive root.
+                let resolved = if kind.is_symlink() {
+                    name.parent().unwrap_or(Path::new("")).join(&link)
+                } else {
+                    link.to_path_buf()
+                };
+                if link.is_absolute() || safe_join(dest, &resolved).is_none() {
+                    self.path_escape(archive_path, &name, "link target");
+                    continue;
+                }
+            }
+            if kind.is_dir() {
+                fs::create_dir_all(&target)?;
+            } else if kind.is_file() && !self.write_entry(&mut entry, &target, archive_path)? {
+                return Ok(());
+            }
+            // Symlinks, hardlinks and device nodes are never materialized:
+            // a link is exactly how an entry escapes after the path check.
+        }
+        Ok(())
+    }
+
+    /// Extract every archive under `dir` (recursively), then the archives they
+    /// contained, until `max_depth` layers have been opened.
+    fn extract_tree(&mut self, dir: &Path, depth: usize) -> Result<(), Box<dyn std::error::Error>> {
+        if depth > self.options.max_depth || self.limit_hit {
+            return Ok(());
+        }
+        let archives: Vec<PathBuf> = WalkDir::new(dir)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter(|e| archive_kind(&e.file_name().to_string_lossy()).is_some())
+            .map(|e| e.into_path())
+            .collect();
+
+        for archive_path in archives {
+            if self.limit_hit {
+                break;
+            }
+            let name = archive_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            let extract_dir = extract_dir_for(&archive_path);
+            fs::create_dir_all(&extract_dir)?;
+            match archive_kind(&name) {
+                Some(ArchiveKind::Zip) => self.extract_zip(&archive_path, &extract_dir)?,
+                Some(ArchiveKind::TarGz) => self.extract_tar_gz(&archive_path, &extract_dir)?,
+                None => continue,
+            }
+            fs::remove_file(&archive_path)?;
+            self.report.archives_extracted += 1;
+            self.extract_tree(&extract_dir, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Extract .whl/.zip and .tar.gz/.tgz archives in a directory (and the
+/// archives inside them, up to `options.max_depth`) so the scanner can inspect
+/// the actual source files inside packages.  A corrupt or unreadable archive
+/// stops extraction and is recorded in `error`; the findings gathered up to
+/// that point are kept.
+pub fn extract_archives(dir: &Path, options: &ExtractOptions) -> ExtractReport {
+    let mut extraction = Extraction {
+        root: dir,
+        options,
+        report: ExtractReport::default(),
+        limit_hit: false,
+    };
+    if let Err(err) = extraction.extract_tree(dir, 1) {
+        extraction.report.error = Some(err.to_string());
+    }
+    extraction.report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        for (name, data) in entries {
+            zip.start_file(*name, zip::write::FileOptions::default())
+                .unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    /// Build a .tar.gz; names are written raw into the header so traversal
+    /// entries (which `tar::Builder` refuses) can be crafted.
+    fn write_tar_gz(path: &Path, entries: &[(&str, &[u8])]) {
+        let gz = flate2::write::GzEncoder::new(
+            fs::File::create(path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(gz);
+        for (name, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            let raw = &mut header.as_gnu_mut().unwrap().name;
+            raw[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_cksum();
+            builder.append(&header, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn safe_join_rejects_escapes() {
+        let root = Path::new("/q/pkg");
+        assert_eq!(
+            safe_join(root, Path::new("a/b.py")),
+            Some(PathBuf::from("/q/pkg/a/b.py"))
+        );
+        assert_eq!(
+            safe_join(root, Path::new("a/../b.py")),
+            Some(PathBuf::from("/q/pkg/b.py"))
+        );
+        assert_eq!(safe_join(root, Path::new("../../.bashrc")), None);
+        assert_eq!(safe_join(root, Path::new("a/../../x")), None);
+        assert_eq!(safe_join(root, Path::new("/etc/passwd")), None);
+        assert_eq!(safe_join(root, Path::new(".")), None);
+    }
+
+    #[test]
+    fn nested_archive_is_fully_extracted() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = tempfile::tempdir().unwrap();
+        let wheel = work.path().join("inner-1.0.whl");
+        write_zip(&wheel, &[("inner/payload.py", b"eval(x)\n")]);
+        let wheel_bytes = fs::read(&wheel).unwrap();
+        write_tar_gz(
+            &dir.path().join("outer.tar.gz"),
+            &[
+                ("outer/inner-1.0.whl", &wheel_bytes),
+                ("outer/setup.py", b"x\n"),
+            ],
+        );
+
+        let report = extract_archives(dir.path(), &ExtractOptions::default());
+
+        assert_eq!(report.archives_extracted, 2);
+        assert!(dir
+            .path()
+            .join("outer/outer/inner-1.0/inner/payload.py")
+            .exists());
+        assert!(!dir.path().join("outer.tar.gz").exists());
+        assert!(!dir.path().join("outer/outer/inner-1.0.whl").exists());
+    }
+
+    #[test]
+    fn depth_limit_leaves_deeper_archives_packed() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = tempfile::tempdir().unwrap();
+        let inner = work.path().join("inner.zip");
+        write_zip(&inner, &[("a.py", b"1\n")]);
+        let inner_bytes = fs::read(&inner).unwrap();
+        write_zip(
+            &dir.path().join("outer.zip"),
+            &[("inner.zip", &inner_bytes)],
+        );
+
+        let options = ExtractOptions {
+            max_depth: 1,
+            ..ExtractOptions::default()
+        };
+        let report = extract_archives(dir.path(), &options);
+
+        assert_eq!(report.archives_extracted, 1);
+        assert!(dir.path().join("outer/inner.zip").exists());
+    }
+
+    #[test]
+    fn expansion_cap_aborts_with_finding() {
+        let dir = tempfile::tempdir().unwrap();
+        let big = vec![b'A'; 64 * 1024];
+        write_zip(
+            &dir.path().join("bomb.zip"),
+            &[("a.txt", &big), ("b.txt", &big)],
+        );
+
+        let options = ExtractOptions {
+            max_total_bytes: 100 * 1024,
+            ..ExtractOptions::default()
+        };
+        let report = extract_archives(dir.path(), &options);
+
+        assert!(report.findings.iter().any(|f| f.rule == "PROV-007"));
+        assert!(report.bytes_written <= options.max_total_bytes);
+        assert!(!dir.path().join("bomb/b.txt").exists());
+    }
+
+    #[test]
+    fn traversal_entries_are_skipped_and_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let quarantine = dir.path().join("q");
+        fs::create_dir_all(&quarantine).unwrap();
+        write_tar_gz(
+            &quarantine.join("evil.tgz"),
+            &[("../../escaped.txt", b"pwned\n"), ("ok.txt", b"fine\n")],
+        );
+        write_zip(
+            &quarantine.join("evil.zip"),
+            &[("../zip-escaped.txt", b"pwned\n"), ("ok.txt", b"fine\n")],
+        );
+
+        let report = extract_archives(&quarantine, &ExtractOptions::default());
+
+        assert_eq!(report.skipped_entries, 2);
+        let escapes: Vec<&Finding> = report
+            .findings
+            .iter()
+            .filter(|f| f.rule == "PROV-008")
+            .collect();
+        assert_eq!(escapes.len(), 2);
+        assert!(escapes.iter().all(|f| f.severity == Severity::Critical));
+        assert!(escapes.iter().any(|f| f.file == "evil.tgz"));
+        assert!(escapes.iter().any(|f| f.file == "evil.zip"));
+        assert!(!dir.path().join("escaped.txt").exists());
+        assert!(!dir.path().join("zip-escaped.txt").exists());
+        assert!(quarantine.join("evil/ok.txt").exists());
+    }
+
+    #[test]
+    fn findings_before_a_corrupt_entry_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("evil.tgz");
+        // Incompressible body, so cutting the file lands inside it.
+        let mut state = 0x2545_f491_u32;
+        let noise: Vec<u8> = (0..64 * 1024)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        write_tar_gz(
+            &archive,
+            &[("../escaped.txt", b"pwned\n"), ("payload.bin", &noise)],
+        );
+        let bytes = fs::read(&archive).unwrap();
+        fs::write(&archive, &bytes[..bytes.len() / 2]).unwrap();
+
+        let report = extract_archives(dir.path(), &ExtractOptions::default());
+
+        assert!(report.error.is_some(), "{report:?}");
+        assert!(report.findings.iter().any(|f| f.rule == "PROV-008"));
+    }
+
+    #[test]
+    fn escaping_symlink_entry_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let gz = flate2::write::GzEncoder::new(
+            fs::File::create(dir.path().join("links.tgz")).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(gz);
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        header.set_mode(0o777);
+        header.set_link_name("../../../home/user/.ssh").unwrap();
+        builder
+            .append_data(&mut header, "pkg/ssh", std::io::empty())
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let report = extract_archives(dir.path(), &ExtractOptions::default());
+
+        assert!(report
+            .findings
+            .iter()
+            .any(|f| f.rule == "PROV-008" && f.snippet.contains("link target")));
+        assert!(fs::symlink_metadata(dir.path().join("links/pkg/ssh")).is_err());
+    }
+}
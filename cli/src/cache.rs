@@ -2,18 +2,50 @@
 //!
 //! Computes a hash of all file paths and bytes in a directory.
 //! If the hash matches a cached result, returns the cached scan without re-scanning.
+//!
+//! Below the whole-directory cache sits a per-file finding cache keyed on each
+//! file's content hash, so a rescan after a small edit only re-runs the
+//! phases on the files that actually changed.
+//!
+//! A scan given `--checkpoint` also keeps its own progress in the same
+//! per-file form, written every few seconds while it runs, so a scan killed
+//! partway can be rerun and skip every file it had already finished.
+//!
+//! `scan --baseline-auto` keeps one more entry per scan target, keyed on the
+//! target's path rather than its contents: the result of the last such scan,
+//! which the next one is diffed against.
+//!
+//! All of these are keyed on the ruleset as well: a fingerprint of the fetched
+//! signatures, user packs and rules, the allowlist, and disabled signature
+//! IDs.  Anything
+//! that changes what a scan can detect (`sigil fetch`, a new rule file)
+//! invalidates every entry, so a new detection is never hidden by a hit.
 
-use crate::scanner::ScanResult;
+use crate::corpus::loader;
+use crate::error::SigilError;
+use crate::logging::log_warn;
+use crate::scanner::{cloud_sigs, user_rules, Finding, ScanResult};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
 
-const CACHE_DIR: &str = ".sigil/cache";
-const CACHE_VERSION: u32 = 1;
+const CACHE_VERSION: u32 = 3;
+/// Sidecar file holding the per-file finding cache.
+const FILE_CACHE_NAME: &str = "files.json";
+/// Subdirectory holding the last `--baseline-auto` result of each target.
+const PREVIOUS_DIR: &str = "previous";
+/// How often a running scan rewrites its checkpoint.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
+/// Upper bound on per-file entries kept on disk; entries used by the latest
+/// scan are always kept, older ones fill the remainder.
+const MAX_FILE_CACHE_ENTRIES: usize = 50_000;
 
 #[derive(Serialize, Deserialize)]
 struct CacheEntry {
@@ -23,12 +55,19 @@ struct CacheEntry {
     // serve stale security verdicts. Entries without this field are invalid.
     #[serde(default)]
     scanner_version: String,
+    /// [`ruleset_fingerprint`] of the rules the result was produced with.
+    #[serde(default)]
+    ruleset: String,
     directory_hash: String,
+    /// Canonical path of the scanned target; only set on
+    /// [`save_previous`] entries, which are looked up by it.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    target: String,
     result: ScanResult,
 }
 
 /// Compute a hash of directory contents (file paths + bytes).
-pub fn compute_directory_hash(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+pub fn compute_directory_hash(path: &Path) -> Result<String, SigilError> {
     let mut hasher = Sha256::new();
     let mut file_map: BTreeMap<PathBuf, PathBuf> = BTreeMap::new();
 
@@ -64,23 +103,71 @@ pub fn compute_directory_hash(path: &Path) -> Result<String, Box<dyn std::error:
     Ok(hex::encode(hasher.finalize()))
 }
 
-/// Get cache directory path.
+/// Get cache directory path (~/.sigil/cache).
 fn cache_dir() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(CACHE_DIR)
+    crate::paths::in_home("cache")
+}
+
+/// Hash of the rules a scan runs beyond those built into the binary (which
+/// `scanner_version` already covers): the fetched signatures, user packs,
+/// user rules, the allowlist, and the disabled signature IDs.
+pub fn ruleset_fingerprint() -> String {
+    let mut disabled: Vec<String> = cloud_sigs::disabled_signatures().into_iter().collect();
+    disabled.sort();
+    ruleset_fingerprint_of(
+        &[
+            cloud_sigs::signatures_path(),
+            loader::user_packs_dir().unwrap_or_default(),
+            user_rules::default_rules_dir(),
+            crate::allowlist::default_path(),
+        ],
+        &disabled,
+    )
+}
+
+/// Hash the bytes of every file under `sources` (files or directories,
+/// missing ones included as absent) and the `disabled` IDs.
+fn ruleset_fingerprint_of(sources: &[PathBuf], disabled: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    for source in sources {
+        hasher.update(source.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        let mut files: Vec<PathBuf> = WalkDir::new(source)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .collect();
+        files.sort();
+        for file in files {
+            hasher.update(file.to_string_lossy().as_bytes());
+            hasher.update([0]);
+            hasher.update(fs::read(&file).unwrap_or_default());
+            hasher.update([0]);
+        }
+    }
+    for id in disabled {
+        hasher.update(id.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
 }
 
 /// Try to load a cached scan result for the given directory.
 pub fn load_cached(path: &Path) -> Option<ScanResult> {
+    load_cached_in(&cache_dir(), path, &ruleset_fingerprint())
+}
+
+fn load_cached_in(cache_dir: &Path, path: &Path, ruleset: &str) -> Option<ScanResult> {
     let dir_hash = compute_directory_hash(path).ok()?;
-    let cache_file = cache_dir().join(format!("{}.json", &dir_hash[..16]));
+    let cache_file = cache_dir.join(format!("{}.json", &dir_hash[..16]));
 
     let data = fs::read_to_string(&cache_file).ok()?;
     let entry: CacheEntry = serde_json::from_str(&data).ok()?;
 
     if entry.version == CACHE_VERSION
         && entry.scanner_version == env!("CARGO_PKG_VERSION")
+        && entry.ruleset == ruleset
         && entry.directory_hash == dir_hash
     {
         Some(entry.result)
@@ -90,15 +177,25 @@ pub fn load_cached(path: &Path) -> Option<ScanResult> {
 }
 
 /// Save a scan result to cache.
-pub fn save_to_cache(path: &Path, result: &ScanResult) -> Result<(), Box<dyn std::error::Error>> {
+pub fn save_to_cache(path: &Path, result: &ScanResult) -> Result<(), SigilError> {
+    save_to_cache_in(&cache_dir(), path, result, &ruleset_fingerprint())
+}
+
+fn save_to_cache_in(
+    cache_path: &Path,
+    path: &Path,
+    result: &ScanResult,
+    ruleset: &str,
+) -> Result<(), SigilError> {
     let dir_hash = compute_directory_hash(path)?;
-    let cache_path = cache_dir();
-    fs::create_dir_all(&cache_path)?;
+    fs::create_dir_all(cache_path)?;
 
     let entry = CacheEntry {
         version: CACHE_VERSION,
         scanner_version: env!("CARGO_PKG_VERSION").to_string(),
+        ruleset: ruleset.to_string(),
         directory_hash: dir_hash.clone(),
+        target: String::new(),
         result: result.clone(),
     };
 
@@ -106,7 +203,7 @@ pub fn save_to_cache(path: &Path, result: &ScanResult) -> Result<(), Box<dyn std
     fs::write(&cache_file, serde_json::to_string(&entry)?)?;
 
     // Prune old cache entries (keep max 100)
-    prune_cache(&cache_path, 100);
+    prune_cache(cache_path, 100);
 
     Ok(())
 }
@@ -134,18 +231,355 @@ fn prune_cache(cache_dir: &Path, max_entries: usize) {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Previous result per target (`scan --baseline-auto`)
+// ---------------------------------------------------------------------------
+
+/// Canonical form of `path` and the file its previous result is kept in.
+fn previous_file(cache_dir: &Path, path: &Path) -> (String, PathBuf) {
+    let target = fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .into_owned();
+    let key = hex::encode(Sha256::digest(target.as_bytes()));
+    let file = cache_dir
+        .join(PREVIOUS_DIR)
+        .join(format!("{}.json", &key[..16]));
+    (target, file)
+}
+
+/// The result of the last [`save_previous`] for `path`, whatever its
+/// contents were then.  `None` before the first one.  Unlike [`load_cached`]
+/// the entry is not tied to the scanner build or ruleset: a result from an
+/// older ruleset is still what the user saw last time, and
+/// [`crate::diff::ruleset_drift`] says when rules changed in between.
+pub fn load_previous(path: &Path) -> Option<ScanResult> {
+    load_previous_in(&cache_dir(), path)
+}
+
+fn load_previous_in(cache_dir: &Path, path: &Path) -> Option<ScanResult> {
+    let (target, file) = previous_file(cache_dir, path);
+    let data = fs::read_to_string(file).ok()?;
+    let entry: CacheEntry = serde_json::from_str(&data).ok()?;
+    (entry.version == CACHE_VERSION && entry.target == target).then_some(entry.result)
+}
+
+/// Keep `result` as the previous result of `path`, replacing the last one.
+pub fn save_previous(path: &Path, result: &ScanResult) -> Result<(), SigilError> {
+    save_previous_in(&cache_dir(), path, result, &ruleset_fingerprint())
+}
+
+fn save_previous_in(
+    cache_dir: &Path,
+    path: &Path,
+    result: &ScanResult,
+    ruleset: &str,
+) -> Result<(), SigilError> {
+    let (target, file) = previous_file(cache_dir, path);
+    fs::create_dir_all(cache_dir.join(PREVIOUS_DIR))?;
+    let entry = CacheEntry {
+        version: CACHE_VERSION,
+        scanner_version: env!("CARGO_PKG_VERSION").to_string(),
+        ruleset: ruleset.to_string(),
+        // Looked up by path, so the contents are not hashed.
+        directory_hash: String::new(),
+        target,
+        result: result.clone(),
+    };
+    fs::write(file, serde_json::to_string(&entry)?)?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Per-file finding cache
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize)]
+struct FileCacheData {
+    version: u32,
+    #[serde(default)]
+    scanner_version: String,
+    #[serde(default)]
+    ruleset: String,
+    entries: HashMap<String, Vec<Finding>>,
+}
+
+/// Findings per (relative path, content SHA-256), shared across the scanner's
+/// worker threads. Only full-phase scans read or populate it, and only for
+/// files every phase finished on, so a hit is exactly what a rescan would
+/// produce for that file under this scanner build.
+#[derive(Debug, Default)]
+pub struct FileCache {
+    path: Option<PathBuf>,
+    ruleset: String,
+    previous: HashMap<String, Vec<Finding>>,
+    current: Mutex<HashMap<String, Vec<Finding>>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+fn file_cache_key(rel_path: &str, sha256: &str) -> String {
+    format!("{}:{}", sha256, rel_path)
+}
+
+impl FileCache {
+    /// Load the per-file cache from `~/.sigil/cache/files.json`.
+    pub fn load() -> FileCache {
+        Self::load_from(&cache_dir().join(FILE_CACHE_NAME))
+    }
+
+    /// Load from an explicit sidecar path. A missing, unreadable, or
+    /// version- or ruleset-mismatched file yields an empty cache.
+    pub fn load_from(path: &Path) -> FileCache {
+        Self::load_with_ruleset(path, ruleset_fingerprint())
+    }
+
+    fn load_with_ruleset(path: &Path, ruleset: String) -> FileCache {
+        let previous = fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<FileCacheData>(&data).ok())
+            .filter(|d| {
+                d.version == CACHE_VERSION
+                    && d.scanner_version == env!("CARGO_PKG_VERSION")
+                    && d.ruleset == ruleset
+            })
+            .map(|d| d.entries)
+            .unwrap_or_default();
+        FileCache {
+            path: Some(path.to_path_buf()),
+            ruleset,
+            previous,
+            ..FileCache::default()
+        }
+    }
+
+    /// Cached findings for a file whose content hashes to `sha256`.
+    pub fn get(&self, rel_path: &str, sha256: &str) -> Option<Vec<Finding>> {
+        let key = file_cache_key(rel_path, sha256);
+        match self.previous.get(&key) {
+            Some(findings) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                if let Ok(mut current) = self.current.lock() {
+                    current.insert(key, findings.clone());
+                }
+                Some(findings.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Record the complete findings for a freshly scanned file.
+    pub fn insert(&self, rel_path: &str, sha256: &str, findings: &[Finding]) {
+        if let Ok(mut current) = self.current.lock() {
+            current.insert(file_cache_key(rel_path, sha256), findings.to_vec());
+        }
+    }
+
+    /// `(hits, misses)` since this cache was loaded.
+    pub fn stats(&self) -> (usize, usize) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Persist entries used or added by this scan, topped up with older
+    /// entries up to `MAX_FILE_CACHE_ENTRIES`.
+    pub fn save(&self) -> Result<(), SigilError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut entries = self
+            .current
+            .lock()
+            .map_err(|_| SigilError::Io("file cache lock poisoned".to_string()))?
+            .clone();
+        for (key, findings) in &self.previous {
+            if entries.len() >= MAX_FILE_CACHE_ENTRIES {
+                break;
+            }
+            entries
+                .entry(key.clone())
+                .or_insert_with(|| findings.clone());
+        }
+
+        let data = FileCacheData {
+            version: CACHE_VERSION,
+            scanner_version: env!("CARGO_PKG_VERSION").to_string(),
+            ruleset: self.ruleset.clone(),
+            entries,
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(&data)?)?;
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Scan checkpoint
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize)]
+struct CheckpointData {
+    version: u32,
+    scanner_version: String,
+    /// [`ruleset_fingerprint`] and the phase selection of the scan.
+    scope: String,
+    entries: HashMap<String, Vec<Finding>>,
+}
+
+/// The files one long scan has finished and their findings, kept at a path
+/// the user names (`scan --checkpoint`).  Unlike [`FileCache`] it is written
+/// while the scan runs, at most every [`CHECKPOINT_INTERVAL`] and once more
+/// at the end, so rerunning an interrupted scan with the same checkpoint
+/// only scans what is left.  [`Checkpoint::finish`] removes it.
+#[derive(Debug)]
+pub struct Checkpoint {
+    path: PathBuf,
+    scope: String,
+    done: Mutex<HashMap<String, Vec<Finding>>>,
+    resumed: AtomicUsize,
+    /// When the checkpoint was last written; held while writing, so only
+    /// one worker writes at a time.
+    last_write: Mutex<Instant>,
+    interval: Duration,
+}
+
+impl Checkpoint {
+    /// Open the checkpoint at `path` for a scan of `phases` (`None` for
+    /// every phase).  A missing file starts an empty one; a file left by a
+    /// scan with other rules or phases is ignored and overwritten.
+    pub fn open(path: &Path, phases: Option<&[String]>) -> Checkpoint {
+        let mut phases: Vec<&str> = phases
+            .unwrap_or_default()
+            .iter()
+            .map(String::as_str)
+            .collect();
+        phases.sort_unstable();
+        let scope = format!("{}:{}", ruleset_fingerprint(), phases.join(","));
+        Self::open_with(path, scope, CHECKPOINT_INTERVAL)
+    }
+
+    fn open_with(path: &Path, scope: String, interval: Duration) -> Checkpoint {
+        let done = fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<CheckpointData>(&data).ok())
+            .filter(|d| {
+                d.version == CACHE_VERSION
+                    && d.scanner_version == env!("CARGO_PKG_VERSION")
+                    && d.scope == scope
+            })
+            .map(|d| d.entries)
+            .unwrap_or_default();
+        Checkpoint {
+            path: path.to_path_buf(),
+            scope,
+            done: Mutex::new(done),
+            resumed: AtomicUsize::new(0),
+            last_write: Mutex::new(Instant::now()),
+            interval,
+        }
+    }
+
+    /// Number of files recorded so far.
+    pub fn recorded(&self) -> usize {
+        self.done.lock().map_or(0, |done| done.len())
+    }
+
+    /// Findings recorded for a file whose content hashes to `sha256`.
+    pub fn get(&self, rel_path: &str, sha256: &str) -> Option<Vec<Finding>> {
+        let done = self.done.lock().ok()?;
+        let findings = done.get(&file_cache_key(rel_path, sha256))?.clone();
+        self.resumed.fetch_add(1, Ordering::Relaxed);
+        Some(findings)
+    }
+
+    /// Record the complete findings for a file, and write the checkpoint
+    /// if it has not been written for a while.
+    pub fn record(&self, rel_path: &str, sha256: &str, findings: &[Finding]) {
+        if let Ok(mut done) = self.done.lock() {
+            done.insert(file_cache_key(rel_path, sha256), findings.to_vec());
+        }
+        // Another worker writing now will include this file, or the next
+        // write will.
+        let Ok(mut last_write) = self.last_write.try_lock() else {
+            return;
+        };
+        if last_write.elapsed() >= self.interval {
+            if let Err(err) = self.write() {
+                log_warn!("checkpoint not written: {}", err);
+            }
+            *last_write = Instant::now();
+        }
+    }
+
+    /// Files whose findings came from the checkpoint in this scan.
+    pub fn resumed(&self) -> usize {
+        self.resumed.load(Ordering::Relaxed)
+    }
+
+    /// Write everything recorded so far.
+    pub fn save(&self) -> Result<(), SigilError> {
+        let _writing = self
+            .last_write
+            .lock()
+            .map_err(|_| SigilError::Io("checkpoint lock poisoned".to_string()))?;
+        self.write()
+    }
+
+    /// The scan completed: the checkpoint is no longer needed.
+    pub fn finish(&self) {
+        let _writing = self.last_write.lock();
+        let _ = fs::remove_file(&self.path);
+    }
+
+    /// Replace the file by rename, so a scan killed mid-write keeps the
+    /// previous checkpoint.
+    fn write(&self) -> Result<(), SigilError> {
+        let entries = self
+            .done
+            .lock()
+            .map_err(|_| SigilError::Io("checkpoint lock poisoned".to_string()))?
+            .clone();
+        let data = CheckpointData {
+            version: CACHE_VERSION,
+            scanner_version: env!("CARGO_PKG_VERSION").to_string(),
+            scope: self.scope.clone(),
+            entries,
+        };
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut staging = self.path.clone().into_os_string();
+        staging.push(".tmp");
+        fs::write(&staging, serde_json::to_string(&data)?)?;
+        fs::rename(&staging, &self.path)?;
+        Ok(())
+    }
+}
+
 /// Clear all cached scan results.
-pub fn clear_cache() -> Result<usize, Box<dyn std::error::Error>> {
+pub fn clear_cache() -> Result<usize, SigilError> {
     let cache_path = cache_dir();
     if !cache_path.exists() {
         return Ok(0);
     }
 
     let mut count = 0;
-    for entry in fs::read_dir(&cache_path)?.flatten() {
-        if entry.path().extension().is_some_and(|e| e == "json") {
-            fs::remove_file(entry.path())?;
-            count += 1;
+    for dir in [cache_path.clone(), cache_path.join(PREVIOUS_DIR)] {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.path().extension().is_some_and(|e| e == "json") {
+                fs::remove_file(entry.path())?;
+                count += 1;
+            }
         }
     }
     Ok(count)
@@ -153,8 +587,13 @@ pub fn clear_cache() -> Result<usize, Box<dyn std::error::Error>> {
 
 #[cfg(test)]
 mod tests {
-    use super::compute_directory_hash;
+    use super::{
+        compute_directory_hash, load_cached_in, ruleset_fingerprint_of, save_to_cache_in,
+        Checkpoint, FileCache,
+    };
+    use crate::scanner::{run_scan_with_options, ScanOptions};
     use std::fs;
+    use std::sync::Arc;
     use std::time::{Duration, SystemTime};
     use tempfile::tempdir;
 
@@ -175,4 +614,159 @@ mod tests {
         let after = compute_directory_hash(dir.path()).expect("hash after");
         assert_ne!(before, after);
     }
+
+    fn scan_with(dir: &std::path::Path, cache: &Arc<FileCache>) -> Vec<String> {
+        let options = ScanOptions {
+            file_cache: Some(Arc::clone(cache)),
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(dir, None, None, &options);
+        result.findings.iter().map(|f| f.rule.clone()).collect()
+    }
+
+    #[test]
+    fn unchanged_files_reuse_cached_findings() {
+        let dir = tempdir().expect("tempdir");
+        let sidecar = dir.path().join("cache").join("files.json");
+        let target = dir.path().join("pkg");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("a.js"), "eval(x)\n").unwrap();
+        fs::write(target.join("b.js"), "console.log(1)\n").unwrap();
+
+        let cache = Arc::new(FileCache::load_from(&sidecar));
+        let first = scan_with(&target, &cache);
+        assert!(first.contains(&"CODE-001".to_string()));
+        assert_eq!(cache.stats(), (0, 2));
+        cache.save().unwrap();
+
+        let cache = Arc::new(FileCache::load_from(&sidecar));
+        let second = scan_with(&target, &cache);
+        assert_eq!(first, second);
+        assert_eq!(cache.stats(), (2, 0));
+    }
+
+    #[test]
+    fn changed_file_is_rescanned() {
+        let dir = tempdir().expect("tempdir");
+        let sidecar = dir.path().join("files.json");
+        let target = dir.path().join("pkg");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("a.js"), "console.log(1)\n").unwrap();
+        fs::write(target.join("b.js"), "console.log(2)\n").unwrap();
+
+        let cache = Arc::new(FileCache::load_from(&sidecar));
+        assert!(scan_with(&target, &cache).is_empty());
+        cache.save().unwrap();
+
+        fs::write(target.join("a.js"), "eval(payload)\n").unwrap();
+        let cache = Arc::new(FileCache::load_from(&sidecar));
+        let rules = scan_with(&target, &cache);
+        assert!(rules.contains(&"CODE-001".to_string()));
+        assert_eq!(cache.stats(), (1, 1));
+    }
+
+    #[test]
+    fn stale_cache_version_is_discarded() {
+        let dir = tempdir().expect("tempdir");
+        let sidecar = dir.path().join("files.json");
+        fs::write(
+            &sidecar,
+            r#"{"version":1,"scanner_version":"0.0.0","entries":{"abc:a.js":[]}}"#,
+        )
+        .unwrap();
+
+        let cache = FileCache::load_from(&sidecar);
+        assert!(cache.get("a.js", "abc").is_none());
+        assert_eq!(cache.stats(), (0, 1));
+    }
+
+    #[test]
+    fn changed_signatures_invalidate_cached_results() {
+        let dir = tempdir().expect("tempdir");
+        let home = dir.path().join("home");
+        let project = dir.path().join("project");
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("index.js"), "eval(x)\n").unwrap();
+        let signatures = home.join("signatures.json");
+        fs::write(&signatures, r#"{"signatures":[]}"#).unwrap();
+
+        let sources = [signatures.clone(), home.join("packs"), home.join("rules")];
+        let before = ruleset_fingerprint_of(&sources, &[]);
+        let result = run_scan_with_options(&project, None, None, &ScanOptions::default());
+        let cache_dir = home.join("cache");
+        save_to_cache_in(&cache_dir, &project, &result, &before).unwrap();
+        assert!(load_cached_in(&cache_dir, &project, &before).is_some());
+
+        fs::write(
+            &signatures,
+            r#"{"signatures":[{"id":"sig-new","pattern":"eval"}]}"#,
+        )
+        .unwrap();
+        let after = ruleset_fingerprint_of(&sources, &[]);
+        assert_ne!(before, after);
+        assert!(load_cached_in(&cache_dir, &project, &after).is_none());
+
+        let disabled = ruleset_fingerprint_of(&sources, &["sig-new".to_string()]);
+        assert_ne!(after, disabled);
+
+        let sidecar = cache_dir.join("files.json");
+        let cache = FileCache::load_with_ruleset(&sidecar, before.clone());
+        cache.insert("index.js", "abc", &[]);
+        cache.save().unwrap();
+        let stale = FileCache::load_with_ruleset(&sidecar, after);
+        assert!(stale.get("index.js", "abc").is_none());
+        let fresh = FileCache::load_with_ruleset(&sidecar, before);
+        assert!(fresh.get("index.js", "abc").is_some());
+    }
+
+    #[test]
+    fn interrupted_scan_resumes_from_its_checkpoint() {
+        let dir = tempdir().expect("tempdir");
+        let file = dir.path().join("scan.checkpoint");
+        let target = dir.path().join("pkg");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("a.js"), "eval(x)\n").unwrap();
+        fs::write(target.join(".b.js"), "new Function(payload)()\n").unwrap();
+        let rules = |result: &crate::scanner::ScanResult| -> Vec<(String, String)> {
+            let mut rules: Vec<(String, String)> = result
+                .findings
+                .iter()
+                .map(|f| (f.file.clone(), f.rule.clone()))
+                .collect();
+            rules.sort();
+            rules
+        };
+        let complete = run_scan_with_options(&target, None, None, &ScanOptions::default());
+
+        // The first run gets through `a.js` only (`.b.js` is hidden from
+        // it) and is never finished, as if killed before the end.
+        let first = Arc::new(Checkpoint::open(&file, None));
+        let options = ScanOptions {
+            checkpoint: Some(Arc::clone(&first)),
+            skip_hidden: true,
+            ..ScanOptions::default()
+        };
+        run_scan_with_options(&target, None, None, &options);
+
+        let resumed = Arc::new(Checkpoint::open(&file, None));
+        assert_eq!(resumed.recorded(), 1);
+        let options = ScanOptions {
+            checkpoint: Some(Arc::clone(&resumed)),
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(&target, None, None, &options);
+        assert_eq!(resumed.resumed(), 1);
+        assert_eq!(resumed.recorded(), 2);
+        assert_eq!(rules(&result), rules(&complete));
+        resumed.finish();
+        assert!(!file.exists());
+
+        // A checkpoint from other rules or phases starts over.
+        run_scan_with_options(&target, None, None, &options);
+        let other = Checkpoint::open_with(&file, "other".to_string(), Duration::ZERO);
+        assert_eq!(other.recorded(), 0);
+        let phases = ["code_patterns".to_string()];
+        assert_eq!(Checkpoint::open(&file, Some(&phases)).recorded(), 0);
+    }
 }
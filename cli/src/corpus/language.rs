@@ -0,0 +1,118 @@
+//! Source-language detection for language-scoped pack rules.
+//!
+//! A rule that lists `languages` only runs on files detected as one of them,
+//! so Python's `exec(` pattern is not applied to Go or Rust.  Detection uses
+//! the file extension first and falls back to the `#!` line.  Files whose
+//! language cannot be told (text, Markdown, data files, no extension and no
+//! shebang) still get every rule: a payload hidden in a `.txt` must not slip
+//! past because its language is unknown.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    Python,
+    /// JavaScript and TypeScript, including markup that embeds scripts.
+    Javascript,
+    Ruby,
+    Php,
+    Shell,
+    Go,
+    Rust,
+    Java,
+    /// C and C++.
+    C,
+}
+
+fn from_extension(ext: &str) -> Option<Language> {
+    Some(match ext {
+        "py" | "pyw" | "pyi" | "ipynb" => Language::Python,
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "vue" | "svelte" | "html"
+        | "htm" => Language::Javascript,
+        "rb" | "gemspec" | "rake" => Language::Ruby,
+        "php" | "phtml" => Language::Php,
+        "sh" | "bash" | "zsh" | "ksh" => Language::Shell,
+        "go" => Language::Go,
+        "rs" => Language::Rust,
+        "java" | "kt" | "kts" | "scala" => Language::Java,
+        "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" => Language::C,
+        _ => return None,
+    })
+}
+
+fn from_shebang(contents: &str) -> Option<Language> {
+    let line = contents.lines().next()?.strip_prefix("#!")?;
+    // `#!/usr/bin/env -S python3 -u` and `#!/bin/bash` both name the
+    // interpreter in the first word after any `env` and its flags.
+    let interpreter = line
+        .split_whitespace()
+        .map(|word| word.rsplit('/').next().unwrap_or(word))
+        .find(|word| *word != "env" && !word.starts_with('-'))?;
+    let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    Some(match name {
+        "python" | "pypy" => Language::Python,
+        "node" | "nodejs" | "deno" | "bun" | "ts-node" => Language::Javascript,
+        "ruby" => Language::Ruby,
+        "php" => Language::Php,
+        "sh" | "bash" | "zsh" | "dash" | "ksh" => Language::Shell,
+        _ => return None,
+    })
+}
+
+/// The language of `filename` (a basename) with the given contents, or
+/// `None` when it cannot be told.
+pub fn detect(filename: &str, contents: &str) -> Option<Language> {
+    let ext = filename
+        .rsplit_once('.')
+        .map(|(_, e)| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    from_extension(&ext).or_else(|| from_shebang(contents))
+}
+
+/// Whether a rule scoped to `languages` runs on a file of `detected`
+/// language.  An empty list is a universal rule.
+pub fn applies(languages: &[Language], detected: Option<Language>) -> bool {
+    match detected {
+        Some(lang) => languages.is_empty() || languages.contains(&lang),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_by_extension_then_shebang() {
+        assert_eq!(detect("setup.py", ""), Some(Language::Python));
+        assert_eq!(detect("index.MJS", ""), Some(Language::Javascript));
+        assert_eq!(
+            detect("main.go", "#!/usr/bin/env python3"),
+            Some(Language::Go)
+        );
+        assert_eq!(
+            detect("install", "#!/usr/bin/env python3\nimport os\n"),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            detect("run", "#!/usr/bin/env -S node --no-warnings\n"),
+            Some(Language::Javascript)
+        );
+        assert_eq!(
+            detect("postinstall", "#!/bin/bash\n"),
+            Some(Language::Shell)
+        );
+        assert_eq!(detect("README.md", "eval(x)"), None);
+        assert_eq!(detect("payload", "eval(x)"), None);
+    }
+
+    #[test]
+    fn unknown_languages_get_every_rule() {
+        let python = [Language::Python];
+        assert!(applies(&python, Some(Language::Python)));
+        assert!(!applies(&python, Some(Language::Go)));
+        assert!(applies(&python, None));
+        assert!(applies(&[], Some(Language::Rust)));
+    }
+}
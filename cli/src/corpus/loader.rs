@@ -6,6 +6,7 @@ use std::path::{Path, PathBuf};
 
 use super::schema::SignaturePack;
 use super::signing::PackVerifier;
+use crate::logging::{log_error, log_warn};
 
 /// All packs bundled with the binary (embedded as static bytes at compile time).
 ///
@@ -24,6 +25,7 @@ const EMBEDDED_PACKS: &[&str] = &[
     include_str!("../../../packs/core/v1/skill_security.json"),
     include_str!("../../../packs/core/v1/inference_security.json"),
     include_str!("../../../packs/core/v1/supply_chain.json"),
+    include_str!("../../../packs/core/v1/windows_payloads.json"),
     // Reverse/bind-shell corpus generated from the MIT-licensed
     // reverse-shell-generator. Regenerate via tools/corpus-gen/.
     //
@@ -57,8 +59,8 @@ pub fn verify_pack_if_keyed(raw: &str) -> Result<(), String> {
             // it cannot be verified — this is the "alertable" path for unsigned
             // environments that receive signed packs.
             if pack_has_signature(raw) {
-                eprintln!(
-                    "[corpus] WARNING: pack carries a meta.signature but \
+                log_warn!(
+                    "corpus: pack carries a meta.signature but \
                      SIGIL_PACK_PUBLIC_KEY is not set — signature not verified"
                 );
             }
@@ -120,7 +122,7 @@ pub fn load_all_packs() -> Result<Vec<SignaturePack>, String> {
             Err(e) => {
                 // A parse failure for a compile-time-embedded pack is a
                 // programming or binary-corruption error — surface it loudly.
-                eprintln!("[corpus] [SECURITY] failed to parse embedded pack: {e}");
+                log_error!("corpus: [SECURITY] failed to parse embedded pack: {e}");
             }
         }
     }
@@ -156,11 +158,11 @@ pub fn load_packs_from_dir(dir: &Path) -> Result<Vec<SignaturePack>, String> {
         match load_pack_from_file(&path) {
             Ok(pack) => packs.push(pack),
             Err(e) if e.contains("[SECURITY]") => {
-                eprintln!("[corpus] {}: {e}", path.display());
+                log_error!("corpus: {}: {e}", path.display());
                 return Err(e);
             }
             Err(e) => {
-                eprintln!("[corpus] skipping {}: {e}", path.display());
+                log_warn!("corpus: skipping {}: {e}", path.display());
             }
         }
     }
@@ -180,9 +182,9 @@ pub fn load_pack_from_file(path: &Path) -> Result<SignaturePack, String> {
     serde_json::from_str::<SignaturePack>(&raw).map_err(|e| format!("parse error: {e}"))
 }
 
-/// Returns `~/.sigil/packs/` when the home directory can be determined.
+/// Returns `packs/` under the Sigil home (`~/.sigil/packs/`).
 pub fn user_packs_dir() -> Option<PathBuf> {
-    dirs::home_dir().map(|h| h.join(".sigil").join("packs"))
+    Some(crate::paths::in_home("packs"))
 }
 
 #[cfg(test)]
@@ -9,8 +9,12 @@
 //! - [`schema`] — `SignaturePack`, `PackRule`, `FileFilter`, `SuppressionPredicates`
 //! - [`loader`] — discovers and parses packs from embedded data and `~/.sigil/packs/`
 //! - [`engine`] — runs pack rules against file content, returning `Finding`s
+//! - [`language`] — detects a file's source language for language-scoped rules
+//! - [`registry`] — indexes every rule ID for lookup (`sigil explain <ID>`)
 
 pub mod engine;
+pub mod language;
 pub mod loader;
+pub mod registry;
 pub mod schema;
 pub mod signing;
@@ -0,0 +1,601 @@
+//! Rule registry: one queryable index of every rule ID the scanner can emit.
+//!
+//! Pack rules come from the loaded signature packs; the handful of checks
+//! that cannot be expressed as a pack rule (shallow clones, archive limits,
+//! manifest parsing, rug-pull drift, threat intel, scan timeouts,
+//! dynamically built calls, Unicode cloaking, registry config files, git
+//! hooks, agent-directed prose, finding correlations) are described in
+//! [`BUILTIN_RULES`].  Scanning and `sigil explain` both read the packs
+//! through [`packs`], so the two can never disagree.
+
+use std::collections::HashMap;
+use std::process;
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use crate::scanner::{Phase, Severity, DEFAULT_CONFIDENCE};
+
+use super::engine::{parse_phase, parse_severity};
+use super::loader::load_all_packs;
+use super::schema::SignaturePack;
+use crate::logging::log_error;
+
+/// Everything `sigil explain <ID>` knows about a rule.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleInfo {
+    pub id: String,
+    pub phase: Phase,
+    pub severity: Severity,
+    /// How likely a match is a true positive, 0-100.
+    pub confidence: u8,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<String>,
+    pub remediation: String,
+}
+
+/// Rules implemented in Rust rather than packs:
+/// `(id, phase, severity, confidence, description, remediation)`.
+const BUILTIN_RULES: &[(&str, Phase, Severity, u8, &str, &str)] = &[
+    (
+        "AGENT-001",
+        Phase::PromptInjection,
+        Severity::High,
+        80,
+        "Instruction override aimed at an AI agent (disregard the system prompt or earlier instructions) in documentation or a docstring",
+        "Remove the sentence; documentation has no reason to address the agent reading it, let alone to countermand its instructions.",
+    ),
+    (
+        "AGENT-002",
+        Phase::PromptInjection,
+        Severity::High,
+        75,
+        "Instruction for an AI agent hidden in an HTML comment, invisible when the document is rendered",
+        "Read the raw file and delete the comment; text meant only for an agent to see is an injection attempt.",
+    ),
+    (
+        "AGENT-003",
+        Phase::PromptInjection,
+        Severity::High,
+        70,
+        "Request that an AI agent widen its tool permissions or act without the user's approval",
+        "Do not let an agent act on the package's docs; permissions are granted by the user, never requested by a dependency.",
+    ),
+    (
+        "AGENT-004",
+        Phase::PromptInjection,
+        Severity::Medium,
+        60,
+        "Request that an AI agent hide what it does from the user",
+        "Treat the surrounding text as hostile; honest instructions never ask to be kept from the person running the tool.",
+    ),
+    (
+        "AGENT-005",
+        Phase::PromptInjection,
+        Severity::Medium,
+        55,
+        "Directive addressed to AI agents ('if you are an AI ...', 'note to assistants') in documentation or a docstring",
+        "Review what the note asks an agent to do; instructions for humans do not need to single out AI readers.",
+    ),
+    (
+        "CODE-DYN-001",
+        Phase::CodePatterns,
+        Severity::High,
+        85,
+        "Dangerous call name assembled from string pieces ('ev' + 'al', getattr(os, 'sys' + 'tem'))",
+        "Resolve the built name and review the call; splitting `eval` or `system` across literals exists only to dodge scanners.",
+    ),
+    (
+        "CORR-001",
+        Phase::NetworkExfil,
+        Severity::Critical,
+        70,
+        "Decoded data sent over the network (decode-then-send) — base64 decoding within 10 lines of a webhook, exfiltration endpoint, HTTP upload, or encoded data",
+        "Trace what is decoded and where it is sent; a package that decodes a payload and ships it off is exfiltrating data.",
+    ),
+    (
+        "INSTALL-009",
+        Phase::InstallHooks,
+        Severity::Medium,
+        90,
+        "Package-manager config file (.npmrc, .yarnrc, .pypirc, pip.conf) shipped in package sources",
+        "Check why the package carries installer configuration; it can change where dependencies are fetched from.",
+    ),
+    (
+        "INSTALL-010",
+        Phase::InstallHooks,
+        Severity::High,
+        80,
+        "Registry or package index override pointing away from the public registry",
+        "Confirm the registry is one you trust; an override lets its operator serve any code under a familiar package name.",
+    ),
+    (
+        "INSTALL-011",
+        Phase::InstallHooks,
+        Severity::High,
+        90,
+        "Registry credential (auth token or password) written into a config file",
+        "Revoke the credential and read it from the environment instead (`_authToken=${NPM_TOKEN}`).",
+    ),
+    (
+        "INSTALL-012",
+        Phase::InstallHooks,
+        Severity::High,
+        95,
+        "Executable git hook (.git/hooks or core.hooksPath) shipped with the repository, or a hooksPath outside it",
+        "Read the hook before running any git command in the checkout; delete it unless you know why the repository needs it.",
+    ),
+    (
+        "INSTALL-013",
+        Phase::InstallHooks,
+        Severity::Critical,
+        90,
+        "Git hook that reaches the network or runs code it builds at run time (curl | sh, /dev/tcp, eval)",
+        "Do not commit, check out, or push in this repository until the hook is removed; it runs on your machine with your credentials.",
+    ),
+    (
+        "INSTALL-014",
+        Phase::InstallHooks,
+        Severity::Critical,
+        90,
+        "CI pipeline step pipes a download into a shell (curl | bash in a workflow, .gitlab-ci.yml, or azure-pipelines.yml)",
+        "Pin the script in the repository or verify its checksum before running it; a pipeline that runs whatever a URL serves hands CI secrets to that host.",
+    ),
+    (
+        "INSTALL-015",
+        Phase::InstallHooks,
+        Severity::Critical,
+        85,
+        "pull_request_target workflow checks out the pull request's code",
+        "Use the pull_request trigger for untrusted code, or check out only the base branch under pull_request_target; never build a fork's code with repository secrets.",
+    ),
+    (
+        "INSTALL-016",
+        Phase::InstallHooks,
+        Severity::High,
+        75,
+        "CI pipeline step prints a secret to the build log",
+        "Remove the echo and rotate the secret; build logs are often readable by anyone who can see the repository.",
+    ),
+    (
+        "INSTALL-017",
+        Phase::InstallHooks,
+        Severity::High,
+        95,
+        "Dockerfile ADD fetches a remote URL without --checksum",
+        "Download with a pinned checksum (`ADD --checksum=sha256:...`) or vendor the file; an unverified ADD bakes whatever the host serves into the image.",
+    ),
+    (
+        "INSTALL-018",
+        Phase::InstallHooks,
+        Severity::High,
+        90,
+        "Dockerfile RUN pipes a download into a shell (curl | sh)",
+        "Copy the script into the build context or verify its checksum before running it; the build runs whatever the URL serves.",
+    ),
+    (
+        "INSTALL-019",
+        Phase::InstallHooks,
+        Severity::Medium,
+        80,
+        "Dockerfile FROM an image with no tag, the latest tag, or an unfamiliar registry",
+        "Pin the base image by digest (`image:tag@sha256:...`) from a registry you trust, so a rebuild cannot silently pull different code.",
+    ),
+    (
+        "INSTALL-020",
+        Phase::InstallHooks,
+        Severity::Medium,
+        90,
+        "Dockerfile asks for a privileged container or build (--privileged, --security=insecure, --cap-add=ALL/SYS_ADMIN)",
+        "Drop the privilege unless the image truly needs it; a privileged container can take over its host.",
+    ),
+    (
+        "INSTALL-021",
+        Phase::InstallHooks,
+        Severity::High,
+        85,
+        "Browser extension asks for every site (<all_urls>, *://*/*) in permissions, host_permissions, or a content script",
+        "Narrow the host permissions to the sites the extension works on; access to every page lets it read and change all of them.",
+    ),
+    (
+        "INSTALL-022",
+        Phase::InstallHooks,
+        Severity::High,
+        85,
+        "MCP server given the filesystem root or home directory",
+        "Grant the server only the directory it works in; a root or home grant exposes every key and credential file.",
+    ),
+    (
+        "INSTALL-023",
+        Phase::InstallHooks,
+        Severity::High,
+        80,
+        "MCP server allowed to reach any network host",
+        "List the hosts the server needs; unrestricted network access lets it send what it reads anywhere.",
+    ),
+    (
+        "INSTALL-024",
+        Phase::InstallHooks,
+        Severity::High,
+        90,
+        "MCP server or tool declared allow_dangerous",
+        "Remove the flag and approve dangerous actions one at a time.",
+    ),
+    (
+        "NET-DYN-001",
+        Phase::NetworkExfil,
+        Severity::High,
+        80,
+        "URL assembled at runtime ('ht' + 'tp://', character-code lists) or a request to a base64-decoded URL",
+        "Decode the URL and review where the request goes; hiding an endpoint from the source only makes sense if it should not be seen.",
+    ),
+    (
+        "OBFUSC-BIDI-001",
+        Phase::Obfuscation,
+        Severity::High,
+        90,
+        "Bidirectional override in source code (Trojan Source) — the code runs in a different order than it reads",
+        "Open the file in an editor that shows control characters and remove them; source code never needs an override.",
+    ),
+    (
+        "OBFUSC-HOMOGLYPH-001",
+        Phase::Obfuscation,
+        Severity::Medium,
+        70,
+        "Identifier mixes Latin letters with Greek or Cyrillic look-alikes (homoglyph spoofing)",
+        "Compare the identifier with the one it imitates; two names that look identical can bind to different code.",
+    ),
+    (
+        "OBFUSC-MIN-001",
+        Phase::Obfuscation,
+        Severity::Medium,
+        45,
+        "Python or JavaScript source minified onto one long line of ;-joined statements (Low for JavaScript)",
+        "Get the readable source from the project's repository and review that; a package's own code has no reason to ship minified outside a *.min.* bundle.",
+    ),
+    (
+        "PROV-005",
+        Phase::Provenance,
+        Severity::Low,
+        100,
+        "Shallow clone — limited git history available",
+        "Fetch the full history (git fetch --unshallow) before reviewing provenance.",
+    ),
+    (
+        "PROV-006",
+        Phase::Provenance,
+        Severity::Medium,
+        100,
+        "No .git directory — provenance cannot be verified via git history",
+        "Obtain the package from its source repository, or verify it against the registry with `sigil verify`.",
+    ),
+    (
+        "PROV-007",
+        Phase::Provenance,
+        Severity::High,
+        90,
+        "Archive expansion limit exceeded — possible decompression bomb",
+        "Do not extract the archive by hand; obtain the package from the official registry and compare digests.",
+    ),
+    (
+        "PROV-008",
+        Phase::Provenance,
+        Severity::Critical,
+        100,
+        "Archive entry escapes the extraction directory (zip-slip)",
+        "Treat the archive as hostile: a legitimate package never writes outside its own directory.",
+    ),
+    (
+        "PROV-009",
+        Phase::Provenance,
+        Severity::High,
+        90,
+        "Dependency fetched from a raw IP address",
+        "Depend on a registry release or a named, trusted host instead of an IP address.",
+    ),
+    (
+        "PROV-010",
+        Phase::Provenance,
+        Severity::Medium,
+        75,
+        "Dependency fetched over plain HTTP, from a non-standard host, or from outside the package",
+        "Pin the dependency to a registry release served over HTTPS.",
+    ),
+    (
+        "PROV-011",
+        Phase::Provenance,
+        Severity::Medium,
+        70,
+        "Internal-scope dependency resolves from the public registry",
+        "Map the scope to your private registry in .npmrc so the public registry cannot serve it.",
+    ),
+    (
+        "PROV-012",
+        Phase::Provenance,
+        Severity::Medium,
+        80,
+        "Unpinned dependency in a package with install hooks",
+        "Pin the dependency to an exact version so install hooks cannot pull in new code.",
+    ),
+    (
+        "PROV-PARSE-001",
+        Phase::Provenance,
+        Severity::Medium,
+        100,
+        "File could not be read or parsed, so it was not analyzed (--strict)",
+        "Fix or remove the malformed file, or check its permissions, and scan again; anything Sigil cannot read it cannot vouch for.",
+    ),
+    (
+        "PROV-PYC-001",
+        Phase::Provenance,
+        Severity::High,
+        70,
+        "Python bytecode without its source, or holding strings its source does not (Medium when Python would not load it)",
+        "Delete the .pyc files and reinstall from source; compare the bytecode's strings with the .py before trusting either.",
+    ),
+    (
+        "PROV-SYMLINK-001",
+        Phase::Provenance,
+        Severity::High,
+        90,
+        "Symbolic link that points outside the scanned tree (High), dangles, or uses an absolute path (Medium)",
+        "Check what the link targets; a package should not ship links to host files, so remove it or replace it with the file itself.",
+    ),
+    (
+        "PROV-TIMEOUT-001",
+        Phase::Provenance,
+        Severity::Medium,
+        100,
+        "File content scan exceeded the per-file time budget, so the file was not fully scanned",
+        "Review the file by hand, or scan it alone with a larger --timeout-per-file; a file built to stall scanners is itself a warning sign.",
+    ),
+    (
+        "PROV-UNREADABLE-001",
+        Phase::Provenance,
+        Severity::Low,
+        100,
+        "File exists but could not be read (permission denied or I/O error), so it was not scanned",
+        "Fix the file's permissions, or scan as a user that can read it; a file Sigil cannot read is a gap in the scan's coverage.",
+    ),
+    (
+        "RUGPULL-001",
+        Phase::Provenance,
+        Severity::Critical,
+        100,
+        "Previously approved package changed since it was recorded in the ledger",
+        "Diff the new version against the approved one before trusting it again.",
+    ),
+    (
+        "THREAT-001",
+        Phase::Provenance,
+        Severity::Critical,
+        100,
+        "File hash matches a known threat in Sigil threat intelligence",
+        "Do not install. Remove the package and report where it came from.",
+    ),
+    (
+        "UNICODE-001",
+        Phase::Obfuscation,
+        Severity::High,
+        85,
+        "Private Use Area characters — invisible payload channel",
+        "Strip the invisible characters and review what the text decodes to; there is no legitimate reason to ship them in code or prompts.",
+    ),
+    (
+        "UNICODE-002",
+        Phase::Obfuscation,
+        Severity::Medium,
+        80,
+        "Bidirectional control characters — Trojan Source text reordering",
+        "Review the file in an editor that shows control characters, and remove them from code.",
+    ),
+    (
+        "UNICODE-003",
+        Phase::Obfuscation,
+        Severity::High,
+        75,
+        "Zero-width characters embedded in text — invisible instruction cloaking",
+        "Remove the zero-width characters and re-read the text as a model would see it.",
+    ),
+];
+
+/// Advice for pack rules that do not carry their own `remediation`.
+fn phase_remediation(phase: Phase) -> &'static str {
+    match phase {
+        Phase::InstallHooks => {
+            "Read the hook before installing; install with scripts disabled until it has been reviewed."
+        }
+        Phase::CodePatterns => {
+            "Check that the call only ever sees trusted, fixed input; otherwise replace it with a safe equivalent."
+        }
+        Phase::NetworkExfil => {
+            "Confirm the destination is expected for this package; unexplained outbound traffic is a reason not to install."
+        }
+        Phase::Credentials => {
+            "Make sure the package only reads the credentials it needs; rotate any secret that may have been exposed."
+        }
+        Phase::Obfuscation => {
+            "Decode the payload and review what it does; legitimate packages rarely hide their code."
+        }
+        Phase::Provenance => "Verify where the package came from before trusting it.",
+        Phase::PromptInjection => {
+            "Remove or neutralize the instruction text before the content reaches a model."
+        }
+        Phase::SkillSecurity => {
+            "Narrow the skill's permissions to what it needs and review anything it runs."
+        }
+        Phase::InferenceSecurity => {
+            "Pin model sources and keep untrusted input away from model configuration."
+        }
+    }
+}
+
+/// All loaded signature packs, parsed once per process.  A fatal loader
+/// error (a user pack failing signature verification) exits with code 2.
+pub fn packs() -> &'static [SignaturePack] {
+    static PACKS: OnceLock<Vec<SignaturePack>> = OnceLock::new();
+    PACKS.get_or_init(|| {
+        load_all_packs().unwrap_or_else(|e| {
+            log_error!("corpus: fatal: {e}");
+            process::exit(2);
+        })
+    })
+}
+
+fn build_index(packs: &[SignaturePack]) -> HashMap<String, RuleInfo> {
+    let mut index = HashMap::new();
+    for &(id, phase, severity, confidence, description, remediation) in BUILTIN_RULES {
+        index.insert(
+            id.to_string(),
+            RuleInfo {
+                id: id.to_string(),
+                phase,
+                severity,
+                confidence,
+                description: description.to_string(),
+                example: None,
+                remediation: remediation.to_string(),
+            },
+        );
+    }
+    for pack in packs {
+        for rule in &pack.rules {
+            let Some(phase) = parse_phase(&rule.phase) else {
+                continue;
+            };
+            index.insert(
+                rule.id.to_uppercase(),
+                RuleInfo {
+                    id: rule.id.clone(),
+                    phase,
+                    severity: parse_severity(&rule.severity),
+                    confidence: rule_confidence(rule.confidence),
+                    description: rule.description.clone(),
+                    example: rule.example.clone(),
+                    remediation: rule
+                        .remediation
+                        .clone()
+                        .unwrap_or_else(|| phase_remediation(phase).to_string()),
+                },
+            );
+        }
+        for rule in &pack.provenance_rules {
+            index.insert(
+                rule.id.to_uppercase(),
+                RuleInfo {
+                    id: rule.id.clone(),
+                    phase: Phase::Provenance,
+                    severity: parse_severity(&rule.severity),
+                    confidence: rule_confidence(rule.confidence),
+                    description: rule.description.clone(),
+                    example: None,
+                    remediation: rule
+                        .remediation
+                        .clone()
+                        .unwrap_or_else(|| phase_remediation(Phase::Provenance).to_string()),
+                },
+            );
+        }
+    }
+    index
+}
+
+fn index() -> &'static HashMap<String, RuleInfo> {
+    static INDEX: OnceLock<HashMap<String, RuleInfo>> = OnceLock::new();
+    INDEX.get_or_init(|| build_index(packs()))
+}
+
+/// Version of the loaded ruleset: the newest pack `updated_at` date.
+/// ISO dates order as strings, so baselines can be compared by it.
+pub fn ruleset_version() -> String {
+    packs()
+        .iter()
+        .map(|p| p.meta.updated_at.as_str())
+        .max()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Look up a rule by ID, case-insensitively.
+pub fn lookup(id: &str) -> Option<&'static RuleInfo> {
+    index().get(&id.trim().to_uppercase())
+}
+
+/// A pack rule's stated confidence, capped at 100, or the default.
+pub fn rule_confidence(stated: Option<u8>) -> u8 {
+    stated.map_or(DEFAULT_CONFIDENCE, |c| c.min(100))
+}
+
+/// Confidence of findings raised by rule `id`; the default for rules the
+/// registry does not know (user rules, advisories).
+pub fn confidence(id: &str) -> u8 {
+    lookup(id).map_or(DEFAULT_CONFIDENCE, |info| info.confidence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::RegexBuilder;
+
+    #[test]
+    fn pack_and_builtin_rules_are_indexed() {
+        let code = lookup("code-004").expect("CODE-004 is a pack rule");
+        assert_eq!(code.id, "CODE-004");
+        assert_eq!(code.phase, Phase::CodePatterns);
+        assert_eq!(code.severity, Severity::High);
+        assert!(code.example.is_some());
+
+        let threat = lookup("THREAT-001").expect("THREAT-001 is built in");
+        assert_eq!(threat.severity, Severity::Critical);
+        assert!(lookup("NOPE-999").is_none());
+    }
+
+    #[test]
+    fn every_rule_states_its_confidence() {
+        for pack in load_all_packs().unwrap() {
+            for (id, confidence) in pack
+                .rules
+                .iter()
+                .map(|r| (&r.id, r.confidence))
+                .chain(pack.provenance_rules.iter().map(|r| (&r.id, r.confidence)))
+            {
+                let confidence = confidence.unwrap_or_else(|| panic!("{id} has no confidence"));
+                assert!(confidence <= 100, "{id}: {confidence}");
+            }
+        }
+        // Specific indicators rank above keyword matches.
+        assert!(confidence("NET-007") > confidence("NET-006"));
+        assert!(confidence("CRED-004") > confidence("CRED-008"));
+        assert_eq!(confidence("THREAT-001"), 100);
+        assert_eq!(confidence("ACME-001"), DEFAULT_CONFIDENCE);
+        assert_eq!(rule_confidence(Some(250)), 100);
+    }
+
+    #[test]
+    fn rules_without_remediation_get_phase_advice() {
+        let cred = lookup("CRED-001").expect("CRED-001 exists");
+        assert_eq!(cred.remediation, phase_remediation(Phase::Credentials));
+    }
+
+    #[test]
+    fn examples_match_their_own_rule() {
+        for pack in load_all_packs().unwrap() {
+            for rule in &pack.rules {
+                let Some(example) = &rule.example else {
+                    continue;
+                };
+                let re = RegexBuilder::new(&rule.pattern)
+                    .dot_matches_new_line(rule.multiline)
+                    .build()
+                    .unwrap();
+                assert!(
+                    re.is_match(example),
+                    "{}: example {:?} does not match its pattern",
+                    rule.id,
+                    example
+                );
+            }
+        }
+    }
+}
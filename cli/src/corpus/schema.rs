@@ -5,6 +5,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::language::Language;
+
 // ---------------------------------------------------------------------------
 // File filter predicates — declarative, no exec code
 // ---------------------------------------------------------------------------
@@ -17,7 +19,8 @@ pub struct FileFilter {
     #[serde(default)]
     pub filename_exact: Vec<String>,
 
-    /// File extensions that must match, without leading dot (e.g. `["py", "js"]`).
+    /// File extensions that must match, without leading dot (e.g. `["py", "js"]`);
+    /// compared case-insensitively.
     #[serde(default)]
     pub extensions: Vec<String>,
 
@@ -44,8 +47,10 @@ impl FileFilter {
             return true;
         }
 
+        // Case-insensitive: `INSTALL.BAT` is as much a batch file as
+        // `install.bat`.
         let ext = filename.rsplit_once('.').map(|(_, e)| e).unwrap_or("");
-        if !ext.is_empty() && self.extensions.iter().any(|e| e == ext) {
+        if !ext.is_empty() && self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)) {
             return true;
         }
 
@@ -189,10 +194,40 @@ pub struct PackRule {
     #[serde(default)]
     pub weight: Option<u32>,
 
+    /// How likely a match is a true positive, 0-100: high for exact
+    /// indicators (a token format, a known payload), low for keyword
+    /// patterns that also match harmless code.  Defaults to
+    /// [`crate::scanner::DEFAULT_CONFIDENCE`] when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<u8>,
+
+    /// Match against the whole file instead of line by line, with `.`
+    /// matching newlines, so a construct split across lines (a socket
+    /// `connect` followed by `dup2`) is caught.  The finding is reported at
+    /// the line where the match starts.  Keep gaps bounded (`.{0,400}?`).
+    #[serde(default)]
+    pub multiline: bool,
+
+    /// A short line the rule matches, shown by `sigil explain <ID>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<String>,
+
+    /// What to do about a match, shown by `sigil explain <ID>`.  Falls back
+    /// to the phase-level advice when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+
     /// Optional file filter.  When absent, rule applies to all files.
     #[serde(default)]
     pub file_filter: FileFilter,
 
+    /// Source languages the pattern is written for (`"python"`,
+    /// `"javascript"`, ...).  Files detected as another language are
+    /// skipped; files of unknown language are still scanned.  Empty means
+    /// the rule is language-agnostic.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<Language>,
+
     /// Optional suppression predicates.
     #[serde(default)]
     pub suppress: SuppressionPredicates,
@@ -224,6 +259,11 @@ pub struct ProvenanceRule {
     pub description: String,
     pub kind: ProvenanceKind,
 
+    /// How likely a match is a true positive, 0-100; see
+    /// [`PackRule::confidence`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<u8>,
+
     /// Regex string (required for `FilenameRegex` kind).
     #[serde(default)]
     pub pattern: Option<String>,
@@ -241,6 +281,10 @@ pub struct ProvenanceRule {
     /// safe dotfiles like `.gitignore`).
     #[serde(default)]
     pub excluded_filenames: Vec<String>,
+
+    /// What to do about a match, shown by `sigil explain <ID>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -135,21 +135,26 @@ impl PackVerifier {
         // 4. Re-serialise to canonical bytes (compact JSON).
         let canonical = serde_json::to_string(&doc).map_err(VerifyError::CanonicaliseError)?;
 
-        // 5. Decode the Base64 signature.
+        // 5. Verify the Base64 signature over the canonical bytes.
+        self.verify_detached(canonical.as_bytes(), &sig_b64)
+    }
+
+    /// Verify a Base64 Ed25519 signature over `message`.  Used directly for
+    /// documents that do not carry their signature in `meta` (the fetched
+    /// signature bundle).
+    pub fn verify_detached(&self, message: &[u8], sig_b64: &str) -> Result<(), VerifyError> {
         let sig_bytes = BASE64
             .decode(sig_b64.as_bytes())
             .map_err(VerifyError::InvalidBase64)?;
 
-        // 6. Parse into an Ed25519 Signature.
         let sig_array: &[u8; 64] = sig_bytes.as_slice().try_into().map_err(|_| {
             VerifyError::InvalidSignatureBytes(ed25519_dalek::SignatureError::new())
         })?;
         let signature = Signature::from_bytes(sig_array);
 
-        // 7. Verify.
         use ed25519_dalek::Verifier as _;
         self.key
-            .verify(canonical.as_bytes(), &signature)
+            .verify(message, &signature)
             .map_err(VerifyError::SignatureMismatch)
     }
 }
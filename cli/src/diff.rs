@@ -1,19 +1,133 @@
 //! Scan result diffing — compare two scan results to identify new and resolved findings.
+//!
+//! A finding whose flagged line was only tweaked (same rule and file, the
+//! snippet a few edits away) is reported as modified, with the snippet
+//! before and after, rather than as one resolved and one new finding.  One
+//! whose snippet is untouched and only its line moved is unchanged.
 
-use crate::scanner::{Finding, ScanResult, Verdict};
+use crate::scanner::{scoring, Finding, ScanResult, Verdict};
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ScanDiff {
     pub new_findings: Vec<Finding>,
     pub resolved_findings: Vec<Finding>,
     pub unchanged_findings: Vec<Finding>,
+    /// Findings whose line was edited between the scans.
+    #[serde(default)]
+    pub modified_findings: Vec<ModifiedFinding>,
     pub score_delta: i64,
     pub previous_verdict: Verdict,
     pub current_verdict: Verdict,
     pub summary: String,
 }
 
+/// One finding in both scans, on an edited line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifiedFinding {
+    pub before: Finding,
+    pub after: Finding,
+}
+
+/// Snippet edits always tolerated as a modification; longer snippets allow
+/// up to a tenth of their length.
+const MODIFIED_MIN_EDITS: usize = 3;
+
+/// A finding's path in a form comparable across scans: forward slashes and
+/// no leading `./`, so the same relative file matches whichever root or
+/// platform produced it.
+pub fn normalize_path(file: &str) -> String {
+    let file = file.replace('\\', "/");
+    let mut file = file.as_str();
+    while let Some(rest) = file.strip_prefix("./") {
+        file = rest;
+    }
+    file.to_string()
+}
+
+/// Two findings are the same if rule, normalized file, and line agree.
+fn same_finding(a: &Finding, b: &Finding) -> bool {
+    a.rule == b.rule && a.line == b.line && normalize_path(&a.file) == normalize_path(&b.file)
+}
+
+/// Levenshtein distance between `a` and `b` in characters, or `None` once
+/// it is certain to exceed `max`.
+fn edit_distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev + usize::from(ca != cb);
+            prev = row[j + 1];
+            row[j + 1] = substitute.min(prev + 1).min(row[j] + 1);
+        }
+        if row.iter().min().is_some_and(|&least| least > max) {
+            return None;
+        }
+    }
+    row.last().copied().filter(|&distance| distance <= max)
+}
+
+/// How far apart `before` and `after` may be and still be one edited
+/// finding: same rule and file, snippets within a few edits.
+fn modification_distance(before: &Finding, after: &Finding) -> Option<usize> {
+    if before.rule != after.rule || normalize_path(&before.file) != normalize_path(&after.file) {
+        return None;
+    }
+    let longest = before
+        .snippet
+        .chars()
+        .count()
+        .max(after.snippet.chars().count());
+    edit_distance(
+        &before.snippet,
+        &after.snippet,
+        MODIFIED_MIN_EDITS.max(longest / 10),
+    )
+}
+
+/// Pair each new finding with the closest resolved one it is an edit of,
+/// taking both out of their lists.  A pair with an identical snippet is the
+/// same line moved, not edited: the new finding goes to `unchanged`.
+fn pair_modified(
+    new: &mut Vec<Finding>,
+    resolved: &mut Vec<Finding>,
+    unchanged: &mut Vec<Finding>,
+) -> Vec<ModifiedFinding> {
+    let mut modified = Vec::new();
+    let mut still_new = Vec::new();
+    for after in new.drain(..) {
+        let line_gap = |f: &Finding| after.line.unwrap_or(0).abs_diff(f.line.unwrap_or(0));
+        let closest = resolved
+            .iter()
+            .enumerate()
+            .filter_map(|(i, before)| {
+                modification_distance(before, &after).map(|d| (d, line_gap(before), i))
+            })
+            .min();
+        match closest {
+            Some((0, _, i)) => {
+                resolved.remove(i);
+                unchanged.push(after);
+            }
+            Some((_, _, i)) => modified.push(ModifiedFinding {
+                before: resolved.remove(i),
+                after,
+            }),
+            None => still_new.push(after),
+        }
+    }
+    *new = still_new;
+    modified
+}
+
 /// Compare two scan results and produce a diff.
 pub fn diff_scans(previous: &ScanResult, current: &ScanResult) -> ScanDiff {
     let mut new_findings = Vec::new();
@@ -22,10 +136,7 @@ pub fn diff_scans(previous: &ScanResult, current: &ScanResult) -> ScanDiff {
 
     // Match findings by (rule, file, line) tuple
     for finding in &current.findings {
-        let exists_in_previous = previous
-            .findings
-            .iter()
-            .any(|f| f.rule == finding.rule && f.file == finding.file && f.line == finding.line);
+        let exists_in_previous = previous.findings.iter().any(|f| same_finding(f, finding));
         if exists_in_previous {
             unchanged_findings.push(finding.clone());
         } else {
@@ -34,19 +145,23 @@ pub fn diff_scans(previous: &ScanResult, current: &ScanResult) -> ScanDiff {
     }
 
     for finding in &previous.findings {
-        let exists_in_current = current
-            .findings
-            .iter()
-            .any(|f| f.rule == finding.rule && f.file == finding.file && f.line == finding.line);
+        let exists_in_current = current.findings.iter().any(|f| same_finding(f, finding));
         if !exists_in_current {
             resolved_findings.push(finding.clone());
         }
     }
 
+    let modified_findings = pair_modified(
+        &mut new_findings,
+        &mut resolved_findings,
+        &mut unchanged_findings,
+    );
+
     let score_delta = current.score as i64 - previous.score as i64;
     let summary = format!(
-        "{} new, {} resolved, {} unchanged (score: {} → {}, {}{})",
+        "{} new, {} modified, {} resolved, {} unchanged (score: {} → {}, {}{})",
         new_findings.len(),
+        modified_findings.len(),
         resolved_findings.len(),
         unchanged_findings.len(),
         previous.score,
@@ -59,9 +174,354 @@ pub fn diff_scans(previous: &ScanResult, current: &ScanResult) -> ScanDiff {
         new_findings,
         resolved_findings,
         unchanged_findings,
+        modified_findings,
         score_delta,
         previous_verdict: previous.verdict,
         current_verdict: current.verdict,
         summary,
     }
 }
+
+/// A warning when `baseline` was produced with an older ruleset than
+/// `current`: rules added or changed since then show up as spurious new or
+/// resolved findings. Baselines without metadata predate it and are not
+/// flagged.
+pub fn ruleset_drift(baseline: &ScanResult, current: &ScanResult) -> Option<String> {
+    let (old, new) = (baseline.meta.as_ref()?, current.meta.as_ref()?);
+    (old.ruleset_version < new.ruleset_version).then(|| {
+        format!(
+            "baseline was scanned with ruleset {} (sigil {}), current ruleset is {}; \
+             rule changes since then may show up as new or resolved findings",
+            old.ruleset_version, old.tool_version, new.ruleset_version
+        )
+    })
+}
+
+/// The directory to scan for one side of a version comparison. A package
+/// unpacked into a single versioned top-level directory (`pkg-1.0/`,
+/// `pkg-1.1/`) is scanned from inside it, so the two versions' relative
+/// paths line up.
+pub fn package_root(dir: &Path) -> PathBuf {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return dir.to_path_buf();
+    };
+    let entries: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    match entries.as_slice() {
+        [only] if only.is_dir() => only.clone(),
+        _ => dir.to_path_buf(),
+    }
+}
+
+/// Read the findings of a baseline for `scan --baseline`. Accepts either a
+/// serialized `ScanResult` (the `diff --baseline` format) or saved
+/// `sigil scan -f json` output.
+pub fn load_baseline_findings(path: &Path) -> Result<Vec<Finding>, String> {
+    let data = std::fs::read_to_string(path)
+        .map_err(|e| format!("failed to read baseline '{}': {}", path.display(), e))?;
+
+    if let Ok(result) = serde_json::from_str::<ScanResult>(&data) {
+        return Ok(result.findings);
+    }
+
+    let (_, findings) = crate::output::parse_scan_report(&data).map_err(|e| {
+        format!(
+            "baseline '{}' is neither a scan result nor `sigil scan -f json` output: {}",
+            path.display(),
+            e
+        )
+    })?;
+    serde_json::from_value(serde_json::Value::Array(findings))
+        .map_err(|e| format!("failed to parse baseline '{}': {}", path.display(), e))
+}
+
+/// Overwrite the baseline at `path` with `result`, in the serialized
+/// `ScanResult` form both `scan --baseline` and `diff --baseline` read. The
+/// file is replaced by rename, so an interrupted write keeps the old one.
+pub fn save_baseline(path: &Path, result: &ScanResult) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(result)
+        .map_err(|e| format!("failed to serialize baseline: {}", e))?;
+    let staging = path.with_extension("json.tmp");
+    std::fs::write(&staging, json)
+        .and_then(|_| std::fs::rename(&staging, path))
+        .map_err(|e| format!("failed to write baseline '{}': {}", path.display(), e))
+}
+
+/// Ratchet `current` against a baseline: findings already present in the
+/// baseline are dropped, and score and verdict are recomputed from the new
+/// findings alone. Returns the number of known findings suppressed.
+pub fn apply_baseline(current: &mut ScanResult, baseline: &[Finding]) -> usize {
+    let previous = ScanResult {
+        findings: baseline.to_vec(),
+        ..current.clone()
+    };
+    let diff = diff_scans(&previous, current);
+    let known = diff.unchanged_findings.len();
+
+    current.findings = diff.new_findings;
+    scoring::rescore(current);
+    current.baseline_suppressed = known;
+    known
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Phase, Severity};
+
+    fn finding(rule: &str, file: &str, line: usize, severity: Severity) -> Finding {
+        Finding {
+            phase: Phase::CodePatterns,
+            rule: rule.to_string(),
+            severity,
+            file: file.to_string(),
+            line: Some(line),
+            snippet: String::new(),
+            weight: 5,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        }
+    }
+
+    fn result(findings: Vec<Finding>) -> ScanResult {
+        let score = scoring::calculate_score(&findings);
+        let verdict = scoring::determine_verdict(&findings, score);
+        ScanResult {
+            findings,
+            score,
+            verdict,
+            files_scanned: 2,
+            duration_ms: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn baseline_keeps_only_new_findings_and_rescores() {
+        let legacy = finding("CODE-001", "a.py", 3, Severity::Critical);
+        let fresh = finding("CODE-002", "b.py", 9, Severity::Medium);
+        let mut current = result(vec![legacy.clone(), fresh]);
+        assert_eq!(current.verdict, Verdict::CriticalRisk);
+
+        let known = apply_baseline(&mut current, &[legacy]);
+
+        assert_eq!(known, 1);
+        assert_eq!(current.baseline_suppressed, 1);
+        assert_eq!(current.findings.len(), 1);
+        assert_eq!(current.findings[0].rule, "CODE-002");
+        assert_eq!(current.score, scoring::calculate_score(&current.findings));
+        assert_ne!(current.verdict, Verdict::CriticalRisk);
+    }
+
+    #[test]
+    fn older_ruleset_baseline_warns() {
+        use crate::scanner::ScanMeta;
+        let meta = |ruleset: &str| ScanMeta {
+            tool_version: "1.0.0".to_string(),
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            ruleset_version: ruleset.to_string(),
+            signatures_version: None,
+            os: "linux-x86_64".to_string(),
+        };
+        let mut old = result(vec![]);
+        let mut new = result(vec![]);
+        assert!(
+            ruleset_drift(&old, &new).is_none(),
+            "no metadata, no warning"
+        );
+
+        old.meta = Some(meta("2026-01-10"));
+        new.meta = Some(meta("2026-06-15"));
+        let warning = ruleset_drift(&old, &new).expect("older ruleset warns");
+        assert!(warning.contains("2026-01-10") && warning.contains("2026-06-15"));
+        assert!(ruleset_drift(&new, &new).is_none());
+
+        // Metadata round-trips through the baseline file; old files still load.
+        let json = serde_json::to_string(&new).unwrap();
+        let loaded: ScanResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.meta, new.meta);
+        let legacy = json.replace(
+            &format!(",\"meta\":{}", serde_json::to_string(&new.meta).unwrap()),
+            "",
+        );
+        assert!(!legacy.contains("meta"));
+        assert!(serde_json::from_str::<ScanResult>(&legacy)
+            .unwrap()
+            .meta
+            .is_none());
+    }
+
+    #[test]
+    fn paths_match_across_roots_and_separators() {
+        let before = result(vec![finding("CODE-001", "./src\\a.py", 3, Severity::High)]);
+        let after = result(vec![
+            finding("CODE-001", "src/a.py", 3, Severity::High),
+            finding("CODE-002", "src/b.py", 1, Severity::High),
+        ]);
+
+        let diff = diff_scans(&before, &after);
+        assert_eq!(diff.unchanged_findings.len(), 1);
+        assert_eq!(diff.new_findings.len(), 1);
+        assert_eq!(diff.new_findings[0].rule, "CODE-002");
+        assert!(diff.resolved_findings.is_empty());
+    }
+
+    #[test]
+    fn one_character_edit_is_a_modification() {
+        let at = |line: usize, snippet: &str| {
+            let mut f = finding("CODE-001", "src/run.py", line, Severity::High);
+            f.snippet = snippet.to_string();
+            f
+        };
+        let before = result(vec![
+            at(4, "eval(user_input)"),
+            at(9, "exec(compile(src, 'x', 'exec'))"),
+        ]);
+        let after = result(vec![at(5, "eval(user_inputs)"), at(30, "os.system(cmd)")]);
+
+        let diff = diff_scans(&before, &after);
+        assert_eq!(diff.modified_findings.len(), 1);
+        let modified = &diff.modified_findings[0];
+        assert_eq!(modified.before.snippet, "eval(user_input)");
+        assert_eq!(modified.after.snippet, "eval(user_inputs)");
+        assert_eq!(
+            (modified.before.line, modified.after.line),
+            (Some(4), Some(5))
+        );
+        // Too different to be the same line edited.
+        assert_eq!(diff.new_findings.len(), 1);
+        assert_eq!(diff.new_findings[0].snippet, "os.system(cmd)");
+        assert_eq!(diff.resolved_findings.len(), 1);
+        assert!(diff.summary.starts_with("1 new, 1 modified, 1 resolved"));
+
+        // Another rule or file is never a modification.
+        let mut other = at(4, "eval(user_input)");
+        other.rule = "CODE-002".to_string();
+        let diff = diff_scans(&before, &result(vec![other]));
+        assert!(diff.modified_findings.is_empty());
+    }
+
+    #[test]
+    fn line_only_move_is_unchanged() {
+        let at = |line: usize| {
+            let mut f = finding("CODE-001", "src/run.py", line, Severity::High);
+            f.snippet = "eval(user_input)".to_string();
+            f
+        };
+        let diff = diff_scans(&result(vec![at(4)]), &result(vec![at(12)]));
+        assert!(diff.modified_findings.is_empty());
+        assert!(diff.new_findings.is_empty());
+        assert!(diff.resolved_findings.is_empty());
+        assert_eq!(diff.unchanged_findings.len(), 1);
+        assert_eq!(diff.unchanged_findings[0].line, Some(12));
+        assert!(diff
+            .summary
+            .starts_with("0 new, 0 modified, 0 resolved, 1 unchanged"));
+    }
+
+    #[test]
+    fn edit_distance_stops_past_the_limit() {
+        assert_eq!(edit_distance("eval(x)", "eval(x)", 0), Some(0));
+        assert_eq!(edit_distance("eval(x)", "eval(y)", 3), Some(1));
+        assert_eq!(edit_distance("kitten", "sitting", 3), Some(3));
+        assert_eq!(edit_distance("kitten", "sitting", 2), None);
+        assert_eq!(edit_distance("a", "abcdef", 3), None);
+    }
+
+    #[test]
+    fn windows_baseline_diffs_cleanly_against_unix_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/lib")).unwrap();
+        std::fs::write(
+            dir.path().join("src/lib/run.py"),
+            "import os\nos.system(cmd)\n",
+        )
+        .unwrap();
+
+        let current = crate::scanner::run_scan(dir.path(), None, None);
+        assert!(!current.findings.is_empty());
+        assert!(current.findings.iter().all(|f| !f.file.contains('\\')));
+
+        let mut baseline = current.clone();
+        for f in &mut baseline.findings {
+            f.file = f.file.replace('/', "\\");
+        }
+        let diff = diff_scans(&baseline, &current);
+        assert!(diff.new_findings.is_empty());
+        assert!(diff.resolved_findings.is_empty());
+        assert_eq!(diff.unchanged_findings.len(), current.findings.len());
+    }
+
+    #[test]
+    fn two_package_versions_diff_by_relative_path() {
+        let dir = tempfile::tempdir().unwrap();
+        for (version, body) in [
+            ("1.0", "print('hello')\n"),
+            ("1.1", "print('hello')\nimport os\nos.system(cmd)\n"),
+        ] {
+            let root = dir.path().join(version).join(format!("pkg-{version}"));
+            std::fs::create_dir_all(&root).unwrap();
+            std::fs::write(root.join("main.py"), body).unwrap();
+        }
+
+        let old_root = package_root(&dir.path().join("1.0"));
+        let new_root = package_root(&dir.path().join("1.1"));
+        assert!(old_root.ends_with("pkg-1.0"));
+
+        let diff = diff_scans(
+            &crate::scanner::run_scan(&old_root, None, None),
+            &crate::scanner::run_scan(&new_root, None, None),
+        );
+        assert!(!diff.new_findings.is_empty());
+        assert!(diff.new_findings.iter().all(|f| f.file == "main.py"));
+        assert!(diff.resolved_findings.is_empty());
+    }
+
+    #[test]
+    fn baseline_loads_from_scan_json_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        let findings = vec![finding("CODE-001", "a.py", 3, Severity::High)];
+        let output = format!(
+            "sigil: scanning pkg...\n{{\n  \"score\": 15\n}}\n{}\n{{\"verdict\":\"MEDIUM RISK\"}}\n",
+            serde_json::to_string_pretty(&findings).unwrap()
+        );
+        std::fs::write(&path, output).unwrap();
+
+        let loaded = load_baseline_findings(&path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].rule, "CODE-001");
+
+        let report = crate::output::scan_report_json(&result(findings.clone()), 5);
+        std::fs::write(&path, report.to_string()).unwrap();
+        assert_eq!(load_baseline_findings(&path).unwrap().len(), 1);
+
+        std::fs::write(
+            &path,
+            serde_json::to_string(&result(findings.clone())).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(load_baseline_findings(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn saved_baseline_round_trips_for_scan_and_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        std::fs::write(&path, "stale").unwrap();
+        let saved = result(vec![
+            finding("CODE-001", "a.py", 3, Severity::High),
+            finding("NET-002", "b.py", 7, Severity::Medium),
+        ]);
+
+        save_baseline(&path, &saved).unwrap();
+
+        assert_eq!(load_baseline_findings(&path).unwrap().len(), 2);
+        let reread: ScanResult =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(diff_scans(&reread, &saved).new_findings.is_empty());
+        assert!(!dir.path().join("baseline.json.tmp").exists());
+    }
+}
@@ -0,0 +1,499 @@
+//! Remote archive download for `sigil scan-url`.
+//!
+//! Fetches a release artifact straight into a quarantine directory without
+//! going through pip/npm/git. The body is streamed to disk with a hard byte
+//! cap so a hostile server cannot fill the disk, and only archive content
+//! types the extractor understands are accepted.
+//!
+//! A transfer that breaks off midway is resumed with an HTTP `Range`
+//! request for the bytes still missing, appending to the partial file,
+//! rather than started over.  A server that ignores the range, or whose
+//! file changed in between (`If-Range`), sends the whole body again and the
+//! download restarts from zero.  The finished file must be exactly the
+//! advertised `Content-Length`.
+
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use reqwest::header::{HeaderValue, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::StatusCode;
+
+use crate::logging::log_warn;
+
+/// How many times an interrupted download is resumed before giving up.
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// Archive suffixes `extract_archives` knows how to unpack.
+const ARCHIVE_SUFFIXES: &[&str] = &[".tar.gz", ".tgz", ".zip", ".whl"];
+
+/// Content types a server may legitimately label an archive with. Generic
+/// `application/octet-stream` is common for release assets, so it is allowed
+/// and the URL suffix decides the format.
+const ARCHIVE_CONTENT_TYPES: &[&str] = &[
+    "application/gzip",
+    "application/x-gzip",
+    "application/x-tar",
+    "application/x-gtar",
+    "application/x-compressed-tar",
+    "application/zip",
+    "application/x-zip-compressed",
+    "application/octet-stream",
+    "binary/octet-stream",
+];
+
+/// Pick the on-disk filename for a downloaded archive: the URL's last path
+/// segment when it carries a known archive suffix, otherwise a name derived
+/// from the content type. `None` means the format cannot be determined.
+pub fn archive_filename(url: &str, content_type: Option<&str>) -> Option<String> {
+    let path = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(url)
+        .trim_end_matches('/');
+    let segment = path.rsplit('/').next().unwrap_or("");
+    let lower = segment.to_lowercase();
+    if !segment.contains("..") && ARCHIVE_SUFFIXES.iter().any(|s| lower.ends_with(s)) {
+        return Some(segment.to_string());
+    }
+
+    match content_type.map(mime_essence).as_deref() {
+        Some("application/gzip" | "application/x-gzip" | "application/x-compressed-tar") => {
+            Some("download.tar.gz".to_string())
+        }
+        Some("application/zip" | "application/x-zip-compressed") => {
+            Some("download.zip".to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Whether a response `Content-Type` is acceptable for an archive download.
+/// A missing header is tolerated (many static hosts omit it).
+pub fn is_archive_content_type(content_type: Option<&str>) -> bool {
+    match content_type {
+        None => true,
+        Some(ct) => ARCHIVE_CONTENT_TYPES.contains(&mime_essence(ct).as_str()),
+    }
+}
+
+/// `type/subtype` without parameters, lowercased.
+fn mime_essence(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase()
+}
+
+/// Why a transfer stopped short.
+enum Transfer {
+    /// The connection broke; the download can be resumed.
+    Interrupted(String),
+    /// Resuming cannot help (a limit was hit, the disk failed).
+    Fatal(String),
+}
+
+/// Wait before resume attempt `attempt` (1-based).
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_millis(200 * u64::from(attempt))
+}
+
+/// Start offset and total size from a `Content-Range: bytes S-E/T` header.
+/// The total is `None` when the server sends `*`.
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let start = range.split_once('-')?.0.trim().parse().ok()?;
+    let total = match total.trim() {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((start, total))
+}
+
+/// Append `response`'s body to `file`, counting into `written` and keeping
+/// the running total under `max_bytes`.
+async fn stream_body(
+    response: &mut reqwest::Response,
+    file: &mut std::fs::File,
+    written: &mut u64,
+    max_bytes: u64,
+) -> Result<(), Transfer> {
+    // Content-Length can lie or be absent; the cap is enforced on the
+    // bytes actually received.
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| Transfer::Interrupted(e.to_string()))?
+    {
+        *written += chunk.len() as u64;
+        if *written > max_bytes {
+            return Err(Transfer::Fatal(format!(
+                "archive exceeds the {} byte download limit",
+                max_bytes
+            )));
+        }
+        file.write_all(&chunk)
+            .map_err(|e| Transfer::Fatal(format!("failed to write archive: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// Ask for the bytes from `offset` on.  `Ok(true)` means the server sent
+/// the rest (206); `Ok(false)` that it sent the whole file again (200), so
+/// the caller starts over.
+async fn request_rest(
+    client: &reqwest::Client,
+    url: &str,
+    offset: u64,
+    validator: Option<&HeaderValue>,
+    total: Option<u64>,
+) -> Result<(reqwest::Response, bool), Transfer> {
+    let mut request = client.get(url).header(RANGE, format!("bytes={}-", offset));
+    if let Some(validator) = validator {
+        request = request.header(IF_RANGE, validator.clone());
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| Transfer::Interrupted(e.to_string()))?;
+    match response.status() {
+        StatusCode::PARTIAL_CONTENT => {
+            let range = response
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_content_range);
+            match range {
+                Some((start, size)) if start == offset && (size.is_none() || size == total) => {
+                    Ok((response, true))
+                }
+                _ => Err(Transfer::Fatal(format!(
+                    "cannot resume: server sent a different range ({})",
+                    response
+                        .headers()
+                        .get(CONTENT_RANGE)
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or("no Content-Range")
+                ))),
+            }
+        }
+        StatusCode::OK => Ok((response, false)),
+        status if status.is_server_error() => {
+            Err(Transfer::Interrupted(format!("server returned {}", status)))
+        }
+        status => Err(Transfer::Fatal(format!(
+            "cannot resume: server returned {}",
+            status
+        ))),
+    }
+}
+
+/// Download `url` into `dest_dir`, refusing bodies larger than `max_bytes`.
+/// An interrupted transfer is resumed up to `retries` times.  Returns the
+/// path of the written archive. A partially written file is removed on any
+/// error.
+pub async fn download_archive(
+    url: &str,
+    dest_dir: &Path,
+    max_bytes: u64,
+    retries: u32,
+) -> Result<PathBuf, String> {
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(format!(
+            "unsupported URL scheme (use http or https): {}",
+            url
+        ));
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(300))
+        .user_agent(format!("sigil-cli/{}", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("http client: {}", e))?;
+
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("download failed: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "download failed: server returned {}",
+            response.status()
+        ));
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    if !is_archive_content_type(content_type.as_deref()) {
+        return Err(format!(
+            "refusing non-archive content type '{}'",
+            content_type.unwrap_or_default()
+        ));
+    }
+
+    let total = response.content_length();
+    if let Some(len) = total {
+        if len > max_bytes {
+            return Err(format!(
+                "archive is {} bytes, over the {} byte download limit",
+                len, max_bytes
+            ));
+        }
+    }
+    // Resumed bytes must come from the same file: a strong ETag, else the
+    // modification time, lets the server send the whole new file instead.
+    let validator = response
+        .headers()
+        .get(ETAG)
+        .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+        .or_else(|| response.headers().get(LAST_MODIFIED))
+        .cloned();
+
+    let name = archive_filename(url, content_type.as_deref()).ok_or_else(|| {
+        format!(
+            "cannot determine archive format for {} (expected .tar.gz, .tgz, .zip, or .whl)",
+            url
+        )
+    })?;
+    let dest = dest_dir.join(&name);
+
+    let result = async {
+        let mut file =
+            std::fs::File::create(&dest).map_err(|e| format!("failed to create file: {}", e))?;
+        let mut written: u64 = 0;
+        let mut attempt = 0;
+        loop {
+            let mut reason =
+                match stream_body(&mut response, &mut file, &mut written, max_bytes).await {
+                    Ok(()) => match total {
+                        Some(total) if written < total => {
+                            format!("connection closed after {} of {} bytes", written, total)
+                        }
+                        _ => break,
+                    },
+                    Err(Transfer::Interrupted(reason)) => reason,
+                    Err(Transfer::Fatal(e)) => return Err(e),
+                };
+            // Resume, retrying the range request itself if it fails too.
+            loop {
+                if attempt >= retries {
+                    return Err(format!(
+                        "download interrupted: {} (gave up after {} retries)",
+                        reason, retries
+                    ));
+                }
+                attempt += 1;
+                log_warn!(
+                    "download interrupted at {} bytes ({}); resuming, retry {}/{}",
+                    written,
+                    reason,
+                    attempt,
+                    retries
+                );
+                tokio::time::sleep(backoff(attempt)).await;
+                match request_rest(&client, url, written, validator.as_ref(), total).await {
+                    Ok((rest, true)) => response = rest,
+                    Ok((whole, false)) => {
+                        if whole.content_length() != total {
+                            return Err(
+                                "archive changed on the server while downloading".to_string()
+                            );
+                        }
+                        file.set_len(0)
+                            .and_then(|()| file.rewind())
+                            .map_err(|e| format!("failed to write archive: {}", e))?;
+                        written = 0;
+                        response = whole;
+                    }
+                    Err(Transfer::Interrupted(again)) => {
+                        reason = again;
+                        continue;
+                    }
+                    Err(Transfer::Fatal(e)) => return Err(e),
+                }
+                break;
+            }
+        }
+        match total {
+            Some(total) if written != total => Err(format!(
+                "download size mismatch: received {} bytes, server announced {}",
+                written, total
+            )),
+            _ => Ok(()),
+        }
+    }
+    .await;
+
+    match result {
+        Ok(()) => Ok(dest),
+        Err(e) => {
+            let _ = std::fs::remove_file(&dest);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn filename_from_url_suffix() {
+        assert_eq!(
+            archive_filename("https://example.com/rel/foo-1.0.tar.gz", None).as_deref(),
+            Some("foo-1.0.tar.gz")
+        );
+        assert_eq!(
+            archive_filename("https://example.com/pkg.whl?token=abc", None).as_deref(),
+            Some("pkg.whl")
+        );
+    }
+
+    #[test]
+    fn filename_falls_back_to_content_type() {
+        assert_eq!(
+            archive_filename(
+                "https://example.com/download/latest",
+                Some("application/gzip")
+            )
+            .as_deref(),
+            Some("download.tar.gz")
+        );
+        assert_eq!(
+            archive_filename(
+                "https://example.com/latest",
+                Some("application/octet-stream")
+            ),
+            None
+        );
+    }
+
+    /// Serve `responses` in order, one per connection, recording each
+    /// request's head.  A response is sent as given and the connection
+    /// closed, so a body shorter than its `Content-Length` is a dropped
+    /// transfer.
+    async fn serve(responses: Vec<Vec<u8>>) -> (String, Arc<Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                seen.lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+                let _ = socket.write_all(&response).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        (format!("http://{}/pkg-1.0.tar.gz", addr), requests)
+    }
+
+    fn response(status: &str, headers: &str, body: &[u8]) -> Vec<u8> {
+        let mut out = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/gzip\r\n{}Connection: close\r\n\r\n",
+            status, headers
+        )
+        .into_bytes();
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn payload() -> Vec<u8> {
+        (0..64 * 1024).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[tokio::test]
+    async fn dropped_transfer_resumes_with_a_range_request() {
+        let body = payload();
+        let half = body.len() / 2;
+        let (url, requests) = serve(vec![
+            response(
+                "200 OK",
+                &format!("Content-Length: {}\r\nETag: \"v1\"\r\n", body.len()),
+                &body[..half],
+            ),
+            response(
+                "206 Partial Content",
+                &format!(
+                    "Content-Length: {}\r\nContent-Range: bytes {}-{}/{}\r\n",
+                    body.len() - half,
+                    half,
+                    body.len() - 1,
+                    body.len()
+                ),
+                &body[half..],
+            ),
+        ])
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let path = download_archive(&url, dir.path(), 1 << 20, 2)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), body);
+        let requests = requests.lock().unwrap();
+        assert!(requests[1].contains(&format!("range: bytes={}-", half)));
+        assert!(requests[1].contains("if-range: \"v1\""));
+    }
+
+    #[tokio::test]
+    async fn range_ignored_restarts_and_exhausted_retries_fail() {
+        let body = payload();
+        let full = format!("Content-Length: {}\r\n", body.len());
+        let (url, _) = serve(vec![
+            response("200 OK", &full, &body[..100]),
+            response("200 OK", &full, &body),
+        ])
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = download_archive(&url, dir.path(), 1 << 20, 1)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), body);
+
+        let (url, _) = serve(vec![
+            response("200 OK", &full, &body[..100]),
+            response("200 OK", &full, &body[..200]),
+        ])
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let err = download_archive(&url, dir.path(), 1 << 20, 1)
+            .await
+            .unwrap_err();
+        assert!(err.contains("gave up after 1 retries"), "{err}");
+        assert!(!dir.path().join("pkg-1.0.tar.gz").exists());
+    }
+
+    #[test]
+    fn content_range_is_parsed() {
+        assert_eq!(
+            parse_content_range("bytes 100-199/200"),
+            Some((100, Some(200)))
+        );
+        assert_eq!(parse_content_range("bytes 5-9/*"), Some((5, None)));
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+    }
+
+    #[test]
+    fn content_type_gate() {
+        assert!(is_archive_content_type(None));
+        assert!(is_archive_content_type(Some("application/x-gzip")));
+        assert!(is_archive_content_type(Some(
+            "application/zip; charset=binary"
+        )));
+        assert!(!is_archive_content_type(Some("text/html; charset=utf-8")));
+    }
+}
@@ -0,0 +1,114 @@
+//! Structured errors for the quarantine, cache, and cloud API layers.
+//!
+//! `Display` renders only the human-readable message, so the CLI prints these
+//! exactly as it printed the old `String` errors; programmatic callers can
+//! match on the variant to tell a missing entry from a network outage.
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SigilError {
+    /// The requested item (quarantine entry, remote resource) does not exist.
+    NotFound(String),
+    /// The server could not be reached or answered with an error status.
+    Network(String),
+    /// Stored or received data could not be parsed or serialized.
+    Parse(String),
+    /// A local filesystem operation failed.
+    Io(String),
+    /// Missing or rejected credentials, or a plan that does not allow the call.
+    Auth(String),
+    /// The operation is not valid for the item's current state.
+    InvalidState(String),
+    /// A shortened ID matched more than one item.
+    Ambiguous(String),
+    /// Client configuration (proxy, CA bundle) is unusable.
+    Config(String),
+    /// Downloaded data failed signature verification and was not used.
+    Integrity(String),
+    /// The server answered 429; `retry_after` is its `Retry-After` in seconds.
+    RateLimited {
+        message: String,
+        retry_after: Option<u64>,
+    },
+}
+
+impl SigilError {
+    /// Classify a non-success HTTP status from the Sigil API.
+    pub fn from_status(status: reqwest::StatusCode, message: String) -> SigilError {
+        match status.as_u16() {
+            401..=403 => SigilError::Auth(message),
+            404 => SigilError::NotFound(message),
+            _ => SigilError::Network(message),
+        }
+    }
+}
+
+impl fmt::Display for SigilError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SigilError::NotFound(msg)
+            | SigilError::Network(msg)
+            | SigilError::Parse(msg)
+            | SigilError::Io(msg)
+            | SigilError::Auth(msg)
+            | SigilError::InvalidState(msg)
+            | SigilError::Ambiguous(msg)
+            | SigilError::Config(msg)
+            | SigilError::Integrity(msg)
+            | SigilError::RateLimited { message: msg, .. } => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for SigilError {}
+
+impl From<std::io::Error> for SigilError {
+    fn from(err: std::io::Error) -> Self {
+        SigilError::Io(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for SigilError {
+    fn from(err: serde_json::Error) -> Self {
+        SigilError::Parse(err.to_string())
+    }
+}
+
+/// Lets `?` lift a `SigilError` into the `String` errors used by the rest of
+/// the CLI.
+impl From<SigilError> for String {
+    fn from(err: SigilError) -> Self {
+        err.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_is_the_bare_message() {
+        let err = SigilError::NotFound("quarantine entry 'abc' not found".into());
+        assert_eq!(err.to_string(), "quarantine entry 'abc' not found");
+        let as_string: String = err.into();
+        assert_eq!(as_string, "quarantine entry 'abc' not found");
+    }
+
+    #[test]
+    fn http_status_is_classified() {
+        use reqwest::StatusCode;
+        assert!(matches!(
+            SigilError::from_status(StatusCode::UNAUTHORIZED, String::new()),
+            SigilError::Auth(_)
+        ));
+        assert!(matches!(
+            SigilError::from_status(StatusCode::NOT_FOUND, String::new()),
+            SigilError::NotFound(_)
+        ));
+        assert!(matches!(
+            SigilError::from_status(StatusCode::BAD_GATEWAY, String::new()),
+            SigilError::Network(_)
+        ));
+    }
+}
@@ -1,5 +1,7 @@
-//! `sigil explain` — AI adjudication of a scan finding (F-009 US-111).
+//! `sigil explain` — describe a rule (`sigil explain CODE-004`), or AI
+//! adjudication of a scan finding (F-009 US-111) when given a scan file.
 //!
+//! Rule descriptions are answered locally from [`crate::corpus::registry`].
 //! Capability-minimal by design (D6): the CLI never calls an LLM itself. It
 //! submits the scan to the Sigil API with the user's token and requests
 //! adjudication of one finding; the server owns model access and metering.
@@ -9,22 +11,13 @@ use serde_json::{json, Value};
 use std::path::Path;
 use std::time::Duration;
 
+use crate::output::outln;
+
 /// Extract the findings array from a `sigil scan -f json` output file.
-///
-/// That output is a concatenation of a banner line, a summary object, the
-/// findings array, and a verdict object — so this scans for the first JSON
-/// array rather than parsing the file as a single document.
 pub fn parse_scan_findings(content: &str) -> Result<Vec<Value>, String> {
-    let start = content.find('[').ok_or_else(|| {
-        "no findings array in scan file (is this `sigil scan -f json` output?)".to_string()
-    })?;
-    let mut de = serde_json::Deserializer::from_str(&content[start..]).into_iter::<Value>();
-    match de.next() {
-        Some(Ok(Value::Array(findings))) => Ok(findings),
-        Some(Ok(_)) => Err("expected a findings array in scan file".to_string()),
-        Some(Err(e)) => Err(format!("failed to parse findings array: {}", e)),
-        None => Err("empty findings array region in scan file".to_string()),
-    }
+    crate::output::parse_scan_report(content)
+        .map(|(_, findings)| findings)
+        .map_err(|e| format!("{} in scan file (is this `sigil scan -f json` output?)", e))
 }
 
 /// Map a CLI phase name (serde CamelCase) to the API's snake_case value.
@@ -108,6 +101,37 @@ fn render_upgrade(detail: &Value) {
     eprintln!("  {} {}", "Upgrade to Pro:".bold(), upgrade_url);
 }
 
+/// `sigil explain <RULE-ID>`: describe one rule and its remediation; unknown IDs exit 2.
+pub fn cmd_explain_rule(id: &str, format: &str) -> i32 {
+    let Some(rule) = crate::corpus::registry::lookup(id) else {
+        eprintln!(
+            "{} unknown rule '{}' (expected a rule ID such as CODE-004, or a scan JSON file)",
+            "error:".bold().red(),
+            id
+        );
+        return 2;
+    };
+
+    if format == "json" {
+        outln!("{}", serde_json::to_string_pretty(rule).unwrap_or_default());
+        return 0;
+    }
+
+    outln!("{}  {}", rule.id.bold(), rule.description);
+    outln!("  severity:    {}", rule.severity);
+    outln!("  confidence:  {}", rule.confidence);
+    outln!("  phase:       {}", rule.phase);
+    if let Some(example) = &rule.example {
+        let mut lines = example.lines();
+        outln!("  example:     {}", lines.next().unwrap_or_default());
+        for line in lines {
+            outln!("               {}", line);
+        }
+    }
+    outln!("  remediation: {}", rule.remediation);
+    0
+}
+
 /// Run `sigil explain`. Returns the process exit code.
 pub async fn cmd_explain(
     scan_json: &Path,
@@ -146,9 +170,17 @@ pub async fn cmd_explain(
         return 2;
     }
 
-    let token = match crate::api::load_token() {
-        Some(t) => t,
-        None => {
+    let token = match crate::api::token_state() {
+        crate::api::TokenState::Valid(t) => t,
+        crate::api::TokenState::Expired => {
+            eprintln!(
+                "{} {}",
+                "error:".bold().red(),
+                crate::api::SESSION_EXPIRED_HINT
+            );
+            return 2;
+        }
+        crate::api::TokenState::Missing => {
             eprintln!(
                 "{} not authenticated — run `sigil login` first",
                 "error:".bold().red()
@@ -366,6 +398,14 @@ pub async fn cmd_explain(
 mod tests {
     use super::*;
 
+    #[test]
+    fn parses_scan_report_object() {
+        let content = "{\"files_scanned\":1,\"verdict\":\"HIGH RISK\",\"findings\":[{\"phase\":\"NetworkExfil\",\"severity\":\"High\",\"rule\":\"NET-006\",\"file\":\"a.js\"}]}";
+        let findings = parse_scan_findings(content).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0]["rule"], "NET-006");
+    }
+
     #[test]
     fn parses_multi_document_scan_output() {
         let content = "sigil: scanning...\n{\"files_scanned\":1}\n[{\"phase\":\"NetworkExfil\",\"severity\":\"High\",\"rule\":\"NET-006\",\"file\":\"a.js\"}]\n{\"verdict\":\"HIGH RISK\"}";
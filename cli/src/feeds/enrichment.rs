@@ -14,6 +14,7 @@
 //! Both feeds are cached. Network failures are treated as "no enrichment available"
 //! and never abort the scan.
 
+use crate::logging::{log_info, log_warn};
 use crate::scanner::Finding;
 use serde::Deserialize;
 use std::collections::{HashMap, HashSet};
@@ -22,7 +23,7 @@ use std::path::{Path, PathBuf};
 // ── Cache helpers ─────────────────────────────────────────────────────────────
 
 fn enrichment_cache_dir() -> Option<PathBuf> {
-    dirs::home_dir().map(|h| h.join(".sigil").join("enrichment-cache"))
+    Some(crate::paths::in_home("enrichment-cache"))
 }
 
 fn read_bytes_cache(dir: &Path, key: &str) -> Option<Vec<u8>> {
@@ -74,7 +75,7 @@ pub fn load_kev_set(fixture_path: Option<&Path>) -> HashSet<String> {
             parse_kev_from_bytes(&bytes)
         }
         Err(e) => {
-            eprintln!("sigil: KEV offline — using cached data ({})", e);
+            log_info!("KEV offline — using cached data ({})", e);
             let cached = read_bytes_cache(&cache_dir, KEV_CACHE_KEY).unwrap_or_default();
             parse_kev_from_bytes(&cached)
         }
@@ -92,7 +93,7 @@ fn parse_kev_from_bytes(bytes: &[u8]) -> HashSet<String> {
             .map(|e| e.cve_id)
             .collect(),
         Err(e) => {
-            eprintln!("sigil: failed to parse KEV feed: {}", e);
+            log_warn!("failed to parse KEV feed: {}", e);
             HashSet::new()
         }
     }
@@ -148,7 +149,7 @@ pub fn load_epss_scores(cve_ids: &[&str], fixture_path: Option<&Path>) -> HashMa
             parse_epss_from_bytes(&bytes)
         }
         Err(e) => {
-            eprintln!("sigil: EPSS offline — using cached data ({})", e);
+            log_info!("EPSS offline — using cached data ({})", e);
             let cached = read_bytes_cache(&cache_dir, &cache_key).unwrap_or_default();
             parse_epss_from_bytes(&cached)
         }
@@ -169,7 +170,7 @@ fn parse_epss_from_bytes(bytes: &[u8]) -> HashMap<String, f32> {
             })
             .collect(),
         Err(e) => {
-            eprintln!("sigil: failed to parse EPSS response: {}", e);
+            log_warn!("failed to parse EPSS response: {}", e);
             HashMap::new()
         }
     }
@@ -267,8 +268,11 @@ mod tests {
             line: None,
             snippet: "known vulnerability in dependency".to_string(),
             weight: 5,
+            confidence: 100,
             kev: false,
             epss: 0.0,
+            sha256: None,
+            region: None,
         }
     }
 
@@ -360,8 +364,11 @@ mod tests {
             line: None,
             snippet: "known vulnerability".to_string(),
             weight: 5,
+            confidence: 100,
             kev: false,
             epss: 0.0,
+            sha256: None,
+            region: None,
         }];
         enrich_findings_with_kev_epss(
             &mut findings,
@@ -12,8 +12,9 @@
 //! and emits a notice to stderr. A scan with neither network nor cache never
 //! errors the containing scan — it returns an empty Vec<Finding>.
 
+use crate::logging::{log_info, log_warn};
 use crate::sbom::Component;
-use crate::scanner::{Finding, Phase, Severity};
+use crate::scanner::{relative_path, Finding, Phase, Severity};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
@@ -31,7 +32,7 @@ fn ecosystem_for(package_type: &str) -> Option<&'static str> {
 // ── Cache helpers ──────────────────────────────────────────────────────────
 
 fn cache_dir() -> Option<PathBuf> {
-    dirs::home_dir().map(|h| h.join(".sigil").join("osv-cache"))
+    Some(crate::paths::in_home("osv-cache"))
 }
 
 fn cache_key(ecosystem: &str, name: &str, version: &str) -> String {
@@ -324,13 +325,13 @@ pub fn query_osv(components: &[Component], offline_hint: bool) -> Vec<(usize, Os
                 }
             }
             Err(e) => {
-                eprintln!("sigil: OSV offline — using cached data ({})", e);
+                log_info!("OSV offline — using cached data ({})", e);
                 // Already have whatever cache provided; uncached positions yield None (no findings)
             }
         }
     } else if uncached_positions.is_empty() && results.iter().any(|r| r.is_none()) && offline_hint {
         // offline_hint set and some positions have no cache
-        eprintln!("sigil: OSV offline — no cached data for some packages");
+        log_info!("OSV offline — no cached data for some packages");
     }
 
     // Flatten into (original_component_index, vuln) pairs
@@ -509,8 +510,11 @@ fn pairs_to_findings(pairs: Vec<(usize, OsvVuln)>, lockfile_path: &str) -> Vec<F
                 line: None,
                 snippet: summary,
                 weight,
+                confidence: 100,
                 kev: false,
                 epss: 0.0,
+                sha256: None,
+                region: None,
             }
         })
         .collect()
@@ -587,28 +591,28 @@ pub fn scan_for_osv_findings(path: &Path) -> Vec<Finding> {
             "package-lock.json" => match parsers::parse_package_lock(file_path) {
                 Ok(c) => c,
                 Err(e) => {
-                    eprintln!("sigil: OSV: failed to parse {}: {}", file_path.display(), e);
+                    log_warn!("OSV: failed to parse {}: {}", file_path.display(), e);
                     continue;
                 }
             },
             "requirements.txt" => match parsers::parse_requirements_txt(file_path) {
                 Ok(c) => c,
                 Err(e) => {
-                    eprintln!("sigil: OSV: failed to parse {}: {}", file_path.display(), e);
+                    log_warn!("OSV: failed to parse {}: {}", file_path.display(), e);
                     continue;
                 }
             },
             "Cargo.lock" => match parsers::parse_cargo_lock(file_path) {
                 Ok(c) => c,
                 Err(e) => {
-                    eprintln!("sigil: OSV: failed to parse {}: {}", file_path.display(), e);
+                    log_warn!("OSV: failed to parse {}: {}", file_path.display(), e);
                     continue;
                 }
             },
             "go.mod" => match parse_go_mod(file_path) {
                 Ok(c) => c,
                 Err(e) => {
-                    eprintln!("sigil: OSV: failed to parse {}: {}", file_path.display(), e);
+                    log_warn!("OSV: failed to parse {}: {}", file_path.display(), e);
                     continue;
                 }
             },
@@ -619,11 +623,7 @@ pub fn scan_for_osv_findings(path: &Path) -> Vec<Finding> {
             continue;
         }
 
-        let lockfile_rel = file_path
-            .strip_prefix(path)
-            .unwrap_or(file_path)
-            .to_string_lossy()
-            .to_string();
+        let lockfile_rel = relative_path(file_path, path);
 
         let findings = osv_findings_for_components(&components, &lockfile_rel);
         all_findings.extend(findings);
@@ -0,0 +1,252 @@
+//! Lines added since a git base ref, for `scan --diff-only`.
+//!
+//! `git diff --unified=0` against the merge base lists every added line in
+//! its hunk headers (`@@ -a,b +c,d @@` adds lines `c..c+d`), so findings can
+//! be kept only where the change under review put new code.  Untracked
+//! files count as entirely added.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::scanner::{scoring, Finding, ScanResult};
+
+/// Added lines per file, keyed by path relative to the scanned directory.
+#[derive(Debug, Default)]
+pub struct AddedLines {
+    /// Inclusive 1-based line ranges; `None` means the whole file is new.
+    files: HashMap<String, Option<Vec<(usize, usize)>>>,
+}
+
+impl AddedLines {
+    /// Whether `finding` sits on an added line.  A finding without a line
+    /// (a file-level check) is kept when its file changed at all.
+    pub fn contains(&self, finding: &Finding) -> bool {
+        let file = crate::diff::normalize_path(&finding.file);
+        match (self.files.get(&file), finding.line) {
+            (None, _) => false,
+            (Some(None), _) | (Some(Some(_)), None) => true,
+            (Some(Some(ranges)), Some(line)) => ranges
+                .iter()
+                .any(|&(start, end)| (start..=end).contains(&line)),
+        }
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+}
+
+/// Parse `git diff --unified=0` output into the added line ranges of each
+/// file.  Deleted files and pure deletions contribute nothing.
+pub fn parse_unified_diff(diff: &str) -> AddedLines {
+    let mut added = AddedLines::default();
+    let mut current: Option<String> = None;
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            current = path
+                .strip_prefix("b/")
+                .map(|p| p.trim_end_matches('\t').to_string());
+            continue;
+        }
+        let (Some(file), Some(header)) = (&current, line.strip_prefix("@@ ")) else {
+            continue;
+        };
+        // "-a,b +c,d @@ context": only the `+c,d` part matters.
+        let Some(new_side) = header.split(' ').find_map(|part| part.strip_prefix('+')) else {
+            continue;
+        };
+        let (start, count) = match new_side.split_once(',') {
+            Some((start, count)) => (start.parse().ok(), count.parse().ok()),
+            None => (new_side.parse().ok(), Some(1)),
+        };
+        let (Some(start), Some(count)) = (start, count) else {
+            continue;
+        };
+        let ranges = added
+            .files
+            .entry(file.clone())
+            .or_insert_with(|| Some(Vec::new()));
+        if let (Some(ranges), true) = (ranges, count > 0) {
+            ranges.push((start, start + count - 1));
+        }
+    }
+    // A file whose hunks only deleted lines has nothing added.
+    added
+        .files
+        .retain(|_, ranges| ranges.as_ref().is_none_or(|r| !r.is_empty()));
+    added
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "core.quotePath=false"])
+        .args(args)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Lines added in the working tree of `path` relative to where it branched
+/// from `base` (`git diff --merge-base <base>`), plus untracked files.
+pub fn added_lines(path: &Path, base: &str) -> Result<AddedLines, String> {
+    let dir = if path.is_file() {
+        path.parent().unwrap_or(path)
+    } else {
+        path
+    };
+    let diff = git(
+        dir,
+        &[
+            "diff",
+            "--merge-base",
+            "--unified=0",
+            "--no-color",
+            "--no-ext-diff",
+            "--relative",
+            "--src-prefix=a/",
+            "--dst-prefix=b/",
+            base,
+            "--",
+            ".",
+        ],
+    )?;
+    let mut added = parse_unified_diff(&diff);
+    let untracked = git(
+        dir,
+        &["ls-files", "--others", "--exclude-standard", "--", "."],
+    )?;
+    for file in untracked.lines().filter(|l| !l.is_empty()) {
+        added.files.insert(file.to_string(), None);
+    }
+    Ok(added)
+}
+
+/// Keep only findings on added lines, then recompute score and verdict
+/// from what is left.  Returns the number of findings dropped.
+pub fn apply_added_lines(result: &mut ScanResult, added: &AddedLines) -> usize {
+    let before = result.findings.len();
+    result.findings.retain(|f| added.contains(f));
+    scoring::rescore(result);
+    before - result.findings.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Phase, Severity};
+
+    fn finding(file: &str, line: Option<usize>) -> Finding {
+        Finding {
+            phase: Phase::CodePatterns,
+            rule: "CODE-001".to_string(),
+            severity: Severity::High,
+            file: file.to_string(),
+            line,
+            snippet: String::new(),
+            weight: 5,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        }
+    }
+
+    const DIFF: &str = "\
+diff --git a/src/app.py b/src/app.py
+index 1111111..2222222 100644
+--- a/src/app.py
++++ b/src/app.py
+@@ -3,0 +4,2 @@ def main():
++    eval(x)
++    exec(y)
+@@ -10 +12 @@ def other():
+-    old()
++    new()
+@@ -20,2 +21,0 @@ def gone():
+diff --git a/only_deleted.py b/only_deleted.py
+--- a/only_deleted.py
++++ b/only_deleted.py
+@@ -1,2 +0,0 @@
+diff --git a/removed.js b/removed.js
+--- a/removed.js
++++ /dev/null
+@@ -1 +0,0 @@
+";
+
+    #[test]
+    fn hunk_headers_give_added_ranges() {
+        let added = parse_unified_diff(DIFF);
+        assert_eq!(added.file_count(), 1);
+        assert!(added.contains(&finding("src/app.py", Some(4))));
+        assert!(added.contains(&finding("src/app.py", Some(5))));
+        assert!(added.contains(&finding("src/app.py", Some(12))));
+        assert!(!added.contains(&finding("src/app.py", Some(6))));
+        assert!(!added.contains(&finding("src/app.py", Some(21))));
+        // File-level findings follow the file; untouched files drop out.
+        assert!(added.contains(&finding("src/app.py", None)));
+        assert!(!added.contains(&finding("only_deleted.py", Some(1))));
+        assert!(!added.contains(&finding("other.py", None)));
+    }
+
+    #[test]
+    fn only_findings_on_added_lines_survive() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let git_ok = |args: &[&str]| {
+            Command::new("git")
+                .arg("-C")
+                .arg(root)
+                .args([
+                    "-c",
+                    "user.name=t",
+                    "-c",
+                    "user.email=t@t",
+                    "-c",
+                    "commit.gpgsign=false",
+                ])
+                .args(args)
+                .output()
+                .is_ok_and(|o| o.status.success())
+        };
+        if !git_ok(&["init", "-q"]) {
+            return; // git unavailable in this environment
+        }
+        std::fs::write(root.join("app.py"), "import os\nos.system(legacy)\n").unwrap();
+        assert!(git_ok(&["add", "."]));
+        assert!(git_ok(&["commit", "-qm", "base"]));
+        assert!(git_ok(&["tag", "base"]));
+        std::fs::write(
+            root.join("app.py"),
+            "import os\nos.system(legacy)\nos.system(fresh)\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("new.py"), "eval(payload)\n").unwrap();
+
+        let mut result = crate::scanner::run_scan(root, None, None);
+        let added = added_lines(root, "base").unwrap();
+        let dropped = apply_added_lines(&mut result, &added);
+
+        assert!(dropped >= 1);
+        let kept: Vec<(&str, Option<usize>)> = result
+            .findings
+            .iter()
+            .filter(|f| f.phase == Phase::CodePatterns)
+            .map(|f| (f.file.as_str(), f.line))
+            .collect();
+        assert!(kept.contains(&("app.py", Some(3))), "{kept:?}");
+        assert!(kept.contains(&("new.py", Some(1))), "{kept:?}");
+        assert!(!kept.contains(&("app.py", Some(2))), "{kept:?}");
+        assert!(added_lines(root, "no-such-ref").is_err());
+    }
+}
@@ -175,10 +175,7 @@ fn version_from_source(source: &str, source_type: &str) -> Option<String> {
 
 /// Base ledger directory: `~/.sigil/ledger/`.
 pub fn ledger_dir() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".sigil")
-        .join("ledger")
+    crate::paths::in_home("ledger")
 }
 
 fn index_path(dir: &Path) -> PathBuf {
@@ -327,8 +324,7 @@ pub fn apply_suppression_in(
     }
 
     if restored || result.suppressed_by.is_some() {
-        result.score = crate::scanner::scoring::calculate_score(&result.findings);
-        result.verdict = crate::scanner::scoring::determine_verdict(&result.findings, result.score);
+        crate::scanner::scoring::rescore(result);
     }
     result.suppressed_by.is_some()
 }
@@ -340,6 +336,7 @@ pub fn apply_suppression(result: &mut ScanResult, root: &Path, ignore: bool) ->
 
 // ── Rug-pull detection (US-F2) ──────────────────────────────────────────────
 
+use crate::corpus::registry;
 use crate::scanner::{Finding, Phase, Severity};
 
 /// Diff the current content of an approved artifact against its pinned baseline.
@@ -439,8 +436,11 @@ pub fn detect_rugpull(current_dir: &Path, baseline: &LedgerRecord) -> Vec<Findin
         line: None,
         snippet: summary,
         weight: 10,
+        confidence: registry::confidence("RUGPULL-001"),
         kev: false,
         epss: 0.0,
+        sha256: None,
+        region: None,
     }]
 }
 
@@ -478,6 +478,7 @@ mod tests {
             updated_at: Utc::now(),
             reason: None,
             scan_score: None,
+            path_missing: false,
         }
     }
 
@@ -733,8 +734,11 @@ mod tests {
             line: Some(1),
             snippet: "eval(x)".to_string(),
             weight: 5,
+            confidence: 100,
             kev: false,
             epss: 0.0,
+            sha256: None,
+            region: None,
         }
     }
 
@@ -747,8 +751,7 @@ mod tests {
             verdict,
             files_scanned: 1,
             duration_ms: 1,
-            suppressed_findings: Vec::new(),
-            suppressed_by: None,
+            ..Default::default()
         }
     }
 
@@ -0,0 +1,243 @@
+//! Operational log lines: errors, warnings, progress, and `--verbose` detail.
+//!
+//! These always go to stderr and are separate from the results `--format`
+//! controls.  With `--log-format text` (the default) they look as they
+//! always have: a coloured `error:` / `warning:` / `sigil:` prefix, and
+//! verbose detail as plain lines.  With `--log-format json` each one is a
+//! single JSON object per line, so a pipeline can filter by level:
+//!
+//! ```text
+//! {"level":"warn","message":"config: ignoring SIGIL_SEVERITY: ...","pid":4211,"timestamp":"2026-10-17T09:14:02.114Z"}
+//! ```
+//!
+//! Call sites use [`log_error!`], [`log_warn!`], [`log_info!`], and
+//! [`log_debug!`] like `eprintln!`; an optional `key = value, ...;` prefix
+//! adds context fields to the JSON form.  Callers still decide whether
+//! verbose detail is wanted: `log_debug!` always emits.  With `--quiet`,
+//! `log_info!` lines are dropped; warnings and errors still print.
+
+use std::sync::OnceLock;
+
+use colored::Colorize;
+use serde_json::{Map, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" => Some(LogFormat::Text),
+            "json" => Some(LogFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Error,
+    Warn,
+    /// Progress and outcome lines shown on every run (`sigil: scanning ...`).
+    Info,
+    /// `--verbose` detail.
+    Debug,
+}
+
+impl Level {
+    fn name(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+        }
+    }
+}
+
+/// How much status output a run wants, from `--quiet` / `--verbose`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Results and problems only: no progress lines, banners, or
+    /// summaries.
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+impl Verbosity {
+    pub fn from_flags(quiet: bool, verbose: bool) -> Self {
+        if quiet {
+            Verbosity::Quiet
+        } else if verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
+static FORMAT: OnceLock<LogFormat> = OnceLock::new();
+static VERBOSITY: OnceLock<Verbosity> = OnceLock::new();
+
+/// Choose the log format and verbosity for this process.  Lines logged
+/// before this (or without it, in tests) use the text format at normal
+/// verbosity.
+pub fn init(format: LogFormat, verbosity: Verbosity) {
+    let _ = FORMAT.set(format);
+    let _ = VERBOSITY.set(verbosity);
+}
+
+fn format() -> LogFormat {
+    FORMAT.get().copied().unwrap_or(LogFormat::Text)
+}
+
+/// The verbosity chosen by [`init`].
+pub fn verbosity() -> Verbosity {
+    VERBOSITY.get().copied().unwrap_or(Verbosity::Normal)
+}
+
+/// Whether `--quiet` is in effect.
+pub fn quiet() -> bool {
+    verbosity() == Verbosity::Quiet
+}
+
+/// Remove ANSI escape sequences, so a message built with `colored` reads
+/// cleanly as a JSON string.
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // CSI: ESC '[' parameters... final byte in '@'..='~'.
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        plain.push(c);
+    }
+    plain
+}
+
+/// One log line in the text format.
+fn text_line(level: Level, message: &str) -> String {
+    match level {
+        Level::Error => format!("{} {}", "error:".bold().red(), message),
+        Level::Warn => format!("{} {}", "warning:".bold().yellow(), message),
+        Level::Info => format!("{} {}", "sigil:".bold().cyan(), message),
+        Level::Debug => message.to_string(),
+    }
+}
+
+/// One log line in the JSON format.  Context fields never replace the
+/// standard ones.
+fn json_line(level: Level, message: &str, fields: Vec<(&str, Value)>) -> String {
+    let mut line = Map::new();
+    line.insert(
+        "timestamp".into(),
+        Value::from(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)),
+    );
+    line.insert("level".into(), Value::from(level.name()));
+    line.insert("message".into(), Value::from(strip_ansi(message)));
+    line.insert("pid".into(), Value::from(std::process::id()));
+    for (key, value) in fields {
+        line.entry(key).or_insert(value);
+    }
+    Value::Object(line).to_string()
+}
+
+#[doc(hidden)]
+pub fn log(level: Level, fields: Vec<(&str, Value)>, args: std::fmt::Arguments<'_>) {
+    if level == Level::Info && quiet() {
+        return;
+    }
+    let message = args.to_string();
+    let line = match format() {
+        LogFormat::Text => text_line(level, &message),
+        LogFormat::Json => json_line(level, &message, fields),
+    };
+    eprintln!("{}", line);
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log {
+    ($level:expr, $($key:ident = $value:expr),+ ; $($arg:tt)+) => {
+        $crate::logging::log(
+            $level,
+            vec![$((stringify!($key), serde_json::Value::from($value))),+],
+            format_args!($($arg)+),
+        )
+    };
+    ($level:expr, $($arg:tt)+) => {
+        $crate::logging::log($level, Vec::new(), format_args!($($arg)+))
+    };
+}
+
+/// Log an error (text: `error: ...`).
+macro_rules! log_error {
+    ($($arg:tt)+) => { $crate::__log!($crate::logging::Level::Error, $($arg)+) };
+}
+
+/// Log a warning (text: `warning: ...`).
+macro_rules! log_warn {
+    ($($arg:tt)+) => { $crate::__log!($crate::logging::Level::Warn, $($arg)+) };
+}
+
+/// Log progress or an outcome (text: `sigil: ...`).
+macro_rules! log_info {
+    ($($arg:tt)+) => { $crate::__log!($crate::logging::Level::Info, $($arg)+) };
+}
+
+/// Log `--verbose` detail (text: the bare message).
+macro_rules! log_debug {
+    ($($arg:tt)+) => { $crate::__log!($crate::logging::Level::Debug, $($arg)+) };
+}
+
+pub(crate) use {log_debug, log_error, log_info, log_warn};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_lines_carry_level_message_and_context() {
+        let line = json_line(
+            Level::Warn,
+            &format!("{} unreadable", "config:".bold()),
+            vec![
+                ("path", Value::from("/tmp/x")),
+                ("level", Value::from("oops")),
+            ],
+        );
+        let parsed: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["level"], "warn");
+        assert_eq!(parsed["message"], "config: unreadable");
+        assert_eq!(parsed["path"], "/tmp/x");
+        assert!(parsed["timestamp"].as_str().unwrap().ends_with('Z'));
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn text_lines_keep_the_familiar_prefixes() {
+        let plain = |level, message| strip_ansi(&text_line(level, message));
+        assert_eq!(plain(Level::Error, "boom"), "error: boom");
+        assert_eq!(plain(Level::Warn, "hmm"), "warning: hmm");
+        assert_eq!(plain(Level::Info, "scanning"), "sigil: scanning");
+        assert_eq!(plain(Level::Debug, "detail"), "detail");
+    }
+
+    #[test]
+    fn ansi_sequences_are_stripped() {
+        assert_eq!(strip_ansi("\u{1b}[1;31merror:\u{1b}[0m x"), "error: x");
+        assert_eq!(strip_ansi("plain"), "plain");
+    }
+}
@@ -1,21 +1,39 @@
+mod aggregate;
+mod allowlist;
 mod api;
+mod archive;
 mod cache;
+mod config;
 mod corpus;
 mod diff;
+mod download;
+mod error;
 mod explain;
 mod feeds;
+mod gitdiff;
 mod ledger;
+mod logging;
 mod output;
+mod paths;
 mod policy;
+mod post_scan;
 mod provenance;
 mod provider;
 mod quarantine;
+#[cfg(feature = "tui")]
+mod review;
 mod sandbox;
 mod sbom;
 mod scanner;
+mod suppressions;
+mod threat_cache;
+mod upstream;
+mod verify;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use colored::Colorize;
+use logging::{log_debug, log_error, log_info, log_warn};
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use std::process;
 
@@ -30,14 +48,40 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
-    /// Output format (text, json)
+    /// Print only results, warnings, and errors: no progress lines,
+    /// banners, or scan summary
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Output format (text, json, sarif, html; junit for scan)
     #[arg(short, long, global = true, default_value = "text")]
     format: String,
 
+    /// Skip TLS certificate verification for Sigil API calls (self-signed
+    /// development endpoints only)
+    #[arg(long, global = true)]
+    insecure: bool,
+
+    /// Write results to this file instead of stdout; status lines go to stderr
+    #[arg(short, long, global = true, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Disable coloured output (also honours NO_COLOR; colour is off
+    /// automatically when stdout is not a terminal)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Format of log lines on stderr (errors, warnings, progress, verbose
+    /// detail): text, or json for one JSON object per line
+    #[arg(long, global = true, default_value = "text", value_parser = ["text", "json"])]
+    log_format: String,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+// Parsed once per run, so `Scan`'s many flags are not worth boxing.
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
     /// Clone a git repository into quarantine and scan it
@@ -66,6 +110,10 @@ enum Commands {
         /// Automatically approve if scan passes
         #[arg(long)]
         auto_approve: bool,
+
+        /// How many levels of nested archives to extract
+        #[arg(long, default_value_t = archive::DEFAULT_MAX_DEPTH)]
+        extract_depth: usize,
     },
 
     /// Download and scan an npm package
@@ -80,46 +128,35 @@ enum Commands {
         /// Automatically approve if scan passes
         #[arg(long)]
         auto_approve: bool,
+
+        /// How many levels of nested archives to extract
+        #[arg(long, default_value_t = archive::DEFAULT_MAX_DEPTH)]
+        extract_depth: usize,
     },
 
     /// Scan an existing directory or file
-    Scan {
-        /// Path to scan
-        path: PathBuf,
-
-        /// Phases to run (comma-separated, or "all")
-        #[arg(short, long, default_value = "all")]
-        phases: String,
-
-        /// Minimum severity to report (low, medium, high, critical)
-        #[arg(short, long, default_value = "low")]
-        severity: String,
-
-        /// Submit results to Sigil cloud
-        #[arg(long)]
-        submit: bool,
+    Scan(ScanArgs),
 
-        /// Disable cache (force a fresh scan even if content is unchanged)
-        #[arg(long)]
-        no_cache: bool,
+    /// Download a remote archive (.tar.gz, .tgz, .zip, .whl) into quarantine and scan it
+    ScanUrl {
+        /// URL of the archive to download
+        url: String,
 
-        /// Enrich scan with cloud threat intelligence (hash lookup)
+        /// Keep the quarantine entry after scanning (default: delete it)
         #[arg(long)]
-        enrich: bool,
+        keep: bool,
 
-        /// Use enhanced LLM-powered analysis (Pro feature, requires authentication)
-        #[arg(long)]
-        enhanced: bool,
+        /// Maximum download size in megabytes
+        #[arg(long, default_value_t = 200)]
+        max_size: u64,
 
-        /// Exit 1 when a finding at or above this severity is present
-        /// (low, medium, high, critical). Default: high.
-        #[arg(long, default_value = "high")]
-        fail_on: String,
+        /// How many levels of nested archives to extract
+        #[arg(long, default_value_t = archive::DEFAULT_MAX_DEPTH)]
+        extract_depth: usize,
 
-        /// Disable trust-ledger allowlisting (report findings even when the
-        /// content digest-matches an approved ledger pin)
-        #[arg(long)]
-        ignore_ledger: bool,
+        /// How many times to resume an interrupted download
+        #[arg(long, default_value_t = download::DEFAULT_RETRIES)]
+        retries: u32,
     },
 
     /// Clear all cached scan results
@@ -130,11 +167,16 @@ enum Commands {
         /// Force re-download even if signatures are fresh
         #[arg(short, long)]
         force: bool,
+
+        /// Report how many signatures would be new or updated without
+        /// writing them (--verbose lists the rule IDs)
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Approve a quarantined item
     Approve {
-        /// Quarantine ID to approve
+        /// Quarantine ID to approve (or a unique prefix of it)
         id: String,
 
         /// Reason for approval
@@ -144,12 +186,22 @@ enum Commands {
 
     /// Reject a quarantined item and remove it
     Reject {
-        /// Quarantine ID to reject
+        /// Quarantine ID to reject (or a unique prefix of it)
         id: String,
 
         /// Reason for rejection
         #[arg(short, long)]
         reason: Option<String>,
+
+        /// Delete without asking; required when stdin is not a terminal
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Re-scan an existing quarantine entry with the current rules and signatures
+    Rescan {
+        /// Quarantine ID to re-scan (or a unique prefix of it)
+        id: String,
     },
 
     /// List quarantined items
@@ -161,6 +213,20 @@ enum Commands {
         /// Show detailed information
         #[arg(short, long)]
         detailed: bool,
+
+        /// Show at most this many entries
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+
+        /// Skip this many entries before listing
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        offset: usize,
+    },
+
+    /// Share quarantine review state with other machines
+    Quarantine {
+        #[command(subcommand)]
+        action: QuarantineAction,
     },
 
     /// Install sigil to system PATH
@@ -168,6 +234,10 @@ enum Commands {
         /// Installation directory
         #[arg(short, long)]
         path: Option<PathBuf>,
+
+        /// Show what would be installed where, without copying anything
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Authenticate with Sigil cloud
@@ -176,29 +246,43 @@ enum Commands {
         #[arg(short, long)]
         token: Option<String>,
 
-        /// API endpoint URL
-        #[arg(long, default_value = "https://api.sigilsec.ai")]
-        endpoint: String,
+        /// API endpoint URL (default: config `endpoint`, then
+        /// https://api.sigilsec.ai)
+        #[arg(long)]
+        endpoint: Option<String>,
+    },
+
+    /// Show who you are logged in as, which endpoint, and when the token expires
+    Whoami,
+
+    /// Show the version, build commit and date, and ruleset version
+    /// (`--format json` for tooling)
+    Version {
+        /// Same as `--format json`
+        #[arg(long)]
+        json: bool,
     },
 
-    /// Explain a scan finding with AI adjudication (Pro feature, server-side)
+    /// Describe a rule and its remediation, or explain a scan finding with
+    /// AI adjudication (Pro feature, server-side)
     Explain {
-        /// Path to a scan JSON file (from `sigil scan -f json`)
-        scan_json: PathBuf,
+        /// Rule ID (e.g. CODE-004), or a scan JSON file from `sigil scan -f json`
+        target: String,
 
-        /// Index of the finding to explain
+        /// Index of the finding to explain (scan JSON only)
         #[arg(long, default_value_t = 0)]
         finding: usize,
 
-        /// API endpoint URL
-        #[arg(long, default_value = "https://api.sigilsec.ai")]
-        endpoint: String,
+        /// API endpoint URL (default: config `endpoint`, then
+        /// https://api.sigilsec.ai)
+        #[arg(long)]
+        endpoint: Option<String>,
     },
 
     /// Report a threat to the Sigil cloud
     Report {
-        /// SHA256 hash of the malicious file
-        hash: String,
+        /// The malicious file, or its SHA-256 hash
+        target: String,
 
         /// Type of threat (e.g. malware, backdoor, exfil)
         #[arg(short = 't', long)]
@@ -207,21 +291,84 @@ enum Commands {
         /// Description of the threat
         #[arg(short, long)]
         description: String,
+
+        /// Scan the file and send its findings with the report (needs a
+        /// file path)
+        #[arg(long)]
+        attach_findings: bool,
+    },
+
+    /// Re-scan every quarantined item and print one consolidated report
+    /// (use --format json, sarif, or csv for an audit artifact)
+    ReportAll {
+        /// Only include entries with this status (pending, approved, rejected)
+        #[arg(short, long)]
+        status: Option<String>,
+    },
+
+    /// Combine stored scan results (one JSON file per package) into one
+    /// report: verdicts, the worst packages, and the most common rules
+    Aggregate {
+        /// Scan result files (a baseline or `sigil scan -f json` output),
+        /// or directories of them
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// How many packages and rules to list
+        #[arg(long, value_name = "N", default_value_t = 10)]
+        top: usize,
     },
 
     /// Compare a scan against a baseline to find new/resolved findings
     Diff {
         /// Path to baseline scan result JSON file
-        #[arg(long)]
-        baseline: String,
+        #[arg(long, required_unless_present_any = ["baseline_dir", "compare_registry"])]
+        baseline: Option<String>,
+
+        /// Previous version of the package: scanned fresh and used as the
+        /// baseline (compares two directories, e.g. v1.0 against v1.1)
+        #[arg(
+            long,
+            value_name = "DIR",
+            alias = "baseline-path",
+            conflicts_with = "baseline"
+        )]
+        baseline_dir: Option<PathBuf>,
+
+        /// Download the published release of the package at PATH and report
+        /// what the local copy adds to it
+        #[arg(long, conflicts_with_all = ["baseline", "baseline_dir"])]
+        compare_registry: bool,
+
+        /// Registry to compare against (pip or npm; default: from the
+        /// package's manifest)
+        #[arg(long, requires = "compare_registry")]
+        ecosystem: Option<String>,
+
+        /// Package to compare against: name, name==version (pip), or
+        /// name@version (npm); default: from the package's manifest
+        #[arg(long, value_name = "SPEC", requires = "compare_registry")]
+        package: Option<String>,
 
         /// Path to scan (runs a fresh scan and compares)
         path: PathBuf,
     },
 
+    /// Check a downloaded package archive against the registry's published hash
+    Verify {
+        /// Package ecosystem (pip or npm)
+        ecosystem: String,
+
+        /// Exact package version (pip: name==version, npm: name@version)
+        package: String,
+
+        /// Path to the local archive (.whl/.tar.gz for pip, .tgz for npm)
+        archive: PathBuf,
+    },
+
     /// View or modify configuration
     Config {
-        /// Configuration key to get or set
+        /// Configuration key to get or set (`unset <key>` removes a key)
         key: Option<String>,
 
         /// Value to set (if omitted, prints current value)
@@ -250,6 +397,12 @@ enum Commands {
         action: LedgerAction,
     },
 
+    /// Manage the allowlist of known-good file contents that scans skip
+    Allowlist {
+        #[command(subcommand)]
+        action: AllowlistAction,
+    },
+
     /// Run a command in a sandboxed environment with policy enforcement
     Run {
         /// Policy file or preset name (strict, standard, permissive)
@@ -266,6 +419,23 @@ enum Commands {
         command: Vec<String>,
     },
 
+    /// List the pinned dependencies a project's lockfiles resolve to
+    /// (requirements.txt, package-lock.json, poetry.lock, Cargo.lock)
+    Deps {
+        /// Project directory or lockfile
+        path: PathBuf,
+
+        /// Look up each dependency with a recorded hash in the Sigil
+        /// threat database
+        #[arg(long)]
+        lookup: bool,
+
+        /// With --lookup, look every hash up again instead of reusing
+        /// answers from ~/.sigil/threat_cache.json
+        #[arg(long, requires = "lookup")]
+        no_threat_cache: bool,
+    },
+
     /// Generate Software Bill of Materials for a project
     Sbom {
         /// Project path to analyze
@@ -278,10 +448,6 @@ enum Commands {
         /// Path to known_threats.json for cross-referencing
         #[arg(long)]
         threats_db: Option<PathBuf>,
-
-        /// Output to file instead of stdout
-        #[arg(short, long)]
-        output: Option<PathBuf>,
     },
 
     /// Scan a path, generate a security policy, and run a command in a sandbox
@@ -307,6 +473,181 @@ enum Commands {
     },
 }
 
+/// `sigil scan` options, as parsed; [`cmd_scan`] takes them whole.
+#[derive(Args)]
+struct ScanArgs {
+    /// Path to scan
+    path: PathBuf,
+
+    /// Phases to run (comma-separated, or "all"); prefix a phase with
+    /// `!`, `-`, or `not:` to leave it out, e.g. "!provenance"
+    #[arg(short, long, default_value = "all")]
+    phases: String,
+
+    /// Minimum severity to report (low, medium, high, critical;
+    /// default: config `severity`, then low)
+    #[arg(short, long)]
+    severity: Option<String>,
+
+    /// Drop findings from rules whose confidence is below N (0-100);
+    /// `sigil explain <ID>` shows a rule's confidence
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(0..=100))]
+    min_confidence: Option<u8>,
+
+    /// Submit results to Sigil cloud
+    #[arg(long)]
+    submit: bool,
+
+    /// Disable cache (force a fresh scan even if content is unchanged)
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Enrich scan with cloud threat intelligence (hash lookup)
+    #[arg(long)]
+    enrich: bool,
+
+    /// With --enrich, look every hash up again instead of reusing
+    /// answers from ~/.sigil/threat_cache.json
+    #[arg(long, requires = "enrich")]
+    no_threat_cache: bool,
+
+    /// Use enhanced LLM-powered analysis (Pro feature, requires authentication)
+    #[arg(long)]
+    enhanced: bool,
+
+    /// Exit 1 when a finding at or above this severity is present
+    /// (low, medium, high, critical). Default: high.
+    #[arg(long, default_value = "high")]
+    fail_on: String,
+
+    /// Disable trust-ledger allowlisting (report findings even when the
+    /// content digest-matches an approved ledger pin)
+    #[arg(long)]
+    ignore_ledger: bool,
+
+    /// Stop scanning new files after this many seconds and report the
+    /// partial result (flagged timed_out)
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Abandon a file whose content scan takes longer than this many
+    /// seconds and report it as PROV-TIMEOUT-001 (no limit by default)
+    #[arg(long, value_name = "SECONDS")]
+    timeout_per_file: Option<u64>,
+
+    /// Suppress findings already present in this baseline (a scan result
+    /// or `sigil scan -f json` output); only new findings are scored
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// After reporting, overwrite the --baseline file with this scan's
+    /// full result, accepting every current finding (asks first unless
+    /// --yes)
+    #[arg(
+        long,
+        alias = "baseline-update",
+        requires = "baseline",
+        conflicts_with = "only_changed_since"
+    )]
+    update_baseline: bool,
+
+    /// Overwrite the baseline with --update-baseline without asking
+    #[arg(long, requires = "update_baseline")]
+    yes: bool,
+
+    /// Report only what changed since the last --baseline-auto scan of
+    /// this path, whose result is kept in the cache; the first run
+    /// records the baseline
+    #[arg(long, conflicts_with = "baseline")]
+    baseline_auto: bool,
+
+    /// Load custom rule files from this directory instead of
+    /// ~/.sigil/rules (YAML or JSON: id, pattern, phase, severity,
+    /// description)
+    #[arg(long, value_name = "DIR")]
+    rules_dir: Option<PathBuf>,
+
+    /// Report malformed package.json files, and unreadable files at
+    /// Medium instead of Low, as PROV-PARSE-001 findings
+    #[arg(long)]
+    strict: bool,
+
+    /// Print a one-line `score=.. verdict=.. critical=..` summary for CI
+    /// gates to stderr, or with `--metrics=FILE` to that file
+    #[arg(long, value_name = "FILE", num_args = 0..=1, require_equals = true)]
+    metrics: Option<Option<PathBuf>>,
+
+    /// Report only findings on lines added since the merge base with
+    /// this git ref (e.g. origin/main); untracked files count as added
+    #[arg(long, value_name = "BASE_REF")]
+    diff_only: Option<String>,
+
+    /// Content-scan only files modified within this long (e.g. 30m, 1h,
+    /// 7d), by filesystem mtime; provenance still covers the whole tree
+    #[arg(long, value_name = "DURATION", value_parser = parse_age)]
+    only_changed_since: Option<std::time::Duration>,
+
+    /// Show findings as a tree of the scanned directory, with the
+    /// finding count and highest severity of each path (text format)
+    #[arg(long)]
+    tree: bool,
+
+    /// Triage the findings in a terminal UI and record the accepted
+    /// ones for this target under ~/.sigil/suppressions (needs the `tui`
+    /// build feature)
+    #[arg(short, long)]
+    interactive: bool,
+
+    /// What a test case is in `--format junit`: each scanned file, or
+    /// each rule that fired
+    #[arg(long, default_value = "file", value_parser = ["file", "rule"])]
+    junit_granularity: String,
+
+    /// Leave dotfiles and dot-directories out of the content phases;
+    /// provenance still reports them, and CI workflows are still scanned
+    #[arg(long)]
+    skip_hidden: bool,
+
+    /// Stop collecting findings after N; the report is marked truncated
+    /// and its score is a lower bound
+    #[arg(long, value_name = "N")]
+    max_findings: Option<std::num::NonZeroUsize>,
+
+    /// Allowlist of known-good file hashes to use instead of
+    /// ~/.sigil/allowlist.json
+    #[arg(long, value_name = "FILE")]
+    allowlist: Option<PathBuf>,
+
+    /// Do not report a missing .git (PROV-006); for directories that are
+    /// intentionally not git checkouts
+    #[arg(long)]
+    no_provenance_git: bool,
+
+    /// Run CMD when the verdict is CRITICAL RISK; it is not passed
+    /// through a shell (see docs/cli.md for what it receives)
+    #[arg(long, value_name = "CMD")]
+    on_critical: Option<String>,
+
+    /// List the N files with the highest summed finding score after the
+    /// summary (0 = leave the list out)
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    top: usize,
+
+    /// Lay out text findings by scan phase, by severity, or by file;
+    /// JSON keeps its own order
+    #[arg(long, default_value = "phase", value_parser = ["phase", "severity", "file"])]
+    sort: String,
+
+    /// Which end `--sort severity` starts from
+    #[arg(long, default_value = "critical-first", value_parser = ["critical-first", "low-first"])]
+    severity_order: String,
+
+    /// Record progress in FILE while scanning; rerunning with the same
+    /// FILE after an interruption skips the files already scanned
+    #[arg(long, value_name = "FILE")]
+    checkpoint: Option<PathBuf>,
+}
+
 #[derive(Subcommand)]
 enum ProviderAction {
     /// Create a new credential provider
@@ -346,15 +687,44 @@ enum LedgerAction {
     },
 }
 
+#[derive(Subcommand)]
+enum AllowlistAction {
+    /// Approve the current contents of files (directories are walked)
+    Add {
+        /// Files or directories to approve
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Why the contents are trusted, e.g. "vendored lodash 4.17.21, reviewed"
+        #[arg(short, long)]
+        note: String,
+    },
+    /// List the approved hashes and their notes
+    List,
+}
+
+#[derive(Subcommand)]
+enum QuarantineAction {
+    /// Write the quarantine index (statuses, reasons, scores) to FILE
+    Export {
+        /// File to write
+        file: PathBuf,
+        /// Also bundle every entry's files, as a gzipped tarball
+        #[arg(long)]
+        with_files: bool,
+    },
+    /// Merge an export into the local index
+    Import {
+        /// File written by `sigil quarantine export`
+        file: PathBuf,
+    },
+}
+
 #[derive(Subcommand)]
 enum PolicyAction {
     /// Generate a policy from scan results
     Generate {
         /// Path to scan
         path: PathBuf,
-        /// Output file (default: stdout)
-        #[arg(short, long)]
-        output: Option<PathBuf>,
         /// Show the scan results alongside the policy
         #[arg(long)]
         verbose: bool,
@@ -375,35 +745,49 @@ enum PolicyAction {
 async fn main() {
     // Set up global panic handler to prevent crashes during scanning
     std::panic::set_hook(Box::new(|panic_info| {
-        use colored::Colorize;
-        eprintln!(
-            "{} SCAN_ERROR: Panic occurred during scanning",
-            "sigil:".bold().red()
+        let location = panic_info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_default();
+        let message = panic_info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|m| m.to_string())
+            .or_else(|| panic_info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        log_error!(
+            location = location.as_str(), panic = message.as_str();
+            "SCAN_ERROR: Panic occurred during scanning\n  Location: {}\n  Message: {}\n  This is likely a Unicode boundary error in file processing.\n  Continuing scan with remaining files...",
+            location,
+            message
         );
-
-        if let Some(location) = panic_info.location() {
-            eprintln!(
-                "  Location: {}:{}:{}",
-                location.file(),
-                location.line(),
-                location.column()
-            );
-        }
-
-        if let Some(msg) = panic_info.payload().downcast_ref::<&str>() {
-            eprintln!("  Message: {}", msg);
-        } else if let Some(msg) = panic_info.payload().downcast_ref::<String>() {
-            eprintln!("  Message: {}", msg);
-        }
-
-        eprintln!("  This is likely a Unicode boundary error in file processing.");
-        eprintln!("  Continuing scan with remaining files...");
     }));
 
     let cli = Cli::parse();
+    output::configure_color(cli.no_color);
+    logging::init(
+        logging::LogFormat::from_name(&cli.log_format).unwrap_or(logging::LogFormat::Text),
+        logging::Verbosity::from_flags(cli.quiet, cli.verbose),
+    );
+
+    // `sbom` and `policy generate` write their own documents to --output.
+    let writes_own_output = matches!(
+        cli.command,
+        Commands::Sbom { .. }
+            | Commands::Policy {
+                action: PolicyAction::Generate { .. }
+            }
+    );
+    if let Some(path) = cli.output.as_deref().filter(|_| !writes_own_output) {
+        output::redirect_results(path);
+    }
 
     if cli.verbose {
-        eprintln!("{} verbose mode enabled", "sigil:".bold().cyan());
+        log_info!("verbose mode enabled");
+    }
+    if cli.insecure {
+        api::set_insecure(true);
+        log_warn!("TLS certificate verification disabled (--insecure)");
     }
 
     let exit_code = match cli.command {
@@ -426,11 +810,13 @@ async fn main() {
             package,
             version,
             auto_approve,
+            extract_depth,
         } => {
             cmd_pip(
                 &package,
                 version.as_deref(),
                 auto_approve,
+                extract_depth,
                 &cli.format,
                 cli.verbose,
             )
@@ -441,38 +827,34 @@ async fn main() {
             package,
             version,
             auto_approve,
+            extract_depth,
         } => {
             cmd_npm(
                 &package,
                 version.as_deref(),
                 auto_approve,
+                extract_depth,
                 &cli.format,
                 cli.verbose,
             )
             .await
         }
 
-        Commands::Scan {
-            path,
-            phases,
-            severity,
-            submit,
-            no_cache,
-            enrich,
-            enhanced,
-            fail_on,
-            ignore_ledger,
+        Commands::Scan(args) => cmd_scan(args, &cli.format, cli.verbose).await,
+
+        Commands::ScanUrl {
+            url,
+            keep,
+            max_size,
+            extract_depth,
+            retries,
         } => {
-            cmd_scan(
-                &path,
-                &phases,
-                &severity,
-                submit,
-                no_cache,
-                enrich,
-                enhanced,
-                &fail_on,
-                ignore_ledger,
+            cmd_scan_url(
+                &url,
+                keep,
+                max_size,
+                extract_depth,
+                retries,
                 &cli.format,
                 cli.verbose,
             )
@@ -481,38 +863,110 @@ async fn main() {
 
         Commands::ClearCache => cmd_clear_cache().await,
 
-        Commands::Fetch { force } => cmd_fetch(force, cli.verbose).await,
+        Commands::Fetch { force, dry_run } => cmd_fetch(force, dry_run, cli.verbose).await,
 
         Commands::Approve { id, reason } => cmd_approve(&id, reason.as_deref(), cli.verbose).await,
 
-        Commands::Reject { id, reason } => cmd_reject(&id, reason.as_deref(), cli.verbose).await,
-
-        Commands::List { status, detailed } => {
-            cmd_list(status.as_deref(), detailed, &cli.format, cli.verbose).await
+        Commands::Reject { id, reason, yes } => {
+            cmd_reject(&id, reason.as_deref(), yes, cli.verbose).await
         }
 
-        Commands::Install { path } => cmd_install(path.as_deref(), cli.verbose).await,
+        Commands::Rescan { id } => cmd_rescan(&id, &cli.format, cli.verbose).await,
+
+        Commands::List {
+            status,
+            detailed,
+            limit,
+            offset,
+        } => cmd_list(status.as_deref(), detailed, offset, limit, &cli.format).await,
+
+        Commands::Install { path, dry_run } => {
+            cmd_install(path.as_deref(), dry_run, cli.verbose).await
+        }
 
         Commands::Login { token, endpoint } => {
+            let endpoint = config::resolve().endpoint(endpoint.as_deref());
             cmd_login(token.as_deref(), &endpoint, cli.verbose).await
         }
 
+        Commands::Whoami => cmd_whoami(&cli.format, cli.verbose).await,
+        Commands::Version { json } => cmd_version(if json { "json" } else { &cli.format }),
+
         Commands::Explain {
-            scan_json,
+            target,
             finding,
             endpoint,
-        } => explain::cmd_explain(&scan_json, finding, &endpoint, cli.verbose).await,
+        } => {
+            let scan_json = Path::new(&target);
+            if scan_json.is_file() {
+                let endpoint = config::resolve().endpoint(endpoint.as_deref());
+                explain::cmd_explain(scan_json, finding, &endpoint, cli.verbose).await
+            } else {
+                explain::cmd_explain_rule(&target, &cli.format)
+            }
+        }
 
         Commands::Report {
-            hash,
+            target,
             threat_type,
             description,
-        } => cmd_report(&hash, &threat_type, &description, cli.verbose).await,
+            attach_findings,
+        } => {
+            cmd_report(
+                &target,
+                &threat_type,
+                &description,
+                attach_findings,
+                cli.verbose,
+            )
+            .await
+        }
+
+        Commands::ReportAll { status } => {
+            cmd_report_all(status.as_deref(), &cli.format, cli.verbose).await
+        }
+
+        Commands::Aggregate { files, top } => cmd_aggregate(&files, top, &cli.format),
+
+        Commands::Diff {
+            compare_registry: true,
+            ecosystem,
+            package,
+            path,
+            ..
+        } => {
+            cmd_compare_registry(
+                &path,
+                ecosystem.as_deref(),
+                package.as_deref(),
+                &cli.format,
+                cli.verbose,
+            )
+            .await
+        }
 
-        Commands::Diff { baseline, path } => {
-            cmd_diff(&baseline, &path, &cli.format, cli.verbose).await
+        Commands::Diff {
+            baseline,
+            baseline_dir,
+            path,
+            ..
+        } => {
+            cmd_diff(
+                baseline.as_deref(),
+                baseline_dir.as_deref(),
+                &path,
+                &cli.format,
+                cli.verbose,
+            )
+            .await
         }
 
+        Commands::Verify {
+            ecosystem,
+            package,
+            archive,
+        } => cmd_verify(&ecosystem, &package, &archive, &cli.format, cli.verbose).await,
+
         Commands::Config { key, value, list } => {
             cmd_config(key.as_deref(), value.as_deref(), list, cli.verbose).await
         }
@@ -526,21 +980,30 @@ async fn main() {
 
         Commands::Provider { action } => cmd_provider(action).await,
 
-        Commands::Policy { action } => cmd_policy(action).await,
+        Commands::Policy { action } => cmd_policy(action, cli.output.as_deref()).await,
 
         Commands::Ledger { action } => cmd_ledger(action).await,
 
+        Commands::Quarantine { action } => cmd_quarantine(action, &cli.format),
+
+        Commands::Allowlist { action } => cmd_allowlist(action, &cli.format),
+
+        Commands::Deps {
+            path,
+            lookup,
+            no_threat_cache,
+        } => cmd_deps(&path, lookup, no_threat_cache, &cli.format, cli.verbose).await,
+
         Commands::Sbom {
             path,
             sbom_format,
             threats_db,
-            output,
         } => {
             cmd_sbom(
                 &path,
                 &sbom_format,
                 threats_db.as_deref(),
-                output.as_deref(),
+                cli.output.as_deref(),
                 cli.verbose,
             )
             .await
@@ -564,54 +1027,108 @@ async fn main() {
             ) {
                 Ok(code) => code,
                 Err(e) => {
-                    eprintln!("error: {}", e);
+                    log_error!("{}", e);
                     1
                 }
             }
         }
     };
 
+    let exit_code = match output::finish_results() {
+        Ok(Some(path)) => {
+            log_info!("results written to {}", path.display());
+            exit_code
+        }
+        Ok(None) => exit_code,
+        Err(err) => {
+            log_error!("{}", err);
+            2
+        }
+    };
+
     process::exit(exit_code);
 }
 
+// ---------------------------------------------------------------------------
+// Package download helper
+// ---------------------------------------------------------------------------
+
+/// Fetch the distribution of `spec` into `dest` with the ecosystem's own
+/// client: `pip download --no-deps` or `npm pack`.  Nothing is installed.
+fn download_package(ecosystem: &str, spec: &str, dest: &Path) -> Result<(), String> {
+    let status = match ecosystem {
+        "pip" => std::process::Command::new("pip")
+            .arg("download")
+            .arg("--no-deps")
+            .arg("--dest")
+            .arg(dest)
+            .arg(spec)
+            .status(),
+        _ => std::process::Command::new("npm")
+            .arg("pack")
+            .arg(spec)
+            .current_dir(dest)
+            .status(),
+    };
+    match status {
+        Ok(s) if s.success() => Ok(()),
+        _ if ecosystem == "pip" => Err("pip download failed".to_string()),
+        _ => Err("npm pack failed".to_string()),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Archive extraction helper
 // ---------------------------------------------------------------------------
 
-/// Extract .whl/.zip and .tar.gz/.tgz archives in a directory so the scanner
-/// can inspect the actual source files inside packages.
-fn extract_archives(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let entries: Vec<_> = std::fs::read_dir(dir)?.filter_map(|e| e.ok()).collect();
+/// Unpack downloaded archives in a quarantine directory. Extraction problems
+/// are warnings (the raw archives are still scanned); the returned findings
+/// (e.g. an expansion-limit hit, or a zip-slip entry met before a corrupt
+/// archive stopped extraction) must be merged into the scan result.
+fn extract_quarantined_archives(
+    dir: &Path,
+    max_depth: usize,
+    verbose: bool,
+) -> Vec<scanner::Finding> {
+    let options = archive::ExtractOptions {
+        max_depth,
+        ..archive::ExtractOptions::default()
+    };
+    let report = archive::extract_archives(dir, &options);
+    if let Some(err) = &report.error {
+        log_warn!(
+            "failed to extract archives: {} (scanning raw archives instead)",
+            err
+        );
+    } else if verbose {
+        log_debug!(
+            "extracted {} archive(s), {} bytes",
+            report.archives_extracted,
+            report.bytes_written
+        );
+    }
+    report.findings
+}
+
+/// Scan a quarantined entry.  Package downloads are not git checkouts, so
+/// only cloned repositories are expected to carry `.git` (PROV-006).
+fn scan_quarantined(entry: &quarantine::QuarantineEntry) -> scanner::ScanResult {
+    if entry.source_type == "git" {
+        scanner::run_scan(&entry.path, None, None)
+    } else {
+        scanner::run_package_scan(&entry.path)
+    }
+}
 
-    for entry in entries {
-        let path = entry.path();
-        let name = path
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
-
-        if name.ends_with(".whl") || name.ends_with(".zip") {
-            // Extract zip archives (.whl files are zip format)
-            let file = std::fs::File::open(&path)?;
-            let mut archive = zip::ZipArchive::new(file)?;
-            let extract_dir = dir.join(name.trim_end_matches(".whl").trim_end_matches(".zip"));
-            std::fs::create_dir_all(&extract_dir)?;
-            archive.extract(&extract_dir)?;
-            std::fs::remove_file(&path)?;
-        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
-            // Extract gzipped tar archives
-            let file = std::fs::File::open(&path)?;
-            let gz = flate2::read::GzDecoder::new(file);
-            let mut archive = tar::Archive::new(gz);
-            let extract_dir = dir.join(name.trim_end_matches(".tar.gz").trim_end_matches(".tgz"));
-            std::fs::create_dir_all(&extract_dir)?;
-            archive.unpack(&extract_dir)?;
-            std::fs::remove_file(&path)?;
-        }
-    }
-
-    Ok(())
+/// Fold findings produced outside `run_scan` into a result, in the canonical
+/// order, and re-derive the score and verdict from the combined set.
+fn merge_findings(result: &mut scanner::ScanResult, findings: Vec<scanner::Finding>) {
+    if findings.is_empty() {
+        return;
+    }
+    result.findings.extend(findings);
+    result.sort_findings();
+    scanner::scoring::rescore(result);
 }
 
 // ---------------------------------------------------------------------------
@@ -625,28 +1142,20 @@ async fn cmd_clone(
     format: &str,
     verbose: bool,
 ) -> i32 {
-    println!(
-        "{} cloning {} into quarantine...",
-        "sigil:".bold().cyan(),
-        url.bold()
-    );
+    log_info!("cloning {} into quarantine...", url.bold());
 
     // 1. Create quarantine entry
     let entry = match quarantine::add(url, "git") {
         Ok(e) => e,
         Err(err) => {
-            eprintln!(
-                "{} failed to create quarantine entry: {}",
-                "error:".bold().red(),
-                err
-            );
+            log_error!("failed to create quarantine entry: {}", err);
             return 1;
         }
     };
 
     if verbose {
-        eprintln!("quarantine id: {}", entry.id);
-        eprintln!("quarantine path: {}", entry.path.display());
+        log_debug!("quarantine id: {}", entry.id);
+        log_debug!("quarantine path: {}", entry.path.display());
     }
 
     // 2. Clone repo into quarantine path
@@ -661,27 +1170,21 @@ async fn cmd_clone(
     match status {
         Ok(s) if s.success() => {}
         _ => {
-            eprintln!("{} git clone failed", "error:".bold().red());
+            log_error!("git clone failed");
             return 1;
         }
     }
 
     // 3. Scan the cloned repo
     let result = scanner::run_scan(&entry.path, None, None);
-    output::print_scan_summary(&result, format);
-    output::print_findings(&result.findings, format);
-    output::print_verdict(&result.verdict, format);
+    output::print_scan_result(&result, format);
 
     // 4. Auto-approve if requested and scan is low risk
     if auto_approve && result.verdict == scanner::Verdict::LowRisk {
         if let Err(err) = approve_with_ledger(&entry.id, Some("auto-approved: low risk scan")) {
-            eprintln!(
-                "{} failed to auto-approve: {}",
-                "warning:".bold().yellow(),
-                err
-            );
+            log_warn!("failed to auto-approve: {}", err);
         } else {
-            println!("{} auto-approved (low risk)", "sigil:".bold().green());
+            log_info!("auto-approved (low risk)");
         }
     }
 
@@ -696,6 +1199,7 @@ async fn cmd_pip(
     package: &str,
     version: Option<&str>,
     auto_approve: bool,
+    extract_depth: usize,
     format: &str,
     verbose: bool,
 ) -> i32 {
@@ -704,68 +1208,42 @@ async fn cmd_pip(
         None => package.to_string(),
     };
 
-    println!(
-        "{} downloading pip package {} into quarantine...",
-        "sigil:".bold().cyan(),
+    log_info!(
+        "downloading pip package {} into quarantine...",
         pkg_spec.bold()
     );
 
     let entry = match quarantine::add(&pkg_spec, "pip") {
         Ok(e) => e,
         Err(err) => {
-            eprintln!(
-                "{} failed to create quarantine entry: {}",
-                "error:".bold().red(),
-                err
-            );
+            log_error!("failed to create quarantine entry: {}", err);
             return 1;
         }
     };
 
     if verbose {
-        eprintln!("quarantine id: {}", entry.id);
+        log_debug!("quarantine id: {}", entry.id);
     }
 
     // Download pip package into quarantine
-    let status = std::process::Command::new("pip")
-        .arg("download")
-        .arg("--no-deps")
-        .arg("--dest")
-        .arg(&entry.path)
-        .arg(&pkg_spec)
-        .status();
-
-    match status {
-        Ok(s) if s.success() => {}
-        _ => {
-            eprintln!("{} pip download failed", "error:".bold().red());
-            return 1;
-        }
+    if let Err(err) = download_package("pip", &pkg_spec, &entry.path) {
+        log_error!("{}", err);
+        return 1;
     }
 
-    // Extract .whl (zip) and .tar.gz files so the scanner sees actual source
-    if let Err(err) = extract_archives(&entry.path) {
-        eprintln!(
-            "{} failed to extract archives: {} (scanning raw archives instead)",
-            "warning:".bold().yellow(),
-            err
-        );
-    }
+    // Extract .whl/.zip and .tar.gz/.tgz (recursively) so the scanner sees
+    // actual source
+    let extraction_findings = extract_quarantined_archives(&entry.path, extract_depth, verbose);
 
-    let result = scanner::run_scan(&entry.path, None, None);
-    output::print_scan_summary(&result, format);
-    output::print_findings(&result.findings, format);
-    output::print_verdict(&result.verdict, format);
+    let mut result = scanner::run_package_scan(&entry.path);
+    merge_findings(&mut result, extraction_findings);
+    output::print_scan_result(&result, format);
 
     if auto_approve && result.verdict == scanner::Verdict::LowRisk {
         if let Err(err) = approve_with_ledger(&entry.id, Some("auto-approved: low risk scan")) {
-            eprintln!(
-                "{} failed to auto-approve: {}",
-                "warning:".bold().yellow(),
-                err
-            );
+            log_warn!("failed to auto-approve: {}", err);
         } else {
-            println!("{} auto-approved (low risk)", "sigil:".bold().green());
+            log_info!("auto-approved (low risk)");
         }
     }
 
@@ -780,6 +1258,7 @@ async fn cmd_npm(
     package: &str,
     version: Option<&str>,
     auto_approve: bool,
+    extract_depth: usize,
     format: &str,
     verbose: bool,
 ) -> i32 {
@@ -788,67 +1267,105 @@ async fn cmd_npm(
         None => package.to_string(),
     };
 
-    println!(
-        "{} downloading npm package {} into quarantine...",
-        "sigil:".bold().cyan(),
+    log_info!(
+        "downloading npm package {} into quarantine...",
         pkg_spec.bold()
     );
 
     let entry = match quarantine::add(&pkg_spec, "npm") {
         Ok(e) => e,
         Err(err) => {
-            eprintln!(
-                "{} failed to create quarantine entry: {}",
-                "error:".bold().red(),
-                err
-            );
+            log_error!("failed to create quarantine entry: {}", err);
             return 1;
         }
     };
 
     if verbose {
-        eprintln!("quarantine id: {}", entry.id);
+        log_debug!("quarantine id: {}", entry.id);
     }
 
     // Download npm package into quarantine
-    let status = std::process::Command::new("npm")
-        .arg("pack")
-        .arg(&pkg_spec)
-        .current_dir(&entry.path)
-        .status();
-
-    match status {
-        Ok(s) if s.success() => {}
-        _ => {
-            eprintln!("{} npm pack failed", "error:".bold().red());
-            return 1;
-        }
+    if let Err(err) = download_package("npm", &pkg_spec, &entry.path) {
+        log_error!("{}", err);
+        return 1;
     }
 
-    // Extract .tgz files so the scanner sees actual source
-    if let Err(err) = extract_archives(&entry.path) {
-        eprintln!(
-            "{} failed to extract archives: {} (scanning raw archives instead)",
-            "warning:".bold().yellow(),
-            err
-        );
-    }
+    // Extract .whl/.zip and .tar.gz/.tgz (recursively) so the scanner sees
+    // actual source
+    let extraction_findings = extract_quarantined_archives(&entry.path, extract_depth, verbose);
 
-    let result = scanner::run_scan(&entry.path, None, None);
-    output::print_scan_summary(&result, format);
-    output::print_findings(&result.findings, format);
-    output::print_verdict(&result.verdict, format);
+    let mut result = scanner::run_package_scan(&entry.path);
+    merge_findings(&mut result, extraction_findings);
+    output::print_scan_result(&result, format);
 
     if auto_approve && result.verdict == scanner::Verdict::LowRisk {
         if let Err(err) = approve_with_ledger(&entry.id, Some("auto-approved: low risk scan")) {
-            eprintln!(
-                "{} failed to auto-approve: {}",
-                "warning:".bold().yellow(),
-                err
-            );
+            log_warn!("failed to auto-approve: {}", err);
         } else {
-            println!("{} auto-approved (low risk)", "sigil:".bold().green());
+            log_info!("auto-approved (low risk)");
+        }
+    }
+
+    match result.verdict {
+        scanner::Verdict::LowRisk => 0,
+        scanner::Verdict::MediumRisk => 1,
+        _ => 2,
+    }
+}
+
+async fn cmd_scan_url(
+    url: &str,
+    keep: bool,
+    max_size_mb: u64,
+    extract_depth: usize,
+    retries: u32,
+    format: &str,
+    verbose: bool,
+) -> i32 {
+    log_info!("downloading {} into quarantine...", url.bold());
+
+    let entry = match quarantine::add(url, "url") {
+        Ok(e) => e,
+        Err(err) => {
+            log_error!("failed to create quarantine entry: {}", err);
+            return 1;
         }
+    };
+
+    if verbose {
+        log_debug!("quarantine id: {}", entry.id);
+    }
+
+    // Throwaway entries are dropped on every exit path unless --keep.
+    let discard = |id: &str| {
+        if keep {
+            return;
+        }
+        if let Err(err) = quarantine::remove(id) {
+            log_warn!("failed to clean up quarantine entry {}: {}", id, err);
+        }
+    };
+
+    let max_bytes = max_size_mb.saturating_mul(1024 * 1024);
+    if let Err(err) = download::download_archive(url, &entry.path, max_bytes, retries).await {
+        log_error!("{}", err);
+        discard(&entry.id);
+        return 1;
+    }
+
+    let extraction_findings = extract_quarantined_archives(&entry.path, extract_depth, verbose);
+
+    let mut result = scanner::run_package_scan(&entry.path);
+    merge_findings(&mut result, extraction_findings);
+    output::print_scan_result(&result, format);
+
+    if keep {
+        log_info!(
+            "kept in quarantine as {} (approve or reject it later)",
+            entry.id
+        );
+    } else {
+        discard(&entry.id);
     }
 
     match result.verdict {
@@ -861,6 +1378,31 @@ async fn cmd_npm(
 #[allow(clippy::too_many_arguments)]
 /// Exit-code contract (ADR-0010): 1 if any finding is at or above the fail
 /// threshold, else 0. Scan errors (handled by the caller) are 2.
+/// Parse an age like `90s`, `30m`, `1h`, `7d`, or `2w` for
+/// `--only-changed-since`.
+fn parse_age(raw: &str) -> Result<std::time::Duration, String> {
+    let raw = raw.trim();
+    let split = raw
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or("missing unit (use s, m, h, d, or w, e.g. 1h)")?;
+    let (count, unit) = raw.split_at(split);
+    let count: u64 = count
+        .parse()
+        .map_err(|_| format!("expected a number followed by a unit, got '{}'", raw))?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 604_800,
+        other => return Err(format!("unknown unit '{}' (use s, m, h, d, or w)", other)),
+    };
+    count
+        .checked_mul(unit_secs)
+        .map(std::time::Duration::from_secs)
+        .ok_or_else(|| format!("duration too large: {}", raw))
+}
+
 fn exit_code_for(findings: &[scanner::Finding], fail_threshold: scanner::Severity) -> i32 {
     if findings.iter().any(|f| f.severity >= fail_threshold) {
         1
@@ -869,133 +1411,415 @@ fn exit_code_for(findings: &[scanner::Finding], fail_threshold: scanner::Severit
     }
 }
 
-/// Shared scan output: summary, findings, verdict, plus the ledger-suppression
-/// attribution when active. In JSON mode the suppression object is emitted
-/// AFTER the findings array, so consumers that parse the first array in the
-/// stream (e.g. scripts/run_eval.py) see only active findings.
-fn print_scan_output(result: &scanner::ScanResult, path: &Path, format: &str) {
+/// Shared scan output: summary, top files, findings, verdict, plus the
+/// ledger-suppression attribution when active. In JSON mode the top-files and
+/// suppression objects are emitted AFTER the findings array, so consumers
+/// that parse the first array in the stream (e.g. scripts/run_eval.py) see
+/// only active findings.
+#[allow(clippy::too_many_arguments)]
+fn print_scan_output(
+    result: &scanner::ScanResult,
+    path: &Path,
+    format: &str,
+    tree: bool,
+    junit_granularity: &str,
+    top: usize,
+    sort: &str,
+    severity_order: &str,
+) {
+    if format == "junit" {
+        output::print_scan_junit(result, &path.to_string_lossy(), junit_granularity);
+        return;
+    }
     if format == "sarif" {
         output::print_scan_sarif(result, &path.to_string_lossy());
         return;
     }
-    output::print_scan_summary(result, format);
-    output::print_findings(&result.findings, format);
+    if format == "html" {
+        output::print_scan_html(result, &path.to_string_lossy());
+        return;
+    }
+    if format == "json" {
+        output::print_scan_json(result, top);
+        return;
+    }
+    output::print_scan_summary(result);
+    output::print_top_files(result, top);
+    if tree {
+        output::print_finding_tree(&result.findings, &path.to_string_lossy());
+    } else {
+        output::print_findings_sorted(&result.findings, format, sort, severity_order);
+    }
     if let Some(by) = &result.suppressed_by {
-        if format == "json" {
-            let obj = serde_json::json!({
-                "suppressed_by": by,
-                "suppressed_findings": result.suppressed_findings,
-            });
-            println!("{}", serde_json::to_string_pretty(&obj).unwrap_or_default());
-        } else {
-            println!(
-                "  {} {} finding{} suppressed by ledger approval ({})",
-                "[*]".green(),
-                result.suppressed_findings.len(),
-                if result.suppressed_findings.len() == 1 {
-                    ""
-                } else {
-                    "s"
-                },
-                by
-            );
-        }
+        output::outln!(
+            "  {} {} finding{} suppressed by ledger approval ({})",
+            "[*]".green(),
+            result.suppressed_findings.len(),
+            if result.suppressed_findings.len() == 1 {
+                ""
+            } else {
+                "s"
+            },
+            by
+        );
     }
-    output::print_verdict(&result.verdict, format);
+    output::print_verdict(&result.verdict);
 }
 
-#[allow(clippy::too_many_arguments)]
-async fn cmd_scan(
-    path: &Path,
-    phases: &str,
-    severity: &str,
-    submit: bool,
-    no_cache: bool,
-    enrich: bool,
-    enhanced: bool,
-    fail_on: &str,
-    ignore_ledger: bool,
-    format: &str,
-    verbose: bool,
-) -> i32 {
+async fn cmd_scan(args: ScanArgs, format: &str, verbose: bool) -> i32 {
+    let ScanArgs {
+        path,
+        phases,
+        severity,
+        min_confidence,
+        submit,
+        no_cache,
+        enrich,
+        no_threat_cache,
+        enhanced,
+        fail_on,
+        ignore_ledger,
+        timeout,
+        timeout_per_file,
+        baseline,
+        update_baseline,
+        yes,
+        baseline_auto,
+        rules_dir,
+        strict,
+        metrics,
+        diff_only,
+        only_changed_since,
+        tree,
+        interactive,
+        junit_granularity,
+        skip_hidden,
+        max_findings,
+        allowlist: allowlist_path,
+        no_provenance_git,
+        on_critical,
+        top,
+        sort,
+        severity_order,
+        checkpoint: checkpoint_path,
+    } = args;
+    let path = path.as_path();
+    let severity = config::resolve().severity(severity.as_deref());
+    let baseline = baseline.as_deref();
+    let update_baseline = update_baseline.then_some(yes);
+    let metrics = metrics.as_ref().map(|file| file.as_deref());
+    let max_findings = max_findings.map(std::num::NonZeroUsize::get);
+    let allowlist_path = allowlist_path.as_deref();
+    let checkpoint_path = checkpoint_path.as_deref();
     // Exit-code contract (ADR-0010): 2 = scan error.
     if !path.exists() {
-        eprintln!(
-            "{} path does not exist: {}",
-            "error:".bold().red(),
-            path.display()
-        );
+        log_error!("path does not exist: {}", path.display());
+        return 2;
+    }
+    // A misspelled filter must not pass as a clean scan.
+    let phase_filter = match scanner::parse_phase_filter(&phases) {
+        Ok(filter) => filter,
+        Err(err) => {
+            log_error!("{}", err);
+            return 2;
+        }
+    };
+    if let Err(err) = scanner::parse_severity(&severity) {
+        log_error!("--severity: {}", err);
+        return 2;
+    }
+    if let Some(dir) = rules_dir.as_deref().filter(|d| !d.is_dir()) {
+        log_error!("rules directory does not exist: {}", dir.display());
+        return 2;
+    }
+    if let Some(file) = allowlist_path.filter(|f| !f.is_file()) {
+        log_error!("allowlist does not exist: {}", file.display());
         return 2;
     }
+    let allowlist = match allowlist::load(allowlist_path.unwrap_or(&allowlist::default_path())) {
+        Ok(list) => (!list.is_empty()).then(|| std::sync::Arc::new(list)),
+        Err(err) => {
+            log_error!("{}", err);
+            return 2;
+        }
+    };
+    // The cloud features share one client; a proxy or CA bundle that cannot
+    // be used is a configuration error, reported before anything is scanned.
+    let client = if enrich || enhanced || submit {
+        match api::SigilClient::new(None) {
+            Ok(client) => Some(client),
+            Err(err) => {
+                log_error!("{}", err);
+                return 2;
+            }
+        }
+    } else {
+        None
+    };
+    // Cached results were produced without these rules, strict checks, or
+    // this allowlist (the default one is part of the cache key).
+    let no_cache =
+        no_cache || rules_dir.is_some() || strict || allowlist_path.is_some() || no_provenance_git;
+
+    // Ratchet baseline: findings already recorded there are not reported,
+    // scored, or allowed to fail the scan.
+    let baseline_findings = match baseline.map(diff::load_baseline_findings).transpose() {
+        Ok(findings) => findings,
+        Err(err) => {
+            log_error!("{}", err);
+            return 2;
+        }
+    };
+    // Diff-only: findings outside the lines added since the base ref are
+    // dropped like baseline ones, so legacy code cannot fail the change.
+    let added_lines = match diff_only.map(|base| gitdiff::added_lines(path, &base)) {
+        Some(Ok(added)) => Some(added),
+        Some(Err(err)) => {
+            log_error!("--diff-only: {}", err);
+            return 2;
+        }
+        None => None,
+    };
+    // Findings a reviewer accepted for this target.  They live in the
+    // Sigil home, never in the scanned tree, so a package cannot accept its
+    // own findings.
+    if path.join(suppressions::LEGACY_FILE_NAME).is_file() {
+        log_warn!(
+            "ignoring {} inside the scanned tree; accepted findings are kept in {}",
+            suppressions::LEGACY_FILE_NAME,
+            suppressions::file_for(path).display()
+        );
+    }
+    let accepted = suppressions::load(path);
+    let apply_baseline = |result: &mut scanner::ScanResult| {
+        let dropped = suppressions::apply(result, &accepted);
+        if dropped > 0 {
+            log_info!(
+                "{} finding(s) accepted in {} not reported",
+                dropped,
+                suppressions::file_for(path).display()
+            );
+        }
+        if let Some(known) = &baseline_findings {
+            let suppressed = diff::apply_baseline(result, known);
+            if verbose {
+                log_debug!("baseline: {} known finding(s) suppressed", suppressed);
+            }
+        }
+        if let Some(added) = &added_lines {
+            let dropped = gitdiff::apply_added_lines(result, added);
+            if verbose {
+                log_debug!(
+                    "diff-only: {} finding(s) outside added lines of {} changed file(s) dropped",
+                    dropped,
+                    added.file_count()
+                );
+            }
+        }
+    };
 
     // Threshold at/above which a finding makes the scan fail (exit 1).
-    let fail_threshold = match fail_on.to_lowercase().as_str() {
-        "low" => scanner::Severity::Low,
-        "medium" => scanner::Severity::Medium,
-        "high" => scanner::Severity::High,
-        "critical" => scanner::Severity::Critical,
-        other => {
-            eprintln!(
-                "{} invalid --fail-on '{}' (use low, medium, high, critical)",
-                "error:".bold().red(),
-                other
-            );
+    let fail_threshold = match scanner::parse_severity(&fail_on) {
+        Ok(threshold) => threshold,
+        Err(err) => {
+            log_error!("--fail-on: {}", err);
             return 2;
         }
     };
+    let mut post_scan_hooks: Vec<post_scan::PostScanHook> =
+        post_scan::configured().into_iter().collect();
+    if let Some(command) = on_critical {
+        match post_scan::split_command(&command) {
+            Ok(argv) => post_scan_hooks.push(post_scan::PostScanHook {
+                argv,
+                min: scanner::Severity::Critical,
+            }),
+            Err(err) => {
+                log_error!("--on-critical: {}", err);
+                return 2;
+            }
+        }
+    }
+    let run_hooks = |result: &scanner::ScanResult| {
+        for hook in post_scan_hooks.iter().filter(|hook| hook.applies(result)) {
+            match hook.run(result, path) {
+                Ok(out) if verbose && !out.is_empty() => log_debug!("post-scan hook: {}", out),
+                Ok(_) => {}
+                Err(err) => log_warn!("post-scan hook failed: {}", err),
+            }
+        }
+    };
     let exit_for =
         |findings: &[scanner::Finding]| -> i32 { exit_code_for(findings, fail_threshold) };
+    let print_metrics = |result: &scanner::ScanResult| {
+        if let Some(file) = metrics {
+            if let Err(err) = output::print_metrics_line(result, file) {
+                log_warn!("failed to write metrics: {}", err);
+            }
+        }
+    };
 
-    println!(
-        "{} scanning {}...",
-        "sigil:".bold().cyan(),
-        path.display().to_string().bold()
-    );
+    // Status lines go to stderr so stdout carries only the report.
+    log_info!("scanning {}...", path.display().to_string().bold());
 
     // --- Cache: only use when running a full unfiltered scan ---
-    let use_cache = !no_cache && phases == "all" && severity == "low";
+    // A cached result covers every file, not just the recently modified or
+    // non-hidden ones, and is never truncated.
+    // Cached results do not keep the list of scanned files the per-file
+    // JUnit report is built from.
+    let use_cache = !no_cache
+        && phase_filter.is_none()
+        && severity == "low"
+        && min_confidence.is_none()
+        && only_changed_since.is_none()
+        && !skip_hidden
+        && max_findings.is_none()
+        && !(format == "junit" && junit_granularity == "file");
 
     // Try loading from cache
     if use_cache {
         if let Some(mut cached) = cache::load_cached(path) {
-            println!("{} using cached result", "sigil:".bold().green(),);
+            log_info!("using cached result");
+            // Scored under the weights and decay of the run that cached it.
+            scanner::scoring::rescore(&mut cached);
             // Re-evaluate ledger suppression against the CURRENT ledger: a pin
             // approved or revoked since the cache was written must take effect.
             ledger::apply_suppression(&mut cached, path, ignore_ledger);
-            print_scan_output(&cached, path, format);
-            return exit_for(&cached.findings);
+            if let Some(client) = client.as_ref().filter(|_| enrich) {
+                enrich_with_file_hashes(client, path, &mut cached, no_threat_cache, verbose).await;
+            }
+            cached.sort_findings();
+            let full = cached.clone();
+            apply_baseline(&mut cached);
+            if interactive {
+                review_findings(&mut cached, path);
+            }
+            let new_findings = if baseline_auto {
+                Some(report_against_previous(path, &cached, format))
+            } else {
+                print_scan_output(
+                    &cached,
+                    path,
+                    format,
+                    tree,
+                    &junit_granularity,
+                    top,
+                    &sort,
+                    &severity_order,
+                );
+                None
+            };
+            print_metrics(&cached);
+            run_hooks(&cached);
+            if let (Some(file), Some(assume_yes)) = (baseline, update_baseline) {
+                if !rewrite_baseline(file, &full, assume_yes) {
+                    return 2;
+                }
+            }
+            return exit_for(new_findings.as_deref().unwrap_or(&cached.findings));
         } else if verbose {
-            eprintln!("no cache entry found, scanning fresh");
+            log_debug!("no cache entry found, scanning fresh");
         }
     }
 
-    // Parse phase filter
-    let phase_filter: Option<Vec<String>> = if phases == "all" {
-        None
-    } else {
-        Some(phases.split(',').map(|s| s.trim().to_string()).collect())
-    };
-
     // Parse severity filter
     let min_severity: Option<&str> = if severity == "low" {
         None // "low" is the default minimum, meaning show everything
     } else {
-        Some(severity)
+        Some(&severity)
     };
 
-    let mut result = scanner::run_scan(path, phase_filter.as_deref(), min_severity);
+    // Per-file cache: unchanged files reuse their findings from earlier scans
+    // even when the directory as a whole changed.
+    let file_cache = (!no_cache && phase_filter.is_none())
+        .then(|| std::sync::Arc::new(cache::FileCache::load()));
+    let checkpoint = checkpoint_path.map(|file| {
+        let checkpoint = cache::Checkpoint::open(file, phase_filter.as_deref());
+        if checkpoint.recorded() > 0 {
+            log_info!(
+                "resuming from {}: {} file(s) already scanned",
+                file.display(),
+                checkpoint.recorded()
+            );
+        }
+        std::sync::Arc::new(checkpoint)
+    });
+    let options = scanner::ScanOptions {
+        timeout: timeout.map(std::time::Duration::from_secs),
+        file_cache: file_cache.clone(),
+        checkpoint: checkpoint.clone(),
+        rules_dir,
+        strict,
+        file_timeout: timeout_per_file
+            .filter(|&secs| secs > 0)
+            .map(std::time::Duration::from_secs),
+        // A window reaching past the epoch just means every file.
+        modified_since: only_changed_since.map(|age| {
+            std::time::SystemTime::now()
+                .checked_sub(age)
+                .unwrap_or(std::time::UNIX_EPOCH)
+        }),
+        skip_hidden,
+        max_findings,
+        allowlist,
+        timings: verbose,
+        no_provenance_git,
+        min_confidence,
+    };
+    let mut result =
+        scanner::run_scan_with_options(path, phase_filter.as_deref(), min_severity, &options);
+    for line in output::phase_timing_lines(&result) {
+        log_debug!("{}", line);
+    }
+    if let Some(checkpoint) = &checkpoint {
+        if verbose {
+            log_debug!(
+                "checkpoint: {} file(s) taken from an earlier run",
+                checkpoint.resumed()
+            );
+        }
+        // A scan cut short by its deadline or the findings cap keeps its
+        // checkpoint for the next run.
+        if !result.timed_out && !result.truncated {
+            checkpoint.finish();
+        }
+    }
+    if let Some(file_cache) = &file_cache {
+        if verbose {
+            let (hits, misses) = file_cache.stats();
+            log_debug!("file cache: {} hit(s), {} rescanned", hits, misses);
+        }
+        if let Err(err) = file_cache.save() {
+            if verbose {
+                log_debug!("file cache save failed: {}", err);
+            }
+        }
+    }
+    if let (true, Some(age)) = (verbose, only_changed_since) {
+        log_debug!(
+            "only-changed-since: {} file(s) modified in the last {:?} content-scanned",
+            result.files_scanned,
+            age
+        );
+    }
+    if verbose && result.timed_out {
+        log_debug!(
+            "scan deadline reached: {} file(s) not scanned",
+            result.unscanned_files
+        );
+    }
 
     // OSV advisory feed (US-E1): append CVE/MAL- findings from lockfiles.
-    // Runs whenever a full-phase scan is requested (phases == "all").
+    // Runs whenever a full-phase scan is requested (no phase filter).
     // Network failures are handled inside scan_for_osv_findings — never fatal.
-    if phases == "all" {
+    if phase_filter.is_none() {
         // The three feeds make network round-trips (OSV detail fetches, npm/PyPI
         // registry lookups). --verbose reports each feed's wall-clock so a slow
         // scan can be attributed to a specific feed rather than guessed at.
         let t = std::time::Instant::now();
         let osv_findings = feeds::osv::scan_for_osv_findings(path);
         if verbose {
-            eprintln!(
+            log_debug!(
                 "feed osv: {:?} ({} findings)",
                 t.elapsed(),
                 osv_findings.len()
@@ -1010,7 +1834,7 @@ async fn cmd_scan(
         let t = std::time::Instant::now();
         feeds::enrichment::enrich_findings_with_kev_epss(&mut result.findings, None, None);
         if verbose {
-            eprintln!("feed kev_epss: {:?}", t.elapsed());
+            log_debug!("feed kev_epss: {:?}", t.elapsed());
         }
 
         // Provenance drift detection (US-E3): detect downgrade, identity-change,
@@ -1021,7 +1845,7 @@ async fn cmd_scan(
         let prov_findings =
             provenance::scan_for_provenance_drift(path, &provenance::ScanOptions::default());
         if verbose {
-            eprintln!(
+            log_debug!(
                 "feed provenance: {:?} ({} findings)",
                 t.elapsed(),
                 prov_findings.len()
@@ -1041,8 +1865,7 @@ async fn cmd_scan(
 
         // Recompute score and verdict with the enriched finding set.
         if !result.findings.is_empty() {
-            result.score = scanner::scoring::calculate_score(&result.findings);
-            result.verdict = scanner::scoring::determine_verdict(&result.findings, result.score);
+            scanner::scoring::rescore(&mut result);
         }
     }
 
@@ -1052,90 +1875,135 @@ async fn cmd_scan(
     // and feed so a RUGPULL-001 drift signal can veto suppression.
     let suppressed = ledger::apply_suppression(&mut result, path, ignore_ledger);
     if verbose && suppressed {
-        eprintln!(
+        log_debug!(
             "ledger: {} finding(s) suppressed ({})",
             result.suppressed_findings.len(),
             result.suppressed_by.as_deref().unwrap_or("")
         );
     }
 
-    print_scan_output(&result, path, format);
+    // Feed and ledger findings were appended; restore the canonical order.
+    result.sort_findings();
+    // Cache the full result: the baseline is a per-invocation view of it.
+    // Timings describe this run only, so a cache hit never replays them.
+    let cacheable = (use_cache && !result.timed_out).then(|| scanner::ScanResult {
+        phase_timings: None,
+        ..result.clone()
+    });
+    // Threat intel changes independently of the content, so it is looked up
+    // fresh on every run rather than cached.
+    if let Some(client) = client.as_ref().filter(|_| enrich) {
+        enrich_with_file_hashes(client, path, &mut result, no_threat_cache, verbose).await;
+        result.sort_findings();
+    }
+    let full = update_baseline.map(|_| result.clone());
+    apply_baseline(&mut result);
+    if interactive {
+        review_findings(&mut result, path);
+    }
 
-    // Save to cache
-    if use_cache {
+    let new_findings = if baseline_auto {
+        Some(report_against_previous(path, &result, format))
+    } else {
+        print_scan_output(
+            &result,
+            path,
+            format,
+            tree,
+            &junit_granularity,
+            top,
+            &sort,
+            &severity_order,
+        );
+        None
+    };
+    print_metrics(&result);
+    run_hooks(&result);
+    let baseline_written = match (baseline, update_baseline, &full) {
+        (Some(file), Some(assume_yes), Some(full)) => rewrite_baseline(file, full, assume_yes),
+        _ => true,
+    };
+
+    // Save to cache (never a partial result: a later run must rescan fully)
+    if let Some(result) = cacheable {
         if let Err(err) = cache::save_to_cache(path, &result) {
             if verbose {
-                eprintln!("cache save failed: {}", err);
+                log_debug!("cache save failed: {}", err);
             }
         } else if verbose {
-            eprintln!("result cached successfully");
+            log_debug!("result cached successfully");
         }
     }
 
     // --- Cloud threat enrichment -------------------------------------------
-    if enrich {
+    if let Some(client) = client.as_ref().filter(|_| enrich) {
         let dir_hash = compute_directory_hash(path);
         if verbose {
-            eprintln!("directory hash: {}", dir_hash);
-            eprintln!("checking hash against cloud threat database...");
+            log_debug!("directory hash: {}", dir_hash);
+            log_debug!("checking hash against cloud threat database...");
         }
 
-        let client = api::SigilClient::new(None);
-        match client.lookup_threat(&dir_hash).await {
+        let mut cache = threat_cache::ThreatCache::load();
+        let lookup = threat_cache::lookup_threats(
+            client,
+            &mut cache,
+            std::slice::from_ref(&dir_hash),
+            1,
+            no_threat_cache,
+        )
+        .await
+        .pop()
+        .map(|(_, lookup)| lookup);
+        match lookup.expect("one result per hash") {
             Ok(info) => {
                 if info.known_malicious {
-                    println!(
-                        "\n  {} {} is a known threat: {}",
+                    log_warn!(
+                        path = path.display().to_string(),
+                        threat_type = info.threat_type.clone();
+                        "{} {} is a known threat: {}{}",
                         "THREAT INTEL:".bold().red(),
                         path.display(),
-                        info.description.as_deref().unwrap_or("no description")
+                        info.description.as_deref().unwrap_or("no description"),
+                        info.threat_type
+                            .as_deref()
+                            .map(|t| format!(" (type: {})", t))
+                            .unwrap_or_default()
                     );
-                    if let Some(threat_type) = &info.threat_type {
-                        println!("  Type: {}", threat_type);
-                    }
                 } else if verbose {
-                    eprintln!("no threat intel match for this target");
+                    log_debug!("no threat intel match for this target");
                 }
             }
             Err(err) => {
                 if verbose {
-                    eprintln!(
-                        "{} cloud enrichment unavailable: {}",
-                        "warning:".bold().yellow(),
-                        err
-                    );
+                    log_warn!("cloud enrichment unavailable: {}", err);
                 }
             }
         }
     }
 
     // --- Enhanced LLM analysis (Pro feature) -------------------------------
-    if enhanced {
-        let client = api::SigilClient::new(None);
-
+    if let Some(client) = client.as_ref().filter(|_| enhanced) {
         if !client.is_authenticated() {
-            eprintln!(
-                "{} Enhanced scanning requires authentication. Run: sigil login",
-                "error:".bold().red()
-            );
+            if client.session_expired() {
+                log_error!("{}", api::SESSION_EXPIRED_HINT);
+            } else {
+                log_error!("Enhanced scanning requires authentication. Run: sigil login");
+            }
             return 1;
         }
 
         if verbose {
-            eprintln!("collecting file contents for LLM analysis...");
+            log_debug!("collecting file contents for LLM analysis...");
         }
 
         // Collect file contents for LLM analysis (limit to reasonable size)
         let file_contents = collect_file_contents(path, 50, verbose);
 
         if file_contents.is_empty() {
-            eprintln!(
-                "{} no readable files found for LLM analysis",
-                "warning:".bold().yellow()
-            );
+            log_warn!("no readable files found for LLM analysis");
         } else {
             if verbose {
-                eprintln!(
+                log_debug!(
                     "submitting {} files for enhanced LLM analysis...",
                     file_contents.len()
                 );
@@ -1143,48 +2011,43 @@ async fn cmd_scan(
 
             match client.submit_enhanced_scan(&result, file_contents).await {
                 Ok(response) => {
-                    println!(
-                        "\n{} Enhanced LLM analysis completed",
-                        "sigil:".bold().green()
-                    );
+                    log_info!("Enhanced LLM analysis completed");
                     if verbose {
-                        eprintln!("  Scan ID: {}", response.id);
+                        log_debug!("  Scan ID: {}", response.id);
                         if let Some(msg) = response.message {
-                            eprintln!("  Message: {}", msg);
+                            log_debug!("  Message: {}", msg);
                         }
                     }
                 }
                 Err(err) => {
-                    eprintln!(
-                        "{} Enhanced analysis failed: {}",
-                        "warning:".bold().yellow(),
+                    log_warn!(
+                        "Enhanced analysis failed: {} (continuing with static analysis results only)",
                         err
                     );
-                    eprintln!("  Continuing with static analysis results only");
                 }
             }
         }
     }
 
-    if submit {
+    if let Some(client) = client.as_ref().filter(|_| submit) {
         if verbose {
-            eprintln!("submitting results to Sigil cloud...");
+            log_debug!("submitting results to Sigil cloud...");
         }
-        let client = api::SigilClient::new(None);
         match client.submit_scan(&result).await {
-            Ok(_) => println!(
-                "{} results submitted to Sigil cloud",
-                "sigil:".bold().green()
-            ),
-            Err(err) => eprintln!(
-                "{} failed to submit results: {} (continuing offline)",
-                "warning:".bold().yellow(),
-                err
-            ),
+            Ok(_) => log_info!("results submitted to Sigil cloud"),
+            Err(err) => log_warn!("failed to submit results: {} (continuing offline)", err),
         }
     }
 
-    exit_for(&result.findings)
+    // A timed-out scan that found nothing at the threshold has not shown the
+    // target is clean, so it cannot pass: report it as a scan error instead.
+    // A baseline that could not be written is a failed run, not a pass.
+    // With --baseline-auto only the findings new since the last run count.
+    match exit_for(new_findings.as_deref().unwrap_or(&result.findings)) {
+        _ if !baseline_written => 2,
+        0 if result.timed_out => 2,
+        code => code,
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -1258,7 +2121,7 @@ fn collect_file_contents(
     for entry in entries {
         if files_collected >= max_files {
             if verbose {
-                eprintln!("Reached max file limit ({}) for LLM analysis", max_files);
+                log_debug!("Reached max file limit ({}) for LLM analysis", max_files);
             }
             break;
         }
@@ -1280,7 +2143,7 @@ fn collect_file_contents(
         if let Ok(metadata) = entry.metadata() {
             if metadata.len() > 100_000 {
                 if verbose {
-                    eprintln!(
+                    log_debug!(
                         "Skipping large file: {} ({} bytes)",
                         file_path.display(),
                         metadata.len()
@@ -1296,17 +2159,13 @@ fn collect_file_contents(
                 // Check for binary content (contains null bytes)
                 if bytes.contains(&0) {
                     if verbose {
-                        eprintln!("Skipping binary file: {}", file_path.display());
+                        log_debug!("Skipping binary file: {}", file_path.display());
                     }
                     continue;
                 }
 
                 let contents = String::from_utf8_lossy(&bytes).into_owned();
-                let rel_path = file_path
-                    .strip_prefix(path)
-                    .unwrap_or(file_path)
-                    .to_string_lossy()
-                    .to_string();
+                let rel_path = scanner::relative_path(file_path, path);
 
                 file_contents.insert(rel_path, contents);
                 files_collected += 1;
@@ -1319,175 +2178,589 @@ fn collect_file_contents(
     }
 
     if verbose && files_collected > 0 {
-        eprintln!("Collected {} files for LLM analysis", files_collected);
+        log_debug!("Collected {} files for LLM analysis", files_collected);
     }
 
     file_contents
 }
 
-async fn cmd_diff(baseline_path: &str, scan_path: &Path, format: &str, verbose: bool) -> i32 {
-    // Load baseline
-    let baseline_data = match std::fs::read_to_string(baseline_path) {
-        Ok(data) => data,
-        Err(err) => {
-            eprintln!(
-                "{} failed to read baseline file '{}': {}",
-                "error:".bold().red(),
-                baseline_path,
-                err
-            );
-            return 1;
-        }
-    };
+async fn cmd_verify(
+    ecosystem: &str,
+    package: &str,
+    archive: &Path,
+    format: &str,
+    verbose: bool,
+) -> i32 {
+    if verbose {
+        log_debug!(
+            "fetching published digest for {} {} from the registry...",
+            ecosystem,
+            package
+        );
+    }
 
-    let baseline_result: scanner::ScanResult = match serde_json::from_str(&baseline_data) {
-        Ok(result) => result,
+    let verification = match verify::verify_package(ecosystem, package, archive).await {
+        Ok(v) => v,
         Err(err) => {
-            eprintln!(
-                "{} failed to parse baseline JSON: {}",
-                "error:".bold().red(),
-                err
-            );
-            return 1;
+            log_error!("{}", err);
+            return 2;
         }
     };
 
-    if verbose {
-        eprintln!(
-            "loaded baseline: {} findings, score {}",
-            baseline_result.findings.len(),
-            baseline_result.score
+    if format == "json" {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&verification).unwrap_or_default()
+        );
+    } else if verification.matches {
+        println!(
+            "{} {} {} matches the registry ({} {})",
+            "sigil:".bold().green(),
+            verification.package.bold(),
+            verification.version,
+            verification.algorithm,
+            verification.actual
+        );
+    } else {
+        println!(
+            "{} {} {} does NOT match the registry",
+            "MISMATCH:".bold().red(),
+            verification.package.bold(),
+            verification.version
+        );
+        println!("  archive:  {}", verification.archive);
+        println!(
+            "  expected: {} {}",
+            verification.algorithm, verification.expected
+        );
+        println!(
+            "  actual:   {} {}",
+            verification.algorithm, verification.actual
         );
     }
 
-    // Run current scan
-    let current_result = scanner::run_scan(scan_path, None, None);
-
-    let diff_result = diff::diff_scans(&baseline_result, &current_result);
-
-    if format == "json" {
-        println!("{}", serde_json::to_string_pretty(&diff_result).unwrap());
+    if verification.matches {
+        0
     } else {
-        println!("\n  {} {}", "Scan Diff:".bold(), diff_result.summary);
+        1
+    }
+}
 
-        if !diff_result.new_findings.is_empty() {
-            println!(
-                "\n  {} ({}):",
-                "NEW FINDINGS".bold().red(),
-                diff_result.new_findings.len()
-            );
-            for f in &diff_result.new_findings {
-                println!(
-                    "    {} [{}] {:?} in {} (line {})",
-                    "+".green(),
-                    f.rule,
-                    f.severity,
-                    f.file,
-                    f.line.unwrap_or(0)
-                );
+/// `scan --interactive`: let the user triage the findings of `result`, then
+/// record the accepted ones for `root` (see [`suppressions`]) and drop them
+/// from the result.  Without a terminal, or in a build without the `tui` feature, it
+/// warns and leaves the result for the normal report.
+fn review_findings(result: &mut scanner::ScanResult, root: &Path) {
+    if result.findings.is_empty() {
+        return;
+    }
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        log_warn!("--interactive needs a terminal; printing the report instead");
+        return;
+    }
+    #[cfg(feature = "tui")]
+    {
+        let accepted = match review::run(&result.findings, root) {
+            Ok(Some(accepted)) => accepted,
+            Ok(None) => {
+                log_info!("review abandoned; nothing written");
+                return;
             }
-        }
-
-        if !diff_result.resolved_findings.is_empty() {
-            println!(
-                "\n  {} ({}):",
-                "RESOLVED".bold().green(),
-                diff_result.resolved_findings.len()
-            );
-            for f in &diff_result.resolved_findings {
-                println!(
-                    "    {} [{}] {:?} in {} (line {})",
-                    "-".red(),
-                    f.rule,
-                    f.severity,
-                    f.file,
-                    f.line.unwrap_or(0)
+            Err(err) => {
+                log_warn!("--interactive: {}; printing the report instead", err);
+                return;
+            }
+        };
+        match suppressions::append(root, &accepted) {
+            Ok(added) => {
+                log_info!(
+                    "{} finding(s) accepted, {} new in {}",
+                    accepted.len(),
+                    added,
+                    suppressions::file_for(root).display()
                 );
+                suppressions::apply(result, &suppressions::load(root));
             }
-        }
-
-        if diff_result.new_findings.is_empty() && diff_result.resolved_findings.is_empty() {
-            println!("  {}", "No changes detected.".dimmed());
+            Err(err) => log_warn!("--interactive: {}", err),
         }
     }
-
-    // Exit with non-zero if new findings were introduced
-    if !diff_result.new_findings.is_empty() {
-        2
-    } else {
-        0
+    #[cfg(not(feature = "tui"))]
+    {
+        let _ = root;
+        log_warn!(
+            "--interactive needs a sigil built with the `tui` feature \
+             (cargo install sigil-cli --features tui); printing the report instead"
+        );
     }
 }
 
-async fn cmd_clear_cache() -> i32 {
-    match cache::clear_cache() {
-        Ok(count) => {
-            println!(
-                "{} cleared {} cached scan result(s)",
-                "sigil:".bold().green(),
-                count
+/// Ask `question` on stderr and read the answer from stdin; only `y` or
+/// `yes` agrees.  Callers check that stdin is a terminal first.
+fn ask_yes_no(question: &str) -> bool {
+    use std::io::{BufRead, Write};
+
+    eprint!("{} [y/N] ", question);
+    let _ = std::io::stderr().flush();
+    let mut answer = String::new();
+    let _ = std::io::stdin().lock().read_line(&mut answer);
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// `scan --update-baseline`: accept every finding of `result` by writing
+/// it over the baseline file.  Without `assume_yes` the user is asked, and a
+/// non-interactive run leaves the file alone.  A partial (timed-out) result
+/// would drop findings from the baseline, so it is never written.  Returns
+/// false only when writing the file failed.
+fn rewrite_baseline(file: &Path, result: &scanner::ScanResult, assume_yes: bool) -> bool {
+    if result.timed_out {
+        log_warn!("baseline not updated: the scan timed out before covering every file");
+        return true;
+    }
+    if !assume_yes {
+        if !std::io::stdin().is_terminal() {
+            log_warn!(
+                "baseline not updated: pass --yes to overwrite {} without a prompt",
+                file.display()
             );
-            0
+            return true;
         }
-        Err(err) => {
-            eprintln!("{} failed to clear cache: {}", "error:".bold().red(), err);
-            1
+        if !ask_yes_no(&format!(
+            "Overwrite {} with {} finding(s)?",
+            file.display(),
+            result.findings.len()
+        )) {
+            log_info!("baseline left unchanged");
+            return true;
         }
     }
-}
-
-async fn cmd_fetch(force: bool, verbose: bool) -> i32 {
-    println!(
-        "{} fetching latest threat signatures...",
-        "sigil:".bold().cyan()
-    );
-
-    let client = api::SigilClient::new(None);
-    match client.get_signatures(force).await {
-        Ok(count) => {
-            println!("{} fetched {} signatures", "sigil:".bold().green(), count);
-            0
+    match diff::save_baseline(file, result) {
+        Ok(()) => {
+            log_info!(
+                "baseline {} updated: {} finding(s) accepted",
+                file.display(),
+                result.findings.len()
+            );
+            true
         }
         Err(err) => {
-            eprintln!(
-                "{} failed to fetch signatures: {}",
-                "error:".bold().red(),
-                err
-            );
-            if verbose {
-                eprintln!("hint: check your network connection or API token");
-            }
-            1
+            log_error!("{}", err);
+            false
         }
     }
 }
 
-async fn cmd_approve(id: &str, reason: Option<&str>, verbose: bool) -> i32 {
-    if verbose {
-        eprintln!("approving quarantine entry: {}", id);
+/// `scan --baseline-auto`: print `result` as a diff against the previous
+/// result of `path` kept in the cache, then keep `result` in its place.
+/// Returns the new findings, which are all the exit code is judged on.  On
+/// the first run there is nothing to compare, so every finding is part of
+/// the baseline and none is new.
+fn report_against_previous(
+    path: &Path,
+    result: &scanner::ScanResult,
+    format: &str,
+) -> Vec<scanner::Finding> {
+    let previous = cache::load_previous(path);
+    if previous.is_none() {
+        log_info!(
+            "no earlier --baseline-auto scan of {}; this scan is the baseline",
+            path.display()
+        );
+    }
+    let previous = previous.unwrap_or_else(|| result.clone());
+    if let Some(warning) = diff::ruleset_drift(&previous, result) {
+        log_warn!("{}", warning);
+    }
+    let diff_result = diff::diff_scans(&previous, result);
+    match format {
+        "sarif" => output::print_diff_sarif(&diff_result, &path.to_string_lossy(), "previous scan"),
+        "json" => output::outln!("{}", serde_json::to_string_pretty(&diff_result).unwrap()),
+        _ => print_diff_text(&diff_result),
     }
 
-    let (entry, rec) = match approve_with_ledger(id, reason) {
-        Ok(result) => result,
-        Err(err) => {
-            eprintln!("{} {}", "error:".bold().red(), err);
-            return 1;
-        }
-    };
+    // A partial result would turn every file it missed into new findings
+    // next time.
+    if result.timed_out || result.truncated {
+        log_warn!("scan incomplete: the --baseline-auto baseline was not updated");
+    } else if let Err(err) = cache::save_previous(path, result) {
+        log_warn!("failed to record the --baseline-auto baseline: {}", err);
+    }
+    diff_result.new_findings
+}
 
-    println!(
-        "{} approved {} ({})",
-        "sigil:".bold().green(),
-        entry.id,
-        entry.source
-    );
-    println!(
-        "  pinned {} files (digest {})",
-        rec.pin.file_count,
-        &rec.pin.artifact_digest[..rec.pin.artifact_digest.len().min(12)]
-    );
-    if !rec.pin.tool_definitions.is_empty() || !rec.pin.instruction_files.is_empty() {
-        println!(
+/// Load a baseline scan result saved as JSON.
+fn load_baseline_result(baseline_path: &str) -> Result<scanner::ScanResult, String> {
+    let baseline_data = std::fs::read_to_string(baseline_path)
+        .map_err(|err| format!("failed to read baseline file '{}': {}", baseline_path, err))?;
+    serde_json::from_str(&baseline_data)
+        .map_err(|err| format!("failed to parse baseline JSON: {}", err))
+}
+
+async fn cmd_diff(
+    baseline_path: Option<&str>,
+    baseline_dir: Option<&Path>,
+    scan_path: &Path,
+    format: &str,
+    verbose: bool,
+) -> i32 {
+    let (baseline_result, current_result) = match (baseline_dir, baseline_path) {
+        // Two versions of a package: scan both, each relative to its own root.
+        (Some(dir), _) => {
+            for p in [dir, scan_path] {
+                if !p.exists() {
+                    log_error!("path does not exist: {}", p.display());
+                    return 1;
+                }
+            }
+            let (old_root, new_root) = if dir.is_dir() && scan_path.is_dir() {
+                (diff::package_root(dir), diff::package_root(scan_path))
+            } else {
+                (dir.to_path_buf(), scan_path.to_path_buf())
+            };
+            log_info!("scanning baseline {}...", old_root.display());
+            let baseline = scanner::run_scan(&old_root, None, None);
+            log_info!("scanning {}...", new_root.display());
+            (baseline, scanner::run_scan(&new_root, None, None))
+        }
+        (None, Some(baseline_path)) => match load_baseline_result(baseline_path) {
+            Ok(baseline) => (baseline, scanner::run_scan(scan_path, None, None)),
+            Err(err) => {
+                log_error!("{}", err);
+                return 1;
+            }
+        },
+        (None, None) => {
+            log_error!("diff needs --baseline <FILE> or --baseline-dir <DIR>");
+            return 2;
+        }
+    };
+
+    if verbose {
+        log_debug!(
+            "loaded baseline: {} findings, score {}",
+            baseline_result.findings.len(),
+            baseline_result.score
+        );
+    }
+
+    if let Some(warning) = diff::ruleset_drift(&baseline_result, &current_result) {
+        log_warn!("{}", warning);
+    }
+    let diff_result = diff::diff_scans(&baseline_result, &current_result);
+
+    if format == "sarif" {
+        let baseline = match (baseline_dir, baseline_path) {
+            (Some(dir), _) => dir.display().to_string(),
+            (None, path) => path.unwrap_or_default().to_string(),
+        };
+        output::print_diff_sarif(&diff_result, &scan_path.to_string_lossy(), &baseline);
+    } else if format == "json" {
+        output::outln!("{}", serde_json::to_string_pretty(&diff_result).unwrap());
+    } else {
+        print_diff_text(&diff_result);
+    }
+
+    // Exit with non-zero if new findings were introduced
+    if !diff_result.new_findings.is_empty() {
+        2
+    } else {
+        0
+    }
+}
+
+/// `diff --compare-registry`: download the published release of the
+/// package at `path`, scan it and the local copy, and report what the local
+/// copy adds.  Exits 2 when it has findings the release does not.
+async fn cmd_compare_registry(
+    path: &Path,
+    ecosystem: Option<&str>,
+    package: Option<&str>,
+    format: &str,
+    verbose: bool,
+) -> i32 {
+    if !path.is_dir() {
+        log_error!("not a package directory: {}", path.display());
+        return 1;
+    }
+    let mut target = match upstream::resolve(path, ecosystem, package) {
+        Ok(target) => target,
+        Err(err) => {
+            log_error!("{}", err);
+            return 1;
+        }
+    };
+    let version = match target.version.clone() {
+        Some(version) => version,
+        None => match verify::latest_version(&target.ecosystem, &target.name).await {
+            Ok(version) => {
+                log_warn!(
+                    "{} does not record its version; comparing with the latest release, {}",
+                    path.display(),
+                    version
+                );
+                version
+            }
+            Err(err) => {
+                log_error!(
+                    "cannot resolve the latest release of {}: {}",
+                    target.name,
+                    err
+                );
+                return 1;
+            }
+        },
+    };
+    target.version = Some(version.clone());
+    let spec = target.spec(&version);
+
+    let scratch = paths::in_home("upstream").join(uuid::Uuid::new_v4().to_string());
+    if let Err(err) = std::fs::create_dir_all(&scratch) {
+        log_error!("failed to create {}: {}", scratch.display(), err);
+        return 1;
+    }
+    log_info!(
+        "downloading {} {} from the registry...",
+        target.ecosystem,
+        spec.bold()
+    );
+    let compared = compare_with_release(path, &scratch, &target.ecosystem, &spec, verbose);
+    let _ = std::fs::remove_dir_all(&scratch);
+    let (content, diff_result) = match compared {
+        Ok(compared) => compared,
+        Err(err) => {
+            log_error!("{}", err);
+            return 1;
+        }
+    };
+
+    if format == "sarif" {
+        let baseline = format!("{}:{}", target.ecosystem, spec);
+        output::print_diff_sarif(&diff_result, &path.to_string_lossy(), &baseline);
+    } else if format == "json" {
+        let mut doc = serde_json::json!(diff_result);
+        doc["upstream"] = serde_json::json!(target);
+        doc["content_diff"] = serde_json::json!(content);
+        output::outln!("{}", serde_json::to_string_pretty(&doc).unwrap());
+    } else {
+        output::outln!("\n  {} {} {}", "Upstream:".bold(), target.ecosystem, spec);
+        output::outln!(
+            "  {} {} modified, {} added, {} removed, {} unchanged",
+            "Files:".bold(),
+            content.modified.len(),
+            content.added.len(),
+            content.removed.len(),
+            content.unchanged
+        );
+        for (mark, files) in [
+            ("M".yellow(), &content.modified),
+            ("A".green(), &content.added),
+            ("D".red(), &content.removed),
+        ] {
+            for file in files.iter().take(20) {
+                output::outln!("    {} {}", mark, file);
+            }
+            if files.len() > 20 {
+                output::outln!("    {} ... and {} more", mark, files.len() - 20);
+            }
+        }
+        print_diff_text(&diff_result);
+    }
+
+    if !diff_result.new_findings.is_empty() {
+        2
+    } else {
+        0
+    }
+}
+
+/// Download `spec` into `scratch`, unpack it, and compare it with the local
+/// copy at `local`: the file-level changes and the findings diff, with the
+/// release as the baseline.
+fn compare_with_release(
+    local: &Path,
+    scratch: &Path,
+    ecosystem: &str,
+    spec: &str,
+    verbose: bool,
+) -> Result<(upstream::ContentDiff, diff::ScanDiff), String> {
+    download_package(ecosystem, spec, scratch)?;
+    let extraction_findings =
+        extract_quarantined_archives(scratch, archive::DEFAULT_MAX_DEPTH, verbose);
+    let release = upstream::comparable_root(&upstream::unpacked_root(scratch), local);
+    if verbose {
+        log_debug!("comparing {} with {}", local.display(), release.display());
+    }
+    let content = upstream::content_diff(&release, local);
+
+    // Both sides are package trees, so neither is expected to be a git
+    // checkout.
+    log_info!("scanning release {}...", spec);
+    let mut baseline = scanner::run_package_scan(&release);
+    merge_findings(&mut baseline, extraction_findings);
+    log_info!("scanning {}...", local.display());
+    let current = scanner::run_package_scan(local);
+    Ok((content, diff::diff_scans(&baseline, &current)))
+}
+
+/// The new, resolved, and modified findings of `diff`, as `sigil diff`
+/// prints them.
+fn print_diff_text(diff_result: &diff::ScanDiff) {
+    output::outln!("\n  {} {}", "Scan Diff:".bold(), diff_result.summary);
+
+    if !diff_result.new_findings.is_empty() {
+        output::outln!(
+            "\n  {} ({}):",
+            "NEW FINDINGS".bold().red(),
+            diff_result.new_findings.len()
+        );
+        for f in &diff_result.new_findings {
+            output::outln!(
+                "    {} [{}] {:?} in {} (line {})",
+                "+".green(),
+                f.rule,
+                f.severity,
+                f.file,
+                f.line.unwrap_or(0)
+            );
+        }
+    }
+
+    if !diff_result.resolved_findings.is_empty() {
+        output::outln!(
+            "\n  {} ({}):",
+            "RESOLVED".bold().green(),
+            diff_result.resolved_findings.len()
+        );
+        for f in &diff_result.resolved_findings {
+            output::outln!(
+                "    {} [{}] {:?} in {} (line {})",
+                "-".red(),
+                f.rule,
+                f.severity,
+                f.file,
+                f.line.unwrap_or(0)
+            );
+        }
+    }
+
+    if !diff_result.modified_findings.is_empty() {
+        output::outln!(
+            "\n  {} ({}):",
+            "MODIFIED".bold().yellow(),
+            diff_result.modified_findings.len()
+        );
+        for m in &diff_result.modified_findings {
+            output::outln!(
+                "    {} [{}] {:?} in {} (line {} -> {})",
+                "~".yellow(),
+                m.after.rule,
+                m.after.severity,
+                m.after.file,
+                m.before.line.unwrap_or(0),
+                m.after.line.unwrap_or(0)
+            );
+            output::outln!("        {} {}", "-".red(), m.before.snippet.dimmed());
+            output::outln!("        {} {}", "+".green(), m.after.snippet);
+        }
+    }
+
+    if diff_result.new_findings.is_empty()
+        && diff_result.resolved_findings.is_empty()
+        && diff_result.modified_findings.is_empty()
+    {
+        output::outln!("  {}", "No changes detected.".dimmed());
+    }
+}
+
+async fn cmd_clear_cache() -> i32 {
+    match cache::clear_cache() {
+        Ok(count) => {
+            println!(
+                "{} cleared {} cached scan result(s)",
+                "sigil:".bold().green(),
+                count
+            );
+            0
+        }
+        Err(err) => {
+            log_error!("failed to clear cache: {}", err);
+            1
+        }
+    }
+}
+
+async fn cmd_fetch(force: bool, dry_run: bool, verbose: bool) -> i32 {
+    log_info!("fetching latest threat signatures...");
+
+    let client = match api::SigilClient::new(None) {
+        Ok(client) => client,
+        Err(err) => {
+            log_error!("{}", err);
+            return 2;
+        }
+    };
+    match client.get_signatures(force, dry_run).await {
+        Ok(delta) => {
+            println!(
+                "{} {} {} new, {} updated, {} total",
+                "sigil:".bold().green(),
+                if dry_run {
+                    "dry run, nothing written:"
+                } else {
+                    "fetched signatures:"
+                },
+                delta.new.len(),
+                delta.updated.len(),
+                delta.total
+            );
+            if verbose {
+                for id in &delta.new {
+                    println!("  {} {}", "+".green(), id);
+                }
+                for id in &delta.updated {
+                    println!("  {} {}", "~".yellow(), id);
+                }
+            }
+            0
+        }
+        Err(err) => {
+            log_error!("failed to fetch signatures: {}", err);
+            if verbose {
+                log_debug!("hint: check your network connection or API token");
+            }
+            1
+        }
+    }
+}
+
+async fn cmd_approve(id: &str, reason: Option<&str>, verbose: bool) -> i32 {
+    if verbose {
+        log_debug!("approving quarantine entry: {}", id);
+    }
+
+    let (entry, rec) = match quarantine::resolve_id(id)
+        .map_err(String::from)
+        .and_then(|id| approve_with_ledger(&id, reason))
+    {
+        Ok(result) => result,
+        Err(err) => {
+            log_error!("{}", err);
+            return 1;
+        }
+    };
+
+    println!(
+        "{} approved {} ({})",
+        "sigil:".bold().green(),
+        entry.id,
+        entry.source
+    );
+    println!(
+        "  pinned {} files (digest {})",
+        rec.pin.file_count,
+        &rec.pin.artifact_digest[..rec.pin.artifact_digest.len().min(12)]
+    );
+    if !rec.pin.tool_definitions.is_empty() || !rec.pin.instruction_files.is_empty() {
+        println!(
             "  watching {} tool-definition + {} instruction file(s) for drift",
             rec.pin.tool_definitions.len(),
             rec.pin.instruction_files.len()
@@ -1513,9 +2786,110 @@ fn approve_with_ledger(
                     err, remove_err
                 ));
             }
-            Err(err)
+            Err(err.into())
+        }
+    }
+}
+
+/// Concurrent threat-intel lookups kept in flight during `--enrich`.
+const THREAT_LOOKUP_CONCURRENCY: usize = 8;
+
+/// Hash every file under `path` for threat lookup.  Returns each distinct
+/// `sha256` with the relative paths of every file that has it, in walk
+/// order, so a hash is looked up once and reported for each copy.
+fn hash_files_for_lookup(path: &Path) -> Vec<(String, Vec<String>)> {
+    let mut hashes: Vec<(String, Vec<String>)> = Vec::new();
+    let mut index: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for file in scanner::collect_files(path) {
+        let Ok(sha256) = allowlist::hash_file(&file) else {
+            continue;
+        };
+        let rel = scanner::relative_path(&file, path);
+        match index.get(&sha256) {
+            Some(&i) => hashes[i].1.push(rel),
+            None => {
+                index.insert(sha256.clone(), hashes.len());
+                hashes.push((sha256, vec![rel]));
+            }
+        }
+    }
+    hashes
+}
+
+/// Critical finding for a file whose hash the threat database marks malicious.
+fn threat_intel_finding(file: &str, info: &api::ThreatInfo) -> scanner::Finding {
+    let mut snippet = info
+        .description
+        .clone()
+        .unwrap_or_else(|| "known malicious file".to_string());
+    if let Some(threat_type) = &info.threat_type {
+        snippet = format!("{} ({})", snippet, threat_type);
+    }
+    scanner::Finding {
+        phase: scanner::Phase::Provenance,
+        rule: "THREAT-001".to_string(),
+        severity: scanner::Severity::Critical,
+        file: file.to_string(),
+        line: None,
+        snippet,
+        weight: 10,
+        confidence: corpus::registry::confidence("THREAT-001"),
+        kev: false,
+        epss: 0.0,
+        sha256: Some(info.hash.clone()),
+        region: None,
+    }
+}
+
+/// Look up each file's SHA-256 in the cloud threat database and fold any
+/// known-malicious hits into `result` as Critical THREAT-001 findings.
+/// Lookups run concurrently (bounded) and back off on rate limiting; hashes
+/// answered within the threat cache's TTL are not looked up again unless
+/// `fresh`.  An unreachable service leaves the result untouched.
+async fn enrich_with_file_hashes(
+    client: &api::SigilClient,
+    path: &Path,
+    result: &mut scanner::ScanResult,
+    fresh: bool,
+    verbose: bool,
+) {
+    let files = hash_files_for_lookup(path);
+    if files.is_empty() {
+        return;
+    }
+    if verbose {
+        log_debug!(
+            "checking {} file hash(es) against cloud threat database...",
+            files.len()
+        );
+    }
+
+    let hashes: Vec<String> = files.iter().map(|(hash, _)| hash.clone()).collect();
+    let mut cache = threat_cache::ThreatCache::load();
+    let lookups = threat_cache::lookup_threats(
+        client,
+        &mut cache,
+        &hashes,
+        THREAT_LOOKUP_CONCURRENCY,
+        fresh,
+    )
+    .await;
+
+    let mut findings = Vec::new();
+    let mut failures = 0usize;
+    for ((_, paths), (_, lookup)) in files.iter().zip(lookups) {
+        match lookup {
+            Ok(info) if info.known_malicious => {
+                findings.extend(paths.iter().map(|file| threat_intel_finding(file, &info)))
+            }
+            Ok(_) => {}
+            Err(_) => failures += 1,
         }
     }
+    if verbose && failures > 0 {
+        log_warn!("{} file hash lookup(s) failed", failures);
+    }
+    merge_findings(result, findings);
 }
 
 /// If `path` is a previously-approved quarantine artifact, diff its current
@@ -1544,10 +2918,10 @@ fn check_rugpull_for_path(path: &Path, verbose: bool) -> Vec<scanner::Finding> {
             if let Err(e) = quarantine::requarantine(&entry.id, Some("content drift (RUGPULL-001)"))
             {
                 if verbose {
-                    eprintln!("rug-pull: re-quarantine of {} failed: {}", entry.id, e);
+                    log_debug!("rug-pull: re-quarantine of {} failed: {}", entry.id, e);
                 }
             } else if verbose {
-                eprintln!("rug-pull: {} drifted — re-quarantined", entry.id);
+                log_debug!("rug-pull: {} drifted — re-quarantined", entry.id);
             }
         }
         return findings;
@@ -1589,23 +2963,45 @@ async fn cmd_ledger(action: LedgerAction) -> i32 {
                 0
             }
             None => {
-                eprintln!(
-                    "{} no ledger pin for '{}' (approve it first)",
-                    "error:".bold().red(),
-                    id
-                );
+                log_error!("no ledger pin for '{}' (approve it first)", id);
                 1
             }
         },
     }
 }
 
-async fn cmd_reject(id: &str, reason: Option<&str>, verbose: bool) -> i32 {
+async fn cmd_reject(id: &str, reason: Option<&str>, yes: bool, verbose: bool) -> i32 {
     if verbose {
-        eprintln!("rejecting quarantine entry: {}", id);
+        log_debug!("rejecting quarantine entry: {}", id);
+    }
+
+    let entry = match quarantine::resolve_id(id).and_then(|id| quarantine::get(&id)) {
+        Ok(entry) => entry,
+        Err(err) => {
+            log_error!("{}", err);
+            return 1;
+        }
+    };
+    // Deletion cannot be undone, and a prefix may not name the entry the
+    // user had in mind: show what is about to go before removing it.
+    if !yes && entry.status == quarantine::QuarantineStatus::Pending {
+        if !std::io::stdin().is_terminal() {
+            log_error!(
+                "refusing to delete {} ({}) without confirmation; pass --yes",
+                entry.id,
+                entry.source
+            );
+            return 1;
+        }
+        eprintln!("  source: {}", entry.source);
+        eprintln!("  path:   {}", entry.path.display());
+        if !ask_yes_no(&format!("Permanently delete {}?", entry.id)) {
+            log_info!("{} left in quarantine", entry.id);
+            return 1;
+        }
     }
 
-    match quarantine::reject(id, reason) {
+    match quarantine::reject(&entry.id, reason) {
         Ok(entry) => {
             println!(
                 "{} rejected {} ({})",
@@ -1618,149 +3014,717 @@ async fn cmd_reject(id: &str, reason: Option<&str>, verbose: bool) -> i32 {
             match ledger::remove(&entry.id) {
                 Ok(true) => println!("  ledger pin revoked"),
                 Ok(false) => {}
-                Err(e) => eprintln!(
-                    "{} rejected but ledger revocation failed: {}",
-                    "warning:".bold().yellow(),
-                    e
-                ),
+                Err(e) => log_warn!("rejected but ledger revocation failed: {}", e),
+            }
+            0
+        }
+        Err(err) => {
+            log_error!("{}", err);
+            1
+        }
+    }
+}
+
+async fn cmd_rescan(id: &str, format: &str, verbose: bool) -> i32 {
+    let entry = match quarantine::resolve_id(id).and_then(|id| quarantine::get(&id)) {
+        Ok(entry) => entry,
+        Err(err) => {
+            log_error!("{}", err);
+            return 2;
+        }
+    };
+    if entry.status == quarantine::QuarantineStatus::Rejected || !entry.path.exists() {
+        log_error!(
+            "entry '{}' is {} and its files are gone; nothing to re-scan",
+            entry.id,
+            entry.status
+        );
+        return 2;
+    }
+
+    log_info!(
+        "re-scanning {} ({}, {})...",
+        entry.id.bold(),
+        entry.source,
+        entry.status
+    );
+    if verbose {
+        log_debug!("quarantine path: {}", entry.path.display());
+        if let Some(previous) = entry.scan_score {
+            log_debug!("previous score: {}", previous);
+        }
+    }
+
+    let mut result = scan_quarantined(&entry);
+    // An approved entry is pinned in the ledger: drift since approval is a
+    // rug-pull and re-quarantines it, exactly as in `sigil scan`.
+    merge_findings(&mut result, check_rugpull_for_path(&entry.path, verbose));
+
+    if let Err(err) = quarantine::set_scan_score(&entry.id, result.score) {
+        log_warn!("failed to record scan score: {}", err);
+    }
+
+    output::print_scan_result(&result, format);
+
+    match result.verdict {
+        scanner::Verdict::LowRisk => 0,
+        scanner::Verdict::MediumRisk => 1,
+        _ => 2,
+    }
+}
+
+async fn cmd_list(
+    status: Option<&str>,
+    detailed: bool,
+    offset: usize,
+    limit: Option<usize>,
+    format: &str,
+) -> i32 {
+    match quarantine::list_page(status, offset, limit) {
+        Ok(page) => {
+            if page.entries.is_empty() && format != "json" {
+                if page.total == 0 {
+                    log_info!("no quarantined items found");
+                } else {
+                    log_info!("no items at offset {} ({} in total)", offset, page.total);
+                }
+                output::create_empty_results();
+                return 0;
+            }
+
+            output::print_quarantine_list(
+                &page.entries,
+                page.total,
+                offset,
+                limit,
+                detailed,
+                format,
+            );
+            0
+        }
+        Err(err) => {
+            log_error!("{}", err);
+            1
+        }
+    }
+}
+
+/// What `sigil install` would do, checked before anything is copied.
+#[derive(Debug)]
+struct InstallPlan {
+    source: PathBuf,
+    target: PathBuf,
+    dir_exists: bool,
+    on_path: bool,
+    /// Whether the directory accepts writes: the binary is staged beside
+    /// the target and renamed over it.
+    writable: bool,
+    /// `Some` when a binary is already installed at the target; holds its
+    /// `--version` output when that could be read.
+    existing: Option<Option<String>>,
+}
+
+impl InstallPlan {
+    fn check(source: &Path, install_dir: &Path, path_env: Option<&std::ffi::OsStr>) -> Self {
+        let target = install_dir.join("sigil");
+        let dir_exists = install_dir.is_dir();
+        let on_path = path_env.is_some_and(|paths| {
+            let dir = install_dir.canonicalize().ok();
+            std::env::split_paths(paths)
+                .any(|p| p == install_dir || (dir.is_some() && p.canonicalize().ok() == dir))
+        });
+        let existing = target.is_file().then(|| installed_version(&target, source));
+        let writable = dir_exists && dir_accepts_writes(install_dir);
+        InstallPlan {
+            source: source.to_path_buf(),
+            target,
+            dir_exists,
+            on_path,
+            writable,
+            existing,
+        }
+    }
+}
+
+/// Probe a directory by creating and removing an empty file in it.
+fn dir_accepts_writes(dir: &Path) -> bool {
+    let probe = dir.join(format!(".sigil-install-probe-{}", std::process::id()));
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Version reported by an installed binary, or `None` if it cannot be run.
+/// The running binary is not re-executed when it is the target itself.
+fn installed_version(target: &Path, source: &Path) -> Option<String> {
+    let same = target.canonicalize().ok() == source.canonicalize().ok();
+    if same {
+        return Some(env!("CARGO_PKG_VERSION").to_string());
+    }
+    let out = std::process::Command::new(target)
+        .arg("--version")
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&out.stdout);
+    // clap prints "sigil 1.2.3".
+    text.split_whitespace().last().map(|v| v.to_string())
+}
+
+async fn cmd_install(path: Option<&std::path::Path>, dry_run: bool, verbose: bool) -> i32 {
+    let install_dir = path
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/usr/local/bin"));
+
+    // Get the path of the currently running binary
+    let current_exe = match std::env::current_exe() {
+        Ok(p) => p,
+        Err(err) => {
+            log_error!("cannot determine current binary path: {}", err);
+            return 1;
+        }
+    };
+
+    let plan = InstallPlan::check(
+        &current_exe,
+        &install_dir,
+        std::env::var_os("PATH").as_deref(),
+    );
+    let new_version = env!("CARGO_PKG_VERSION");
+
+    if dry_run {
+        println!(
+            "{} install dry run (nothing copied)",
+            "sigil:".bold().cyan()
+        );
+        println!("  source:   {} (v{})", plan.source.display(), new_version);
+        println!("  target:   {}", plan.target.display());
+        let existing = match &plan.existing {
+            None => "no".to_string(),
+            Some(Some(old)) => format!("yes — v{} would be replaced by v{}", old, new_version),
+            Some(None) => "yes — unknown version would be replaced".to_string(),
+        };
+        println!("  exists:   {}", existing);
+        println!(
+            "  writable: {}",
+            if plan.writable {
+                "yes"
+            } else if plan.dir_exists {
+                "no (you may need to run with sudo)"
+            } else {
+                "no (directory does not exist)"
+            }
+        );
+        println!("  on PATH:  {}", if plan.on_path { "yes" } else { "no" });
+        return if plan.writable { 0 } else { 1 };
+    }
+
+    if !plan.dir_exists {
+        log_error!(
+            "install directory does not exist: {}",
+            install_dir.display()
+        );
+        return 1;
+    }
+    if !plan.writable {
+        log_error!(
+            "cannot write to {} (you may need to run with sudo)",
+            plan.target.display()
+        );
+        return 1;
+    }
+    if !plan.on_path {
+        log_warn!(
+            "{} is not on your PATH; add it to run `sigil` directly",
+            install_dir.display()
+        );
+    }
+
+    log_info!("installing sigil to {}...", install_dir.display());
+    match &plan.existing {
+        Some(Some(old)) => log_info!("replacing v{} with v{}", old, new_version),
+        Some(None) => log_info!("replacing existing binary with v{}", new_version),
+        None => {}
+    }
+
+    if verbose {
+        log_debug!(
+            "copying {} -> {}",
+            current_exe.display(),
+            plan.target.display()
+        );
+    }
+
+    match install_binary(&current_exe, &plan.target) {
+        Ok(digest) => {
+            let resolved = plan.target.canonicalize().unwrap_or(plan.target.clone());
+            println!(
+                "{} installed sigil v{} to {}",
+                "sigil:".bold().green(),
+                new_version,
+                resolved.display()
+            );
+            if verbose {
+                log_debug!("sha256 {}", digest);
             }
             0
         }
-        Err(err) => {
-            eprintln!("{} {}", "error:".bold().red(), err);
-            1
+        Err(err) => {
+            log_error!(
+                "installation failed: {} (you may need to run with sudo)",
+                err
+            );
+            1
+        }
+    }
+}
+
+/// Copy `source` to `target` and return the SHA-256 they share.
+///
+/// The copy is staged beside the target, checksummed against the source,
+/// made executable, and only then renamed into place — so a short copy
+/// (disk full) is removed instead of installed, and a failed install never
+/// leaves a truncated binary where the old one was.
+fn install_binary(source: &Path, target: &Path) -> Result<String, String> {
+    let dir = target.parent().unwrap_or(Path::new("."));
+    let staging = dir.join(format!(".sigil.install-{}", std::process::id()));
+    let result = (|| {
+        std::fs::copy(source, &staging).map_err(|e| e.to_string())?;
+        let expected = verify::digest_file(source, "sha256")?;
+        let actual = verify::digest_file(&staging, "sha256")?;
+        if actual != expected {
+            return Err(format!(
+                "checksum mismatch after copy (expected {}, got {}); nothing installed",
+                expected, actual
+            ));
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&staging, std::fs::Permissions::from_mode(0o755))
+                .map_err(|e| format!("cannot mark {} executable: {}", staging.display(), e))?;
+        }
+        std::fs::rename(&staging, target).map_err(|e| e.to_string())?;
+        Ok(actual)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&staging);
+    }
+    result
+}
+
+async fn cmd_login(token: Option<&str>, endpoint: &str, verbose: bool) -> i32 {
+    if verbose {
+        log_debug!("authenticating with {}", endpoint);
+    }
+
+    let client = match api::SigilClient::new(Some(endpoint.to_string())) {
+        Ok(client) => client,
+        Err(err) => {
+            log_error!("{}", err);
+            return 2;
+        }
+    };
+
+    match token {
+        Some(t) => match client.login_with_token(t).await {
+            Ok(_) => {
+                println!("{} authenticated successfully", "sigil:".bold().green());
+                0
+            }
+            Err(err) => {
+                log_error!("authentication failed: {}", err);
+                1
+            }
+        },
+        None => {
+            // Browser-based device authorization flow (replaces password login).
+            match client.login_device_flow().await {
+                Ok(_) => {
+                    println!("{} logged in successfully", "sigil:".bold().green());
+                    0
+                }
+                Err(err) => {
+                    log_error!("login failed: {}", err);
+                    1
+                }
+            }
+        }
+    }
+}
+
+/// `sigil version`: what this binary is, for bug reports and for tying a
+/// scan result to the build and rules that produced it.
+fn cmd_quarantine(action: QuarantineAction, format: &str) -> i32 {
+    match action {
+        QuarantineAction::Export { file, with_files } => {
+            match quarantine::export(&file, with_files) {
+                Ok(count) => {
+                    log_info!(
+                        "exported {} quarantine entr{} to {}{}",
+                        count,
+                        if count == 1 { "y" } else { "ies" },
+                        file.display(),
+                        if with_files { " with their files" } else { "" }
+                    );
+                    0
+                }
+                Err(err) => {
+                    log_error!("{}", err);
+                    2
+                }
+            }
+        }
+        QuarantineAction::Import { file } => {
+            match quarantine::import(&file) {
+                Ok(summary) if format == "json" => {
+                    output::outln!(
+                        "{}",
+                        serde_json::to_string_pretty(&summary).unwrap_or_default()
+                    );
+                    0
+                }
+                Ok(summary) => {
+                    for (exported, local) in &summary.renamed {
+                        output::outln!("  {} imported as {} (ID already in use)", exported, local);
+                    }
+                    output::outln!(
+                        "  {} added, {} updated, {} unchanged",
+                        summary.added,
+                        summary.updated,
+                        summary.unchanged
+                    );
+                    if summary.path_missing > 0 {
+                        log_warn!(
+                        "{} imported entr{} ha{} no files on this machine (marked path-missing)",
+                        summary.path_missing,
+                        if summary.path_missing == 1 { "y" } else { "ies" },
+                        if summary.path_missing == 1 { "s" } else { "ve" }
+                    );
+                    }
+                    0
+                }
+                Err(err) => {
+                    log_error!("{}", err);
+                    2
+                }
+            }
+        }
+    }
+}
+
+fn cmd_allowlist(action: AllowlistAction, format: &str) -> i32 {
+    let path = allowlist::default_path();
+    match action {
+        AllowlistAction::Add { paths, note } => match allowlist::add(&path, &paths, &note) {
+            Ok(added) => {
+                for (file, sha256) in &added {
+                    output::outln!("  added {}  {}", &sha256[..12], file);
+                }
+                log_info!(
+                    "{} new hash(es) allowlisted in {}",
+                    added.len(),
+                    path.display()
+                );
+                0
+            }
+            Err(err) => {
+                log_error!("{}", err);
+                2
+            }
+        },
+        AllowlistAction::List => match allowlist::load(&path) {
+            Ok(list) if format == "json" => {
+                output::outln!(
+                    "{}",
+                    serde_json::to_string_pretty(&list).unwrap_or_default()
+                );
+                0
+            }
+            Ok(list) => {
+                if list.is_empty() {
+                    log_info!("allowlist is empty ({})", path.display());
+                }
+                for (sha256, entry) in &list.entries {
+                    output::outln!(
+                        "{}  {}  {}",
+                        sha256,
+                        entry.path.as_deref().unwrap_or("-"),
+                        entry.note
+                    );
+                }
+                0
+            }
+            Err(err) => {
+                log_error!("{}", err);
+                2
+            }
+        },
+    }
+}
+
+fn cmd_version(format: &str) -> i32 {
+    let info = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_commit": env!("SIGIL_GIT_COMMIT"),
+        "build_date": env!("SIGIL_BUILD_DATE"),
+        "ruleset_version": corpus::registry::ruleset_version(),
+        "default_endpoint": api::DEFAULT_ENDPOINT,
+        "os": format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+    });
+    if format == "json" {
+        output::outln!(
+            "{}",
+            serde_json::to_string_pretty(&info).unwrap_or_default()
+        );
+        return 0;
+    }
+    output::outln!("sigil {}", env!("CARGO_PKG_VERSION"));
+    for (label, key) in [
+        ("commit", "git_commit"),
+        ("built", "build_date"),
+        ("ruleset", "ruleset_version"),
+        ("endpoint", "default_endpoint"),
+        ("platform", "os"),
+    ] {
+        output::outln!("  {:<9} {}", label, info[key].as_str().unwrap_or_default());
+    }
+    0
+}
+
+async fn cmd_whoami(format: &str, verbose: bool) -> i32 {
+    let Some(stored) = api::stored_token() else {
+        log_error!("not logged in (run: sigil login)");
+        return 1;
+    };
+    let endpoint = config::resolve().endpoint(stored.endpoint.as_deref());
+    let expired = stored.is_expired(chrono::Utc::now());
+
+    // Only a live token is worth sending; an expired one is reported locally.
+    let (account, note) = if expired {
+        (None, Some(api::SESSION_EXPIRED_HINT.to_string()))
+    } else {
+        if verbose {
+            log_debug!("verifying token with {}", endpoint);
+        }
+        let client = match api::SigilClient::new(Some(endpoint.clone())) {
+            Ok(client) => client,
+            Err(err) => {
+                log_error!("{}", err);
+                return 2;
+            }
+        };
+        match client.whoami().await {
+            Ok(account) => (Some(account), None),
+            Err(err @ error::SigilError::Network(_)) => {
+                if verbose {
+                    log_debug!("{}", err);
+                }
+                (None, Some("unable to verify online".to_string()))
+            }
+            Err(err) => {
+                log_error!("{}", err);
+                return 1;
+            }
+        }
+    };
+
+    // The server's view of the expiry wins over the locally recorded one.
+    let expires_at = account
+        .as_ref()
+        .and_then(|a| a.expires_at.clone())
+        .or(stored.expires_at.clone());
+    let email = account.as_ref().and_then(|a| a.email.clone());
+
+    if format == "json" {
+        let out = serde_json::json!({
+            "email": email,
+            "endpoint": endpoint,
+            "expires_at": expires_at,
+            "expired": expired,
+            "verified": account.is_some(),
+            "note": note,
+        });
+        output::outln!("{}", serde_json::to_string_pretty(&out).unwrap_or_default());
+    } else {
+        if expired {
+            output::outln!(
+                "{} session expired (was logged in to {})",
+                "sigil:".bold().yellow(),
+                endpoint
+            );
+        } else {
+            output::outln!(
+                "{} logged in as {}",
+                "sigil:".bold().cyan(),
+                email.as_deref().unwrap_or("(unknown account)").bold()
+            );
+        }
+        output::outln!("  endpoint: {}", endpoint);
+        output::outln!(
+            "  expires:  {}",
+            expires_at.as_deref().unwrap_or("no expiry recorded")
+        );
+        if let Some(note) = &note {
+            output::outln!("  {} {}", "note:".bold().yellow(), note);
         }
     }
+
+    if expired {
+        1
+    } else {
+        0
+    }
 }
 
-async fn cmd_list(status: Option<&str>, detailed: bool, format: &str, _verbose: bool) -> i32 {
-    match quarantine::list(status) {
-        Ok(entries) => {
-            if entries.is_empty() {
-                println!("{} no quarantined items found", "sigil:".bold().cyan());
-                return 0;
-            }
+/// The hash `sigil report` sends for `target`: the SHA-256 of the file at
+/// that path, or `target` itself when it is a hash.  The path is returned
+/// too when there is one.
+fn report_hash(target: &str) -> Result<(String, Option<PathBuf>), String> {
+    let path = Path::new(target);
+    if path.is_dir() {
+        return Err(format!("{} is a directory; report one file", target));
+    }
+    if path.exists() {
+        return allowlist::hash_file(path).map(|hash| (hash, Some(path.to_path_buf())));
+    }
+    let hash = target.strip_prefix("sha256:").unwrap_or(target);
+    if hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Ok((hash.to_ascii_lowercase(), None));
+    }
+    Err(format!("{} is neither a file nor a SHA-256 hash", target))
+}
 
-            output::print_quarantine_list(&entries, detailed, format);
-            0
-        }
+async fn cmd_report(
+    target: &str,
+    threat_type: &str,
+    description: &str,
+    attach_findings: bool,
+    verbose: bool,
+) -> i32 {
+    let (hash, path) = match report_hash(target) {
+        Ok(resolved) => resolved,
         Err(err) => {
-            eprintln!("{} {}", "error:".bold().red(), err);
-            1
+            log_error!("{}", err);
+            return 1;
         }
+    };
+    let findings = match (attach_findings, &path) {
+        (false, _) => Vec::new(),
+        (true, Some(path)) => scanner::run_package_scan(path).findings,
+        (true, None) => {
+            log_error!("--attach-findings needs a file path, not a hash");
+            return 1;
+        }
+    };
+    if verbose {
+        log_debug!(
+            "reporting threat: hash={} ({} finding(s) attached)",
+            hash,
+            findings.len()
+        );
     }
-}
-
-async fn cmd_install(path: Option<&std::path::Path>, verbose: bool) -> i32 {
-    let install_dir = path
-        .map(|p| p.to_path_buf())
-        .unwrap_or_else(|| PathBuf::from("/usr/local/bin"));
 
-    println!(
-        "{} installing sigil to {}...",
-        "sigil:".bold().cyan(),
-        install_dir.display()
-    );
-
-    let target = install_dir.join("sigil");
-
-    // Get the path of the currently running binary
-    let current_exe = match std::env::current_exe() {
-        Ok(p) => p,
+    let client = match api::SigilClient::new(None) {
+        Ok(client) => client,
         Err(err) => {
-            eprintln!(
-                "{} cannot determine current binary path: {}",
-                "error:".bold().red(),
-                err
-            );
-            return 1;
+            log_error!("{}", err);
+            return 2;
         }
     };
 
-    if verbose {
-        eprintln!("copying {} -> {}", current_exe.display(), target.display());
+    if !client.is_authenticated() {
+        if client.session_expired() {
+            log_error!("{}", api::SESSION_EXPIRED_HINT);
+        } else {
+            log_error!("you must be logged in to report threats (run: sigil login)");
+        }
+        return 1;
     }
 
-    match std::fs::copy(&current_exe, &target) {
-        Ok(_) => {
+    match client
+        .report_threat(&hash, threat_type, description, &findings)
+        .await
+    {
+        Ok(response) => {
             println!(
-                "{} installed successfully to {}",
+                "{} threat reported successfully (id: {})",
                 "sigil:".bold().green(),
-                target.display()
+                response.id
             );
             0
         }
         Err(err) => {
-            eprintln!("{} installation failed: {}", "error:".bold().red(), err);
-            eprintln!("hint: you may need to run with sudo");
+            log_error!("failed to report threat: {}", err);
             1
         }
     }
 }
 
-async fn cmd_login(token: Option<&str>, endpoint: &str, verbose: bool) -> i32 {
-    if verbose {
-        eprintln!("authenticating with {}", endpoint);
+async fn cmd_report_all(status: Option<&str>, format: &str, verbose: bool) -> i32 {
+    let entries = match quarantine::list(status) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log_error!("{}", err);
+            return 1;
+        }
+    };
+
+    let mut audit = Vec::new();
+    for entry in entries {
+        // Rejected entries have had their files deleted.
+        if !entry.path.exists() {
+            log_warn!(
+                "skipping {} ({}): its files are gone",
+                entry.id,
+                entry.status
+            );
+            continue;
+        }
+        if verbose {
+            log_debug!("scanning {} ({})", entry.id, entry.source);
+        }
+        let result = scan_quarantined(&entry);
+        audit.push(output::AuditEntry::new(entry, result));
     }
 
-    let client = api::SigilClient::new(Some(endpoint.to_string()));
+    log_info!(
+        "scanned {} quarantined item{}",
+        audit.len(),
+        if audit.len() == 1 { "" } else { "s" }
+    );
+    output::print_audit_report(&audit, format);
+    0
+}
 
-    match token {
-        Some(t) => match client.login_with_token(t).await {
-            Ok(_) => {
-                println!("{} authenticated successfully", "sigil:".bold().green());
-                0
-            }
+/// `sigil aggregate`: roll stored scan results up into one report.  Files
+/// that are not scan results are skipped with a warning; exit 1 when none
+/// could be read.
+fn cmd_aggregate(inputs: &[PathBuf], top: usize, format: &str) -> i32 {
+    let mut results = Vec::new();
+    let mut skipped = 0usize;
+    for file in aggregate::input_files(inputs) {
+        match aggregate::load_result(&file) {
+            Ok(result) => results.push((file.display().to_string(), result)),
             Err(err) => {
-                eprintln!("{} authentication failed: {}", "error:".bold().red(), err);
-                1
-            }
-        },
-        None => {
-            // Browser-based device authorization flow (replaces password login).
-            match client.login_device_flow().await {
-                Ok(_) => {
-                    println!("{} logged in successfully", "sigil:".bold().green());
-                    0
-                }
-                Err(err) => {
-                    eprintln!("{} login failed: {}", "error:".bold().red(), err);
-                    1
-                }
+                log_warn!("skipping {}: {}", file.display(), err);
+                skipped += 1;
             }
         }
     }
-}
-
-async fn cmd_report(hash: &str, threat_type: &str, description: &str, verbose: bool) -> i32 {
-    if verbose {
-        eprintln!("reporting threat: hash={}", hash);
-    }
-
-    let client = api::SigilClient::new(None);
-
-    if !client.is_authenticated() {
-        eprintln!(
-            "{} you must be logged in to report threats (run: sigil login)",
-            "error:".bold().red()
-        );
+    if results.is_empty() {
+        log_error!("no scan results to aggregate");
         return 1;
     }
-
-    match client.report_threat(hash, threat_type, description).await {
-        Ok(response) => {
-            println!(
-                "{} threat reported successfully (id: {})",
-                "sigil:".bold().green(),
-                response.id
-            );
-            0
-        }
-        Err(err) => {
-            eprintln!("{} failed to report threat: {}", "error:".bold().red(), err);
-            1
-        }
-    }
+    output::print_org_summary(&aggregate::summarize(&results, skipped, top), format);
+    0
 }
 
 async fn cmd_run(
@@ -1777,12 +3741,7 @@ async fn cmd_run(
         match policy::schema::SigilPolicy::from_file(policy_path) {
             Ok(p) => p,
             Err(err) => {
-                eprintln!(
-                    "{} failed to load policy file '{}': {}",
-                    "error:".bold().red(),
-                    policy_name,
-                    err
-                );
+                log_error!("failed to load policy file '{}': {}", policy_name, err);
                 return 1;
             }
         }
@@ -1790,9 +3749,8 @@ async fn cmd_run(
         match policy::schema::SigilPolicy::preset(policy_name) {
             Some(p) => p,
             None => {
-                eprintln!(
-                    "{} unknown policy '{}'. Use: strict, standard, permissive, or a file path.",
-                    "error:".bold().red(),
+                log_error!(
+                    "unknown policy '{}'. Use: strict, standard, permissive, or a file path.",
                     policy_name
                 );
                 return 1;
@@ -1851,11 +3809,7 @@ async fn cmd_run(
     match sandbox::container::run_sandboxed(&policy, &workdir, &command, &env_vars, verbose) {
         Ok(exit_code) => exit_code,
         Err(err) => {
-            eprintln!(
-                "{} sandbox execution failed: {}",
-                "error:".bold().red(),
-                err
-            );
+            log_error!("sandbox execution failed: {}", err);
             1
         }
     }
@@ -1871,10 +3825,7 @@ async fn cmd_provider(action: ProviderAction) -> i32 {
             let var_list: Vec<String> = vars.split(',').map(|s| s.trim().to_string()).collect();
 
             if var_list.is_empty() || var_list.iter().all(|v| v.is_empty()) {
-                eprintln!(
-                    "{} no environment variable names provided",
-                    "error:".bold().red()
-                );
+                log_error!("no environment variable names provided");
                 return 1;
             }
 
@@ -1893,7 +3844,7 @@ async fn cmd_provider(action: ProviderAction) -> i32 {
                     0
                 }
                 Err(err) => {
-                    eprintln!("{} failed to save provider: {}", "error:".bold().red(), err);
+                    log_error!("failed to save provider: {}", err);
                     1
                 }
             }
@@ -1951,7 +3902,7 @@ async fn cmd_provider(action: ProviderAction) -> i32 {
                 0
             }
             Err(err) => {
-                eprintln!("{} {}", "error:".bold().red(), err);
+                log_error!("{}", err);
                 1
             }
         },
@@ -1966,7 +3917,7 @@ async fn cmd_provider(action: ProviderAction) -> i32 {
                 0
             }
             Err(err) => {
-                eprintln!("{} {}", "error:".bold().red(), err);
+                log_error!("{}", err);
                 1
             }
         },
@@ -2002,9 +3953,7 @@ async fn cmd_provider(action: ProviderAction) -> i32 {
 }
 
 async fn cmd_config(key: Option<&str>, value: Option<&str>, list: bool, _verbose: bool) -> i32 {
-    let config_path = dirs::home_dir()
-        .map(|h| h.join(".sigil").join("config.json"))
-        .unwrap_or_else(|| PathBuf::from(".sigil/config.json"));
+    let config_path = config::config_path();
 
     if list {
         match std::fs::read_to_string(&config_path) {
@@ -2017,27 +3966,61 @@ async fn cmd_config(key: Option<&str>, value: Option<&str>, list: bool, _verbose
                 0
             }
         }
+    } else if let (Some("unset"), Some(k)) = (key, value) {
+        let mut config = match config::load() {
+            Ok(c) => c,
+            Err(err) => {
+                log_error!("{}", err);
+                return 1;
+            }
+        };
+        if config.remove(k).is_none() {
+            log_error!("key '{}' not found", k);
+            return 1;
+        }
+        match config::save(&config) {
+            Ok(()) => {
+                println!("{} unset {}", "sigil:".bold().green(), k);
+                0
+            }
+            Err(err) => {
+                log_error!("{}", err);
+                1
+            }
+        }
     } else if let Some(k) = key {
         if let Some(v) = value {
             // Set a config value
-            let mut config: serde_json::Value = std::fs::read_to_string(&config_path)
-                .ok()
-                .and_then(|c| serde_json::from_str(&c).ok())
-                .unwrap_or_else(|| serde_json::json!({}));
-
-            config[k] = serde_json::Value::String(v.to_string());
-
-            if let Some(parent) = config_path.parent() {
-                let _ = std::fs::create_dir_all(parent);
+            let typed = match config::validate(k, v) {
+                Ok(typed) => typed,
+                Err(err) => {
+                    log_error!("{}", err);
+                    return 1;
+                }
+            };
+            let mut config = match config::load() {
+                Ok(c) => c,
+                Err(err) => {
+                    log_error!("{}", err);
+                    return 1;
+                }
+            };
+            if !config::is_known(k) {
+                log_warn!("'{}' is not a key sigil reads; storing it anyway", k);
             }
-
-            match std::fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap()) {
-                Ok(_) => {
-                    println!("{} {} = {}", "sigil:".bold().green(), k, v);
+            let shown = typed
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| typed.to_string());
+            config.insert(k.to_string(), typed);
+
+            match config::save(&config) {
+                Ok(()) => {
+                    println!("{} {} = {}", "sigil:".bold().green(), k, shown);
                     0
                 }
                 Err(err) => {
-                    eprintln!("{} failed to write config: {}", "error:".bold().red(), err);
+                    log_error!("{}", err);
                     1
                 }
             }
@@ -2052,23 +4035,23 @@ async fn cmd_config(key: Option<&str>, value: Option<&str>, list: bool, _verbose
                                 0
                             }
                             None => {
-                                eprintln!("{} key '{}' not found", "sigil:".bold().yellow(), k);
+                                log_error!("key '{}' not found", k);
                                 1
                             }
                         }
                     } else {
-                        eprintln!("{} corrupt config file", "error:".bold().red());
+                        log_error!("corrupt config file");
                         1
                     }
                 }
                 Err(_) => {
-                    eprintln!("{} no configuration file found", "sigil:".bold().cyan());
+                    log_info!("no configuration file found");
                     1
                 }
             }
         }
     } else {
-        eprintln!("{} specify a key or use --list", "sigil:".bold().yellow());
+        log_error!("specify a key or use --list");
         1
     }
 }
@@ -2077,6 +4060,168 @@ async fn cmd_config(key: Option<&str>, value: Option<&str>, list: bool, _verbose
 // sbom command
 // ---------------------------------------------------------------------------
 
+/// `sigil deps`: what a project pulls in, from its lockfiles, without
+/// installing anything.  Exit 1 when `--lookup` flags a dependency as a
+/// known threat, 2 when the path does not exist.
+async fn cmd_deps(
+    path: &Path,
+    lookup: bool,
+    no_threat_cache: bool,
+    format: &str,
+    verbose: bool,
+) -> i32 {
+    if !path.exists() {
+        log_error!("path does not exist: {}", path.display());
+        return 2;
+    }
+
+    let mut lockfiles = sbom::find_lockfiles(path);
+    if lockfiles.is_empty() {
+        log_warn!(
+            "no dependency files found under {} (looked for {})",
+            path.display(),
+            sbom::LOCKFILE_NAMES.join(", ")
+        );
+    }
+    for lockfile in lockfiles.iter().filter(|l| l.error.is_some()) {
+        log_warn!(
+            "could not parse {}: {}",
+            lockfile.path,
+            lockfile.error.as_deref().unwrap_or_default()
+        );
+    }
+
+    let mut looked_up = 0usize;
+    if lookup {
+        let mut hashes: Vec<String> = lockfiles
+            .iter()
+            .flat_map(|l| l.components.iter())
+            .filter_map(|c| c.hash.clone())
+            .collect();
+        hashes.sort();
+        hashes.dedup();
+        looked_up = hashes.len();
+        if verbose {
+            log_debug!(
+                "checking {} dependency hash(es) against cloud threat database...",
+                hashes.len()
+            );
+        }
+        let client = match api::SigilClient::new(None) {
+            Ok(client) => client,
+            Err(err) => {
+                log_error!("{}", err);
+                return 2;
+            }
+        };
+        let mut cache = threat_cache::ThreatCache::load();
+        let mut threats = std::collections::HashMap::new();
+        let mut failures = 0usize;
+        for (hash, lookup) in threat_cache::lookup_threats(
+            &client,
+            &mut cache,
+            &hashes,
+            THREAT_LOOKUP_CONCURRENCY,
+            no_threat_cache,
+        )
+        .await
+        {
+            match lookup {
+                Ok(info) if info.known_malicious => {
+                    threats.insert(hash, info);
+                }
+                Ok(_) => {}
+                Err(_) => failures += 1,
+            }
+        }
+        if failures > 0 {
+            log_warn!("{} dependency hash lookup(s) failed", failures);
+        }
+        for component in lockfiles.iter_mut().flat_map(|l| l.components.iter_mut()) {
+            let Some(info) = component.hash.as_ref().and_then(|h| threats.get(h)) else {
+                continue;
+            };
+            component.threat_flagged = true;
+            component.threat_severity = info.threat_type.clone();
+            component.threat_description = info.description.clone();
+        }
+    }
+
+    let total: usize = lockfiles.iter().map(|l| l.components.len()).sum();
+    let flagged = lockfiles
+        .iter()
+        .flat_map(|l| l.components.iter())
+        .filter(|c| c.threat_flagged)
+        .count();
+
+    if format == "json" {
+        let report = serde_json::json!({
+            "path": path.display().to_string(),
+            "lockfiles": lockfiles,
+            "total_count": total,
+            "looked_up": looked_up,
+            "threat_count": flagged,
+        });
+        output::outln!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        );
+    } else {
+        for lockfile in &lockfiles {
+            output::outln!();
+            match &lockfile.error {
+                Some(_) => output::outln!("  {}  {}", lockfile.path.bold(), "not parsed".yellow()),
+                None => output::outln!(
+                    "  {}  ({} dependenc{})",
+                    lockfile.path.bold(),
+                    lockfile.components.len(),
+                    if lockfile.components.len() == 1 {
+                        "y"
+                    } else {
+                        "ies"
+                    }
+                ),
+            }
+            for component in &lockfile.components {
+                let version = component
+                    .version
+                    .as_deref()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| "(unpinned)".dimmed().to_string());
+                let threat = if component.threat_flagged {
+                    format!(
+                        "  {} {}",
+                        "KNOWN THREAT".red().bold(),
+                        component.threat_description.as_deref().unwrap_or_default()
+                    )
+                } else {
+                    String::new()
+                };
+                output::outln!("    {} {}{}", component.name, version, threat);
+            }
+        }
+        let parsed = lockfiles.iter().filter(|l| l.error.is_none()).count();
+        output::outln!();
+        output::outln!(
+            "  {} dependencies from {} of {} file(s){}",
+            total,
+            parsed,
+            lockfiles.len(),
+            if lookup {
+                format!(", {} looked up, {} flagged", looked_up, flagged)
+            } else {
+                String::new()
+            }
+        );
+    }
+
+    if flagged > 0 {
+        1
+    } else {
+        0
+    }
+}
+
 async fn cmd_sbom(
     path: &Path,
     format: &str,
@@ -2085,34 +4230,25 @@ async fn cmd_sbom(
     verbose: bool,
 ) -> i32 {
     if verbose {
-        eprintln!(
-            "{} generating SBOM for {}",
-            "sigil:".bold().cyan(),
-            path.display()
-        );
+        log_info!("generating SBOM for {}", path.display());
     }
 
     if !path.exists() {
-        eprintln!(
-            "{} path does not exist: {}",
-            "error:".bold().red(),
-            path.display()
-        );
+        log_error!("path does not exist: {}", path.display());
         return 1;
     }
 
     let sbom = match sbom::generate_sbom(path, threats_db) {
         Ok(s) => s,
         Err(e) => {
-            eprintln!("{} failed to generate SBOM: {}", "error:".bold().red(), e);
+            log_error!("failed to generate SBOM: {}", e);
             return 1;
         }
     };
 
     if verbose {
-        eprintln!(
-            "{} found {} components, {} threats",
-            "sigil:".bold().cyan(),
+        log_info!(
+            "found {} components, {} threats",
             sbom.total_count,
             sbom.threat_count
         );
@@ -2123,11 +4259,7 @@ async fn cmd_sbom(
         "cyclonedx" => sbom::format_cyclonedx(&sbom),
         "json" => serde_json::to_string_pretty(&sbom).unwrap_or_else(|_| "{}".to_string()),
         _ => {
-            eprintln!(
-                "{} unknown format '{}', use table, cyclonedx, or json",
-                "error:".bold().red(),
-                format
-            );
+            log_error!("unknown format '{}', use table, cyclonedx, or json", format);
             return 1;
         }
     };
@@ -2135,14 +4267,10 @@ async fn cmd_sbom(
     if let Some(out_path) = output {
         match std::fs::write(out_path, &formatted) {
             Ok(_) => {
-                eprintln!(
-                    "{} SBOM written to {}",
-                    "sigil:".bold().green(),
-                    out_path.display()
-                );
+                log_info!("SBOM written to {}", out_path.display());
             }
             Err(e) => {
-                eprintln!("{} failed to write output: {}", "error:".bold().red(), e);
+                log_error!("failed to write output: {}", e);
                 return 1;
             }
         }
@@ -2161,37 +4289,31 @@ async fn cmd_sbom(
 // Policy command
 // ---------------------------------------------------------------------------
 
-async fn cmd_policy(action: PolicyAction) -> i32 {
+async fn cmd_policy(action: PolicyAction, output: Option<&Path>) -> i32 {
     match action {
-        PolicyAction::Generate {
-            path,
-            output,
-            verbose,
-        } => {
-            println!(
-                "{} scanning {} to generate policy...",
-                "sigil:".bold().cyan(),
+        PolicyAction::Generate { path, verbose } => {
+            log_info!(
+                "scanning {} to generate policy...",
                 path.display().to_string().bold()
             );
 
             let (policy_result, scan) = match policy::generate::generate_for_path(&path) {
                 Ok(result) => result,
                 Err(e) => {
-                    eprintln!("{} failed to generate policy: {}", "error:".bold().red(), e);
+                    log_error!("failed to generate policy: {}", e);
                     return 1;
                 }
             };
 
             if verbose {
-                eprintln!(
-                    "{} scan complete: {} findings, score {}, verdict {}",
-                    "sigil:".bold().cyan(),
+                log_info!(
+                    "scan complete: {} findings, score {}, verdict {}",
                     scan.findings.len(),
                     scan.score,
                     scan.verdict
                 );
                 for finding in &scan.findings {
-                    eprintln!(
+                    log_debug!(
                         "  [{}] {} — {} ({}:{})",
                         finding.severity,
                         finding.phase,
@@ -2200,23 +4322,18 @@ async fn cmd_policy(action: PolicyAction) -> i32 {
                         finding.line.map(|l| l.to_string()).unwrap_or_default()
                     );
                 }
-                eprintln!();
             }
 
             let yaml = match policy_result.to_yaml() {
                 Ok(y) => y,
                 Err(e) => {
-                    eprintln!(
-                        "{} failed to serialize policy: {}",
-                        "error:".bold().red(),
-                        e
-                    );
+                    log_error!("failed to serialize policy: {}", e);
                     return 1;
                 }
             };
 
             if let Some(out_path) = output {
-                match std::fs::write(&out_path, &yaml) {
+                match std::fs::write(out_path, &yaml) {
                     Ok(_) => {
                         println!(
                             "{} policy written to {}",
@@ -2225,7 +4342,7 @@ async fn cmd_policy(action: PolicyAction) -> i32 {
                         );
                     }
                     Err(e) => {
-                        eprintln!("{} failed to write policy: {}", "error:".bold().red(), e);
+                        log_error!("failed to write policy: {}", e);
                         return 1;
                     }
                 }
@@ -2246,7 +4363,7 @@ async fn cmd_policy(action: PolicyAction) -> i32 {
                 0
             }
             Err(e) => {
-                eprintln!("{} policy validation failed: {}", "error:".bold().red(), e);
+                log_error!("policy validation failed: {}", e);
                 1
             }
         },
@@ -2258,18 +4375,13 @@ async fn cmd_policy(action: PolicyAction) -> i32 {
                     0
                 }
                 Err(e) => {
-                    eprintln!(
-                        "{} failed to serialize preset: {}",
-                        "error:".bold().red(),
-                        e
-                    );
+                    log_error!("failed to serialize preset: {}", e);
                     1
                 }
             },
             None => {
-                eprintln!(
-                    "{} unknown preset '{}'. Available: strict, standard, permissive",
-                    "error:".bold().red(),
+                log_error!(
+                    "unknown preset '{}'. Available: strict, standard, permissive",
                     name
                 );
                 1
@@ -2309,8 +4421,11 @@ mod exit_code_tests {
             line: None,
             snippet: String::new(),
             weight: 1,
+            confidence: 100,
             kev: false,
             epss: 0.0,
+            sha256: None,
+            region: None,
         }
     }
 
@@ -2374,3 +4489,193 @@ mod exit_code_tests {
         });
     }
 }
+
+#[cfg(test)]
+mod threat_intel_tests {
+    use super::scanner::Severity;
+    use super::{api, exit_code_for, hash_files_for_lookup, threat_intel_finding};
+
+    #[test]
+    fn duplicate_files_are_looked_up_once_but_all_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.js"), "same").unwrap();
+        std::fs::write(dir.path().join("b.js"), "same").unwrap();
+        std::fs::write(dir.path().join("c.js"), "other").unwrap();
+        let hashes = hash_files_for_lookup(dir.path());
+        assert_eq!(hashes.len(), 2);
+        let mut copies = hashes
+            .iter()
+            .find(|(_, paths)| paths.len() == 2)
+            .expect("shared hash")
+            .1
+            .clone();
+        copies.sort();
+        assert_eq!(copies, vec!["a.js", "b.js"]);
+    }
+
+    #[test]
+    fn threat_hit_becomes_critical_finding() {
+        let info = api::ThreatInfo {
+            hash: "ab12".into(),
+            known_malicious: true,
+            threat_type: Some("stealer".into()),
+            description: Some("credential stealer".into()),
+            first_seen: None,
+            references: Vec::new(),
+        };
+        let f = threat_intel_finding("lib/x.js", &info);
+        assert_eq!(f.severity, Severity::Critical);
+        assert_eq!(f.rule, "THREAT-001");
+        assert_eq!(f.sha256.as_deref(), Some("ab12"));
+        assert!(f.snippet.contains("stealer"));
+        assert_eq!(exit_code_for(&[f], Severity::High), 1);
+    }
+}
+
+#[cfg(test)]
+mod report_tests {
+    use super::{api, report_hash};
+
+    #[test]
+    fn path_and_hash_forms_send_the_same_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("malware.py");
+        std::fs::write(&file, "exec(base64.b64decode(payload))\n").unwrap();
+        let digest = crate::allowlist::hash_file(&file).unwrap();
+
+        let (from_path, path) = report_hash(&file.to_string_lossy()).unwrap();
+        assert_eq!(path.as_deref(), Some(file.as_path()));
+        let (from_hash, none) = report_hash(&digest.to_uppercase()).unwrap();
+        assert!(none.is_none());
+        let (prefixed, _) = report_hash(&format!("sha256:{}", digest)).unwrap();
+        let body = |hash: &str| api::report_body(hash, "backdoor", "opens a shell", &[]);
+        assert_eq!(body(&from_path), body(&from_hash));
+        assert_eq!(body(&from_path), body(&prefixed));
+        assert_eq!(body(&from_path)["hash"], digest);
+        assert!(body(&from_path).get("findings").is_none());
+
+        assert!(report_hash(&dir.path().to_string_lossy()).is_err());
+        assert!(report_hash("not-a-hash").is_err());
+        assert!(report_hash(&"a".repeat(63)).is_err());
+
+        let findings = crate::scanner::run_package_scan(&file).findings;
+        assert!(!findings.is_empty());
+        let attached = api::report_body(&from_path, "backdoor", "opens a shell", &findings);
+        assert_eq!(
+            attached["findings"].as_array().unwrap().len(),
+            findings.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod install_tests {
+    use super::{install_binary, InstallPlan};
+    use std::ffi::OsString;
+    use std::fs;
+
+    #[test]
+    fn fresh_directory_on_path_is_installable() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("build-sigil");
+        fs::write(&source, b"binary").unwrap();
+        let bin = dir.path().join("bin");
+        fs::create_dir(&bin).unwrap();
+        let path_env = std::env::join_paths([dir.path().join("other"), bin.clone()]).unwrap();
+
+        let plan = InstallPlan::check(&source, &bin, Some(&path_env));
+
+        assert_eq!(plan.target, bin.join("sigil"));
+        assert!(plan.dir_exists && plan.writable && plan.on_path);
+        assert!(plan.existing.is_none());
+        assert_eq!(fs::read_dir(&bin).unwrap().count(), 0, "probe left behind");
+    }
+
+    #[test]
+    fn missing_directory_off_path_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("build-sigil");
+        fs::write(&source, b"binary").unwrap();
+        let missing = dir.path().join("nope");
+
+        let plan = InstallPlan::check(&source, &missing, Some(&OsString::from("/usr/bin")));
+
+        assert!(!plan.dir_exists && !plan.writable && !plan.on_path);
+    }
+
+    #[test]
+    fn existing_install_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("build-sigil");
+        fs::write(&source, b"binary").unwrap();
+        // Not executable, so its version cannot be read.
+        fs::write(dir.path().join("sigil"), b"old").unwrap();
+
+        let plan = InstallPlan::check(&source, dir.path(), None);
+
+        assert_eq!(plan.existing, Some(None));
+        assert!(plan.writable);
+        assert_eq!(fs::read(dir.path().join("sigil")).unwrap(), b"old");
+    }
+
+    #[test]
+    fn installed_binary_matches_source_and_is_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("build-sigil");
+        fs::write(&source, b"#!/bin/sh\necho sigil 9.9.9\n").unwrap();
+        let bin = dir.path().join("bin");
+        fs::create_dir(&bin).unwrap();
+        fs::write(bin.join("sigil"), b"old binary").unwrap();
+
+        let digest = install_binary(&source, &bin.join("sigil")).unwrap();
+
+        assert_eq!(
+            fs::read(bin.join("sigil")).unwrap(),
+            fs::read(&source).unwrap()
+        );
+        assert_eq!(
+            digest,
+            crate::verify::digest_file(&source, "sha256").unwrap()
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(bin.join("sigil"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+        // Only the installed binary remains; the staging file is gone.
+        assert_eq!(fs::read_dir(&bin).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn failed_install_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin = dir.path().join("bin");
+        fs::create_dir(&bin).unwrap();
+
+        let err = install_binary(&dir.path().join("missing"), &bin.join("sigil")).unwrap_err();
+
+        assert!(!err.is_empty());
+        assert_eq!(fs::read_dir(&bin).unwrap().count(), 0);
+    }
+}
+
+#[cfg(test)]
+mod age_tests {
+    use super::parse_age;
+    use std::time::Duration;
+
+    #[test]
+    fn ages_take_a_unit() {
+        assert_eq!(parse_age("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_age("1h"), Ok(Duration::from_secs(3_600)));
+        assert_eq!(parse_age(" 7d "), Ok(Duration::from_secs(604_800)));
+        assert!(parse_age("15").unwrap_err().contains("missing unit"));
+        assert!(parse_age("3y").unwrap_err().contains("unknown unit"));
+        assert!(parse_age("h").is_err());
+        assert!(parse_age("99999999999999999999w").is_err());
+    }
+}
@@ -1,24 +1,160 @@
 use colored::Colorize;
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
+use crate::diff::{normalize_path, ScanDiff};
 use crate::quarantine::QuarantineEntry;
 use crate::scanner::{Finding, Phase, ScanResult, Severity, Verdict};
 
+// ---------------------------------------------------------------------------
+// Colour
+// ---------------------------------------------------------------------------
+
+/// Whether to emit ANSI colour. `--no-color` and a non-empty `NO_COLOR`
+/// always win; otherwise colour needs stdout to be a terminal, unless
+/// `CLICOLOR_FORCE` asks for it anyway.
+fn color_wanted(flag: bool, no_color: Option<&str>, force: Option<&str>, tty: bool) -> bool {
+    if flag || no_color.is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    tty || force.is_some_and(|v| v != "0")
+}
+
+/// Apply the colour decision for this process. Called once, before anything
+/// is printed.
+pub fn configure_color(no_color_flag: bool) {
+    use std::io::IsTerminal;
+    let no_color = std::env::var("NO_COLOR").ok();
+    let force = std::env::var("CLICOLOR_FORCE").ok();
+    colored::control::set_override(color_wanted(
+        no_color_flag,
+        no_color.as_deref(),
+        force.as_deref(),
+        io::stdout().is_terminal(),
+    ));
+}
+
+// ---------------------------------------------------------------------------
+// Result destination
+// ---------------------------------------------------------------------------
+
+/// The `--output` file. It is created on the first result written, so commands
+/// that produce no results leave nothing behind unless they ask for it.
+struct ResultFile {
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    error: Option<io::Error>,
+}
+
+static RESULT_FILE: Mutex<Option<ResultFile>> = Mutex::new(None);
+
+/// Send formatted results (text, json, sarif, html) to `path` instead of
+/// stdout. Colour is turned off so the file holds plain text.
+pub fn redirect_results(path: &Path) {
+    colored::control::set_override(false);
+    *RESULT_FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(ResultFile {
+        path: path.to_path_buf(),
+        writer: None,
+        error: None,
+    });
+}
+
+impl ResultFile {
+    /// Create the file unless that already happened or failed.
+    fn open(&mut self) {
+        if self.error.is_some() || self.writer.is_some() {
+            return;
+        }
+        match File::create(&self.path) {
+            Ok(f) => self.writer = Some(BufWriter::new(f)),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn write(&mut self, args: std::fmt::Arguments<'_>) {
+        self.open();
+        if let Some(Err(e)) = self.writer.as_mut().map(|w| w.write_fmt(args)) {
+            self.error = Some(e);
+        }
+    }
+
+    fn finish(mut self) -> Result<Option<PathBuf>, String> {
+        if let Some(writer) = self.writer.as_mut() {
+            if let Err(e) = writer.flush() {
+                self.error.get_or_insert(e);
+            }
+        }
+        match self.error {
+            Some(e) => Err(format!("failed to write {}: {}", self.path.display(), e)),
+            None => Ok(self.writer.map(|_| self.path)),
+        }
+    }
+}
+
+/// Flush the `--output` file. Returns its path if any result was written, or
+/// the first error hit while creating or writing it.
+pub fn finish_results() -> Result<Option<PathBuf>, String> {
+    match RESULT_FILE.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        Some(file) => file.finish(),
+        None => Ok(None),
+    }
+}
+
+/// Create the `--output` file even though no result will be written to it,
+/// for commands whose empty result is still an answer (`sigil list`).
+pub fn create_empty_results() {
+    if let Some(file) = RESULT_FILE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+    {
+        file.open();
+    }
+}
+
+#[doc(hidden)]
+pub fn write_result(args: std::fmt::Arguments<'_>) {
+    match RESULT_FILE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+    {
+        Some(file) => file.write(args),
+        None => print!("{}", args),
+    }
+}
+
+/// `print!` to the result destination.
+macro_rules! out {
+    ($($arg:tt)*) => {
+        $crate::output::write_result(format_args!($($arg)*))
+    };
+}
+
+/// `println!` to the result destination.
+macro_rules! outln {
+    () => {
+        $crate::output::write_result(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {{
+        $crate::output::write_result(format_args!($($arg)*));
+        $crate::output::write_result(format_args!("\n"));
+    }};
+}
+
+pub(crate) use outln;
+
 /// Return the path to the disclaimer-shown marker file (~/.sigil/.disclaimer_shown).
 fn disclaimer_marker_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".sigil")
-        .join(".disclaimer_shown")
+    crate::paths::in_home(".disclaimer_shown")
 }
 
 /// Check whether the user has suppressed disclaimers via config.
 fn disclaimer_suppressed() -> bool {
-    let config_path = dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".sigil")
-        .join("config");
+    let config_path = crate::paths::in_home("config");
     if let Ok(contents) = std::fs::read_to_string(&config_path) {
         return contents.lines().any(|l| l.trim() == "disclaimer=false");
     }
@@ -30,78 +166,76 @@ fn disclaimer_suppressed() -> bool {
 // ---------------------------------------------------------------------------
 
 /// Print the final verdict with color coding and ASCII art.
-pub fn print_verdict(verdict: &Verdict, format: &str) {
-    if format == "json" {
-        println!(
-            "{}",
-            serde_json::json!({ "verdict": format!("{}", verdict) })
-        );
+pub fn print_verdict(verdict: &Verdict) {
+    // --quiet: the verdict alone, without the banner or disclaimer.
+    if crate::logging::quiet() {
+        outln!("{}", verdict);
         return;
     }
 
-    println!();
+    outln!();
     let line = "=".repeat(60);
 
     match verdict {
         Verdict::LowRisk => {
-            println!("{}", line.green());
-            println!(
+            outln!("{}", line.green());
+            outln!(
                 "{}",
                 "  LOW RISK -- No known malicious patterns detected"
                     .green()
                     .bold()
             );
-            println!("{}", line.green());
+            outln!("{}", line.green());
         }
         Verdict::MediumRisk => {
-            println!("{}", line.yellow());
-            println!(
+            outln!("{}", line.yellow());
+            outln!(
                 "{}",
                 "  MEDIUM RISK -- Suspicious patterns detected"
                     .yellow()
                     .bold()
             );
-            println!("{}", line.yellow());
+            outln!("{}", line.yellow());
         }
         Verdict::HighRisk => {
-            println!("{}", line.red());
-            println!(
+            outln!("{}", line.red());
+            outln!(
                 "{}",
                 "  HIGH RISK -- Likely malicious patterns found"
                     .red()
                     .bold()
             );
-            println!("{}", line.red());
+            outln!("{}", line.red());
         }
         Verdict::CriticalRisk => {
-            println!("{}", line.red().bold());
-            println!(
+            outln!("{}", line.red().bold());
+            outln!(
                 "{}",
                 "  CRITICAL RISK -- Almost certainly malicious!"
                     .red()
                     .bold()
             );
-            println!("{}", "  DO NOT install or execute this code.".red().bold());
-            println!("{}", line.red().bold());
+            outln!("{}", "  DO NOT install or execute this code.".red().bold());
+            outln!("{}", line.red().bold());
         }
     }
-    println!();
+    outln!();
 
     // Disclaimer: long form on first run, short on subsequent (configurable)
     if !disclaimer_suppressed() {
         let marker = disclaimer_marker_path();
         if !marker.exists() {
             // First-run: show long disclaimer
-            println!(
+            outln!(
                 "{}",
                 "  Note: Sigil scans detect known malicious patterns through static analysis."
                     .dimmed()
             );
-            println!(
+            outln!(
                 "{}",
                 "  A low risk result does not guarantee the absence of all threats.".dimmed()
             );
-            println!(
+            outln!(
                 "{}",
                 "  Always review code before use. See sigilsec.ai/terms for full terms.".dimmed()
             );
@@ -112,7 +246,7 @@ pub fn print_verdict(verdict: &Verdict, format: &str) {
             let _ = std::fs::write(&marker, "");
         } else {
             // Subsequent runs: short disclaimer
-            println!(
+            outln!(
                 "{}",
                 "  \u{2139} Scan results are not a guarantee of safety. Review code before use."
                     .dimmed()
@@ -120,7 +254,7 @@ pub fn print_verdict(verdict: &Verdict, format: &str) {
         }
     }
 
-    println!();
+    outln!();
 }
 
 // ---------------------------------------------------------------------------
@@ -129,8 +263,15 @@ pub fn print_verdict(verdict: &Verdict, format: &str) {
 
 /// Print findings grouped by scan phase.
 pub fn print_findings(findings: &[Finding], format: &str) {
+    print_findings_sorted(findings, format, "phase", "critical-first");
+}
+
+/// Print findings laid out by `sort` (`phase`, `severity`, or `file`; see
+/// [`finding_groups`]).  The layout is for reading only: JSON keeps the
+/// findings in their canonical order whatever `sort` says.
+pub fn print_findings_sorted(findings: &[Finding], format: &str, sort: &str, severity_order: &str) {
     if format == "json" {
-        println!(
+        outln!(
             "{}",
             serde_json::to_string_pretty(findings).unwrap_or_default()
         );
@@ -138,62 +279,110 @@ pub fn print_findings(findings: &[Finding], format: &str) {
     }
 
     if findings.is_empty() {
-        println!("{} No findings.", "  [*]".green());
+        outln!("{} No findings.", "  [*]".green());
         return;
     }
 
-    // Group findings by phase
-    let mut by_phase: HashMap<String, Vec<&Finding>> = HashMap::new();
-    for finding in findings {
-        by_phase
-            .entry(format!("{}", finding.phase))
-            .or_default()
-            .push(finding);
-    }
+    for (title, group) in finding_groups(findings, sort, severity_order) {
+        outln!();
+        outln!(
+            "  {} {} ({} finding{})",
+            ">>".bold(),
+            title.bold(),
+            group.len(),
+            if group.len() == 1 { "" } else { "s" }
+        );
+        outln!("  {}", "-".repeat(56));
 
-    // Print in phase order
-    let phase_order = [
-        Phase::InstallHooks,
-        Phase::CodePatterns,
-        Phase::NetworkExfil,
-        Phase::Credentials,
-        Phase::Obfuscation,
-        Phase::Provenance,
-    ];
+        for finding in group {
+            let severity_str = format_severity(finding.severity);
+            let location = match finding.line {
+                Some(line) => format!("{}:{}", finding.file, line),
+                None => finding.file.clone(),
+            };
 
-    for phase in &phase_order {
-        let key = format!("{}", phase);
-        if let Some(phase_findings) = by_phase.get(&key) {
-            println!();
-            println!(
-                "  {} {} ({} finding{})",
-                ">>".bold(),
-                key.bold(),
-                phase_findings.len(),
-                if phase_findings.len() == 1 { "" } else { "s" }
+            outln!(
+                "  {} [{}] {} ",
+                severity_str,
+                finding.rule.dimmed(),
+                location.bold()
             );
-            println!("  {}", "-".repeat(56));
-
-            for finding in phase_findings {
-                let severity_str = format_severity(finding.severity);
-                let location = match finding.line {
-                    Some(line) => format!("{}:{}", finding.file, line),
-                    None => finding.file.clone(),
-                };
-
-                println!(
-                    "  {} [{}] {} ",
-                    severity_str,
-                    finding.rule.dimmed(),
-                    location.bold()
-                );
-                println!("       {}", finding.snippet.dimmed());
+            outln!("       {}", finding.snippet.dimmed());
+        }
+    }
+}
+
+/// Findings in sections, each with its heading:
+///
+/// - `phase`: one section per scan phase, in scan order.
+/// - `severity`: one section per severity, Critical first, or Low first
+///   when `severity_order` is `low-first`.
+/// - `file`: one section per file, in path order, findings by line.
+///
+/// Within a section findings keep the order they were given in, apart from
+/// `file`, which also orders them by line.
+fn finding_groups<'a>(
+    findings: &'a [Finding],
+    sort: &str,
+    severity_order: &str,
+) -> Vec<(String, Vec<&'a Finding>)> {
+    match sort {
+        "severity" => {
+            let mut order = [
+                Severity::Critical,
+                Severity::High,
+                Severity::Medium,
+                Severity::Low,
+            ];
+            if severity_order == "low-first" {
+                order.reverse();
             }
+            order
+                .into_iter()
+                .map(|severity| {
+                    let group: Vec<&Finding> =
+                        findings.iter().filter(|f| f.severity == severity).collect();
+                    (severity.to_string(), group)
+                })
+                .filter(|(_, group)| !group.is_empty())
+                .collect()
+        }
+        "file" => {
+            let mut by_file: Vec<&Finding> = findings.iter().collect();
+            by_file.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+            let mut groups: Vec<(String, Vec<&Finding>)> = Vec::new();
+            for finding in by_file {
+                match groups.last_mut() {
+                    Some((file, group)) if *file == finding.file => group.push(finding),
+                    _ => groups.push((finding.file.clone(), vec![finding])),
+                }
+            }
+            groups
+        }
+        _ => {
+            let phase_order = [
+                Phase::InstallHooks,
+                Phase::CodePatterns,
+                Phase::NetworkExfil,
+                Phase::Credentials,
+                Phase::Obfuscation,
+                Phase::Provenance,
+            ];
+            phase_order
+                .into_iter()
+                .map(|phase| {
+                    let group: Vec<&Finding> =
+                        findings.iter().filter(|f| f.phase == phase).collect();
+                    (format!("{}", phase), group)
+                })
+                .filter(|(_, group)| !group.is_empty())
+                .collect()
         }
     }
 }
 
-/// Format a severity label with appropriate color.
+/// Format a severity label with appropriate color. Text output only: JSON
+/// paths serialize the `Severity` itself.
 fn format_severity(severity: Severity) -> String {
     match severity {
         Severity::Low => format!("{}", "LOW     ".dimmed()),
@@ -204,38 +393,282 @@ fn format_severity(severity: Severity) -> String {
 }
 
 // ---------------------------------------------------------------------------
-// Scan summary
+// Finding tree (`scan --tree`)
 // ---------------------------------------------------------------------------
 
-/// Print a summary with scan statistics.
-pub fn print_scan_summary(result: &ScanResult, format: &str) {
-    if format == "json" {
-        // Scalars only: scripts/run_eval.py locates the findings array by the
-        // first `[` in stdout, so this object must never contain an array.
-        let summary = serde_json::json!({
-            "files_scanned": result.files_scanned,
-            "findings_count": result.findings.len(),
-            "suppressed_count": result.suppressed_findings.len(),
-            "score": result.score,
-            "verdict": format!("{}", result.verdict),
-            "duration_ms": result.duration_ms,
+/// One path component in the finding tree, with totals for its subtree.
+#[derive(Default)]
+struct TreeNode {
+    children: HashMap<String, TreeNode>,
+    count: usize,
+    max: Option<Severity>,
+}
+
+impl TreeNode {
+    fn add(&mut self, parts: &[&str], severity: Severity) {
+        self.count += 1;
+        self.max = self.max.max(Some(severity));
+        if let Some((first, rest)) = parts.split_first() {
+            self.children
+                .entry(first.to_string())
+                .or_default()
+                .add(rest, severity);
+        }
+    }
+
+    /// Hottest first: highest severity, then most findings, then name.
+    fn sorted_children(&self) -> Vec<(&String, &TreeNode)> {
+        let mut children: Vec<_> = self.children.iter().collect();
+        children.sort_by(|(a_name, a), (b_name, b)| {
+            b.max
+                .cmp(&a.max)
+                .then(b.count.cmp(&a.count))
+                .then(a_name.cmp(b_name))
         });
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&summary).unwrap_or_default()
+        children
+    }
+
+    /// `name` followed by the directories it is the only entry of, so a
+    /// chain like `src/lib/util/` takes one line.  Returns the label and the
+    /// node whose children come next.
+    fn collapse<'a>(&'a self, name: &str) -> (String, &'a TreeNode) {
+        let mut label = name.to_string();
+        let mut node = self;
+        while node.children.len() == 1 {
+            let (child_name, child) = node.children.iter().next().unwrap();
+            if child.children.is_empty() || child.count != node.count {
+                break;
+            }
+            label = format!("{}/{}", label, child_name);
+            node = child;
+        }
+        if !node.children.is_empty() {
+            label.push('/');
+        }
+        (label, node)
+    }
+}
+
+/// The finding tree as `(drawn line, finding count, max severity)` rows,
+/// starting with the scan root.  Only paths with findings appear; clean
+/// files and directories are left out.
+fn finding_tree_rows(findings: &[Finding], root: &str) -> Vec<(String, usize, Severity)> {
+    fn walk(node: &TreeNode, prefix: &str, rows: &mut Vec<(String, usize, Severity)>) {
+        let children = node.sorted_children();
+        let last = children.len().saturating_sub(1);
+        for (i, (name, child)) in children.into_iter().enumerate() {
+            let (branch, indent) = if i == last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            let (label, next) = child.collapse(name);
+            if let Some(max) = child.max {
+                rows.push((format!("{}{}{}", prefix, branch, label), child.count, max));
+            }
+            walk(next, &format!("{}{}", prefix, indent), rows);
+        }
+    }
+
+    let mut tree = TreeNode::default();
+    for finding in findings {
+        let path = normalize_path(&finding.file);
+        let parts: Vec<&str> = path
+            .split('/')
+            .filter(|p| !p.is_empty() && *p != ".")
+            .collect();
+        tree.add(&parts, finding.severity);
+    }
+    let Some(max) = tree.max else {
+        return Vec::new();
+    };
+    let mut rows = vec![(format!("{}/", root.trim_end_matches('/')), tree.count, max)];
+    walk(&tree, "", &mut rows);
+    rows
+}
+
+/// Print findings as a tree of the scanned directory, each node annotated
+/// with its finding count and highest severity.
+pub fn print_finding_tree(findings: &[Finding], root: &str) {
+    if findings.is_empty() {
+        outln!("{} No findings.", "  [*]".green());
+        return;
+    }
+    outln!();
+    for (line, count, max) in finding_tree_rows(findings, root) {
+        outln!(
+            "  {}  ({} finding{}, max {})",
+            line.bold(),
+            count,
+            if count == 1 { "" } else { "s" },
+            format_severity(max).trim_end()
         );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Scan summary
+// ---------------------------------------------------------------------------
+
+/// The JSON scan summary: the scalar half of the `--format json` report
+/// (see [`scan_report_json`]), and what post-scan hooks read on stdin.
+pub fn scan_summary_json(result: &ScanResult) -> serde_json::Value {
+    let mut summary = serde_json::json!({
+        "files_scanned": result.files_scanned,
+        "findings_count": result.findings.len(),
+        "suppressed_count": result.suppressed_findings.len(),
+        "score": result.score,
+        "verdict": format!("{}", result.verdict),
+        "duration_ms": result.duration_ms,
+        "timed_out": result.timed_out,
+        "unscanned_files": result.unscanned_files,
+        "unreadable_files": result.unreadable_files,
+        "baseline_suppressed": result.baseline_suppressed,
+        "phase_scores": result.phase_scores,
+        "truncated": result.truncated,
+        "total_matched": result.total_matched,
+        "allowlisted": result.allowlisted.len(),
+        "streamed_files": result.streamed_files.len(),
+    });
+    if let Some(timings) = &result.phase_timings {
+        summary["phase_timings"] = serde_json::json!(timings);
+    }
+    if let Some(meta) = &result.meta {
+        summary["meta"] = serde_json::json!(meta);
+    }
+    summary
+}
+
+/// The `--format json` scan report: one object holding the summary, the
+/// findings, the `top` files by risk (when `top` is non-zero), and any
+/// findings a ledger approval suppressed.
+pub fn scan_report_json(result: &ScanResult, top: usize) -> serde_json::Value {
+    let mut report = scan_summary_json(result);
+    report["findings"] = serde_json::json!(result.findings);
+    if top > 0 {
+        report["top_files"] =
+            serde_json::json!(crate::scanner::scoring::top_files(&result.findings, top));
+    }
+    if let Some(by) = &result.suppressed_by {
+        report["suppressed_by"] = serde_json::json!(by);
+        report["suppressed_findings"] = serde_json::json!(result.suppressed_findings);
+    }
+    report
+}
+
+/// Split saved `sigil scan -f json` output back into its summary and its
+/// findings.  Reads the one-object report, and also the summary, findings
+/// array, and verdict documents older versions printed one after another.
+pub fn parse_scan_report(
+    data: &str,
+) -> Result<
+    (
+        serde_json::Map<String, serde_json::Value>,
+        Vec<serde_json::Value>,
+    ),
+    String,
+> {
+    let start = data
+        .find(['{', '['])
+        .ok_or("not `sigil scan -f json` output")?;
+    let mut summary = serde_json::Map::new();
+    for document in serde_json::Deserializer::from_str(&data[start..]).into_iter() {
+        match document.map_err(|e| e.to_string())? {
+            serde_json::Value::Object(mut obj) => {
+                if let Some(serde_json::Value::Array(findings)) = obj.remove("findings") {
+                    return Ok((obj, findings));
+                }
+                if summary.is_empty() {
+                    summary = obj;
+                }
+            }
+            serde_json::Value::Array(findings) => return Ok((summary, findings)),
+            _ => {}
+        }
+    }
+    Err("no findings array".to_string())
+}
+
+/// Print a scan result: the summary, the findings, and the verdict, or the
+/// single [`scan_report_json`] object for `--format json`.
+pub fn print_scan_result(result: &ScanResult, format: &str) {
+    if format == "json" {
+        print_scan_json(result, 0);
         return;
     }
+    print_scan_summary(result);
+    print_findings(&result.findings, format);
+    print_verdict(&result.verdict);
+}
 
-    println!();
-    println!(
+/// Print the [`scan_report_json`] object.
+pub fn print_scan_json(result: &ScanResult, top: usize) {
+    outln!(
+        "{}",
+        serde_json::to_string_pretty(&scan_report_json(result, top)).unwrap_or_default()
+    );
+}
+
+/// Print a summary with scan statistics.
+pub fn print_scan_summary(result: &ScanResult) {
+    if crate::logging::quiet() {
+        return;
+    }
+
+    outln!();
+    outln!(
         "  {} Scan complete in {}ms",
         "sigil".bold().cyan(),
         result.duration_ms
     );
-    println!("  {} files scanned", result.files_scanned);
-    println!("  {} findings", result.findings.len());
-    println!("  Risk score: {}", format_score(result.score));
+    outln!("  {} files scanned", result.files_scanned);
+    if result.timed_out {
+        outln!(
+            "  {} scan timed out: {} file(s) not scanned (partial result)",
+            "[!]".yellow().bold(),
+            result.unscanned_files
+        );
+    }
+    if result.unreadable_files > 0 {
+        outln!(
+            "  {} {} file(s) could not be read and were not scanned",
+            "[!]".yellow().bold(),
+            result.unreadable_files
+        );
+    }
+    outln!("  {} findings", result.findings.len());
+    if result.truncated {
+        outln!(
+            "  {} findings capped at {} (--max-findings); at least {} matched",
+            "[!]".yellow().bold(),
+            result.findings.len(),
+            result.total_matched
+        );
+    }
+    if !result.allowlisted.is_empty() {
+        outln!("  {} allowlisted file(s) skipped", result.allowlisted.len());
+    }
+    if !result.streamed_files.is_empty() {
+        outln!(
+            "  {} file(s) too large to read whole, scanned line by line (multiline rules skipped): {}",
+            result.streamed_files.len(),
+            result.streamed_files.join(", ")
+        );
+    }
+    if result.baseline_suppressed > 0 {
+        outln!(
+            "  {} known finding(s) suppressed by baseline",
+            result.baseline_suppressed
+        );
+    }
+    if result.truncated {
+        outln!("  Risk score: {} (lower bound)", format_score(result.score));
+    } else {
+        outln!("  Risk score: {}", format_score(result.score));
+    }
+    if let Some(line) = phase_score_line(result) {
+        outln!("  By phase: {}", line);
+    }
 
     // Count by severity
     let mut critical = 0u32;
@@ -255,7 +688,7 @@ pub fn print_scan_summary(result: &ScanResult, format: &str) {
         return;
     }
 
-    println!(
+    outln!(
         "  Breakdown: {} critical, {} high, {} medium, {} low",
         if critical > 0 {
             format!("{}", critical).red().bold().to_string()
@@ -276,7 +709,108 @@ pub fn print_scan_summary(result: &ScanResult, format: &str) {
     );
 }
 
-/// Format the numeric score with color (thresholds: 0/10/25/50).
+/// `Credentials: 45, Obfuscation: 20, ...`: each phase's share of the score,
+/// largest first.  `None` when nothing scored.
+pub fn phase_score_line(result: &ScanResult) -> Option<String> {
+    let mut scores: Vec<(&Phase, &u32)> = result.phase_scores.iter().collect();
+    if scores.is_empty() {
+        return None;
+    }
+    scores.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    Some(
+        scores
+            .iter()
+            .map(|(phase, score)| format!("{}: {}", phase, score))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+/// `--top N`: the files whose findings add most to the score, so a
+/// reviewer of a large package knows where to look first.  JSON carries them
+/// as `top_files` in the [`scan_report_json`] object.
+pub fn print_top_files(result: &ScanResult, n: usize) {
+    let top = crate::scanner::scoring::top_files(&result.findings, n);
+    if top.is_empty() || crate::logging::quiet() {
+        return;
+    }
+    outln!("  Top files by risk:");
+    for file in top {
+        outln!(
+            "    {:>5}  {} {} ({} finding{})",
+            file.score,
+            format_severity(file.worst_severity),
+            file.file.bold(),
+            file.findings,
+            if file.findings == 1 { "" } else { "s" }
+        );
+    }
+}
+
+/// `--verbose` breakdown of where the scan spent its time, slowest stage
+/// first, one line per stage.  Empty when timings were not recorded.
+pub fn phase_timing_lines(result: &ScanResult) -> Vec<String> {
+    let Some(timings) = &result.phase_timings else {
+        return Vec::new();
+    };
+    let total: f64 = timings.values().sum();
+    let mut stages: Vec<(&String, &f64)> = timings.iter().collect();
+    stages.sort_by(|a, b| b.1.total_cmp(a.1).then(a.0.cmp(b.0)));
+    let mut lines = vec![format!(
+        "time by stage (summed over worker threads; {}ms wall clock):",
+        result.duration_ms
+    )];
+    lines.extend(stages.into_iter().map(|(stage, ms)| {
+        format!(
+            "  {:<18} {:>10.3}ms {:>5.1}%",
+            stage,
+            ms,
+            if total > 0.0 { ms / total * 100.0 } else { 0.0 }
+        )
+    }));
+    lines
+}
+
+/// One-line `key=value` summary for CI gates (`--metrics`).  The keys and
+/// their order are a stable interface: add new keys at the end, never
+/// rename or reorder existing ones.
+pub fn metrics_line(result: &ScanResult) -> String {
+    let count = |severity: Severity| {
+        result
+            .findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    };
+    format!(
+        "score={} verdict={:?} critical={} high={} medium={} low={} files={} duration_ms={} timed_out={}",
+        result.score,
+        result.verdict,
+        count(Severity::Critical),
+        count(Severity::High),
+        count(Severity::Medium),
+        count(Severity::Low),
+        result.files_scanned,
+        result.duration_ms,
+        result.timed_out
+    )
+}
+
+/// Write the metrics line to `file`, or to stderr when no file is given,
+/// whatever the main `--format` is.
+pub fn print_metrics_line(result: &ScanResult, file: Option<&Path>) -> io::Result<()> {
+    let line = metrics_line(result);
+    match file {
+        Some(path) => std::fs::write(path, format!("{}\n", line)),
+        None => {
+            eprintln!("{}", line);
+            Ok(())
+        }
+    }
+}
+
+/// Format the numeric score with color (thresholds: 0/10/25/50). Text
+/// output only: JSON paths must emit the raw number.
 fn format_score(score: u32) -> String {
     if score == 0 {
         format!("{}", "0".green().bold())
@@ -294,22 +828,90 @@ fn format_score(score: u32) -> String {
 // ---------------------------------------------------------------------------
 
 /// Print a list of quarantine entries.
-pub fn print_quarantine_list(entries: &[QuarantineEntry], detailed: bool, format: &str) {
+/// Version of the `sigil list --format json` envelope. Bump it when a field
+/// is removed or changes meaning; adding fields is backwards compatible.
+pub const LIST_SCHEMA_VERSION: u32 = 1;
+
+/// The external `list` JSON shape, decoupled from `QuarantineEntry` so
+/// internal struct changes do not break tooling.
+#[derive(serde::Serialize)]
+struct ListedEntry<'a> {
+    id: &'a str,
+    source: &'a str,
+    source_type: &'a str,
+    path: String,
+    status: &'static str,
+    created_at: String,
+    updated_at: String,
+    reason: Option<&'a str>,
+    scan_score: Option<u32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    path_missing: bool,
+}
+
+impl<'a> From<&'a QuarantineEntry> for ListedEntry<'a> {
+    fn from(entry: &'a QuarantineEntry) -> Self {
+        use crate::quarantine::QuarantineStatus;
+        ListedEntry {
+            id: &entry.id,
+            source: &entry.source,
+            source_type: &entry.source_type,
+            path: entry.path.display().to_string(),
+            status: match entry.status {
+                QuarantineStatus::Pending => "pending",
+                QuarantineStatus::Approved => "approved",
+                QuarantineStatus::Rejected => "rejected",
+            },
+            created_at: entry.created_at.to_rfc3339(),
+            updated_at: entry.updated_at.to_rfc3339(),
+            reason: entry.reason.as_deref(),
+            scan_score: entry.scan_score,
+            path_missing: entry.path_missing,
+        }
+    }
+}
+
+/// Build the versioned `list` JSON envelope for one page of entries.
+pub fn quarantine_list_json(
+    entries: &[QuarantineEntry],
+    total: usize,
+    offset: usize,
+    limit: Option<usize>,
+) -> serde_json::Value {
+    let listed: Vec<ListedEntry<'_>> = entries.iter().map(ListedEntry::from).collect();
+    serde_json::json!({
+        "schema_version": LIST_SCHEMA_VERSION,
+        "entries": listed,
+        "total": total,
+        "offset": offset,
+        "limit": limit,
+    })
+}
+
+pub fn print_quarantine_list(
+    entries: &[QuarantineEntry],
+    total: usize,
+    offset: usize,
+    limit: Option<usize>,
+    detailed: bool,
+    format: &str,
+) {
     if format == "json" {
-        println!(
+        let envelope = quarantine_list_json(entries, total, offset, limit);
+        outln!(
             "{}",
-            serde_json::to_string_pretty(entries).unwrap_or_default()
+            serde_json::to_string_pretty(&envelope).unwrap_or_default()
         );
         return;
     }
 
-    println!();
-    println!(
+    outln!();
+    outln!(
         "  {} Quarantined items ({})",
         "sigil".bold().cyan(),
         entries.len()
     );
-    println!("  {}", "-".repeat(60));
+    outln!("  {}", "-".repeat(60));
 
     for entry in entries {
         let status_str = match &entry.status {
@@ -318,7 +920,7 @@ pub fn print_quarantine_list(entries: &[QuarantineEntry], detailed: bool, format
             crate::quarantine::QuarantineStatus::Rejected => "REJECTED".red().to_string(),
         };
 
-        println!(
+        outln!(
             "  {} [{}] {} ({})",
             status_str,
             entry.id.dimmed(),
@@ -327,24 +929,40 @@ pub fn print_quarantine_list(entries: &[QuarantineEntry], detailed: bool, format
         );
 
         if detailed {
-            println!("       Path:    {}", entry.path.display());
-            println!(
+            if entry.path_missing {
+                outln!(
+                    "       Path:    {} (files not imported)",
+                    entry.path.display()
+                );
+            } else {
+                outln!("       Path:    {}", entry.path.display());
+            }
+            outln!(
                 "       Created: {}",
                 entry.created_at.format("%Y-%m-%d %H:%M:%S UTC")
             );
-            println!(
+            outln!(
                 "       Updated: {}",
                 entry.updated_at.format("%Y-%m-%d %H:%M:%S UTC")
             );
             if let Some(ref reason) = entry.reason {
-                println!("       Reason:  {}", reason);
+                outln!("       Reason:  {}", reason);
             }
             if let Some(score) = entry.scan_score {
-                println!("       Score:   {}", format_score(score));
+                outln!("       Score:   {}", format_score(score));
             }
-            println!();
+            outln!();
         }
     }
+
+    if entries.len() < total {
+        outln!(
+            "  showing {}-{} of {} (use --offset/--limit to page)",
+            offset + 1,
+            offset + entries.len(),
+            total
+        );
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -357,7 +975,101 @@ pub fn print_quarantine_list(entries: &[QuarantineEntry], detailed: bool, format
 /// is consumed by GitHub Code Scanning, VS Code SARIF Viewer, and other
 /// security tooling.
 pub fn print_scan_sarif(result: &ScanResult, target: &str) {
-    let sarif = serde_json::json!({
+    let mut properties = serde_json::json!({
+        "riskScore": result.score,
+        "verdict": format!("{:?}", result.verdict),
+        "filesScanned": result.files_scanned,
+        "durationMs": result.duration_ms,
+        "timedOut": result.timed_out,
+        "unscannedFiles": result.unscanned_files,
+        "unreadableFiles": result.unreadable_files,
+        "phaseScores": result.phase_scores,
+        "truncated": result.truncated,
+        "totalMatched": result.total_matched,
+        "allowlistedFiles": result.allowlisted,
+        "streamedFiles": result.streamed_files
+    });
+    if let Some(meta) = &result.meta {
+        properties["meta"] = serde_json::json!(meta);
+    }
+    let mut sarif = sarif_document(
+        &result.findings,
+        vec![sarif_root(target)],
+        !result.timed_out,
+        properties,
+    );
+    if let Some(meta) = &result.meta {
+        sarif["runs"][0]["invocations"][0]["startTimeUtc"] = serde_json::json!(meta.timestamp);
+    }
+    outln!("{}", serde_json::to_string_pretty(&sarif).unwrap());
+}
+
+/// Print a scan diff as SARIF 2.1.0 carrying only the new findings, so a
+/// Code Scanning upload shows just what a change introduced. The baseline
+/// and the diff counts are recorded in `invocations[0].properties`.
+pub fn print_diff_sarif(diff: &ScanDiff, target: &str, baseline: &str) {
+    let sarif = sarif_document(
+        &diff.new_findings,
+        vec![sarif_root(target)],
+        true,
+        diff_sarif_properties(diff, baseline),
+    );
+    outln!("{}", serde_json::to_string_pretty(&sarif).unwrap());
+}
+
+fn diff_sarif_properties(diff: &ScanDiff, baseline: &str) -> serde_json::Value {
+    serde_json::json!({
+        "baseline": baseline,
+        "newFindings": diff.new_findings.len(),
+        "resolvedFindings": diff.resolved_findings.len(),
+        "unchangedFindings": diff.unchanged_findings.len(),
+        "modifiedFindings": diff.modified_findings.len(),
+        "scoreDelta": diff.score_delta,
+        "previousVerdict": format!("{:?}", diff.previous_verdict),
+        "verdict": format!("{:?}", diff.current_verdict)
+    })
+}
+
+/// The artifact for a scanned directory.
+fn sarif_root(target: &str) -> serde_json::Value {
+    serde_json::json!({
+        "location": {
+            "uri": target,
+            "uriBaseId": "%SRCROOT%"
+        }
+    })
+}
+
+/// Build a single-run SARIF document for `findings` under the scanned
+/// `roots` (artifact objects, one per scanned directory).
+fn sarif_document(
+    findings: &[Finding],
+    roots: Vec<serde_json::Value>,
+    execution_successful: bool,
+    invocation_properties: serde_json::Value,
+) -> serde_json::Value {
+    // The roots come first; each hashed file gets its own entry so results
+    // can point at it and carry `hashes.sha-256`.
+    let mut artifacts = roots;
+    let mut artifact_index: HashMap<&str, usize> = HashMap::new();
+    for f in findings {
+        if let Some(sha256) = &f.sha256 {
+            artifact_index.entry(f.file.as_str()).or_insert_with(|| {
+                artifacts.push(serde_json::json!({
+                    "location": {
+                        "uri": normalize_path(&f.file),
+                        "uriBaseId": "%SRCROOT%"
+                    },
+                    "hashes": {
+                        "sha-256": sha256
+                    }
+                }));
+                artifacts.len() - 1
+            });
+        }
+    }
+
+    serde_json::json!({
         "$schema": "https://raw.githubusercontent.com/oasis-tcs/sarif-spec/main/sarif-2.1/schema/sarif-schema-2.1.0.json",
         "version": "2.1.0",
         "runs": [{
@@ -366,10 +1078,17 @@ pub fn print_scan_sarif(result: &ScanResult, target: &str) {
                     "name": "Sigil",
                     "version": env!("CARGO_PKG_VERSION"),
                     "informationUri": "https://github.com/nomark/sigil",
-                    "rules": generate_rules(&result.findings)
+                    "rules": generate_rules(findings)
                 }
             },
-            "results": result.findings.iter().map(|f| {
+            "results": findings.iter().map(|f| {
+                let mut artifact_location = serde_json::json!({
+                    "uri": normalize_path(&f.file),
+                    "uriBaseId": "%SRCROOT%"
+                });
+                if let Some(index) = artifact_index.get(f.file.as_str()) {
+                    artifact_location["index"] = serde_json::json!(index);
+                }
                 serde_json::json!({
                     "ruleId": f.rule,
                     "level": severity_to_sarif_level(f.severity),
@@ -378,41 +1097,271 @@ pub fn print_scan_sarif(result: &ScanResult, target: &str) {
                     },
                     "locations": [{
                         "physicalLocation": {
-                            "artifactLocation": {
-                                "uri": f.file.clone(),
-                                "uriBaseId": "%SRCROOT%"
-                            },
-                            "region": {
-                                "startLine": f.line.unwrap_or(1),
-                                "startColumn": 1
-                            }
+                            "artifactLocation": artifact_location,
+                            "region": sarif_region(f)
                         }
                     }],
                     "properties": {
                         "phase": format!("{:?}", f.phase),
-                        "weight": f.weight
+                        "weight": f.weight,
+                        "confidence": f.confidence
                     }
                 })
             }).collect::<Vec<_>>(),
             "invocations": [{
-                "executionSuccessful": true,
-                "properties": {
-                    "riskScore": result.score,
-                    "verdict": format!("{:?}", result.verdict),
-                    "filesScanned": result.files_scanned,
-                    "durationMs": result.duration_ms
-                }
+                "executionSuccessful": execution_successful,
+                "properties": invocation_properties
             }],
-            "artifacts": [{
-                "location": {
-                    "uri": target,
-                    "uriBaseId": "%SRCROOT%"
-                }
-            }]
+            "artifacts": artifacts
         }]
+    })
+}
+
+/// The SARIF `region` of a finding: the exact match span when the rule
+/// engine recorded one, otherwise the whole line.
+fn sarif_region(f: &Finding) -> serde_json::Value {
+    match &f.region {
+        Some(region) => serde_json::json!({
+            "startLine": region.start_line,
+            "startColumn": region.start_column,
+            "endLine": region.end_line,
+            "endColumn": region.end_column,
+            "charOffset": region.char_offset,
+            "charLength": region.char_length
+        }),
+        None => serde_json::json!({
+            "startLine": f.line.unwrap_or(1),
+            "startColumn": 1
+        }),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Quarantine audit report (`sigil report-all`)
+// ---------------------------------------------------------------------------
+
+/// A quarantine entry and a fresh scan of it.  Finding paths are prefixed
+/// with the entry's `source`, so findings from different packages stay
+/// apart once merged.
+pub struct AuditEntry {
+    pub entry: QuarantineEntry,
+    pub result: ScanResult,
+}
+
+impl AuditEntry {
+    pub fn new(entry: QuarantineEntry, mut result: ScanResult) -> Self {
+        let source = entry.source.trim_end_matches('/');
+        for finding in &mut result.findings {
+            finding.file = format!("{}/{}", source, normalize_path(&finding.file));
+        }
+        AuditEntry { entry, result }
+    }
+
+    fn summary_json(&self) -> serde_json::Value {
+        let mut summary = serde_json::json!(ListedEntry::from(&self.entry));
+        summary["scan_score"] = serde_json::json!(self.result.score);
+        summary["verdict"] = serde_json::json!(self.result.verdict);
+        summary["findings"] = serde_json::json!(self.result.findings.len());
+        summary
+    }
+}
+
+/// The merged report as one JSON document: a summary per entry and every
+/// finding tagged with the entry it came from.
+pub fn audit_json(audit: &[AuditEntry]) -> serde_json::Value {
+    let findings: Vec<serde_json::Value> = audit
+        .iter()
+        .flat_map(|a| {
+            a.result.findings.iter().map(|f| {
+                let mut finding = serde_json::json!(f);
+                finding["quarantine_id"] = serde_json::json!(a.entry.id);
+                finding
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+        "entries": audit.iter().map(AuditEntry::summary_json).collect::<Vec<_>>(),
+        "findings": findings,
+    })
+}
+
+/// The merged report as a single SARIF run with one root artifact per
+/// quarantine source.
+pub fn audit_sarif(audit: &[AuditEntry]) -> serde_json::Value {
+    let roots = audit
+        .iter()
+        .map(|a| {
+            serde_json::json!({
+                "location": { "uri": a.entry.source },
+                "properties": a.summary_json()
+            })
+        })
+        .collect();
+    let findings: Vec<Finding> = audit
+        .iter()
+        .flat_map(|a| a.result.findings.iter().cloned())
+        .collect();
+    let properties = serde_json::json!({
+        "entries": audit.len(),
+        "findings": findings.len()
     });
+    let mut sarif = sarif_document(&findings, roots, true, properties);
+    let ids = audit
+        .iter()
+        .flat_map(|a| a.result.findings.iter().map(|_| a.entry.id.as_str()));
+    if let Some(results) = sarif["runs"][0]["results"].as_array_mut() {
+        for (result, id) in results.iter_mut().zip(ids) {
+            result["properties"]["quarantineId"] = serde_json::json!(id);
+        }
+    }
+    sarif
+}
 
-    println!("{}", serde_json::to_string_pretty(&sarif).unwrap());
+/// Quote a CSV field when it holds a comma, quote, or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// The merged report as CSV, one row per finding.
+pub fn audit_csv(audit: &[AuditEntry]) -> Vec<String> {
+    let mut rows = vec![
+        "quarantine_id,source,status,file,line,rule,severity,phase,weight,snippet".to_string(),
+    ];
+    for a in audit {
+        for f in &a.result.findings {
+            let fields = [
+                a.entry.id.clone(),
+                a.entry.source.clone(),
+                a.entry.status.to_string(),
+                f.file.clone(),
+                f.line.map(|l| l.to_string()).unwrap_or_default(),
+                f.rule.clone(),
+                format!("{:?}", f.severity),
+                format!("{:?}", f.phase),
+                f.weight.to_string(),
+                f.snippet.clone(),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            rows.push(row.join(","));
+        }
+    }
+    rows
+}
+
+/// Print the quarantine audit report in `format` (json, sarif, csv, or a
+/// text table).
+pub fn print_audit_report(audit: &[AuditEntry], format: &str) {
+    match format {
+        "json" => outln!(
+            "{}",
+            serde_json::to_string_pretty(&audit_json(audit)).unwrap_or_default()
+        ),
+        "sarif" => outln!(
+            "{}",
+            serde_json::to_string_pretty(&audit_sarif(audit)).unwrap_or_default()
+        ),
+        "csv" => {
+            for row in audit_csv(audit) {
+                outln!("{}", row);
+            }
+        }
+        _ => {
+            outln!();
+            outln!(
+                "  {} Quarantine audit ({} entries)",
+                "sigil".bold().cyan(),
+                audit.len()
+            );
+            outln!("  {}", "-".repeat(60));
+            for a in audit {
+                outln!(
+                    "  {} [{}] {} ({}, {} finding{})",
+                    format_score(a.result.score),
+                    a.entry.id.dimmed(),
+                    a.entry.source.bold(),
+                    a.result.verdict,
+                    a.result.findings.len(),
+                    if a.result.findings.len() == 1 {
+                        ""
+                    } else {
+                        "s"
+                    }
+                );
+            }
+            let total: usize = audit.iter().map(|a| a.result.findings.len()).sum();
+            outln!();
+            outln!("  {} findings in total", total);
+        }
+    }
+}
+
+/// Print the `sigil aggregate` roll-up as JSON or text.
+pub fn print_org_summary(summary: &crate::aggregate::OrgSummary, format: &str) {
+    if format == "json" {
+        outln!(
+            "{}",
+            serde_json::to_string_pretty(summary).unwrap_or_default()
+        );
+        return;
+    }
+    outln!();
+    outln!(
+        "  {} {} package{}, {} finding{}",
+        "sigil".bold().cyan(),
+        summary.packages,
+        if summary.packages == 1 { "" } else { "s" },
+        summary.total_findings,
+        if summary.total_findings == 1 { "" } else { "s" }
+    );
+    if summary.skipped > 0 {
+        outln!(
+            "  {} {} file(s) skipped (not a readable scan result)",
+            "[!]".yellow().bold(),
+            summary.skipped
+        );
+    }
+    outln!(
+        "  Verdicts: {}",
+        summary
+            .verdicts
+            .iter()
+            .map(|v| format!("{} {}", v.packages, v.verdict))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if !summary.worst.is_empty() {
+        outln!();
+        outln!("  Worst packages:");
+        for package in &summary.worst {
+            outln!(
+                "    {} {} ({}, {} finding{})",
+                format_score(package.score),
+                package.source.bold(),
+                package.verdict,
+                package.findings,
+                if package.findings == 1 { "" } else { "s" }
+            );
+        }
+    }
+    if !summary.top_rules.is_empty() {
+        outln!();
+        outln!("  Most common rules:");
+        for rule in &summary.top_rules {
+            outln!(
+                "    {:<14} {} package{}, {} finding{}",
+                rule.rule,
+                rule.packages,
+                if rule.packages == 1 { "" } else { "s" },
+                rule.findings,
+                if rule.findings == 1 { "" } else { "s" }
+            );
+        }
+    }
 }
 
 /// Map a Severity to the SARIF level string.
@@ -450,3 +1399,839 @@ fn generate_rules(findings: &[Finding]) -> Vec<serde_json::Value> {
         })
         .collect()
 }
+
+// ---------------------------------------------------------------------------
+// HTML report
+// ---------------------------------------------------------------------------
+
+/// Inline stylesheet for the HTML report; the file must stand alone.
+const HTML_STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; margin: 2rem; color: #1f2328; }
+h1 { margin-bottom: 0.25rem; }
+.target { color: #59636e; margin-top: 0; word-break: break-all; }
+.summary { display: flex; gap: 1rem; flex-wrap: wrap; margin: 1.5rem 0; }
+.card { border: 1px solid #d1d9e0; border-radius: 6px; padding: 0.75rem 1rem; min-width: 7rem; }
+.card .label { font-size: 0.75rem; text-transform: uppercase; color: #59636e; }
+.card .value { font-size: 1.5rem; font-weight: 600; }
+.verdict-low { background: #dafbe1; } .verdict-medium { background: #fff8c5; }
+.verdict-high { background: #ffebe9; } .verdict-critical { background: #cf222e; color: #fff; }
+.verdict-critical .label { color: #fff; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2rem; }
+th, td { text-align: left; padding: 0.4rem 0.6rem; border-bottom: 1px solid #d1d9e0; vertical-align: top; }
+th { background: #f6f8fa; }
+.sev { font-weight: 600; white-space: nowrap; }
+.sev-low { color: #59636e; } .sev-medium { color: #9a6700; }
+.sev-high { color: #cf222e; } .sev-critical { color: #fff; background: #cf222e; }
+.loc { font-family: ui-monospace, Menlo, Consolas, monospace; word-break: break-all; }
+pre { background: #f6f8fa; padding: 0.5rem; overflow-x: auto; white-space: pre-wrap; }
+.warn { color: #9a6700; font-weight: 600; }
+footer { color: #59636e; font-size: 0.8rem; }
+";
+
+/// Escape text for HTML element content and double-quoted attributes.
+/// File paths and snippets come from the scanned (untrusted) package.
+fn html_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn severity_class(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Low => "sev-low",
+        Severity::Medium => "sev-medium",
+        Severity::High => "sev-high",
+        Severity::Critical => "sev-critical",
+    }
+}
+
+fn verdict_class(verdict: Verdict) -> &'static str {
+    match verdict {
+        Verdict::LowRisk => "verdict-low",
+        Verdict::MediumRisk => "verdict-medium",
+        Verdict::HighRisk => "verdict-high",
+        Verdict::CriticalRisk => "verdict-critical",
+    }
+}
+
+/// Render a scan result as a single self-contained HTML document: summary
+/// header, then a severity-colored findings table per phase with snippets
+/// in collapsible `<details>` blocks. No scripts or external assets.
+pub fn render_scan_html(result: &ScanResult, target: &str) -> String {
+    let mut html = String::new();
+    let title = format!("Sigil scan report: {}", target);
+
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", html_escape(&title)));
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", HTML_STYLE));
+    html.push_str("<h1>Sigil scan report</h1>\n");
+    html.push_str(&format!(
+        "<p class=\"target\">{}</p>\n",
+        html_escape(target)
+    ));
+
+    let count = |sev: Severity| result.findings.iter().filter(|f| f.severity == sev).count();
+    let cards = [
+        (
+            "Verdict",
+            result.verdict.to_string(),
+            verdict_class(result.verdict),
+        ),
+        ("Risk score", result.score.to_string(), ""),
+        ("Files scanned", result.files_scanned.to_string(), ""),
+        ("Findings", result.findings.len().to_string(), ""),
+        ("Critical", count(Severity::Critical).to_string(), ""),
+        ("High", count(Severity::High).to_string(), ""),
+        ("Medium", count(Severity::Medium).to_string(), ""),
+        ("Low", count(Severity::Low).to_string(), ""),
+    ];
+    html.push_str("<div class=\"summary\">\n");
+    for (label, value, class) in cards {
+        html.push_str(&format!(
+            "<div class=\"card {}\"><div class=\"label\">{}</div><div class=\"value\">{}</div></div>\n",
+            class, label, value
+        ));
+    }
+    html.push_str("</div>\n");
+
+    if result.timed_out {
+        html.push_str(&format!(
+            "<p class=\"warn\">Scan timed out: {} file(s) not scanned (partial result).</p>\n",
+            result.unscanned_files
+        ));
+    }
+    if result.truncated {
+        html.push_str(&format!(
+            "<p class=\"warn\">Findings capped at {} (--max-findings); at least {} matched, so the risk score is a lower bound.</p>\n",
+            result.findings.len(),
+            result.total_matched
+        ));
+    }
+    if let Some(by) = &result.suppressed_by {
+        html.push_str(&format!(
+            "<p>{} finding(s) suppressed by ledger approval ({}).</p>\n",
+            result.suppressed_findings.len(),
+            html_escape(by)
+        ));
+    }
+
+    if result.findings.is_empty() {
+        html.push_str("<p>No findings.</p>\n");
+    }
+
+    let phase_order = [
+        Phase::InstallHooks,
+        Phase::CodePatterns,
+        Phase::NetworkExfil,
+        Phase::Credentials,
+        Phase::Obfuscation,
+        Phase::Provenance,
+        Phase::PromptInjection,
+        Phase::SkillSecurity,
+        Phase::InferenceSecurity,
+    ];
+    for phase in phase_order {
+        let mut findings: Vec<&Finding> = result
+            .findings
+            .iter()
+            .filter(|f| f.phase == phase)
+            .collect();
+        if findings.is_empty() {
+            continue;
+        }
+        findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+
+        html.push_str(&format!(
+            "<h2>{} ({})</h2>\n<table>\n<tr><th>Severity</th><th>Rule</th><th>Location</th><th>Snippet</th></tr>\n",
+            html_escape(&phase.to_string()),
+            findings.len()
+        ));
+        for f in findings {
+            let location = match f.line {
+                Some(line) => format!("{}:{}", f.file, line),
+                None => f.file.clone(),
+            };
+            html.push_str(&format!(
+                "<tr><td class=\"sev {}\">{}</td><td>{}</td><td class=\"loc\">{}</td><td><details><summary>show</summary><pre>{}</pre></details></td></tr>\n",
+                severity_class(f.severity),
+                f.severity,
+                html_escape(&f.rule),
+                html_escape(&location),
+                html_escape(&f.snippet)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str(&format!(
+        "<footer>Generated by Sigil {} in {}ms. Static analysis results are not a guarantee of safety.</footer>\n</body>\n</html>\n",
+        env!("CARGO_PKG_VERSION"),
+        result.duration_ms
+    ));
+    html
+}
+
+/// Print the self-contained HTML report.
+pub fn print_scan_html(result: &ScanResult, target: &str) {
+    out!("{}", render_scan_html(result, target));
+}
+
+// ---------------------------------------------------------------------------
+// JUnit XML report (`--format junit`)
+// ---------------------------------------------------------------------------
+
+/// [`html_escape`] minus the control characters XML 1.0 cannot carry at
+/// all, which a scanned file's snippets may contain.
+fn xml_escape(text: &str) -> String {
+    let printable: String = text
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    html_escape(&printable)
+}
+
+fn junit_location(f: &Finding) -> String {
+    match f.line {
+        Some(line) => format!("{}:{}", f.file, line),
+        None => f.file.clone(),
+    }
+}
+
+/// One `<testcase>`, failing when `findings` is non-empty.
+fn junit_case(
+    xml: &mut String,
+    classname: &str,
+    name: &str,
+    findings: &[&Finding],
+    detail: impl Fn(&Finding) -> String,
+) {
+    xml.push_str(&format!(
+        "    <testcase classname=\"{}\" name=\"{}\"",
+        xml_escape(classname),
+        xml_escape(name)
+    ));
+    let Some(worst) = findings.iter().map(|f| f.severity).max() else {
+        xml.push_str("/>\n");
+        return;
+    };
+    let mut rules: Vec<&str> = findings.iter().map(|f| f.rule.as_str()).collect();
+    rules.sort_unstable();
+    rules.dedup();
+    let body: Vec<String> = findings.iter().map(|f| xml_escape(&detail(f))).collect();
+    xml.push_str(&format!(
+        ">\n      <failure type=\"{}\" message=\"{} finding(s): {}\">{}</failure>\n    </testcase>\n",
+        worst,
+        findings.len(),
+        xml_escape(&rules.join(", ")),
+        body.join("\n")
+    ));
+}
+
+/// Render a scan as JUnit XML.  With `granularity` "file" every scanned
+/// file is a test case (classname is its path) that fails if it has
+/// findings, so the test count is the number of files scanned.  With
+/// "rule" each rule that fired is a failing test case under its phase.
+pub fn render_scan_junit(result: &ScanResult, target: &str, granularity: &str) -> String {
+    let mut cases = String::new();
+    let mut tests = 0;
+    let mut failures = 0;
+    if granularity == "rule" {
+        let mut by_rule: Vec<(&str, Vec<&Finding>)> = Vec::new();
+        for f in &result.findings {
+            match by_rule.iter_mut().find(|(rule, _)| *rule == f.rule) {
+                Some((_, hits)) => hits.push(f),
+                None => by_rule.push((&f.rule, vec![f])),
+            }
+        }
+        by_rule.sort_by_key(|(rule, hits)| (hits[0].phase, *rule));
+        for (rule, hits) in &by_rule {
+            let phase = hits[0].phase.to_string();
+            junit_case(&mut cases, &phase, rule, hits, |f| {
+                format!("{} {}: {}", f.severity, junit_location(f), f.snippet)
+            });
+        }
+        tests = by_rule.len();
+        failures = tests;
+    } else {
+        // Findings on paths that were not content-scanned (provenance, git
+        // hooks) still get a test case of their own.
+        let mut files: Vec<&str> = result.scanned_files.iter().map(String::as_str).collect();
+        files.extend(result.findings.iter().map(|f| f.file.as_str()));
+        files.sort_unstable();
+        files.dedup();
+        for file in files {
+            let hits: Vec<&Finding> = result.findings.iter().filter(|f| f.file == file).collect();
+            junit_case(&mut cases, file, "sigil scan", &hits, |f| {
+                format!(
+                    "[{}] {} {} {}: {}",
+                    f.phase,
+                    f.severity,
+                    f.rule,
+                    junit_location(f),
+                    f.snippet
+                )
+            });
+            tests += 1;
+            failures += usize::from(!hits.is_empty());
+        }
+    }
+
+    let time = result.duration_ms as f64 / 1000.0;
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"sigil\" tests=\"{tests}\" failures=\"{failures}\" time=\"{time:.3}\">\n"
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"sigil scan {}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" skipped=\"0\" time=\"{time:.3}\">\n",
+        xml_escape(target)
+    ));
+    xml.push_str(&format!(
+        "    <properties>\n      <property name=\"files_scanned\" value=\"{}\"/>\n      <property name=\"score\" value=\"{}\"/>\n      <property name=\"verdict\" value=\"{}\"/>\n",
+        result.files_scanned, result.score, result.verdict
+    ));
+    if result.truncated {
+        xml.push_str(&format!(
+            "      <property name=\"truncated\" value=\"true\"/>\n      <property name=\"total_matched\" value=\"{}\"/>\n",
+            result.total_matched
+        ));
+    }
+    xml.push_str("    </properties>\n");
+    xml.push_str(&cases);
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Print the JUnit XML report.
+pub fn print_scan_junit(result: &ScanResult, target: &str, granularity: &str) {
+    out!("{}", render_scan_junit(result, target, granularity));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finding_tree_aggregates_by_directory_hottest_first() {
+        let finding = |file: &str, severity| Finding {
+            phase: Phase::CodePatterns,
+            rule: "CODE-001".to_string(),
+            severity,
+            file: file.to_string(),
+            line: None,
+            snippet: String::new(),
+            weight: 5,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        };
+        let findings = vec![
+            finding("README.md", Severity::Medium),
+            finding("src/lib/net/send.py", Severity::Critical),
+            finding("src/lib/net/send.py", Severity::Low),
+            finding("src/lib/net/util.py", Severity::High),
+            finding("./setup.py", Severity::High),
+            finding(".", Severity::Low),
+        ];
+        let rows = finding_tree_rows(&findings, "pkg/");
+        let rows: Vec<(&str, usize, Severity)> = rows
+            .iter()
+            .map(|(line, count, max)| (line.as_str(), *count, *max))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("pkg/", 6, Severity::Critical),
+                ("├── src/lib/net/", 3, Severity::Critical),
+                ("│   ├── send.py", 2, Severity::Critical),
+                ("│   └── util.py", 1, Severity::High),
+                ("├── setup.py", 1, Severity::High),
+                ("└── README.md", 1, Severity::Medium),
+            ]
+        );
+        assert!(finding_tree_rows(&[], "pkg").is_empty());
+    }
+
+    #[test]
+    fn finding_groups_follow_the_sort_mode() {
+        let finding = |phase, severity, file: &str, line| Finding {
+            phase,
+            rule: "R".to_string(),
+            severity,
+            file: file.to_string(),
+            line: Some(line),
+            snippet: String::new(),
+            weight: 1,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        };
+        // Canonical order: by phase, then file and line.
+        let findings = vec![
+            finding(Phase::InstallHooks, Severity::Medium, "setup.py", 4),
+            finding(Phase::CodePatterns, Severity::Low, "b.py", 9),
+            finding(Phase::CodePatterns, Severity::Critical, "setup.py", 2),
+            finding(Phase::Credentials, Severity::Critical, "b.py", 1),
+        ];
+        let layout = |sort: &str, order: &str| -> Vec<(String, Vec<(String, usize)>)> {
+            finding_groups(&findings, sort, order)
+                .into_iter()
+                .map(|(title, group)| {
+                    let rows = group
+                        .iter()
+                        .map(|f| (f.file.clone(), f.line.unwrap()))
+                        .collect();
+                    (title, rows)
+                })
+                .collect()
+        };
+        let titles = |sort: &str, order: &str| -> Vec<String> {
+            layout(sort, order).into_iter().map(|(t, _)| t).collect()
+        };
+
+        assert_eq!(
+            titles("phase", "critical-first"),
+            vec![
+                Phase::InstallHooks.to_string(),
+                Phase::CodePatterns.to_string(),
+                Phase::Credentials.to_string(),
+            ]
+        );
+
+        let by_severity = layout("severity", "critical-first");
+        assert_eq!(
+            by_severity[0],
+            (
+                "CRITICAL".to_string(),
+                vec![("setup.py".to_string(), 2), ("b.py".to_string(), 1)]
+            )
+        );
+        assert_eq!(
+            titles("severity", "critical-first"),
+            ["CRITICAL", "MEDIUM", "LOW"]
+        );
+        assert_eq!(
+            titles("severity", "low-first"),
+            ["LOW", "MEDIUM", "CRITICAL"]
+        );
+
+        assert_eq!(
+            layout("file", "critical-first"),
+            vec![
+                (
+                    "b.py".to_string(),
+                    vec![("b.py".to_string(), 1), ("b.py".to_string(), 9)]
+                ),
+                (
+                    "setup.py".to_string(),
+                    vec![("setup.py".to_string(), 2), ("setup.py".to_string(), 4)]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn junit_file_report_has_a_case_per_scanned_file() {
+        let finding = Finding {
+            phase: Phase::NetworkExfil,
+            rule: "NET-001".to_string(),
+            severity: Severity::High,
+            file: "src/<a&b>.py".to_string(),
+            line: Some(3),
+            snippet: "send(\"x\")\u{1b}".to_string(),
+            weight: 3,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        };
+        let result = ScanResult {
+            findings: vec![finding],
+            score: 18,
+            verdict: Verdict::MediumRisk,
+            files_scanned: 3,
+            duration_ms: 1500,
+            scanned_files: vec![
+                "README.md".to_string(),
+                "src/<a&b>.py".to_string(),
+                "src/ok.py".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        let xml = render_scan_junit(&result, "pkg", "file");
+        assert!(xml.contains(r#"<testsuite name="sigil scan pkg" tests="3" failures="1""#));
+        assert!(xml.contains(r#"<testcase classname="README.md" name="sigil scan"/>"#));
+        assert!(xml.contains(r#"<testcase classname="src/&lt;a&amp;b&gt;.py" name="sigil scan">"#));
+        assert!(xml.contains("NET-001 src/&lt;a&amp;b&gt;.py:3: send(&quot;x&quot;)</failure>"));
+
+        let xml = render_scan_junit(&result, "pkg", "rule");
+        assert!(xml.contains(r#"tests="1" failures="1""#));
+        assert!(xml.contains(r#"<testcase classname="Network/Exfil" name="NET-001">"#));
+    }
+
+    #[test]
+    fn metrics_line_counts_by_severity() {
+        let finding = |severity| Finding {
+            phase: Phase::CodePatterns,
+            rule: "CODE-001".to_string(),
+            severity,
+            file: "a.py".to_string(),
+            line: None,
+            snippet: String::new(),
+            weight: 5,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        };
+        let result = ScanResult {
+            files_scanned: 210,
+            findings: vec![
+                finding(Severity::Critical),
+                finding(Severity::High),
+                finding(Severity::High),
+                finding(Severity::Low),
+            ],
+            score: 42,
+            verdict: Verdict::HighRisk,
+            duration_ms: 88,
+            ..Default::default()
+        };
+        assert_eq!(
+            metrics_line(&result),
+            "score=42 verdict=HighRisk critical=1 high=2 medium=0 low=1 files=210 duration_ms=88 timed_out=false"
+        );
+    }
+
+    #[test]
+    fn scan_report_keeps_top_files_in_the_one_object() {
+        let finding = |file: &str, severity| Finding {
+            phase: Phase::CodePatterns,
+            rule: "CODE-001".to_string(),
+            severity,
+            file: file.to_string(),
+            line: Some(1),
+            snippet: String::new(),
+            weight: 5,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        };
+        let result = ScanResult {
+            findings: vec![
+                finding("a.py", Severity::Low),
+                finding("b.py", Severity::Critical),
+            ],
+            score: 30,
+            verdict: Verdict::HighRisk,
+            files_scanned: 2,
+            ..Default::default()
+        };
+
+        let report = scan_report_json(&result, 1);
+        assert_eq!(report["verdict"], "HIGH RISK");
+        assert_eq!(report["findings"].as_array().unwrap().len(), 2);
+        let top = report["top_files"].as_array().unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0]["file"], "b.py");
+
+        // `--top 0` leaves the list out rather than printing an empty one.
+        assert!(scan_report_json(&result, 0).get("top_files").is_none());
+    }
+
+    #[test]
+    fn phase_scores_are_listed_largest_first() {
+        let mut result = ScanResult {
+            files_scanned: 1,
+            duration_ms: 1,
+            ..Default::default()
+        };
+        assert_eq!(phase_score_line(&result), None);
+        result.phase_scores = [
+            (Phase::Obfuscation, 20),
+            (Phase::Credentials, 45),
+            (Phase::Provenance, 20),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            phase_score_line(&result).unwrap(),
+            "Credentials: 45, Obfuscation: 20, Provenance: 20"
+        );
+    }
+
+    #[test]
+    fn audit_report_prefixes_sources_and_keeps_one_sarif_run() {
+        let entry = |id: &str, source: &str| QuarantineEntry {
+            id: id.to_string(),
+            source: source.to_string(),
+            source_type: "pip".to_string(),
+            path: PathBuf::from("/tmp/q").join(id),
+            status: crate::quarantine::QuarantineStatus::Pending,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            reason: None,
+            scan_score: None,
+            path_missing: false,
+        };
+        let result = |file: &str, snippet: &str| ScanResult {
+            files_scanned: 1,
+            findings: vec![Finding {
+                phase: Phase::CodePatterns,
+                rule: "CODE-001".to_string(),
+                severity: Severity::High,
+                file: file.to_string(),
+                line: Some(3),
+                snippet: snippet.to_string(),
+                weight: 5,
+                confidence: 100,
+                kev: false,
+                epss: 0.0,
+                sha256: None,
+                region: None,
+            }],
+            score: 5,
+            verdict: Verdict::MediumRisk,
+            duration_ms: 1,
+            ..Default::default()
+        };
+        let audit = vec![
+            AuditEntry::new(entry("a1", "requests"), result("setup.py", "eval(x)")),
+            AuditEntry::new(
+                entry("b2", "https://example.com/tool.zip"),
+                result("src\\run.js", "say \"hi\", then eval"),
+            ),
+        ];
+
+        let json = audit_json(&audit);
+        assert_eq!(json["entries"].as_array().unwrap().len(), 2);
+        assert_eq!(json["findings"][0]["file"], "requests/setup.py");
+        assert_eq!(
+            json["findings"][1]["file"],
+            "https://example.com/tool.zip/src/run.js"
+        );
+        assert_eq!(json["findings"][1]["quarantine_id"], "b2");
+
+        let sarif = audit_sarif(&audit);
+        assert_eq!(sarif["runs"].as_array().unwrap().len(), 1);
+        let run = &sarif["runs"][0];
+        assert_eq!(run["artifacts"][0]["location"]["uri"], "requests");
+        assert_eq!(run["artifacts"][1]["properties"]["id"], "b2");
+        assert_eq!(run["results"][1]["properties"]["quarantineId"], "b2");
+        assert_eq!(run["results"][1]["properties"]["confidence"], 100);
+
+        let csv = audit_csv(&audit);
+        assert_eq!(csv.len(), 3);
+        assert_eq!(
+            csv[2],
+            "b2,https://example.com/tool.zip,pending,https://example.com/tool.zip/src/run.js,3,CODE-001,High,CodePatterns,5,\"say \"\"hi\"\", then eval\""
+        );
+    }
+
+    #[test]
+    fn diff_sarif_reports_only_new_findings() {
+        let finding = |rule: &str, file: &str| Finding {
+            phase: Phase::CodePatterns,
+            rule: rule.to_string(),
+            severity: Severity::High,
+            file: file.to_string(),
+            line: Some(2),
+            snippet: "eval(x)".to_string(),
+            weight: 5,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        };
+        let diff = ScanDiff {
+            new_findings: vec![finding("CODE-001", "new.py")],
+            resolved_findings: vec![finding("CODE-002", "gone.py")],
+            unchanged_findings: vec![finding("CODE-003", "old.py")],
+            modified_findings: Vec::new(),
+            score_delta: 0,
+            previous_verdict: Verdict::MediumRisk,
+            current_verdict: Verdict::MediumRisk,
+            summary: String::new(),
+        };
+
+        let sarif = sarif_document(
+            &diff.new_findings,
+            vec![sarif_root("pkg")],
+            true,
+            diff_sarif_properties(&diff, "scan-v1.json"),
+        );
+        let run = &sarif["runs"][0];
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], "CODE-001");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 1);
+        let props = &run["invocations"][0]["properties"];
+        assert_eq!(props["baseline"], "scan-v1.json");
+        assert_eq!(props["resolvedFindings"], 1);
+        assert_eq!(props["unchangedFindings"], 1);
+    }
+
+    #[test]
+    fn sarif_region_uses_the_match_span_when_known() {
+        let mut finding = Finding {
+            phase: Phase::CodePatterns,
+            rule: "CODE-001".to_string(),
+            severity: Severity::High,
+            file: "a.js".to_string(),
+            line: Some(2),
+            snippet: "eval(s)".to_string(),
+            weight: 5,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        };
+        assert_eq!(
+            sarif_region(&finding),
+            serde_json::json!({"startLine": 2, "startColumn": 1})
+        );
+
+        finding.region = Some(crate::scanner::Region::of_match(
+            "// ü\nconst s = \"😀\"; eval(s)\n",
+            24,
+            29,
+        ));
+        let region = sarif_region(&finding);
+        assert_eq!(region["startColumn"], 17);
+        assert_eq!(region["endColumn"], 22);
+        assert_eq!(region["charOffset"], 21);
+        assert_eq!(region["charLength"], 5);
+    }
+
+    #[test]
+    fn color_follows_flag_env_and_tty() {
+        assert!(color_wanted(false, None, None, true));
+        assert!(!color_wanted(false, None, None, false));
+        assert!(!color_wanted(true, None, None, true));
+        assert!(!color_wanted(false, Some("1"), None, true));
+        assert!(color_wanted(false, Some(""), None, true));
+        assert!(color_wanted(false, None, Some("1"), false));
+        assert!(!color_wanted(false, None, Some("0"), false));
+        assert!(!color_wanted(false, Some("1"), Some("1"), true));
+    }
+
+    #[test]
+    fn html_report_escapes_untrusted_paths_and_snippets() {
+        let findings = vec![Finding {
+            phase: Phase::CodePatterns,
+            rule: "CODE-001".to_string(),
+            severity: Severity::High,
+            file: "<img src=x onerror=alert(1)>.js".to_string(),
+            line: Some(4),
+            snippet: "eval(\"</pre><script>steal()</script>\")".to_string(),
+            weight: 5,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        }];
+        let result = ScanResult {
+            score: crate::scanner::scoring::calculate_score(&findings),
+            verdict: Verdict::MediumRisk,
+            findings,
+            files_scanned: 1,
+            duration_ms: 3,
+            ..Default::default()
+        };
+
+        let html = render_scan_html(&result, "pkg/<b>");
+
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("<img"));
+        assert!(html.contains("&lt;img src=x onerror=alert(1)&gt;.js:4"));
+        assert!(html.contains("&lt;/pre&gt;&lt;script&gt;steal()&lt;/script&gt;"));
+        assert!(html.contains("pkg/&lt;b&gt;"));
+        assert!(html.contains("Code Patterns (1)"));
+    }
+
+    #[test]
+    fn result_file_is_created_on_first_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.json");
+        let unused = ResultFile {
+            path: path.clone(),
+            writer: None,
+            error: None,
+        };
+        assert_eq!(unused.finish().unwrap(), None);
+        assert!(!path.exists());
+
+        let mut file = ResultFile {
+            path: path.clone(),
+            writer: None,
+            error: None,
+        };
+        file.write(format_args!("{}\n", "{\"ok\": true}"));
+        assert_eq!(file.finish().unwrap(), Some(path.clone()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\"ok\": true}\n");
+    }
+
+    #[test]
+    fn unwritable_result_file_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut file = ResultFile {
+            path: dir.path().join("missing").join("out.json"),
+            writer: None,
+            error: None,
+        };
+        file.write(format_args!("x"));
+        assert!(file.finish().unwrap_err().contains("failed to write"));
+    }
+
+    #[test]
+    fn list_json_envelope_is_versioned() {
+        use crate::quarantine::QuarantineStatus;
+        let now = chrono::Utc::now();
+        let entry = QuarantineEntry {
+            id: "abc12345".to_string(),
+            source: "requests".to_string(),
+            source_type: "pip".to_string(),
+            path: PathBuf::from("/q/abc12345"),
+            status: QuarantineStatus::Approved,
+            created_at: now,
+            updated_at: now,
+            reason: None,
+            scan_score: Some(4),
+            path_missing: false,
+        };
+
+        let json = quarantine_list_json(&[entry], 7, 2, Some(1));
+
+        assert_eq!(json["schema_version"], LIST_SCHEMA_VERSION);
+        assert_eq!(json["total"], 7);
+        assert_eq!(json["offset"], 2);
+        assert_eq!(json["limit"], 1);
+        let listed = &json["entries"][0];
+        assert_eq!(listed["id"], "abc12345");
+        assert_eq!(listed["status"], "approved");
+        assert_eq!(listed["path"], "/q/abc12345");
+        assert_eq!(listed["created_at"], now.to_rfc3339());
+        assert_eq!(listed["scan_score"], 4);
+        assert!(listed["reason"].is_null());
+    }
+}
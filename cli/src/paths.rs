@@ -0,0 +1,103 @@
+//! Where Sigil keeps its state: the `.sigil` directory and everything in it
+//! (quarantine, caches, signatures, token, config, ledgers, packs).
+//!
+//! The directory defaults to `~/.sigil`.  `SIGIL_HOME` moves the whole tree,
+//! which suits CI runners with throwaway home directories; failing that, a
+//! `home_dir` key in `~/.sigil/config.json` does the same for a user who
+//! wants the state on another volume.  That one key is read from the default
+//! location, since the config file itself moves with the tree.
+//!
+//! Every module builds its paths from [`sigil_home`], so the override
+//! applies everywhere at once.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+/// Environment variable that relocates the `.sigil` tree.
+pub const HOME_ENV: &str = "SIGIL_HOME";
+
+/// Config key that relocates the `.sigil` tree.
+pub const HOME_KEY: &str = "home_dir";
+
+/// `~/.sigil`, or `./.sigil` when the home directory is unknown.
+fn default_home() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".sigil")
+}
+
+/// The `home_dir` value of the config file in `default`, if one is set.
+fn configured_home(default: &Path) -> Option<PathBuf> {
+    let raw = std::fs::read_to_string(default.join("config.json")).ok()?;
+    let config: Value = serde_json::from_str(&raw).ok()?;
+    config
+        .get(HOME_KEY)?
+        .as_str()
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Pick the state directory: the environment override, then the
+/// configured one, then the default.
+fn resolve_home(env: Option<String>, default: PathBuf) -> PathBuf {
+    env.filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+        .or_else(|| configured_home(&default))
+        .unwrap_or(default)
+}
+
+/// The directory holding all Sigil state.
+pub fn sigil_home() -> PathBuf {
+    resolve_home(std::env::var(HOME_ENV).ok(), default_home())
+}
+
+/// `<sigil home>/<name>`.
+pub fn in_home(name: &str) -> PathBuf {
+    sigil_home().join(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_beats_config_beats_default() {
+        let default = tempfile::tempdir().unwrap();
+        let env_home = tempfile::tempdir().unwrap();
+        let env = || Some(env_home.path().display().to_string());
+
+        assert_eq!(
+            resolve_home(None, default.path().to_path_buf()),
+            default.path()
+        );
+        assert_eq!(
+            resolve_home(Some("  ".into()), default.path().to_path_buf()),
+            default.path()
+        );
+
+        std::fs::write(
+            default.path().join("config.json"),
+            r#"{"home_dir": "/mnt/sigil-state", "severity": "high"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            resolve_home(None, default.path().to_path_buf()),
+            Path::new("/mnt/sigil-state")
+        );
+        assert_eq!(
+            resolve_home(env(), default.path().to_path_buf()),
+            env_home.path()
+        );
+    }
+
+    #[test]
+    fn unreadable_config_falls_back_to_default() {
+        let default = tempfile::tempdir().unwrap();
+        std::fs::write(default.path().join("config.json"), "not json").unwrap();
+        assert_eq!(
+            resolve_home(None, default.path().to_path_buf()),
+            default.path()
+        );
+    }
+}
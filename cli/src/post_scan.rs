@@ -0,0 +1,242 @@
+//! Post-scan hooks: a user command run after `sigil scan` reports, when the
+//! verdict reaches a configured level (a Slack notification, a ticket).
+//!
+//! The command is configured as `hooks.post_scan` (run when the verdict is
+//! at least `hooks.post_scan_on`, default `high`) or given per run with
+//! `--on-critical <CMD>`.  It is split into arguments once, when read, and
+//! run directly rather than through a shell, so nothing in a scanned tree
+//! can inject into it.  The result reaches the command three ways:
+//!
+//! - `{target}`, `{verdict}`, `{score}` and `{findings}` in an argument are
+//!   replaced with the value, as that one argument;
+//! - `SIGIL_SCAN_*` environment variables (see [`env`]);
+//! - the JSON scan summary on stdin.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::scanner::{ScanResult, Severity, Verdict};
+
+/// Config key holding the command.
+pub const COMMAND_KEY: &str = "hooks.post_scan";
+/// Config key holding the lowest verdict level that runs it.
+pub const LEVEL_KEY: &str = "hooks.post_scan_on";
+
+/// A command to run after a scan whose verdict is at least `min`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostScanHook {
+    pub argv: Vec<String>,
+    pub min: Severity,
+}
+
+/// Split `command` into arguments: whitespace separates them, and single or
+/// double quotes group words.  There is no other shell syntax.
+pub fn split_command(command: &str) -> Result<Vec<String>, String> {
+    let mut argv = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => argv.extend(current.take()),
+            (None, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err("unterminated quote".to_string());
+    }
+    argv.extend(current);
+    if argv.is_empty() {
+        return Err("expected a command".to_string());
+    }
+    Ok(argv)
+}
+
+/// The verdict as the severity level thresholds are written in.
+pub(crate) fn verdict_level(verdict: &Verdict) -> Severity {
+    match verdict {
+        Verdict::LowRisk => Severity::Low,
+        Verdict::MediumRisk => Severity::Medium,
+        Verdict::HighRisk => Severity::High,
+        Verdict::CriticalRisk => Severity::Critical,
+    }
+}
+
+/// The configured `hooks.post_scan`, if any.  A malformed value is reported
+/// and ignored rather than failing the scan.
+pub fn configured() -> Option<PostScanHook> {
+    let config = crate::config::resolve().merged();
+    let argv: Vec<String> = match config.get(COMMAND_KEY)? {
+        serde_json::Value::Array(args) => args
+            .iter()
+            .filter_map(|arg| arg.as_str().map(str::to_string))
+            .collect(),
+        serde_json::Value::String(command) => match split_command(command) {
+            Ok(argv) => argv,
+            Err(e) => {
+                crate::logging::log_warn!("ignoring {}: {}", COMMAND_KEY, e);
+                return None;
+            }
+        },
+        _ => return None,
+    };
+    let min = match config.get(LEVEL_KEY).and_then(|v| v.as_str()) {
+        Some(level) => match crate::scanner::parse_severity(level) {
+            Ok(min) => min,
+            Err(e) => {
+                crate::logging::log_warn!("ignoring {}: {}: {}", COMMAND_KEY, LEVEL_KEY, e);
+                return None;
+            }
+        },
+        None => Severity::High,
+    };
+    (!argv.is_empty()).then_some(PostScanHook { argv, min })
+}
+
+/// `SIGIL_SCAN_*` variables describing `result`, as passed to a hook.
+pub fn env(result: &ScanResult, target: &Path) -> Vec<(&'static str, String)> {
+    let count = |severity: Severity| {
+        result
+            .findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+            .to_string()
+    };
+    vec![
+        ("SIGIL_SCAN_TARGET", target.display().to_string()),
+        ("SIGIL_SCAN_VERDICT", format!("{:?}", result.verdict)),
+        ("SIGIL_SCAN_SCORE", result.score.to_string()),
+        ("SIGIL_SCAN_FINDINGS", result.findings.len().to_string()),
+        ("SIGIL_SCAN_CRITICAL", count(Severity::Critical)),
+        ("SIGIL_SCAN_HIGH", count(Severity::High)),
+        ("SIGIL_SCAN_MEDIUM", count(Severity::Medium)),
+        ("SIGIL_SCAN_LOW", count(Severity::Low)),
+        ("SIGIL_SCAN_FILES", result.files_scanned.to_string()),
+    ]
+}
+
+/// `argv` with the `{...}` placeholders filled in from `result`.
+fn expand(argv: &[String], result: &ScanResult, target: &Path) -> Vec<String> {
+    argv.iter()
+        .map(|arg| {
+            arg.replace("{target}", &target.display().to_string())
+                .replace("{verdict}", &format!("{:?}", result.verdict))
+                .replace("{score}", &result.score.to_string())
+                .replace("{findings}", &result.findings.len().to_string())
+        })
+        .collect()
+}
+
+impl PostScanHook {
+    /// Whether `result` reaches this hook's level.
+    pub fn applies(&self, result: &ScanResult) -> bool {
+        verdict_level(&result.verdict) >= self.min
+    }
+
+    /// Run the hook for `result` and wait for it.  The hook's own output is
+    /// returned rather than printed, so it never mixes with the report.
+    pub fn run(&self, result: &ScanResult, target: &Path) -> Result<String, String> {
+        let argv = expand(&self.argv, result, target);
+        let mut child = Command::new(&argv[0])
+            .args(&argv[1..])
+            .envs(env(result, target))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to run {}: {}", argv[0], e))?;
+        let summary = crate::output::scan_summary_json(result).to_string();
+        if let Some(mut stdin) = child.stdin.take() {
+            // A hook that ignores stdin may exit before reading it.
+            let _ = stdin.write_all(summary.as_bytes());
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("failed to run {}: {}", argv[0], e))?;
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() {
+            Ok(stdout)
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(format!(
+                "{} exited with {}: {}",
+                argv[0],
+                output.status,
+                stderr.trim()
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(verdict: Verdict) -> ScanResult {
+        ScanResult {
+            score: 42,
+            verdict,
+            files_scanned: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn commands_split_on_whitespace_and_quotes_only() {
+        assert_eq!(
+            split_command(r#"notify --title "scan failed" 'a;b' $(x)"#).unwrap(),
+            vec!["notify", "--title", "scan failed", "a;b", "$(x)"]
+        );
+        assert_eq!(split_command("tool ''").unwrap(), vec!["tool", ""]);
+        assert!(split_command("  ").is_err());
+        assert!(split_command("tool 'open").is_err());
+    }
+
+    #[test]
+    fn hooks_apply_at_or_above_their_level() {
+        let hook = PostScanHook {
+            argv: vec!["true".to_string()],
+            min: Severity::High,
+        };
+        assert!(!hook.applies(&result(Verdict::MediumRisk)));
+        assert!(hook.applies(&result(Verdict::HighRisk)));
+        assert!(hook.applies(&result(Verdict::CriticalRisk)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hook_gets_placeholders_env_and_summary_without_a_shell() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("pkg; touch injected");
+        let hook = PostScanHook {
+            argv: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                r#"printf '%s|%s|%s|' "$1" "$SIGIL_SCAN_VERDICT" "$SIGIL_SCAN_SCORE"; cat"#
+                    .to_string(),
+                "hook".to_string(),
+                "{target}".to_string(),
+            ],
+            min: Severity::Low,
+        };
+        let out = hook.run(&result(Verdict::CriticalRisk), &target).unwrap();
+        let (args, summary) = out.rsplit_once('|').unwrap();
+        assert_eq!(args, format!("{}|CriticalRisk|42", target.display()));
+        let summary: serde_json::Value = serde_json::from_str(summary).unwrap();
+        assert_eq!(summary["score"], 42);
+        assert!(!dir.path().join("injected").exists());
+
+        let failing = PostScanHook {
+            argv: vec!["false".to_string()],
+            min: Severity::Low,
+        };
+        assert!(failing.run(&result(Verdict::LowRisk), &target).is_err());
+    }
+}
@@ -76,7 +76,7 @@ pub struct ScanOptions<'a> {
 // ── Ledger helpers ────────────────────────────────────────────────────────────
 
 fn ledger_dir_default() -> Option<PathBuf> {
-    dirs::home_dir().map(|h| h.join(".sigil").join("provenance-ledger"))
+    Some(crate::paths::in_home("provenance-ledger"))
 }
 
 fn ledger_key(ecosystem: &str, name: &str, version: &str) -> String {
@@ -294,8 +294,11 @@ fn detect_drift(
                 ecosystem, name, version
             ),
             weight: 3,
+            confidence: 100,
             kev: false,
             epss: 0.0,
+            sha256: None,
+            region: None,
         });
     }
 
@@ -315,8 +318,11 @@ fn detect_drift(
                     ecosystem, name, version, prev_id, curr_id
                 ),
                 weight: 10,
+                confidence: 100,
                 kev: false,
                 epss: 0.0,
+                sha256: None,
+                region: None,
             });
         }
     }
@@ -340,8 +346,11 @@ fn detect_drift(
                     ecosystem, name, version, prev_repo, curr_repo
                 ),
                 weight: 3,
+                confidence: 100,
                 kev: false,
                 epss: 0.0,
+                sha256: None,
+                region: None,
             });
         }
     }
@@ -31,10 +31,7 @@ impl Provider {
 
 /// Returns the path to `~/.sigil/providers/`, creating it if necessary.
 pub fn providers_dir() -> PathBuf {
-    let dir = dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".sigil")
-        .join("providers");
+    let dir = crate::paths::in_home("providers");
     let _ = std::fs::create_dir_all(&dir);
     dir
 }
@@ -1,8 +1,10 @@
+use crate::error::SigilError;
 use chrono::{DateTime, Utc};
+use fs2::FileExt;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::ErrorKind;
-use std::path::PathBuf;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 /// Status of a quarantined item.
@@ -44,22 +46,24 @@ pub struct QuarantineEntry {
     pub reason: Option<String>,
     /// Scan score (populated after scanning)
     pub scan_score: Option<u32>,
+    /// Imported from another machine without its files; `path` is where
+    /// they would live here.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub path_missing: bool,
 }
 
 // ---------------------------------------------------------------------------
 // Path helpers
 // ---------------------------------------------------------------------------
 
-/// Return the base quarantine directory: ~/.sigil/quarantine/
+/// Return the base quarantine directory: `SIGIL_QUARANTINE_DIR`, else
+/// `quarantine/` under the Sigil home (~/.sigil/quarantine/).
 pub fn quarantine_path() -> PathBuf {
     if let Ok(path) = std::env::var("SIGIL_QUARANTINE_DIR") {
         return PathBuf::from(path);
     }
 
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".sigil")
-        .join("quarantine")
+    crate::paths::in_home("quarantine")
 }
 
 /// Return the path to the quarantine index file: ~/.sigil/quarantine/index.json
@@ -69,45 +73,80 @@ fn index_path() -> PathBuf {
 
 /// Load the quarantine index from disk. Missing indexes are empty; unreadable
 /// or invalid indexes are treated as tampering and fail closed.
-fn load_index() -> Result<Vec<QuarantineEntry>, String> {
+fn load_index() -> Result<Vec<QuarantineEntry>, SigilError> {
     let path = index_path();
     match fs::read_to_string(&path) {
         Ok(contents) => serde_json::from_str(&contents)
-            .map_err(|e| format!("failed to parse quarantine index: {}", e)),
+            .map_err(|e| SigilError::Parse(format!("failed to parse quarantine index: {}", e))),
         Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
-        Err(err) => Err(format!("failed to read quarantine index: {}", err)),
+        Err(err) => Err(SigilError::Io(format!(
+            "failed to read quarantine index: {}",
+            err
+        ))),
     }
 }
 
-/// Persist the quarantine index to disk.
-fn save_index(entries: &[QuarantineEntry]) -> Result<(), String> {
+/// Persist the quarantine index to disk. The new index is written beside the
+/// old one and renamed over it, so lock-free readers never see a partial file.
+fn save_index(entries: &[QuarantineEntry]) -> Result<(), SigilError> {
     let path = index_path();
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
-            .map_err(|e| format!("failed to create quarantine directory: {}", e))?;
+            .map_err(|e| SigilError::Io(format!("failed to create quarantine directory: {}", e)))?;
     }
     let json = serde_json::to_string_pretty(entries)
-        .map_err(|e| format!("failed to serialize index: {}", e))?;
-    fs::write(&path, json).map_err(|e| format!("failed to write index: {}", e))?;
+        .map_err(|e| SigilError::Parse(format!("failed to serialize index: {}", e)))?;
+    let staging = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    fs::write(&staging, json)
+        .and_then(|_| fs::rename(&staging, &path))
+        .map_err(|e| {
+            let _ = fs::remove_file(&staging);
+            SigilError::Io(format!("failed to write index: {}", e))
+        })?;
     Ok(())
 }
 
+/// Run a read-modify-write of the index under an exclusive advisory lock on
+/// `index.lock`, so concurrent `sigil` processes (parallel CI scans) cannot
+/// clobber each other's entries. The index is saved only when `update`
+/// succeeds; the lock is released when the file handle drops.
+fn update_index<T>(
+    update: impl FnOnce(&mut Vec<QuarantineEntry>) -> Result<T, SigilError>,
+) -> Result<T, SigilError> {
+    let base = quarantine_path();
+    fs::create_dir_all(&base)
+        .map_err(|e| SigilError::Io(format!("failed to create quarantine directory: {}", e)))?;
+    let lock = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(base.join("index.lock"))
+        .map_err(|e| SigilError::Io(format!("failed to open index lock: {}", e)))?;
+    lock.lock_exclusive()
+        .map_err(|e| SigilError::Io(format!("failed to lock quarantine index: {}", e)))?;
+
+    let mut index = load_index()?;
+    let result = update(&mut index)?;
+    save_index(&index)?;
+    Ok(result)
+}
+
 // ---------------------------------------------------------------------------
 // Public operations
 // ---------------------------------------------------------------------------
 
 /// Add a new item to quarantine. Creates the quarantine directory and returns
 /// the entry with its generated ID and path.
-pub fn add(source: &str, source_type: &str) -> Result<QuarantineEntry, String> {
+pub fn add(source: &str, source_type: &str) -> Result<QuarantineEntry, SigilError> {
     let id = short_id();
     let item_path = quarantine_path().join(&id);
 
     fs::create_dir_all(&item_path).map_err(|e| {
-        format!(
+        SigilError::Io(format!(
             "failed to create quarantine dir {}: {}",
             item_path.display(),
             e
-        )
+        ))
     })?;
 
     let now = Utc::now();
@@ -121,94 +160,135 @@ pub fn add(source: &str, source_type: &str) -> Result<QuarantineEntry, String> {
         updated_at: now,
         reason: None,
         scan_score: None,
+        path_missing: false,
     };
 
-    let mut index = load_index()?;
-    index.push(entry.clone());
-    save_index(&index)?;
+    update_index(|index| {
+        index.push(entry.clone());
+        Ok(())
+    })?;
 
     Ok(entry)
 }
 
 /// Approve a quarantined item by ID. Returns the updated entry.
-pub fn approve(id: &str, reason: Option<&str>) -> Result<QuarantineEntry, String> {
-    let mut index = load_index()?;
-    let entry = index
-        .iter_mut()
-        .find(|e| e.id == id)
-        .ok_or_else(|| format!("quarantine entry '{}' not found", id))?;
-
-    if entry.status != QuarantineStatus::Pending {
-        return Err(format!(
-            "entry '{}' is already {} (cannot approve)",
-            id, entry.status
-        ));
-    }
-
-    entry.status = QuarantineStatus::Approved;
-    entry.updated_at = Utc::now();
-    entry.reason = reason.map(|r| r.to_string());
+pub fn approve(id: &str, reason: Option<&str>) -> Result<QuarantineEntry, SigilError> {
+    update_index(|index| {
+        let entry = index
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or_else(|| SigilError::NotFound(format!("quarantine entry '{}' not found", id)))?;
+
+        if entry.status != QuarantineStatus::Pending {
+            return Err(SigilError::InvalidState(format!(
+                "entry '{}' is already {} (cannot approve)",
+                id, entry.status
+            )));
+        }
 
-    let result = entry.clone();
-    save_index(&index)?;
+        entry.status = QuarantineStatus::Approved;
+        entry.updated_at = Utc::now();
+        entry.reason = reason.map(|r| r.to_string());
 
-    Ok(result)
+        Ok(entry.clone())
+    })
 }
 
 /// Reject a quarantined item by ID. Removes the quarantined files and returns
-/// the updated entry.
-pub fn reject(id: &str, reason: Option<&str>) -> Result<QuarantineEntry, String> {
-    let mut index = load_index()?;
-    let entry_index = index
-        .iter()
-        .position(|e| e.id == id)
-        .ok_or_else(|| format!("quarantine entry '{}' not found", id))?;
-    let entry = &index[entry_index];
-
-    if entry.status != QuarantineStatus::Pending {
-        return Err(format!(
-            "entry '{}' is already {} (cannot reject)",
-            id, entry.status
-        ));
-    }
-
-    if entry.path.exists() {
-        fs::remove_dir_all(&entry.path)
-            .map_err(|e| format!("failed to remove quarantined files for '{}': {}", id, e))?;
-    }
+/// the updated entry. This cannot be undone; `sigil reject` asks before
+/// calling it unless given `--yes`.
+pub fn reject(id: &str, reason: Option<&str>) -> Result<QuarantineEntry, SigilError> {
+    update_index(|index| {
+        let entry = index
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or_else(|| SigilError::NotFound(format!("quarantine entry '{}' not found", id)))?;
+
+        if entry.status != QuarantineStatus::Pending {
+            return Err(SigilError::InvalidState(format!(
+                "entry '{}' is already {} (cannot reject)",
+                id, entry.status
+            )));
+        }
 
-    let entry = &mut index[entry_index];
-    entry.status = QuarantineStatus::Rejected;
-    entry.updated_at = Utc::now();
-    entry.reason = reason.map(|r| r.to_string());
+        if entry.path.exists() {
+            fs::remove_dir_all(&entry.path).map_err(|e| {
+                SigilError::Io(format!(
+                    "failed to remove quarantined files for '{}': {}",
+                    id, e
+                ))
+            })?;
+        }
 
-    let result = entry.clone();
-    save_index(&index)?;
+        entry.status = QuarantineStatus::Rejected;
+        entry.updated_at = Utc::now();
+        entry.reason = reason.map(|r| r.to_string());
 
-    Ok(result)
+        Ok(entry.clone())
+    })
 }
 
 /// Re-quarantine an item by flipping an Approved entry back to Pending. Used by
 /// rug-pull detection (US-F2): an approved artifact whose content drifted loses
 /// its trust and must be re-reviewed. No-op (Ok) if already Pending.
-pub fn requarantine(id: &str, reason: Option<&str>) -> Result<QuarantineEntry, String> {
-    let mut index = load_index()?;
-    let entry = index
-        .iter_mut()
-        .find(|e| e.id == id)
-        .ok_or_else(|| format!("quarantine entry '{}' not found", id))?;
+pub fn requarantine(id: &str, reason: Option<&str>) -> Result<QuarantineEntry, SigilError> {
+    update_index(|index| {
+        let entry = index
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or_else(|| SigilError::NotFound(format!("quarantine entry '{}' not found", id)))?;
+
+        entry.status = QuarantineStatus::Pending;
+        entry.updated_at = Utc::now();
+        entry.reason = reason.map(|r| r.to_string());
+
+        Ok(entry.clone())
+    })
+}
 
-    entry.status = QuarantineStatus::Pending;
-    entry.updated_at = Utc::now();
-    entry.reason = reason.map(|r| r.to_string());
+/// Drop an entry from quarantine entirely: delete its files and remove it from
+/// the index. Used for throwaway entries (e.g. `scan-url` without `--keep`)
+/// that never need a review decision recorded.
+pub fn remove(id: &str) -> Result<(), SigilError> {
+    update_index(|index| {
+        let position = index
+            .iter()
+            .position(|e| e.id == id)
+            .ok_or_else(|| SigilError::NotFound(format!("quarantine entry '{}' not found", id)))?;
+
+        let path = &index[position].path;
+        if path.exists() {
+            fs::remove_dir_all(path).map_err(|e| {
+                SigilError::Io(format!(
+                    "failed to remove quarantined files for '{}': {}",
+                    id, e
+                ))
+            })?;
+        }
 
-    let result = entry.clone();
-    save_index(&index)?;
-    Ok(result)
+        index.remove(position);
+        Ok(())
+    })
+}
+
+/// Record the risk score of the latest scan of an entry. Returns the updated
+/// entry.
+pub fn set_scan_score(id: &str, score: u32) -> Result<QuarantineEntry, SigilError> {
+    update_index(|index| {
+        let entry = index
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or_else(|| SigilError::NotFound(format!("quarantine entry '{}' not found", id)))?;
+
+        entry.scan_score = Some(score);
+        entry.updated_at = Utc::now();
+
+        Ok(entry.clone())
+    })
 }
 
 /// List quarantined items, optionally filtered by status.
-pub fn list(status_filter: Option<&str>) -> Result<Vec<QuarantineEntry>, String> {
+pub fn list(status_filter: Option<&str>) -> Result<Vec<QuarantineEntry>, SigilError> {
     let index = load_index()?;
 
     let filter = status_filter.map(|s| match s.to_lowercase().as_str() {
@@ -226,11 +306,66 @@ pub fn list(status_filter: Option<&str>) -> Result<Vec<QuarantineEntry>, String>
     Ok(entries)
 }
 
-pub fn get(id: &str) -> Result<QuarantineEntry, String> {
+/// One page of [`list`] results, with the size of the full filtered list.
+#[derive(Debug)]
+pub struct ListPage {
+    pub entries: Vec<QuarantineEntry>,
+    pub total: usize,
+}
+
+/// [`list`], skipping the first `offset` entries and returning at most
+/// `limit` (all remaining when `None`).
+pub fn list_page(
+    status_filter: Option<&str>,
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<ListPage, SigilError> {
+    let all = list(status_filter)?;
+    let total = all.len();
+    let entries = all
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    Ok(ListPage { entries, total })
+}
+
+/// Resolve a full ID, or a unique prefix of one, to the entry's ID. A
+/// prefix shared by several entries is an error listing them.
+pub fn resolve_id(prefix: &str) -> Result<String, SigilError> {
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.is_empty() {
+        return Err(SigilError::NotFound("no quarantine ID given".to_string()));
+    }
+    let index = load_index()?;
+    if index.iter().any(|e| e.id == prefix) {
+        return Ok(prefix);
+    }
+    let matches: Vec<&QuarantineEntry> =
+        index.iter().filter(|e| e.id.starts_with(&prefix)).collect();
+    match matches.as_slice() {
+        [] => Err(SigilError::NotFound(format!(
+            "no quarantine entry matches '{}'",
+            prefix
+        ))),
+        [entry] => Ok(entry.id.clone()),
+        candidates => Err(SigilError::Ambiguous(format!(
+            "quarantine ID '{}' is ambiguous; it matches:\n{}",
+            prefix,
+            candidates
+                .iter()
+                .map(|e| format!("  {} {} ({})", e.id, e.source, e.status))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ))),
+    }
+}
+
+pub fn get(id: &str) -> Result<QuarantineEntry, SigilError> {
     load_index()?
         .into_iter()
         .find(|entry| entry.id == id)
-        .ok_or_else(|| format!("quarantine entry '{}' not found", id))
+        .ok_or_else(|| SigilError::NotFound(format!("quarantine entry '{}' not found", id)))
 }
 
 /// Generate a short unique identifier (first 8 chars of a UUID v4).
@@ -238,9 +373,237 @@ fn short_id() -> String {
     Uuid::new_v4().to_string()[..8].to_string()
 }
 
+// ---------------------------------------------------------------------------
+// Export / import
+// ---------------------------------------------------------------------------
+
+/// `format` of an export document.
+const EXPORT_FORMAT: &str = "sigil-quarantine-export";
+const EXPORT_VERSION: u32 = 1;
+/// The export document inside a bundle; each entry's files sit under
+/// `files/<id>/`.
+const BUNDLE_INDEX: &str = "index.json";
+const BUNDLE_FILES: &str = "files";
+
+/// The index as shared between machines by `sigil quarantine export`.
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportDocument {
+    format: String,
+    version: u32,
+    exported_at: DateTime<Utc>,
+    entries: Vec<QuarantineEntry>,
+}
+
+/// What [`import`] did with the entries it read.
+#[derive(Debug, Default, Serialize)]
+pub struct ImportSummary {
+    /// Entries new to this machine.
+    pub added: usize,
+    /// Entries already here whose review state the import was newer for.
+    pub updated: usize,
+    /// Entries already here and at least as recent.
+    pub unchanged: usize,
+    /// `(exported ID, local ID)` for entries whose ID was taken by a
+    /// different entry here.
+    pub renamed: Vec<(String, String)>,
+    /// Added entries whose files were not in the import.
+    pub path_missing: usize,
+}
+
+/// Write the quarantine index to `file`: plain JSON, or with `with_files` a
+/// gzipped tarball that also carries every entry's files.  Returns the
+/// number of entries written.
+pub fn export(file: &Path, with_files: bool) -> Result<usize, SigilError> {
+    let document = ExportDocument {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: Utc::now(),
+        entries: load_index()?,
+    };
+    let json = serde_json::to_string_pretty(&document)
+        .map_err(|e| SigilError::Parse(format!("failed to serialize export: {}", e)))?;
+    let io_err =
+        |e: std::io::Error| SigilError::Io(format!("failed to write {}: {}", file.display(), e));
+    if !with_files {
+        fs::write(file, json + "\n").map_err(io_err)?;
+        return Ok(document.entries.len());
+    }
+
+    let out = fs::File::create(file).map_err(io_err)?;
+    let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+        out,
+        flate2::Compression::default(),
+    ));
+    // Quarantined packages are hostile: keep their symlinks as links
+    // instead of archiving whatever they point at.
+    tar.follow_symlinks(false);
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(document.exported_at.timestamp().max(0) as u64);
+    header.set_cksum();
+    tar.append_data(&mut header, BUNDLE_INDEX, json.as_bytes())
+        .map_err(io_err)?;
+    for entry in &document.entries {
+        if entry.path.is_dir() {
+            tar.append_dir_all(Path::new(BUNDLE_FILES).join(&entry.id), &entry.path)
+                .map_err(io_err)?;
+        }
+    }
+    tar.into_inner()
+        .and_then(|gz| gz.finish())
+        .map_err(io_err)?;
+    Ok(document.entries.len())
+}
+
+/// Unpack a bundle into `staging`, returning its export document.  Entry
+/// paths that would land outside `staging` are refused by `unpack_in`.
+fn unpack_bundle(bytes: &[u8], staging: &Path) -> Result<ExportDocument, SigilError> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+    let entries = archive
+        .entries()
+        .map_err(|e| SigilError::Parse(format!("invalid export bundle: {}", e)))?;
+    for entry in entries {
+        let mut entry =
+            entry.map_err(|e| SigilError::Parse(format!("invalid export bundle: {}", e)))?;
+        entry
+            .unpack_in(staging)
+            .map_err(|e| SigilError::Io(format!("failed to unpack export bundle: {}", e)))?;
+    }
+    let index = fs::read_to_string(staging.join(BUNDLE_INDEX))
+        .map_err(|_| SigilError::Parse(format!("export bundle has no {}", BUNDLE_INDEX)))?;
+    parse_export(&index)
+}
+
+fn parse_export(json: &str) -> Result<ExportDocument, SigilError> {
+    let document: ExportDocument = serde_json::from_str(json)
+        .map_err(|e| SigilError::Parse(format!("invalid quarantine export: {}", e)))?;
+    if document.format != EXPORT_FORMAT || document.version > EXPORT_VERSION {
+        return Err(SigilError::Parse(format!(
+            "not a supported quarantine export ({} v{})",
+            document.format, document.version
+        )));
+    }
+    Ok(document)
+}
+
+/// Merge an export written by [`export`] into the local index.
+///
+/// An entry already here (same source and creation time) takes the
+/// imported status, reason, and score when the import is newer.  Any other
+/// entry is added, under a fresh ID if its own is taken.  Imported paths
+/// are never trusted: an added entry's files go in this machine's
+/// quarantine directory, from the bundle when it has them, and an entry
+/// without them is marked `path_missing` rather than failing the import.
+pub fn import(file: &Path) -> Result<ImportSummary, SigilError> {
+    let mut bytes = Vec::new();
+    fs::File::open(file)
+        .and_then(|mut f| f.read_to_end(&mut bytes))
+        .map_err(|e| SigilError::Io(format!("failed to read {}: {}", file.display(), e)))?;
+
+    let base = quarantine_path();
+    let staging = base.join(format!(".import-{}", short_id()));
+    let result = (|| {
+        let (document, files) = if bytes.starts_with(&[0x1f, 0x8b]) {
+            fs::create_dir_all(&staging).map_err(|e| {
+                SigilError::Io(format!("failed to create {}: {}", staging.display(), e))
+            })?;
+            (
+                unpack_bundle(&bytes, &staging)?,
+                Some(staging.join(BUNDLE_FILES)),
+            )
+        } else {
+            let json = String::from_utf8(bytes).map_err(|_| {
+                SigilError::Parse("invalid quarantine export: not UTF-8".to_string())
+            })?;
+            (parse_export(&json)?, None)
+        };
+        update_index(|index| merge(index, document.entries, files.as_deref()))
+    })();
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+fn merge(
+    index: &mut Vec<QuarantineEntry>,
+    imported: Vec<QuarantineEntry>,
+    files: Option<&Path>,
+) -> Result<ImportSummary, SigilError> {
+    let mut summary = ImportSummary::default();
+    for mut entry in imported {
+        // Identity is the package and when it was quarantined, not the ID,
+        // so an entry renamed by an earlier import is still recognised.
+        let same = index.iter_mut().find(|local| {
+            local.source == entry.source
+                && local.source_type == entry.source_type
+                && local.created_at == entry.created_at
+        });
+        if let Some(local) = same {
+            if entry.updated_at > local.updated_at {
+                local.status = entry.status;
+                local.reason = entry.reason;
+                local.scan_score = entry.scan_score;
+                local.updated_at = entry.updated_at;
+                summary.updated += 1;
+            } else {
+                summary.unchanged += 1;
+            }
+            continue;
+        }
+
+        let exported_id = entry.id.clone();
+        if index.iter().any(|local| local.id == entry.id) || !is_plain_id(&entry.id) {
+            entry.id = loop {
+                let id = short_id();
+                if !index.iter().any(|local| local.id == id) {
+                    break id;
+                }
+            };
+            summary
+                .renamed
+                .push((exported_id.clone(), entry.id.clone()));
+        }
+        entry.path = quarantine_path().join(&entry.id);
+        let bundled = files
+            .map(|files| files.join(&exported_id))
+            .filter(|dir| dir.is_dir() && is_plain_id(&exported_id));
+        if let Some(dir) = bundled {
+            if entry.path.exists() {
+                return Err(SigilError::InvalidState(format!(
+                    "cannot import '{}': {} already exists",
+                    exported_id,
+                    entry.path.display()
+                )));
+            }
+            fs::rename(&dir, &entry.path).map_err(|e| {
+                SigilError::Io(format!(
+                    "failed to import files for '{}': {}",
+                    exported_id, e
+                ))
+            })?;
+        }
+        // Rejected entries have had their files deleted; nothing is missing.
+        entry.path_missing = entry.status != QuarantineStatus::Rejected && !entry.path.is_dir();
+        if entry.path_missing {
+            summary.path_missing += 1;
+        }
+        summary.added += 1;
+        index.push(entry);
+    }
+    Ok(summary)
+}
+
+/// Whether `id` can name a directory under the quarantine root as is.
+fn is_plain_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{add, get, list, reject, QuarantineStatus};
+    use super::{
+        add, approve, export, get, import, list, list_page, reject, remove, resolve_id,
+        set_scan_score, QuarantineStatus, SigilError,
+    };
     use std::fs;
     use std::sync::Mutex;
     use tempfile::tempdir;
@@ -264,7 +627,10 @@ mod tests {
             fs::write(base.join("index.json"), "not json").expect("write invalid index");
 
             let error = list(None).expect_err("invalid index should fail");
-            assert!(error.contains("failed to parse quarantine index"));
+            assert!(matches!(error, SigilError::Parse(_)));
+            assert!(error
+                .to_string()
+                .contains("failed to parse quarantine index"));
         });
     }
 
@@ -276,10 +642,211 @@ mod tests {
             fs::write(&entry.path, "not a directory").expect("replace path with file");
 
             let error = reject(&entry.id, Some("bad")).expect_err("delete should fail");
-            assert!(error.contains("failed to remove quarantined files"));
+            assert!(matches!(error, SigilError::Io(_)));
+            assert!(error
+                .to_string()
+                .contains("failed to remove quarantined files"));
 
             let stored = get(&entry.id).expect("entry remains indexed");
             assert_eq!(stored.status, QuarantineStatus::Pending);
         });
     }
+
+    /// Write an index holding entries with the given IDs.
+    fn write_index(ids: &[&str]) {
+        let base = super::quarantine_path();
+        fs::create_dir_all(&base).expect("create quarantine");
+        let entries: Vec<String> = ids
+            .iter()
+            .map(|id| {
+                format!(
+                    r#"{{"id":"{id}","source":"pkg-{id}","source_type":"pip","path":"/tmp/q/{id}","status":"Pending","created_at":"2026-01-01T00:00:00Z","updated_at":"2026-01-01T00:00:00Z","reason":null,"scan_score":null}}"#
+                )
+            })
+            .collect();
+        fs::write(base.join("index.json"), format!("[{}]", entries.join(",")))
+            .expect("write index");
+    }
+
+    #[test]
+    fn unique_prefix_resolves_to_full_id() {
+        with_quarantine_dir(|| {
+            write_index(&["a1b2c3d4", "f00dcafe"]);
+            assert_eq!(resolve_id("a1").unwrap(), "a1b2c3d4");
+            assert_eq!(resolve_id("F00D").unwrap(), "f00dcafe");
+            assert_eq!(resolve_id("f00dcafe").unwrap(), "f00dcafe");
+        });
+    }
+
+    #[test]
+    fn ambiguous_prefix_lists_candidates() {
+        with_quarantine_dir(|| {
+            write_index(&["abcd0001", "abcd0002", "ffff0000"]);
+            let error = resolve_id("abcd").expect_err("two entries share the prefix");
+            assert!(matches!(error, SigilError::Ambiguous(_)));
+            let message = error.to_string();
+            assert!(message.contains("abcd0001 pkg-abcd0001"), "{message}");
+            assert!(message.contains("abcd0002 pkg-abcd0002"), "{message}");
+            assert!(!message.contains("ffff0000"), "{message}");
+            assert_eq!(resolve_id("abcd0002").unwrap(), "abcd0002");
+        });
+    }
+
+    #[test]
+    fn prefix_matching_nothing_is_not_found() {
+        with_quarantine_dir(|| {
+            write_index(&["abcd0001"]);
+            for prefix in ["zz", "abcd00011", ""] {
+                assert!(matches!(resolve_id(prefix), Err(SigilError::NotFound(_))));
+            }
+        });
+    }
+
+    #[test]
+    fn remove_deletes_files_and_index_entry() {
+        with_quarantine_dir(|| {
+            let entry = add("https://example.com/a.tgz", "url").expect("add entry");
+            fs::write(entry.path.join("a.tgz"), "x").expect("write file");
+
+            remove(&entry.id).expect("remove");
+
+            assert!(!entry.path.exists());
+            assert!(get(&entry.id).is_err());
+        });
+    }
+
+    #[test]
+    fn set_scan_score_updates_entry() {
+        with_quarantine_dir(|| {
+            let entry = add("pkg", "pip").expect("add entry");
+            assert_eq!(entry.scan_score, None);
+
+            let updated = set_scan_score(&entry.id, 42).expect("set score");
+            assert_eq!(updated.scan_score, Some(42));
+            assert_eq!(get(&entry.id).unwrap().scan_score, Some(42));
+            assert!(matches!(
+                set_scan_score("missing", 1),
+                Err(SigilError::NotFound(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn list_page_applies_offset_and_limit() {
+        with_quarantine_dir(|| {
+            let ids: Vec<String> = (0..5)
+                .map(|i| add(&format!("pkg{}", i), "pip").expect("add").id)
+                .collect();
+
+            let page = list_page(None, 1, Some(2)).expect("page");
+            assert_eq!(page.total, 5);
+            let got: Vec<&str> = page.entries.iter().map(|e| e.id.as_str()).collect();
+            assert_eq!(got, vec![ids[1].as_str(), ids[2].as_str()]);
+
+            let tail = list_page(None, 4, Some(10)).expect("tail");
+            assert_eq!(tail.entries.len(), 1);
+            let past_end = list_page(None, 9, None).expect("past end");
+            assert!(past_end.entries.is_empty());
+            assert_eq!(past_end.total, 5);
+        });
+    }
+
+    #[test]
+    fn concurrent_adds_are_not_lost() {
+        with_quarantine_dir(|| {
+            let handles: Vec<_> = (0..8)
+                .map(|t| {
+                    std::thread::spawn(move || {
+                        (0..5)
+                            .map(|i| add(&format!("pkg-{}-{}", t, i), "git").expect("add").id)
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            let mut added: Vec<String> = handles
+                .into_iter()
+                .flat_map(|h| h.join().expect("thread"))
+                .collect();
+
+            let mut listed: Vec<String> = list(None)
+                .expect("list")
+                .into_iter()
+                .map(|e| e.id)
+                .collect();
+            added.sort();
+            listed.sort();
+            assert_eq!(listed.len(), 40);
+            assert_eq!(listed, added);
+        });
+    }
+
+    #[test]
+    fn import_keeps_review_state_and_renames_colliding_ids() {
+        let shared = tempdir().expect("tempdir");
+        let file = shared.path().join("review.json");
+        let (approved_id, pending_id) = with_quarantine_dir(|| {
+            let approved = add("requests==2.32.3", "pip").expect("add");
+            approve(&approved.id, Some("reviewed by sec team")).expect("approve");
+            let pending = add("left-pad", "npm").expect("add");
+            assert_eq!(export(&file, false).expect("export"), 2);
+            (approved.id, pending.id)
+        });
+
+        with_quarantine_dir(|| {
+            // A different local entry already uses the pending entry's ID.
+            write_index(&[pending_id.as_str()]);
+            let summary = import(&file).expect("import");
+            assert_eq!(summary.added, 2);
+            assert_eq!(summary.path_missing, 2);
+            assert_eq!(summary.renamed.len(), 1);
+            assert_eq!(summary.renamed[0].0, pending_id);
+
+            let imported = get(&approved_id).expect("approved entry keeps its ID");
+            assert_eq!(imported.status, QuarantineStatus::Approved);
+            assert_eq!(imported.reason.as_deref(), Some("reviewed by sec team"));
+            assert!(imported.path_missing);
+            assert!(imported.path.starts_with(super::quarantine_path()));
+            let renamed = get(&summary.renamed[0].1).expect("renamed entry");
+            assert_eq!(renamed.source, "left-pad");
+            assert_eq!(
+                get(&pending_id).unwrap().source,
+                format!("pkg-{}", pending_id)
+            );
+
+            // Importing the same state again changes nothing.
+            let again = import(&file).expect("re-import");
+            assert_eq!((again.added, again.unchanged), (0, 2));
+        });
+    }
+
+    #[test]
+    fn bundle_carries_files_into_the_local_quarantine() {
+        let shared = tempdir().expect("tempdir");
+        let file = shared.path().join("review.tar.gz");
+        let id = with_quarantine_dir(|| {
+            let entry = add("https://example.com/a.tgz", "url").expect("add");
+            fs::create_dir_all(entry.path.join("pkg")).expect("mkdir");
+            fs::write(entry.path.join("pkg/setup.py"), "print('hi')\n").expect("write");
+            export(&file, true).expect("export bundle");
+            entry.id
+        });
+
+        with_quarantine_dir(|| {
+            let summary = import(&file).expect("import bundle");
+            assert_eq!((summary.added, summary.path_missing), (1, 0));
+            let entry = get(&id).expect("entry");
+            assert!(!entry.path_missing);
+            assert_eq!(
+                fs::read_to_string(entry.path.join("pkg/setup.py")).unwrap(),
+                "print('hi')\n"
+            );
+            // The staging directory is cleaned up.
+            let leftovers: Vec<_> = fs::read_dir(super::quarantine_path())
+                .unwrap()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_name().to_string_lossy().starts_with(".import-"))
+                .collect();
+            assert!(leftovers.is_empty());
+        });
+    }
 }
@@ -0,0 +1,231 @@
+//! `sigil scan --interactive`: a terminal UI for triaging findings.
+//!
+//! Findings are listed on the left; the right pane shows the selected one
+//! with the surrounding lines of its file.  The reviewer marks the findings
+//! to accept, and on exit those are returned to be recorded as the
+//! target's suppressions.  Built only with the `tui` feature.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::scanner::{Finding, Severity};
+
+/// Lines of the file shown above and below a finding.
+const CONTEXT_LINES: usize = 6;
+
+const HELP: &str =
+    " ↑/↓ move   a/space accept   A accept all of rule   q save and quit   esc quit without saving ";
+
+struct Review<'a> {
+    findings: &'a [Finding],
+    root: &'a Path,
+    accepted: Vec<bool>,
+    list: ListState,
+    /// File contents by finding path; `None` when unreadable.
+    files: HashMap<String, Option<Vec<String>>>,
+}
+
+impl<'a> Review<'a> {
+    fn new(findings: &'a [Finding], root: &'a Path) -> Self {
+        Review {
+            findings,
+            root,
+            accepted: vec![false; findings.len()],
+            list: ListState::default().with_selected(Some(0)),
+            files: HashMap::new(),
+        }
+    }
+
+    fn selected(&self) -> usize {
+        self.list.selected().unwrap_or(0)
+    }
+
+    fn path_of(&self, file: &str) -> PathBuf {
+        if self.root.is_dir() {
+            self.root.join(file)
+        } else {
+            self.root.to_path_buf()
+        }
+    }
+
+    fn lines_of(&mut self, file: &str) -> Option<&[String]> {
+        if !self.files.contains_key(file) {
+            let lines = std::fs::read(self.path_of(file)).ok().map(|bytes| {
+                String::from_utf8_lossy(&bytes)
+                    .lines()
+                    .map(|l| l.replace('\t', "    "))
+                    .collect()
+            });
+            self.files.insert(file.to_string(), lines);
+        }
+        self.files.get(file).and_then(|lines| lines.as_deref())
+    }
+
+    fn toggle_rule(&mut self) {
+        let rule = &self.findings[self.selected()].rule;
+        let all = self
+            .findings
+            .iter()
+            .zip(&self.accepted)
+            .filter(|(f, _)| &f.rule == rule)
+            .all(|(_, accepted)| *accepted);
+        for (f, accepted) in self.findings.iter().zip(self.accepted.iter_mut()) {
+            if &f.rule == rule {
+                *accepted = !all;
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [body, help] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(body);
+
+        let items: Vec<ListItem> = self
+            .findings
+            .iter()
+            .zip(&self.accepted)
+            .map(|(f, accepted)| {
+                let mark = if *accepted { "[x] " } else { "[ ] " };
+                let location = match f.line {
+                    Some(line) => format!("{}:{}", f.file, line),
+                    None => f.file.clone(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(mark),
+                    Span::styled(format!("{:<8} ", f.severity), severity_style(f.severity)),
+                    Span::raw(format!("{} {}", f.rule, location)),
+                ]))
+            })
+            .collect();
+        let done = self.accepted.iter().filter(|a| **a).count();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                " Findings ({} accepted of {}) ",
+                done,
+                self.findings.len()
+            )))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, left, &mut self.list);
+
+        let finding = &self.findings[self.selected()];
+        let mut text = vec![
+            Line::from(vec![
+                Span::styled(
+                    format!("{} ", finding.severity),
+                    severity_style(finding.severity),
+                ),
+                Span::styled(
+                    finding.rule.clone(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!("  {}", finding.phase)),
+            ]),
+            Line::from(finding.snippet.clone()),
+            Line::from(""),
+        ];
+        let target = finding.line;
+        match (target, self.lines_of(&finding.file)) {
+            (Some(target), Some(lines)) => {
+                let first = target.saturating_sub(CONTEXT_LINES).max(1);
+                let last = (target + CONTEXT_LINES).min(lines.len());
+                for n in first..=last {
+                    let content = format!("{:>5} | {}", n, lines[n - 1]);
+                    text.push(if n == target {
+                        Line::styled(content, Style::default().fg(Color::Yellow))
+                    } else {
+                        Line::styled(content, Style::default().fg(Color::DarkGray))
+                    });
+                }
+            }
+            (None, _) => text.push(Line::from("(finding is not tied to a line)")),
+            (_, None) => text.push(Line::from("(file could not be read)")),
+        }
+        let title = format!(" {} ", finding.file);
+        let detail = Paragraph::new(text)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail, right);
+
+        frame.render_widget(
+            Paragraph::new(HELP).style(Style::default().add_modifier(Modifier::REVERSED)),
+            help,
+        );
+    }
+}
+
+fn severity_style(severity: Severity) -> Style {
+    let color = match severity {
+        Severity::Critical => Color::Red,
+        Severity::High => Color::LightRed,
+        Severity::Medium => Color::Yellow,
+        Severity::Low => Color::Blue,
+    };
+    Style::default().fg(color).add_modifier(Modifier::BOLD)
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, review: &mut Review) -> std::io::Result<bool> {
+    let last = review.findings.len() - 1;
+    loop {
+        terminal.draw(|frame| review.draw(frame))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let selected = review.selected();
+        match key.code {
+            KeyCode::Char('q') => return Ok(true),
+            KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Ok(false)
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                review.list.select(Some((selected + 1).min(last)))
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                review.list.select(Some(selected.saturating_sub(1)))
+            }
+            KeyCode::PageDown => review.list.select(Some((selected + 10).min(last))),
+            KeyCode::PageUp => review.list.select(Some(selected.saturating_sub(10))),
+            KeyCode::Home | KeyCode::Char('g') => review.list.select(Some(0)),
+            KeyCode::End | KeyCode::Char('G') => review.list.select(Some(last)),
+            KeyCode::Char('a') | KeyCode::Char(' ') => {
+                review.accepted[selected] = !review.accepted[selected];
+                review.list.select(Some((selected + 1).min(last)));
+            }
+            KeyCode::Char('A') => review.toggle_rule(),
+            _ => {}
+        }
+    }
+}
+
+/// Let the user review `findings` from a scan of `root`.  Returns the ones
+/// they accepted, or `None` when they quit without saving.  The caller must
+/// have checked that stdin and stdout are terminals.
+pub fn run(findings: &[Finding], root: &Path) -> std::io::Result<Option<Vec<Finding>>> {
+    if findings.is_empty() {
+        return Ok(Some(Vec::new()));
+    }
+    let mut review = Review::new(findings, root);
+    let mut terminal = ratatui::try_init()?;
+    let outcome = event_loop(&mut terminal, &mut review);
+    ratatui::restore();
+    let accepted = findings
+        .iter()
+        .zip(&review.accepted)
+        .filter(|(_, accepted)| **accepted)
+        .map(|(f, _)| f.clone())
+        .collect();
+    Ok(outcome?.then_some(accepted))
+}
@@ -1,5 +1,6 @@
 pub mod parsers;
 
+use crate::logging::log_warn;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
@@ -121,31 +122,35 @@ fn severity_rank(s: &str) -> u8 {
     }
 }
 
-/// Walk the project directory, find lockfiles, parse them, and cross-reference
-/// against known threats.
-pub fn generate_sbom(
-    path: &Path,
-    threats_db: Option<&Path>,
-) -> Result<Sbom, Box<dyn std::error::Error>> {
-    let threats = if let Some(db_path) = threats_db {
-        load_known_threats(db_path)
-    } else {
-        // Try default location relative to the binary or project
-        let default_path = Path::new("api/data/known_threats.json");
-        if default_path.exists() {
-            load_known_threats(default_path)
-        } else {
-            HashMap::new()
-        }
-    };
-
-    let mut all_components: Vec<Component> = Vec::new();
-
-    // Walk the directory looking for dependency files
-    let lockfile_names = ["package-lock.json", "requirements.txt", "Cargo.lock"];
+/// Dependency files [`find_lockfiles`] recognises, by file name.
+pub const LOCKFILE_NAMES: &[&str] = &[
+    "package-lock.json",
+    "requirements.txt",
+    "poetry.lock",
+    "Cargo.lock",
+];
+
+/// One dependency file found under a project, and what parsing it yielded.
+#[derive(Debug, Clone, Serialize)]
+pub struct Lockfile {
+    /// Path relative to the project root
+    pub path: String,
+    /// Dependencies listed in the file (empty when it could not be parsed)
+    pub components: Vec<Component>,
+    /// Why the file could not be parsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
 
+/// Find and parse every dependency file under `path`, skipping
+/// `node_modules` and `target` trees.  A file that cannot be parsed is
+/// returned with its error rather than dropped, so callers can say which
+/// files were read.
+pub fn find_lockfiles(path: &Path) -> Vec<Lockfile> {
+    let mut lockfiles = Vec::new();
     for entry in WalkDir::new(path)
         .follow_links(false)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(|e| e.ok())
     {
@@ -161,22 +166,62 @@ pub fn generate_sbom(
             continue;
         }
 
-        if !lockfile_names.contains(&file_name) {
-            continue;
-        }
-
         let parsed = match file_name {
             "package-lock.json" => parsers::parse_package_lock(file_path),
             "requirements.txt" => parsers::parse_requirements_txt(file_path),
+            "poetry.lock" => parsers::parse_poetry_lock(file_path),
             "Cargo.lock" => parsers::parse_cargo_lock(file_path),
             _ => continue,
         };
 
-        match parsed {
-            Ok(mut components) => all_components.append(&mut components),
-            Err(e) => {
-                eprintln!("Warning: failed to parse {}: {}", file_path.display(), e);
-            }
+        // A lockfile given directly is reported by its name.
+        let rel = match crate::scanner::relative_path(file_path, path) {
+            rel if rel.is_empty() => file_name.to_string(),
+            rel => rel,
+        };
+        lockfiles.push(match parsed {
+            Ok(components) => Lockfile {
+                path: rel,
+                components,
+                error: None,
+            },
+            Err(e) => Lockfile {
+                path: rel,
+                components: Vec::new(),
+                error: Some(e.to_string()),
+            },
+        });
+    }
+    lockfiles
+}
+
+/// Walk the project directory, find lockfiles, parse them, and cross-reference
+/// against known threats.
+pub fn generate_sbom(
+    path: &Path,
+    threats_db: Option<&Path>,
+) -> Result<Sbom, Box<dyn std::error::Error>> {
+    let threats = if let Some(db_path) = threats_db {
+        load_known_threats(db_path)
+    } else {
+        // Try default location relative to the binary or project
+        let default_path = Path::new("api/data/known_threats.json");
+        if default_path.exists() {
+            load_known_threats(default_path)
+        } else {
+            HashMap::new()
+        }
+    };
+
+    let mut all_components: Vec<Component> = Vec::new();
+    for lockfile in find_lockfiles(path) {
+        match lockfile.error {
+            None => all_components.extend(lockfile.components),
+            Some(e) => log_warn!(
+                "failed to parse {}: {}",
+                path.join(&lockfile.path).display(),
+                e
+            ),
         }
     }
 
@@ -313,3 +358,37 @@ pub fn format_cyclonedx(sbom: &Sbom) -> String {
 
     serde_json::to_string_pretty(&cdx).unwrap_or_else(|_| "{}".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lockfiles_are_listed_with_parse_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("requirements.txt"), "requests==2.31.0\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("web/node_modules/x")).unwrap();
+        std::fs::write(dir.path().join("web/package-lock.json"), "{not json").unwrap();
+        std::fs::write(
+            dir.path().join("web/node_modules/x/package-lock.json"),
+            "{}",
+        )
+        .unwrap();
+
+        let lockfiles = find_lockfiles(dir.path());
+        let summary: Vec<_> = lockfiles
+            .iter()
+            .map(|l| (l.path.as_str(), l.components.len(), l.error.is_some()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("requirements.txt", 1, false),
+                ("web/package-lock.json", 0, true),
+            ]
+        );
+
+        let single = find_lockfiles(&dir.path().join("requirements.txt"));
+        assert_eq!(single[0].path, "requirements.txt");
+    }
+}
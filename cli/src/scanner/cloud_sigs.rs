@@ -1,11 +1,13 @@
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
+use super::phases::make_finding;
 use super::{Finding, Phase, Severity};
 
 /// A cloud-fetched signature (matches the API response format).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CloudSignature {
     pub id: String,
     pub pattern: String,
@@ -26,40 +28,210 @@ pub struct SignatureResponse {
     pub total: usize,
     #[serde(default)]
     pub last_updated: Option<String>,
+    /// Base64 Ed25519 signature over the rest of the response; see
+    /// [`verify_bundle`].
+    #[serde(default)]
+    pub signature: Option<String>,
 }
 
 /// Path to the locally cached signatures file.
 pub fn signatures_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".sigil")
-        .join("signatures.json")
+    crate::paths::in_home("signatures.json")
+}
+
+/// Copy of the last fetched set that was written intact, read when
+/// `signatures.json` is corrupt.
+fn known_good_path() -> PathBuf {
+    signatures_path().with_file_name("signatures.good.json")
 }
 
 /// Path to the metadata file that tracks when signatures were last fetched.
 fn sync_meta_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join(".sigil")
-        .join("signatures_meta.json")
+    crate::paths::in_home("signatures_meta.json")
 }
 
-/// Load cloud signatures from disk.  Returns an empty vec if the file is
-/// missing or malformed (offline-safe).
-pub fn load_cloud_signatures() -> Vec<CloudSignature> {
-    let path = signatures_path();
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return vec![],
+/// Curated signatures compiled into the binary: a subset of the cloud set
+/// (`api/data/threat_signatures.json`) that no core pack covers. They are
+/// the base that fetched signatures are merged onto, so cloud rules work
+/// before the first `sigil fetch` and in air-gapped CI.
+const BUNDLED_SIGNATURES: &str = include_str!("bundled_signatures.json");
+
+/// Config key holding signature IDs to skip, bundled or fetched.
+pub const DISABLED_KEY: &str = "signatures.disabled";
+
+/// Config key that turns bundle verification off (on by default).
+pub const VERIFY_KEY: &str = "signatures.verify";
+
+/// Ed25519 key that signs the bundle served by the Sigil API.  Self-hosted
+/// endpoints do not hold its private half, so they set
+/// `signatures.verify false`.
+pub const SIGNATURES_PUBLIC_KEY: [u8; 32] = [
+    0xab, 0x9a, 0x72, 0x66, 0xc9, 0x37, 0x18, 0xb6, 0x35, 0x0b, 0x55, 0x3b, 0x13, 0x67, 0xe9, 0x38,
+    0x8a, 0x29, 0x90, 0x32, 0x28, 0xf1, 0xa7, 0x10, 0x02, 0xe7, 0x39, 0xf2, 0x78, 0x78, 0x24, 0xa4,
+];
+
+/// Parse either the wrapped (`{signatures: [...]}`) or raw array format.
+fn parse_signatures(contents: &str) -> Option<Vec<CloudSignature>> {
+    if let Ok(resp) = serde_json::from_str::<SignatureResponse>(contents) {
+        return Some(resp.signatures);
+    }
+    serde_json::from_str::<Vec<CloudSignature>>(contents).ok()
+}
+
+/// The signatures shipped inside the binary.
+pub fn bundled_signatures() -> Vec<CloudSignature> {
+    parse_signatures(BUNDLED_SIGNATURES).unwrap_or_default()
+}
+
+/// Load the signatures saved by `sigil fetch`.  Returns an empty vec if the
+/// file is missing (offline-safe); a malformed file falls back to the
+/// last-known-good copy.
+pub fn load_fetched_signatures() -> Vec<CloudSignature> {
+    read_signatures_file(&signatures_path(), &known_good_path())
+}
+
+fn read_signatures_file(path: &Path, known_good: &Path) -> Vec<CloudSignature> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Vec::new();
     };
+    parse_signatures(&contents)
+        .or_else(|| {
+            std::fs::read_to_string(known_good)
+                .ok()
+                .and_then(|good| parse_signatures(&good))
+        })
+        .unwrap_or_default()
+}
+
+/// Write a fetched set to `signatures.json` and keep a copy as the
+/// last-known-good bundle.  The file is replaced by rename, so an
+/// interrupted write never leaves a half-written set behind.
+pub fn save_fetched_signatures(json: &str) -> std::io::Result<()> {
+    let path = signatures_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, &path)?;
+    std::fs::write(known_good_path(), json)
+}
 
-    // Try wrapped format first ({signatures: [...]})
-    if let Ok(resp) = serde_json::from_str::<SignatureResponse>(&contents) {
-        return resp.signatures;
+/// Whether fetched bundles must carry a valid signature
+/// (`signatures.verify`, default true).
+pub fn verification_enabled() -> bool {
+    match crate::config::resolve().merged().get(VERIFY_KEY) {
+        Some(serde_json::Value::Bool(verify)) => *verify,
+        Some(serde_json::Value::String(raw)) => !matches!(raw.trim(), "false" | "0" | "no" | "off"),
+        _ => true,
     }
+}
+
+/// Check the `signature` of a GET /v1/signatures response body: a Base64
+/// Ed25519 signature over the compact JSON of the body without its
+/// `signature` key.  A missing signature is an error.
+pub fn verify_bundle(body: &str, public_key: &[u8; 32]) -> Result<(), String> {
+    let mut doc: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("response is not JSON: {}", e))?;
+    let signature = doc
+        .as_object_mut()
+        .and_then(|obj| obj.remove("signature"))
+        .and_then(|sig| sig.as_str().map(str::to_string))
+        .ok_or("response is not signed")?;
+    let canonical = serde_json::to_string(&doc).map_err(|e| e.to_string())?;
+    let verifier = crate::corpus::signing::PackVerifier::from_public_key_bytes(public_key)
+        .map_err(|e| e.to_string())?;
+    verifier
+        .verify_detached(canonical.as_bytes(), &signature)
+        .map_err(|e| e.to_string())
+}
 
-    // Fall back to raw array format
-    serde_json::from_str::<Vec<CloudSignature>>(&contents).unwrap_or_default()
+/// Overlay `fetched` on `bundled` by ID (a fetched signature replaces the
+/// bundled one with the same ID), then drop every ID in `disabled`.
+pub fn merge_signatures(
+    bundled: Vec<CloudSignature>,
+    fetched: Vec<CloudSignature>,
+    disabled: &HashSet<String>,
+) -> Vec<CloudSignature> {
+    let mut merged = bundled;
+    for sig in fetched {
+        match merged.iter().position(|s| s.id == sig.id) {
+            Some(pos) => merged[pos] = sig,
+            None => merged.push(sig),
+        }
+    }
+    merged.retain(|s| !disabled.contains(&s.id.to_lowercase()));
+    merged
+}
+
+/// What a fetch changes in the local signature set.
+#[derive(Debug, Default, PartialEq)]
+pub struct SignatureDelta {
+    /// IDs not present locally before the fetch.
+    pub new: Vec<String>,
+    /// IDs present locally whose pattern, phase, severity, or description
+    /// changed.
+    pub updated: Vec<String>,
+    /// Size of the local set after the fetch.
+    pub total: usize,
+}
+
+/// Upsert `fetched` onto `existing` by ID, returning the merged set and
+/// what changed.
+pub fn apply_fetched(
+    existing: Vec<CloudSignature>,
+    fetched: &[CloudSignature],
+) -> (Vec<CloudSignature>, SignatureDelta) {
+    let mut merged = existing;
+    let mut delta = SignatureDelta::default();
+    for sig in fetched {
+        match merged.iter().position(|s| s.id == sig.id) {
+            Some(pos) => {
+                let old = &merged[pos];
+                let changed = old.pattern != sig.pattern
+                    || old.phase != sig.phase
+                    || old.severity != sig.severity
+                    || old.description != sig.description;
+                if changed {
+                    delta.updated.push(sig.id.clone());
+                }
+                merged[pos] = sig.clone();
+            }
+            None => {
+                delta.new.push(sig.id.clone());
+                merged.push(sig.clone());
+            }
+        }
+    }
+    delta.total = merged.len();
+    (merged, delta)
+}
+
+/// Lower-cased IDs listed under `signatures.disabled` in the config.
+pub(crate) fn disabled_signatures() -> HashSet<String> {
+    match crate::config::resolve().merged().get(DISABLED_KEY) {
+        Some(serde_json::Value::Array(ids)) => ids
+            .iter()
+            .filter_map(|id| id.as_str())
+            .map(str::to_lowercase)
+            .collect(),
+        Some(serde_json::Value::String(ids)) => ids
+            .split(',')
+            .map(|id| id.trim().to_lowercase())
+            .filter(|id| !id.is_empty())
+            .collect(),
+        _ => HashSet::new(),
+    }
+}
+
+/// The signatures a scan applies: bundled, overlaid with fetched, minus any
+/// the user disabled.
+pub fn load_cloud_signatures() -> Vec<CloudSignature> {
+    merge_signatures(
+        bundled_signatures(),
+        load_fetched_signatures(),
+        &disabled_signatures(),
+    )
 }
 
 /// Get the last_updated timestamp from the sync metadata, for delta sync.
@@ -109,26 +281,23 @@ fn parse_severity(s: &str) -> Severity {
     }
 }
 
-fn phase_weight(phase: Phase) -> u32 {
-    match phase {
-        Phase::InstallHooks => 10,
-        Phase::CodePatterns => 5,
-        Phase::NetworkExfil => 3,
-        Phase::Credentials => 2,
-        Phase::Obfuscation => 5,
-        Phase::Provenance => 1,
-        Phase::PromptInjection => 10,
-        Phase::SkillSecurity => 5,
-        Phase::InferenceSecurity => 5,
-    }
-}
-
 /// Scan a file's contents against all loaded cloud signatures.
 /// Returns findings for any matches.
 pub fn scan_with_cloud_signatures(
     file: &str,
     contents: &str,
     signatures: &[CloudSignature],
+) -> Vec<Finding> {
+    scan_with_signatures(file, contents, signatures, "cloud")
+}
+
+/// Match `signatures` line by line, tagging each snippet with `[source]`
+/// (`cloud` for fetched signatures, `custom` for local user rules).
+pub fn scan_with_signatures(
+    file: &str,
+    contents: &str,
+    signatures: &[CloudSignature],
+    source: &str,
 ) -> Vec<Finding> {
     let mut findings = Vec::new();
 
@@ -140,7 +309,6 @@ pub fn scan_with_cloud_signatures(
 
         let phase = parse_phase(&sig.phase);
         let severity = parse_severity(&sig.severity);
-        let weight = phase_weight(phase);
 
         for (line_num, line) in contents.lines().enumerate() {
             if re.is_match(line) {
@@ -149,20 +317,140 @@ pub fn scan_with_cloud_signatures(
                 } else {
                     line.to_string()
                 };
-                findings.push(Finding {
+                findings.push(make_finding(
                     phase,
-                    rule: sig.id.clone(),
+                    &sig.id,
                     severity,
-                    file: file.to_string(),
-                    line: Some(line_num + 1),
-                    snippet: format!("[cloud] {}: {}", sig.description.as_str(), snippet.trim()),
-                    weight,
-                    kev: false,
-                    epss: 0.0,
-                });
+                    file,
+                    Some(line_num + 1),
+                    format!(
+                        "[{}] {}: {}",
+                        source,
+                        sig.description.as_str(),
+                        snippet.trim()
+                    ),
+                ));
             }
         }
     }
 
     findings
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{run_scan, Severity};
+
+    fn sig(id: &str, severity: &str) -> CloudSignature {
+        CloudSignature {
+            id: id.to_string(),
+            pattern: "x".to_string(),
+            phase: "code_patterns".to_string(),
+            severity: severity.to_string(),
+            description: String::new(),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn bundled_signatures_are_valid() {
+        let bundled = bundled_signatures();
+        assert!(!bundled.is_empty());
+        for sig in &bundled {
+            assert!(Regex::new(&sig.pattern).is_ok(), "{}: bad pattern", sig.id);
+            assert!(
+                crate::corpus::engine::parse_phase(&sig.phase).is_some(),
+                "{}: unknown phase",
+                sig.id
+            );
+        }
+    }
+
+    #[test]
+    fn bundled_signatures_fire_without_fetch() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("notify.py"),
+            "URL = 'https://discord.com/api/webhooks/123456789012345678/abcDEF_123'\n",
+        )
+        .unwrap();
+
+        let result = run_scan(dir.path(), None, None);
+        let hit = result
+            .findings
+            .iter()
+            .find(|f| f.rule == "sig-net-003")
+            .expect("bundled Discord webhook signature should fire");
+        assert_eq!(hit.severity, Severity::High);
+        assert!(hit.snippet.starts_with("[cloud] "));
+    }
+
+    #[test]
+    fn fetch_delta_counts_new_and_updated_ids() {
+        let existing = vec![sig("sig-a", "high"), sig("sig-b", "low")];
+        let mut changed = sig("sig-b", "critical");
+        changed.updated_at = Some("2026-07-01".to_string());
+        let fetched = vec![sig("sig-a", "high"), changed, sig("sig-c", "medium")];
+
+        let (merged, delta) = apply_fetched(existing, &fetched);
+        assert_eq!(delta.new, vec!["sig-c"]);
+        assert_eq!(delta.updated, vec!["sig-b"]);
+        assert_eq!(delta.total, 3);
+        assert_eq!(merged[1].severity, "critical");
+    }
+
+    #[test]
+    fn only_bundles_signed_by_the_pinned_key_verify() {
+        use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let public = key.verifying_key().to_bytes();
+        let mut doc = serde_json::json!({
+            "signatures": [sig("sig-a", "high")],
+            "total": 1,
+            "last_updated": "2026-07-01",
+        });
+        let sig = key.sign(serde_json::to_string(&doc).unwrap().as_bytes());
+        doc["signature"] = BASE64.encode(sig.to_bytes()).into();
+        let body = doc.to_string();
+        assert!(verify_bundle(&body, &public).is_ok());
+
+        // A rule swapped in transit, an unsigned response, and the wrong key.
+        let tampered = body.replace("\"high\"", "\"low\"");
+        assert!(verify_bundle(&tampered, &public).is_err());
+        doc.as_object_mut().unwrap().remove("signature");
+        let err = verify_bundle(&doc.to_string(), &public).unwrap_err();
+        assert!(err.contains("not signed"), "{err}");
+        assert!(verify_bundle(&body, &SIGNATURES_PUBLIC_KEY).is_err());
+    }
+
+    #[test]
+    fn corrupt_signature_file_falls_back_to_known_good() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signatures.json");
+        let good = dir.path().join("signatures.good.json");
+        std::fs::write(&good, r#"{"signatures": [{"id": "sig-a", "pattern": "x", "phase": "code_patterns", "severity": "high"}]}"#).unwrap();
+
+        assert!(read_signatures_file(&path, &good).is_empty());
+        std::fs::write(&path, "{\"signatures\": [").unwrap();
+        let loaded = read_signatures_file(&path, &good);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, "sig-a");
+    }
+
+    #[test]
+    fn fetched_signatures_override_and_disabled_ones_drop() {
+        let bundled = vec![sig("sig-a", "low"), sig("sig-b", "low")];
+        let fetched = vec![sig("sig-a", "critical"), sig("sig-c", "high")];
+        let disabled: HashSet<String> = ["sig-b".to_string()].into_iter().collect();
+
+        let merged = merge_signatures(bundled, fetched, &disabled);
+        let ids: Vec<(&str, &str)> = merged
+            .iter()
+            .map(|s| (s.id.as_str(), s.severity.as_str()))
+            .collect();
+        assert_eq!(ids, vec![("sig-a", "critical"), ("sig-c", "high")]);
+    }
+}
@@ -14,6 +14,59 @@ pub fn is_declaration_file(rel_path: &str) -> bool {
         || lower.ends_with(".pyi")
 }
 
+/// A path with a dot-named file or directory in it (`.env`,
+/// `.config/tool.js`).  `rel_path` is relative to the scan root.
+pub fn is_hidden_path(rel_path: &str) -> bool {
+    rel_path
+        .split('/')
+        .any(|part| part.starts_with('.') && part != "." && part != "..")
+}
+
+/// CI pipeline definitions at the scan root.  They run code on every push, so they are
+/// content-scanned even when `--skip-hidden` leaves other dotfiles out.
+pub fn is_ci_config(rel_path: &str) -> bool {
+    let lower = rel_path.to_lowercase();
+    let workflow = lower
+        .strip_prefix(".github/workflows/")
+        .is_some_and(|name| {
+            !name.contains('/') && (name.ends_with(".yml") || name.ends_with(".yaml"))
+        });
+    workflow || lower == ".gitlab-ci.yml" || lower == "azure-pipelines.yml"
+}
+
+/// `Dockerfile`, `Containerfile`, and their variants (`Dockerfile.dev`,
+/// `api.dockerfile`), anywhere in the tree.
+pub fn is_dockerfile(rel_path: &str) -> bool {
+    let name = rel_path
+        .rsplit('/')
+        .next()
+        .unwrap_or(rel_path)
+        .to_lowercase();
+    ["dockerfile", "containerfile"]
+        .iter()
+        .any(|base| name == *base || name.starts_with(&format!("{}.", base)))
+        || name.ends_with(".dockerfile")
+}
+
+/// Files that grant a tool its permissions: browser-extension
+/// `manifest.json` and MCP server configs (`mcp.json`, `.mcp.json`,
+/// `claude_desktop_config.json`), anywhere in the tree.
+pub fn is_permission_manifest(rel_path: &str) -> bool {
+    let name = rel_path
+        .rsplit('/')
+        .next()
+        .unwrap_or(rel_path)
+        .to_lowercase();
+    [
+        "manifest.json",
+        "mcp.json",
+        "mcp_config.json",
+        "claude_desktop_config.json",
+    ]
+    .contains(&name.as_str())
+        || name.ends_with(".mcp.json")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -28,4 +81,40 @@ mod tests {
         // A directory named like a decl file must not suppress its children.
         assert!(!is_declaration_file("evil.d.ts/payload.js"));
     }
+
+    #[test]
+    fn hidden_paths_and_ci_configs() {
+        assert!(is_hidden_path(".env"));
+        assert!(is_hidden_path("src/.cache/loader.js"));
+        assert!(!is_hidden_path("src/index.js"));
+        assert!(!is_hidden_path("./src/index.js"));
+
+        assert!(is_ci_config(".github/workflows/release.yml"));
+        assert!(is_ci_config(".github/workflows/ci.YAML"));
+        assert!(is_ci_config(".gitlab-ci.yml"));
+        assert!(is_ci_config("azure-pipelines.yml"));
+        assert!(!is_ci_config(".github/dependabot.yml"));
+        assert!(!is_ci_config("docs/.github/workflows/ci.yml"));
+    }
+
+    #[test]
+    fn dockerfile_names() {
+        assert!(is_dockerfile("Dockerfile"));
+        assert!(is_dockerfile("services/api/Dockerfile.prod"));
+        assert!(is_dockerfile("build/worker.dockerfile"));
+        assert!(is_dockerfile("Containerfile"));
+        assert!(!is_dockerfile("docs/dockerfiles.md"));
+        assert!(!is_dockerfile("Dockerfile-notes/readme.txt"));
+    }
+
+    #[test]
+    fn permission_manifest_names() {
+        assert!(is_permission_manifest("extension/manifest.json"));
+        assert!(is_permission_manifest(".mcp.json"));
+        assert!(is_permission_manifest(".vscode/mcp.json"));
+        assert!(is_permission_manifest("tools/github.mcp.json"));
+        assert!(is_permission_manifest("claude_desktop_config.json"));
+        assert!(!is_permission_manifest("package.json"));
+        assert!(!is_permission_manifest("docs/manifest.json.md"));
+    }
 }
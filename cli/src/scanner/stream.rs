@@ -0,0 +1,260 @@
+//! Content scanning for files too large to read into memory.
+//!
+//! A file over [`STREAM_THRESHOLD_BYTES`] is read a chunk of lines at a
+//! time, and each chunk is matched and dropped before the next is read, so
+//! memory stays bounded whatever the file's size.  Only checks that look at
+//! one line at a time can run this way: the line-by-line pack rules,
+//! fetched signatures, and user rules.  Multiline rules and the whole-file
+//! checks (CI/Dockerfile/manifest parsing, invisible-Unicode inspection)
+//! are skipped, and the file is listed in `ScanResult::streamed_files`.
+//! A line longer than [`MAX_LINE_BYTES`] is matched in pieces, so even a
+//! file without a single newline is never held whole.
+
+use std::io::{self, BufRead, Read};
+use std::time::Instant;
+
+use sha2::{Digest, Sha256};
+
+use super::{ContentScan, Finding};
+
+/// Files larger than this are streamed rather than read whole.
+pub const STREAM_THRESHOLD_BYTES: u64 = 10_000_000;
+
+/// Text gathered before a chunk is matched.
+const CHUNK_BYTES: usize = 1 << 20;
+
+/// Longest piece of a line matched as one; the rest of the line follows as
+/// further pieces with the same line number.
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// What streaming one file found.
+#[derive(Debug, Default)]
+pub(super) struct Streamed {
+    pub findings: Vec<Finding>,
+    /// SHA-256 of the whole file; empty when `binary`, `over_budget`, or
+    /// `cut_off` stopped the read early.
+    pub sha256: String,
+    /// The file holds a NUL byte and was not scanned further.
+    pub binary: bool,
+    /// `budget` passed before the end of the file.
+    pub over_budget: bool,
+    /// The scan's own deadline passed before the end of the file.
+    pub cut_off: bool,
+    /// Largest chunk buffer held at once, in bytes: the memory bound.
+    pub peak_chunk_bytes: usize,
+}
+
+/// Lines of the current chunk with the file line each came from.
+#[derive(Default)]
+struct Chunk {
+    text: String,
+    lines: Vec<usize>,
+}
+
+/// Scan `reader`, the contents of `rel_path`, a chunk at a time.  Stops at
+/// the per-file `budget` (`over_budget`), or once the scan's own deadline
+/// has passed (`cut_off`), keeping what was found so far.
+pub(super) fn scan_reader(
+    mut reader: impl BufRead,
+    rel_path: &str,
+    scan: &ContentScan,
+    budget: Option<Instant>,
+) -> io::Result<Streamed> {
+    let mut streamed = Streamed::default();
+    let mut hasher = Sha256::new();
+    let mut header = String::new();
+    let mut chunk = Chunk::default();
+    let mut piece = Vec::new();
+    let mut line = 1;
+
+    loop {
+        piece.clear();
+        let read = reader
+            .by_ref()
+            .take(MAX_LINE_BYTES as u64)
+            .read_until(b'\n', &mut piece)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&piece);
+        if piece.contains(&0) {
+            streamed.binary = true;
+            return Ok(streamed);
+        }
+        let text = String::from_utf8_lossy(&piece);
+        chunk.text.push_str(text.trim_end_matches(['\n', '\r']));
+        chunk.text.push('\n');
+        chunk.lines.push(line);
+        if piece.ends_with(b"\n") {
+            line += 1;
+        }
+
+        if chunk.text.len() >= CHUNK_BYTES {
+            scan_chunk(&mut chunk, &mut header, rel_path, scan, &mut streamed);
+            if budget.is_some_and(|at| Instant::now() >= at) {
+                streamed.over_budget = true;
+                return Ok(streamed);
+            }
+            if scan.expired() {
+                streamed.cut_off = true;
+                return Ok(streamed);
+            }
+        }
+    }
+    scan_chunk(&mut chunk, &mut header, rel_path, scan, &mut streamed);
+    streamed.sha256 = hex::encode(hasher.finalize());
+    Ok(streamed)
+}
+
+/// Match the gathered lines and empty `chunk` for the next ones.
+fn scan_chunk(
+    chunk: &mut Chunk,
+    header: &mut String,
+    rel_path: &str,
+    scan: &ContentScan,
+    streamed: &mut Streamed,
+) {
+    if chunk.text.is_empty() {
+        return;
+    }
+    if header.is_empty() {
+        *header = chunk.text.chars().take(1024).collect();
+    }
+    streamed.peak_chunk_bytes = streamed.peak_chunk_bytes.max(chunk.text.capacity());
+    for mut finding in scan.scan_lines(rel_path, &chunk.text, header) {
+        // Line numbers count from the start of the chunk.
+        finding.line = finding
+            .line
+            .and_then(|n| chunk.lines.get(n.wrapping_sub(1)).copied());
+        streamed.findings.push(finding);
+    }
+    chunk.text.clear();
+    chunk.lines.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::Phase;
+
+    /// `size` bytes of filler lines, generated as they are read, with
+    /// `needle` on line `needle_line`.
+    struct Synthetic {
+        size: u64,
+        produced: u64,
+        line: usize,
+        needle_line: usize,
+        needle: &'static [u8],
+        pending: Vec<u8>,
+    }
+
+    impl Read for Synthetic {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.pending.is_empty() {
+                if self.produced >= self.size {
+                    return Ok(0);
+                }
+                self.line += 1;
+                self.pending = if self.line == self.needle_line {
+                    self.needle.to_vec()
+                } else {
+                    b"2024-01-01T00:00:00Z INFO request served in 12ms status=200\n".to_vec()
+                };
+            }
+            let n = buf.len().min(self.pending.len());
+            buf[..n].copy_from_slice(&self.pending[..n]);
+            self.pending.drain(..n);
+            self.produced += n as u64;
+            Ok(n)
+        }
+    }
+
+    /// Code patterns only: every phase over megabytes of text is slow in
+    /// an unoptimised test build.
+    fn content_scan() -> ContentScan {
+        ContentScan {
+            active_phases: Some(vec![Phase::CodePatterns]),
+            deadline: None,
+            cloud_sigs: Vec::new(),
+            user_rules: Vec::new(),
+            timings: None,
+            cut_short: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    #[test]
+    fn large_input_is_scanned_in_bounded_chunks() {
+        // The input never exists in memory at once: the reader makes it up
+        // as it goes, and only one chunk of lines is held at a time.
+        let input = Synthetic {
+            size: 6 * CHUNK_BYTES as u64,
+            produced: 0,
+            line: 0,
+            needle_line: 80_000,
+            needle: b"payload = eval(base64_decode(blob))\n",
+            pending: Vec::new(),
+        };
+        let scan = content_scan();
+        let streamed = scan_reader(io::BufReader::new(input), "server.log", &scan, None).unwrap();
+        assert!(!streamed.binary && !streamed.over_budget);
+        assert!(
+            streamed.peak_chunk_bytes < 2 * CHUNK_BYTES + MAX_LINE_BYTES,
+            "held {} bytes",
+            streamed.peak_chunk_bytes
+        );
+        let eval = streamed
+            .findings
+            .iter()
+            .find(|f| f.phase == Phase::CodePatterns)
+            .expect("eval finding");
+        assert_eq!(eval.line, Some(80_000));
+        assert!(eval.region.is_none());
+    }
+
+    #[test]
+    fn a_file_without_newlines_is_matched_in_pieces() {
+        let mut text = "x".repeat(3 * MAX_LINE_BYTES);
+        text.push_str(" eval(payload) ");
+        text.push_str(&"y".repeat(MAX_LINE_BYTES));
+        let scan = content_scan();
+        let streamed = scan_reader(text.as_bytes(), "blob.js", &scan, None).unwrap();
+        let lines: Vec<Option<usize>> = streamed
+            .findings
+            .iter()
+            .filter(|f| f.phase == Phase::CodePatterns)
+            .map(|f| f.line)
+            .collect();
+        assert_eq!(lines, vec![Some(1)]);
+        assert_eq!(
+            streamed.sha256,
+            hex::encode(Sha256::digest(text.as_bytes()))
+        );
+
+        let binary = scan_reader(&b"MZ\0\0eval(x)\n"[..], "a.exe", &scan, None);
+        assert!(binary.unwrap().binary);
+    }
+
+    #[test]
+    fn deadline_passing_mid_file_cuts_it_off_without_a_hash() {
+        let input = Synthetic {
+            size: 3 * CHUNK_BYTES as u64,
+            produced: 0,
+            line: 0,
+            needle_line: 1,
+            needle: b"payload = eval(blob)\n",
+            pending: Vec::new(),
+        };
+        let scan = ContentScan {
+            deadline: Some(Instant::now()),
+            ..content_scan()
+        };
+        let streamed = scan_reader(io::BufReader::new(input), "server.log", &scan, None).unwrap();
+        assert!(streamed.cut_off && !streamed.over_budget);
+        assert!(streamed.sha256.is_empty());
+        // What the first chunk held is still reported.
+        assert!(streamed
+            .findings
+            .iter()
+            .any(|f| f.phase == Phase::CodePatterns && f.line == Some(1)));
+    }
+}
@@ -0,0 +1,139 @@
+//! Symbolic links shipped in a scanned tree.
+//!
+//! The file walk never follows links, so a link's target is not scanned.
+//! A link that leads out of the tree (`config -> /etc/passwd`,
+//! `key -> ../../.ssh/id_rsa`) is still a red flag: a package has no reason
+//! to point at files on the host, and any tool that does follow links will
+//! read or overwrite them.  Such a link is `PROV-SYMLINK-001` at High.  A
+//! dangling link, or one written as an absolute path that stays inside the
+//! tree, is the same rule at Medium, since what it points at depends on the
+//! machine it lands on.  Relative links within the tree are not reported.
+
+use std::path::{Component, Path, PathBuf};
+
+use super::phases::make_finding;
+use super::{Finding, Phase, Severity};
+
+/// `path` made absolute, with `.` and `..` resolved without touching the
+/// filesystem.
+fn lexical(path: &Path) -> PathBuf {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Whether `link` resolves to somewhere outside `base`.  A link whose
+/// target exists is judged by where it really ends up, through any chain of
+/// links; a dangling one by its target path alone.
+fn escapes(base: &Path, link: &Path, target: &Path) -> bool {
+    if let (Ok(root), Ok(resolved)) = (std::fs::canonicalize(base), std::fs::canonicalize(link)) {
+        return !resolved.starts_with(root);
+    }
+    let parent = link.parent().unwrap_or(Path::new("."));
+    !lexical(&parent.join(target)).starts_with(lexical(base))
+}
+
+/// Report the suspicious links among `links`, found under the scan root
+/// `base`.
+pub fn scan_symlinks(base: &Path, links: &[PathBuf]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for link in links {
+        let Ok(target) = std::fs::read_link(link) else {
+            continue;
+        };
+        let rel = super::relative_path(link, base);
+        let shown = target.display();
+        let dangling = std::fs::metadata(link).is_err();
+        if escapes(base, link, &target) {
+            findings.push(make_finding(
+                Phase::Provenance,
+                "PROV-SYMLINK-001",
+                Severity::High,
+                &rel,
+                None,
+                format!(
+                    "Symlink points outside the scanned tree: {} -> {}",
+                    rel, shown
+                ),
+            ));
+        } else if dangling {
+            findings.push(make_finding(
+                Phase::Provenance,
+                "PROV-SYMLINK-001",
+                Severity::Medium,
+                &rel,
+                None,
+                format!("Dangling symlink: {} -> {}", rel, shown),
+            ));
+        } else if target.is_absolute() {
+            findings.push(make_finding(
+                Phase::Provenance,
+                "PROV-SYMLINK-001",
+                Severity::Medium,
+                &rel,
+                None,
+                format!("Symlink to an absolute path: {} -> {}", rel, shown),
+            ));
+        }
+    }
+    findings
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    fn scan(base: &Path) -> Vec<(String, Severity)> {
+        scan_symlinks(base, &crate::scanner::collect_symlinks(base))
+            .into_iter()
+            .map(|f| (f.file, f.severity))
+            .collect()
+    }
+
+    #[test]
+    fn links_within_the_tree_are_not_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::write(root.join("lib/real.js"), "module.exports = 1;\n").unwrap();
+        symlink("lib/real.js", root.join("index.js")).unwrap();
+        symlink("../lib", root.join("lib/self")).unwrap();
+        assert!(scan(root).is_empty());
+    }
+
+    #[test]
+    fn escaping_dangling_and_absolute_links_are_reported() {
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("id_rsa"), "secret\n").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("real.txt"), "ok\n").unwrap();
+
+        symlink(outside.path().join("id_rsa"), root.join("key")).unwrap();
+        symlink("../../../../../../../../etc/passwd", root.join("passwd")).unwrap();
+        symlink("missing.txt", root.join("gone")).unwrap();
+        symlink(root.join("real.txt"), root.join("abs")).unwrap();
+
+        let mut found = scan(root);
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                ("abs".to_string(), Severity::Medium),
+                ("gone".to_string(), Severity::Medium),
+                ("key".to_string(), Severity::High),
+                ("passwd".to_string(), Severity::High),
+            ]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Where a scan spends its time, for `--verbose`.
+//!
+//! Each stage accumulates the time spent in it across every file and worker
+//! thread, so the totals add up to more than the wall-clock duration on a
+//! parallel scan; they show which stage dominates, not how long the user
+//! waited.  Timing is off unless `ScanOptions::timings` asks for it, and then
+//! costs two clock reads per stage per file.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use super::Phase;
+
+/// A timed part of the scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Reading and hashing file contents.
+    FileIo,
+    /// Invisible-Unicode inspection and normalisation before matching.
+    Normalize,
+    /// One scan phase.
+    Phase(Phase),
+    /// Cloud signatures and user rules.
+    Signatures,
+}
+
+const PHASES: [Phase; 9] = [
+    Phase::InstallHooks,
+    Phase::CodePatterns,
+    Phase::NetworkExfil,
+    Phase::Credentials,
+    Phase::Obfuscation,
+    Phase::Provenance,
+    Phase::PromptInjection,
+    Phase::SkillSecurity,
+    Phase::InferenceSecurity,
+];
+const STAGES: usize = PHASES.len() + 3;
+
+impl Stage {
+    fn index(self) -> usize {
+        match self {
+            Stage::FileIo => 0,
+            Stage::Normalize => 1,
+            Stage::Signatures => 2,
+            Stage::Phase(phase) => 3 + PHASES.iter().position(|&p| p == phase).unwrap_or(0),
+        }
+    }
+
+    fn from_index(index: usize) -> Stage {
+        match index {
+            0 => Stage::FileIo,
+            1 => Stage::Normalize,
+            2 => Stage::Signatures,
+            _ => Stage::Phase(PHASES[index - 3]),
+        }
+    }
+
+    /// Key in `ScanResult::phase_timings`; phases use their serialised name,
+    /// as in `phase_scores`.
+    pub fn key(self) -> String {
+        match self {
+            Stage::FileIo => "FileIo".to_string(),
+            Stage::Normalize => "Normalize".to_string(),
+            Stage::Signatures => "Signatures".to_string(),
+            Stage::Phase(phase) => format!("{:?}", phase),
+        }
+    }
+}
+
+/// Cumulative time per stage, shared by the scan's worker threads.
+#[derive(Debug, Default)]
+pub struct Timings {
+    nanos: [AtomicU64; STAGES],
+}
+
+impl Timings {
+    pub fn add(&self, stage: Stage, elapsed: Duration) {
+        self.nanos[stage.index()].fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Milliseconds per stage that took any time at all.
+    pub fn to_millis(&self) -> BTreeMap<String, f64> {
+        self.nanos
+            .iter()
+            .enumerate()
+            .map(|(index, nanos)| (index, nanos.load(Ordering::Relaxed)))
+            .filter(|&(_, nanos)| nanos > 0)
+            .map(|(index, nanos)| {
+                let millis = (nanos as f64 / 1_000.0).round() / 1_000.0;
+                (Stage::from_index(index).key(), millis)
+            })
+            .collect()
+    }
+}
+
+/// Run `f`, charging its time to `stage` when `timings` is on.
+pub fn timed<T>(timings: Option<&Timings>, stage: Stage, f: impl FnOnce() -> T) -> T {
+    match timings {
+        None => f(),
+        Some(timings) => {
+            let start = Instant::now();
+            let out = f();
+            timings.add(stage, start.elapsed());
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages_round_trip_and_only_used_ones_are_reported() {
+        for index in 0..STAGES {
+            assert_eq!(Stage::from_index(index).index(), index);
+        }
+        let timings = Timings::default();
+        timings.add(
+            Stage::Phase(Phase::Obfuscation),
+            Duration::from_micros(2_500),
+        );
+        timings.add(Stage::FileIo, Duration::from_millis(1));
+        assert_eq!(timed(None, Stage::Normalize, || 7), 7);
+
+        let millis = timings.to_millis();
+        assert_eq!(millis.len(), 2);
+        assert_eq!(millis["Obfuscation"], 2.5);
+        assert_eq!(millis["FileIo"], 1.0);
+    }
+}
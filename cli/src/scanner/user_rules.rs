@@ -0,0 +1,176 @@
+//! Local, org-specific rules from `~/.sigil/rules/*.yaml` (or `--rules-dir`).
+//!
+//! Each file holds one rule, a list of rules, or `{rules: [...]}` with the
+//! same fields as a cloud signature: `id`, `pattern`, `phase`, `severity`,
+//! `description`.  Rules are validated when loaded — a bad regex, unknown
+//! phase or severity skips that rule with a reported problem — and then run
+//! through the cloud-signature matcher.
+
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use super::cloud_sigs::CloudSignature;
+use crate::corpus::engine::parse_phase;
+
+/// Default location of user rule files.
+pub fn default_rules_dir() -> PathBuf {
+    crate::paths::in_home("rules")
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RuleFile {
+    Wrapped { rules: Vec<CloudSignature> },
+    List(Vec<CloudSignature>),
+    Single(CloudSignature),
+}
+
+/// Check one rule, returning why it cannot be used.
+fn validate(rule: &CloudSignature) -> Result<(), String> {
+    if rule.id.trim().is_empty() {
+        return Err("rule has an empty id".to_string());
+    }
+    if let Err(e) = Regex::new(&rule.pattern) {
+        return Err(format!("{}: invalid pattern: {}", rule.id, e));
+    }
+    if parse_phase(&rule.phase).is_none() {
+        return Err(format!("{}: unknown phase '{}'", rule.id, rule.phase));
+    }
+    if !matches!(
+        rule.severity.to_lowercase().as_str(),
+        "low" | "medium" | "high" | "critical"
+    ) {
+        return Err(format!(
+            "{}: unknown severity '{}' (expected low, medium, high, or critical)",
+            rule.id, rule.severity
+        ));
+    }
+    Ok(())
+}
+
+/// Load every `*.yaml`, `*.yml`, and `*.json` rule file in `dir`, in name
+/// order.  Returns the valid rules plus one message per file or rule that
+/// was skipped.  A missing directory simply yields no rules.
+pub fn load_user_rules(dir: &Path) -> (Vec<CloudSignature>, Vec<String>) {
+    let mut rules = Vec::new();
+    let mut problems = Vec::new();
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (rules, problems);
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && matches!(
+                    p.extension().and_then(|e| e.to_str()),
+                    Some("yaml" | "yml" | "json")
+                )
+        })
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let name = path.display();
+        let raw = match std::fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(e) => {
+                problems.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+        // YAML is a superset of JSON, so one parser covers both extensions.
+        let parsed = match serde_yaml::from_str::<RuleFile>(&raw) {
+            Ok(RuleFile::Wrapped { rules } | RuleFile::List(rules)) => rules,
+            Ok(RuleFile::Single(rule)) => vec![rule],
+            Err(e) => {
+                problems.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+        for rule in parsed {
+            match validate(&rule) {
+                Ok(()) => rules.push(rule),
+                Err(e) => problems.push(format!("{}: {}", name, e)),
+            }
+        }
+    }
+    (rules, problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{run_scan_with_options, ScanOptions, Severity};
+    use std::fs;
+
+    #[test]
+    fn custom_yaml_rule_fires() {
+        let rules = tempfile::tempdir().unwrap();
+        fs::write(
+            rules.path().join("acme.yaml"),
+            "- id: ACME-001\n  pattern: 'internal\\.acme\\.corp'\n  phase: network_exfil\n  severity: high\n  description: Calls the internal ACME host\n",
+        )
+        .unwrap();
+        let target = tempfile::tempdir().unwrap();
+        fs::write(
+            target.path().join("client.py"),
+            "import requests\nrequests.get('https://internal.acme.corp/api')\n",
+        )
+        .unwrap();
+
+        let options = ScanOptions {
+            rules_dir: Some(rules.path().to_path_buf()),
+            ..ScanOptions::default()
+        };
+        let result = run_scan_with_options(target.path(), None, None, &options);
+        let hit = result
+            .findings
+            .iter()
+            .find(|f| f.rule == "ACME-001")
+            .expect("custom rule should fire");
+        assert_eq!(hit.severity, Severity::High);
+        assert_eq!(hit.line, Some(2));
+        assert!(hit.snippet.starts_with("[custom] "));
+    }
+
+    #[test]
+    fn invalid_rules_are_reported_and_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("rules.yml"),
+            "rules:\n  - {id: OK-1, pattern: 'ok', phase: credentials, severity: low, description: fine}\n  - {id: BAD-1, pattern: '(unclosed', phase: credentials, severity: low}\n  - {id: BAD-2, pattern: 'x', phase: nowhere, severity: low}\n  - {id: BAD-3, pattern: 'x', phase: credentials, severity: severe}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("single.json"),
+            r#"{"id":"OK-2","pattern":"y","phase":"obfuscation","severity":"critical"}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("broken.yaml"), "id: [").unwrap();
+        fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let (rules, problems) = load_user_rules(dir.path());
+        let ids: Vec<&str> = rules.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["OK-1", "OK-2"]);
+        assert_eq!(problems.len(), 4, "{problems:?}");
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("BAD-1: invalid pattern")));
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("unknown phase 'nowhere'")));
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("unknown severity 'severe'")));
+        assert!(problems.iter().any(|p| p.contains("broken.yaml")));
+    }
+
+    #[test]
+    fn missing_directory_yields_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let (rules, problems) = load_user_rules(&dir.path().join("absent"));
+        assert!(rules.is_empty() && problems.is_empty());
+    }
+}
@@ -0,0 +1,285 @@
+//! Findings a reviewer has accepted for a scan target.
+//!
+//! They are kept under `~/.sigil/suppressions/`, one file per target keyed
+//! by its canonical path, and never read from the scanned tree: a package
+//! under review must not be able to silence its own findings.  Each line
+//! names one accepted finding as `RULE-ID path[:line]`; `#` starts a
+//! comment.  Without a line number the entry covers every hit of the rule
+//! in that file.  `sigil scan --interactive` appends to it, and every scan
+//! of the target drops the findings it lists.
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::diff::normalize_path;
+use crate::logging::log_warn;
+use crate::scanner::{scoring, Finding, ScanResult};
+
+/// Directory under the Sigil home holding one suppressions file per target.
+const STORE_DIR: &str = "suppressions";
+
+/// The in-tree file earlier versions read.  A target that ships one gets a
+/// warning that it is ignored.
+pub const LEGACY_FILE_NAME: &str = ".sigil-suppressions";
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+const HEADER: &str = "\
+# Findings accepted for this target; `sigil scan` does not report them.
+# One per line: RULE-ID path[:line]
+";
+
+/// One accepted finding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    pub rule: String,
+    pub file: String,
+    pub line: Option<usize>,
+}
+
+impl Suppression {
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    fn for_finding(finding: &Finding) -> Self {
+        Suppression {
+            rule: finding.rule.clone(),
+            file: normalize_path(&finding.file),
+            line: finding.line,
+        }
+    }
+
+    fn covers(&self, finding: &Finding) -> bool {
+        self.rule == finding.rule
+            && self.line.is_none_or(|line| finding.line == Some(line))
+            && self.file == normalize_path(&finding.file)
+    }
+
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    fn to_line(&self) -> String {
+        match self.line {
+            Some(line) => format!("{} {}:{}", self.rule, self.file, line),
+            None => format!("{} {}", self.rule, self.file),
+        }
+    }
+}
+
+/// Where the suppressions for a scan of `root` live.
+pub fn file_for(root: &Path) -> PathBuf {
+    file_in(&crate::paths::in_home(STORE_DIR), root)
+}
+
+/// The suppressions file for `root` in `store`, named by a hash of its
+/// canonical path so the same target is found from any working directory.
+fn file_in(store: &Path, root: &Path) -> PathBuf {
+    let target = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let key = hex::encode(Sha256::digest(target.to_string_lossy().as_bytes()));
+    store.join(format!("{}.txt", &key[..16]))
+}
+
+/// Parse the body of a suppressions file.  Malformed lines are skipped and
+/// described, with their line number, in the second list.
+pub fn parse(contents: &str) -> (Vec<Suppression>, Vec<String>) {
+    let mut entries = Vec::new();
+    let mut problems = Vec::new();
+    for (idx, raw) in contents.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let (Some(rule), Some(target), None) = (parts.next(), parts.next(), parts.next()) else {
+            problems.push(format!(
+                "line {}: expected `RULE-ID path[:line]`, got '{}'",
+                idx + 1,
+                line
+            ));
+            continue;
+        };
+        let (file, line) = match target.rsplit_once(':') {
+            Some((file, n)) if !file.is_empty() => match n.parse::<usize>() {
+                Ok(n) => (file, Some(n)),
+                Err(_) => (target, None),
+            },
+            _ => (target, None),
+        };
+        entries.push(Suppression {
+            rule: rule.to_string(),
+            file: normalize_path(file),
+            line,
+        });
+    }
+    (entries, problems)
+}
+
+/// The suppressions for a scan of `root`; none when there are none stored.
+/// An unreadable file or a malformed line is warned about, never fatal.
+pub fn load(root: &Path) -> Vec<Suppression> {
+    load_from(&file_for(root))
+}
+
+fn load_from(path: &Path) -> Vec<Suppression> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            log_warn!("failed to read {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+    let (entries, problems) = parse(&contents);
+    for problem in problems {
+        log_warn!("{}: {}; line ignored", path.display(), problem);
+    }
+    entries
+}
+
+/// Drop the findings `entries` cover from `result` and rescore it.  Returns
+/// the number dropped.
+pub fn apply(result: &mut ScanResult, entries: &[Suppression]) -> usize {
+    if entries.is_empty() {
+        return 0;
+    }
+    let before = result.findings.len();
+    result
+        .findings
+        .retain(|f| !entries.iter().any(|entry| entry.covers(f)));
+    let dropped = before - result.findings.len();
+    if dropped > 0 {
+        scoring::rescore(result);
+    }
+    dropped
+}
+
+/// Record `accepted` in the suppressions file for `root`, creating it if
+/// needed.  Findings it already covers are not added twice.  Returns the
+/// number of lines written.
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+pub fn append(root: &Path, accepted: &[Finding]) -> Result<usize, String> {
+    append_to(&file_for(root), root, accepted)
+}
+
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+fn append_to(path: &Path, root: &Path, accepted: &[Finding]) -> Result<usize, String> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("failed to read {}: {}", path.display(), e)),
+    };
+    let (mut known, _) = parse(&existing);
+    let mut out = if existing.is_empty() {
+        let target = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        format!("{}# Target: {}\n", HEADER, target.display())
+    } else {
+        existing.clone()
+    };
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    let mut added = 0;
+    for finding in accepted {
+        if known.iter().any(|entry| entry.covers(finding)) {
+            continue;
+        }
+        let entry = Suppression::for_finding(finding);
+        out.push_str(&entry.to_line());
+        out.push('\n');
+        known.push(entry);
+        added += 1;
+    }
+    if added > 0 {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(path, out)
+            .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Phase, Severity, Verdict};
+
+    fn finding(rule: &str, file: &str, line: usize) -> Finding {
+        Finding {
+            phase: Phase::NetworkExfil,
+            rule: rule.to_string(),
+            severity: Severity::Medium,
+            file: file.to_string(),
+            line: Some(line),
+            snippet: String::new(),
+            weight: 3,
+            confidence: 100,
+            kev: false,
+            epss: 0.0,
+            sha256: None,
+            region: None,
+        }
+    }
+
+    #[test]
+    fn entries_match_by_rule_file_and_optional_line() {
+        let (entries, problems) = parse(
+            "# accepted\n\nNET-001 src/client.py:12  # retry helper\nCODE-001 ./tools/build.py\n",
+        );
+        assert!(problems.is_empty(), "{problems:?}");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].file, "tools/build.py");
+
+        let mut result = ScanResult {
+            findings: vec![
+                finding("NET-001", "src/client.py", 12),
+                finding("NET-001", "src/client.py", 40),
+                finding("CODE-001", "tools/build.py", 3),
+                finding("CODE-001", "tools/build.py", 9),
+            ],
+            score: 24,
+            verdict: Verdict::MediumRisk,
+            files_scanned: 2,
+            ..Default::default()
+        };
+        assert_eq!(apply(&mut result, &entries), 3);
+        assert_eq!(result.findings.len(), 1);
+        assert_eq!(result.findings[0].line, Some(40));
+        assert_eq!(result.score, 6);
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped_and_reported() {
+        let (entries, problems) = parse("garbage\nNET-001 a.py extra\nNET-002 b.py:4\n");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].rule, "NET-002");
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].starts_with("line 1"), "{problems:?}");
+    }
+
+    #[test]
+    fn append_writes_new_entries_once() {
+        let store = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        let path = file_in(store.path(), target.path());
+        let accepted = vec![finding("NET-001", "a.py", 3), finding("NET-002", "b.py", 7)];
+        assert_eq!(append_to(&path, target.path(), &accepted).unwrap(), 2);
+        assert_eq!(append_to(&path, target.path(), &accepted).unwrap(), 0);
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.starts_with("# Findings accepted"));
+        assert!(
+            written.ends_with("NET-001 a.py:3\nNET-002 b.py:7\n"),
+            "{written}"
+        );
+        assert_eq!(load_from(&path).len(), 2);
+    }
+
+    #[test]
+    fn store_is_keyed_by_target_outside_the_tree() {
+        let store = tempfile::tempdir().unwrap();
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        let path = file_in(store.path(), a.path());
+        assert!(path.starts_with(store.path()));
+        assert_eq!(path, file_in(store.path(), &a.path().join(".")));
+        assert_ne!(path, file_in(store.path(), b.path()));
+    }
+}
@@ -0,0 +1,234 @@
+//! `~/.sigil/threat_cache.json`: threat-database answers kept for a while,
+//! so scanning the same artifact again does not look up the same hashes.
+//!
+//! Every answer is kept, "not known" included, stamped with when it was
+//! fetched; an entry older than the TTL (`threat_cache.ttl_hours`, 24 unless
+//! set, 0 to turn the cache off) is looked up again.  Failed lookups are
+//! never stored.  `--no-threat-cache` skips the stored answers for one run
+//! and refreshes them with what it fetches.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::api::{SigilClient, ThreatInfo};
+use crate::error::SigilError;
+use crate::logging::log_warn;
+
+/// Config key holding the TTL in hours.
+pub const TTL_KEY: &str = "threat_cache.ttl_hours";
+/// TTL when [`TTL_KEY`] is not set.
+pub const DEFAULT_TTL_HOURS: u64 = 24;
+
+const CACHE_NAME: &str = "threat_cache.json";
+const CACHE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    info: ThreatInfo,
+    /// Unix seconds.
+    fetched_at: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheData {
+    version: u32,
+    entries: HashMap<String, Entry>,
+}
+
+/// Stored threat lookups by hash.
+#[derive(Debug)]
+pub struct ThreatCache {
+    path: PathBuf,
+    ttl_secs: i64,
+    entries: HashMap<String, Entry>,
+}
+
+/// The configured TTL in hours.
+pub fn ttl_hours() -> u64 {
+    let config = crate::config::resolve().merged();
+    match config.get(TTL_KEY) {
+        None => DEFAULT_TTL_HOURS,
+        Some(raw) => match raw
+            .as_u64()
+            .or_else(|| raw.as_str().and_then(|s| s.trim().parse().ok()))
+        {
+            Some(hours) => hours,
+            None => {
+                log_warn!("ignoring {} = {} (expected a whole number)", TTL_KEY, raw);
+                DEFAULT_TTL_HOURS
+            }
+        },
+    }
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+impl ThreatCache {
+    /// Load `~/.sigil/threat_cache.json` with the configured TTL.
+    pub fn load() -> ThreatCache {
+        let ttl = ttl_hours().saturating_mul(3600);
+        Self::load_from(
+            &crate::paths::in_home(CACHE_NAME),
+            i64::try_from(ttl).unwrap_or(i64::MAX),
+        )
+    }
+
+    /// Load from `path`.  A missing, unreadable, or other-version file
+    /// yields an empty cache.
+    fn load_from(path: &Path, ttl_secs: i64) -> ThreatCache {
+        let entries = fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str::<CacheData>(&data).ok())
+            .filter(|data| data.version == CACHE_VERSION)
+            .map(|data| data.entries)
+            .unwrap_or_default();
+        ThreatCache {
+            path: path.to_path_buf(),
+            ttl_secs,
+            entries,
+        }
+    }
+
+    /// The stored answer for `hash`, unless it was fetched more than the
+    /// TTL before `now`.
+    fn get(&self, hash: &str, now: i64) -> Option<&ThreatInfo> {
+        self.entries
+            .get(hash)
+            .filter(|entry| now.saturating_sub(entry.fetched_at) < self.ttl_secs)
+            .map(|entry| &entry.info)
+    }
+
+    fn insert(&mut self, hash: &str, info: ThreatInfo, now: i64) {
+        self.entries.insert(
+            hash.to_string(),
+            Entry {
+                info,
+                fetched_at: now,
+            },
+        );
+    }
+
+    /// Write the cache back, dropping entries past the TTL at `now`.
+    fn save(&mut self, now: i64) -> Result<(), SigilError> {
+        let ttl = self.ttl_secs;
+        self.entries
+            .retain(|_, entry| now.saturating_sub(entry.fetched_at) < ttl);
+        let data = CacheData {
+            version: CACHE_VERSION,
+            entries: self.entries.clone(),
+        };
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string(&data)?)?;
+        Ok(())
+    }
+}
+
+/// [`SigilClient::lookup_threats`] through `cache`: hashes with a stored
+/// answer inside the TTL are not sent, unless `fresh` is set.  Results come
+/// back in input order.
+pub async fn lookup_threats(
+    client: &SigilClient,
+    cache: &mut ThreatCache,
+    hashes: &[String],
+    max_in_flight: usize,
+    fresh: bool,
+) -> Vec<(String, Result<ThreatInfo, SigilError>)> {
+    let at = now();
+    let misses: Vec<String> = hashes
+        .iter()
+        .filter(|hash| fresh || cache.get(hash, at).is_none())
+        .cloned()
+        .collect();
+    let mut fetched: HashMap<String, Result<ThreatInfo, SigilError>> = client
+        .lookup_threats(&misses, max_in_flight)
+        .await
+        .into_iter()
+        .collect();
+    let stored = fetched.values().filter(|r| r.is_ok()).count();
+    for (hash, lookup) in &fetched {
+        if let Ok(info) = lookup {
+            cache.insert(hash, info.clone(), at);
+        }
+    }
+    if stored > 0 && cache.ttl_secs > 0 {
+        if let Err(err) = cache.save(at) {
+            log_warn!("threat cache not saved: {}", err);
+        }
+    }
+
+    hashes
+        .iter()
+        .map(|hash| {
+            let result = match fetched.remove(hash) {
+                Some(result) => result,
+                None => cache
+                    .get(hash, at)
+                    .cloned()
+                    .ok_or_else(|| SigilError::Network("threat lookup skipped".to_string())),
+            };
+            (hash.clone(), result)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(hash: &str, known_malicious: bool) -> ThreatInfo {
+        ThreatInfo {
+            hash: hash.to_string(),
+            known_malicious,
+            threat_type: None,
+            description: None,
+            first_seen: None,
+            references: vec![],
+        }
+    }
+
+    #[test]
+    fn entries_are_served_within_the_ttl_and_expire_after_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CACHE_NAME);
+        let mut cache = ThreatCache::load_from(&path, 3600);
+        cache.insert("aaa", info("aaa", true), 1_000);
+        cache.insert("bbb", info("bbb", false), 1_000 - 3600);
+
+        assert!(cache.get("aaa", 1_000 + 3599).unwrap().known_malicious);
+        assert!(cache.get("aaa", 1_000 + 3600).is_none());
+        assert!(cache.get("bbb", 1_000).is_none());
+
+        // Saving drops what has expired and keeps the rest across loads.
+        cache.save(1_000).unwrap();
+        let reloaded = ThreatCache::load_from(&path, 3600);
+        assert!(reloaded.get("aaa", 1_500).is_some());
+        assert!(!reloaded.entries.contains_key("bbb"));
+
+        // A TTL of 0 never serves an entry.
+        assert!(ThreatCache::load_from(&path, 0).get("aaa", 1_000).is_none());
+    }
+
+    #[tokio::test]
+    async fn cached_hashes_skip_the_network() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cache = ThreatCache::load_from(&dir.path().join(CACHE_NAME), 3600);
+        cache.insert("aaa", info("aaa", true), now());
+        // Nothing listens here, so any request that goes out fails.
+        let client = SigilClient::new(Some("http://127.0.0.1:9".to_string())).unwrap();
+        let hashes = vec!["aaa".to_string(), "bbb".to_string()];
+
+        let results = lookup_threats(&client, &mut cache, &hashes, 4, false).await;
+        assert_eq!(results[0].0, "aaa");
+        assert!(results[0].1.as_ref().unwrap().known_malicious);
+        assert!(results[1].1.is_err());
+
+        let fresh = lookup_threats(&client, &mut cache, &hashes, 4, true).await;
+        assert!(fresh.iter().all(|(_, result)| result.is_err()));
+    }
+}
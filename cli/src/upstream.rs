@@ -0,0 +1,319 @@
+//! `sigil diff --compare-registry`: a local or vendored copy of a package
+//! against the release its registry publishes.
+//!
+//! A trojanized fork looks like the real package with a few lines added, so
+//! scanning it alone says little; what matters is what it has that upstream
+//! does not.  This module works out which package a directory holds (from
+//! `package.json`, Python core metadata, `pyproject.toml`, or `setup.cfg`),
+//! lines the unpacked release up with the local tree, and lists the files
+//! that differ.  Downloading and scanning are left to the caller.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::diff::package_root;
+use crate::ledger::pin_directory;
+
+/// The package a local directory holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LocalPackage {
+    /// `pip` or `npm`.
+    pub ecosystem: String,
+    pub name: String,
+    /// `None` when the local copy does not say; the caller resolves it.
+    pub version: Option<String>,
+}
+
+impl LocalPackage {
+    /// Install spec for `version` of this package.
+    pub fn spec(&self, version: &str) -> String {
+        match self.ecosystem.as_str() {
+            "pip" => format!("{}=={}", self.name, version),
+            _ => format!("{}@{}", self.name, version),
+        }
+    }
+}
+
+/// Files that differ between the published release and the local copy,
+/// by path relative to each root.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ContentDiff {
+    /// In both, with different bytes.
+    pub modified: Vec<String>,
+    /// Only in the local copy.
+    pub added: Vec<String>,
+    /// Only in the release.
+    pub removed: Vec<String>,
+    pub unchanged: usize,
+}
+
+/// `key = value` under `[section]` in an INI-like file (`setup.cfg`, or
+/// the flat string keys of `pyproject.toml`), with TOML quotes removed.
+fn section_value(contents: &str, section: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_section = line == format!("[{}]", section);
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        let Some((k, v)) = line.split_once('=') else {
+            continue;
+        };
+        if k.trim() == key {
+            let v = v.trim().trim_matches(|c| c == '"' || c == '\'').trim();
+            return (!v.is_empty()).then(|| v.to_string());
+        }
+    }
+    None
+}
+
+/// `Name` and `Version` from Python core metadata (`PKG-INFO`, `METADATA`),
+/// whose headers end at the first blank line.
+fn core_metadata(contents: &str) -> Option<(String, Option<String>)> {
+    let mut name = None;
+    let mut version = None;
+    for line in contents.lines().take_while(|line| !line.trim().is_empty()) {
+        if let Some(v) = line.strip_prefix("Name:") {
+            name = Some(v.trim().to_string());
+        } else if let Some(v) = line.strip_prefix("Version:") {
+            version = Some(v.trim().to_string());
+        }
+    }
+    name.filter(|n| !n.is_empty()).map(|n| (n, version))
+}
+
+fn npm_package(root: &Path) -> Option<LocalPackage> {
+    let raw = std::fs::read_to_string(root.join("package.json")).ok()?;
+    let doc: serde_json::Value = serde_json::from_str(&raw).ok()?;
+    let field = |key: &str| doc.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    Some(LocalPackage {
+        ecosystem: "npm".to_string(),
+        name: field("name").filter(|n| !n.is_empty())?,
+        version: field("version").filter(|v| !v.is_empty()),
+    })
+}
+
+fn pip_package(root: &Path) -> Option<LocalPackage> {
+    let read = |path: PathBuf| std::fs::read_to_string(path).ok();
+    // Core metadata at the root (an sdist) or in a wheel's `.dist-info`.
+    let mut metadata = vec![root.join("PKG-INFO")];
+    if let Ok(entries) = std::fs::read_dir(root) {
+        let mut dirs: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+        dirs.sort();
+        for dir in dirs {
+            let name = dir.file_name().unwrap_or_default().to_string_lossy();
+            if name.ends_with(".dist-info") {
+                metadata.push(dir.join("METADATA"));
+            } else if name.ends_with(".egg-info") {
+                metadata.push(dir.join("PKG-INFO"));
+            }
+        }
+    }
+    let found = metadata
+        .into_iter()
+        .find_map(|path| core_metadata(&read(path)?))
+        .or_else(|| {
+            let pyproject = read(root.join("pyproject.toml"))?;
+            ["project", "tool.poetry"].iter().find_map(|section| {
+                let name = section_value(&pyproject, section, "name")?;
+                Some((name, section_value(&pyproject, section, "version")))
+            })
+        })
+        .or_else(|| {
+            let setup_cfg = read(root.join("setup.cfg"))?;
+            let name = section_value(&setup_cfg, "metadata", "name")?;
+            Some((name, section_value(&setup_cfg, "metadata", "version")))
+        })?;
+    Some(LocalPackage {
+        ecosystem: "pip".to_string(),
+        name: found.0,
+        // A dynamic or templated version is not a version.
+        version: found.1.filter(|v| !v.contains(['{', ':', ' '])),
+    })
+}
+
+/// Which package `root` holds, judged by its manifest files.
+pub fn identify(root: &Path) -> Option<LocalPackage> {
+    npm_package(root).or_else(|| pip_package(root))
+}
+
+/// The package to compare `root` with: `ecosystem` and `package` (a bare
+/// name, `name==version`, or `name@version`) override what the local
+/// manifests say.
+pub fn resolve(
+    root: &Path,
+    ecosystem: Option<&str>,
+    package: Option<&str>,
+) -> Result<LocalPackage, String> {
+    let detected = identify(root);
+    let ecosystem = match ecosystem.or(detected.as_ref().map(|p| p.ecosystem.as_str())) {
+        Some(e @ ("pip" | "npm")) => e.to_string(),
+        Some(other) => {
+            return Err(format!(
+                "unsupported ecosystem '{}' (use pip or npm)",
+                other
+            ))
+        }
+        None => {
+            return Err(format!(
+                "cannot tell which package {} holds; pass --ecosystem and --package",
+                root.display()
+            ))
+        }
+    };
+    if let Some(spec) = package {
+        let (name, version) = match crate::verify::parse_spec(&ecosystem, spec) {
+            Ok((name, version)) => (name, Some(version)),
+            Err(_) => (spec.trim().to_string(), None),
+        };
+        return Ok(LocalPackage {
+            ecosystem,
+            name,
+            version,
+        });
+    }
+    match detected {
+        Some(found) if found.ecosystem == ecosystem => Ok(found),
+        _ => Err(format!(
+            "no {} package name found in {}; pass --package",
+            ecosystem,
+            root.display()
+        )),
+    }
+}
+
+/// Where the unpacked release's files start: past the wrapper directories
+/// archives add (`pkg-1.0/`, npm's `package/`, the extraction directory).
+pub fn unpacked_root(dir: &Path) -> PathBuf {
+    let mut root = dir.to_path_buf();
+    loop {
+        let next = package_root(&root);
+        if next == root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+/// The part of the release to compare with `local`.  A vendored copy is
+/// often just the import package (`vendor/requests/`), which a wheel holds
+/// as a subdirectory of the same name beside its `.dist-info`.
+pub fn comparable_root(release: &Path, local: &Path) -> PathBuf {
+    let local_name = local
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|name| name.to_string_lossy().to_string()));
+    match local_name.map(|name| release.join(name)) {
+        Some(inner) if inner.is_dir() => inner,
+        _ => release.to_path_buf(),
+    }
+}
+
+/// Compare file contents under `release` and `local`.
+pub fn content_diff(release: &Path, local: &Path) -> ContentDiff {
+    let release = pin_directory(release, None).files;
+    let local = pin_directory(local, None).files;
+    let mut diff = ContentDiff::default();
+    for (path, hash) in &local {
+        match release.get(path) {
+            Some(theirs) if theirs == hash => diff.unchanged += 1,
+            Some(_) => diff.modified.push(path.clone()),
+            None => diff.added.push(path.clone()),
+        }
+    }
+    diff.removed = release
+        .keys()
+        .filter(|path| !local.contains_key(*path))
+        .cloned()
+        .collect();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn packages_are_identified_from_their_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        fs::write(
+            root.join("pyproject.toml"),
+            "[build-system]\nrequires = [\"hatchling\"]\n\n[project]\nname = \"demo-lib\"\ndynamic = [\"version\"]\n",
+        )
+        .unwrap();
+        let found = identify(root).unwrap();
+        assert_eq!(
+            (found.ecosystem.as_str(), found.name.as_str()),
+            ("pip", "demo-lib")
+        );
+        assert_eq!(found.version, None);
+
+        fs::create_dir(root.join("demo_lib-1.2.0.dist-info")).unwrap();
+        fs::write(
+            root.join("demo_lib-1.2.0.dist-info/METADATA"),
+            "Metadata-Version: 2.1\nName: demo-lib\nVersion: 1.2.0\n\nVersion: not a header\n",
+        )
+        .unwrap();
+        assert_eq!(identify(root).unwrap().version.as_deref(), Some("1.2.0"));
+
+        fs::write(
+            root.join("package.json"),
+            r#"{"name": "@acme/widget", "version": "3.0.1"}"#,
+        )
+        .unwrap();
+        let npm = identify(root).unwrap();
+        assert_eq!(npm.spec("3.0.1"), "@acme/widget@3.0.1");
+    }
+
+    #[test]
+    fn overrides_win_and_unknown_trees_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(resolve(dir.path(), None, None)
+            .unwrap_err()
+            .contains("--ecosystem"));
+        assert!(resolve(dir.path(), Some("pip"), None)
+            .unwrap_err()
+            .contains("--package"));
+
+        let pinned = resolve(dir.path(), Some("pip"), Some("requests==2.31.0")).unwrap();
+        assert_eq!(pinned.spec("2.31.0"), "requests==2.31.0");
+        let bare = resolve(dir.path(), Some("npm"), Some("left-pad")).unwrap();
+        assert_eq!((bare.name.as_str(), bare.version), ("left-pad", None));
+    }
+
+    #[test]
+    fn content_diff_lists_changed_files_against_the_release() {
+        let dir = tempfile::tempdir().unwrap();
+        let release = dir.path().join("dl/demo-1.0.tar_extracted/demo-1.0");
+        let local = dir.path().join("vendor/demo");
+        for root in [&release, &local] {
+            fs::create_dir_all(root.join("demo")).unwrap();
+            fs::write(root.join("demo/__init__.py"), "VERSION = '1.0'\n").unwrap();
+            fs::write(root.join("demo/api.py"), "def get(url): ...\n").unwrap();
+        }
+        fs::write(local.join("demo/api.py"), "def get(url): exfil()\n").unwrap();
+        fs::write(local.join("demo/_hook.py"), "import os\n").unwrap();
+        fs::write(release.join("setup.py"), "setup()\n").unwrap();
+
+        assert_eq!(unpacked_root(&dir.path().join("dl")), release);
+        let diff = content_diff(&release, &local);
+        assert_eq!(diff.modified, vec!["demo/api.py"]);
+        assert_eq!(diff.added, vec!["demo/_hook.py"]);
+        assert_eq!(diff.removed, vec!["setup.py"]);
+        assert_eq!(diff.unchanged, 1);
+
+        // A copy of only the import package lines up with its subdirectory.
+        assert_eq!(
+            comparable_root(&release, &local.join("demo")),
+            release.join("demo")
+        );
+    }
+}
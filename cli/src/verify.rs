@@ -0,0 +1,321 @@
+//! Package integrity verification for `sigil verify`.
+//!
+//! Compares a locally downloaded pip/npm archive against the digest the
+//! registry published for that exact release, catching tampered mirrors and
+//! caches. PyPI publishes a SHA-256 per distribution file; npm publishes an
+//! SRI `integrity` string (usually SHA-512) and a legacy SHA-1 `shasum`.
+
+use base64::Engine;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::path::Path;
+
+const PYPI_BASE: &str = "https://pypi.org/pypi";
+const NPM_REGISTRY: &str = "https://registry.npmjs.org";
+
+/// A digest the registry published, in the form needed for comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedDigest {
+    /// `sha256`, `sha384`, `sha512`, or `sha1`.
+    pub algorithm: String,
+    /// Lowercase hex encoding of the digest.
+    pub hex: String,
+}
+
+/// Outcome of comparing a local archive against the registry.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Verification {
+    pub package: String,
+    pub version: String,
+    pub archive: String,
+    pub algorithm: String,
+    pub expected: String,
+    pub actual: String,
+    pub matches: bool,
+}
+
+/// Split a package spec into `(name, version)`: `name==version` for pip,
+/// `name@version` (including `@scope/name@version`) for npm.
+pub fn parse_spec(ecosystem: &str, spec: &str) -> Result<(String, String), String> {
+    let split = match ecosystem {
+        "pip" => spec.split_once("=="),
+        "npm" => spec
+            .rfind('@')
+            .filter(|&i| i > 0)
+            .map(|i| (&spec[..i], &spec[i + 1..])),
+        other => {
+            return Err(format!(
+                "unsupported ecosystem '{}' (use pip or npm)",
+                other
+            ))
+        }
+    };
+    match split {
+        Some((name, version)) if !name.is_empty() && !version.is_empty() => {
+            Ok((name.trim().to_string(), version.trim().to_string()))
+        }
+        _ => Err(format!(
+            "'{}' must pin an exact version ({})",
+            spec,
+            if ecosystem == "pip" {
+                "name==version"
+            } else {
+                "name@version"
+            }
+        )),
+    }
+}
+
+/// Find the published SHA-256 for `filename` in a PyPI release JSON document
+/// (`GET /pypi/{name}/{version}/json`).
+pub fn expected_pypi_digest(raw: &[u8], filename: &str) -> Result<ExpectedDigest, String> {
+    let val: serde_json::Value =
+        serde_json::from_slice(raw).map_err(|e| format!("invalid PyPI response: {}", e))?;
+    let urls = val
+        .get("urls")
+        .and_then(|u| u.as_array())
+        .ok_or("PyPI response has no distribution files")?;
+
+    let entry = urls
+        .iter()
+        .find(|u| u.get("filename").and_then(|f| f.as_str()) == Some(filename))
+        .ok_or_else(|| {
+            let published: Vec<&str> = urls
+                .iter()
+                .filter_map(|u| u.get("filename").and_then(|f| f.as_str()))
+                .collect();
+            format!(
+                "'{}' is not a file PyPI published for this release (published: {})",
+                filename,
+                published.join(", ")
+            )
+        })?;
+
+    let sha256 = entry
+        .get("digests")
+        .and_then(|d| d.get("sha256"))
+        .and_then(|s| s.as_str())
+        .ok_or_else(|| format!("PyPI lists no sha256 digest for '{}'", filename))?;
+    Ok(ExpectedDigest {
+        algorithm: "sha256".to_string(),
+        hex: sha256.to_lowercase(),
+    })
+}
+
+/// Extract the published digest from an npm version document
+/// (`GET /{name}/{version}`). The SRI `dist.integrity` is preferred; the
+/// SHA-1 `dist.shasum` is only used for old releases that lack one.
+pub fn expected_npm_digest(raw: &[u8]) -> Result<ExpectedDigest, String> {
+    let val: serde_json::Value =
+        serde_json::from_slice(raw).map_err(|e| format!("invalid npm response: {}", e))?;
+    let dist = val.get("dist").ok_or("npm response has no dist metadata")?;
+
+    if let Some(integrity) = dist.get("integrity").and_then(|i| i.as_str()) {
+        // An SRI string may list several digests; take the strongest we know.
+        let mut best: Option<ExpectedDigest> = None;
+        for token in integrity.split_whitespace() {
+            let Some((algorithm, b64)) = token.split_once('-') else {
+                continue;
+            };
+            if !matches!(algorithm, "sha256" | "sha384" | "sha512") {
+                continue;
+            }
+            let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(b64) else {
+                continue;
+            };
+            let rank = |a: &str| ["sha256", "sha384", "sha512"].iter().position(|x| *x == a);
+            if best
+                .as_ref()
+                .is_none_or(|b| rank(algorithm) > rank(&b.algorithm))
+            {
+                best = Some(ExpectedDigest {
+                    algorithm: algorithm.to_string(),
+                    hex: hex::encode(bytes),
+                });
+            }
+        }
+        if let Some(best) = best {
+            return Ok(best);
+        }
+    }
+
+    dist.get("shasum")
+        .and_then(|s| s.as_str())
+        .map(|s| ExpectedDigest {
+            algorithm: "sha1".to_string(),
+            hex: s.to_lowercase(),
+        })
+        .ok_or_else(|| "npm lists neither integrity nor shasum for this release".to_string())
+}
+
+/// Hex digest of a file with the given algorithm.
+pub fn digest_file(path: &Path, algorithm: &str) -> Result<String, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let digest = match algorithm {
+        "sha1" => hex::encode(Sha1::digest(&bytes)),
+        "sha256" => hex::encode(Sha256::digest(&bytes)),
+        "sha384" => hex::encode(Sha384::digest(&bytes)),
+        "sha512" => hex::encode(Sha512::digest(&bytes)),
+        other => return Err(format!("unsupported digest algorithm '{}'", other)),
+    };
+    Ok(digest)
+}
+
+async fn fetch_json(url: &str) -> Result<Vec<u8>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .user_agent(format!("sigil-cli/{}", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("http client: {}", e))?;
+    let resp = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("registry request failed: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("registry returned {} for {}", resp.status(), url));
+    }
+    resp.bytes()
+        .await
+        .map(|b| b.to_vec())
+        .map_err(|e| format!("failed to read registry response: {}", e))
+}
+
+/// Fetch the registry's published digest for `spec` and compare it with the
+/// local `archive`.
+pub async fn verify_package(
+    ecosystem: &str,
+    spec: &str,
+    archive: &Path,
+) -> Result<Verification, String> {
+    let (name, version) = parse_spec(ecosystem, spec)?;
+    if !archive.is_file() {
+        return Err(format!("archive not found: {}", archive.display()));
+    }
+
+    let expected = if ecosystem == "pip" {
+        let filename = archive
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let raw = fetch_json(&format!("{}/{}/{}/json", PYPI_BASE, name, version)).await?;
+        expected_pypi_digest(&raw, &filename)?
+    } else {
+        let raw = fetch_json(&format!("{}/{}/{}", NPM_REGISTRY, name, version)).await?;
+        expected_npm_digest(&raw)?
+    };
+
+    let actual = digest_file(archive, &expected.algorithm)?;
+    Ok(Verification {
+        package: name,
+        version,
+        archive: archive.display().to_string(),
+        matches: actual == expected.hex,
+        algorithm: expected.algorithm,
+        expected: expected.hex,
+        actual,
+    })
+}
+
+/// The version a registry currently serves as the latest release, from
+/// PyPI's project document (`info.version`) or npm's `latest` dist-tag
+/// document (`version`).
+pub fn parse_latest_version(ecosystem: &str, raw: &[u8]) -> Result<String, String> {
+    let doc: serde_json::Value =
+        serde_json::from_slice(raw).map_err(|e| format!("invalid registry JSON: {}", e))?;
+    let version = match ecosystem {
+        "pip" => doc.get("info").and_then(|info| info.get("version")),
+        _ => doc.get("version"),
+    };
+    version
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| "registry response has no version".to_string())
+}
+
+/// Ask the registry for the latest published version of `name`.
+pub async fn latest_version(ecosystem: &str, name: &str) -> Result<String, String> {
+    let url = match ecosystem {
+        "pip" => format!("{}/{}/json", PYPI_BASE, name),
+        "npm" => format!("{}/{}/latest", NPM_REGISTRY, name),
+        other => {
+            return Err(format!(
+                "unsupported ecosystem '{}' (use pip or npm)",
+                other
+            ))
+        }
+    };
+    parse_latest_version(ecosystem, &fetch_json(&url).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latest_version_is_read_from_either_registry() {
+        let pypi = br#"{"info":{"name":"demo","version":"2.1.0"},"releases":{}}"#;
+        assert_eq!(parse_latest_version("pip", pypi).unwrap(), "2.1.0");
+        let npm = br#"{"name":"demo","version":"4.17.21"}"#;
+        assert_eq!(parse_latest_version("npm", npm).unwrap(), "4.17.21");
+        assert!(parse_latest_version("npm", b"{}").is_err());
+    }
+
+    #[test]
+    fn parses_pip_and_npm_specs() {
+        assert_eq!(
+            parse_spec("pip", "requests==2.31.0").unwrap(),
+            ("requests".to_string(), "2.31.0".to_string())
+        );
+        assert_eq!(
+            parse_spec("npm", "@scope/pkg@1.2.3").unwrap(),
+            ("@scope/pkg".to_string(), "1.2.3".to_string())
+        );
+        assert!(parse_spec("pip", "requests").is_err());
+        assert!(parse_spec("npm", "@scope/pkg").is_err());
+        assert!(parse_spec("cargo", "serde@1").is_err());
+    }
+
+    #[test]
+    fn pypi_digest_is_matched_by_filename() {
+        let raw = br#"{"urls":[
+            {"filename":"demo-1.0-py3-none-any.whl","digests":{"sha256":"AAAA"}},
+            {"filename":"demo-1.0.tar.gz","digests":{"sha256":"bbbb"}}
+        ]}"#;
+        let d = expected_pypi_digest(raw, "demo-1.0.tar.gz").unwrap();
+        assert_eq!(d.algorithm, "sha256");
+        assert_eq!(d.hex, "bbbb");
+        let err = expected_pypi_digest(raw, "demo-9.9.tar.gz").unwrap_err();
+        assert!(err.contains("demo-1.0.tar.gz"), "{err}");
+    }
+
+    #[test]
+    fn npm_integrity_preferred_over_shasum() {
+        let tmp = tempfile::tempdir().unwrap();
+        let tarball = tmp.path().join("pkg-1.0.0.tgz");
+        std::fs::write(&tarball, b"tarball bytes").unwrap();
+        let sha512 = Sha512::digest(b"tarball bytes");
+        let sri = format!(
+            "sha512-{}",
+            base64::engine::general_purpose::STANDARD.encode(sha512)
+        );
+        let raw = serde_json::json!({
+            "dist": {"integrity": sri, "shasum": "deadbeef"}
+        })
+        .to_string();
+
+        let d = expected_npm_digest(raw.as_bytes()).unwrap();
+        assert_eq!(d.algorithm, "sha512");
+        assert_eq!(digest_file(&tarball, &d.algorithm).unwrap(), d.hex);
+    }
+
+    #[test]
+    fn npm_falls_back_to_shasum() {
+        let raw = br#"{"dist":{"shasum":"ABCDEF0123"}}"#;
+        let d = expected_npm_digest(raw).unwrap();
+        assert_eq!(d.algorithm, "sha1");
+        assert_eq!(d.hex, "abcdef0123");
+    }
+}
@@ -0,0 +1,75 @@
+//! `scan --baseline-auto` diffs each scan of a path against the previous
+//! one kept in the cache, so local iteration only shows what changed.
+
+mod common;
+
+use std::path::Path;
+
+use common::run_sigil;
+
+fn scan_diff(home: &Path, target: &Path) -> (Option<i32>, serde_json::Value, String) {
+    let out = run_sigil(
+        home,
+        &[
+            "scan",
+            target.to_str().unwrap(),
+            "--baseline-auto",
+            "--format",
+            "json",
+        ],
+    );
+    let diff = serde_json::from_slice(&out.stdout)
+        .unwrap_or_else(|err| panic!("{err}: {}", String::from_utf8_lossy(&out.stdout)));
+    (
+        out.status.code(),
+        diff,
+        String::from_utf8_lossy(&out.stderr).into_owned(),
+    )
+}
+
+fn rules(findings: &serde_json::Value) -> Vec<&str> {
+    findings
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["rule"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn second_scan_reports_only_the_added_finding() {
+    let home = tempfile::tempdir().unwrap();
+    let target = tempfile::tempdir().unwrap();
+    std::fs::write(
+        target.path().join("util.py"),
+        "def add(a, b):\n    return a + b\n",
+    )
+    .unwrap();
+
+    // First run: nothing to compare, so whatever is there is the baseline.
+    let (code, diff, stderr) = scan_diff(home.path(), target.path());
+    assert_eq!(code, Some(0), "{stderr}");
+    assert!(stderr.contains("this scan is the baseline"), "{stderr}");
+    assert!(rules(&diff["new_findings"]).is_empty(), "{diff}");
+    let unchanged = rules(&diff["unchanged_findings"]);
+
+    std::fs::write(target.path().join("run.py"), "eval(input())\n").unwrap();
+    let (code, diff, stderr) = scan_diff(home.path(), target.path());
+    assert_eq!(code, Some(1), "{stderr}");
+    assert!(!stderr.contains("this scan is the baseline"), "{stderr}");
+    assert_eq!(rules(&diff["new_findings"]), vec!["CODE-001"], "{diff}");
+    assert_eq!(rules(&diff["unchanged_findings"]), unchanged);
+
+    // That scan became the new baseline.
+    let (code, diff, stderr) = scan_diff(home.path(), target.path());
+    assert_eq!(code, Some(0), "{stderr}");
+    assert!(rules(&diff["new_findings"]).is_empty(), "{diff}");
+
+    std::fs::remove_file(target.path().join("run.py")).unwrap();
+    let (_, diff, _) = scan_diff(home.path(), target.path());
+    assert_eq!(
+        rules(&diff["resolved_findings"]),
+        vec!["CODE-001"],
+        "{diff}"
+    );
+}
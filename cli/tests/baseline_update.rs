@@ -0,0 +1,58 @@
+//! `scan --baseline FILE --update-baseline` closes the ratchet loop: the
+//! scan reports against the old baseline, then accepts every current finding
+//! by rewriting FILE in the format `scan --baseline` and `diff --baseline`
+//! both read.
+
+mod common;
+
+use common::run_sigil;
+
+#[test]
+fn update_baseline_accepts_current_findings() {
+    let home = tempfile::tempdir().unwrap();
+    let target = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tests/fixtures/reverse_shell"
+    );
+    let baseline = home.path().join("baseline.json");
+    std::fs::write(&baseline, "[]").unwrap();
+    let file = baseline.to_str().unwrap();
+
+    // Not interactive and no --yes: reported, but the file is left alone.
+    let out = run_sigil(
+        home.path(),
+        &["scan", target, "--baseline", file, "--update-baseline"],
+    );
+    assert_eq!(out.status.code(), Some(1), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--yes"));
+    assert_eq!(std::fs::read_to_string(&baseline).unwrap(), "[]");
+
+    let out = run_sigil(
+        home.path(),
+        &[
+            "scan",
+            target,
+            "--baseline",
+            file,
+            "--update-baseline",
+            "--yes",
+        ],
+    );
+    assert_eq!(out.status.code(), Some(1), "{out:?}");
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&baseline).unwrap()).unwrap();
+    assert!(!saved["findings"].as_array().unwrap().is_empty());
+
+    // Everything is now known: the ratchet passes and diff sees nothing new.
+    let out = run_sigil(home.path(), &["scan", target, "--baseline", file]);
+    assert_eq!(out.status.code(), Some(0), "{out:?}");
+    let out = run_sigil(home.path(), &["diff", "--baseline", file, target]);
+    assert_eq!(out.status.code(), Some(0), "{out:?}");
+}
+
+#[test]
+fn update_baseline_requires_a_baseline() {
+    let home = tempfile::tempdir().unwrap();
+    let out = run_sigil(home.path(), &["scan", ".", "--update-baseline"]);
+    assert_eq!(out.status.code(), Some(2), "{out:?}");
+}
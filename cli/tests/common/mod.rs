@@ -0,0 +1,30 @@
+//! Running the built `sigil` binary from integration tests, isolated from
+//! the state and environment of whoever runs them.
+
+// Each test binary compiles its own copy and uses only some of it.
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// `sigil` with `HOME` at `home`, `SIGIL_HOME` at `home/.sigil`, stdin
+/// closed, and every other `SIGIL_*` variable removed, so a developer's
+/// token, endpoint, or severity setting never reaches a test.  Tests that
+/// need more set it on the returned command.
+pub fn sigil(home: &Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_sigil"));
+    for (key, _) in std::env::vars_os() {
+        if key.to_string_lossy().starts_with("SIGIL_") {
+            cmd.env_remove(key);
+        }
+    }
+    cmd.env("HOME", home)
+        .env("SIGIL_HOME", home.join(".sigil"))
+        .stdin(Stdio::null());
+    cmd
+}
+
+/// Run `sigil <args>` under [`sigil`]'s isolation.
+pub fn run_sigil(home: &Path, args: &[&str]) -> Output {
+    sigil(home).args(args).output().expect("run sigil")
+}
@@ -3,9 +3,10 @@
 //! The CLI must post the finding to the Sigil API (no client-side LLM call) and
 //! render the adjudication verdict; 402 maps to a clear upgrade message.
 
+mod common;
+
 use std::io::{Read, Write};
 use std::net::TcpListener;
-use std::process::Command;
 
 /// A canned HTTP response for one (method, path-prefix) route.
 struct Route {
@@ -76,23 +77,21 @@ fn spawn_mock_api(routes: Vec<Route>, max_conns: usize) -> String {
     format!("http://{}", addr)
 }
 
-/// A scan-JSON file shaped like real `sigil scan -f json` output:
-/// banner line, summary object, findings array, verdict object.
+/// A scan-JSON file shaped like real `sigil scan -f json` output: one
+/// report object with the findings inside it.
 fn write_scan_json(dir: &std::path::Path) -> std::path::PathBuf {
-    let content = r#"sigil: scanning /tmp/pkg...
-{
+    let content = r#"{
   "duration_ms": 10,
   "files_scanned": 3,
   "findings_count": 2,
   "score": 12,
   "suppressed_count": 0,
-  "verdict": "HIGH RISK"
+  "verdict": "HIGH RISK",
+  "findings": [
+    {"phase":"NetworkExfil","rule":"NET-006","severity":"High","file":"package/index.js","line":7,"snippet":"fetch('https://example.com/hook')","weight":3},
+    {"phase":"CodePatterns","rule":"code-eval","severity":"High","file":"package/lib.js","line":42,"snippet":"eval(payload)","weight":5}
+  ]
 }
-[
-  {"phase":"NetworkExfil","rule":"NET-006","severity":"High","file":"package/index.js","line":7,"snippet":"fetch('https://example.com/hook')","weight":3},
-  {"phase":"CodePatterns","rule":"code-eval","severity":"High","file":"package/lib.js","line":42,"snippet":"eval(payload)","weight":5}
-]
-{"verdict":"HIGH RISK"}
 "#;
     let path = dir.join("scan.json");
     std::fs::write(&path, content).unwrap();
@@ -113,7 +112,7 @@ fn run_explain(
     endpoint: &str,
     finding: usize,
 ) -> std::process::Output {
-    Command::new(env!("CARGO_BIN_EXE_sigil"))
+    common::sigil(home)
         .args([
             "explain",
             scan_json.to_str().unwrap(),
@@ -122,7 +121,6 @@ fn run_explain(
             "--endpoint",
             endpoint,
         ])
-        .env("HOME", home)
         .output()
         .expect("run sigil explain")
 }
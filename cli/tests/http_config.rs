@@ -0,0 +1,48 @@
+//! An unusable proxy or CA bundle is a configuration error (exit 2), never
+//! a silent fallback to connecting without it.
+
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+
+/// Run `sigil <args>` with `SIGIL_CA_BUNDLE` naming a file that does not
+/// exist.
+fn run_with_missing_bundle(home: &Path, args: &[&str]) -> Output {
+    common::sigil(home)
+        .args(args)
+        .env("SIGIL_CA_BUNDLE", home.join("missing.pem"))
+        .env("SIGIL_ENDPOINT", "http://127.0.0.1:9")
+        .output()
+        .expect("run sigil")
+}
+
+#[test]
+fn missing_ca_bundle_fails_dependency_lookup() {
+    let home = tempfile::tempdir().unwrap();
+    let project = tempfile::tempdir().unwrap();
+    std::fs::write(
+        project.path().join("requirements.txt"),
+        "requests==2.31.0\n",
+    )
+    .unwrap();
+    let out = run_with_missing_bundle(
+        home.path(),
+        &["deps", project.path().to_str().unwrap(), "--lookup"],
+    );
+    assert_eq!(out.status.code(), Some(2), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stderr).contains("SIGIL_CA_BUNDLE"));
+}
+
+#[test]
+fn missing_ca_bundle_fails_enriched_scan_before_scanning() {
+    let home = tempfile::tempdir().unwrap();
+    let target = tempfile::tempdir().unwrap();
+    std::fs::write(target.path().join("hello.py"), "print('hello')\n").unwrap();
+    let out = run_with_missing_bundle(
+        home.path(),
+        &["scan", target.path().to_str().unwrap(), "--enrich"],
+    );
+    assert_eq!(out.status.code(), Some(2), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stdout).is_empty());
+}
@@ -0,0 +1,201 @@
+//! Machine-readable output must be machine-readable: with `--format json`,
+//! stdout carries one JSON document and every status banner goes to stderr,
+//! so `sigil scan x -f json > out.json` is safe to parse.
+
+mod common;
+
+use common::{run_sigil, sigil};
+
+/// Parse `stdout` as a single JSON document, failing on anything else.
+fn parse_json(stdout: &[u8]) -> serde_json::Value {
+    serde_json::from_slice(stdout).unwrap_or_else(|e| {
+        panic!(
+            "stdout is not one JSON document ({e}):\n{}",
+            String::from_utf8_lossy(stdout)
+        )
+    })
+}
+
+#[test]
+fn scan_json_stdout_parses_cleanly() {
+    let home = tempfile::tempdir().unwrap();
+    let target = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tests/fixtures/reverse_shell"
+    );
+
+    let out = run_sigil(
+        home.path(),
+        &["scan", target, "--format", "json", "--no-cache"],
+    );
+
+    let report = parse_json(&out.stdout);
+    assert!(
+        report["findings"].as_array().is_some_and(|a| !a.is_empty()),
+        "expected a non-empty findings array in {report}"
+    );
+    assert!(report["verdict"].is_string(), "{report}");
+    assert!(report["top_files"].is_array(), "{report}");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("scanning"),
+        "banner belongs on stderr: {stderr}"
+    );
+}
+
+#[test]
+fn empty_list_json_is_an_empty_envelope() {
+    let home = tempfile::tempdir().unwrap();
+
+    let out = run_sigil(home.path(), &["list", "--format", "json"]);
+
+    assert!(out.status.success());
+    assert_eq!(
+        parse_json(&out.stdout),
+        serde_json::json!({
+            "schema_version": 1,
+            "entries": [],
+            "total": 0,
+            "offset": 0,
+            "limit": null,
+        })
+    );
+}
+
+#[test]
+fn piped_output_carries_no_ansi_escapes() {
+    let home = tempfile::tempdir().unwrap();
+    let target = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tests/fixtures/reverse_shell"
+    );
+
+    for format in ["text", "json"] {
+        let out = run_sigil(
+            home.path(),
+            &["scan", target, "--format", format, "--no-cache"],
+        );
+        assert!(
+            !out.stdout.contains(&0x1b) && !out.stderr.contains(&0x1b),
+            "ANSI escape in piped {format} output:\n{}",
+            String::from_utf8_lossy(&out.stdout)
+        );
+    }
+}
+
+#[test]
+fn json_log_format_makes_every_stderr_line_a_json_object() {
+    let home = tempfile::tempdir().unwrap();
+    let target = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tests/fixtures/reverse_shell"
+    );
+
+    let out = sigil(home.path())
+        .args([
+            "--log-format",
+            "json",
+            "--verbose",
+            "scan",
+            target,
+            "--no-cache",
+        ])
+        .env("CLICOLOR_FORCE", "1")
+        .env_remove("NO_COLOR")
+        .output()
+        .expect("run sigil");
+
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let lines: Vec<serde_json::Value> = stderr
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|e| panic!("not JSON ({e}): {line}")))
+        .collect();
+    assert!(lines
+        .iter()
+        .any(|l| l["level"] == "info" && l["message"] == format!("scanning {target}...")));
+    assert!(lines.iter().any(|l| l["level"] == "debug"));
+    assert!(lines
+        .iter()
+        .all(|l| l["timestamp"].is_string() && !l["message"].as_str().unwrap().contains('\x1b')));
+}
+
+#[test]
+fn detailed_list_json_keeps_scores_numeric_with_color_forced() {
+    let home = tempfile::tempdir().unwrap();
+    let quarantine = home.path().join("quarantine");
+    std::fs::create_dir_all(&quarantine).unwrap();
+    std::fs::write(
+        quarantine.join("index.json"),
+        r#"[{"id":"abc12345","source":"requests","source_type":"pip","path":"/tmp/q/abc12345","status":"Pending","created_at":"2026-01-01T00:00:00Z","updated_at":"2026-01-01T00:00:00Z","reason":null,"scan_score":42}]"#,
+    )
+    .unwrap();
+
+    let out = sigil(home.path())
+        .args(["list", "--detailed", "--format", "json"])
+        .env("SIGIL_QUARANTINE_DIR", &quarantine)
+        .env("CLICOLOR_FORCE", "1")
+        .env_remove("NO_COLOR")
+        .output()
+        .expect("run sigil");
+
+    assert!(out.status.success());
+    assert!(
+        !out.stdout.contains(&0x1b),
+        "ANSI escape in list JSON:\n{}",
+        String::from_utf8_lossy(&out.stdout)
+    );
+    let list = parse_json(&out.stdout);
+    assert_eq!(list["entries"][0]["scan_score"], serde_json::json!(42));
+}
+
+#[test]
+fn metrics_line_goes_to_stderr_or_file_without_touching_stdout() {
+    let home = tempfile::tempdir().unwrap();
+    let target = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tests/fixtures/reverse_shell"
+    );
+
+    let out = run_sigil(
+        home.path(),
+        &[
+            "scan",
+            "--metrics",
+            target,
+            "--format",
+            "json",
+            "--no-cache",
+        ],
+    );
+    parse_json(&out.stdout);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let line = stderr
+        .lines()
+        .find(|l| l.starts_with("score="))
+        .unwrap_or_else(|| panic!("no metrics line on stderr: {stderr}"));
+    let keys: Vec<&str> = line
+        .split(' ')
+        .map(|kv| kv.split_once('=').expect("key=value").0)
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            "score",
+            "verdict",
+            "critical",
+            "high",
+            "medium",
+            "low",
+            "files",
+            "duration_ms",
+            "timed_out"
+        ]
+    );
+
+    let file = home.path().join("metrics.txt");
+    let flag = format!("--metrics={}", file.display());
+    let out = run_sigil(home.path(), &["scan", target, &flag, "--no-cache"]);
+    let written = std::fs::read_to_string(&file).expect("metrics file written");
+    assert!(written.starts_with("score=") && written.ends_with('\n'));
+    assert!(!String::from_utf8_lossy(&out.stderr).contains("score="));
+}
@@ -0,0 +1,34 @@
+//! `sigil list --output FILE` writes FILE even when nothing is quarantined,
+//! so a script reading it never finds it missing.
+
+mod common;
+
+#[test]
+fn empty_list_still_creates_the_output_file() {
+    let home = tempfile::tempdir().unwrap();
+    let file = home.path().join("list.txt");
+    let out = common::run_sigil(home.path(), &["list", "--output", file.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(0), "{out:?}");
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "");
+}
+
+#[test]
+fn empty_json_list_writes_an_empty_envelope() {
+    let home = tempfile::tempdir().unwrap();
+    let file = home.path().join("list.json");
+    let out = common::run_sigil(
+        home.path(),
+        &[
+            "--format",
+            "json",
+            "list",
+            "--output",
+            file.to_str().unwrap(),
+        ],
+    );
+    assert_eq!(out.status.code(), Some(0), "{out:?}");
+    let list: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+    assert_eq!(list["entries"], serde_json::json!([]));
+    assert_eq!(list["total"], 0);
+}
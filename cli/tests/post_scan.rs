@@ -0,0 +1,29 @@
+//! A `hooks.post_scan_on` level sigil cannot read drops the hook with a
+//! warning naming the value, rather than silently.
+
+mod common;
+
+#[test]
+fn unknown_post_scan_level_is_reported() {
+    let home = tempfile::tempdir().unwrap();
+    let target = tempfile::tempdir().unwrap();
+    std::fs::write(target.path().join("hello.py"), "print('hello')\n").unwrap();
+    // Hand-edited: `sigil config` would have refused the level.
+    let sigil_home = home.path().join(".sigil");
+    std::fs::create_dir_all(&sigil_home).unwrap();
+    std::fs::write(
+        sigil_home.join("config.json"),
+        r#"{"hooks.post_scan": "true", "hooks.post_scan_on": "severe"}"#,
+    )
+    .unwrap();
+
+    let out = common::sigil(home.path())
+        .args(["scan", "--phases", "code-patterns"])
+        .arg(target.path())
+        .output()
+        .expect("run sigil");
+    assert_eq!(out.status.code(), Some(0), "{out:?}");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("hooks.post_scan_on"), "{stderr}");
+    assert!(stderr.contains("'severe'"), "{stderr}");
+}
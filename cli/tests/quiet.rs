@@ -0,0 +1,26 @@
+//! `--quiet` leaves only the result: no `sigil:` progress lines on stderr
+//! and no summary or verdict banner on stdout.
+
+mod common;
+
+#[test]
+fn quiet_clean_scan_prints_no_banners() {
+    let home = tempfile::tempdir().unwrap();
+    let target = tempfile::tempdir().unwrap();
+    std::fs::write(target.path().join("hello.py"), "print('hello')\n").unwrap();
+
+    let out = common::sigil(home.path())
+        .args(["--quiet", "scan", "--phases", "code-patterns"])
+        .arg(target.path())
+        .output()
+        .expect("run sigil");
+    assert_eq!(out.status.code(), Some(0), "{out:?}");
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(!stderr.contains("sigil:"), "{stderr}");
+    for banner in ["sigil", "Scan complete", "files scanned", "====", "Note:"] {
+        assert!(!stdout.contains(banner), "{banner:?} in {stdout}");
+    }
+    assert_eq!(stdout.lines().last(), Some("LOW RISK"), "{stdout}");
+}
@@ -0,0 +1,29 @@
+//! `sigil reject` deletes quarantined files for good, so a run that cannot
+//! ask first (stdin is not a terminal) refuses unless given `--yes`.
+
+mod common;
+
+use common::run_sigil;
+
+#[test]
+fn reject_without_a_terminal_requires_yes() {
+    let home = tempfile::tempdir().unwrap();
+    let quarantine = home.path().join(".sigil/quarantine");
+    let item = quarantine.join("abc12345");
+    std::fs::create_dir_all(&item).unwrap();
+    std::fs::write(item.join("setup.py"), "print('hi')\n").unwrap();
+    let index = format!(
+        r#"[{{"id":"abc12345","source":"requests","source_type":"pip","path":{},"status":"Pending","created_at":"2026-01-01T00:00:00Z","updated_at":"2026-01-01T00:00:00Z","reason":null,"scan_score":null}}]"#,
+        serde_json::to_string(&item).unwrap()
+    );
+    std::fs::write(quarantine.join("index.json"), index).unwrap();
+
+    let out = run_sigil(home.path(), &["reject", "abc1"]);
+    assert_eq!(out.status.code(), Some(1), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--yes"));
+    assert!(item.join("setup.py").exists());
+
+    let out = run_sigil(home.path(), &["reject", "abc1", "--yes"]);
+    assert!(out.status.success(), "{out:?}");
+    assert!(!item.exists());
+}
@@ -0,0 +1,79 @@
+//! A misspelled `--phases` or `--severity` must fail the scan (exit 2), not
+//! run nothing and report a clean result.
+//! `--min-confidence` keeps only findings from rules at least that confident.
+
+mod common;
+
+use common::run_sigil;
+
+const TARGET: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../tests/fixtures/reverse_shell"
+);
+
+#[test]
+fn misspelled_phase_is_an_error() {
+    let home = tempfile::tempdir().unwrap();
+    let out = run_sigil(home.path(), &["scan", TARGET, "--phases", "codepaterns"]);
+    assert_eq!(out.status.code(), Some(2), "{out:?}");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains("unknown phase 'codepaterns'"), "{stderr}");
+    assert!(stderr.contains("code_patterns"), "{stderr}");
+}
+
+#[test]
+fn misspelled_severity_is_an_error() {
+    let home = tempfile::tempdir().unwrap();
+    let out = run_sigil(home.path(), &["scan", TARGET, "--severity", "hgih"]);
+    assert_eq!(out.status.code(), Some(2), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stderr).contains("unknown severity 'hgih'"));
+
+    let out = run_sigil(home.path(), &["scan", TARGET, "--fail-on", "crit"]);
+    assert_eq!(out.status.code(), Some(2), "{out:?}");
+}
+
+#[test]
+fn valid_filters_still_scan() {
+    let home = tempfile::tempdir().unwrap();
+    let out = run_sigil(
+        home.path(),
+        &[
+            "scan",
+            TARGET,
+            "--phases",
+            "network-exfil",
+            "--severity",
+            "HIGH",
+        ],
+    );
+    assert_eq!(out.status.code(), Some(1), "{out:?}");
+}
+
+#[test]
+fn min_confidence_keeps_only_confident_findings() {
+    let home = tempfile::tempdir().unwrap();
+    let out = run_sigil(
+        home.path(),
+        &[
+            "scan",
+            TARGET,
+            "--format",
+            "json",
+            "--no-cache",
+            "--min-confidence",
+            "90",
+        ],
+    );
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).expect("JSON report");
+    let findings = report["findings"].as_array().expect("findings array");
+    assert!(!findings.is_empty(), "{out:?}");
+    assert!(
+        findings
+            .iter()
+            .all(|f| f["confidence"].as_u64() >= Some(90)),
+        "{findings:?}"
+    );
+
+    let out = run_sigil(home.path(), &["scan", TARGET, "--min-confidence", "101"]);
+    assert_eq!(out.status.code(), Some(2), "{out:?}");
+}
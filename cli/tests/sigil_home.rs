@@ -0,0 +1,71 @@
+//! `SIGIL_HOME` moves the whole `.sigil` tree: config, caches, and the
+//! quarantine are all read and written under it, and nothing lands in
+//! `~/.sigil`.
+
+mod common;
+
+use std::path::Path;
+use std::process::Output;
+
+use common::sigil;
+
+fn run_sigil(home: &Path, sigil_home: &Path, args: &[&str]) -> Output {
+    sigil(home)
+        .args(args)
+        .env("SIGIL_HOME", sigil_home)
+        .output()
+        .expect("run sigil")
+}
+
+#[test]
+fn state_is_kept_under_sigil_home() {
+    let home = tempfile::tempdir().unwrap();
+    let state = tempfile::tempdir().unwrap();
+    let target = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tests/fixtures/reverse_shell"
+    );
+
+    let out = run_sigil(home.path(), state.path(), &["config", "severity", "high"]);
+    assert!(out.status.success(), "{out:?}");
+    let config = std::fs::read_to_string(state.path().join("config.json")).unwrap();
+    assert!(config.contains("\"high\""), "{config}");
+
+    run_sigil(home.path(), state.path(), &["scan", target]);
+    assert!(state.path().join("cache").is_dir());
+
+    let quarantine = state.path().join("quarantine");
+    std::fs::create_dir_all(&quarantine).unwrap();
+    std::fs::write(
+        quarantine.join("index.json"),
+        r#"[{"id":"abc12345","source":"requests","source_type":"pip","path":"/tmp/q/abc12345","status":"Pending","created_at":"2026-01-01T00:00:00Z","updated_at":"2026-01-01T00:00:00Z","reason":null,"scan_score":null}]"#,
+    )
+    .unwrap();
+    let out = run_sigil(home.path(), state.path(), &["list", "--format", "json"]);
+    assert!(String::from_utf8_lossy(&out.stdout).contains("abc12345"));
+
+    assert!(!home.path().join(".sigil").exists());
+}
+
+#[test]
+fn home_dir_config_key_relocates_state() {
+    let home = tempfile::tempdir().unwrap();
+    let state = tempfile::tempdir().unwrap();
+    let state_dir = state.path().display().to_string();
+
+    let out = sigil(home.path())
+        .args(["config", "home_dir", &state_dir])
+        .env_remove("SIGIL_HOME")
+        .output()
+        .expect("run sigil");
+    assert!(out.status.success(), "{out:?}");
+
+    let out = sigil(home.path())
+        .args(["config", "severity", "critical"])
+        .env_remove("SIGIL_HOME")
+        .output()
+        .expect("run sigil");
+    assert!(out.status.success(), "{out:?}");
+    let relocated = std::fs::read_to_string(state.path().join("config.json")).unwrap();
+    assert!(relocated.contains("critical"), "{relocated}");
+}
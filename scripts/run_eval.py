@@ -172,17 +172,18 @@ def scan_dir(binary: str, target: Path, env: dict | None = None) -> SampleResult
     except subprocess.TimeoutExpired:
         return SampleResult(str(target), None, 0, error="scan timeout")
     out = proc.stdout
-    start = out.find("[")
+    start = out.find("{")
     if start == -1:
-        # No findings array emitted (clean) — exit 2 would be a real error.
+        # No report emitted — exit 2 is a real error.
         if proc.returncode == 2:
             return SampleResult(str(target), None, 0, error="scan error (exit 2)")
         return SampleResult(str(target), None, 0)
     try:
         decoder = json.JSONDecoder()
-        findings, _ = decoder.raw_decode(out[start:])
+        report, _ = decoder.raw_decode(out[start:])
     except json.JSONDecodeError as e:
         return SampleResult(str(target), None, 0, error=f"json parse: {e}")
+    findings = report.get("findings", [])
     if not findings:
         return SampleResult(str(target), None, 0)
     max_sev = max(findings, key=lambda f: SEVERITY_ORDER.get(f.get("severity", "Low"), 0))
@@ -65,11 +65,10 @@ require_sigil() {
 }
 
 # ── JSON merging ──────────────────────────────────────────────────────────
-# The Rust CLI outputs 3 separate JSON objects when using --format json:
-#   1. Summary: {files_scanned, findings_count, score, verdict, duration_ms}
-#   2. Findings array: [{phase, rule, severity, file, line, snippet, weight}]
-#   3. Verdict: {verdict: "..."}
-# This function merges them into a single envelope and adds a target field.
+# The Rust CLI outputs one JSON report when using --format json:
+#   {files_scanned, findings_count, score, verdict, duration_ms, ...,
+#    findings: [{phase, rule, severity, file, line, snippet, weight}]}
+# This function adds a target field and a per-phase breakdown.
 
 merge_scan_json() {
   local raw_output="$1"
@@ -83,10 +82,10 @@ merge_scan_json() {
   if command -v jq >/dev/null 2>&1; then
     echo "$json_only" | jq -s --arg target "$target" '
       (.[0] // {}) + {
-        findings: (.[1] // []),
+        findings: (.[0].findings // []),
         target: $target,
         phases: (
-          (.[1] // []) | group_by(.phase) | map({
+          (.[0].findings // []) | group_by(.phase) | map({
             key: .[0].phase,
             value: {
               findings: length,
@@ -121,8 +120,8 @@ while idx < len(data):
     except json.JSONDecodeError:
         idx += 1
 
-result = parts[0] if parts else {}
-findings = parts[1] if len(parts) > 1 and isinstance(parts[1], list) else []
+result = parts[0] if parts and isinstance(parts[0], dict) else {}
+findings = result.get('findings', [])
 result['findings'] = findings
 result['target'] = '$target'
 
